@@ -0,0 +1,37 @@
+//! Keyframe evaluation on dense animation tracks.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use essentia_video_editor_plugin::bench::{self, TimePosition};
+
+/// Lookups per iteration.
+const LOOKUPS: u64 = 1000;
+
+fn evaluate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("animation_track");
+    group.throughput(Throughput::Elements(LOOKUPS));
+    for keyframes in [100, 1_000, 10_000] {
+        let track = bench::dense_track(keyframes);
+        let end = keyframes as u64 * 40;
+        // Scattered like scrubbing, and in order like playback
+        let scattered: Vec<_> =
+            (0..LOOKUPS).map(|i| TimePosition::from_ms(i * 7919 % end)).collect();
+        let playback: Vec<_> =
+            (0..LOOKUPS).map(|i| TimePosition::from_ms(i * end / LOOKUPS)).collect();
+
+        for (name, times) in [("scattered", &scattered), ("playback", &playback)] {
+            group.bench_with_input(BenchmarkId::new(name, keyframes), &track, |b, track| {
+                b.iter(|| {
+                    for &time in times {
+                        black_box(track.evaluate(black_box(time)));
+                    }
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, evaluate);
+criterion_main!(benches);
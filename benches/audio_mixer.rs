@@ -0,0 +1,47 @@
+//! Mixer DSP over playback-sized blocks.
+
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+use essentia_video_editor_plugin::bench;
+
+const SAMPLE_RATE: u32 = 48_000;
+
+/// Frames per block, the mixer's default playback block size.
+const BLOCK: usize = 1024;
+
+fn process_block(c: &mut Criterion) {
+    let block = bench::stereo_block(SAMPLE_RATE, BLOCK);
+    let clip = bench::pitched_clip(3.0);
+    let mut mixer = bench::denoising_mixer(SAMPLE_RATE, BLOCK);
+
+    let mut group = c.benchmark_group("audio_mixer");
+    group.throughput(Throughput::Elements(BLOCK as u64));
+    group.bench_function("clip_fades", |b| {
+        b.iter_batched_ref(
+            || block.clone(),
+            |samples| mixer.apply_clip_fades(&clip, 0.0, 2, samples),
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("pitch_shift", |b| {
+        b.iter_batched_ref(
+            || block.clone(),
+            |samples| mixer.process_clip(&clip, 2, samples),
+            BatchSize::SmallInput,
+        );
+    });
+    group.bench_function("spectral_denoise", |b| {
+        b.iter_batched_ref(
+            || block.clone(),
+            |samples| mixer.process_track_inserts(1, 2, samples),
+            BatchSize::SmallInput,
+        );
+    });
+    let master = mixer.master().id();
+    group.bench_function("render_outputs", |b| {
+        b.iter(|| mixer.render_outputs(&[(master, block.as_slice())], BLOCK));
+    });
+    group.finish();
+}
+
+criterion_group!(benches, process_block);
+criterion_main!(benches);
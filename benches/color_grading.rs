@@ -0,0 +1,54 @@
+//! Color grading over frame-sized buffers and 3D LUT lookups.
+
+use std::hint::black_box;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use essentia_video_editor_plugin::bench::{self, LutInterpolation};
+
+/// 1080p frame.
+const FRAME: (u32, u32) = (1920, 1080);
+
+fn grade_frame(c: &mut Criterion) {
+    let (width, height) = FRAME;
+    let node = bench::full_grade();
+    let frame = bench::gradient_frame(width, height);
+    let pixels = bench::gradient_rgba8(width, height);
+
+    let mut group = c.benchmark_group("color_grading");
+    group.throughput(Throughput::Elements(u64::from(width * height)));
+    group.sample_size(10);
+    group.bench_function("apply_1080p", |b| {
+        b.iter(|| frame.iter().map(|c| node.apply(black_box(c))).collect::<Vec<_>>());
+    });
+    group.bench_function("apply_slice_1080p", |b| {
+        b.iter_batched_ref(|| frame.clone(), |f| node.apply_slice(f), BatchSize::LargeInput);
+    });
+    group.bench_function("apply_rgba8_1080p", |b| {
+        b.iter_batched_ref(|| pixels.clone(), |p| node.apply_rgba8(p), BatchSize::LargeInput);
+    });
+    group.finish();
+}
+
+fn lut_lookup(c: &mut Criterion) {
+    let frame = bench::gradient_frame(256, 256);
+    let interpolations = [
+        ("nearest", LutInterpolation::Nearest),
+        ("trilinear", LutInterpolation::Trilinear),
+        ("tetrahedral", LutInterpolation::Tetrahedral),
+    ];
+
+    let mut group = c.benchmark_group("lut3d");
+    group.throughput(Throughput::Elements(frame.len() as u64));
+    for (name, interpolation) in interpolations {
+        for size in [17, 33, 65] {
+            let lut = bench::graded_lut(size, interpolation);
+            group.bench_with_input(BenchmarkId::new(name, size), &lut, |b, lut| {
+                b.iter(|| frame.iter().map(|c| lut.apply(black_box(c))).collect::<Vec<_>>());
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, grade_frame, lut_lookup);
+criterion_main!(benches);
@@ -0,0 +1,150 @@
+//! Get and remove by ID on the export queue and the marker, transition
+//! and animation managers.
+
+use std::hint::black_box;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use essentia_video_editor_plugin::bench;
+
+/// Items held by each manager.
+const SIZES: [usize; 3] = [1_000, 10_000, 50_000];
+
+/// Lookups per iteration.
+const LOOKUPS: usize = 1000;
+
+/// Removals per iteration.
+const REMOVALS: usize = 100;
+
+/// `count` positions spread over `0..len`.
+fn scattered(count: usize, len: usize) -> Vec<usize> {
+    (0..count).map(|i| i * 7919 % len).collect()
+}
+
+/// IDs at `picks` in `ids`.
+fn picked<K: Copy>(picks: &[usize], ids: impl Iterator<Item = K>) -> Vec<K> {
+    let ids: Vec<_> = ids.collect();
+    picks.iter().map(|&i| ids[i]).collect()
+}
+
+fn get(c: &mut Criterion) {
+    let mut group = c.benchmark_group("manager_get");
+    group.throughput(Throughput::Elements(LOOKUPS as u64));
+    for size in SIZES {
+        let picks = scattered(LOOKUPS, size);
+
+        let queue = bench::export_queue(size);
+        let ids = picked(&picks, queue.jobs().map(|job| job.id()));
+        group.bench_with_input(BenchmarkId::new("export_queue", size), &ids, |b, ids| {
+            b.iter(|| {
+                for &id in ids {
+                    black_box(queue.get_job(id));
+                }
+            });
+        });
+
+        let markers = bench::marker_manager(size);
+        let ids = picked(&picks, markers.markers().map(|marker| marker.id()));
+        group.bench_with_input(BenchmarkId::new("markers", size), &ids, |b, ids| {
+            b.iter(|| {
+                for &id in ids {
+                    black_box(markers.get_marker(id));
+                }
+            });
+        });
+
+        let transitions = bench::transition_manager(size);
+        let ids = picked(&picks, transitions.all_transitions().map(|t| t.transition.id()));
+        group.bench_with_input(BenchmarkId::new("transitions", size), &ids, |b, ids| {
+            b.iter(|| {
+                for &id in ids {
+                    black_box(transitions.get_transition(id));
+                }
+            });
+        });
+
+        let animation = bench::animation_manager(size);
+        let ids: Vec<_> = picks.iter().map(|&i| i as u64 + 1).collect();
+        group.bench_with_input(BenchmarkId::new("animation", size), &ids, |b, ids| {
+            b.iter(|| {
+                for &id in ids {
+                    black_box(animation.get_layer(id));
+                }
+            });
+        });
+    }
+    group.finish();
+}
+
+fn remove(c: &mut Criterion) {
+    let mut group = c.benchmark_group("manager_remove");
+    group.throughput(Throughput::Elements(REMOVALS as u64));
+    for size in SIZES {
+        // Distinct positions, so every removal hits
+        let picks = scattered(REMOVALS, size);
+
+        group.bench_function(BenchmarkId::new("export_queue", size), |b| {
+            b.iter_batched(
+                || {
+                    let queue = bench::export_queue(size);
+                    let ids = picked(&picks, queue.jobs().map(|job| job.id()));
+                    (queue, ids)
+                },
+                |(mut queue, ids)| {
+                    for id in ids {
+                        black_box(queue.remove_job(id));
+                    }
+                },
+                BatchSize::LargeInput,
+            );
+        });
+
+        group.bench_function(BenchmarkId::new("markers", size), |b| {
+            b.iter_batched(
+                || {
+                    let markers = bench::marker_manager(size);
+                    let ids = picked(&picks, markers.markers().map(|marker| marker.id()));
+                    (markers, ids)
+                },
+                |(mut markers, ids)| {
+                    for id in ids {
+                        black_box(markers.remove_marker(id));
+                    }
+                },
+                BatchSize::LargeInput,
+            );
+        });
+
+        group.bench_function(BenchmarkId::new("transitions", size), |b| {
+            b.iter_batched(
+                || {
+                    let transitions = bench::transition_manager(size);
+                    let ids =
+                        picked(&picks, transitions.all_transitions().map(|t| t.transition.id()));
+                    (transitions, ids)
+                },
+                |(mut transitions, ids)| {
+                    for id in ids {
+                        black_box(transitions.remove_transition(id));
+                    }
+                },
+                BatchSize::LargeInput,
+            );
+        });
+
+        group.bench_function(BenchmarkId::new("animation", size), |b| {
+            b.iter_batched(
+                || bench::animation_manager(size),
+                |mut animation| {
+                    for &i in &picks {
+                        black_box(animation.remove_layer(i as u64 + 1));
+                    }
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, get, remove);
+criterion_main!(benches);
@@ -0,0 +1,192 @@
+//! Fixtures for the micro-benchmarks in `benches/`.
+//!
+//! Benchmarks link against the crate like any host, so this module
+//! re-exports the internal types they measure and builds the same frame-
+//! and block-sized inputs for every run, keeping baselines comparable
+//! across changes. Only built with the `bench` feature; not a stable API.
+
+pub use essentia_color_types::Color;
+
+pub use crate::{
+    implementation::{
+        AnimatedValue, AnimationManager, AnimationTrack, AnimationTrackId, AudioEffectType,
+        AudioMixer, ColorGradingNode, ExportQueue, InterpolationType, Lut3D, LutInterpolation,
+        MarkerManager, MarkerType, TransitionManager,
+    },
+    types::{ClipFade, ClipPitch, FadeShape, TimePosition, timeline::TimelineClip},
+};
+
+/// Interpolation types cycled through by [`dense_track`].
+const CURVES: [InterpolationType; 4] = [
+    InterpolationType::Linear,
+    InterpolationType::Bezier,
+    InterpolationType::EaseInOut,
+    InterpolationType::Hold,
+];
+
+/// Frame sweeping red across, green down and blue diagonally.
+#[must_use]
+pub fn gradient_frame(width: u32, height: u32) -> Vec<Color> {
+    let (w, h) = (width.max(1) as f32, height.max(1) as f32);
+    (0..height)
+        .flat_map(|y| {
+            (0..width).map(move |x| {
+                let (u, v) = (x as f32 / w, y as f32 / h);
+                Color::rgb(u, v, (u + v) * 0.5)
+            })
+        })
+        .collect()
+}
+
+/// [`gradient_frame`] as straight-alpha RGBA8.
+#[must_use]
+pub fn gradient_rgba8(width: u32, height: u32) -> Vec<u8> {
+    let to_u8 = |v: f32| (v * 255.0).round() as u8;
+    gradient_frame(width, height)
+        .iter()
+        .flat_map(|c| [to_u8(c.r), to_u8(c.g), to_u8(c.b), 255])
+        .collect()
+}
+
+/// Grade with every stage active: white balance, exposure, contrast,
+/// color wheels, saturation and a 33³ LUT at partial intensity.
+#[must_use]
+pub fn full_grade() -> ColorGradingNode {
+    let mut node = ColorGradingNode::new("bench");
+    node.temperature = 800.0;
+    node.tint = 0.05;
+    node.exposure = 0.5;
+    node.contrast = 0.2;
+    node.saturation = 0.15;
+    node.three_way.shadows.hue = 0.02;
+    node.three_way.highlights.saturation = -0.1;
+    node.lut = Some(graded_lut(33, LutInterpolation::Trilinear));
+    node.lut_intensity = 0.75;
+    node
+}
+
+/// `size`³ LUT of a warm, contrasty look, read with `interpolation`.
+#[must_use]
+pub fn graded_lut(size: u32, interpolation: LutInterpolation) -> Lut3D {
+    let mut look = ColorGradingNode::new("look");
+    look.temperature = 1500.0;
+    look.contrast = 0.3;
+    let mut lut = look.bake_to_lut(size);
+    lut.set_interpolation(interpolation);
+    lut
+}
+
+/// Float track with `keyframes` keyframes one frame (40 ms) apart,
+/// cycling through linear, bezier, eased and hold segments.
+#[must_use]
+pub fn dense_track(keyframes: usize) -> AnimationTrack {
+    let mut track =
+        AnimationTrack::new(AnimationTrackId::new(1), "opacity", AnimatedValue::Float(0.0));
+    for i in 0..keyframes {
+        let value = AnimatedValue::Float((i % 7) as f64 / 6.0);
+        let index = track.add_keyframe(TimePosition::from_ms(i as u64 * 40), value);
+        if let Some(keyframe) = track.get_keyframe_mut(index) {
+            keyframe.set_interpolation(CURVES[i % CURVES.len()]);
+        }
+    }
+    track
+}
+
+/// Interleaved stereo block: a 440 Hz tone over low-level noise.
+#[must_use]
+pub fn stereo_block(sample_rate: u32, frames: usize) -> Vec<f32> {
+    let mut state = 0x2545_f491_u32;
+    let step = core::f32::consts::TAU * 440.0 / sample_rate.max(1) as f32;
+    (0..frames)
+        .flat_map(|i| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let noise = (state as f32 / u32::MAX as f32 - 0.5) * 0.02;
+            let tone = (i as f32 * step).sin() * 0.5;
+            [tone + noise, tone - noise]
+        })
+        .collect()
+}
+
+/// Mixer with track 1 running spectral noise reduction with a profile
+/// learned from one second of [`stereo_block`].
+#[must_use]
+pub fn denoising_mixer(sample_rate: u32, buffer_size: usize) -> AudioMixer {
+    let mut mixer = AudioMixer::new(sample_rate, buffer_size);
+    let slot = match mixer.add_track(1, "dialog") {
+        Ok(track) => track.add_insert(AudioEffectType::SpectralNoiseReduction),
+        Err(_) => return mixer,
+    };
+    let noise = stereo_block(sample_rate, sample_rate as usize);
+    let _ = mixer.learn_noise_profile(
+        1,
+        slot,
+        &noise,
+        2,
+        TimePosition::from_ms(0),
+        TimePosition::from_ms(1000),
+    );
+    mixer
+}
+
+/// Ten second clip pitched by `semitones` with half-second equal-power
+/// fades at both ends.
+#[must_use]
+pub fn pitched_clip(semitones: f32) -> TimelineClip {
+    let mut clip = TimelineClip::new(1, 1, TimePosition::from_ms(0), TimePosition::from_ms(10_000));
+    clip.pitch = ClipPitch::new(semitones, 0.0);
+    clip.audio_fade_in = ClipFade::new(TimePosition::from_ms(500), FadeShape::EqualPower);
+    clip.audio_fade_out = clip.audio_fade_in;
+    clip
+}
+
+/// Queue of `jobs` default exports of ten seconds at 25 fps.
+#[must_use]
+pub fn export_queue(jobs: usize) -> ExportQueue {
+    let mut queue = ExportQueue::new();
+    for _ in 0..jobs {
+        queue.add_job(1, Default::default(), 250);
+    }
+    queue
+}
+
+/// `markers` markers one frame (40 ms) apart, every tenth a chapter.
+#[must_use]
+pub fn marker_manager(markers: usize) -> MarkerManager {
+    let mut manager = MarkerManager::new();
+    for i in 0..markers {
+        let position = TimePosition::from_ms(i as u64 * 40);
+        if i % 10 == 0 {
+            manager.add_chapter(position, format!("Chapter {}", i / 10 + 1));
+        } else {
+            manager.add_marker(position, MarkerType::Standard);
+        }
+    }
+    manager
+}
+
+/// `transitions` default transitions one second apart, alternating
+/// between two tracks.
+#[must_use]
+pub fn transition_manager(transitions: usize) -> TransitionManager {
+    let mut manager = TransitionManager::new();
+    for i in 0..transitions as u64 {
+        let start = TimePosition::from_ms(i * 1000);
+        manager.add_transition(i % 2 + 1, i * 2 + 1, i * 2 + 2, start, None, None);
+    }
+    manager
+}
+
+/// `layers` animation layers targeting IDs `1..=layers`, each with an
+/// opacity track.
+#[must_use]
+pub fn animation_manager(layers: usize) -> AnimationManager {
+    let mut manager = AnimationManager::new();
+    for target_id in 1..=layers as u64 {
+        if let Ok(layer) = manager.create_layer(format!("Layer {target_id}"), target_id) {
+            layer.create_track("opacity", AnimatedValue::Float(1.0));
+        }
+    }
+    manager
+}
@@ -0,0 +1,743 @@
+//! `.evproj` project bundles.
+//!
+//! A bundle is a single file holding the project JSON together with its
+//! proxies, thumbnails, LUTs and, optionally, consolidated media. Members
+//! are stored back to back after an 8-byte header and located through a
+//! directory written at the end of the file:
+//!
+//! ```text
+//! "EVPROJ\0\x01" | member data ... | directory | footer
+//! directory: u32 count, then per member
+//!            u16 name length, name, u64 offset, u64 size, u64 xxh64
+//! footer:    u64 directory offset, u64 directory length,
+//!            u64 directory xxh64, "EVPJEND\x01"
+//! ```
+//!
+//! All integers are little-endian. Saving appends only members whose
+//! content changed, followed by a fresh directory, so large bundles are not
+//! rewritten on every save. Superseded data stays in the file as garbage
+//! until it outweighs the live members, at which point the bundle is
+//! compacted.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    checksum::{Xxh64, xxh64},
+    errors::{VideoEditorError, VideoEditorResult},
+};
+
+/// Bundle file signature.
+pub const BUNDLE_MAGIC: &[u8; 8] = b"EVPROJ\0\x01";
+
+/// Name of the project document inside a bundle.
+pub const BUNDLE_PROJECT_MEMBER: &str = "project.json";
+
+/// Name of the track list display state inside a bundle.
+pub const BUNDLE_TRACK_VIEW_MEMBER: &str = "track_view.bin";
+
+/// Footer signature closing every directory.
+const FOOTER_MAGIC: &[u8; 8] = b"EVPJEND\x01";
+
+/// Footer size in bytes.
+const FOOTER_LEN: u64 = 32;
+
+/// Buffer size for streaming file members.
+const COPY_CHUNK: usize = 64 * 1024;
+
+/// What a bundle member holds, from the folder it is stored under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleMemberKind {
+    /// The project document.
+    Project,
+    /// Proxy media, under `proxies/`.
+    Proxy,
+    /// Thumbnail images, under `thumbnails/`.
+    Thumbnail,
+    /// Color lookup tables, under `luts/`.
+    Lut,
+    /// Consolidated source media, under `media/`.
+    Media,
+    /// Anything else.
+    Other,
+}
+
+impl BundleMemberKind {
+    /// Kind of the member called `name`.
+    #[must_use]
+    pub fn of(name: &str) -> Self {
+        if name == BUNDLE_PROJECT_MEMBER {
+            return Self::Project;
+        }
+        match name.split_once('/').map(|(folder, _)| folder) {
+            Some("proxies") => Self::Proxy,
+            Some("thumbnails") => Self::Thumbnail,
+            Some("luts") => Self::Lut,
+            Some("media") => Self::Media,
+            _ => Self::Other,
+        }
+    }
+
+    /// Folder members of this kind are stored under.
+    #[must_use]
+    pub const fn folder(self) -> Option<&'static str> {
+        match self {
+            Self::Proxy => Some("proxies"),
+            Self::Thumbnail => Some("thumbnails"),
+            Self::Lut => Some("luts"),
+            Self::Media => Some("media"),
+            Self::Project | Self::Other => None,
+        }
+    }
+}
+
+/// Directory entry of a bundle member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleMember {
+    /// `/`-separated name inside the bundle.
+    pub name:   String,
+    /// Offset of the data in the bundle file.
+    pub offset: u64,
+    /// Data size in bytes.
+    pub size:   u64,
+    /// xxHash64 of the data.
+    pub xxh64:  u64,
+}
+
+impl BundleMember {
+    /// What the member holds.
+    #[must_use]
+    pub fn kind(&self) -> BundleMemberKind {
+        BundleMemberKind::of(&self.name)
+    }
+}
+
+/// Files to pack next to the project document.
+#[derive(Debug, Clone, Default)]
+pub struct BundleAssets {
+    /// Proxy media files.
+    pub proxies:       Vec<PathBuf>,
+    /// Thumbnail images.
+    pub thumbnails:    Vec<PathBuf>,
+    /// LUT files.
+    pub luts:          Vec<PathBuf>,
+    /// Also pack the project's source media.
+    pub include_media: bool,
+}
+
+/// Outcome of committing changes to a bundle.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BundleSaveStats {
+    /// Members whose data was written.
+    pub written:       Vec<String>,
+    /// Members staged with content identical to what the bundle holds.
+    pub unchanged:     usize,
+    /// Members removed.
+    pub removed:       usize,
+    /// Bytes written, including the directory.
+    pub bytes_written: u64,
+    /// Whether the bundle was compacted afterwards.
+    pub compacted:     bool,
+}
+
+/// Where a staged member's data comes from.
+#[derive(Debug, Clone)]
+enum Source {
+    Bytes(Vec<u8>),
+    File {
+        path: PathBuf,
+        /// `(size, xxh64)` when already known, so unchanged files are not
+        /// read again.
+        hint: Option<(u64, u64)>,
+    },
+}
+
+/// A change waiting for [`ProjectBundle::commit`].
+#[derive(Debug, Clone)]
+enum Staged {
+    Put(String, Source),
+    Remove(String),
+}
+
+/// An `.evproj` bundle on disk.
+#[derive(Debug)]
+pub struct ProjectBundle {
+    path:     PathBuf,
+    members:  Vec<BundleMember>,
+    /// Length of the file, including directory and footer.
+    file_len: u64,
+    /// Length of the current directory.
+    dir_len:  u64,
+    staged:   Vec<Staged>,
+}
+
+impl ProjectBundle {
+    /// Create an empty bundle, replacing any file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn create(path: impl AsRef<Path>) -> VideoEditorResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = File::create(&path).map_err(|e| io_error(&path, e))?;
+        let mut out = BUNDLE_MAGIC.to_vec();
+        let dir_len = write_directory(&mut out, &[]);
+        file.write_all(&out).map_err(|e| io_error(&path, e))?;
+        Ok(Self {
+            path,
+            members: Vec::new(),
+            file_len: out.len() as u64,
+            dir_len,
+            staged: Vec::new(),
+        })
+    }
+
+    /// Open an existing bundle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a bundle.
+    pub fn open(path: impl AsRef<Path>) -> VideoEditorResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = File::open(&path).map_err(|e| io_error(&path, e))?;
+        let file_len = file.metadata().map_err(|e| io_error(&path, e))?.len();
+        let mut magic = [0; 8];
+        if file_len < BUNDLE_MAGIC.len() as u64 + FOOTER_LEN
+            || file.read_exact(&mut magic).is_err()
+            || &magic != BUNDLE_MAGIC
+        {
+            return Err(VideoEditorError::conversion("Not an .evproj bundle"));
+        }
+
+        let mut footer = [0; FOOTER_LEN as usize];
+        file.seek(SeekFrom::Start(file_len - FOOTER_LEN))
+            .and_then(|_| file.read_exact(&mut footer))
+            .map_err(|e| io_error(&path, e))?;
+        let field =
+            |i: usize| u64::from_le_bytes(footer[i * 8..i * 8 + 8].try_into().unwrap_or_default());
+        if &footer[24..] != FOOTER_MAGIC {
+            return Err(VideoEditorError::conversion("Bundle directory is missing"));
+        }
+        let (dir_offset, dir_len, dir_hash) = (field(0), field(1), field(2));
+        if dir_offset.checked_add(dir_len) != Some(file_len - FOOTER_LEN) {
+            return Err(VideoEditorError::conversion("Bundle directory is out of bounds"));
+        }
+
+        let mut directory = vec![0; dir_len as usize];
+        file.seek(SeekFrom::Start(dir_offset))
+            .and_then(|_| file.read_exact(&mut directory))
+            .map_err(|e| io_error(&path, e))?;
+        if xxh64(&directory) != dir_hash {
+            return Err(VideoEditorError::conversion("Bundle directory is corrupt"));
+        }
+        let members = read_directory(&directory, dir_offset)?;
+        Ok(Self { path, members, file_len, dir_len, staged: Vec::new() })
+    }
+
+    /// Open the bundle at `path`, or create it if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an existing file is not a bundle or the file
+    /// cannot be accessed.
+    pub fn open_or_create(path: impl AsRef<Path>) -> VideoEditorResult<Self> {
+        if path.as_ref().exists() { Self::open(path) } else { Self::create(path) }
+    }
+
+    /// Bundle file path.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Committed members, in file order of their directory.
+    #[must_use]
+    pub fn members(&self) -> &[BundleMember] {
+        &self.members
+    }
+
+    /// Committed member called `name`.
+    #[must_use]
+    pub fn member(&self, name: &str) -> Option<&BundleMember> {
+        self.members.iter().find(|m| m.name == name)
+    }
+
+    /// Returns whether a committed member called `name` exists.
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.member(name).is_some()
+    }
+
+    /// Bytes held by superseded member data and old directories.
+    #[must_use]
+    pub fn garbage_bytes(&self) -> u64 {
+        let live: u64 = self.members.iter().map(|m| m.size).sum();
+        self.file_len.saturating_sub(BUNDLE_MAGIC.len() as u64 + live + self.dir_len + FOOTER_LEN)
+    }
+
+    /// Read a member, checking its hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the member does not exist, cannot be read or
+    /// fails its hash check.
+    pub fn read(&self, name: &str) -> VideoEditorResult<Vec<u8>> {
+        let member = self.require(name)?;
+        let mut file = File::open(&self.path).map_err(|e| io_error(&self.path, e))?;
+        let mut data = vec![0; member.size as usize];
+        file.seek(SeekFrom::Start(member.offset))
+            .and_then(|_| file.read_exact(&mut data))
+            .map_err(|e| io_error(&self.path, e))?;
+        if xxh64(&data) != member.xxh64 {
+            return Err(VideoEditorError::conversion(format!("Bundle member {name} is corrupt")));
+        }
+        Ok(data)
+    }
+
+    /// Read the project document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bundle has no project or it is not UTF-8.
+    pub fn read_project(&self) -> VideoEditorResult<String> {
+        String::from_utf8(self.read(BUNDLE_PROJECT_MEMBER)?)
+            .map_err(|_| VideoEditorError::conversion("Bundled project is not UTF-8"))
+    }
+
+    /// Copy a member out to `target`, checking its hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the member does not exist, cannot be copied or
+    /// fails its hash check.
+    pub fn extract(&self, name: &str, target: impl AsRef<Path>) -> VideoEditorResult<()> {
+        let target = target.as_ref();
+        let member = self.require(name)?;
+        let mut source = File::open(&self.path).map_err(|e| io_error(&self.path, e))?;
+        source.seek(SeekFrom::Start(member.offset)).map_err(|e| io_error(&self.path, e))?;
+        if let Some(dir) = target.parent() {
+            fs::create_dir_all(dir).map_err(|e| io_error(dir, e))?;
+        }
+        let mut out = File::create(target).map_err(|e| io_error(target, e))?;
+        let (_, hash) = copy_hashed(&mut source.take(member.size), &mut out)
+            .map_err(|e| io_error(target, e))?;
+        if hash != member.xxh64 {
+            return Err(VideoEditorError::conversion(format!("Bundle member {name} is corrupt")));
+        }
+        Ok(())
+    }
+
+    /// Stage a member with in-memory content.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `name` is not a valid member name.
+    pub fn put(&mut self, name: &str, data: impl Into<Vec<u8>>) -> VideoEditorResult<()> {
+        self.stage_put(name, Source::Bytes(data.into()))
+    }
+
+    /// Stage the project document.
+    pub fn put_project(&mut self, json: &str) {
+        self.staged.push(Staged::Put(BUNDLE_PROJECT_MEMBER.into(), Source::Bytes(json.into())));
+    }
+
+    /// Stage a member read from `path` when committing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `name` is not a valid member name.
+    pub fn put_file(&mut self, name: &str, path: impl AsRef<Path>) -> VideoEditorResult<()> {
+        self.stage_put(name, Source::File { path: path.as_ref().to_path_buf(), hint: None })
+    }
+
+    /// Like [`put_file`](Self::put_file) for a file whose size and xxHash64
+    /// are already known; an unchanged member is then skipped without
+    /// reading the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `name` is not a valid member name.
+    pub fn put_file_hashed(
+        &mut self, name: &str, path: impl AsRef<Path>, size: u64, xxh64: u64,
+    ) -> VideoEditorResult<()> {
+        let hint = Some((size, xxh64));
+        self.stage_put(name, Source::File { path: path.as_ref().to_path_buf(), hint })
+    }
+
+    /// Stage the removal of a member.
+    pub fn remove(&mut self, name: &str) {
+        self.staged.push(Staged::Remove(name.into()));
+    }
+
+    /// Stage the removal of every committed or staged member for which
+    /// `keep` returns false.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        let mut names: Vec<String> = self.members.iter().map(|m| m.name.clone()).collect();
+        names.extend(self.staged.iter().filter_map(|s| match s {
+            Staged::Put(name, _) => Some(name.clone()),
+            Staged::Remove(_) => None,
+        }));
+        names.sort();
+        names.dedup();
+        for name in names.into_iter().filter(|n| !keep(n)) {
+            self.staged.push(Staged::Remove(name));
+        }
+    }
+
+    /// Names of members a commit would leave in the bundle.
+    #[must_use]
+    pub fn staged_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.members.iter().map(|m| m.name.clone()).collect();
+        for staged in &self.staged {
+            match staged {
+                Staged::Put(name, _) if !names.contains(name) => names.push(name.clone()),
+                Staged::Remove(name) => names.retain(|n| n != name),
+                Staged::Put(..) => {},
+            }
+        }
+        names
+    }
+
+    /// Returns whether there are staged changes.
+    #[must_use]
+    pub fn has_staged_changes(&self) -> bool {
+        !self.staged.is_empty()
+    }
+
+    /// Write staged changes.
+    ///
+    /// Members whose content matches the committed data are skipped; the
+    /// rest are appended, then a new directory replaces the old one. Once
+    /// garbage exceeds the live data the bundle is compacted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a source file cannot be read or the bundle
+    /// cannot be written. The bundle on disk keeps its previous directory
+    /// when a commit fails part way.
+    pub fn commit(&mut self) -> VideoEditorResult<BundleSaveStats> {
+        let staged = std::mem::take(&mut self.staged);
+        let mut stats = BundleSaveStats::default();
+        let mut members = self.members.clone();
+        let mut file =
+            OpenOptions::new().write(true).open(&self.path).map_err(|e| io_error(&self.path, e))?;
+        let mut end = self.file_len;
+        file.seek(SeekFrom::Start(end)).map_err(|e| io_error(&self.path, e))?;
+
+        for change in staged {
+            let name = match change {
+                Staged::Remove(name) => {
+                    let before = members.len();
+                    members.retain(|m| m.name != name);
+                    stats.removed += before - members.len();
+                    continue;
+                },
+                Staged::Put(name, source) => {
+                    let existing = members.iter().position(|m| m.name == name);
+                    let current = existing.map(|i| (members[i].size, members[i].xxh64));
+                    let written = match write_member(&mut file, &source, current) {
+                        Ok(written) => written,
+                        Err(e) => {
+                            // Drop the partial data so the old footer ends the file again
+                            let _ = file.set_len(self.file_len);
+                            return Err(io_error(&self.path, e));
+                        },
+                    };
+                    let Some((size, hash)) = written else {
+                        stats.unchanged += 1;
+                        continue;
+                    };
+                    let member =
+                        BundleMember { name: name.clone(), offset: end, size, xxh64: hash };
+                    match existing {
+                        Some(i) => members[i] = member,
+                        None => members.push(member),
+                    }
+                    end += size;
+                    stats.bytes_written += size;
+                    name
+                },
+            };
+            stats.written.retain(|n| *n != name);
+            stats.written.push(name);
+        }
+
+        if stats.written.is_empty() && stats.removed == 0 {
+            return Ok(stats);
+        }
+        let mut out = Vec::new();
+        let dir_len = write_directory(&mut out, &members);
+        // The directory is written relative to where the data ended
+        patch_directory_offset(&mut out, end);
+        if let Err(e) = file
+            .write_all(&out)
+            .and_then(|()| file.set_len(end + out.len() as u64))
+            .and_then(|()| file.sync_data())
+        {
+            let _ = file.set_len(self.file_len);
+            return Err(io_error(&self.path, e));
+        }
+        stats.bytes_written += out.len() as u64;
+        self.members = members;
+        self.file_len = end + out.len() as u64;
+        self.dir_len = dir_len;
+
+        let live: u64 = self.members.iter().map(|m| m.size).sum();
+        if self.garbage_bytes() > live {
+            self.compact()?;
+            stats.compacted = true;
+        }
+        Ok(stats)
+    }
+
+    /// Rewrite the bundle with only its live members.
+    ///
+    /// The new file is written next to the bundle and renamed over it, so
+    /// the bundle stays readable if compaction is interrupted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bundle cannot be read or rewritten.
+    pub fn compact(&mut self) -> VideoEditorResult<()> {
+        let temp = self.path.with_extension("evproj.tmp");
+        let mut source = File::open(&self.path).map_err(|e| io_error(&self.path, e))?;
+        let mut out = File::create(&temp).map_err(|e| io_error(&temp, e))?;
+        out.write_all(BUNDLE_MAGIC).map_err(|e| io_error(&temp, e))?;
+        let mut end = BUNDLE_MAGIC.len() as u64;
+        let mut members = Vec::with_capacity(self.members.len());
+        for member in &self.members {
+            source.seek(SeekFrom::Start(member.offset)).map_err(|e| io_error(&self.path, e))?;
+            let (size, hash) = copy_hashed(&mut (&mut source).take(member.size), &mut out)
+                .map_err(|e| io_error(&temp, e))?;
+            if size != member.size || hash != member.xxh64 {
+                let _ = fs::remove_file(&temp);
+                return Err(VideoEditorError::conversion(format!(
+                    "Bundle member {} is corrupt",
+                    member.name
+                )));
+            }
+            members.push(BundleMember { offset: end, ..member.clone() });
+            end += size;
+        }
+        let mut directory = Vec::new();
+        let dir_len = write_directory(&mut directory, &members);
+        patch_directory_offset(&mut directory, end);
+        out.write_all(&directory).and_then(|()| out.sync_all()).map_err(|e| io_error(&temp, e))?;
+        drop(out);
+        fs::rename(&temp, &self.path).map_err(|e| io_error(&self.path, e))?;
+        self.members = members;
+        self.file_len = end + directory.len() as u64;
+        self.dir_len = dir_len;
+        Ok(())
+    }
+
+    fn require(&self, name: &str) -> VideoEditorResult<&BundleMember> {
+        self.member(name)
+            .ok_or_else(|| VideoEditorError::Asset(format!("Bundle has no member {name}")))
+    }
+
+    fn stage_put(&mut self, name: &str, source: Source) -> VideoEditorResult<()> {
+        let valid = !name.is_empty()
+            && name.len() <= usize::from(u16::MAX)
+            && !name.starts_with('/')
+            && !name.contains('\\')
+            && name.split('/').all(|part| !part.is_empty() && part != "." && part != "..");
+        if !valid {
+            return Err(VideoEditorError::Asset(format!("Invalid bundle member name: {name}")));
+        }
+        self.staged.push(Staged::Put(name.into(), source));
+        Ok(())
+    }
+}
+
+/// Append a member's data unless it matches `current`, returning the
+/// written `(size, xxh64)`.
+fn write_member(
+    file: &mut File, source: &Source, current: Option<(u64, u64)>,
+) -> io::Result<Option<(u64, u64)>> {
+    match source {
+        Source::Bytes(data) => {
+            let written = (data.len() as u64, xxh64(data));
+            if current == Some(written) {
+                return Ok(None);
+            }
+            file.write_all(data)?;
+            Ok(Some(written))
+        },
+        Source::File { path, hint } => {
+            if current.is_some() && *hint == current {
+                return Ok(None);
+            }
+            let size = fs::metadata(path)?.len();
+            if current.is_some_and(|(current_size, _)| current_size == size) {
+                let (_, hash) = copy_hashed(&mut File::open(path)?, &mut io::sink())?;
+                if current == Some((size, hash)) {
+                    return Ok(None);
+                }
+            }
+            // A file that grows while being copied is cut at its stat size
+            copy_hashed(&mut File::open(path)?.take(size), file).map(Some)
+        },
+    }
+}
+
+/// Copy everything from `reader`, returning the byte count and xxHash64.
+fn copy_hashed(reader: &mut impl Read, writer: &mut impl Write) -> io::Result<(u64, u64)> {
+    let mut hasher = Xxh64::default();
+    let mut buffer = vec![0; COPY_CHUNK];
+    let mut size = 0;
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read])?;
+        size += read as u64;
+    }
+    Ok((size, hasher.finish()))
+}
+
+/// Append the directory and a footer with a zero offset, returning the
+/// directory length.
+fn write_directory(out: &mut Vec<u8>, members: &[BundleMember]) -> u64 {
+    let start = out.len();
+    out.extend_from_slice(&(members.len() as u32).to_le_bytes());
+    for member in members {
+        out.extend_from_slice(&(member.name.len() as u16).to_le_bytes());
+        out.extend_from_slice(member.name.as_bytes());
+        out.extend_from_slice(&member.offset.to_le_bytes());
+        out.extend_from_slice(&member.size.to_le_bytes());
+        out.extend_from_slice(&member.xxh64.to_le_bytes());
+    }
+    let dir_len = (out.len() - start) as u64;
+    let dir_hash = xxh64(&out[start..]);
+    out.extend_from_slice(&(start as u64).to_le_bytes());
+    out.extend_from_slice(&dir_len.to_le_bytes());
+    out.extend_from_slice(&dir_hash.to_le_bytes());
+    out.extend_from_slice(FOOTER_MAGIC);
+    dir_len
+}
+
+/// Shift the footer's directory offset by `base`, for a directory buffer
+/// written at `base` in the file.
+fn patch_directory_offset(out: &mut [u8], base: u64) {
+    let at = out.len() - FOOTER_LEN as usize;
+    let offset = u64::from_le_bytes(out[at..at + 8].try_into().unwrap_or_default()) + base;
+    out[at..at + 8].copy_from_slice(&offset.to_le_bytes());
+}
+
+fn read_directory(data: &[u8], dir_offset: u64) -> VideoEditorResult<Vec<BundleMember>> {
+    let truncated = || VideoEditorError::conversion("Bundle directory is truncated");
+    let mut pos = 0;
+    let mut take = |len: usize| -> VideoEditorResult<&[u8]> {
+        let bytes = data.get(pos..pos + len).ok_or_else(truncated)?;
+        pos += len;
+        Ok(bytes)
+    };
+    let count = u32::from_le_bytes(take(4)?.try_into().map_err(|_| truncated())?);
+    let mut members = Vec::with_capacity(count.min(1 << 16) as usize);
+    for _ in 0..count {
+        let name_len = u16::from_le_bytes(take(2)?.try_into().map_err(|_| truncated())?);
+        let name = String::from_utf8(take(usize::from(name_len))?.to_vec())
+            .map_err(|_| VideoEditorError::conversion("Bundle member name is not UTF-8"))?;
+        let mut field = || -> VideoEditorResult<u64> {
+            Ok(u64::from_le_bytes(take(8)?.try_into().map_err(|_| truncated())?))
+        };
+        let (offset, size, hash) = (field()?, field()?, field()?);
+        if offset < BUNDLE_MAGIC.len() as u64 || offset.saturating_add(size) > dir_offset {
+            return Err(VideoEditorError::conversion(format!(
+                "Bundle member {name} is out of bounds"
+            )));
+        }
+        members.push(BundleMember { name, offset, size, xxh64: hash });
+    }
+    Ok(members)
+}
+
+fn io_error(path: &Path, error: io::Error) -> VideoEditorError {
+    VideoEditorError::io(path, error)
+}
+
+#[cfg(all(test, feature = "full-tests"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_incremental_save() {
+        let dir = std::env::temp_dir().join(format!("evep_bundle_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("test assertion");
+        let proxy = dir.join("a_proxy.mp4");
+        fs::write(&proxy, vec![7u8; 200_000]).expect("test assertion");
+        let path = dir.join("show.evproj");
+
+        let mut bundle = ProjectBundle::create(&path).expect("test assertion");
+        bundle.put_project("{\"name\":\"Show\"}");
+        bundle.put_file("proxies/a_proxy.mp4", &proxy).expect("test assertion");
+        bundle.put("luts/warm.cube", b"LUT_3D_SIZE 2".to_vec()).expect("test assertion");
+        let stats = bundle.commit().expect("test assertion");
+        assert_eq!(stats.written.len(), 3);
+        let size = fs::metadata(&path).expect("test assertion").len();
+
+        // Only the changed project document is appended
+        bundle.put_project("{\"name\":\"Show v2\"}");
+        bundle.put_file("proxies/a_proxy.mp4", &proxy).expect("test assertion");
+        bundle.remove("luts/warm.cube");
+        let stats = bundle.commit().expect("test assertion");
+        assert_eq!(stats.written, vec![BUNDLE_PROJECT_MEMBER.to_string()]);
+        assert_eq!((stats.unchanged, stats.removed), (1, 1));
+        assert!(stats.bytes_written < 200);
+        assert!(fs::metadata(&path).expect("test assertion").len() < size + 200);
+
+        let reopened = ProjectBundle::open(&path).expect("test assertion");
+        assert_eq!(reopened.read_project().expect("test assertion"), "{\"name\":\"Show v2\"}");
+        assert!(!reopened.contains("luts/warm.cube"));
+        assert_eq!(
+            reopened.member("proxies/a_proxy.mp4").map(BundleMember::kind),
+            Some(BundleMemberKind::Proxy)
+        );
+        let extracted = dir.join("out/a_proxy.mp4");
+        reopened.extract("proxies/a_proxy.mp4", &extracted).expect("test assertion");
+        assert_eq!(fs::read(&extracted).expect("test assertion"), vec![7u8; 200_000]);
+
+        assert!(bundle.put("../escape", Vec::new()).is_err());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_bundle_compacts_garbage() {
+        let dir = std::env::temp_dir().join(format!("evep_bundle_gc_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("test assertion");
+        let path = dir.join("gc.evproj");
+        let mut bundle = ProjectBundle::create(&path).expect("test assertion");
+        bundle.put("media/a.mov", vec![0u8; 25_000]).expect("test assertion");
+        bundle.put("thumbnails/a.png", vec![0u8; 10_000]).expect("test assertion");
+        bundle.commit().expect("test assertion");
+
+        let initial = bundle.garbage_bytes();
+        // Each rewrite of the thumbnail leaves its old copy behind until the
+        // garbage outgrows the 35 kB of live data
+        for version in 1..=4u8 {
+            bundle.put("thumbnails/a.png", vec![version; 10_000]).expect("test assertion");
+            let stats = bundle.commit().expect("test assertion");
+            assert_eq!(stats.compacted, version == 4);
+            if version == 1 {
+                let superseded = 10_000 + bundle.dir_len + FOOTER_LEN;
+                assert_eq!(bundle.garbage_bytes(), initial + superseded);
+            }
+        }
+        assert_eq!(bundle.garbage_bytes(), 0);
+        assert_eq!(fs::metadata(&path).expect("test assertion").len(), bundle.file_len);
+        let reopened = ProjectBundle::open(&path).expect("test assertion");
+        assert_eq!(reopened.read("thumbnails/a.png").expect("test assertion"), vec![4u8; 10_000]);
+
+        fs::write(&path, b"not a bundle at all, really").expect("test assertion");
+        assert!(ProjectBundle::open(&path).is_err());
+        fs::remove_dir_all(&dir).ok();
+    }
+}
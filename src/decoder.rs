@@ -0,0 +1,446 @@
+//! Pluggable decoder interface.
+//!
+//! Decoders register themselves for one or more [`InputFormat`]s in a
+//! [`DecoderRegistry`]. The [`FormatConverter`](crate::converter::FormatConverter)
+//! consults the registry before falling back to its built-in conversion paths,
+//! so external crates can contribute decoders (ProRes, EXR sequences, ...)
+//! without modifying the converter.
+
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::{
+    converter::InputFormat,
+    errors::{CodecStage, VideoEditorError, VideoEditorResult},
+    trace::{self, TracePhase},
+    types::{
+        BufferDescriptor, FieldOrder, FrameBuffer, FrameBufferPool, PixelAspectRatio,
+        ProjectionMode, Rotation,
+    },
+};
+
+/// Stream information reported by a decoder when a source is opened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamInfo {
+    /// Detected input format.
+    pub format:              InputFormat,
+    /// Frame width in pixels (0 for audio-only sources).
+    pub width:               u32,
+    /// Frame height in pixels (0 for audio-only sources).
+    pub height:              u32,
+    /// Frame rate numerator.
+    pub fps_num:             u32,
+    /// Frame rate denominator.
+    pub fps_den:             u32,
+    /// Total number of frames, if known.
+    pub frame_count:         Option<u64>,
+    /// Duration in milliseconds, if known.
+    pub duration_ms:         Option<u64>,
+    /// Whether the source carries audio.
+    pub has_audio:           bool,
+    /// Whether the source has a variable frame rate.
+    pub variable_frame_rate: bool,
+    /// Color space tagged in the source, if any.
+    pub color_space:         Option<String>,
+    /// Progressive or interlaced scan.
+    pub field_order:         FieldOrder,
+    /// Pixel aspect tagged in the source, if any.
+    pub pixel_aspect:        Option<PixelAspectRatio>,
+    /// Rotation from the source's orientation tag or display matrix.
+    pub rotation:            Rotation,
+    /// Projection from the source's spherical metadata.
+    pub projection:          ProjectionMode,
+}
+
+impl StreamInfo {
+    /// Creates stream info for a video source.
+    #[must_use]
+    pub fn video(format: InputFormat, width: u32, height: u32, fps_num: u32, fps_den: u32) -> Self {
+        Self {
+            format,
+            width,
+            height,
+            fps_num,
+            fps_den: fps_den.max(1),
+            frame_count: None,
+            duration_ms: None,
+            has_audio: false,
+            variable_frame_rate: false,
+            color_space: None,
+            field_order: FieldOrder::Progressive,
+            pixel_aspect: None,
+            rotation: Rotation::None,
+            projection: ProjectionMode::Flat,
+        }
+    }
+
+    /// Sets the total frame count.
+    #[must_use]
+    pub fn with_frame_count(mut self, frame_count: u64) -> Self {
+        self.frame_count = Some(frame_count);
+        self
+    }
+
+    /// Sets the duration.
+    #[must_use]
+    pub fn with_duration_ms(mut self, duration_ms: u64) -> Self {
+        self.duration_ms = Some(duration_ms);
+        self
+    }
+
+    /// Marks the source as variable frame rate.
+    #[must_use]
+    pub fn with_variable_frame_rate(mut self) -> Self {
+        self.variable_frame_rate = true;
+        self
+    }
+
+    /// Sets the tagged color space.
+    #[must_use]
+    pub fn with_color_space(mut self, color_space: impl Into<String>) -> Self {
+        self.color_space = Some(color_space.into());
+        self
+    }
+
+    /// Sets the scan of interlaced sources.
+    #[must_use]
+    pub fn with_field_order(mut self, field_order: FieldOrder) -> Self {
+        self.field_order = field_order;
+        self
+    }
+
+    /// Sets the tagged pixel aspect.
+    #[must_use]
+    pub fn with_pixel_aspect(mut self, pixel_aspect: PixelAspectRatio) -> Self {
+        self.pixel_aspect = Some(pixel_aspect);
+        self
+    }
+
+    /// Sets the tagged rotation.
+    #[must_use]
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Sets the tagged projection.
+    #[must_use]
+    pub fn with_projection(mut self, projection: ProjectionMode) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Returns the frame rate as a float.
+    #[must_use]
+    pub fn fps(&self) -> f64 {
+        self.fps_num as f64 / self.fps_den.max(1) as f64
+    }
+}
+
+/// A single decoded frame.
+#[derive(Debug, Clone)]
+pub struct DecodedFrame {
+    /// Frame index within the stream (0-indexed).
+    pub index:    u64,
+    /// Presentation timestamp in milliseconds.
+    pub pts_ms:   u64,
+    /// Whether this frame can be decoded independently.
+    pub keyframe: bool,
+    /// Frame width in pixels.
+    pub width:    u32,
+    /// Frame height in pixels.
+    pub height:   u32,
+    /// Decoded RGBA8 pixel data, returned to its pool when dropped.
+    pub data:     FrameBuffer,
+}
+
+impl DecodedFrame {
+    /// Packed RGBA8 layout of a frame this size.
+    #[must_use]
+    pub const fn descriptor(&self) -> BufferDescriptor {
+        BufferDescriptor::rgba8(self.width, self.height)
+    }
+}
+
+/// Decoder for one or more input formats.
+///
+/// A decoder instance is stateful: [`open`](Decoder::open) binds it to a
+/// source, after which frames are pulled with [`read_frame`](Decoder::read_frame).
+pub trait Decoder: Send {
+    /// Human readable decoder name.
+    fn name(&self) -> &str;
+
+    /// Returns true if this decoder can handle the given source.
+    ///
+    /// Called before [`open`](Decoder::open) so several decoders registered
+    /// for the same format can decide which one takes the file.
+    fn probe(&self, path: &str) -> bool;
+
+    /// Opens a source and returns its stream information.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source cannot be opened or parsed.
+    fn open(&mut self, path: &str) -> VideoEditorResult<StreamInfo>;
+
+    /// Reads the next frame, or `None` at end of stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame cannot be decoded.
+    fn read_frame(&mut self) -> VideoEditorResult<Option<DecodedFrame>>;
+
+    /// Seeks so the next [`read_frame`](Decoder::read_frame) returns `frame`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame is out of range or the source is not seekable.
+    fn seek(&mut self, frame: u64) -> VideoEditorResult<()>;
+
+    /// Hands over the pool to take frame buffers from.
+    ///
+    /// Called by [`DecoderRegistry::open`] before [`open`](Decoder::open).
+    /// Decoders that can write into caller memory keep the pool and decode
+    /// into [`FrameBufferPool::acquire`]d buffers, so frames dropped by the
+    /// converter, preview cache or export are reused rather than
+    /// reallocated. Ignored by default.
+    fn set_frame_pool(&mut self, pool: &FrameBufferPool) {
+        let _ = pool;
+    }
+}
+
+/// Reads the next frame of `decoder` inside a decode trace span.
+pub(crate) fn read_frame_traced(
+    decoder: &mut dyn Decoder,
+) -> VideoEditorResult<Option<DecodedFrame>> {
+    let _span = trace::span(TracePhase::Decode, decoder.name());
+    decoder.read_frame()
+}
+
+/// Factory creating fresh decoder instances.
+pub type DecoderFactory = Box<dyn Fn() -> Box<dyn Decoder> + Send + Sync>;
+
+/// Registry of decoders keyed by input format.
+#[derive(Default)]
+pub struct DecoderRegistry {
+    /// Registered factories per format, most recently registered last.
+    factories:  HashMap<InputFormat, Vec<DecoderFactory>>,
+    /// Pool handed to every opened decoder.
+    frame_pool: FrameBufferPool,
+}
+
+impl fmt::Debug for DecoderRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecoderRegistry")
+            .field("formats", &self.factories.keys().collect::<Vec<_>>())
+            .field("frame_pool", &self.frame_pool.stats())
+            .finish()
+    }
+}
+
+impl DecoderRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a decoder factory for the given formats.
+    ///
+    /// Decoders registered later take precedence over earlier ones for the
+    /// same format.
+    pub fn register<F>(&mut self, formats: &[InputFormat], factory: F)
+    where
+        F: Fn() -> Box<dyn Decoder> + Send + Sync + Clone + 'static,
+    {
+        for format in formats {
+            self.factories.entry(*format).or_default().push(Box::new(factory.clone()));
+        }
+    }
+
+    /// Removes all decoders registered for a format.
+    pub fn unregister(&mut self, format: InputFormat) -> bool {
+        self.factories.remove(&format).is_some()
+    }
+
+    /// Pool opened decoders take frame buffers from.
+    #[must_use]
+    pub const fn frame_pool(&self) -> &FrameBufferPool {
+        &self.frame_pool
+    }
+
+    /// Shares `pool` with decoders opened from now on, e.g. the pool the
+    /// preview cache and export use.
+    pub fn set_frame_pool(&mut self, pool: FrameBufferPool) {
+        self.frame_pool = pool;
+    }
+
+    /// Checks if any decoder is registered for a format.
+    #[must_use]
+    pub fn supports(&self, format: InputFormat) -> bool {
+        self.factories.get(&format).is_some_and(|f| !f.is_empty())
+    }
+
+    /// Returns the number of decoders registered for a format.
+    #[must_use]
+    pub fn decoder_count(&self, format: InputFormat) -> usize {
+        self.factories.get(&format).map_or(0, Vec::len)
+    }
+
+    /// Returns all formats with at least one registered decoder.
+    #[must_use]
+    pub fn formats(&self) -> Vec<InputFormat> {
+        self.factories.iter().filter(|(_, f)| !f.is_empty()).map(|(format, _)| *format).collect()
+    }
+
+    /// Creates a decoder for `path`, picking the most recently registered
+    /// decoder for `format` whose probe accepts the file.
+    #[must_use]
+    pub fn create(&self, format: InputFormat, path: &str) -> Option<Box<dyn Decoder>> {
+        self.factories
+            .get(&format)?
+            .iter()
+            .rev()
+            .map(|factory| factory())
+            .find(|decoder| decoder.probe(path))
+    }
+
+    /// Creates and opens a decoder for `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no registered decoder accepts the file or opening fails.
+    pub fn open(
+        &self, format: InputFormat, path: &str,
+    ) -> VideoEditorResult<(Box<dyn Decoder>, StreamInfo)> {
+        let mut decoder = self.create(format, path).ok_or_else(|| {
+            let message = format!("No registered decoder accepts {format} file");
+            VideoEditorError::codec(format.to_string(), CodecStage::Probe, message)
+        })?;
+        decoder.set_frame_pool(&self.frame_pool);
+        let info = decoder.open(path)?;
+        Ok((decoder, info))
+    }
+}
+
+#[cfg(all(test, feature = "full-tests"))]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct CountingDecoder {
+        name:   &'static str,
+        frames: u64,
+        next:   u64,
+        pool:   Option<FrameBufferPool>,
+    }
+
+    impl Decoder for CountingDecoder {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn probe(&self, path: &str) -> bool {
+            !path.contains("reject")
+        }
+
+        fn open(&mut self, _path: &str) -> VideoEditorResult<StreamInfo> {
+            self.next = 0;
+            Ok(StreamInfo::video(InputFormat::Exr, 4, 4, 24, 1).with_frame_count(self.frames))
+        }
+
+        fn read_frame(&mut self) -> VideoEditorResult<Option<DecodedFrame>> {
+            if self.next >= self.frames {
+                return Ok(None);
+            }
+            let index = self.next;
+            self.next += 1;
+            let descriptor = BufferDescriptor::rgba8(4, 4);
+            let mut data = match &self.pool {
+                Some(pool) => pool.acquire(descriptor),
+                None => vec![0; 64].into(),
+            };
+            data.fill(index as u8);
+            Ok(Some(DecodedFrame {
+                index,
+                pts_ms: index * 1000 / 24,
+                keyframe: true,
+                width: 4,
+                height: 4,
+                data,
+            }))
+        }
+
+        fn seek(&mut self, frame: u64) -> VideoEditorResult<()> {
+            if frame >= self.frames {
+                return Err(VideoEditorError::codec("test", CodecStage::Seek, "Seek out of range"));
+            }
+            self.next = frame;
+            Ok(())
+        }
+
+        fn set_frame_pool(&mut self, pool: &FrameBufferPool) {
+            self.pool = Some(pool.clone());
+        }
+    }
+
+    fn counting(name: &'static str, frames: u64) -> impl Fn() -> Box<dyn Decoder> + Clone {
+        move || Box::new(CountingDecoder { name, frames, next: 0, pool: None }) as Box<dyn Decoder>
+    }
+
+    #[test]
+    fn test_register_and_open() {
+        let mut registry = DecoderRegistry::new();
+        registry.register(&[InputFormat::Exr, InputFormat::Psd], counting("exr", 3));
+
+        assert!(registry.supports(InputFormat::Exr));
+        assert!(registry.supports(InputFormat::Psd));
+        assert!(!registry.supports(InputFormat::Mp4));
+
+        let (mut decoder, info) =
+            registry.open(InputFormat::Exr, "shot.exr").expect("test assertion");
+        assert_eq!(info.frame_count, Some(3));
+        decoder.seek(2).expect("test assertion");
+        assert_eq!(decoder.read_frame().expect("test assertion").map(|f| f.index), Some(2));
+        assert!(decoder.read_frame().expect("test assertion").is_none());
+    }
+
+    #[test]
+    fn test_later_registration_wins() {
+        let mut registry = DecoderRegistry::new();
+        registry.register(&[InputFormat::Mov], counting("builtin", 1));
+        registry.register(&[InputFormat::Mov], counting("prores", 1));
+
+        let decoder = registry.create(InputFormat::Mov, "clip.mov").expect("test assertion");
+        assert_eq!(decoder.name(), "prores");
+        assert_eq!(registry.decoder_count(InputFormat::Mov), 2);
+    }
+
+    #[test]
+    fn test_probe_rejection() {
+        let mut registry = DecoderRegistry::new();
+        registry.register(&[InputFormat::Exr], counting("exr", 1));
+
+        assert!(registry.create(InputFormat::Exr, "reject.exr").is_none());
+        assert!(registry.open(InputFormat::Exr, "reject.exr").is_err());
+    }
+
+    #[test]
+    fn test_decoders_draw_from_pool() {
+        let mut registry = DecoderRegistry::new();
+        registry.register(&[InputFormat::Exr], counting("exr", 4));
+
+        let (mut decoder, _) = registry.open(InputFormat::Exr, "shot.exr").expect("test assertion");
+        let kept = decoder.read_frame().expect("test assertion").expect("test assertion");
+        while let Some(frame) = decoder.read_frame().expect("test assertion") {
+            assert!(frame.data.is_pooled());
+            assert_eq!(frame.data[0], frame.index as u8);
+        }
+
+        // One buffer stays with `kept`; the rest cycle through a second
+        let stats = registry.frame_pool().stats();
+        assert_eq!((stats.allocated, stats.reused, stats.free), (2, 2, 1));
+        drop(kept);
+        assert_eq!(registry.frame_pool().stats().free, 2);
+    }
+}
@@ -5,4 +5,4 @@
 
 mod video_editor_error;
 
-pub use video_editor_error::{VideoEditorError, VideoEditorResult};
+pub use video_editor_error::{CodecStage, ErrorContext, VideoEditorError, VideoEditorResult};
@@ -1,35 +1,121 @@
 //! Video editor error definitions.
 //!
 //! Provides `VideoEditorError` for video editing operations including
-//! timeline, asset, effect, GPU, export, format conversion, and decoding
-//! errors.
+//! timeline, asset, effect, GPU, export, format conversion, and codec
+//! errors, and cancellation of long operations.
+//!
+//! Variants that hosts act on carry structured fields (the path of a
+//! failed file operation, the codec and stage of a codec failure, the
+//! edit and clip of a timeline failure). Every error has a stable numeric
+//! [`code`](VideoEditorError::code) for mapping to user-facing messages
+//! and telemetry, and [`context`](VideoEditorError::context) wraps an
+//! error with what was being done without changing its code.
 
 use core::fmt;
+use std::path::PathBuf;
+
+/// Stage of a codec operation that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodecStage {
+    /// Finding a codec for the source.
+    Probe,
+    /// Opening the stream.
+    Open,
+    /// Decoding a frame.
+    Decode,
+    /// Seeking within the stream.
+    Seek,
+    /// Encoding a frame.
+    Encode,
+    /// Writing the container.
+    Mux,
+}
+
+impl fmt::Display for CodecStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Probe => "probe",
+            Self::Open => "open",
+            Self::Decode => "decode",
+            Self::Seek => "seek",
+            Self::Encode => "encode",
+            Self::Mux => "mux",
+        })
+    }
+}
 
 /// Video editor operation errors.
 #[derive(Debug)]
 pub enum VideoEditorError {
-    /// Timeline error.
-    Timeline(String),
-    /// Asset error.
+    /// Timeline edit rejected. Code 1000.
+    Timeline {
+        /// Edit that failed, e.g. `"trim"`.
+        op:      &'static str,
+        /// Clip the edit was applied to, if any.
+        clip_id: Option<u64>,
+        /// What went wrong.
+        message: String,
+    },
+    /// Asset error. Code 1100.
     Asset(String),
-    /// Effect error.
+    /// Effect error. Code 1200.
     Effect(String),
-    /// GPU error.
+    /// GPU error. Code 1300.
     Gpu(String),
-    /// Export error.
+    /// Export error. Code 1400.
     Export(String),
-    /// IO error.
-    Io(String),
-    /// Unsupported format error.
+    /// File operation failed. Code 1500.
+    Io {
+        /// File or directory the operation was on.
+        path:   PathBuf,
+        /// Underlying error.
+        source: std::io::Error,
+    },
+    /// Unsupported format error. Code 1600.
     UnsupportedFormat(String),
-    /// Conversion error.
+    /// Conversion error. Code 1601.
     Conversion(String),
-    /// Decoder error.
-    Decoder(String),
+    /// Codec failure. Code 1700.
+    Codec {
+        /// Codec or decoder name.
+        codec:   String,
+        /// Stage that failed.
+        stage:   CodecStage,
+        /// What went wrong.
+        message: String,
+    },
+    /// Project session error, e.g. no project open. Code 1800.
+    Project(String),
+    /// Operation stopped by its cancellation token. Code 1900.
+    Cancelled(String),
+    /// Error wrapped with what was being done; has its source's code.
+    Context {
+        /// Description of the failed operation.
+        context: String,
+        /// Error being described.
+        source:  Box<VideoEditorError>,
+    },
 }
 
 impl VideoEditorError {
+    /// Create a timeline error for an edit.
+    #[must_use]
+    pub fn timeline(op: &'static str, message: impl Into<String>) -> Self {
+        Self::Timeline { op, clip_id: None, message: message.into() }
+    }
+
+    /// Create a timeline error for an edit of a clip.
+    #[must_use]
+    pub fn timeline_clip(op: &'static str, clip_id: u64, message: impl Into<String>) -> Self {
+        Self::Timeline { op, clip_id: Some(clip_id), message: message.into() }
+    }
+
+    /// Create a file operation error.
+    #[must_use]
+    pub fn io(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        Self::Io { path: path.into(), source }
+    }
+
     /// Create an unsupported format error.
     #[must_use]
     pub fn unsupported_format(msg: impl Into<String>) -> Self {
@@ -42,30 +128,144 @@ impl VideoEditorError {
         Self::Conversion(msg.into())
     }
 
-    /// Create a decoder error.
+    /// Create a codec error.
+    #[must_use]
+    pub fn codec(codec: impl Into<String>, stage: CodecStage, msg: impl Into<String>) -> Self {
+        Self::Codec { codec: codec.into(), stage, message: msg.into() }
+    }
+
+    /// Create a cancellation error for an operation.
+    #[must_use]
+    pub fn cancelled(operation: impl Into<String>) -> Self {
+        Self::Cancelled(operation.into())
+    }
+
+    /// Wrap the error with a description of what was being done.
+    #[must_use]
+    pub fn context(self, context: impl Into<String>) -> Self {
+        Self::Context { context: context.into(), source: Box::new(self) }
+    }
+
+    /// Innermost error under any context.
+    #[must_use]
+    pub fn root(&self) -> &Self {
+        match self {
+            Self::Context { source, .. } => source.root(),
+            _ => self,
+        }
+    }
+
+    /// Stable numeric code of the error, for hosts to map to messages and
+    /// telemetry. Context doesn't change the code.
     #[must_use]
-    pub fn decoder(msg: impl Into<String>) -> Self {
-        Self::Decoder(msg.into())
+    pub fn code(&self) -> u32 {
+        match self.root() {
+            Self::Timeline { .. } => 1000,
+            Self::Asset(_) => 1100,
+            Self::Effect(_) => 1200,
+            Self::Gpu(_) => 1300,
+            Self::Export(_) => 1400,
+            Self::Io { .. } => 1500,
+            Self::UnsupportedFormat(_) => 1600,
+            Self::Conversion(_) => 1601,
+            Self::Codec { .. } => 1700,
+            Self::Project(_) => 1800,
+            Self::Cancelled(_) => 1900,
+            Self::Context { .. } => unreachable!("root is never a context"),
+        }
+    }
+
+    /// Check if the error is a cancellation rather than a failure.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.root(), Self::Cancelled(_))
     }
 }
 
 impl fmt::Display for VideoEditorError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Timeline(msg) => write!(f, "Timeline error: {msg}"),
+            Self::Timeline { message, .. } => write!(f, "Timeline error: {message}"),
             Self::Asset(msg) => write!(f, "Asset error: {msg}"),
             Self::Effect(msg) => write!(f, "Effect error: {msg}"),
             Self::Gpu(msg) => write!(f, "GPU error: {msg}"),
             Self::Export(msg) => write!(f, "Export error: {msg}"),
-            Self::Io(msg) => write!(f, "IO error: {msg}"),
+            Self::Io { path, source } => write!(f, "IO error: {}: {source}", path.display()),
             Self::UnsupportedFormat(msg) => write!(f, "Unsupported format: {msg}"),
             Self::Conversion(msg) => write!(f, "Conversion error: {msg}"),
-            Self::Decoder(msg) => write!(f, "Decoder error: {msg}"),
+            Self::Codec { codec, stage, message } => {
+                write!(f, "Codec error: {codec} {stage}: {message}")
+            },
+            Self::Project(msg) => write!(f, "Project error: {msg}"),
+            Self::Cancelled(operation) => write!(f, "Cancelled: {operation}"),
+            Self::Context { context, source } => write!(f, "{context}: {source}"),
         }
     }
 }
 
-impl std::error::Error for VideoEditorError {}
+impl std::error::Error for VideoEditorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 /// Result type for video editor operations.
 pub type VideoEditorResult<T> = Result<T, VideoEditorError>;
+
+/// Adds context to the error of a [`VideoEditorResult`].
+pub trait ErrorContext<T> {
+    /// Wrap an error with a description of what was being done.
+    ///
+    /// # Errors
+    ///
+    /// Returns the original error wrapped in [`VideoEditorError::Context`].
+    fn context(self, context: impl Into<String>) -> VideoEditorResult<T>;
+
+    /// Like [`Self::context`], building the description only on error.
+    ///
+    /// # Errors
+    ///
+    /// Returns the original error wrapped in [`VideoEditorError::Context`].
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> VideoEditorResult<T>;
+}
+
+impl<T> ErrorContext<T> for VideoEditorResult<T> {
+    fn context(self, context: impl Into<String>) -> VideoEditorResult<T> {
+        self.map_err(|e| e.context(context))
+    }
+
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> VideoEditorResult<T> {
+        self.map_err(|e| e.context(context()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn test_codes_and_context() {
+        let missing = std::io::Error::new(std::io::ErrorKind::NotFound, "gone");
+        let err: VideoEditorResult<()> = Err(VideoEditorError::io("media/a.mov", missing));
+        let err = err.context("relinking media").expect_err("test assertion");
+        assert_eq!(err.code(), 1500);
+        assert_eq!(err.to_string(), "relinking media: IO error: media/a.mov: gone");
+        assert!(matches!(err.root(), VideoEditorError::Io { path, .. } if path.ends_with("a.mov")));
+        assert!(err.source().and_then(Error::source).is_some());
+
+        let trim = VideoEditorError::timeline_clip("trim", 7, "Trim would leave the clip empty");
+        assert!(matches!(trim, VideoEditorError::Timeline { op: "trim", clip_id: Some(7), .. }));
+        assert_eq!(trim.code(), 1000);
+        let cancelled = VideoEditorError::cancelled("export").context("rendering");
+        assert!(cancelled.is_cancelled());
+        assert_eq!(cancelled.code(), 1900);
+        let codec = VideoEditorError::codec("prores", CodecStage::Decode, "bad slice");
+        assert_eq!(codec.to_string(), "Codec error: prores decode: bad slice");
+    }
+}
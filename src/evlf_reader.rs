@@ -0,0 +1,606 @@
+//! Reading EVLF containers.
+//!
+//! An EVLF file starts with its header, followed by the track headers.
+//! Frame payloads live anywhere after that, located through the frame
+//! index the header points at:
+//!
+//! ```text
+//! header (64 bytes) | track headers (96 bytes each) | payloads ... |
+//! checksums (at `metadata_offset`, optional) | frame index (at `index_offset`)
+//! frame index: u64 entry count, u32 flags, u32 reserved,
+//!              then 48 bytes per indexed frame
+//! ```
+//!
+//! All integers are little-endian. The index covers every frame, or only
+//! keyframes when written with
+//! [`FrameIndexBuilder::keyframes_only`](crate::evlf_types::FrameIndexBuilder::keyframes_only).
+//! Opening a file only reads the headers; index entries are parsed when a
+//! frame is asked for, so projects referencing long renders open without
+//! reading them, and seeking binary-searches the index in place. Payloads
+//! are read on demand, or served as slices of a memory mapping when the
+//! file is opened with [`EvlfReader::open_mapped`] on a platform with
+//! `mmap`.
+//!
+//! Files flagged [`EvlfFlags::HAS_CHECKSUMS`](crate::evlf_types::EvlfFlags::HAS_CHECKSUMS)
+//! carry the digest of their source and the hash of every payload, see
+//! [`EvlfReader::checksums`].
+
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    evlf_types::{
+        EVLF_HEADER_SIZE, EVLF_INDEX_ENTRY_SIZE, EVLF_INDEX_HEADER_SIZE, EVLF_INDEX_KEYFRAMES_ONLY,
+        EVLF_TRACK_HEADER_SIZE, EvlfChecksums, EvlfFlags, EvlfHeader, EvlfTrackHeader,
+        FrameIndexEntry,
+    },
+    types::TimePosition,
+};
+
+/// Where payload bytes come from.
+enum Source {
+    Mapped(mmap::Mapping),
+    Buffered(Mutex<File>),
+}
+
+/// Lazily parsed, read-only view of an EVLF file.
+pub struct EvlfReader {
+    path:           PathBuf,
+    header:         EvlfHeader,
+    tracks:         Vec<EvlfTrackHeader>,
+    /// Entries in the frame index.
+    index_len:      u64,
+    keyframes_only: bool,
+    file_len:       u64,
+    source:         Source,
+}
+
+impl EvlfReader {
+    /// Open an EVLF file, reading payloads through the file handle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, is not an EVLF file,
+    /// or was written by an incompatible version.
+    pub fn open(path: impl AsRef<Path>) -> VideoEditorResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let (file, file_len) = Self::open_file(&path)?;
+        Self::from_source(path, file_len, Source::Buffered(Mutex::new(file)))
+    }
+
+    /// Open an EVLF file memory-mapped, serving payloads as slices of the
+    /// mapping instead of copying them. Falls back to buffered reads when
+    /// the platform has no `mmap` or the file can't be mapped, e.g. when
+    /// it is empty; [`Self::is_mapped`] tells which one is used.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other
+    /// process, while the reader or any payload borrowed from it is alive.
+    /// Doing so changes memory the reader has handed out as `&[u8]`, or
+    /// makes reading it fault.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, is not an EVLF file,
+    /// or was written by an incompatible version.
+    pub unsafe fn open_mapped(path: impl AsRef<Path>) -> VideoEditorResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let (file, file_len) = Self::open_file(&path)?;
+        // SAFETY: the caller keeps the file unchanged while the mapping,
+        // owned by the reader, is alive.
+        let source = match unsafe { mmap::Mapping::new(&file, file_len) } {
+            Ok(mapping) => Source::Mapped(mapping),
+            Err(_) => Source::Buffered(Mutex::new(file)),
+        };
+        Self::from_source(path, file_len, source)
+    }
+
+    /// Opens the file at `path`, returning it with its length.
+    fn open_file(path: &Path) -> VideoEditorResult<(File, u64)> {
+        let file = File::open(path).map_err(|e| VideoEditorError::io(path, e))?;
+        let file_len = file.metadata().map_err(|e| VideoEditorError::io(path, e))?.len();
+        Ok((file, file_len))
+    }
+
+    /// Reads the headers through `source`.
+    fn from_source(path: PathBuf, file_len: u64, source: Source) -> VideoEditorResult<Self> {
+        let header = Self::read_header(&source, &path, file_len)?;
+        let tracks = Self::read_tracks(&source, &path, file_len, &header)?;
+        let (index_len, keyframes_only) = Self::read_index(&source, &path, file_len, &header)?;
+        Ok(Self { path, header, tracks, index_len, keyframes_only, file_len, source })
+    }
+
+    /// File path.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Container header.
+    #[must_use]
+    pub fn header(&self) -> &EvlfHeader {
+        &self.header
+    }
+
+    /// Track headers, in file order.
+    #[must_use]
+    pub fn tracks(&self) -> &[EvlfTrackHeader] {
+        &self.tracks
+    }
+
+    /// Number of frames in the index.
+    #[must_use]
+    pub fn frame_count(&self) -> u64 {
+        self.header.frame_count
+    }
+
+    /// Whether the frame index only covers keyframes.
+    #[must_use]
+    pub fn has_keyframe_index(&self) -> bool {
+        self.keyframes_only
+    }
+
+    /// Whether payloads are served from a memory mapping.
+    #[must_use]
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.source, Source::Mapped(_))
+    }
+
+    /// Index entry of `frame`, parsed on demand.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `frame` is out of range, is not in a
+    /// keyframe-only index, or its entry is corrupt.
+    pub fn frame_entry(&self, frame: u64) -> VideoEditorResult<FrameIndexEntry> {
+        if frame >= self.header.frame_count {
+            return Err(VideoEditorError::conversion(format!(
+                "Frame {frame} is out of range ({} frames)",
+                self.header.frame_count
+            )));
+        }
+        if !self.keyframes_only {
+            return self.index_entry(frame);
+        }
+        let position = self.partition_point(|entry| entry.frame_number <= frame)?;
+        match position.checked_sub(1).map(|p| self.index_entry(p)).transpose()? {
+            Some(entry) if entry.frame_number == frame => Ok(entry),
+            _ => Err(VideoEditorError::conversion(format!("Frame {frame} is not indexed"))),
+        }
+    }
+
+    /// Keyframe to start decoding at to show `time`.
+    ///
+    /// This is the last keyframe at or before `time`, or the first one
+    /// when `time` precedes them all. Returns `None` if no keyframe is
+    /// indexed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an index entry is corrupt.
+    pub fn seek_to_nearest_keyframe(
+        &self, time: TimePosition,
+    ) -> VideoEditorResult<Option<FrameIndexEntry>> {
+        let after = self.partition_point(|entry| entry.pts_ms <= time.ms)?;
+        for position in (0..after).rev() {
+            let entry = self.index_entry(position)?;
+            if entry.is_keyframe() {
+                return Ok(Some(entry));
+            }
+        }
+        for position in after..self.index_len {
+            let entry = self.index_entry(position)?;
+            if entry.is_keyframe() {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Entries of the frame index, in frame order, parsed as they are
+    /// iterated.
+    pub fn index_entries(&self) -> impl Iterator<Item = VideoEditorResult<FrameIndexEntry>> + '_ {
+        (0..self.index_len).map(|position| self.index_entry(position))
+    }
+
+    /// Checksum section, or `None` if the file was written without one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the section lies outside the file, is corrupt,
+    /// or reading fails.
+    pub fn checksums(&self) -> VideoEditorResult<Option<EvlfChecksums>> {
+        if !EvlfFlags(self.header.flags).has(EvlfFlags::HAS_CHECKSUMS) {
+            return Ok(None);
+        }
+        let corrupt = || VideoEditorError::conversion("Corrupt EVLF checksum section");
+        let len = self.header.index_offset.checked_sub(self.header.metadata_offset);
+        let len = len.and_then(|len| usize::try_from(len).ok()).ok_or_else(corrupt)?;
+        let bytes =
+            self.source.read(&self.path, self.file_len, self.header.metadata_offset, len)?;
+        EvlfChecksums::from_bytes(&bytes).map(Some).ok_or_else(corrupt)
+    }
+
+    /// Payload of `frame`.
+    ///
+    /// Borrowed from the mapping when the file is mapped, read into a new
+    /// buffer otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `frame` is out of range, its payload lies
+    /// outside the file, or reading fails.
+    pub fn frame_data(&self, frame: u64) -> VideoEditorResult<Cow<'_, [u8]>> {
+        let entry = self.frame_entry(frame)?;
+        self.source
+            .read(&self.path, self.file_len, entry.data_offset, entry.data_size as usize)
+            .map_err(|e| e.context(format!("Frame {frame}")))
+    }
+
+    /// Entry at `position` in the frame index.
+    fn index_entry(&self, position: u64) -> VideoEditorResult<FrameIndexEntry> {
+        let offset = self.header.index_offset
+            + EVLF_INDEX_HEADER_SIZE as u64
+            + position * EVLF_INDEX_ENTRY_SIZE as u64;
+        let bytes = self.source.read(&self.path, self.file_len, offset, EVLF_INDEX_ENTRY_SIZE)?;
+        FrameIndexEntry::from_bytes(&bytes).ok_or_else(|| {
+            VideoEditorError::conversion(format!("Corrupt frame index entry {position}"))
+        })
+    }
+
+    /// Number of index entries for which `pred` holds, given that it holds
+    /// for a prefix of the index.
+    fn partition_point(&self, pred: impl Fn(&FrameIndexEntry) -> bool) -> VideoEditorResult<u64> {
+        let (mut low, mut high) = (0, self.index_len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if pred(&self.index_entry(mid)?) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(low)
+    }
+
+    /// Reads the container header and checks it describes a readable file.
+    fn read_header(source: &Source, path: &Path, file_len: u64) -> VideoEditorResult<EvlfHeader> {
+        let not_evlf = || VideoEditorError::conversion("Not an EVLF file");
+        if file_len < EVLF_HEADER_SIZE as u64 {
+            return Err(not_evlf());
+        }
+        let header = EvlfHeader::from_bytes(&source.read(path, file_len, 0, EVLF_HEADER_SIZE)?)
+            .filter(EvlfHeader::is_valid)
+            .ok_or_else(not_evlf)?;
+        if !header.is_supported_version() {
+            return Err(VideoEditorError::unsupported_format(format!(
+                "EVLF version {}.{} is not supported",
+                header.version >> 16,
+                (header.version >> 8) & 0xFF
+            )));
+        }
+        Ok(header)
+    }
+
+    /// Reads the frame index header, returning the entry count and whether
+    /// only keyframes are indexed.
+    fn read_index(
+        source: &Source, path: &Path, file_len: u64, header: &EvlfHeader,
+    ) -> VideoEditorResult<(u64, bool)> {
+        if header.frame_count == 0 {
+            return Ok((0, false));
+        }
+        let bytes = source.read(path, file_len, header.index_offset, EVLF_INDEX_HEADER_SIZE)?;
+        let count = u64::from_le_bytes(bytes[..8].try_into().unwrap_or_default());
+        let flags = u32::from_le_bytes(bytes[8..12].try_into().unwrap_or_default());
+        let end = count
+            .checked_mul(EVLF_INDEX_ENTRY_SIZE as u64)
+            .and_then(|len| len.checked_add(header.index_offset + EVLF_INDEX_HEADER_SIZE as u64));
+        let keyframes_only = flags & EVLF_INDEX_KEYFRAMES_ONLY != 0;
+        if (count != header.frame_count && !keyframes_only)
+            || count > header.frame_count
+            || end.is_none_or(|end| end > file_len)
+        {
+            return Err(VideoEditorError::conversion("EVLF frame index is out of bounds"));
+        }
+        Ok((count, keyframes_only))
+    }
+
+    /// Parses the track headers following the container header.
+    fn read_tracks(
+        source: &Source, path: &Path, file_len: u64, header: &EvlfHeader,
+    ) -> VideoEditorResult<Vec<EvlfTrackHeader>> {
+        let len = (header.track_count as usize)
+            .checked_mul(EVLF_TRACK_HEADER_SIZE)
+            .ok_or_else(|| VideoEditorError::conversion("EVLF track count is out of range"))?;
+        let bytes = source.read(path, file_len, EVLF_HEADER_SIZE as u64, len)?;
+        bytes
+            .chunks_exact(EVLF_TRACK_HEADER_SIZE)
+            .enumerate()
+            .map(|(i, chunk)| {
+                EvlfTrackHeader::from_bytes(chunk).ok_or_else(|| {
+                    VideoEditorError::conversion(format!("Corrupt EVLF track header {i}"))
+                })
+            })
+            .collect()
+    }
+}
+
+impl Source {
+    /// Bytes `offset..offset + len` of the file at `path`, `file_len`
+    /// bytes long.
+    fn read(
+        &self, path: &Path, file_len: u64, offset: u64, len: usize,
+    ) -> VideoEditorResult<Cow<'_, [u8]>> {
+        if offset.checked_add(len as u64).is_none_or(|end| end > file_len) {
+            return Err(VideoEditorError::conversion(format!(
+                "EVLF data at {offset}+{len} is out of bounds"
+            )));
+        }
+        match self {
+            Source::Mapped(mapping) => {
+                let start = offset as usize;
+                Ok(Cow::Borrowed(&mapping.bytes()[start..start + len]))
+            },
+            Source::Buffered(file) => {
+                let mut file = file.lock().unwrap_or_else(PoisonError::into_inner);
+                let mut bytes = vec![0; len];
+                file.seek(SeekFrom::Start(offset))
+                    .and_then(|_| file.read_exact(&mut bytes))
+                    .map_err(|e| VideoEditorError::io(path, e))?;
+                Ok(Cow::Owned(bytes))
+            },
+        }
+    }
+}
+
+impl std::fmt::Debug for EvlfReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EvlfReader")
+            .field("path", &self.path)
+            .field("header", &self.header)
+            .field("tracks", &self.tracks.len())
+            .field("mapped", &self.is_mapped())
+            .finish()
+    }
+}
+
+/// Read-only file mappings through the platform `mmap`.
+///
+/// Only built where the protection and flag values below are the ones the
+/// C library uses and `off_t` is 64 bits.
+#[cfg(all(
+    target_pointer_width = "64",
+    any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "freebsd")
+))]
+mod mmap {
+    use std::{
+        ffi::{c_int, c_void},
+        fs::File,
+        io,
+        os::fd::AsRawFd,
+        ptr,
+    };
+
+    /// Whether files can be mapped on this platform.
+    pub(super) const SUPPORTED: bool = true;
+
+    const PROT_READ: c_int = 1;
+    const MAP_PRIVATE: c_int = 2;
+
+    unsafe extern "C" {
+        fn mmap(
+            addr: *mut c_void, len: usize, prot: c_int, flags: c_int, fd: c_int, offset: i64,
+        ) -> *mut c_void;
+        fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    }
+
+    /// A whole file mapped read-only.
+    pub(super) struct Mapping {
+        ptr: *mut c_void,
+        len: usize,
+    }
+
+    // SAFETY: the mapping is read-only and owned, so it can be shared and
+    // moved like a `Box<[u8]>`.
+    unsafe impl Send for Mapping {}
+    unsafe impl Sync for Mapping {}
+
+    impl Mapping {
+        /// Maps the first `len` bytes of `file`.
+        ///
+        /// # Safety
+        ///
+        /// The file must not be modified or truncated while the mapping
+        /// is alive.
+        pub(super) unsafe fn new(file: &File, len: u64) -> io::Result<Self> {
+            let len = usize::try_from(len).map_err(|_| io::ErrorKind::FileTooLarge)?;
+            // Zero-length mappings are rejected by the OS
+            if len == 0 {
+                return Err(io::ErrorKind::InvalidInput.into());
+            }
+            // SAFETY: a fresh private read-only mapping of an open file
+            // descriptor; the result is checked before use, and the caller
+            // keeps the file unchanged while it is alive.
+            let ptr =
+                unsafe { mmap(ptr::null_mut(), len, PROT_READ, MAP_PRIVATE, file.as_raw_fd(), 0) };
+            if ptr as isize == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self { ptr, len })
+        }
+
+        /// Mapped bytes.
+        pub(super) fn bytes(&self) -> &[u8] {
+            // SAFETY: `ptr` points to `len` readable bytes until `drop`.
+            unsafe { std::slice::from_raw_parts(self.ptr.cast(), self.len) }
+        }
+    }
+
+    impl Drop for Mapping {
+        fn drop(&mut self) {
+            // SAFETY: unmaps exactly the region mapped in `new`.
+            unsafe {
+                munmap(self.ptr, self.len);
+            }
+        }
+    }
+}
+
+/// Stand-in on platforms without `mmap`; mapping always fails.
+#[cfg(not(all(
+    target_pointer_width = "64",
+    any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "freebsd")
+)))]
+mod mmap {
+    use std::{convert::Infallible, fs::File, io};
+
+    pub(super) const SUPPORTED: bool = false;
+
+    pub(super) struct Mapping(Infallible);
+
+    impl Mapping {
+        pub(super) unsafe fn new(_file: &File, _len: u64) -> io::Result<Self> {
+            Err(io::ErrorKind::Unsupported.into())
+        }
+
+        pub(super) fn bytes(&self) -> &[u8] {
+            match self.0 {}
+        }
+    }
+}
+
+#[cfg(all(test, feature = "full-tests"))]
+mod tests {
+    use super::*;
+    use crate::evlf_types::{EVLF_VERSION, EvlfTrackType, FrameIndexBuilder, FrameType};
+
+    /// Writes an EVLF file with two tracks and `frames` one-byte-valued
+    /// payloads at 25 fps, a keyframe every fourth frame, returning its
+    /// path.
+    fn write_evlf(name: &str, frames: u64, version: u32, keyframes_only: bool) -> PathBuf {
+        let mut header = EvlfHeader::new(4, 2, 25, 1);
+        header.version = version;
+        header.track_count = 2;
+        header.frame_count = frames;
+
+        let mut bytes = vec![0; EVLF_HEADER_SIZE];
+        bytes.extend(EvlfTrackHeader::video(1, "Picture").to_bytes());
+        bytes.extend(EvlfTrackHeader::audio(2, "Dialogue").to_bytes());
+        let mut index = if keyframes_only {
+            FrameIndexBuilder::keyframes_only(bytes.len() as u64)
+        } else {
+            FrameIndexBuilder::new(bytes.len() as u64)
+        };
+        for frame in 0..frames {
+            let frame_type =
+                if frame % 4 == 0 { FrameType::Keyframe } else { FrameType::Predictive };
+            index.push(frame * 40, frame_type, 32);
+            bytes.extend(vec![frame as u8; 32]);
+        }
+        header.index_offset = index.data_end();
+        bytes.extend(index.to_bytes());
+        bytes[..EVLF_HEADER_SIZE].copy_from_slice(&header.to_bytes());
+
+        let path = std::env::temp_dir().join(format!("evep_{name}_{}.evlf", std::process::id()));
+        std::fs::write(&path, bytes).expect("test assertion");
+        path
+    }
+
+    #[test]
+    fn test_reader_serves_frames_lazily() {
+        let path = write_evlf("reader", 5, EVLF_VERSION, false);
+        // SAFETY: the test file is not changed while the reader is open.
+        let mapped = unsafe { EvlfReader::open_mapped(&path) }.expect("test assertion");
+        assert_eq!(mapped.is_mapped(), mmap::SUPPORTED);
+        let buffered = EvlfReader::open(&path).expect("test assertion");
+        assert!(!buffered.is_mapped());
+        for reader in [mapped, buffered] {
+            assert_eq!(reader.frame_count(), 5);
+            assert_eq!(reader.tracks()[0].name, "Picture");
+            assert_eq!(reader.tracks()[1].track_type, EvlfTrackType::Audio);
+
+            let entry = reader.frame_entry(3).expect("test assertion");
+            assert_eq!((entry.frame_number, entry.pts_ms), (3, 120));
+            let data = reader.frame_data(3).expect("test assertion");
+            assert_eq!(&*data, &[3; 32]);
+            assert_eq!(matches!(data, Cow::Borrowed(_)), reader.is_mapped());
+            assert!(reader.frame_data(5).is_err());
+        }
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_seek_to_nearest_keyframe() {
+        for keyframes_only in [false, true] {
+            let path = write_evlf("reader_seek", 10, EVLF_VERSION, keyframes_only);
+            let reader = EvlfReader::open(&path).expect("test assertion");
+            assert_eq!(reader.has_keyframe_index(), keyframes_only);
+            assert_eq!(reader.frame_count(), 10);
+
+            let seek = |ms| {
+                reader
+                    .seek_to_nearest_keyframe(TimePosition::from_ms(ms))
+                    .expect("test assertion")
+                    .map(|entry| entry.frame_number)
+            };
+            assert_eq!(seek(0), Some(0));
+            assert_eq!(seek(250), Some(4));
+            assert_eq!(seek(320), Some(8));
+            assert_eq!(seek(10_000), Some(8));
+
+            // Payloads of indexed frames are still addressable
+            assert_eq!(&*reader.frame_data(8).expect("test assertion"), &[8; 32]);
+            assert_eq!(reader.frame_entry(5).is_ok(), !keyframes_only);
+            std::fs::remove_file(path).ok();
+        }
+    }
+
+    #[test]
+    fn test_reader_validates_header() {
+        let path = write_evlf("reader_version", 1, 0x0002_0000, false);
+        let err = EvlfReader::open(&path).expect_err("test assertion");
+        assert!(matches!(err, VideoEditorError::UnsupportedFormat(_)), "{err}");
+        std::fs::remove_file(path).ok();
+
+        // Minor revisions stay readable
+        let path = write_evlf("reader_minor", 1, EVLF_VERSION + 0x0100, false);
+        assert!(EvlfReader::open(&path).is_ok());
+
+        // An index running past the end of the file is rejected up front
+        let mut bytes = std::fs::read(&path).expect("test assertion");
+        bytes.truncate(bytes.len() - 1);
+        std::fs::write(&path, &bytes).expect("test assertion");
+        assert!(EvlfReader::open(&path).is_err());
+
+        bytes[0] ^= 0xFF;
+        std::fs::write(&path, &bytes).expect("test assertion");
+        assert!(EvlfReader::open(&path).is_err());
+
+        // Empty files can't be mapped and are read buffered, failing on
+        // the header rather than the mapping
+        std::fs::write(&path, b"").expect("test assertion");
+        // SAFETY: the test file is not changed while the reader is open.
+        let err = unsafe { EvlfReader::open_mapped(&path) }.expect_err("test assertion");
+        assert!(matches!(err, VideoEditorError::Conversion(_)), "{err}");
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_import_reads_evlf_headers() {
+        let path = write_evlf("reader_import", 5, EVLF_VERSION, false);
+        let mut assets = crate::AssetLibrary::new();
+        let id = assets.import_video(&path.to_string_lossy()).expect("test assertion");
+        let clip = assets.video_clip(id).expect("test assertion");
+        assert_eq!((clip.resolution.width, clip.resolution.height), (4, 2));
+        assert_eq!((clip.frame_rate.numerator, clip.frame_count), (25, 5));
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -19,6 +19,21 @@ pub const EVLF_TRAILER_MAGIC: u32 = 0x464C5645;
 /// Header size in bytes.
 pub const EVLF_HEADER_SIZE: usize = 64;
 
+/// Track header size in bytes.
+pub const EVLF_TRACK_HEADER_SIZE: usize = 96;
+
+/// Frame index section header size in bytes.
+pub const EVLF_INDEX_HEADER_SIZE: usize = 16;
+
+/// Frame index entry size in bytes.
+pub const EVLF_INDEX_ENTRY_SIZE: usize = 48;
+
+/// Frame index flag: only keyframes are indexed.
+pub const EVLF_INDEX_KEYFRAMES_ONLY: u32 = 1 << 0;
+
+/// Bytes reserved for a track name, NUL-padded.
+const TRACK_NAME_SIZE: usize = 64;
+
 /// EVLF container header (64 bytes).
 #[derive(Debug, Clone, Copy)]
 #[repr(C)]
@@ -73,6 +88,14 @@ impl EvlfHeader {
         self.magic == EVLF_MAGIC
     }
 
+    /// Whether a reader of [`EVLF_VERSION`] understands this file.
+    ///
+    /// Minor and patch revisions only add fields at the end of sections,
+    /// so only the major version has to match.
+    pub fn is_supported_version(&self) -> bool {
+        self.version >> 16 == EVLF_VERSION >> 16
+    }
+
     /// Converts to bytes for writing.
     pub fn to_bytes(&self) -> [u8; EVLF_HEADER_SIZE] {
         let mut bytes = [0u8; EVLF_HEADER_SIZE];
@@ -175,6 +198,8 @@ impl EvlfFlags {
     pub const STEREOSCOPIC_3D: u32 = 1 << 6;
     /// Contains AI annotations.
     pub const AI_ANNOTATED: u32 = 1 << 7;
+    /// Contains source and per-frame checksums.
+    pub const HAS_CHECKSUMS: u32 = 1 << 8;
 
     /// Creates new flags.
     pub fn new() -> Self {
@@ -224,6 +249,25 @@ pub enum EvlfTrackType {
     Metadata    = 255,
 }
 
+impl EvlfTrackType {
+    /// Parses a stored track type.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            0 => Self::Video,
+            1 => Self::Audio,
+            2 => Self::Text,
+            3 => Self::Effect,
+            4 => Self::Geometry3D,
+            5 => Self::Vector,
+            6 => Self::Particles,
+            7 => Self::AIContent,
+            8 => Self::Interactive,
+            255 => Self::Metadata,
+            _ => return None,
+        })
+    }
+}
+
 /// Track flags.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct TrackFlags(pub u8);
@@ -291,6 +335,35 @@ pub enum BlendMode {
     Subtract   = 17,
 }
 
+impl BlendMode {
+    /// All blend modes, in stored order.
+    const ALL: [Self; 18] = [
+        Self::Normal,
+        Self::Multiply,
+        Self::Screen,
+        Self::Overlay,
+        Self::Darken,
+        Self::Lighten,
+        Self::ColorDodge,
+        Self::ColorBurn,
+        Self::HardLight,
+        Self::SoftLight,
+        Self::Difference,
+        Self::Exclusion,
+        Self::Hue,
+        Self::Saturation,
+        Self::Color,
+        Self::Luminosity,
+        Self::Add,
+        Self::Subtract,
+    ];
+
+    /// Parses a stored blend mode.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(value)).copied()
+    }
+}
+
 /// Track header (96 bytes).
 #[derive(Debug, Clone)]
 pub struct EvlfTrackHeader {
@@ -333,6 +406,29 @@ impl EvlfTrackHeader {
         }
     }
 
+    /// Creates a new image layer header (RGBA8).
+    pub fn image(track_id: u32, name: impl Into<String>) -> Self {
+        Self { codec: 0x72676261, ..Self::video(track_id, name) } // "rgba"
+    }
+
+    /// Creates a new vector layer header.
+    pub fn vector(track_id: u32, name: impl Into<String>) -> Self {
+        Self {
+            track_type: EvlfTrackType::Vector,
+            codec: 0x76656374, // "vect"
+            ..Self::video(track_id, name)
+        }
+    }
+
+    /// Creates a new 3D geometry layer header.
+    pub fn geometry(track_id: u32, name: impl Into<String>) -> Self {
+        Self {
+            track_type: EvlfTrackType::Geometry3D,
+            codec: 0x676c7466, // "gltf"
+            ..Self::video(track_id, name)
+        }
+    }
+
     /// Creates a new audio track header.
     pub fn audio(track_id: u32, name: impl Into<String>) -> Self {
         Self {
@@ -348,6 +444,49 @@ impl EvlfTrackHeader {
             data_size: 0,
         }
     }
+
+    /// Converts to bytes for writing.
+    ///
+    /// Names longer than 64 bytes are cut at a character boundary.
+    pub fn to_bytes(&self) -> [u8; EVLF_TRACK_HEADER_SIZE] {
+        let mut bytes = [0u8; EVLF_TRACK_HEADER_SIZE];
+        bytes[0..4].copy_from_slice(&self.track_id.to_le_bytes());
+        bytes[4] = self.track_type as u8;
+        bytes[5] = self.flags.0;
+        bytes[6] = self.blend_mode as u8;
+        bytes[7] = self.opacity;
+        bytes[8..12].copy_from_slice(&self.codec.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.z_order.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.data_offset.to_le_bytes());
+        bytes[24..32].copy_from_slice(&self.data_size.to_le_bytes());
+        let mut len = self.name.len().min(TRACK_NAME_SIZE);
+        while !self.name.is_char_boundary(len) {
+            len -= 1;
+        }
+        bytes[32..32 + len].copy_from_slice(&self.name.as_bytes()[..len]);
+        bytes
+    }
+
+    /// Parses a track header from bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..EVLF_TRACK_HEADER_SIZE)?;
+        let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap_or_default());
+        let u64_at = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap_or_default());
+        let name = &bytes[32..];
+        let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(name.len())];
+        Some(Self {
+            track_id:    u32_at(0),
+            track_type:  EvlfTrackType::from_u8(bytes[4])?,
+            flags:       TrackFlags(bytes[5]),
+            name:        String::from_utf8(name.to_vec()).ok()?,
+            codec:       u32_at(8),
+            z_order:     u32_at(12),
+            blend_mode:  BlendMode::from_u8(bytes[6])?,
+            opacity:     bytes[7],
+            data_offset: u64_at(16),
+            data_size:   u64_at(24),
+        })
+    }
 }
 
 /// Frame type.
@@ -367,6 +506,20 @@ pub enum FrameType {
     MergePoint    = 4,
 }
 
+impl FrameType {
+    /// Parses a stored frame type.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            0 => Self::Keyframe,
+            1 => Self::Predictive,
+            2 => Self::Bidirectional,
+            3 => Self::BranchPoint,
+            4 => Self::MergePoint,
+            _ => return None,
+        })
+    }
+}
+
 /// Frame index entry (48 bytes).
 #[derive(Debug, Clone, Copy)]
 pub struct FrameIndexEntry {
@@ -402,6 +555,195 @@ impl FrameIndexEntry {
             metadata_offset: 0,
         }
     }
+
+    /// Whether decoding can start at this frame.
+    pub fn is_keyframe(&self) -> bool {
+        self.frame_type == FrameType::Keyframe
+    }
+
+    /// Converts to bytes for writing.
+    ///
+    /// The decode timestamp is stored as an offset from the presentation
+    /// timestamp, clamped to ±24 days.
+    pub fn to_bytes(&self) -> [u8; EVLF_INDEX_ENTRY_SIZE] {
+        let dts_offset = (self.dts_ms as i64 - self.pts_ms as i64)
+            .clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32;
+        let mut bytes = [0u8; EVLF_INDEX_ENTRY_SIZE];
+        bytes[0..8].copy_from_slice(&self.frame_number.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.pts_ms.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.data_offset.to_le_bytes());
+        bytes[24..32].copy_from_slice(&self.metadata_offset.to_le_bytes());
+        bytes[32..36].copy_from_slice(&self.data_size.to_le_bytes());
+        bytes[36..40].copy_from_slice(&self.branch_id.to_le_bytes());
+        bytes[40..44].copy_from_slice(&dts_offset.to_le_bytes());
+        bytes[44] = self.frame_type as u8;
+        bytes
+    }
+
+    /// Parses an index entry from bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..EVLF_INDEX_ENTRY_SIZE)?;
+        let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap_or_default());
+        let u64_at = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap_or_default());
+        let pts_ms = u64_at(8);
+        Some(Self {
+            frame_number: u64_at(0),
+            pts_ms,
+            dts_ms: pts_ms.saturating_add_signed(i64::from(u32_at(40) as i32)),
+            frame_type: FrameType::from_u8(bytes[44])?,
+            data_offset: u64_at(16),
+            data_size: u32_at(32),
+            branch_id: u32_at(36),
+            metadata_offset: u64_at(24),
+        })
+    }
+}
+
+/// Builds the frame index of a file as its frames are written.
+///
+/// Payloads are laid out back to back from the data offset given to the
+/// builder. A keyframe-only index records just the seek points, which
+/// keeps the index of a long file small enough to search instantly.
+///
+/// The index section is a 16-byte header (u64 entry count, u32 flags,
+/// u32 reserved) followed by the entries in presentation order.
+#[derive(Debug, Clone, Default)]
+pub struct FrameIndexBuilder {
+    entries:        Vec<FrameIndexEntry>,
+    keyframes_only: bool,
+    frame_count:    u64,
+    next_offset:    u64,
+}
+
+impl FrameIndexBuilder {
+    /// Creates a builder indexing every frame, with payloads starting at
+    /// `data_offset`.
+    pub fn new(data_offset: u64) -> Self {
+        Self { next_offset: data_offset, ..Self::default() }
+    }
+
+    /// Creates a builder indexing only keyframes.
+    pub fn keyframes_only(data_offset: u64) -> Self {
+        Self { keyframes_only: true, ..Self::new(data_offset) }
+    }
+
+    /// Whether only keyframes are indexed.
+    pub fn is_keyframes_only(&self) -> bool {
+        self.keyframes_only
+    }
+
+    /// Records the next frame, returning the offset its payload is
+    /// written at.
+    pub fn push(&mut self, pts_ms: u64, frame_type: FrameType, data_size: u32) -> u64 {
+        let data_offset = self.next_offset;
+        let entry = FrameIndexEntry {
+            frame_type,
+            ..FrameIndexEntry::keyframe(self.frame_count, pts_ms, data_offset, data_size)
+        };
+        if !self.keyframes_only || entry.is_keyframe() {
+            self.entries.push(entry);
+        }
+        self.frame_count += 1;
+        self.next_offset += u64::from(data_size);
+        data_offset
+    }
+
+    /// Frames recorded so far, indexed or not.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Offset just past the last payload, where the index can be written.
+    pub fn data_end(&self) -> u64 {
+        self.next_offset
+    }
+
+    /// Indexed entries.
+    pub fn entries(&self) -> &[FrameIndexEntry] {
+        &self.entries
+    }
+
+    /// Takes the indexed entries.
+    pub fn into_entries(self) -> Vec<FrameIndexEntry> {
+        self.entries
+    }
+
+    /// Converts the index section to bytes for writing.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let flags = if self.keyframes_only { EVLF_INDEX_KEYFRAMES_ONLY } else { 0 };
+        let mut bytes =
+            Vec::with_capacity(EVLF_INDEX_HEADER_SIZE + self.entries.len() * EVLF_INDEX_ENTRY_SIZE);
+        bytes.extend_from_slice(&(self.entries.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&flags.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        for entry in &self.entries {
+            bytes.extend_from_slice(&entry.to_bytes());
+        }
+        bytes
+    }
+}
+
+/// Checksum section magic: "EVCK" in big-endian.
+pub const EVLF_CHECKSUM_MAGIC: u32 = 0x4556434B;
+
+/// Source integrity and per-frame checksums for archival verification.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EvlfChecksums {
+    /// Source path at conversion time.
+    pub source_path:   String,
+    /// Source size in bytes.
+    pub source_size:   u64,
+    /// xxHash64 of the source file.
+    pub source_xxh64:  u64,
+    /// SHA-256 of the source file.
+    pub source_sha256: [u8; 32],
+    /// xxHash64 of each output frame, in frame order.
+    pub frame_hashes:  Vec<u64>,
+}
+
+impl EvlfChecksums {
+    /// Converts to bytes for writing.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let path = self.source_path.as_bytes();
+        let mut bytes = Vec::with_capacity(64 + path.len() + self.frame_hashes.len() * 8);
+        bytes.extend_from_slice(&EVLF_CHECKSUM_MAGIC.to_le_bytes());
+        bytes.extend_from_slice(&(path.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(path);
+        bytes.extend_from_slice(&self.source_size.to_le_bytes());
+        bytes.extend_from_slice(&self.source_xxh64.to_le_bytes());
+        bytes.extend_from_slice(&self.source_sha256);
+        bytes.extend_from_slice(&(self.frame_hashes.len() as u64).to_le_bytes());
+        for hash in &self.frame_hashes {
+            bytes.extend_from_slice(&hash.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Parses a checksum section from bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut offset = 0usize;
+        let mut take = |len: usize| {
+            let field = bytes.get(offset..offset.checked_add(len)?)?;
+            offset += len;
+            Some(field)
+        };
+        let read_u32 = |b: &[u8]| u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+        let read_u64 =
+            |b: &[u8]| u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]);
+
+        if read_u32(take(4)?) != EVLF_CHECKSUM_MAGIC {
+            return None;
+        }
+        let path_len = read_u32(take(4)?) as usize;
+        let source_path = String::from_utf8(take(path_len)?.to_vec()).ok()?;
+        let source_size = read_u64(take(8)?);
+        let source_xxh64 = read_u64(take(8)?);
+        let source_sha256 = take(32)?.try_into().ok()?;
+        let count = usize::try_from(read_u64(take(8)?)).ok()?;
+        let frame_hashes = take(count.checked_mul(8)?)?.chunks_exact(8).map(read_u64).collect();
+
+        Some(Self { source_path, source_size, source_xxh64, source_sha256, frame_hashes })
+    }
 }
 
 /// Branch type.
@@ -0,0 +1,232 @@
+//! Writing EVLF containers.
+//!
+//! Frames are streamed to disk as they arrive, in the layout
+//! [`EvlfReader`](crate::EvlfReader) reads: the header and track headers
+//! are reserved up front, payloads follow back to back, and
+//! [`EvlfWriter::finish`] appends the frame index and fills in the header
+//! once the frame count and offsets are known.
+//!
+//! A writer given source checksums with [`EvlfWriter::with_checksums`]
+//! hashes every payload it writes and stores the checksum section between
+//! the payloads and the index, pointed at by the header's metadata offset.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    checksum,
+    errors::{VideoEditorError, VideoEditorResult},
+    evlf_types::{
+        EVLF_HEADER_SIZE, EVLF_TRACK_HEADER_SIZE, EvlfChecksums, EvlfFlags, EvlfHeader,
+        EvlfTrackHeader, FrameIndexBuilder, FrameType,
+    },
+};
+
+/// Streams frames into a new EVLF file.
+pub struct EvlfWriter {
+    path:      PathBuf,
+    file:      BufWriter<File>,
+    header:    EvlfHeader,
+    index:     FrameIndexBuilder,
+    /// Checksum section, with the hashes of the frames written so far.
+    checksums: Option<EvlfChecksums>,
+}
+
+impl EvlfWriter {
+    /// Create the file at `path` with `header` and `tracks`, indexing only
+    /// keyframes when `keyframes_only` is set.
+    ///
+    /// The header's track count, frame count, duration and index offset
+    /// are filled in by the writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written.
+    pub fn create(
+        path: impl AsRef<Path>, mut header: EvlfHeader, tracks: &[EvlfTrackHeader],
+        keyframes_only: bool,
+    ) -> VideoEditorResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        header.track_count = u32::try_from(tracks.len())
+            .map_err(|_| VideoEditorError::conversion("Too many EVLF tracks"))?;
+        let file = File::create(&path).map_err(|e| VideoEditorError::io(&path, e))?;
+        let mut file = BufWriter::new(file);
+        // The header is written again by `finish`, once offsets are known
+        std::iter::once(header.to_bytes().to_vec())
+            .chain(tracks.iter().map(|track| track.to_bytes().to_vec()))
+            .try_for_each(|bytes| file.write_all(&bytes))
+            .map_err(|e| VideoEditorError::io(&path, e))?;
+
+        let data_offset = (EVLF_HEADER_SIZE + tracks.len() * EVLF_TRACK_HEADER_SIZE) as u64;
+        let index = if keyframes_only {
+            FrameIndexBuilder::keyframes_only(data_offset)
+        } else {
+            FrameIndexBuilder::new(data_offset)
+        };
+        Ok(Self { path, file, header, index, checksums: None })
+    }
+
+    /// Record `checksums` in the file, appending the xxHash64 of every
+    /// frame written to its frame hashes.
+    #[must_use]
+    pub fn with_checksums(mut self, checksums: EvlfChecksums) -> Self {
+        self.checksums = Some(checksums);
+        self
+    }
+
+    /// Checksums recorded so far, if any.
+    #[must_use]
+    pub fn checksums(&self) -> Option<&EvlfChecksums> {
+        self.checksums.as_ref()
+    }
+
+    /// File path.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Frames written so far.
+    #[must_use]
+    pub fn frame_count(&self) -> u64 {
+        self.index.frame_count()
+    }
+
+    /// Append the payload of the next frame, presented at `pts_ms`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload is larger than an index entry can
+    /// describe or writing fails.
+    pub fn write_frame(
+        &mut self, pts_ms: u64, frame_type: FrameType, data: &[u8],
+    ) -> VideoEditorResult<()> {
+        let size = u32::try_from(data.len()).map_err(|_| {
+            VideoEditorError::conversion(format!("Frame {} is too large", self.frame_count()))
+        })?;
+        self.file.write_all(data).map_err(|e| VideoEditorError::io(&self.path, e))?;
+        self.index.push(pts_ms, frame_type, size);
+        if let Some(checksums) = &mut self.checksums {
+            checksums.frame_hashes.push(checksum::xxh64(data));
+        }
+
+        // Duration runs to the end of the last frame
+        let frame_ms = if self.header.frame_rate_num > 0 {
+            1000 * u64::from(self.header.frame_rate_den) / u64::from(self.header.frame_rate_num)
+        } else {
+            0
+        };
+        self.header.duration_ms = self.header.duration_ms.max(pts_ms + frame_ms);
+        Ok(())
+    }
+
+    /// Write the checksum section, the frame index and the final header,
+    /// returning the index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn finish(mut self) -> VideoEditorResult<FrameIndexBuilder> {
+        self.header.frame_count = self.index.frame_count();
+        let mut bytes = Vec::new();
+        if let Some(checksums) = &self.checksums {
+            let mut flags = EvlfFlags(self.header.flags);
+            flags.set(EvlfFlags::HAS_CHECKSUMS);
+            self.header.flags = flags.0;
+            self.header.metadata_offset = self.index.data_end();
+            bytes = checksums.to_bytes();
+        }
+        self.header.index_offset = self.index.data_end() + bytes.len() as u64;
+        bytes.extend(self.index.to_bytes());
+        self.file
+            .write_all(&bytes)
+            .and_then(|()| self.file.seek(SeekFrom::Start(0)))
+            .and_then(|_| self.file.write_all(&self.header.to_bytes()))
+            .and_then(|()| self.file.flush())
+            .map_err(|e| VideoEditorError::io(&self.path, e))?;
+        Ok(self.index)
+    }
+}
+
+impl std::fmt::Debug for EvlfWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EvlfWriter")
+            .field("path", &self.path)
+            .field("header", &self.header)
+            .field("frames", &self.frame_count())
+            .finish()
+    }
+}
+
+#[cfg(all(test, feature = "full-tests"))]
+mod tests {
+    use super::*;
+    use crate::{EvlfReader, types::TimePosition};
+
+    #[test]
+    fn test_written_file_reads_back() {
+        let path = std::env::temp_dir().join(format!("evep_writer_{}.evlf", std::process::id()));
+        let tracks = [EvlfTrackHeader::image(1, "Picture")];
+        let mut writer = EvlfWriter::create(&path, EvlfHeader::new(2, 1, 25, 1), &tracks, true)
+            .expect("test assertion");
+        for frame in 0..6u8 {
+            let frame_type =
+                if frame % 3 == 0 { FrameType::Keyframe } else { FrameType::Predictive };
+            let payload = vec![frame; 8 + usize::from(frame)];
+            writer
+                .write_frame(u64::from(frame) * 40, frame_type, &payload)
+                .expect("test assertion");
+        }
+        assert_eq!(writer.frame_count(), 6);
+        let index = writer.finish().expect("test assertion");
+        assert_eq!(index.entries().len(), 2);
+
+        let reader = EvlfReader::open(&path).expect("test assertion");
+        assert_eq!(reader.frame_count(), 6);
+        assert_eq!(reader.header().duration_ms, 240);
+        assert_eq!(reader.tracks()[0].name, "Picture");
+        assert!(reader.has_keyframe_index());
+        assert_eq!(reader.frame_data(3).expect("test assertion").as_ref(), &[3; 11]);
+        assert!(reader.frame_data(4).is_err());
+        let keyframe = reader.seek_to_nearest_keyframe(TimePosition::from_ms(200));
+        assert_eq!(keyframe.expect("test assertion").map(|entry| entry.frame_number), Some(3));
+
+        let missing = std::env::temp_dir().join("evep_missing_dir").join("out.evlf");
+        let header = EvlfHeader::new(2, 1, 25, 1);
+        assert!(EvlfWriter::create(missing, header, &tracks, false).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_checksums_are_stored_before_the_index() {
+        let path = std::env::temp_dir().join(format!("evep_writer_ck_{}.evlf", std::process::id()));
+        let tracks = [EvlfTrackHeader::image(1, "Picture")];
+        let source = EvlfChecksums { source_path: "take.mov".into(), ..Default::default() };
+        let mut writer = EvlfWriter::create(&path, EvlfHeader::new(2, 1, 25, 1), &tracks, false)
+            .expect("test assertion")
+            .with_checksums(source);
+        for frame in [b"ab", b"cd"] {
+            writer.write_frame(0, FrameType::Keyframe, frame).expect("test assertion");
+        }
+        writer.finish().expect("test assertion");
+
+        let reader = EvlfReader::open(&path).expect("test assertion");
+        let checksums = reader.checksums().expect("test assertion").expect("test assertion");
+        assert_eq!(checksums.source_path, "take.mov");
+        assert_eq!(checksums.frame_hashes, vec![checksum::xxh64(b"ab"), checksum::xxh64(b"cd")]);
+        assert_eq!(reader.frame_data(1).expect("test assertion").as_ref(), b"cd");
+        let entries: Vec<_> = reader.index_entries().map(|e| e.expect("test assertion")).collect();
+        assert_eq!(entries.len(), 2);
+
+        // Without checksums the file has no section
+        let writer = EvlfWriter::create(&path, EvlfHeader::new(2, 1, 25, 1), &tracks, false)
+            .expect("test assertion");
+        writer.finish().expect("test assertion");
+        let reader = EvlfReader::open(&path).expect("test assertion");
+        assert_eq!(reader.checksums().expect("test assertion"), None);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -9,9 +9,14 @@
 //! - Effects and transitions configuration
 //! - GPU rendering pipeline settings
 //! - Real-time preview streaming (60fps)
+//! - Live track and master meters published at the streaming cadence
 //! - Asset library browser
 
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
 use essentia_traits::plugin_contracts::{
     ConfigField, ConfigSchema, EditorAction, EditorPresentable, FlexForgeCapability,
@@ -19,6 +24,8 @@ use essentia_traits::plugin_contracts::{
     UiConfigurable,
 };
 
+use crate::implementation::CommandRegistry;
+
 // ============================================================================
 // Configuration Types
 // ============================================================================
@@ -51,6 +58,8 @@ pub struct VideoEditorConfig {
     pub export_bitrate_mbps:   u32,
     /// Enable hardware encoding
     pub hardware_encoding:     bool,
+    /// Hardware encoder label ("auto" picks the best detected encoder)
+    pub hardware_encoder:      String,
     // AI settings
     /// Enable AI scene detection
     pub ai_scene_detection:    bool,
@@ -72,6 +81,7 @@ impl Default for VideoEditorConfig {
             export_codec:          String::from("h265"),
             export_bitrate_mbps:   50,
             hardware_encoding:     true,
+            hardware_encoder:      String::from("auto"),
             ai_scene_detection:    true,
             ai_color_grading:      false,
         }
@@ -82,17 +92,203 @@ impl Default for VideoEditorConfig {
 #[derive(Debug, Clone, Default)]
 pub struct VideoEditorMetrics {
     /// Current playback position (ms)
-    pub playback_position_ms: u64,
+    pub playback_position_ms:     u64,
     /// Total timeline duration (ms)
-    pub timeline_duration_ms: u64,
+    pub timeline_duration_ms:     u64,
     /// GPU memory usage (bytes)
-    pub gpu_memory_bytes:     u64,
+    pub gpu_memory_bytes:         u64,
     /// Render FPS
-    pub render_fps:           f32,
+    pub render_fps:               f32,
+    /// Dropped frames since playback started
+    pub dropped_frames:           u64,
     /// Active tracks count
-    pub active_tracks:        u32,
+    pub active_tracks:            u32,
     /// Processing status
-    pub processing:           bool,
+    pub processing:               bool,
+    /// Master momentary loudness (LUFS)
+    pub loudness_momentary_lufs:  f32,
+    /// Master short-term loudness (LUFS)
+    pub loudness_short_term_lufs: f32,
+    /// Master integrated loudness (LUFS)
+    pub loudness_integrated_lufs: f32,
+    /// Master true peak (dBTP)
+    pub true_peak_dbtp:           f32,
+    /// Meter levels per mixer track, by track ID
+    pub track_meters:             Vec<(u64, MeterSnapshot)>,
+    /// Master bus meter levels
+    pub master_meter:             MeterSnapshot,
+    /// Preview frame cache hit ratio (0.0 to 1.0)
+    pub cache_hit_ratio:          f64,
+}
+
+/// Level meter reading of a track or bus for live panel meters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeterSnapshot {
+    /// Highest channel peak (dBFS)
+    pub peak_dbfs:       f32,
+    /// Highest channel RMS (dBFS)
+    pub rms_dbfs:        f32,
+    /// Short-term loudness (LUFS)
+    pub short_term_lufs: f32,
+    /// Whether the signal clipped since the meters were reset
+    pub clipping:        bool,
+}
+
+impl MeterSnapshot {
+    /// Level shown for silence (dBFS and LUFS)
+    pub const FLOOR_DB: f32 = -70.0;
+
+    /// Converts a linear level to dBFS, floored at [`Self::FLOOR_DB`].
+    #[must_use]
+    pub fn to_dbfs(level: f32) -> f32 {
+        if level <= 0.0 {
+            return Self::FLOOR_DB;
+        }
+        (20.0 * level.log10()).max(Self::FLOOR_DB)
+    }
+}
+
+impl Default for MeterSnapshot {
+    fn default() -> Self {
+        Self {
+            peak_dbfs:       Self::FLOOR_DB,
+            rms_dbfs:        Self::FLOOR_DB,
+            short_term_lufs: Self::FLOOR_DB,
+            clipping:        false,
+        }
+    }
+}
+
+/// Fills in live metrics (meters, cache ratios, dropped frames) each
+/// streamed frame.
+pub type MetricsSource = Arc<dyn Fn(&mut VideoEditorMetrics) + Send + Sync>;
+
+/// Timestamped metrics sample kept for performance graphs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetricsSample {
+    /// Milliseconds since the integration was created
+    pub timestamp_ms:     u64,
+    /// Render FPS
+    pub render_fps:       f32,
+    /// GPU memory usage (bytes)
+    pub gpu_memory_bytes: u64,
+    /// Dropped frames since playback started
+    pub dropped_frames:   u64,
+}
+
+impl MetricsSample {
+    /// Returns the value of a metric.
+    #[must_use]
+    pub fn value(&self, kind: MetricKind) -> f64 {
+        match kind {
+            MetricKind::RenderFps => f64::from(self.render_fps),
+            MetricKind::GpuMemory => self.gpu_memory_bytes as f64,
+            MetricKind::DroppedFrames => self.dropped_frames as f64,
+        }
+    }
+}
+
+/// Metric tracked in the history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetricKind {
+    /// Render FPS
+    RenderFps,
+    /// GPU memory usage (bytes)
+    GpuMemory,
+    /// Dropped frames
+    DroppedFrames,
+}
+
+/// Fixed-capacity ring buffer of metrics samples.
+#[derive(Debug, Clone)]
+pub struct MetricsHistory {
+    /// Samples, oldest first
+    samples:  VecDeque<MetricsSample>,
+    /// Maximum number of samples kept
+    capacity: usize,
+}
+
+impl MetricsHistory {
+    /// Default capacity (five minutes at 10 samples per second)
+    pub const DEFAULT_CAPACITY: usize = 3000;
+
+    /// Creates an empty history.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self { samples: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    /// Appends a sample, dropping the oldest when full.
+    pub fn push(&mut self, sample: MetricsSample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Returns the number of samples.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns whether the history is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Returns the newest sample.
+    #[must_use]
+    pub fn latest(&self) -> Option<&MetricsSample> {
+        self.samples.back()
+    }
+
+    /// Returns samples from the last `seconds` before the newest one,
+    /// oldest first.
+    pub fn last_seconds(&self, seconds: f64) -> impl Iterator<Item = &MetricsSample> {
+        let newest = self.latest().map_or(0, |s| s.timestamp_ms);
+        let since = newest.saturating_sub((seconds.max(0.0) * 1000.0) as u64);
+        self.samples.iter().filter(move |s| s.timestamp_ms >= since)
+    }
+
+    /// Returns the values of a metric over the last `seconds`.
+    #[must_use]
+    pub fn values(&self, kind: MetricKind, seconds: f64) -> Vec<f64> {
+        self.last_seconds(seconds).map(|s| s.value(kind)).collect()
+    }
+
+    /// Returns the mean of a metric over the last `seconds`.
+    #[must_use]
+    pub fn average(&self, kind: MetricKind, seconds: f64) -> Option<f64> {
+        let values = self.values(kind, seconds);
+        (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+    }
+
+    /// Returns a percentile (0 to 100, nearest rank) of a metric over the
+    /// last `seconds`.
+    #[must_use]
+    pub fn percentile(&self, kind: MetricKind, seconds: f64, percentile: f64) -> Option<f64> {
+        let mut values = self.values(kind, seconds);
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * values.len() as f64).ceil() as usize;
+        Some(values[rank.saturating_sub(1).min(values.len() - 1)])
+    }
+
+    /// Removes all samples.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+impl Default for MetricsHistory {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
 }
 
 // ============================================================================
@@ -100,12 +296,15 @@ pub struct VideoEditorMetrics {
 // ============================================================================
 
 /// FlexForge integration for Video Editor plugin.
-#[derive(Debug)]
 pub struct VideoEditorFlexForge {
     /// Current configuration
     config:           Arc<Mutex<VideoEditorConfig>>,
     /// Current metrics
     metrics:          Arc<Mutex<VideoEditorMetrics>>,
+    /// Recent metrics for performance graphs
+    history:          Arc<Mutex<MetricsHistory>>,
+    /// Time base for metrics timestamps
+    created:          Instant,
     /// Streaming active flag
     stream_active:    bool,
     /// Current stream ID
@@ -116,6 +315,27 @@ pub struct VideoEditorFlexForge {
     current_project:  Option<String>,
     /// Project modified flag
     project_modified: bool,
+    /// Editor commands the toolbar is derived from
+    commands:         CommandRegistry,
+    /// Labels of the detected hardware encoders
+    hw_encoders:      Vec<String>,
+    /// Live metrics refreshed at the streaming cadence
+    metrics_source:   Option<MetricsSource>,
+}
+
+impl std::fmt::Debug for VideoEditorFlexForge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VideoEditorFlexForge")
+            .field("config", &self.config)
+            .field("metrics", &self.metrics)
+            .field("stream_active", &self.stream_active)
+            .field("stream_id", &self.stream_id)
+            .field("current_project", &self.current_project)
+            .field("project_modified", &self.project_modified)
+            .field("hw_encoders", &self.hw_encoders)
+            .field("metrics_source", &self.metrics_source.is_some())
+            .finish_non_exhaustive()
+    }
 }
 
 impl VideoEditorFlexForge {
@@ -125,14 +345,53 @@ impl VideoEditorFlexForge {
         Self {
             config:           Arc::new(Mutex::new(VideoEditorConfig::default())),
             metrics:          Arc::new(Mutex::new(VideoEditorMetrics::default())),
+            history:          Arc::new(Mutex::new(MetricsHistory::default())),
+            created:          Instant::now(),
             stream_active:    false,
             stream_id:        None,
             next_id:          1,
             current_project:  None,
             project_modified: false,
+            commands:         CommandRegistry::with_defaults(),
+            hw_encoders:      Vec::new(),
+            metrics_source:   None,
         }
     }
 
+    /// Returns the editor commands.
+    #[must_use]
+    pub fn commands(&self) -> &CommandRegistry {
+        &self.commands
+    }
+
+    /// Replaces the editor commands, e.g. with the plugin's registry after
+    /// the user remapped shortcuts.
+    pub fn set_commands(&mut self, commands: CommandRegistry) {
+        self.commands = commands;
+    }
+
+    /// Sets the detected hardware encoders offered in the export
+    /// settings, by label (e.g. `"nvenc"`).
+    ///
+    /// A selected encoder that is no longer detected resets to `"auto"`.
+    pub fn set_hardware_encoders(&mut self, labels: &[&str]) {
+        self.hw_encoders = labels.iter().map(|l| l.to_string()).collect();
+        if let Ok(mut config) = self.config.lock()
+            && !self.hardware_encoder_options().contains(&config.hardware_encoder)
+        {
+            config.hardware_encoder = String::from("auto");
+        }
+    }
+
+    /// Returns the hardware encoder choices: auto, software and the
+    /// detected encoders.
+    #[must_use]
+    pub fn hardware_encoder_options(&self) -> Vec<String> {
+        let mut options = vec![String::from("auto"), String::from("software")];
+        options.extend(self.hw_encoders.iter().cloned());
+        options
+    }
+
     /// Returns panel info with capabilities.
     #[must_use]
     pub fn panel_info(&self) -> FlexForgePanelInfo {
@@ -153,17 +412,66 @@ impl VideoEditorFlexForge {
 
     /// Updates video editor metrics.
     pub fn update_metrics(&mut self, metrics: VideoEditorMetrics) {
+        let timestamp_ms = self.created.elapsed().as_millis() as u64;
+        self.update_metrics_at(metrics, timestamp_ms);
+    }
+
+    /// Updates video editor metrics with an explicit timestamp (ms since
+    /// the integration was created) and records them in the history.
+    pub fn update_metrics_at(&mut self, metrics: VideoEditorMetrics, timestamp_ms: u64) {
+        if let Ok(mut history) = self.history.lock() {
+            history.push(MetricsSample {
+                timestamp_ms,
+                render_fps: metrics.render_fps,
+                gpu_memory_bytes: metrics.gpu_memory_bytes,
+                dropped_frames: metrics.dropped_frames,
+            });
+        }
         if let Ok(mut m) = self.metrics.lock() {
             *m = metrics;
         }
     }
 
+    /// Sets where live metrics come from. While streaming, the source
+    /// fills in track and master meters, cache hit ratios and dropped
+    /// frames for every streamed frame, so the panel draws live meters
+    /// from [`Self::metrics`] without reaching into the mixer or preview.
+    pub fn set_metrics_source(&mut self, source: MetricsSource) {
+        self.metrics_source = Some(source);
+    }
+
+    /// Returns the latest metrics.
+    #[must_use]
+    pub fn metrics(&self) -> VideoEditorMetrics {
+        self.metrics.lock().map(|m| m.clone()).unwrap_or_default()
+    }
+
+    /// Returns metrics samples from the last `seconds`, oldest first.
+    #[must_use]
+    pub fn metrics_history(&self, seconds: f64) -> Vec<MetricsSample> {
+        self.history.lock().map(|h| h.last_seconds(seconds).copied().collect()).unwrap_or_default()
+    }
+
+    /// Returns a percentile (0 to 100) of a metric over the last `seconds`.
+    #[must_use]
+    pub fn metric_percentile(
+        &self, kind: MetricKind, seconds: f64, percentile: f64,
+    ) -> Option<f64> {
+        self.history.lock().ok()?.percentile(kind, seconds, percentile)
+    }
+
     /// Returns current render FPS.
     #[must_use]
     pub fn render_fps(&self) -> f32 {
         self.metrics.lock().map(|m| m.render_fps).unwrap_or(0.0)
     }
 
+    /// Returns current master integrated loudness (LUFS).
+    #[must_use]
+    pub fn integrated_loudness(&self) -> f32 {
+        self.metrics.lock().map(|m| m.loudness_integrated_lufs).unwrap_or(0.0)
+    }
+
     fn next_stream_id(&mut self) -> u64 {
         let id = self.next_id;
         self.next_id = self.next_id.wrapping_add(1);
@@ -313,6 +621,15 @@ impl UiConfigurable for VideoEditorFlexForge {
                     .with_description("Use GPU hardware encoder")
                     .with_group("Export"),
             )
+            .with_field(
+                ConfigField::select(
+                    "hardware_encoder",
+                    "Hardware Encoder",
+                    self.hardware_encoder_options(),
+                )
+                .with_description("Detected encoder; auto falls back to software")
+                .with_group("Export"),
+            )
             // AI settings
             .with_field(
                 ConfigField::toggle("ai_scene_detection", "AI Scene Detection", true)
@@ -381,6 +698,13 @@ impl UiConfigurable for VideoEditorFlexForge {
                 config.hardware_encoding = value == "true";
                 Ok(())
             },
+            "hardware_encoder" => {
+                if !self.hardware_encoder_options().iter().any(|o| o == value) {
+                    return Err(format!("Hardware encoder not available: {value}"));
+                }
+                config.hardware_encoder = value.to_string();
+                Ok(())
+            },
             "ai_scene_detection" => {
                 config.ai_scene_detection = value == "true";
                 Ok(())
@@ -434,6 +758,10 @@ impl UiConfigurable for VideoEditorFlexForge {
                 String::from("hardware_encoding"),
                 config.hardware_encoding.to_string(),
             ),
+            (
+                String::from("hardware_encoder"),
+                config.hardware_encoder.clone(),
+            ),
             (
                 String::from("ai_scene_detection"),
                 config.ai_scene_detection.to_string(),
@@ -486,43 +814,7 @@ impl EditorPresentable for VideoEditorFlexForge {
     }
 
     fn get_toolbar_actions(&self) -> Vec<EditorAction> {
-        vec![
-            EditorAction {
-                id:       String::from("video_play"),
-                label:    String::from("Play/Pause"),
-                icon:     String::from("\u{E768}"),
-                shortcut: Some(String::from("Space")),
-                enabled:  self.current_project.is_some(),
-            },
-            EditorAction {
-                id:       String::from("video_cut"),
-                label:    String::from("Cut"),
-                icon:     String::from("\u{E8C6}"),
-                shortcut: Some(String::from("Ctrl+X")),
-                enabled:  self.current_project.is_some(),
-            },
-            EditorAction {
-                id:       String::from("video_add_track"),
-                label:    String::from("Add Track"),
-                icon:     String::from("\u{E710}"),
-                shortcut: Some(String::from("Ctrl+Shift+T")),
-                enabled:  self.current_project.is_some(),
-            },
-            EditorAction {
-                id:       String::from("video_effects"),
-                label:    String::from("Effects"),
-                icon:     String::from("\u{E7AC}"),
-                shortcut: Some(String::from("Ctrl+E")),
-                enabled:  self.current_project.is_some(),
-            },
-            EditorAction {
-                id:       String::from("video_export"),
-                label:    String::from("Export"),
-                icon:     String::from("\u{E898}"),
-                shortcut: Some(String::from("Ctrl+Shift+E")),
-                enabled:  self.current_project.is_some(),
-            },
-        ]
+        self.commands.toolbar_actions(self.current_project.is_some())
     }
 }
 
@@ -574,10 +866,24 @@ impl StreamingCapable for VideoEditorFlexForge {
         }
 
         // Update playback position
-        if let Ok(mut metrics) = self.metrics.lock() {
-            metrics.playback_position_ms += delta_ms as u64;
-            if metrics.playback_position_ms > metrics.timeline_duration_ms {
-                metrics.playback_position_ms = 0; // Loop
+        let Ok(mut metrics) = self.metrics.lock() else {
+            return true;
+        };
+        metrics.playback_position_ms += delta_ms as u64;
+        if metrics.playback_position_ms > metrics.timeline_duration_ms {
+            metrics.playback_position_ms = 0; // Loop
+        }
+
+        // Publish live meters at the streaming cadence
+        if let Some(source) = &self.metrics_source {
+            source(&mut metrics);
+            if let Ok(mut history) = self.history.lock() {
+                history.push(MetricsSample {
+                    timestamp_ms:     self.created.elapsed().as_millis() as u64,
+                    render_fps:       metrics.render_fps,
+                    gpu_memory_bytes: metrics.gpu_memory_bytes,
+                    dropped_frames:   metrics.dropped_frames,
+                });
             }
         }
 
@@ -639,4 +945,82 @@ mod tests {
         // Invalid preview quality
         assert!(integration.on_config_changed("preview_quality", "150").is_err());
     }
+
+    #[test]
+    fn test_hardware_encoder_config() {
+        let mut integration = VideoEditorFlexForge::new();
+        let schema = integration.config_schema();
+        assert!(schema.fields.iter().any(|f| f.key == "hardware_encoder"));
+
+        assert!(integration.on_config_changed("hardware_encoder", "nvenc").is_err());
+        integration.set_hardware_encoders(&["nvenc"]);
+        assert_eq!(integration.hardware_encoder_options(), ["auto", "software", "nvenc"]);
+        assert!(integration.on_config_changed("hardware_encoder", "nvenc").is_ok());
+
+        // The encoder went away (e.g. a driver update); back to auto
+        integration.set_hardware_encoders(&[]);
+        let config = integration.get_current_config();
+        assert!(config.contains(&("hardware_encoder".into(), "auto".into())));
+    }
+
+    #[test]
+    #[allow(clippy::expect_used)]
+    fn test_streamed_meters() {
+        let mut integration = VideoEditorFlexForge::new();
+        integration.set_metrics_source(Arc::new(|metrics| {
+            let meter =
+                MeterSnapshot { peak_dbfs: MeterSnapshot::to_dbfs(0.5), ..Default::default() };
+            metrics.track_meters = vec![(1, meter)];
+            metrics.cache_hit_ratio = 0.75;
+            metrics.dropped_frames += 1;
+        }));
+        assert!(integration.metrics().track_meters.is_empty());
+
+        let stream_id = integration.start_stream().expect("should start streaming");
+        assert!(integration.render_frame(stream_id, 16.0));
+        assert!(integration.render_frame(stream_id, 16.0));
+        let metrics = integration.metrics();
+        assert_eq!(metrics.track_meters.len(), 1);
+        assert!((metrics.track_meters[0].1.peak_dbfs + 6.02).abs() < 0.01);
+        assert_eq!(metrics.track_meters[0].1.rms_dbfs, MeterSnapshot::FLOOR_DB);
+        assert_eq!(metrics.cache_hit_ratio, 0.75);
+        assert_eq!(metrics.dropped_frames, 2);
+        assert_eq!(integration.metrics_history(60.0).len(), 2);
+
+        // Nothing is published without a stream
+        integration.stop_stream(stream_id).expect("should stop streaming");
+        assert!(!integration.render_frame(stream_id, 16.0));
+        assert_eq!(integration.metrics().dropped_frames, 2);
+    }
+
+    #[test]
+    fn test_metrics_history() {
+        let mut integration = VideoEditorFlexForge::new();
+        for i in 0..20u64 {
+            let metrics = VideoEditorMetrics {
+                render_fps: 40.0 + i as f32,
+                gpu_memory_bytes: i * 1024,
+                dropped_frames: i / 5,
+                ..VideoEditorMetrics::default()
+            };
+            integration.update_metrics_at(metrics, i * 500);
+        }
+
+        // Last 2 s: samples at 7.5, 8.0, 8.5, 9.0 and 9.5 s
+        let recent = integration.metrics_history(2.0);
+        assert_eq!(recent.len(), 5);
+        assert_eq!(recent[0].timestamp_ms, 7_500);
+        assert_eq!(integration.metric_percentile(MetricKind::RenderFps, 2.0, 50.0), Some(57.0));
+        assert_eq!(
+            integration.metric_percentile(MetricKind::DroppedFrames, 60.0, 100.0),
+            Some(3.0)
+        );
+
+        let mut history = MetricsHistory::new(3);
+        for sample in integration.metrics_history(60.0) {
+            history.push(sample);
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.average(MetricKind::GpuMemory, 60.0), Some(18.0 * 1024.0));
+    }
 }
@@ -0,0 +1,779 @@
+//! glTF 2.0 / GLB reader for 3D layers.
+//!
+//! Loads meshes, node transforms, metallic-roughness materials, textures,
+//! cameras, skins and animations into a [`Scene3D`]. Buffers and images may
+//! be embedded (GLB binary chunk, `data:` URIs) or external files next to
+//! a `.gltf`. Sparse accessors and morph targets are not supported.
+
+use std::path::Path;
+
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    json::JsonValue,
+    scene3d::{
+        AlphaMode, Animation3D, AnimationChannel, AnimationInterpolation, AnimationPath,
+        CameraProjection, Mat4, Mesh3D, MeshPrimitive, NodeTransform, PbrMaterial, Scene3D,
+        SceneNode, Skin, Texture3D,
+    },
+};
+
+/// GLB file magic (`glTF`).
+pub const GLB_MAGIC: u32 = 0x4654_6C67;
+
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A;
+const GLB_CHUNK_BIN: u32 = 0x004E_4942;
+
+/// Primitive topology values.
+const MODE_TRIANGLES: usize = 4;
+const MODE_TRIANGLE_STRIP: usize = 5;
+const MODE_TRIANGLE_FAN: usize = 6;
+
+impl Scene3D {
+    /// Loads a `.gltf` or `.glb` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the file or its external resources cannot be read,
+    /// or the content is not valid glTF 2.0.
+    pub fn open(path: impl AsRef<Path>) -> VideoEditorResult<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| VideoEditorError::io(path, e))?;
+        let base = path.parent();
+        if bytes.starts_with(&GLB_MAGIC.to_le_bytes()) {
+            Self::from_glb_with_base(&bytes, base)
+        } else {
+            let text = std::str::from_utf8(&bytes)
+                .map_err(|_| VideoEditorError::conversion("glTF JSON is not UTF-8"))?;
+            Self::from_gltf(text, base)
+        }
+    }
+
+    /// Parses a binary GLB container with all resources embedded.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the container or its JSON is invalid.
+    pub fn from_glb(bytes: &[u8]) -> VideoEditorResult<Self> {
+        Self::from_glb_with_base(bytes, None)
+    }
+
+    fn from_glb_with_base(bytes: &[u8], base: Option<&Path>) -> VideoEditorResult<Self> {
+        let word = |offset: usize| {
+            bytes
+                .get(offset..offset + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .ok_or_else(|| VideoEditorError::conversion("Truncated GLB file"))
+        };
+        if word(0)? != GLB_MAGIC {
+            return Err(VideoEditorError::conversion("Not a GLB file"));
+        }
+        if word(4)? != 2 {
+            return Err(VideoEditorError::unsupported_format("Only glTF 2.0 is supported"));
+        }
+        let length = (word(8)? as usize).min(bytes.len());
+
+        let (mut json, mut bin) = (None, None);
+        let mut offset = 12;
+        while offset + 8 <= length {
+            let (chunk_length, chunk_type) = (word(offset)? as usize, word(offset + 4)?);
+            let data = bytes
+                .get(offset + 8..offset + 8 + chunk_length)
+                .ok_or_else(|| VideoEditorError::conversion("Truncated GLB chunk"))?;
+            match chunk_type {
+                GLB_CHUNK_JSON => json = Some(data),
+                GLB_CHUNK_BIN if bin.is_none() => bin = Some(data),
+                _ => {},
+            }
+            offset += 8 + chunk_length.next_multiple_of(4);
+        }
+        let json = json.ok_or_else(|| VideoEditorError::conversion("GLB has no JSON chunk"))?;
+        let text = std::str::from_utf8(json)
+            .map_err(|_| VideoEditorError::conversion("GLB JSON chunk is not UTF-8"))?;
+        Loader::new(&JsonValue::parse(text)?, bin, base)?.load()
+    }
+
+    /// Parses glTF JSON. External buffers and images resolve against `base`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the JSON is invalid or a resource cannot be loaded.
+    pub fn from_gltf(text: &str, base: Option<&Path>) -> VideoEditorResult<Self> {
+        Loader::new(&JsonValue::parse(text)?, None, base)?.load()
+    }
+}
+
+fn invalid(message: impl Into<String>) -> VideoEditorError {
+    VideoEditorError::conversion(format!("Invalid glTF: {}", message.into()))
+}
+
+/// Resolves glTF indices against loaded buffers.
+struct Loader<'a> {
+    root:    &'a JsonValue,
+    buffers: Vec<Vec<u8>>,
+    base:    Option<&'a Path>,
+}
+
+impl<'a> Loader<'a> {
+    fn new(
+        root: &'a JsonValue, bin: Option<&[u8]>, base: Option<&'a Path>,
+    ) -> VideoEditorResult<Self> {
+        let version = root.get("asset").and_then(|a| a.get("version")).and_then(JsonValue::as_str);
+        if !version.is_some_and(|v| v.starts_with("2.")) {
+            return Err(VideoEditorError::unsupported_format("Only glTF 2.0 is supported"));
+        }
+        let mut loader = Self { root, buffers: Vec::new(), base };
+        for (index, buffer) in loader.array("buffers").iter().enumerate() {
+            let data = match buffer.get("uri").and_then(JsonValue::as_str) {
+                Some(uri) => loader.resolve_uri(uri)?,
+                // The first buffer without a URI is the GLB binary chunk
+                None if index == 0 => {
+                    bin.map(<[u8]>::to_vec).ok_or_else(|| invalid("missing GLB binary chunk"))?
+                },
+                None => return Err(invalid(format!("buffer {index} has no data"))),
+            };
+            loader.buffers.push(data);
+        }
+        Ok(loader)
+    }
+
+    fn array(&self, name: &str) -> &'a [JsonValue] {
+        self.root.get(name).map_or(&[], JsonValue::items)
+    }
+
+    fn resolve_uri(&self, uri: &str) -> VideoEditorResult<Vec<u8>> {
+        if let Some(data) = uri.strip_prefix("data:") {
+            let (_, payload) = data
+                .split_once(";base64,")
+                .ok_or_else(|| invalid("only base64 data URIs are supported"))?;
+            return decode_base64(payload).ok_or_else(|| invalid("bad base64 data URI"));
+        }
+        let relative = percent_decode(uri);
+        let path =
+            self.base.map_or_else(|| Path::new(&relative).to_path_buf(), |b| b.join(&relative));
+        std::fs::read(&path).map_err(|e| VideoEditorError::io(path, e))
+    }
+
+    fn load(&self) -> VideoEditorResult<Scene3D> {
+        let nodes = self.array("nodes").iter().map(load_node).collect::<Vec<_>>();
+        let scene = self
+            .root
+            .get("scene")
+            .and_then(JsonValue::as_usize)
+            .and_then(|i| self.array("scenes").get(i))
+            .or_else(|| self.array("scenes").first());
+        let roots = match scene {
+            Some(scene) => indices(scene.get("nodes")),
+            // No scene: every node that is nobody's child
+            None => (0..nodes.len())
+                .filter(|i| !nodes.iter().any(|n: &SceneNode| n.children.contains(i)))
+                .collect(),
+        };
+
+        Ok(Scene3D {
+            roots,
+            meshes: self
+                .array("meshes")
+                .iter()
+                .map(|m| self.load_mesh(m))
+                .collect::<VideoEditorResult<_>>()?,
+            materials: self.array("materials").iter().map(load_material).collect(),
+            textures: self.load_textures()?,
+            skins: self
+                .array("skins")
+                .iter()
+                .map(|s| self.load_skin(s))
+                .collect::<VideoEditorResult<_>>()?,
+            cameras: self.array("cameras").iter().filter_map(load_camera).collect(),
+            animations: self
+                .array("animations")
+                .iter()
+                .map(|a| self.load_animation(a))
+                .collect::<VideoEditorResult<_>>()?,
+            nodes,
+        })
+    }
+
+    fn load_mesh(&self, mesh: &JsonValue) -> VideoEditorResult<Mesh3D> {
+        let mut primitives = Vec::new();
+        for primitive in mesh.get("primitives").map_or(&[][..], JsonValue::items) {
+            let mode =
+                primitive.get("mode").and_then(JsonValue::as_usize).unwrap_or(MODE_TRIANGLES);
+            if !matches!(mode, MODE_TRIANGLES | MODE_TRIANGLE_STRIP | MODE_TRIANGLE_FAN) {
+                // Points and lines have no surface to composite
+                continue;
+            }
+            let attributes = primitive.get("attributes");
+            let attribute =
+                |name: &str| attributes.and_then(|a| a.get(name)).and_then(JsonValue::as_usize);
+            let Some(position) = attribute("POSITION") else {
+                continue;
+            };
+
+            let positions = chunks(&self.read_accessor(position)?);
+            let indices: Vec<u32> = match primitive.get("indices").and_then(JsonValue::as_usize) {
+                Some(accessor) => {
+                    self.read_accessor(accessor)?.into_iter().map(|i| i as u32).collect()
+                },
+                None => (0..positions.len() as u32).collect(),
+            };
+            if indices.iter().any(|&i| i as usize >= positions.len()) {
+                return Err(invalid("vertex index out of range"));
+            }
+            let optional = |name: &str| attribute(name).map(|a| self.read_accessor(a)).transpose();
+            primitives.push(MeshPrimitive {
+                normals: optional("NORMAL")?.map(|v| chunks(&v)).unwrap_or_default(),
+                uvs: optional("TEXCOORD_0")?.map(|v| chunks(&v)).unwrap_or_default(),
+                joints: optional("JOINTS_0")?
+                    .map(|v| chunks::<4>(&v).into_iter().map(|j| j.map(|c| c as u16)).collect())
+                    .unwrap_or_default(),
+                weights: optional("WEIGHTS_0")?.map(|v| chunks(&v)).unwrap_or_default(),
+                indices: triangulate(indices, mode),
+                material: primitive.get("material").and_then(JsonValue::as_usize),
+                positions,
+            });
+        }
+        Ok(Mesh3D { name: name(mesh), primitives })
+    }
+
+    fn load_textures(&self) -> VideoEditorResult<Vec<Texture3D>> {
+        let images = self.array("images");
+        self.array("textures")
+            .iter()
+            .map(|texture| {
+                let Some(image) =
+                    texture.get("source").and_then(JsonValue::as_usize).and_then(|i| images.get(i))
+                else {
+                    return Ok(Texture3D { name: name(texture), ..Texture3D::default() });
+                };
+                let data =
+                    match (image.get("uri").and_then(JsonValue::as_str), image.get("bufferView")) {
+                        (Some(uri), _) => self.resolve_uri(uri)?,
+                        (None, Some(view)) => {
+                            let view =
+                                view.as_usize().ok_or_else(|| invalid("image bufferView"))?;
+                            self.buffer_view(view)?.0.to_vec()
+                        },
+                        (None, None) => Vec::new(),
+                    };
+                let uri = image.get("uri").and_then(JsonValue::as_str).unwrap_or_default();
+                let mime_type = image
+                    .get("mimeType")
+                    .and_then(JsonValue::as_str)
+                    .map(str::to_string)
+                    .or_else(|| {
+                        let lower = uri.to_ascii_lowercase();
+                        let mime = if lower.ends_with(".png") || lower.starts_with("data:image/png")
+                        {
+                            "image/png"
+                        } else if lower.ends_with(".jpg")
+                            || lower.ends_with(".jpeg")
+                            || lower.starts_with("data:image/jpeg")
+                        {
+                            "image/jpeg"
+                        } else {
+                            return None;
+                        };
+                        Some(mime.to_string())
+                    })
+                    .unwrap_or_default();
+                Ok(Texture3D { name: name(image), mime_type, data })
+            })
+            .collect()
+    }
+
+    fn load_skin(&self, skin: &JsonValue) -> VideoEditorResult<Skin> {
+        let joints = indices(skin.get("joints"));
+        let inverse_binds = match skin.get("inverseBindMatrices").and_then(JsonValue::as_usize) {
+            Some(accessor) => {
+                chunks::<16>(&self.read_accessor(accessor)?).into_iter().map(Mat4).collect()
+            },
+            None => vec![Mat4::IDENTITY; joints.len()],
+        };
+        Ok(Skin { name: name(skin), joints, inverse_binds })
+    }
+
+    fn load_animation(&self, animation: &JsonValue) -> VideoEditorResult<Animation3D> {
+        let samplers = animation.get("samplers").map_or(&[][..], JsonValue::items);
+        let mut channels = Vec::new();
+        for channel in animation.get("channels").map_or(&[][..], JsonValue::items) {
+            let target = channel.get("target");
+            let Some(node) = target.and_then(|t| t.get("node")).and_then(JsonValue::as_usize)
+            else {
+                continue;
+            };
+            let path = match target.and_then(|t| t.get("path")).and_then(JsonValue::as_str) {
+                Some("translation") => AnimationPath::Translation,
+                Some("rotation") => AnimationPath::Rotation,
+                Some("scale") => AnimationPath::Scale,
+                Some("weights") => AnimationPath::Weights,
+                _ => continue,
+            };
+            let sampler = channel
+                .get("sampler")
+                .and_then(JsonValue::as_usize)
+                .and_then(|i| samplers.get(i))
+                .ok_or_else(|| invalid("animation channel without sampler"))?;
+            let accessor = |key: &str| {
+                sampler
+                    .get(key)
+                    .and_then(JsonValue::as_usize)
+                    .ok_or_else(|| invalid(format!("sampler {key}")))
+            };
+            let interpolation = match sampler.get("interpolation").and_then(JsonValue::as_str) {
+                Some("STEP") => AnimationInterpolation::Step,
+                Some("CUBICSPLINE") => AnimationInterpolation::CubicSpline,
+                _ => AnimationInterpolation::Linear,
+            };
+            channels.push(AnimationChannel {
+                node,
+                path,
+                interpolation,
+                times: self.read_accessor(accessor("input")?)?,
+                values: self.read_accessor(accessor("output")?)?,
+            });
+        }
+        Ok(Animation3D { name: name(animation), channels })
+    }
+
+    /// Bytes of a buffer view and its stride (0 = tightly packed).
+    fn buffer_view(&self, index: usize) -> VideoEditorResult<(&[u8], usize)> {
+        let view = self
+            .array("bufferViews")
+            .get(index)
+            .ok_or_else(|| invalid(format!("bufferView {index}")))?;
+        let field = |key: &str| view.get(key).and_then(JsonValue::as_usize);
+        let buffer = field("buffer")
+            .and_then(|b| self.buffers.get(b))
+            .ok_or_else(|| invalid("bufferView buffer"))?;
+        let offset = field("byteOffset").unwrap_or(0);
+        let length = field("byteLength").ok_or_else(|| invalid("bufferView byteLength"))?;
+        let bytes = offset
+            .checked_add(length)
+            .and_then(|end| buffer.get(offset..end))
+            .ok_or_else(|| invalid("bufferView out of range"))?;
+        Ok((bytes, field("byteStride").unwrap_or(0)))
+    }
+
+    /// Reads an accessor as flattened floats, normalizing integer data
+    /// when the accessor is marked normalized.
+    fn read_accessor(&self, index: usize) -> VideoEditorResult<Vec<f32>> {
+        let accessor = self
+            .array("accessors")
+            .get(index)
+            .ok_or_else(|| invalid(format!("accessor {index}")))?;
+        let count = accessor
+            .get("count")
+            .and_then(JsonValue::as_usize)
+            .ok_or_else(|| invalid("accessor count"))?;
+        let components = match accessor.get("type").and_then(JsonValue::as_str) {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") | Some("MAT2") => 4,
+            Some("MAT3") => 9,
+            Some("MAT4") => 16,
+            _ => return Err(invalid("accessor type")),
+        };
+        let component_type =
+            accessor.get("componentType").and_then(JsonValue::as_usize).unwrap_or(0);
+        let size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            other => return Err(invalid(format!("component type {other}"))),
+        };
+        let normalized = accessor.get("normalized").and_then(JsonValue::as_bool).unwrap_or(false);
+        let element = components * size;
+        let values = count.checked_mul(components).ok_or_else(|| invalid("accessor count"))?;
+
+        let Some(view) = accessor.get("bufferView").and_then(JsonValue::as_usize) else {
+            // Without a buffer view the data is all zeros (sparse is not applied);
+            // still no larger than the loaded buffers could hold
+            let available: usize = self.buffers.iter().map(Vec::len).sum();
+            if count.checked_mul(element).is_none_or(|len| len > available) {
+                return Err(invalid("accessor count"));
+            }
+            return Ok(vec![0.0; values]);
+        };
+        let (bytes, stride) = self.buffer_view(view)?;
+        let offset = accessor.get("byteOffset").and_then(JsonValue::as_usize).unwrap_or(0);
+        let stride = if stride == 0 { element } else { stride };
+
+        // The last element must end inside the view
+        let end = match count.checked_sub(1) {
+            None => Some(offset),
+            Some(last) => last
+                .checked_mul(stride)
+                .and_then(|start| start.checked_add(offset))
+                .and_then(|start| start.checked_add(element)),
+        };
+        if end.is_none_or(|end| end > bytes.len()) {
+            return Err(invalid("accessor out of range"));
+        }
+
+        let mut out = Vec::with_capacity(values);
+        for i in 0..count {
+            let start = offset + i * stride;
+            let data = &bytes[start..start + element];
+            for c in data.chunks_exact(size) {
+                let value = match component_type {
+                    5120 => f32::from(c[0] as i8) / if normalized { 127.0 } else { 1.0 },
+                    5121 => f32::from(c[0]) / if normalized { 255.0 } else { 1.0 },
+                    5122 => {
+                        f32::from(i16::from_le_bytes([c[0], c[1]]))
+                            / if normalized { 32767.0 } else { 1.0 }
+                    },
+                    5123 => {
+                        f32::from(u16::from_le_bytes([c[0], c[1]]))
+                            / if normalized { 65535.0 } else { 1.0 }
+                    },
+                    5125 => u32::from_le_bytes([c[0], c[1], c[2], c[3]]) as f32,
+                    _ => f32::from_le_bytes([c[0], c[1], c[2], c[3]]),
+                };
+                out.push(if normalized { value.max(-1.0) } else { value });
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn name(value: &JsonValue) -> String {
+    value.get("name").and_then(JsonValue::as_str).unwrap_or_default().to_string()
+}
+
+fn indices(value: Option<&JsonValue>) -> Vec<usize> {
+    value.map_or(&[][..], JsonValue::items).iter().filter_map(JsonValue::as_usize).collect()
+}
+
+fn floats<const N: usize>(value: Option<&JsonValue>) -> Option<[f32; N]> {
+    let items = value?.items();
+    (items.len() == N).then(|| std::array::from_fn(|i| items[i].as_f64().unwrap_or(0.0) as f32))
+}
+
+fn chunks<const N: usize>(values: &[f32]) -> Vec<[f32; N]> {
+    values.chunks_exact(N).map(|c| std::array::from_fn(|i| c[i])).collect()
+}
+
+fn load_node(node: &JsonValue) -> SceneNode {
+    let field = |key: &str| node.get(key).and_then(JsonValue::as_usize);
+    SceneNode {
+        name:      name(node),
+        transform: NodeTransform {
+            translation: floats(node.get("translation")).unwrap_or([0.0; 3]),
+            rotation:    floats(node.get("rotation")).unwrap_or([0.0, 0.0, 0.0, 1.0]),
+            scale:       floats(node.get("scale")).unwrap_or([1.0; 3]),
+        },
+        matrix:    floats(node.get("matrix")).map(Mat4),
+        children:  indices(node.get("children")),
+        mesh:      field("mesh"),
+        skin:      field("skin"),
+        camera:    field("camera"),
+    }
+}
+
+fn load_material(material: &JsonValue) -> PbrMaterial {
+    let texture = |value: Option<&JsonValue>| {
+        value.and_then(|t| t.get("index")).and_then(JsonValue::as_usize)
+    };
+    let number = |value: Option<&JsonValue>, default: f32| {
+        value.and_then(JsonValue::as_f64).map_or(default, |n| n as f32)
+    };
+    let pbr = material.get("pbrMetallicRoughness");
+    let pbr_field = |key: &str| pbr.and_then(|p| p.get(key));
+    PbrMaterial {
+        name:                       name(material),
+        base_color:                 floats(pbr_field("baseColorFactor")).unwrap_or([1.0; 4]),
+        base_color_texture:         texture(pbr_field("baseColorTexture")),
+        metallic:                   number(pbr_field("metallicFactor"), 1.0),
+        roughness:                  number(pbr_field("roughnessFactor"), 1.0),
+        metallic_roughness_texture: texture(pbr_field("metallicRoughnessTexture")),
+        normal_texture:             texture(material.get("normalTexture")),
+        emissive:                   floats(material.get("emissiveFactor")).unwrap_or([0.0; 3]),
+        emissive_texture:           texture(material.get("emissiveTexture")),
+        alpha_mode:                 match material.get("alphaMode").and_then(JsonValue::as_str) {
+            Some("MASK") => AlphaMode::Mask(number(material.get("alphaCutoff"), 0.5)),
+            Some("BLEND") => AlphaMode::Blend,
+            _ => AlphaMode::Opaque,
+        },
+        double_sided:               material
+            .get("doubleSided")
+            .and_then(JsonValue::as_bool)
+            .unwrap_or(false),
+    }
+}
+
+fn load_camera(camera: &JsonValue) -> Option<CameraProjection> {
+    let number =
+        |value: &JsonValue, key: &str| value.get(key).and_then(JsonValue::as_f64).map(|n| n as f32);
+    match camera.get("type").and_then(JsonValue::as_str)? {
+        "perspective" => {
+            let p = camera.get("perspective")?;
+            Some(CameraProjection::Perspective {
+                yfov:   number(p, "yfov")?,
+                aspect: number(p, "aspectRatio"),
+                znear:  number(p, "znear")?,
+                zfar:   number(p, "zfar"),
+            })
+        },
+        "orthographic" => {
+            let o = camera.get("orthographic")?;
+            Some(CameraProjection::Orthographic {
+                xmag:  number(o, "xmag")?,
+                ymag:  number(o, "ymag")?,
+                znear: number(o, "znear")?,
+                zfar:  number(o, "zfar")?,
+            })
+        },
+        _ => None,
+    }
+}
+
+/// Converts strips and fans into a triangle list.
+fn triangulate(indices: Vec<u32>, mode: usize) -> Vec<u32> {
+    let triangles = indices.len().saturating_sub(2);
+    match mode {
+        MODE_TRIANGLE_STRIP => (0..triangles)
+            .flat_map(|i| {
+                // Alternate winding to keep faces consistent
+                if i % 2 == 0 {
+                    [indices[i], indices[i + 1], indices[i + 2]]
+                } else {
+                    [indices[i + 1], indices[i], indices[i + 2]]
+                }
+            })
+            .collect(),
+        MODE_TRIANGLE_FAN => {
+            (0..triangles).flat_map(|i| [indices[0], indices[i + 1], indices[i + 2]]).collect()
+        },
+        _ => {
+            let mut indices = indices;
+            indices.truncate(indices.len() / 3 * 3);
+            indices
+        },
+    }
+}
+
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(hex) = uri.get(i + 1..i + 3)
+            && let Ok(byte) = u8::from_str_radix(hex, 16)
+        {
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    };
+    let digits: Vec<u8> = text
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+        .map(value)
+        .collect::<Option<_>>()?;
+    let mut out = Vec::with_capacity(digits.len() * 3 / 4);
+    for group in digits.chunks(4) {
+        let bits =
+            group.iter().enumerate().fold(0u32, |acc, (i, &d)| acc | u32::from(d) << (18 - 6 * i));
+        let bytes = bits.to_be_bytes();
+        out.extend_from_slice(&bytes[1..group.len()]);
+    }
+    Some(out)
+}
+
+#[cfg(all(test, feature = "full-tests"))]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Builds a GLB with one textured triangle under a rotating parent,
+    /// a perspective camera and a linear translation animation.
+    pub(crate) fn triangle_glb() -> Vec<u8> {
+        let mut bin = Vec::new();
+        for v in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            bin.extend_from_slice(&v.to_le_bytes());
+        }
+        for i in [0u16, 1, 2, 0] {
+            bin.extend_from_slice(&i.to_le_bytes());
+        }
+        for t in [0.0f32, 2.0] {
+            bin.extend_from_slice(&t.to_le_bytes());
+        }
+        for v in [0.0f32, 0.0, 0.0, 4.0, 0.0, 0.0] {
+            bin.extend_from_slice(&v.to_le_bytes());
+        }
+        bin.extend_from_slice(&[0x89, b'P', b'N', b'G']);
+
+        let json = format!(
+            r#"{{"asset":{{"version":"2.0"}},"scene":0,"scenes":[{{"nodes":[0,2]}}],
+            "nodes":[{{"name":"Root","children":[1],"rotation":[0,0,0.7071068,0.7071068]}},
+                     {{"name":"Tri","mesh":0,"translation":[0,0,-5]}},
+                     {{"name":"Cam","camera":0,"translation":[0,0,10]}}],
+            "cameras":[{{"type":"perspective","perspective":{{"yfov":0.8,"znear":0.1,"zfar":100}}}}],
+            "meshes":[{{"name":"Triangle","primitives":[{{"attributes":{{"POSITION":0}},"indices":1,"material":0}}]}}],
+            "materials":[{{"name":"Paint","pbrMetallicRoughness":{{"baseColorFactor":[1,0.5,0,1],
+                "baseColorTexture":{{"index":0}},"metallicFactor":0.2,"roughnessFactor":0.6}},
+                "alphaMode":"MASK","doubleSided":true}}],
+            "textures":[{{"source":0}}],
+            "images":[{{"bufferView":4,"mimeType":"image/png"}}],
+            "animations":[{{"channels":[{{"sampler":0,"target":{{"node":1,"path":"translation"}}}}],
+                "samplers":[{{"input":2,"output":3}}]}}],
+            "accessors":[{{"bufferView":0,"componentType":5126,"count":3,"type":"VEC3"}},
+                         {{"bufferView":1,"componentType":5123,"count":3,"type":"SCALAR"}},
+                         {{"bufferView":2,"componentType":5126,"count":2,"type":"SCALAR"}},
+                         {{"bufferView":3,"componentType":5126,"count":2,"type":"VEC3"}}],
+            "bufferViews":[{{"buffer":0,"byteOffset":0,"byteLength":36}},
+                           {{"buffer":0,"byteOffset":36,"byteLength":6}},
+                           {{"buffer":0,"byteOffset":44,"byteLength":8}},
+                           {{"buffer":0,"byteOffset":52,"byteLength":24}},
+                           {{"buffer":0,"byteOffset":76,"byteLength":4}}],
+            "buffers":[{{"byteLength":{}}}]}}"#,
+            bin.len()
+        );
+
+        let mut json = json.into_bytes();
+        json.resize(json.len().next_multiple_of(4), b' ');
+        let mut glb = Vec::new();
+        glb.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&GLB_CHUNK_JSON.to_le_bytes());
+        glb.extend(json);
+        glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&GLB_CHUNK_BIN.to_le_bytes());
+        glb.extend(bin);
+        glb
+    }
+
+    fn close(a: [f32; 3], b: [f32; 3]) -> bool {
+        a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-4)
+    }
+
+    #[test]
+    fn test_load_glb_scene() {
+        let scene = Scene3D::from_glb(&triangle_glb()).expect("test assertion");
+        assert_eq!(scene.roots, vec![0, 2]);
+        assert_eq!(scene.vertex_count(), 3);
+        assert_eq!(scene.triangle_count(), 1);
+        assert_eq!(scene.meshes[0].primitives[0].indices, vec![0, 1, 2]);
+
+        let material = &scene.materials[0];
+        assert_eq!(material.base_color, [1.0, 0.5, 0.0, 1.0]);
+        assert_eq!(material.base_color_texture, Some(0));
+        assert_eq!(material.alpha_mode, AlphaMode::Mask(0.5));
+        assert!(material.double_sided);
+        assert_eq!(scene.textures[0].mime_type, "image/png");
+        assert_eq!(scene.textures[0].data, vec![0x89, b'P', b'N', b'G']);
+
+        // Parent rotates 90° about Z: local +X ends up on +Y
+        let draws = scene.draw_list(None);
+        assert_eq!(draws.len(), 1);
+        assert!(close(draws[0].transform.transform_point([1.0, 0.0, 0.0]), [0.0, 1.0, -5.0]));
+
+        let animation = &scene.animations[0];
+        assert_eq!(animation.duration(), 2.0);
+        let pose = scene.pose(0, 1.0);
+        assert_eq!(pose[1].translation, [2.0, 0.0, 0.0]);
+        let draws = scene.draw_list(Some(&pose));
+        assert!(close(draws[0].transform.transform_point([0.0; 3]), [0.0, 2.0, 0.0]));
+
+        // The camera at z=10 sees the origin in the middle of the frame
+        let view_projection = scene.view_projection(16.0 / 9.0, None);
+        let center = view_projection.transform_point([0.0, 0.0, 0.0]);
+        assert!(center[0].abs() < 1e-4 && center[1].abs() < 1e-4);
+
+        let mut gpu = crate::GpuPipeline::new(true);
+        assert!(gpu.dispatch_scene(&scene, 1.0, None).is_err());
+        gpu.initialize();
+        let dispatch = gpu.dispatch_scene(&scene, 1.0, Some((0, 1.0))).expect("test assertion");
+        assert_eq!(dispatch.draws.len(), 1);
+        assert_eq!(dispatch.view_projection, scene.view_projection(1.0, None));
+    }
+
+    #[test]
+    fn test_gltf_json_with_data_uri() {
+        let positions: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let encoded = encode_base64(&positions);
+        let json = format!(
+            r#"{{"asset":{{"version":"2.0"}},"nodes":[{{"mesh":0}}],
+            "meshes":[{{"primitives":[{{"attributes":{{"POSITION":0}},"mode":5}}]}}],
+            "accessors":[{{"bufferView":0,"componentType":5126,"count":4,"type":"VEC3"}}],
+            "bufferViews":[{{"buffer":0,"byteLength":48}}],
+            "buffers":[{{"byteLength":48,"uri":"data:application/octet-stream;base64,{encoded}"}}]}}"#
+        );
+        let scene = Scene3D::from_gltf(&json, None).expect("test assertion");
+        assert_eq!(scene.roots, vec![0]);
+        assert_eq!(scene.meshes[0].primitives[0].indices, vec![0, 1, 2, 2, 1, 3]);
+        let [min, max] = scene.bounds(None).expect("test assertion");
+        assert!(close(min, [0.0; 3]) && close(max, [1.0, 1.0, 0.0]));
+
+        assert!(Scene3D::from_gltf(r#"{"asset":{"version":"1.0"}}"#, None).is_err());
+        assert!(Scene3D::from_gltf("{", None).is_err());
+        assert!(Scene3D::from_glb(b"glTF").is_err());
+    }
+
+    #[test]
+    fn test_rejects_oversized_accessors() {
+        let encoded = encode_base64(&[0; 48]);
+        let load = |accessor: &str, view: &str| {
+            Scene3D::from_gltf(
+                &format!(
+                    r#"{{"asset":{{"version":"2.0"}},"nodes":[{{"mesh":0}}],
+                    "meshes":[{{"primitives":[{{"attributes":{{"POSITION":0}}}}]}}],
+                    "accessors":[{accessor}],"bufferViews":[{view}],
+                    "buffers":[{{"byteLength":48,"uri":"data:;base64,{encoded}"}}]}}"#
+                ),
+                None,
+            )
+        };
+        let view = r#"{"buffer":0,"byteLength":48}"#;
+        let vec3 = |rest: &str| format!(r#"{{"componentType":5126,"type":"VEC3",{rest}}}"#);
+        assert!(load(&vec3(r#""bufferView":0,"count":4"#), view).is_ok());
+
+        // Counts past the end of the view, or too large to allocate
+        assert!(load(&vec3(r#""bufferView":0,"count":5"#), view).is_err());
+        assert!(load(&vec3(r#""bufferView":0,"count":1e15"#), view).is_err());
+        assert!(load(&vec3(r#""bufferView":0,"count":1e30"#), view).is_err());
+        assert!(load(&vec3(r#""count":1e15"#), view).is_err());
+        // Offsets and strides that overflow
+        let strided = r#"{"buffer":0,"byteLength":48,"byteStride":1e30}"#;
+        let shifted = r#"{"buffer":0,"byteOffset":1e30,"byteLength":48}"#;
+        assert!(load(&vec3(r#""bufferView":0,"count":1,"byteOffset":1e30"#), view).is_err());
+        assert!(load(&vec3(r#""bufferView":0,"count":4"#), strided).is_err());
+        assert!(load(&vec3(r#""bufferView":0,"count":1"#), shifted).is_err());
+    }
+
+    fn encode_base64(data: &[u8]) -> String {
+        const ALPHABET: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut out = String::new();
+        for chunk in data.chunks(3) {
+            let bits = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |acc, (i, &b)| acc | u32::from(b) << (16 - 8 * i));
+            for i in 0..=chunk.len() {
+                out.push(ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            }
+        }
+        out
+    }
+}
@@ -1,23 +1,42 @@
 //! Asset library management.
 
+use std::path::Path;
+
+use super::{generators::GeneratorSource, media_refs::normalize};
 use crate::{
+    converter::{
+        ConversionStats, FormatConverter, ImportFileResult, ImportReport, ImportWarning,
+        InputFormatCategory, SequencePattern,
+    },
     errors::{VideoEditorError, VideoEditorResult},
+    evlf_reader::EvlfReader,
     types::{
-        AudioClip, AudioFormat, FrameRate, Resolution, TimelinePosition, VideoClip, VideoFormat,
+        AudioClip, AudioFormat, BwfMetadata, FrameRate, ImageSequenceClip, Resolution, Rotation,
+        TimelinePosition, VideoClip, VideoFormat, clip::ClipState,
     },
 };
 
 /// Asset library for managing media files.
 pub struct AssetLibrary {
-    video_clips:  Vec<VideoClip>,
-    audio_clips:  Vec<AudioClip>,
-    next_clip_id: u64,
+    video_clips:     Vec<VideoClip>,
+    audio_clips:     Vec<AudioClip>,
+    image_sequences: Vec<ImageSequenceClip>,
+    generators:      Vec<(u64, GeneratorSource)>,
+    bins:            Vec<(String, Vec<u64>)>,
+    next_clip_id:    u64,
 }
 
 impl AssetLibrary {
     /// Create a new asset library.
     pub fn new() -> Self {
-        Self { video_clips: Vec::new(), audio_clips: Vec::new(), next_clip_id: 1 }
+        Self {
+            video_clips:     Vec::new(),
+            audio_clips:     Vec::new(),
+            image_sequences: Vec::new(),
+            generators:      Vec::new(),
+            bins:            Vec::new(),
+            next_clip_id:    1,
+        }
     }
 
     /// Import a video file.
@@ -30,12 +49,28 @@ impl AssetLibrary {
         self.next_clip_id += 1;
 
         // Placeholder - would analyze video file
-        let clip = VideoClip::new(id, path)
+        let mut clip = VideoClip::new(id, path)
             .with_resolution(Resolution::FHD)
             .with_frame_rate(FrameRate::FPS_30)
             .with_duration(TimelinePosition::from_ms(10000))
             .with_format(VideoFormat::H264);
 
+        // Converted media describes itself in the EVLF header. Only the
+        // headers are read, so projects full of long renders open at once
+        let extension = path.rsplit('.').next().unwrap_or_default();
+        if extension.eq_ignore_ascii_case("evlf")
+            && let Ok(reader) = EvlfReader::open(path)
+            && reader.header().frame_rate_den > 0
+        {
+            let header = reader.header();
+            clip = clip
+                .with_resolution(Resolution::new(header.width, header.height))
+                .with_frame_rate(FrameRate::new(header.frame_rate_num, header.frame_rate_den))
+                .with_duration(TimelinePosition::from_ms(header.duration_ms))
+                .with_format(VideoFormat::Raw);
+            clip.frame_count = header.frame_count;
+        }
+
         self.video_clips.push(clip);
 
         Ok(id)
@@ -51,17 +86,302 @@ impl AssetLibrary {
         self.next_clip_id += 1;
 
         // Placeholder - would analyze audio file
-        let clip = AudioClip::new(id, path)
+        let mut clip = AudioClip::new(id, path)
             .with_sample_rate(48000)
             .with_channels(2)
             .with_duration(TimelinePosition::from_ms(10000))
             .with_format(AudioFormat::AAC);
 
+        // Production sound carries BWF timecode and iXML track names
+        let extension = path.rsplit('.').next().unwrap_or_default().to_ascii_lowercase();
+        if matches!(extension.as_str(), "wav" | "bwf")
+            && let Ok(Some(bwf)) = BwfMetadata::read_wav(path)
+        {
+            clip = clip.with_bwf(bwf).with_format(AudioFormat::WAV);
+        }
+
         self.audio_clips.push(clip);
 
         Ok(id)
     }
 
+    /// Import a numbered image sequence (e.g. `frame_%04d.exr`).
+    ///
+    /// Missing frames are recorded on the clip rather than failing the import.
+    pub fn import_image_sequence(
+        &mut self, path: &str, frame_rate: FrameRate,
+    ) -> VideoEditorResult<u64> {
+        if path.is_empty() {
+            return Err(VideoEditorError::Asset("Path cannot be empty".to_string()));
+        }
+
+        let info = FormatConverter::scan_sequence(path)?;
+
+        let id = self.next_clip_id;
+        self.next_clip_id += 1;
+
+        let clip = ImageSequenceClip::new(id, info).with_frame_rate(frame_rate);
+        self.image_sequences.push(clip);
+
+        Ok(id)
+    }
+
+    /// Import a batch of files, continuing past individual failures.
+    ///
+    /// Files are dispatched by detected format; image sequence patterns use
+    /// `sequence_rate`. Each file's outcome is recorded in the report.
+    pub fn import_batch(&mut self, paths: &[&str], sequence_rate: FrameRate) -> ImportReport {
+        let mut report = ImportReport::new();
+        let mut seen: Vec<&str> = Vec::with_capacity(paths.len());
+
+        for &path in paths {
+            let format = FormatConverter::detect_format(path);
+            if seen.contains(&path) {
+                report.push(ImportFileResult::skipped(path, format, "duplicate input"));
+                continue;
+            }
+            seen.push(path);
+
+            let Some(format) = format else {
+                report.push(ImportFileResult::skipped(path, None, "unrecognized file extension"));
+                continue;
+            };
+
+            let imported = match format.category() {
+                InputFormatCategory::Video => self.import_video(path),
+                InputFormatCategory::Audio => self.import_audio(path),
+                InputFormatCategory::Image if SequencePattern::is_pattern(path) => {
+                    self.import_image_sequence(path, sequence_rate)
+                },
+                category => {
+                    let reason = format!("{category:?} assets cannot be imported directly");
+                    report.push(ImportFileResult::skipped(path, Some(format), reason));
+                    continue;
+                },
+            };
+
+            match imported {
+                Ok(id) => {
+                    let mut entry = ImportFileResult::imported(path, format);
+                    entry.asset_id = Some(id);
+                    if let Some(space) = FormatConverter::guess_color_space(format) {
+                        entry.warnings.push(ImportWarning::ColorSpaceGuessed(space.to_string()));
+                    }
+                    if let Some(clip) = self.image_sequence(id)
+                        && clip.has_missing_frames()
+                    {
+                        let missing = clip.missing_frames.len() as u64;
+                        entry.warnings.push(ImportWarning::MissingFrames(missing));
+                    }
+                    report.push(entry);
+                },
+                Err(err) => {
+                    report.push(ImportFileResult::failed(path, Some(format), err.to_string()));
+                },
+            }
+        }
+
+        report
+    }
+
+    /// Add a generator (test pattern or tone) as a media-less asset.
+    pub fn add_generator(&mut self, source: GeneratorSource) -> u64 {
+        let id = self.next_clip_id;
+        self.next_clip_id += 1;
+        self.generators.push((id, source));
+        id
+    }
+
+    /// Get a generator asset by ID.
+    pub fn generator(&self, id: u64) -> Option<&GeneratorSource> {
+        self.generators.iter().find(|(gid, _)| *gid == id).map(|(_, g)| g)
+    }
+
+    /// Remove an asset of any kind. Returns whether it existed.
+    pub fn remove_asset(&mut self, id: u64) -> bool {
+        let before = self.asset_count();
+        self.video_clips.retain(|c| c.id != id);
+        self.audio_clips.retain(|c| c.id != id);
+        self.image_sequences.retain(|c| c.id != id);
+        self.generators.retain(|(gid, _)| *gid != id);
+        for (_, ids) in &mut self.bins {
+            ids.retain(|&bid| bid != id);
+        }
+        self.asset_count() != before
+    }
+
+    /// Add a copy of a video or audio asset backed by a trimmed file
+    /// holding `duration` of its media, e.g. after consolidation. The copy
+    /// joins the original's bins. Returns the new ID, or `None` if `id` is
+    /// not a video or audio asset.
+    pub fn add_trimmed_copy(
+        &mut self, id: u64, path: &str, duration: TimelinePosition,
+    ) -> Option<u64> {
+        let new_id = self.next_clip_id;
+        if let Some(clip) = self.video_clip(id) {
+            let fps = clip.frame_rate.as_f64();
+            let mut copy = clip.clone();
+            copy.id = new_id;
+            copy.path = path.to_string();
+            copy.duration = duration;
+            copy.frame_count = (duration.as_secs_f64() * fps).round() as u64;
+            self.video_clips.push(copy);
+        } else if let Some(clip) = self.audio_clip(id) {
+            let mut copy = clip.clone();
+            copy.id = new_id;
+            copy.path = path.to_string();
+            copy.duration = duration;
+            copy.sample_count = duration.ms * u64::from(clip.sample_rate) / 1000;
+            self.audio_clips.push(copy);
+        } else {
+            return None;
+        }
+        self.next_clip_id += 1;
+        for (_, ids) in &mut self.bins {
+            if ids.contains(&id) {
+                ids.push(new_id);
+            }
+        }
+        Some(new_id)
+    }
+
+    /// Mark video and audio assets whose media is missing as offline, and
+    /// bring back offline assets whose media has returned. `media_online`
+    /// reports whether a media path can be read. Returns the IDs of the
+    /// offline assets.
+    ///
+    /// Offline assets keep all their metadata, so clips using them can
+    /// still be edited; they render as a placeholder slate.
+    pub fn refresh_offline(&mut self, media_online: impl Fn(&str) -> bool) -> Vec<u64> {
+        let states = self
+            .video_clips
+            .iter_mut()
+            .map(|c| (c.id, c.path.as_str(), &mut c.state))
+            .chain(self.audio_clips.iter_mut().map(|c| (c.id, c.path.as_str(), &mut c.state)));
+        let mut offline = Vec::new();
+        for (id, path, state) in states {
+            if !media_online(path) {
+                *state = ClipState::Offline;
+                offline.push(id);
+            } else if *state == ClipState::Offline {
+                *state = ClipState::Unloaded;
+            }
+        }
+        offline
+    }
+
+    /// Returns whether an asset's media is offline.
+    pub fn is_offline(&self, id: u64) -> bool {
+        self.video_clip(id).map(|c| c.state).or_else(|| self.audio_clip(id).map(|c| c.state))
+            == Some(ClipState::Offline)
+    }
+
+    /// Point video and audio assets using the media at `old` to `new`,
+    /// e.g. after a relink, bringing them back online. Returns the IDs of
+    /// the assets changed.
+    pub fn relink(&mut self, old: &str, new: &str) -> Vec<u64> {
+        let old = normalize(Path::new(old));
+        let media = self
+            .video_clips
+            .iter_mut()
+            .map(|c| (c.id, &mut c.path, &mut c.state))
+            .chain(self.audio_clips.iter_mut().map(|c| (c.id, &mut c.path, &mut c.state)));
+        let mut relinked = Vec::new();
+        for (id, path, state) in media {
+            if normalize(Path::new(path.as_str())) != old {
+                continue;
+            }
+            *path = new.to_string();
+            if *state == ClipState::Offline {
+                *state = ClipState::Unloaded;
+            }
+            relinked.push(id);
+        }
+        relinked
+    }
+
+    /// Store what converting a video asset's media detected about its
+    /// picture: pixel aspect, rotation and projection. Returns whether the
+    /// asset exists.
+    pub fn apply_conversion(&mut self, id: u64, stats: &ConversionStats) -> bool {
+        let Some(clip) = self.video_clip_mut(id) else {
+            return false;
+        };
+        clip.pixel_aspect = stats.pixel_aspect;
+        clip.rotation = stats.rotation;
+        clip.projection = stats.projection;
+        true
+    }
+
+    /// Show a video asset with `rotation` instead of its tagged one, for
+    /// media tagged wrong or not at all. `None` goes back to the tag.
+    /// Returns whether the asset exists.
+    pub fn set_rotation_override(&mut self, id: u64, rotation: Option<Rotation>) -> bool {
+        let Some(clip) = self.video_clip_mut(id) else {
+            return false;
+        };
+        clip.rotation_override = rotation;
+        true
+    }
+
+    /// Get the IDs of all assets, including generators.
+    pub fn asset_ids(&self) -> Vec<u64> {
+        self.video_clips
+            .iter()
+            .map(|c| c.id)
+            .chain(self.audio_clips.iter().map(|c| c.id))
+            .chain(self.image_sequences.iter().map(|c| c.id))
+            .chain(self.generators.iter().map(|(id, _)| *id))
+            .collect()
+    }
+
+    /// Source duration of a video, audio or image sequence asset.
+    pub fn asset_duration(&self, id: u64) -> Option<TimelinePosition> {
+        self.video_clip(id)
+            .map(|c| c.duration)
+            .or_else(|| self.audio_clip(id).map(|c| c.duration))
+            .or_else(|| self.image_sequence(id).map(ImageSequenceClip::duration))
+    }
+
+    /// Add an asset to a bin, creating the bin if needed.
+    ///
+    /// Returns `false` if the asset does not exist or is already in the bin.
+    pub fn add_to_bin(&mut self, bin: &str, id: u64) -> bool {
+        if !self.asset_ids().contains(&id) {
+            return false;
+        }
+        let index = match self.bins.iter().position(|(name, _)| name == bin) {
+            Some(index) => index,
+            None => {
+                self.bins.push((bin.to_string(), Vec::new()));
+                self.bins.len() - 1
+            },
+        };
+        let ids = &mut self.bins[index].1;
+        if ids.contains(&id) {
+            return false;
+        }
+        ids.push(id);
+        true
+    }
+
+    /// Asset IDs in a bin, in the order they were added.
+    pub fn bin(&self, bin: &str) -> Option<&[u64]> {
+        self.bins.iter().find(|(name, _)| name == bin).map(|(_, ids)| ids.as_slice())
+    }
+
+    /// Names of all bins.
+    pub fn bin_names(&self) -> impl Iterator<Item = &str> {
+        self.bins.iter().map(|(name, _)| name.as_str())
+    }
+
+    fn asset_count(&self) -> usize {
+        self.video_clips.len()
+            + self.audio_clips.len()
+            + self.image_sequences.len()
+            + self.generators.len()
+    }
+
     /// Get all video clips.
     pub fn video_clips(&self) -> &[VideoClip] {
         &self.video_clips
@@ -71,6 +391,36 @@ impl AssetLibrary {
     pub fn audio_clips(&self) -> &[AudioClip] {
         &self.audio_clips
     }
+
+    /// Get all image sequence clips.
+    pub fn image_sequences(&self) -> &[ImageSequenceClip] {
+        &self.image_sequences
+    }
+
+    /// Get an image sequence clip by ID.
+    pub fn image_sequence(&self, id: u64) -> Option<&ImageSequenceClip> {
+        self.image_sequences.iter().find(|c| c.id == id)
+    }
+
+    /// Get a mutable image sequence clip by ID.
+    pub fn image_sequence_mut(&mut self, id: u64) -> Option<&mut ImageSequenceClip> {
+        self.image_sequences.iter_mut().find(|c| c.id == id)
+    }
+
+    /// Get a video clip by ID.
+    pub fn video_clip(&self, id: u64) -> Option<&VideoClip> {
+        self.video_clips.iter().find(|c| c.id == id)
+    }
+
+    /// Get a mutable video clip by ID.
+    pub fn video_clip_mut(&mut self, id: u64) -> Option<&mut VideoClip> {
+        self.video_clips.iter_mut().find(|c| c.id == id)
+    }
+
+    /// Get an audio clip by ID.
+    pub fn audio_clip(&self, id: u64) -> Option<&AudioClip> {
+        self.audio_clips.iter().find(|c| c.id == id)
+    }
 }
 
 impl Default for AssetLibrary {
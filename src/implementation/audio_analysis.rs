@@ -0,0 +1,749 @@
+//! Audio analysis for assisted editing.
+//!
+//! [`VoiceActivityDetector`] finds speech in a clip's audio and turns the
+//! pauses between it into [`EditSuggestion`]s that
+//! `TimelineManager::apply_suggestions` cuts in bulk. [`sync_by_waveform`]
+//! lines up separately recorded sound with camera audio for
+//! `TimelineManager::align_and_link`. [`BeatDetector`] estimates the tempo
+//! of music and places beats for `TimelineManager::snap_cuts_to_beats`.
+
+use super::marker_system::{MarkerId, MarkerManager, MarkerType};
+use crate::types::{EditSuggestion, EditSuggestionKind, TimePosition, timeline::TimelineClip};
+
+/// Level reported for digital silence (dBFS).
+const SILENCE_DB: f32 = -120.0;
+
+/// Onset envelope frame length for waveform sync.
+const ENVELOPE_MS: u32 = 10;
+
+/// Envelope frames per coarse sync step.
+const COARSE_FACTOR: usize = 10;
+
+/// Coarse sync peaks refined at full resolution.
+const SYNC_CANDIDATES: usize = 4;
+
+/// Time range within an analyzed buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActivityRange {
+    /// Range start.
+    pub start: TimePosition,
+    /// Range end (exclusive).
+    pub end:   TimePosition,
+}
+
+impl ActivityRange {
+    /// Returns the range length.
+    pub fn duration(&self) -> TimePosition {
+        TimePosition::from_ms(self.end.ms.saturating_sub(self.start.ms))
+    }
+}
+
+/// Energy-based voice activity detector.
+///
+/// Frames louder than an adaptive threshold count as voice: the threshold
+/// sits `margin_db` above the estimated noise floor, but never above the
+/// speech level of the loudest frames, so clips without pauses stay whole.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoiceActivityDetector {
+    /// Analysis frame length in milliseconds.
+    pub frame_ms:       u32,
+    /// Level above the noise floor that counts as voice, in dB.
+    pub margin_db:      f32,
+    /// Frames quieter than this are always silence (dBFS).
+    pub floor_db:       f32,
+    /// Time voice is held after the level drops, bridging short gaps
+    /// between words.
+    pub hangover_ms:    u32,
+    /// Bursts shorter than this (clicks, bumps) are not voice.
+    pub min_voice_ms:   u32,
+    /// Only pauses longer than this are suggested as cuts.
+    pub min_silence_ms: u64,
+    /// Pause kept on both sides of a suggested cut so speech is not clipped.
+    pub padding_ms:     u64,
+}
+
+impl Default for VoiceActivityDetector {
+    fn default() -> Self {
+        Self {
+            frame_ms:       20,
+            margin_db:      12.0,
+            floor_db:       -60.0,
+            hangover_ms:    200,
+            min_voice_ms:   60,
+            min_silence_ms: 700,
+            padding_ms:     150,
+        }
+    }
+}
+
+impl VoiceActivityDetector {
+    /// RMS level of each analysis frame of interleaved samples, in dBFS.
+    pub fn frame_levels(&self, samples: &[f32], sample_rate: u32, channels: usize) -> Vec<f32> {
+        let channels = channels.max(1);
+        let frame_len = (sample_rate as usize * self.frame_ms.max(1) as usize / 1000).max(1);
+        samples
+            .chunks(frame_len * channels)
+            .map(|frame| {
+                let power = frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32;
+                if power > 0.0 { (10.0 * power.log10()).max(SILENCE_DB) } else { SILENCE_DB }
+            })
+            .collect()
+    }
+
+    /// Detect voiced ranges in interleaved samples.
+    pub fn detect(&self, samples: &[f32], sample_rate: u32, channels: usize) -> Vec<ActivityRange> {
+        let levels = self.frame_levels(samples, sample_rate, channels);
+        if levels.is_empty() {
+            return Vec::new();
+        }
+        let mut sorted = levels.clone();
+        sorted.sort_by(f32::total_cmp);
+        let percentile = |p: f32| sorted[((sorted.len() - 1) as f32 * p).round() as usize];
+        let threshold = (percentile(0.1) + self.margin_db)
+            .min(percentile(0.9) - self.margin_db)
+            .max(self.floor_db);
+
+        let frame_ms = u64::from(self.frame_ms.max(1));
+        let hangover = u64::from(self.hangover_ms).div_ceil(frame_ms) as usize;
+        let total_ms =
+            (samples.len() / channels.max(1)) as u64 * 1000 / u64::from(sample_rate.max(1));
+        let mut ranges: Vec<ActivityRange> = Vec::new();
+        let mut last_voiced: Option<usize> = None;
+        let mut open: Option<usize> = None;
+        for (frame, &level) in levels.iter().enumerate() {
+            if level > threshold {
+                open.get_or_insert(frame);
+                last_voiced = Some(frame);
+            } else if let (Some(begin), Some(last)) = (open, last_voiced)
+                && frame > last + hangover
+            {
+                ranges.push(self.range(begin, last + 1, total_ms));
+                open = None;
+            }
+        }
+        if let (Some(begin), Some(last)) = (open, last_voiced) {
+            ranges.push(self.range(begin, last + 1, total_ms));
+        }
+        ranges.retain(|r| r.duration().ms >= u64::from(self.min_voice_ms));
+        ranges
+    }
+
+    /// Voiced range for frames `begin..end`, extended by the hangover.
+    fn range(&self, begin: usize, end: usize, total_ms: u64) -> ActivityRange {
+        let frame_ms = u64::from(self.frame_ms.max(1));
+        ActivityRange {
+            start: TimePosition::from_ms(begin as u64 * frame_ms),
+            end:   TimePosition::from_ms(
+                (end as u64 * frame_ms + u64::from(self.hangover_ms)).min(total_ms),
+            ),
+        }
+    }
+
+    /// Detect the pauses between voiced ranges, including leading and
+    /// trailing silence.
+    pub fn silences(
+        &self, samples: &[f32], sample_rate: u32, channels: usize,
+    ) -> Vec<ActivityRange> {
+        let total_ms =
+            (samples.len() / channels.max(1)) as u64 * 1000 / u64::from(sample_rate.max(1));
+        let mut silences = Vec::new();
+        let mut cursor = 0;
+        for voice in self.detect(samples, sample_rate, channels) {
+            if voice.start.ms > cursor {
+                silences.push(ActivityRange {
+                    start: TimePosition::from_ms(cursor),
+                    end:   voice.start,
+                });
+            }
+            cursor = voice.end.ms;
+        }
+        if total_ms > cursor {
+            silences.push(ActivityRange {
+                start: TimePosition::from_ms(cursor),
+                end:   TimePosition::from_ms(total_ms),
+            });
+        }
+        silences
+    }
+
+    /// Suggest cutting silences longer than `min_silence_ms` from a clip.
+    ///
+    /// `samples` is the clip's source audio from the start of the media.
+    /// Cuts keep `padding_ms` of each pause and are mapped through the
+    /// clip's in point and speed onto the timeline, limited to the clip.
+    pub fn suggest_cuts(
+        &self, track_id: u64, clip: &TimelineClip, samples: &[f32], sample_rate: u32,
+        channels: usize,
+    ) -> Vec<EditSuggestion> {
+        let (min_silence_ms, padding_ms) = (self.min_silence_ms, self.padding_ms);
+        let speed = f64::from(clip.speed.max(f32::EPSILON));
+        let to_timeline = |source_ms: u64| {
+            let offset = (source_ms as f64 - clip.in_point.ms as f64) / speed;
+            (clip.start.ms as f64 + offset).clamp(clip.start.ms as f64, clip.end().ms as f64) as u64
+        };
+
+        self.silences(samples, sample_rate, channels)
+            .into_iter()
+            .filter(|s| s.duration().ms > min_silence_ms)
+            .filter_map(|s| {
+                let start = to_timeline(s.start.ms + padding_ms);
+                let end = to_timeline(s.end.ms.saturating_sub(padding_ms));
+                (end > start).then(|| EditSuggestion {
+                    track_id,
+                    clip_id: clip.id,
+                    start: TimePosition::from_ms(start),
+                    end: TimePosition::from_ms(end),
+                    kind: EditSuggestionKind::RemoveSilence,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Decoded audio of one clip's source.
+#[derive(Debug, Clone, Copy)]
+pub struct ClipAudio<'a> {
+    /// Interleaved samples from the start of the media.
+    pub samples:     &'a [f32],
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// Channel count.
+    pub channels:    usize,
+}
+
+impl ClipAudio<'_> {
+    /// Onset strength per 10 ms, mean removed: the rise in log RMS level,
+    /// so the envelope ignores gain differences between microphones.
+    fn onset_envelope(&self) -> Vec<f32> {
+        let channels = self.channels.max(1);
+        let frame_len = (self.sample_rate as usize * ENVELOPE_MS as usize / 1000).max(1);
+        let levels: Vec<f32> = self
+            .samples
+            .chunks(frame_len * channels)
+            .map(|frame| {
+                let power = frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32;
+                (power.sqrt() + 1e-4).ln()
+            })
+            .collect();
+        let mut envelope = Vec::with_capacity(levels.len());
+        envelope.push(0.0);
+        envelope.extend(levels.windows(2).map(|pair| (pair[1] - pair[0]).max(0.0)));
+        let mean = envelope.iter().sum::<f32>() / envelope.len() as f32;
+        envelope.iter_mut().for_each(|v| *v -= mean);
+        envelope
+    }
+}
+
+/// Result of [`sync_by_waveform`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaveformSync {
+    /// Source time in clip A of clip B's source time zero, in milliseconds;
+    /// negative if B starts before A.
+    pub offset_ms:  i64,
+    /// Match quality (0.0 - 1.0): correlation strength discounted by how
+    /// close the best competing offset came.
+    pub confidence: f32,
+}
+
+/// Find the offset that lines up clip B's audio with clip A's by
+/// cross-correlating their onset envelopes.
+///
+/// Searches every offset at which the recordings overlap by at least half
+/// of the shorter one, coarse to fine. Returns `None` if either recording
+/// is too short or nothing correlates.
+pub fn sync_by_waveform(clip_a: &ClipAudio<'_>, clip_b: &ClipAudio<'_>) -> Option<WaveformSync> {
+    let (a, b) = (clip_a.onset_envelope(), clip_b.onset_envelope());
+    let min_overlap = a.len().min(b.len()) / 2;
+    if min_overlap < COARSE_FACTOR * 2 {
+        return None;
+    }
+    let lags = -((b.len() - min_overlap) as isize)..=(a.len() - min_overlap) as isize;
+
+    let decimate =
+        |e: &[f32]| e.chunks(COARSE_FACTOR).map(|c| c.iter().sum::<f32>()).collect::<Vec<f32>>();
+    let (coarse_a, coarse_b) = (decimate(&a), decimate(&b));
+    let coarse_overlap = min_overlap / COARSE_FACTOR;
+    let coarse: Vec<(isize, f32)> = (-((coarse_b.len() - coarse_overlap) as isize)
+        ..=(coarse_a.len() - coarse_overlap) as isize)
+        .map(|lag| (lag, correlation(&coarse_a, &coarse_b, lag)))
+        .collect();
+
+    // Refine the strongest distinct coarse peaks; the runner-up measures
+    // how ambiguous the match is
+    let mut candidates: Vec<isize> = Vec::with_capacity(SYNC_CANDIDATES);
+    let mut ranked = coarse;
+    ranked.sort_by(|x, y| y.1.total_cmp(&x.1));
+    for &(lag, _) in &ranked {
+        if candidates.len() == SYNC_CANDIDATES {
+            break;
+        }
+        if candidates.iter().all(|c| c.abs_diff(lag) > 2) {
+            candidates.push(lag);
+        }
+    }
+    let span = COARSE_FACTOR as isize * 2;
+    let mut refined: Vec<Vec<(isize, f32)>> = candidates
+        .iter()
+        .map(|&coarse_lag| {
+            let center = coarse_lag * COARSE_FACTOR as isize;
+            (center - span..=center + span)
+                .filter(|lag| lags.contains(lag))
+                .map(|lag| (lag, correlation(&a, &b, lag)))
+                .collect()
+        })
+        .collect();
+    let best_of = |fine: &[(isize, f32)]| fine.iter().map(|&(_, r)| r).fold(f32::MIN, f32::max);
+    refined.sort_by(|x, y| best_of(y).total_cmp(&best_of(x)));
+    let runner_up = refined.get(1).map_or(0.0, |fine| best_of(fine).max(0.0));
+    let fine = refined.first()?;
+    let (index, &(lag, peak)) = fine.iter().enumerate().max_by(|x, y| x.1.1.total_cmp(&y.1.1))?;
+    if peak <= 0.0 {
+        return None;
+    }
+
+    // Parabolic refinement between neighbouring lags
+    let fraction = match (index.checked_sub(1).and_then(|i| fine.get(i)), fine.get(index + 1)) {
+        (Some(&(_, before)), Some(&(_, after))) => {
+            let denom = before - 2.0 * peak + after;
+            if denom < -f32::EPSILON {
+                (0.5 * (before - after) / denom).clamp(-0.5, 0.5)
+            } else {
+                0.0
+            }
+        },
+        _ => 0.0,
+    };
+    Some(WaveformSync {
+        offset_ms:  ((lag as f32 + fraction) * ENVELOPE_MS as f32).round() as i64,
+        confidence: (peak.min(1.0) * (1.0 - runner_up / peak)).clamp(0.0, 1.0),
+    })
+}
+
+/// One detected beat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Beat {
+    /// Beat time.
+    pub time:       TimePosition,
+    /// Onset strength at the beat relative to the strongest onset
+    /// (0.0 - 1.0); beats the tracker filled in over quiet passages score
+    /// low.
+    pub confidence: f32,
+}
+
+/// Result of [`BeatDetector::detect`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeatGrid {
+    /// Estimated tempo in beats per minute.
+    pub tempo_bpm:  f32,
+    /// How periodic the onsets are (0.0 - 1.0).
+    pub confidence: f32,
+    /// Beats in time order.
+    pub beats:      Vec<Beat>,
+}
+
+impl BeatGrid {
+    /// Returns the beat times.
+    pub fn times(&self) -> Vec<TimePosition> {
+        self.beats.iter().map(|b| b.time).collect()
+    }
+
+    /// Add a `Beat` marker at every beat, commented with its confidence.
+    pub fn add_markers(&self, markers: &mut MarkerManager) -> Vec<MarkerId> {
+        self.beats
+            .iter()
+            .map(|beat| {
+                let id = markers.add_marker(beat.time, MarkerType::Beat);
+                if let Some(marker) = markers.get_marker_mut(id) {
+                    marker.set_comment(format!(
+                        "{:.1} BPM, confidence {:.2}",
+                        self.tempo_bpm, beat.confidence
+                    ));
+                }
+                id
+            })
+            .collect()
+    }
+}
+
+/// Onset-based tempo and beat detector.
+///
+/// The tempo is the strongest periodicity of the onset envelope, weighted
+/// towards 120 BPM so half and double tempos lose ties. Beats are then
+/// placed by dynamic programming: each beat is on a strong onset and about
+/// one period after the previous one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeatDetector {
+    /// Slowest tempo considered.
+    pub min_bpm:   f32,
+    /// Fastest tempo considered.
+    pub max_bpm:   f32,
+    /// How strongly beat spacing is held to the tempo; higher values
+    /// ignore off-beat onsets more.
+    pub tightness: f32,
+}
+
+impl Default for BeatDetector {
+    fn default() -> Self {
+        Self { min_bpm: 60.0, max_bpm: 200.0, tightness: 100.0 }
+    }
+}
+
+impl BeatDetector {
+    /// Detect beats in source time. Returns `None` if the audio is shorter
+    /// than two beats at the slowest tempo or has no periodic onsets.
+    pub fn detect(&self, audio: &ClipAudio<'_>) -> Option<BeatGrid> {
+        let mut envelope = audio.onset_envelope();
+        let spread = (envelope.iter().map(|v| v * v).sum::<f32>() / envelope.len() as f32).sqrt();
+        if spread <= f32::EPSILON {
+            return None;
+        }
+        envelope.iter_mut().for_each(|v| *v /= spread);
+
+        let frames_per_minute = 60_000.0 / ENVELOPE_MS as f32;
+        let min_lag = (frames_per_minute / self.max_bpm.max(1.0)).floor().max(1.0) as usize;
+        let max_lag = (frames_per_minute / self.min_bpm.max(1.0)).ceil() as usize;
+        if envelope.len() < max_lag * 2 {
+            return None;
+        }
+        let autocorrelation: Vec<f32> = (0..=max_lag + 1)
+            .map(|lag| {
+                envelope.iter().zip(&envelope[lag..]).map(|(a, b)| a * b).sum::<f32>()
+                    / (envelope.len() - lag) as f32
+            })
+            .collect();
+        let weighted = |lag: usize| {
+            let octaves = (frames_per_minute / lag as f32 / 120.0).log2();
+            autocorrelation[lag] * (-0.5 * octaves * octaves).exp()
+        };
+        let lag = (min_lag..=max_lag).max_by(|&a, &b| weighted(a).total_cmp(&weighted(b)))?;
+        let peak = autocorrelation[lag];
+        if peak <= 0.0 {
+            return None;
+        }
+        let (before, after) = (autocorrelation[lag - 1], autocorrelation[lag + 1]);
+        let denom = before - 2.0 * peak + after;
+        let fraction = if denom < -f32::EPSILON {
+            (0.5 * (before - after) / denom).clamp(-0.5, 0.5)
+        } else {
+            0.0
+        };
+        let period = lag as f32 + fraction;
+
+        let beats = self.track(&envelope, period);
+        let strongest = envelope.iter().copied().fold(f32::EPSILON, f32::max);
+        Some(BeatGrid {
+            tempo_bpm:  frames_per_minute / period,
+            confidence: (peak / autocorrelation[0]).clamp(0.0, 1.0),
+            beats:      beats
+                .into_iter()
+                .map(|i| Beat {
+                    time:       TimePosition::from_ms(i as u64 * u64::from(ENVELOPE_MS)),
+                    confidence: (envelope[i] / strongest).clamp(0.0, 1.0),
+                })
+                .collect(),
+        })
+    }
+
+    /// Detect beats in a clip's source audio, mapped through its in point
+    /// and speed onto the timeline. Beats outside the clip are dropped.
+    pub fn detect_clip(&self, clip: &TimelineClip, audio: &ClipAudio<'_>) -> Option<BeatGrid> {
+        let mut grid = self.detect(audio)?;
+        let speed = f64::from(clip.speed.max(f32::EPSILON));
+        grid.tempo_bpm *= speed as f32;
+        grid.beats.retain_mut(|beat| {
+            let offset = (beat.time.ms as f64 - clip.in_point.ms as f64) / speed;
+            let time = clip.start.ms as f64 + offset;
+            beat.time = TimePosition::from_ms(time.max(0.0).round() as u64);
+            offset >= 0.0 && time < clip.end().ms as f64
+        });
+        Some(grid)
+    }
+
+    /// Beat frames of an onset envelope with a period in frames.
+    fn track(&self, envelope: &[f32], period: f32) -> Vec<usize> {
+        let mut score = envelope.to_vec();
+        let mut previous: Vec<Option<usize>> = vec![None; envelope.len()];
+        let (shortest, longest) =
+            ((period / 2.0).round() as usize, (period * 2.0).round() as usize);
+        for i in shortest.max(1)..envelope.len() {
+            let best = (i.saturating_sub(longest)..=i - shortest.max(1))
+                .map(|prev| {
+                    let spacing = ((i - prev) as f32 / period).ln();
+                    (prev, score[prev] - self.tightness * spacing * spacing)
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((prev, value)) = best
+                && value > 0.0
+            {
+                score[i] += value;
+                previous[i] = Some(prev);
+            }
+        }
+
+        // Start from the best beat in the last period and walk back
+        let tail = envelope.len().saturating_sub(period.ceil() as usize);
+        let mut beat = (tail..envelope.len()).max_by(|&a, &b| score[a].total_cmp(&score[b]));
+        let mut beats = Vec::new();
+        while let Some(i) = beat {
+            beats.push(i);
+            beat = previous[i];
+        }
+        beats.reverse();
+        beats
+    }
+}
+
+/// Normalized correlation of `b` against `a` shifted so `b[i]` meets
+/// `a[i + lag]`, over their overlap.
+fn correlation(a: &[f32], b: &[f32], lag: isize) -> f32 {
+    let start = (-lag).max(0) as usize;
+    let end = (a.len() as isize - lag).min(b.len() as isize).max(0) as usize;
+    let (mut dot, mut energy_a, mut energy_b) = (0.0f32, 0.0f32, 0.0f32);
+    for i in start..end {
+        let (x, y) = (a[(i as isize + lag) as usize], b[i]);
+        dot += x * y;
+        energy_a += x * x;
+        energy_b += y * y;
+    }
+    let norm = (energy_a * energy_b).sqrt();
+    if norm > f32::EPSILON { dot / norm } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        implementation::{RippleSync, TimelineManager},
+        types::TrackType,
+    };
+
+    const RATE: u32 = 16_000;
+
+    /// Mono buffer of `(milliseconds, voiced)` segments over a noise floor.
+    fn speech(segments: &[(u64, bool)]) -> Vec<f32> {
+        let mut samples = Vec::new();
+        let mut seed = 1u32;
+        for &(ms, voiced) in segments {
+            for _ in 0..ms * u64::from(RATE) / 1000 {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let noise = (seed >> 8) as f32 / (1 << 24) as f32 - 0.5;
+                let tone = (samples.len() as f32 * 0.07).sin() * 0.3;
+                samples.push(noise * 0.002 + if voiced { tone } else { 0.0 });
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_detect_voice_and_silence() {
+        let samples =
+            speech(&[(500, false), (1000, true), (100, false), (800, true), (1600, false)]);
+        let detector = VoiceActivityDetector::default();
+        let voice = detector.detect(&samples, RATE, 1);
+        // The 100 ms gap is bridged by the hangover
+        assert_eq!(voice.len(), 1);
+        assert!(voice[0].start.ms.abs_diff(500) <= 20);
+        assert!(voice[0].end.ms.abs_diff(2400 + 200) <= 40);
+
+        let silences = detector.silences(&samples, RATE, 1);
+        assert_eq!(silences.len(), 2);
+        assert_eq!(silences[0].start.ms, 0);
+        assert_eq!(silences[1].end.ms, 4000);
+
+        // Continuous speech has no pauses to cut
+        let samples = speech(&[(2000, true)]);
+        assert_eq!(detector.silences(&samples, RATE, 1), Vec::new());
+    }
+
+    #[test]
+    fn test_suggested_cuts_apply_in_bulk() {
+        let samples = speech(&[(300, true), (1500, false), (400, true), (200, false), (500, true)]);
+        let mut timeline = TimelineManager::new();
+        let video = timeline.add_track("V1", TrackType::Video);
+        let audio = timeline.add_track("A1", TrackType::Audio);
+        let mut clip =
+            TimelineClip::new(2, 1, TimePosition::from_ms(1000), TimePosition::from_ms(2900));
+        clip.in_point = TimePosition::from_ms(0);
+        timeline.add_clip(audio, clip.clone()).expect("test assertion");
+        let picture = TimelineClip { id: 1, ..clip.clone() };
+        timeline.add_clip(video, picture).expect("test assertion");
+
+        let detector =
+            VoiceActivityDetector { min_silence_ms: 500, padding_ms: 100, ..Default::default() };
+        let suggestions = detector.suggest_cuts(audio, &clip, &samples, RATE, 1);
+        assert_eq!(suggestions.len(), 1);
+        let cut = &suggestions[0];
+        assert_eq!(cut.kind, EditSuggestionKind::RemoveSilence);
+        assert_eq!(cut.clip_id, 2);
+        assert!(cut.start.ms.abs_diff(1000 + 300 + 200 + 100) <= 40);
+        assert!(cut.end.ms.abs_diff(1000 + 1800 - 100) <= 40);
+
+        let removed = cut.duration().ms;
+        let applied = timeline
+            .apply_suggestions(&suggestions, RippleSync::AllTracks)
+            .expect("test assertion");
+        assert_eq!(applied, 1);
+        assert_eq!(timeline.duration().ms, 3900 - removed);
+        for track_id in [video, audio] {
+            let clips = &timeline.get_track(track_id).expect("test assertion").clips;
+            assert_eq!(clips.len(), 2);
+            assert_eq!(clips[0].end(), clips[1].start);
+            assert_eq!(clips[1].in_point.ms, cut.end.ms - 1000);
+        }
+
+        let bad = EditSuggestion { track_id: 99, ..cut.clone() };
+        assert!(timeline.apply_suggestions(&[bad], RippleSync::Track).is_err());
+    }
+
+    /// Mono noise bursts of varied length and level, like claps and speech.
+    fn bursts(rate: u32, seconds: u32, seed: u32) -> Vec<f32> {
+        let mut seed = seed;
+        let mut random = move || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f32 / (1 << 24) as f32
+        };
+        let mut samples = vec![0.0; (rate * seconds) as usize];
+        let mut at = 0;
+        while at < samples.len() {
+            let length = (rate as f32 * (0.03 + 0.3 * random())) as usize;
+            let level = 0.1 + 0.6 * random();
+            for sample in samples.iter_mut().skip(at).take(length) {
+                *sample = (random() - 0.5) * level;
+            }
+            at += length + (rate as f32 * (0.1 + 0.5 * random())) as usize;
+        }
+        samples
+    }
+
+    #[test]
+    fn test_sync_by_waveform_aligns_and_links() {
+        let camera = bursts(16_000, 20, 7);
+        // External recorder: starts 1.234 s into the camera take, at half
+        // the sample rate, quieter and with its own hiss
+        let recorder: Vec<f32> = camera[19_744..]
+            .iter()
+            .step_by(2)
+            .enumerate()
+            .map(|(i, s)| s * 0.4 + ((i * 7919 % 200) as f32 / 200.0 - 0.5) * 0.004)
+            .collect();
+        let clip_a = ClipAudio { samples: &camera, sample_rate: 16_000, channels: 1 };
+        let clip_b = ClipAudio { samples: &recorder, sample_rate: 8_000, channels: 1 };
+
+        let sync = sync_by_waveform(&clip_a, &clip_b).expect("test assertion");
+        assert!(sync.offset_ms.abs_diff(1234) <= 5, "offset {}", sync.offset_ms);
+        assert!(sync.confidence > 0.5, "confidence {}", sync.confidence);
+        let reverse = sync_by_waveform(&clip_b, &clip_a).expect("test assertion");
+        assert!(reverse.offset_ms.abs_diff(-1234) <= 5);
+
+        let unrelated = bursts(8_000, 18, 99);
+        let other = ClipAudio { samples: &unrelated, sample_rate: 8_000, channels: 1 };
+        let weak = sync_by_waveform(&clip_a, &other).map_or(0.0, |s| s.confidence);
+        assert!(weak < sync.confidence / 2.0, "unrelated confidence {weak}");
+        let short = ClipAudio { samples: &camera[..100], sample_rate: 16_000, channels: 1 };
+        assert_eq!(sync_by_waveform(&clip_a, &short), None);
+
+        let mut timeline = TimelineManager::new();
+        let video = timeline.add_track("V1", TrackType::Video);
+        let audio = timeline.add_track("A1", TrackType::Audio);
+        timeline
+            .add_clip(
+                video,
+                TimelineClip::new(1, 1, TimePosition::from_ms(2000), TimePosition::from_ms(20_000)),
+            )
+            .expect("test assertion");
+        let mut external =
+            TimelineClip::new(2, 2, TimePosition::from_ms(0), TimePosition::from_ms(10_000));
+        external.in_point = TimePosition::from_ms(500);
+        timeline.add_clip(audio, external).expect("test assertion");
+
+        let group = timeline.align_and_link(1, 2, sync.offset_ms).expect("test assertion");
+        let clip = &timeline.get_track(audio).expect("test assertion").clips[0];
+        assert_eq!(clip.start.ms, (2000 + 500 + sync.offset_ms) as u64);
+        assert_eq!(timeline.group_of(2).map(|g| g.id), Some(group));
+        assert!(timeline.align_and_link(1, 2, -10_000).is_err());
+    }
+
+    /// Mono 16 kHz drum loop: a decaying click every beat and a softer
+    /// one on each off-beat.
+    fn clicks(bpm: f32, seconds: f32) -> Vec<f32> {
+        let period = (RATE as f32 * 60.0 / bpm) as usize;
+        let mut seed = 7u32;
+        (0..(RATE as f32 * seconds) as usize)
+            .map(|i| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let noise = (seed >> 8) as f32 / (1 << 24) as f32 - 0.5;
+                let phase = i % period;
+                let hit = |start: usize, gain: f32| {
+                    let t = phase.wrapping_sub(start);
+                    if t < 800 { gain * (-(t as f32) / 150.0).exp() } else { 0.0 }
+                };
+                noise * (0.01 + hit(0, 0.8) + hit(period / 2, 0.25))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_beat_detection_and_snapping() {
+        let samples = clicks(120.0, 8.0);
+        let audio = ClipAudio { samples: &samples, sample_rate: RATE, channels: 1 };
+        let grid = BeatDetector::default().detect(&audio).expect("test assertion");
+        assert!((grid.tempo_bpm - 120.0).abs() < 2.0, "{grid:?}");
+        assert!(grid.confidence > 0.1);
+        assert!(grid.beats.len() >= 15 && grid.beats.len() <= 16, "{:?}", grid.beats.len());
+        for beat in &grid.beats {
+            let off = beat.time.ms % 500;
+            assert!(off.min(500 - off) <= 20, "{beat:?}");
+            assert!(beat.confidence > 0.5);
+        }
+        assert!(
+            BeatDetector::default().detect(&ClipAudio { samples: &[0.0; 100], ..audio }).is_none()
+        );
+
+        // Half speed from 2 s into the source: 60 BPM on the timeline
+        let mut clip =
+            TimelineClip::new(1, 1, TimePosition::from_ms(10_000), TimePosition::from_ms(4_000));
+        clip.in_point = TimePosition::from_ms(2_000);
+        clip.speed = 0.5;
+        let grid = BeatDetector::default().detect_clip(&clip, &audio).expect("test assertion");
+        assert!((grid.tempo_bpm - 60.0).abs() < 1.0);
+        assert!(grid.beats.iter().all(|b| (10_000..14_000).contains(&b.time.ms)));
+        assert!(grid.beats[0].time.ms.abs_diff(10_000) <= 40);
+        assert_eq!(grid.beats.len(), 4);
+
+        let mut markers = MarkerManager::new();
+        let ids = grid.add_markers(&mut markers);
+        assert_eq!(ids.len(), 4);
+        let marker = markers.get_marker(ids[1]).expect("test assertion");
+        assert_eq!(marker.marker_type(), MarkerType::Beat);
+        assert!(marker.comment().starts_with("60."), "{}", marker.comment());
+
+        let mut timeline = TimelineManager::new();
+        let track = timeline.add_track("V1", TrackType::Video);
+        for (id, start, duration) in [(1, 0, 1_960), (2, 1_960, 1_080), (3, 3_040, 1_000)] {
+            let mut clip = TimelineClip::new(
+                id,
+                id,
+                TimePosition::from_ms(start),
+                TimePosition::from_ms(duration),
+            );
+            clip.in_point = TimePosition::from_ms(1_000);
+            timeline.add_clip(track, clip).expect("test assertion");
+        }
+        let beats: Vec<TimePosition> = (0..10).map(|b| TimePosition::from_ms(b * 500)).collect();
+
+        // A track locked through its group keeps its cuts
+        let group = timeline.group_tracks("Picture", &[track]).expect("test assertion");
+        timeline.set_track_group_locked(group, true);
+        assert!(timeline.snap_cuts_to_beats(&beats, TimePosition::from_ms(50)).is_empty());
+        timeline.set_track_group_locked(group, false);
+
+        let moved = timeline.snap_cuts_to_beats(&beats, TimePosition::from_ms(50));
+        assert_eq!(moved, [1, 2]);
+        let clips = &timeline.get_track(track).expect("test assertion").clips;
+        assert_eq!((clips[0].end().ms, clips[1].start.ms), (2_000, 2_000));
+        assert_eq!(clips[1].in_point.ms, 1_040);
+        assert_eq!((clips[1].end().ms, clips[2].start.ms), (3_000, 3_000));
+        assert_eq!(clips[2].in_point.ms, 960);
+        assert!(timeline.snap_cuts_to_beats(&beats, TimePosition::from_ms(50)).is_empty());
+    }
+}
@@ -2,9 +2,20 @@
 //! GAP-220-B-002: Audio Mixing System
 //!
 //! Features: Track mixing, volume control, pan, EQ, compression,
-//! meters, ducking, and real-time audio monitoring.
-
-use crate::errors::VideoEditorResult;
+//! meters, ducking, real-time audio monitoring, and bus routing to
+//! hardware outputs with monitoring downmixes.
+
+use super::{
+    noise_reduction::{NoiseProfile, SpectralDenoiser},
+    output_routing::{DownmixMatrix, HardwareOutput, OutputRouting},
+    preview_manager::{AudioMonitor, PlaybackSpeed, PlaybackState},
+    timeline::TimelineManager,
+};
+use crate::{
+    errors::VideoEditorResult,
+    flexforge::{MeterSnapshot, VideoEditorMetrics},
+    types::{ClipPitch, FadeShape, TimePosition, timeline::TimelineClip},
+};
 
 /// Unique identifier for an audio bus.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -129,6 +140,314 @@ impl AudioMeterLevels {
     }
 }
 
+/// Loudness measurement results (ITU-R BS.1770 / EBU R128).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoudnessReading {
+    /// Momentary loudness over the last 400 ms (LUFS).
+    pub momentary_lufs:  f32,
+    /// Short-term loudness over the last 3 s (LUFS).
+    pub short_term_lufs: f32,
+    /// Gated integrated loudness since the last reset (LUFS).
+    pub integrated_lufs: f32,
+    /// Maximum true peak since the last reset (dBTP).
+    pub true_peak_dbtp:  f32,
+}
+
+impl Default for LoudnessReading {
+    fn default() -> Self {
+        Self {
+            momentary_lufs:  LoudnessMeter::SILENCE_LUFS,
+            short_term_lufs: LoudnessMeter::SILENCE_LUFS,
+            integrated_lufs: LoudnessMeter::SILENCE_LUFS,
+            true_peak_dbtp:  LoudnessMeter::SILENCE_LUFS,
+        }
+    }
+}
+
+/// Biquad filter section (direct form I).
+#[derive(Debug, Clone, Copy, Default)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    x1: f64,
+    x2: f64,
+    y1: f64,
+    y2: f64,
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2
+            - self.a1 * self.y1
+            - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+/// Per-channel loudness state: K-weighting filters and true-peak history.
+#[derive(Debug, Clone)]
+struct LoudnessChannel {
+    shelf:    Biquad,
+    highpass: Biquad,
+    weight:   f64,
+    history:  [f32; 4],
+}
+
+/// EBU R128 loudness meter.
+///
+/// K-weights each channel, accumulates mean square power in 100 ms steps and
+/// derives momentary (400 ms), short-term (3 s) and gated integrated
+/// loudness per ITU-R BS.1770. True peak is estimated by 4x oversampling.
+#[derive(Debug, Clone)]
+pub struct LoudnessMeter {
+    /// Sample rate in Hz.
+    sample_rate: u32,
+    /// Channel filter state.
+    channels:    Vec<LoudnessChannel>,
+    /// Samples per 100 ms step.
+    step_len:    usize,
+    /// Samples accumulated in the current step.
+    step_pos:    usize,
+    /// Weighted sum of squares in the current step.
+    step_sum:    f64,
+    /// Mean square power of the last 30 steps (3 s), oldest first.
+    steps:       Vec<f64>,
+    /// Mean square power of each 400 ms gating block.
+    blocks:      Vec<f64>,
+    /// Maximum oversampled absolute sample value.
+    true_peak:   f32,
+}
+
+impl LoudnessMeter {
+    /// Reported loudness for silence.
+    pub const SILENCE_LUFS: f32 = -70.0;
+    /// Absolute gating threshold (LUFS).
+    const ABSOLUTE_GATE: f64 = -70.0;
+    /// Relative gating threshold (LU below the ungated level).
+    const RELATIVE_GATE: f64 = -10.0;
+    /// Number of 100 ms steps in the short-term window.
+    const SHORT_TERM_STEPS: usize = 30;
+    /// Number of 100 ms steps in a momentary/gating block.
+    const BLOCK_STEPS: usize = 4;
+
+    /// Creates a meter for the given sample rate and channel count.
+    ///
+    /// For 5.1 input the LFE channel (index 3) is ignored and the surround
+    /// channels get the +1.5 dB weighting from BS.1770.
+    #[must_use]
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        let sample_rate = sample_rate.max(1);
+        let channels = (0..channels.max(1))
+            .map(|i| {
+                let weight = match (channels, i) {
+                    (6, 3) => 0.0,
+                    (6, 4 | 5) => 1.41,
+                    _ => 1.0,
+                };
+                LoudnessChannel {
+                    shelf: Self::shelf_filter(sample_rate),
+                    highpass: Self::highpass_filter(sample_rate),
+                    weight,
+                    history: [0.0; 4],
+                }
+            })
+            .collect();
+
+        Self {
+            sample_rate,
+            channels,
+            step_len: (sample_rate as usize / 10).max(1),
+            step_pos: 0,
+            step_sum: 0.0,
+            steps: Vec::with_capacity(Self::SHORT_TERM_STEPS),
+            blocks: Vec::new(),
+            true_peak: 0.0,
+        }
+    }
+
+    /// Stage 1 of the K-weighting: high shelf (+4 dB above ~1.7 kHz).
+    fn shelf_filter(sample_rate: u32) -> Biquad {
+        let f0 = 1_681.974_450_955_533;
+        let gain_db = 3.999_843_853_973_347;
+        let q = 0.707_175_236_955_419_6;
+
+        let k = (core::f64::consts::PI * f0 / f64::from(sample_rate)).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.499_666_774_154_541_6);
+        let a0 = 1.0 + k / q + k * k;
+
+        Biquad {
+            b0: (vh + vb * k / q + k * k) / a0,
+            b1: 2.0 * (k * k - vh) / a0,
+            b2: (vh - vb * k / q + k * k) / a0,
+            a1: 2.0 * (k * k - 1.0) / a0,
+            a2: (1.0 - k / q + k * k) / a0,
+            ..Biquad::default()
+        }
+    }
+
+    /// Stage 2 of the K-weighting: RLB high-pass (~38 Hz).
+    fn highpass_filter(sample_rate: u32) -> Biquad {
+        let f0 = 38.135_470_876_024_44;
+        let q = 0.500_327_037_323_877_3;
+
+        let k = (core::f64::consts::PI * f0 / f64::from(sample_rate)).tan();
+        let a0 = 1.0 + k / q + k * k;
+
+        Biquad {
+            b0: 1.0,
+            b1: -2.0,
+            b2: 1.0,
+            a1: 2.0 * (k * k - 1.0) / a0,
+            a2: (1.0 - k / q + k * k) / a0,
+            ..Biquad::default()
+        }
+    }
+
+    /// Returns the sample rate.
+    #[must_use]
+    pub const fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns the channel count.
+    #[must_use]
+    pub fn channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Feeds interleaved samples into the meter.
+    pub fn process(&mut self, samples: &[f32]) {
+        let channel_count = self.channels.len();
+
+        for frame in samples.chunks_exact(channel_count) {
+            let mut sum = 0.0;
+            for (channel, &sample) in self.channels.iter_mut().zip(frame) {
+                let filtered = channel.highpass.process(channel.shelf.process(f64::from(sample)));
+                sum += channel.weight * filtered * filtered;
+
+                channel.history.rotate_left(1);
+                channel.history[3] = sample;
+                self.true_peak = self.true_peak.max(Self::oversampled_peak(&channel.history));
+            }
+
+            self.step_sum += sum;
+            self.step_pos += 1;
+            if self.step_pos >= self.step_len {
+                self.finish_step();
+            }
+        }
+    }
+
+    /// Peak of the segment between the two middle samples, interpolated at
+    /// 4x with a Catmull-Rom spline.
+    fn oversampled_peak(h: &[f32; 4]) -> f32 {
+        let mut peak = h[2].abs();
+        for t in [0.25_f32, 0.5, 0.75] {
+            let t2 = t * t;
+            let t3 = t2 * t;
+            let v = 0.5
+                * (2.0 * h[1]
+                    + (h[2] - h[0]) * t
+                    + (2.0 * h[0] - 5.0 * h[1] + 4.0 * h[2] - h[3]) * t2
+                    + (3.0 * h[1] - h[0] - 3.0 * h[2] + h[3]) * t3);
+            peak = peak.max(v.abs());
+        }
+        peak
+    }
+
+    fn finish_step(&mut self) {
+        if self.steps.len() == Self::SHORT_TERM_STEPS {
+            self.steps.remove(0);
+        }
+        self.steps.push(self.step_sum / self.step_len as f64);
+        self.step_sum = 0.0;
+        self.step_pos = 0;
+
+        // 400 ms gating blocks with 75% overlap
+        if self.steps.len() >= Self::BLOCK_STEPS {
+            let block = Self::mean(&self.steps[self.steps.len() - Self::BLOCK_STEPS..]);
+            self.blocks.push(block);
+        }
+    }
+
+    fn mean(powers: &[f64]) -> f64 {
+        if powers.is_empty() { 0.0 } else { powers.iter().sum::<f64>() / powers.len() as f64 }
+    }
+
+    fn power_to_lufs(power: f64) -> f64 {
+        if power <= 0.0 { f64::NEG_INFINITY } else { -0.691 + 10.0 * power.log10() }
+    }
+
+    fn to_reading(lufs: f64) -> f32 {
+        lufs.max(f64::from(Self::SILENCE_LUFS)) as f32
+    }
+
+    /// Returns the momentary loudness (LUFS).
+    #[must_use]
+    pub fn momentary(&self) -> f32 {
+        let start = self.steps.len().saturating_sub(Self::BLOCK_STEPS);
+        Self::to_reading(Self::power_to_lufs(Self::mean(&self.steps[start..])))
+    }
+
+    /// Returns the short-term loudness (LUFS).
+    #[must_use]
+    pub fn short_term(&self) -> f32 {
+        Self::to_reading(Self::power_to_lufs(Self::mean(&self.steps)))
+    }
+
+    /// Returns the gated integrated loudness (LUFS).
+    #[must_use]
+    pub fn integrated(&self) -> f32 {
+        let absolute: Vec<f64> = self
+            .blocks
+            .iter()
+            .copied()
+            .filter(|&p| Self::power_to_lufs(p) > Self::ABSOLUTE_GATE)
+            .collect();
+        if absolute.is_empty() {
+            return Self::SILENCE_LUFS;
+        }
+
+        let relative_gate = Self::power_to_lufs(Self::mean(&absolute)) + Self::RELATIVE_GATE;
+        let gated: Vec<f64> =
+            absolute.into_iter().filter(|&p| Self::power_to_lufs(p) > relative_gate).collect();
+        Self::to_reading(Self::power_to_lufs(Self::mean(&gated)))
+    }
+
+    /// Returns the maximum true peak (dBTP).
+    #[must_use]
+    pub fn true_peak_dbtp(&self) -> f32 {
+        if self.true_peak <= 0.0 {
+            return Self::SILENCE_LUFS;
+        }
+        (20.0 * self.true_peak.log10()).max(Self::SILENCE_LUFS)
+    }
+
+    /// Returns all current readings.
+    #[must_use]
+    pub fn reading(&self) -> LoudnessReading {
+        LoudnessReading {
+            momentary_lufs:  self.momentary(),
+            short_term_lufs: self.short_term(),
+            integrated_lufs: self.integrated(),
+            true_peak_dbtp:  self.true_peak_dbtp(),
+        }
+    }
+
+    /// Resets all measurements.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.sample_rate, self.channels.len());
+    }
+}
+
 /// Audio pan law determines how volume is distributed during panning.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum PanLaw {
@@ -179,6 +498,195 @@ impl PanLaw {
     }
 }
 
+/// Editable fade gain curve.
+///
+/// A cubic bezier from (0, 0) to (1, 1) where x is fade progress and y is
+/// gain, shaped by two control points (same convention as CSS easing).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FadeCurve {
+    /// First control point (progress, gain).
+    pub p1: (f32, f32),
+    /// Second control point (progress, gain).
+    pub p2: (f32, f32),
+}
+
+impl FadeCurve {
+    /// Straight line.
+    pub const LINEAR: Self = Self { p1: (1.0 / 3.0, 1.0 / 3.0), p2: (2.0 / 3.0, 2.0 / 3.0) };
+    /// Slow start, fast finish.
+    pub const EASE_IN: Self = Self { p1: (0.42, 0.0), p2: (1.0, 1.0) };
+    /// Fast start, slow finish.
+    pub const EASE_OUT: Self = Self { p1: (0.0, 0.0), p2: (0.58, 1.0) };
+    /// Slow start and finish.
+    pub const S_CURVE: Self = Self { p1: (0.42, 0.0), p2: (0.58, 1.0) };
+    /// Quarter sine, within about 1% of `sin(progress * PI / 2)`.
+    pub const EQUAL_POWER: Self =
+        Self { p1: (1.0 / 3.0, core::f32::consts::FRAC_PI_6), p2: (2.0 / 3.0, 1.0) };
+
+    /// Creates a curve from two control points.
+    ///
+    /// Progress coordinates are clamped to 0.0..=1.0 so the curve stays a
+    /// function of progress; gain coordinates may overshoot.
+    #[must_use]
+    pub fn new(p1: (f32, f32), p2: (f32, f32)) -> Self {
+        Self { p1: (p1.0.clamp(0.0, 1.0), p1.1), p2: (p2.0.clamp(0.0, 1.0), p2.1) }
+    }
+
+    /// Evaluates the gain (0.0 to 1.0) at a fade progress (0.0 to 1.0).
+    #[must_use]
+    pub fn evaluate(&self, progress: f32) -> f32 {
+        let x = progress.clamp(0.0, 1.0);
+        if x <= 0.0 {
+            return 0.0;
+        }
+        if x >= 1.0 {
+            return 1.0;
+        }
+
+        // Solve bezier_x(t) = x by bisection; x(t) is monotonic since the
+        // control point x coordinates lie within 0..=1.
+        let (mut lo, mut hi) = (0.0_f32, 1.0_f32);
+        let mut t = x;
+        for _ in 0..24 {
+            t = (lo + hi) * 0.5;
+            if Self::bezier(self.p1.0, self.p2.0, t) < x {
+                lo = t;
+            } else {
+                hi = t;
+            }
+        }
+        Self::bezier(self.p1.1, self.p2.1, t).clamp(0.0, 1.0)
+    }
+
+    fn bezier(a: f32, b: f32, t: f32) -> f32 {
+        let mt = 1.0 - t;
+        3.0 * mt * mt * t * a + 3.0 * mt * t * t * b + t * t * t
+    }
+}
+
+impl Default for FadeCurve {
+    fn default() -> Self {
+        Self::LINEAR
+    }
+}
+
+impl From<FadeShape> for FadeCurve {
+    fn from(shape: FadeShape) -> Self {
+        match shape {
+            FadeShape::Linear => Self::LINEAR,
+            FadeShape::EqualPower => Self::EQUAL_POWER,
+            FadeShape::SCurve => Self::S_CURVE,
+        }
+    }
+}
+
+/// Unique identifier for an audio fade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AudioFadeId(u64);
+
+impl AudioFadeId {
+    /// Creates a new audio fade ID.
+    #[must_use]
+    pub const fn new(id: u64) -> Self {
+        Self(id)
+    }
+
+    /// Returns the inner ID value.
+    #[must_use]
+    pub const fn inner(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Kind of audio fade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioFadeKind {
+    /// Fade from silence.
+    In,
+    /// Fade to silence.
+    Out,
+    /// Fade between an outgoing and an incoming clip.
+    Cross,
+}
+
+/// A fade or crossfade instance on an audio track.
+///
+/// Each side has its own curve: the incoming gain follows `fade_in`, the
+/// outgoing gain is `1 - fade_out` so both curves describe how far the
+/// transition has progressed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioFade {
+    /// Fade ID.
+    pub id:       AudioFadeId,
+    /// Track the fade belongs to.
+    pub track_id: u64,
+    /// Fade kind.
+    pub kind:     AudioFadeKind,
+    /// Timeline start.
+    pub start:    TimePosition,
+    /// Fade length.
+    pub duration: TimePosition,
+    /// Curve of the outgoing side.
+    pub fade_out: FadeCurve,
+    /// Curve of the incoming side.
+    pub fade_in:  FadeCurve,
+}
+
+impl AudioFade {
+    /// Creates a fade with linear curves.
+    #[must_use]
+    pub fn new(
+        id: AudioFadeId, track_id: u64, kind: AudioFadeKind, start: TimePosition,
+        duration: TimePosition,
+    ) -> Self {
+        Self {
+            id,
+            track_id,
+            kind,
+            start,
+            duration,
+            fade_out: FadeCurve::LINEAR,
+            fade_in: FadeCurve::LINEAR,
+        }
+    }
+
+    /// Returns the timeline end.
+    #[must_use]
+    pub fn end(&self) -> TimePosition {
+        self.start + self.duration
+    }
+
+    /// Returns whether a timeline time falls inside the fade.
+    #[must_use]
+    pub fn contains(&self, time: TimePosition) -> bool {
+        time.ms >= self.start.ms && time.ms < self.end().ms
+    }
+
+    /// Returns the fade progress (0.0 to 1.0) at a timeline time in ms.
+    #[must_use]
+    pub fn progress_at(&self, time_ms: f64) -> f32 {
+        if self.duration.ms == 0 {
+            return if time_ms < self.start.ms as f64 { 0.0 } else { 1.0 };
+        }
+        ((time_ms - self.start.ms as f64) / self.duration.ms as f64).clamp(0.0, 1.0) as f32
+    }
+
+    /// Returns the (outgoing, incoming) gains at a timeline time in ms.
+    ///
+    /// Fade-ins have no outgoing side and fade-outs no incoming side.
+    #[must_use]
+    pub fn gains_at(&self, time_ms: f64) -> (f32, f32) {
+        let progress = self.progress_at(time_ms);
+        let outgoing = 1.0 - self.fade_out.evaluate(progress);
+        let incoming = self.fade_in.evaluate(progress);
+        match self.kind {
+            AudioFadeKind::In => (0.0, incoming),
+            AudioFadeKind::Out => (outgoing, 0.0),
+            AudioFadeKind::Cross => (outgoing, incoming),
+        }
+    }
+}
+
 /// Audio track strip with volume, pan, and effects.
 #[derive(Debug, Clone)]
 pub struct AudioTrackStrip {
@@ -200,6 +708,8 @@ pub struct AudioTrackStrip {
     channels:   AudioChannelConfig,
     /// Current meter levels.
     meters:     AudioMeterLevels,
+    /// Loudness meter.
+    loudness:   LoudnessMeter,
     /// Insert effects.
     inserts:    Vec<AudioInsert>,
     /// Send levels to aux buses.
@@ -210,13 +720,15 @@ pub struct AudioTrackStrip {
 #[derive(Debug, Clone)]
 pub struct AudioInsert {
     /// Insert slot index.
-    pub slot:       u8,
+    pub slot:          u8,
     /// Effect type.
-    pub effect:     AudioEffectType,
+    pub effect:        AudioEffectType,
     /// Effect parameters.
-    pub parameters: AudioEffectParams,
+    pub parameters:    AudioEffectParams,
     /// Whether insert is bypassed.
-    pub bypassed:   bool,
+    pub bypassed:      bool,
+    /// Learned noise profile for spectral noise reduction.
+    pub noise_profile: Option<NoiseProfile>,
 }
 
 /// Audio effect types.
@@ -244,6 +756,8 @@ pub enum AudioEffectType {
     HighPassFilter,
     /// Notch filter.
     NotchFilter,
+    /// Spectral noise reduction against a learned noise profile.
+    SpectralNoiseReduction,
 }
 
 /// Parameters for audio effects.
@@ -302,6 +816,16 @@ impl AudioEffectParams {
         params.set("knee", 6.0);
         params
     }
+
+    /// Creates default spectral noise reduction parameters.
+    #[must_use]
+    pub fn default_noise_reduction() -> Self {
+        let mut params = Self::new();
+        params.set("reduction", 12.0);
+        params.set("sensitivity", 1.5);
+        params.set("smoothing", 0.5);
+        params
+    }
 }
 
 /// Audio send to an auxiliary bus.
@@ -331,6 +855,7 @@ impl AudioTrackStrip {
             output_bus,
             channels: AudioChannelConfig::Stereo,
             meters: AudioMeterLevels::new(2),
+            loudness: LoudnessMeter::new(48000, 2),
             inserts: Vec::new(),
             sends: Vec::new(),
         }
@@ -424,19 +949,37 @@ impl AudioTrackStrip {
         &self.meters
     }
 
-    /// Updates meter levels with new audio data.
+    /// Returns the loudness meter.
+    #[must_use]
+    pub fn loudness(&self) -> &LoudnessMeter {
+        &self.loudness
+    }
+
+    /// Updates meter levels and loudness with new audio data.
     pub fn update_meters(&mut self, samples: &[f32]) {
         self.meters.update(samples, self.channels.channel_count() as usize);
+        self.loudness.process(samples);
+    }
+
+    /// Returns the meter levels as published to the panel.
+    #[must_use]
+    pub fn meter_snapshot(&self) -> MeterSnapshot {
+        meter_snapshot(&self.meters, &self.loudness)
     }
 
     /// Adds an insert effect.
     pub fn add_insert(&mut self, effect: AudioEffectType) -> u8 {
         let slot = self.inserts.len() as u8;
+        let parameters = match effect {
+            AudioEffectType::SpectralNoiseReduction => AudioEffectParams::default_noise_reduction(),
+            _ => AudioEffectParams::new(),
+        };
         self.inserts.push(AudioInsert {
             slot,
             effect,
-            parameters: AudioEffectParams::new(),
+            parameters,
             bypassed: false,
+            noise_profile: None,
         });
         slot
     }
@@ -461,6 +1004,11 @@ impl AudioTrackStrip {
         &self.inserts
     }
 
+    /// Gets a mutable insert effect by slot.
+    pub fn get_insert_mut(&mut self, slot: u8) -> Option<&mut AudioInsert> {
+        self.inserts.iter_mut().find(|i| i.slot == slot)
+    }
+
     /// Adds a send to an auxiliary bus.
     pub fn add_send(&mut self, bus_id: AudioBusId, level: f32, pre_fader: bool) {
         self.sends.push(AudioSend {
@@ -510,6 +1058,8 @@ pub struct AudioBus {
     output:   Option<AudioBusId>,
     /// Current meter levels.
     meters:   AudioMeterLevels,
+    /// Loudness meter.
+    loudness: LoudnessMeter,
     /// Insert effects.
     inserts:  Vec<AudioInsert>,
 }
@@ -540,6 +1090,7 @@ impl AudioBus {
             solo: false,
             output: None,
             meters: AudioMeterLevels::new(2),
+            loudness: LoudnessMeter::new(48000, 2),
             inserts: Vec::new(),
         }
     }
@@ -590,108 +1141,731 @@ impl AudioBus {
     pub fn meters(&self) -> &AudioMeterLevels {
         &self.meters
     }
-}
-
-/// The main audio mixer.
-pub struct AudioMixer {
-    /// Master output bus.
-    master:      AudioBus,
-    /// Auxiliary buses.
-    aux_buses:   Vec<AudioBus>,
-    /// Group buses.
-    group_buses: Vec<AudioBus>,
-    /// Track strips.
-    tracks:      Vec<AudioTrackStrip>,
-    /// Pan law setting.
-    pan_law:     PanLaw,
-    /// Sample rate.
-    sample_rate: u32,
-    /// Buffer size.
-    buffer_size: usize,
-    /// Next bus ID counter.
-    next_bus_id: u64,
-    /// Whether any track is soloed.
-    has_solo:    bool,
-}
 
-impl AudioMixer {
-    /// Creates a new audio mixer.
+    /// Returns the loudness meter.
     #[must_use]
-    pub fn new(sample_rate: u32, buffer_size: usize) -> Self {
-        Self {
-            master: AudioBus::master(),
-            aux_buses: Vec::new(),
-            group_buses: Vec::new(),
-            tracks: Vec::new(),
-            pan_law: PanLaw::default(),
-            sample_rate,
-            buffer_size,
-            next_bus_id: 1, // 0 is reserved for master
-            has_solo: false,
-        }
+    pub fn loudness(&self) -> &LoudnessMeter {
+        &self.loudness
     }
 
-    /// Returns the master bus.
-    #[must_use]
-    pub fn master(&self) -> &AudioBus {
-        &self.master
+    /// Updates meter levels with interleaved bus output.
+    pub fn update_meters(&mut self, samples: &[f32]) {
+        let channels = self.meters.peak.len();
+        self.meters.update(samples, channels);
     }
 
-    /// Returns mutable master bus.
-    pub fn master_mut(&mut self) -> &mut AudioBus {
-        &mut self.master
+    /// Returns the meter levels as published to the panel.
+    #[must_use]
+    pub fn meter_snapshot(&self) -> MeterSnapshot {
+        meter_snapshot(&self.meters, &self.loudness)
     }
 
-    /// Adds a new track strip.
-    ///
-    /// # Errors
-    ///
-    /// Returns `VideoEditorError::Timeline` if the track cannot be created.
-    pub fn add_track(
-        &mut self, track_id: u64, name: impl Into<String>,
-    ) -> crate::errors::VideoEditorResult<&mut AudioTrackStrip> {
-        self.tracks.push(AudioTrackStrip::new(track_id, name, self.master.id()));
-        // SAFETY: Element was just pushed, so last_mut will always succeed
-        self.tracks.last_mut().ok_or_else(|| {
-            crate::VideoEditorError::Timeline("Track was just added but not found".to_string())
-        })
+    /// Feeds interleaved bus output into the loudness meter.
+    pub fn update_loudness(&mut self, samples: &[f32]) {
+        self.loudness.process(samples);
     }
 
-    /// Removes a track strip.
-    pub fn remove_track(&mut self, track_id: u64) -> bool {
-        if let Some(pos) = self.tracks.iter().position(|t| t.track_id() == track_id) {
-            self.tracks.remove(pos);
-            self.update_solo_state();
-            true
-        } else {
-            false
-        }
+    /// Resets the loudness meter.
+    pub fn reset_loudness(&mut self) {
+        self.loudness.reset();
     }
+}
 
-    /// Gets a track strip by track ID.
-    #[must_use]
-    pub fn get_track(&self, track_id: u64) -> Option<&AudioTrackStrip> {
-        self.tracks.iter().find(|t| t.track_id() == track_id)
+/// Panel meter reading from meter levels and loudness: the loudest
+/// channel's peak and RMS.
+fn meter_snapshot(meters: &AudioMeterLevels, loudness: &LoudnessMeter) -> MeterSnapshot {
+    let loudest = |levels: &[f32]| levels.iter().copied().fold(0.0, f32::max);
+    MeterSnapshot {
+        peak_dbfs:       MeterSnapshot::to_dbfs(loudest(&meters.peak)),
+        rms_dbfs:        MeterSnapshot::to_dbfs(loudest(&meters.rms)),
+        short_term_lufs: loudness.short_term(),
+        clipping:        meters.is_clipping,
     }
+}
 
-    /// Gets a mutable track strip by track ID.
-    pub fn get_track_mut(&mut self, track_id: u64) -> Option<&mut AudioTrackStrip> {
-        self.tracks.iter_mut().find(|t| t.track_id() == track_id)
-    }
+/// Audio processing latency mode.
+///
+/// Selects the block size the mixer processes: small blocks keep scrubbing
+/// and recording responsive, large blocks keep heavy mixes and exports
+/// efficient.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AudioLatencyMode {
+    /// Scrubbing, shuttling and recording (256 samples).
+    LowLatency,
+    /// Normal playback (1024 samples).
+    #[default]
+    Playback,
+    /// Offline rendering and export (4096 samples).
+    Export,
+}
 
-    /// Returns all track strips.
+impl AudioLatencyMode {
+    /// Returns the block size for this mode in samples.
     #[must_use]
-    pub fn tracks(&self) -> &[AudioTrackStrip] {
-        &self.tracks
+    pub const fn block_size(&self) -> usize {
+        match self {
+            Self::LowLatency => 256,
+            Self::Playback => 1024,
+            Self::Export => 4096,
+        }
     }
 
-    /// Creates a new auxiliary bus.
-    pub fn create_aux_bus(&mut self, name: impl Into<String>) -> AudioBusId {
-        let id = AudioBusId::new(self.next_bus_id);
-        self.next_bus_id += 1;
+    /// Returns the mode suited to a preview playback state.
+    #[must_use]
+    pub const fn for_playback(state: PlaybackState) -> Self {
+        match state {
+            PlaybackState::Scrubbing
+            | PlaybackState::ShuttleForward
+            | PlaybackState::ShuttleBackward
+            | PlaybackState::Stepping => Self::LowLatency,
+            PlaybackState::Rendering => Self::Export,
+            PlaybackState::Stopped | PlaybackState::Paused | PlaybackState::Playing => {
+                Self::Playback
+            },
+        }
+    }
+}
+
+/// Delay-line pitch shifter with optional formant preservation.
+///
+/// Two read taps sweep a short delay line half a window apart and are
+/// crossfaded, which changes pitch without changing duration. With formants
+/// preserved, the shift runs on the LPC residual and the original spectral
+/// envelope is reapplied. The envelope is re-estimated on a fixed hop, so
+/// the output doesn't depend on the block size (preview and export bounce
+/// the same samples).
+#[derive(Debug, Clone)]
+pub struct PitchShifter {
+    pitch:    ClipPitch,
+    channels: usize,
+    window:   usize,
+    phase:    f32,
+    position: u64,
+    lines:    Vec<Vec<f32>>,
+    write:    usize,
+    formants: Vec<FormantState>,
+}
+
+/// Per-channel LPC state for formant preservation.
+#[derive(Debug, Clone)]
+struct FormantState {
+    history: Vec<f32>,
+    coeffs:  [f32; PitchShifter::LPC_ORDER],
+    input:   [f32; PitchShifter::LPC_ORDER],
+    output:  [f32; PitchShifter::LPC_ORDER],
+    /// Smoothed input and output power for level matching.
+    power:   (f32, f32),
+}
+
+impl FormantState {
+    fn new() -> Self {
+        Self {
+            history: vec![0.0; PitchShifter::LPC_LEN],
+            coeffs:  [0.0; PitchShifter::LPC_ORDER],
+            input:   [0.0; PitchShifter::LPC_ORDER],
+            output:  [0.0; PitchShifter::LPC_ORDER],
+            power:   (0.0, 0.0),
+        }
+    }
+
+    /// Re-estimates the envelope from the analysis history (autocorrelation
+    /// and Levinson-Durbin).
+    fn analyze(&mut self, history_start: usize) {
+        let len = self.history.len();
+        let windowed: Vec<f64> = (0..len)
+            .map(|i| {
+                let hann = 0.5 - 0.5 * (core::f64::consts::TAU * i as f64 / len as f64).cos();
+                f64::from(self.history[(history_start + i) % len]) * hann
+            })
+            .collect();
+
+        let mut r = [0.0f64; PitchShifter::LPC_ORDER + 1];
+        for (lag, value) in r.iter_mut().enumerate() {
+            *value = windowed[lag..].iter().zip(&windowed).map(|(a, b)| a * b).sum();
+        }
+        if r[0] <= f64::EPSILON {
+            self.coeffs = [0.0; PitchShifter::LPC_ORDER];
+            return;
+        }
+        // A -30 dB noise floor keeps pure tones from making the filter
+        // singular
+        r[0] *= 1.001;
+
+        let mut a = [0.0f64; PitchShifter::LPC_ORDER + 1];
+        a[0] = 1.0;
+        let mut error = r[0];
+        for i in 1..=PitchShifter::LPC_ORDER {
+            let acc: f64 = (1..i).map(|j| a[j] * r[i - j]).sum::<f64>() + r[i];
+            let k = -acc / error;
+            let previous = a;
+            for j in 1..i {
+                a[j] = previous[j] + k * previous[i - j];
+            }
+            a[i] = k;
+            error *= 1.0 - k * k;
+        }
+        // Bandwidth expansion pulls the poles inside the unit circle
+        let mut gamma = 1.0;
+        for (coeff, value) in self.coeffs.iter_mut().zip(&a[1..]) {
+            gamma *= 0.98;
+            *coeff = (value * gamma) as f32;
+        }
+    }
+
+    /// Power follower with instant attack and smoothed release.
+    fn follow(power: f32, x: f32) -> f32 {
+        (x * x).max(power + (x * x - power) * PitchShifter::LEVEL_SMOOTHING)
+    }
+
+    /// Inverse filter: removes the envelope from an input sample.
+    fn whiten(&mut self, x: f32) -> f32 {
+        self.power.0 = Self::follow(self.power.0, x);
+        let e = x + self.coeffs.iter().zip(&self.input).map(|(a, s)| a * s).sum::<f32>();
+        self.input.copy_within(..PitchShifter::LPC_ORDER - 1, 1);
+        self.input[0] = x;
+        e
+    }
+
+    /// Synthesis filter: reapplies the envelope to a shifted sample.
+    fn color(&mut self, e: f32) -> f32 {
+        let y = e - self.coeffs.iter().zip(&self.output).map(|(a, s)| a * s).sum::<f32>();
+        let y = if y.is_finite() { y } else { 0.0 };
+        self.output.copy_within(..PitchShifter::LPC_ORDER - 1, 1);
+        self.output[0] = y;
+
+        // A shifted partial can land on an envelope peak; never come out
+        // louder than the input
+        self.power.1 = Self::follow(self.power.1, y);
+        let gain = ((self.power.0 + 1e-9) / (self.power.1 + 1e-9)).sqrt().min(1.0);
+        y * gain
+    }
+}
+
+impl PitchShifter {
+    /// Delay window in milliseconds.
+    pub const WINDOW_MS: f32 = 40.0;
+    /// LPC order used for formant preservation.
+    const LPC_ORDER: usize = 16;
+    /// Samples analyzed per envelope estimate.
+    const LPC_LEN: usize = 1024;
+    /// Samples between envelope estimates.
+    const LPC_HOP: u64 = 256;
+    /// One-pole smoothing of the level match (about 20 ms at 48 kHz).
+    const LEVEL_SMOOTHING: f32 = 0.001;
+
+    /// Creates a pitch shifter for interleaved audio.
+    #[must_use]
+    pub fn new(pitch: ClipPitch, sample_rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        let window = ((Self::WINDOW_MS / 1000.0 * sample_rate as f32) as usize).max(16);
+        let mut shifter = Self {
+            pitch,
+            channels,
+            window,
+            phase: 0.0,
+            position: 0,
+            lines: vec![vec![0.0; window + 2]; channels],
+            write: 0,
+            formants: Vec::new(),
+        };
+        shifter.reset_formants();
+        shifter
+    }
+
+    /// Returns the pitch shift.
+    #[must_use]
+    pub const fn pitch(&self) -> ClipPitch {
+        self.pitch
+    }
+
+    /// Returns the channel count.
+    #[must_use]
+    pub const fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Changes the shift, keeping the delay line so playback doesn't click.
+    pub fn set_pitch(&mut self, pitch: ClipPitch) {
+        let formants_changed = pitch.preserve_formants != self.pitch.preserve_formants;
+        self.pitch = pitch;
+        if formants_changed {
+            self.reset_formants();
+        }
+    }
+
+    /// Clears all state (e.g. after a seek).
+    pub fn reset(&mut self) {
+        for line in &mut self.lines {
+            line.fill(0.0);
+        }
+        self.phase = 0.0;
+        self.position = 0;
+        self.write = 0;
+        self.reset_formants();
+    }
+
+    fn reset_formants(&mut self) {
+        self.formants = if self.pitch.preserve_formants {
+            vec![FormantState::new(); self.channels]
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// Shifts an interleaved buffer in place.
+    pub fn process(&mut self, samples: &mut [f32]) {
+        if self.pitch.is_identity() {
+            return;
+        }
+
+        let len = self.window + 2;
+        let step = (1.0 - self.pitch.ratio()) / self.window as f32;
+        for frame in samples.chunks_mut(self.channels) {
+            if !self.formants.is_empty() && self.position.is_multiple_of(Self::LPC_HOP) {
+                let start = (self.position % Self::LPC_LEN as u64) as usize;
+                for state in &mut self.formants {
+                    state.analyze(start);
+                }
+            }
+
+            let delay_a = self.phase * self.window as f32;
+            let delay_b = (self.phase + 0.5).rem_euclid(1.0) * self.window as f32;
+            let gain_a = (core::f32::consts::PI * self.phase).sin().powi(2);
+            for (ch, sample) in frame.iter_mut().enumerate() {
+                let mut x = *sample;
+                if let Some(state) = self.formants.get_mut(ch) {
+                    state.history[(self.position % Self::LPC_LEN as u64) as usize] = x;
+                    x = state.whiten(x);
+                }
+
+                let line = &mut self.lines[ch];
+                line[self.write] = x;
+                let read = |delay: f32| {
+                    let pos = (self.write as f32 - delay).rem_euclid(len as f32);
+                    let i = pos as usize % len;
+                    let frac = pos - pos.floor();
+                    line[i] * (1.0 - frac) + line[(i + 1) % len] * frac
+                };
+                let mut y = read(delay_a) * gain_a + read(delay_b) * (1.0 - gain_a);
+
+                if let Some(state) = self.formants.get_mut(ch) {
+                    y = state.color(y);
+                }
+                *sample = y;
+            }
+
+            self.phase = (self.phase + step).rem_euclid(1.0);
+            self.write = (self.write + 1) % len;
+            self.position += 1;
+        }
+    }
+}
+
+impl Default for PitchShifter {
+    fn default() -> Self {
+        Self::new(ClipPitch::default(), 48000, 2)
+    }
+}
+
+/// Overlap-add output ring for Hann-windowed grains at 50% overlap.
+#[derive(Debug, Clone)]
+struct OverlapAdd {
+    channels: usize,
+    frames:   usize,
+    buffer:   Vec<f32>,
+    read:     usize,
+}
+
+impl OverlapAdd {
+    fn new(frames: usize, channels: usize) -> Self {
+        Self { channels, frames, buffer: vec![0.0; frames * channels], read: 0 }
+    }
+
+    /// Adds a windowed grain of interleaved `source` starting at source
+    /// frame `start`. Frames outside the source read as silence.
+    fn add_grain(&mut self, source: &[f32], start: i64) {
+        let source_frames = (source.len() / self.channels) as i64;
+        for k in 0..self.frames {
+            let frame = start + k as i64;
+            if frame < 0 || frame >= source_frames {
+                continue;
+            }
+            let w = (core::f32::consts::PI * k as f32 / self.frames as f32).sin().powi(2);
+            let slot = (self.read + k) % self.frames * self.channels;
+            let base = frame as usize * self.channels;
+            for ch in 0..self.channels {
+                self.buffer[slot + ch] += w * source[base + ch];
+            }
+        }
+    }
+
+    /// Writes the next output frame and frees its slot.
+    fn pop(&mut self, out: &mut [f32]) {
+        let slot = self.read * self.channels;
+        for (ch, sample) in out.iter_mut().enumerate().take(self.channels) {
+            *sample = self.buffer[slot + ch];
+            self.buffer[slot + ch] = 0.0;
+        }
+        self.read = (self.read + 1) % self.frames;
+    }
+
+    fn clear(&mut self) {
+        self.buffer.fill(0.0);
+        self.read = 0;
+    }
+}
+
+/// Granular scrub audio.
+///
+/// Plays short windowed grains read around the scrub position, so dragging
+/// the playhead sounds like the material at its original pitch. When the
+/// playhead stops moving no new grains start and the output decays to
+/// silence within one grain.
+#[derive(Debug, Clone)]
+pub struct ScrubEngine {
+    channels:    usize,
+    sample_rate: u32,
+    grain:       usize,
+    output:      OverlapAdd,
+    until_next:  usize,
+    last:        Option<u64>,
+}
+
+impl ScrubEngine {
+    /// Grain length in milliseconds.
+    pub const GRAIN_MS: f32 = 60.0;
+
+    /// Creates a scrub engine for interleaved audio.
+    #[must_use]
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        let grain = ((Self::GRAIN_MS / 1000.0 * sample_rate as f32) as usize).max(16);
+        Self {
+            channels,
+            sample_rate,
+            grain,
+            output: OverlapAdd::new(grain, channels),
+            until_next: 0,
+            last: None,
+        }
+    }
+
+    /// Forgets the previous scrub position and pending grains.
+    pub fn reset(&mut self) {
+        self.output.clear();
+        self.until_next = 0;
+        self.last = None;
+    }
+
+    /// Renders one block for a playhead that moved to `position` since the
+    /// previous block. Grain positions are interpolated across the block.
+    ///
+    /// `source` is interleaved program audio starting at time zero.
+    pub fn render(&mut self, source: &[f32], position: TimePosition, out: &mut [f32]) {
+        let target = position.ms * u64::from(self.sample_rate) / 1000;
+        let from = self.last.unwrap_or(target);
+        let frames = (out.len() / self.channels).max(1);
+        for (i, frame) in out.chunks_mut(self.channels).enumerate() {
+            if self.until_next == 0 {
+                if target != from {
+                    let center =
+                        from as f64 + (target as f64 - from as f64) * i as f64 / frames as f64;
+                    self.output.add_grain(source, center as i64 - (self.grain / 2) as i64);
+                }
+                self.until_next = self.grain / 2;
+            }
+            self.output.pop(frame);
+            self.until_next -= 1;
+        }
+        self.last = Some(target);
+    }
+}
+
+impl Default for ScrubEngine {
+    fn default() -> Self {
+        Self::new(48000, 2)
+    }
+}
+
+/// Pitch-preserving time stretch (WSOLA).
+///
+/// Windowed frames are read from the source every `speed` hops and
+/// overlap-added at a fixed hop. Each frame's start is nudged within a
+/// small tolerance to the offset that best continues the previous frame,
+/// which keeps periodic material phase-aligned so speech stays intelligible
+/// between 0.25x and 2x. Negative speeds play grains in reverse order.
+#[derive(Debug, Clone)]
+pub struct TimeStretcher {
+    channels:   usize,
+    frame:      usize,
+    tolerance:  usize,
+    speed:      f64,
+    position:   f64,
+    previous:   Option<i64>,
+    output:     OverlapAdd,
+    until_next: usize,
+}
+
+impl TimeStretcher {
+    /// Analysis window in milliseconds.
+    pub const WINDOW_MS: f32 = 40.0;
+    /// Search tolerance around the nominal read position in milliseconds.
+    pub const TOLERANCE_MS: f32 = 10.0;
+    /// Slowest supported speed.
+    pub const MIN_SPEED: f64 = 0.25;
+    /// Fastest supported speed.
+    pub const MAX_SPEED: f64 = 2.0;
+    /// Every n-th sample is used when scoring candidate offsets.
+    const SEARCH_STRIDE: usize = 4;
+
+    /// Creates a time stretcher for interleaved audio.
+    #[must_use]
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        let frame = ((Self::WINDOW_MS / 1000.0 * sample_rate as f32) as usize).max(16);
+        let tolerance = (Self::TOLERANCE_MS / 1000.0 * sample_rate as f32) as usize;
+        Self {
+            channels,
+            frame,
+            tolerance,
+            speed: 1.0,
+            position: 0.0,
+            previous: None,
+            output: OverlapAdd::new(frame, channels),
+            until_next: 0,
+        }
+    }
+
+    /// Returns whether a playback speed is in the stretchable range.
+    #[must_use]
+    pub fn supports(speed: f64) -> bool {
+        (Self::MIN_SPEED..=Self::MAX_SPEED).contains(&speed.abs())
+    }
+
+    /// Returns the playback speed.
+    #[must_use]
+    pub const fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Sets the playback speed, clamping its magnitude to the supported range.
+    pub fn set_speed(&mut self, speed: f64) {
+        let magnitude = speed.abs().clamp(Self::MIN_SPEED, Self::MAX_SPEED);
+        self.speed = if speed < 0.0 { -magnitude } else { magnitude };
+    }
+
+    /// Returns the analysis window length in frames.
+    #[must_use]
+    pub const fn window(&self) -> usize {
+        self.frame
+    }
+
+    /// Returns the source frame the stretcher reads next.
+    #[must_use]
+    pub fn position(&self) -> u64 {
+        self.position.max(0.0) as u64
+    }
+
+    /// Moves the read position, dropping pending output.
+    pub fn seek(&mut self, frame: u64) {
+        self.position = frame as f64;
+        self.previous = None;
+        self.output.clear();
+        self.until_next = 0;
+    }
+
+    /// Renders one block of stretched audio from interleaved `source`.
+    pub fn render(&mut self, source: &[f32], out: &mut [f32]) {
+        let hop = self.frame / 2;
+        for frame in out.chunks_mut(self.channels) {
+            if self.until_next == 0 {
+                let nominal = self.position.round() as i64;
+                let start = match self.previous {
+                    Some(previous) => self.best_start(source, nominal, previous + hop as i64),
+                    None => nominal,
+                };
+                self.output.add_grain(source, start);
+                self.previous = Some(start);
+                self.position = (self.position + self.speed * hop as f64).max(0.0);
+                self.until_next = hop;
+            }
+            self.output.pop(frame);
+            self.until_next -= 1;
+        }
+    }
+
+    /// Picks the start near `nominal` whose overlap region best matches the
+    /// natural continuation of the previous frame (normalized
+    /// cross-correlation).
+    fn best_start(&self, source: &[f32], nominal: i64, natural: i64) -> i64 {
+        let source_frames = (source.len() / self.channels) as i64;
+        let mono = |frame: i64| -> f32 {
+            if frame < 0 || frame >= source_frames {
+                return 0.0;
+            }
+            let base = frame as usize * self.channels;
+            source[base..base + self.channels].iter().sum()
+        };
+
+        let overlap = self.frame / 2;
+        let tolerance = self.tolerance as i64;
+        let mut best = (f32::NEG_INFINITY, nominal);
+        for candidate in nominal - tolerance..=nominal + tolerance {
+            let (mut cross, mut energy) = (0.0f32, 0.0f32);
+            for k in (0..overlap).step_by(Self::SEARCH_STRIDE) {
+                let x = mono(candidate + k as i64);
+                cross += x * mono(natural + k as i64);
+                energy += x * x;
+            }
+            let score = cross / (energy + 1e-9).sqrt();
+            if score > best.0 {
+                best = (score, candidate);
+            }
+        }
+        best.1
+    }
+}
+
+impl Default for TimeStretcher {
+    fn default() -> Self {
+        Self::new(48000, 2)
+    }
+}
+
+/// The main audio mixer.
+pub struct AudioMixer {
+    /// Master output bus.
+    master:              AudioBus,
+    /// Auxiliary buses.
+    aux_buses:           Vec<AudioBus>,
+    /// Group buses.
+    group_buses:         Vec<AudioBus>,
+    /// Track strips.
+    tracks:              Vec<AudioTrackStrip>,
+    /// Pan law setting.
+    pan_law:             PanLaw,
+    /// Sample rate.
+    sample_rate:         u32,
+    /// Buffer size.
+    buffer_size:         usize,
+    /// Block size waiting for the next block boundary.
+    pending_buffer_size: Option<usize>,
+    /// Current latency mode.
+    latency_mode:        AudioLatencyMode,
+    /// Next bus ID counter.
+    next_bus_id:         u64,
+    /// Whether any track is soloed.
+    has_solo:            bool,
+    /// Pitch shifter state per clip.
+    clip_shifters:       Vec<(u64, PitchShifter)>,
+    /// Spectral denoiser state per track insert.
+    denoisers:           Vec<((u64, u8), SpectralDenoiser)>,
+    /// Grain engine for scrub audio.
+    scrub:               ScrubEngine,
+    /// Time stretch for slow and fast playback.
+    stretcher:           TimeStretcher,
+    /// Bus assignments to hardware outputs.
+    routing:             OutputRouting,
+}
+
+impl AudioMixer {
+    /// Smallest supported block size.
+    pub const MIN_BUFFER_SIZE: usize = 64;
+    /// Largest supported block size.
+    pub const MAX_BUFFER_SIZE: usize = 8192;
+
+    /// Creates a new audio mixer.
+    #[must_use]
+    pub fn new(sample_rate: u32, buffer_size: usize) -> Self {
+        let mut master = AudioBus::master();
+        master.loudness = LoudnessMeter::new(sample_rate, 2);
+        let mut routing = OutputRouting::new();
+        routing.assign(master.id(), HardwareOutput::Main, AudioChannelConfig::Stereo);
+
+        Self {
+            master,
+            aux_buses: Vec::new(),
+            group_buses: Vec::new(),
+            tracks: Vec::new(),
+            pan_law: PanLaw::default(),
+            sample_rate,
+            buffer_size: Self::clamp_buffer_size(buffer_size),
+            pending_buffer_size: None,
+            latency_mode: AudioLatencyMode::default(),
+            next_bus_id: 1, // 0 is reserved for master
+            has_solo: false,
+            clip_shifters: Vec::new(),
+            denoisers: Vec::new(),
+            scrub: ScrubEngine::new(sample_rate, 2),
+            stretcher: TimeStretcher::new(sample_rate, 2),
+            routing,
+        }
+    }
+
+    /// Returns the master bus.
+    #[must_use]
+    pub fn master(&self) -> &AudioBus {
+        &self.master
+    }
+
+    /// Returns mutable master bus.
+    pub fn master_mut(&mut self) -> &mut AudioBus {
+        &mut self.master
+    }
+
+    /// Adds a new track strip.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Timeline` if the track cannot be created.
+    pub fn add_track(
+        &mut self, track_id: u64, name: impl Into<String>,
+    ) -> crate::errors::VideoEditorResult<&mut AudioTrackStrip> {
+        let mut strip = AudioTrackStrip::new(track_id, name, self.master.id());
+        strip.loudness = LoudnessMeter::new(self.sample_rate, 2);
+        self.tracks.push(strip);
+        // SAFETY: Element was just pushed, so last_mut will always succeed
+        self.tracks.last_mut().ok_or_else(|| {
+            crate::VideoEditorError::timeline("add_track", "Track was just added but not found")
+        })
+    }
+
+    /// Removes a track strip.
+    pub fn remove_track(&mut self, track_id: u64) -> bool {
+        if let Some(pos) = self.tracks.iter().position(|t| t.track_id() == track_id) {
+            self.tracks.remove(pos);
+            self.update_solo_state();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Gets a track strip by track ID.
+    #[must_use]
+    pub fn get_track(&self, track_id: u64) -> Option<&AudioTrackStrip> {
+        self.tracks.iter().find(|t| t.track_id() == track_id)
+    }
+
+    /// Gets a mutable track strip by track ID.
+    pub fn get_track_mut(&mut self, track_id: u64) -> Option<&mut AudioTrackStrip> {
+        self.tracks.iter_mut().find(|t| t.track_id() == track_id)
+    }
+
+    /// Returns all track strips.
+    #[must_use]
+    pub fn tracks(&self) -> &[AudioTrackStrip] {
+        &self.tracks
+    }
+
+    /// Creates a new auxiliary bus.
+    pub fn create_aux_bus(&mut self, name: impl Into<String>) -> AudioBusId {
+        let id = AudioBusId::new(self.next_bus_id);
+        self.next_bus_id += 1;
 
         let mut bus = AudioBus::new(id, name, AudioBusType::Aux);
         bus.output = Some(self.master.id());
+        bus.loudness = LoudnessMeter::new(self.sample_rate, 2);
 
         self.aux_buses.push(bus);
         id
@@ -704,6 +1878,7 @@ impl AudioMixer {
 
         let mut bus = AudioBus::new(id, name, AudioBusType::Group);
         bus.output = Some(self.master.id());
+        bus.loudness = LoudnessMeter::new(self.sample_rate, 2);
 
         self.group_buses.push(bus);
         id
@@ -721,6 +1896,86 @@ impl AudioMixer {
             .or_else(|| self.group_buses.iter().find(|b| b.id() == id))
     }
 
+    /// Gets a mutable bus by ID.
+    pub fn get_bus_mut(&mut self, id: AudioBusId) -> Option<&mut AudioBus> {
+        if id.inner() == 0 {
+            return Some(&mut self.master);
+        }
+        if let Some(pos) = self.aux_buses.iter().position(|b| b.id() == id) {
+            return self.aux_buses.get_mut(pos);
+        }
+        self.group_buses.iter_mut().find(|b| b.id() == id)
+    }
+
+    /// Returns the bus assignments to hardware outputs. The master bus
+    /// starts out on the main outputs.
+    #[must_use]
+    pub fn output_routing(&self) -> &OutputRouting {
+        &self.routing
+    }
+
+    /// Sets the channel layout of a hardware output, e.g. 5.1 main
+    /// monitors.
+    pub fn set_output_layout(&mut self, output: HardwareOutput, layout: AudioChannelConfig) {
+        self.routing.set_layout(output, layout);
+    }
+
+    /// Sends a bus carrying `bus_layout` audio to a hardware output,
+    /// folding it down to the output layout, e.g. a 5.1 mix to stereo cue.
+    /// Returns `false` if the bus doesn't exist.
+    pub fn assign_bus_output(
+        &mut self, bus: AudioBusId, output: HardwareOutput, bus_layout: AudioChannelConfig,
+    ) -> bool {
+        if self.get_bus(bus).is_none() {
+            return false;
+        }
+        self.routing.assign(bus, output, bus_layout);
+        true
+    }
+
+    /// Sends a bus to a hardware output through a custom matrix. Returns
+    /// `false` if the bus doesn't exist or the matrix doesn't match the
+    /// output layout.
+    pub fn set_bus_output_matrix(
+        &mut self, bus: AudioBusId, output: HardwareOutput, matrix: DownmixMatrix,
+    ) -> bool {
+        self.get_bus(bus).is_some() && self.routing.assign_matrix(bus, output, matrix)
+    }
+
+    /// Stops sending a bus to a hardware output. Returns whether it was
+    /// sent.
+    pub fn unassign_bus_output(&mut self, bus: AudioBusId, output: HardwareOutput) -> bool {
+        self.routing.unassign(bus, output)
+    }
+
+    /// Renders every assigned hardware output from interleaved bus audio
+    /// `frames` frames long, each in its output's layout.
+    #[must_use]
+    pub fn render_outputs(
+        &self, buses: &[(AudioBusId, &[f32])], frames: usize,
+    ) -> Vec<(HardwareOutput, Vec<f32>)> {
+        self.routing.render(buses, frames)
+    }
+
+    /// Returns the loudness readings of the master bus.
+    #[must_use]
+    pub fn master_loudness(&self) -> LoudnessReading {
+        self.master.loudness.reading()
+    }
+
+    /// Copies master loudness readings and the track and master meters
+    /// into plugin metrics.
+    pub fn fill_metrics(&self, metrics: &mut VideoEditorMetrics) {
+        let reading = self.master_loudness();
+        metrics.loudness_momentary_lufs = reading.momentary_lufs;
+        metrics.loudness_short_term_lufs = reading.short_term_lufs;
+        metrics.loudness_integrated_lufs = reading.integrated_lufs;
+        metrics.true_peak_dbtp = reading.true_peak_dbtp;
+        metrics.track_meters =
+            self.tracks.iter().map(|t| (t.track_id(), t.meter_snapshot())).collect();
+        metrics.master_meter = self.master.meter_snapshot();
+    }
+
     /// Returns the pan law setting.
     #[must_use]
     pub const fn pan_law(&self) -> PanLaw {
@@ -738,6 +1993,63 @@ impl AudioMixer {
         self.sample_rate
     }
 
+    /// Returns the block size currently processed in samples.
+    #[must_use]
+    pub const fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    /// Returns the current latency mode.
+    #[must_use]
+    pub const fn latency_mode(&self) -> AudioLatencyMode {
+        self.latency_mode
+    }
+
+    /// Returns the processing latency of one block in milliseconds.
+    #[must_use]
+    pub fn latency_ms(&self) -> f64 {
+        self.buffer_size as f64 * 1000.0 / f64::from(self.sample_rate.max(1))
+    }
+
+    /// Switches the latency mode and requests its block size.
+    pub fn set_latency_mode(&mut self, mode: AudioLatencyMode) {
+        self.latency_mode = mode;
+        self.request_buffer_size(mode.block_size());
+    }
+
+    /// Follows a preview playback state change (e.g. scrubbing starts).
+    pub fn follow_playback_state(&mut self, state: PlaybackState) {
+        let mode = AudioLatencyMode::for_playback(state);
+        if mode != self.latency_mode {
+            self.set_latency_mode(mode);
+        }
+    }
+
+    /// Requests a new block size, returning the size that will be used.
+    ///
+    /// The size is rounded to a power of two within `MIN_BUFFER_SIZE..=
+    /// MAX_BUFFER_SIZE`. It takes effect at the next block boundary, so a
+    /// block is never resized while it is being processed.
+    pub fn request_buffer_size(&mut self, size: usize) -> usize {
+        let size = Self::clamp_buffer_size(size);
+        self.pending_buffer_size = (size != self.buffer_size).then_some(size);
+        size
+    }
+
+    /// Starts a new block, applying any pending block size switch.
+    ///
+    /// Returns the size of the block to process.
+    pub fn begin_block(&mut self) -> usize {
+        if let Some(size) = self.pending_buffer_size.take() {
+            self.buffer_size = size;
+        }
+        self.buffer_size
+    }
+
+    fn clamp_buffer_size(size: usize) -> usize {
+        size.clamp(Self::MIN_BUFFER_SIZE, Self::MAX_BUFFER_SIZE).next_power_of_two()
+    }
+
     /// Updates the solo state based on track settings.
     fn update_solo_state(&mut self) {
         self.has_solo = self.tracks.iter().any(|t| t.is_solo());
@@ -751,6 +2063,17 @@ impl AudioMixer {
         self.update_solo_state();
     }
 
+    /// Copies effective mute and solo state from the timeline, including
+    /// track group mute and solo, onto the track strips.
+    pub fn sync_track_states(&mut self, timeline: &TimelineManager) {
+        for track in &mut self.tracks {
+            let id = track.track_id();
+            track.set_muted(timeline.is_track_muted(id));
+            track.set_solo(timeline.is_track_soloed(id));
+        }
+        self.update_solo_state();
+    }
+
     /// Returns whether the mixer has any soloed tracks.
     #[must_use]
     pub const fn has_solo(&self) -> bool {
@@ -771,8 +2094,257 @@ impl AudioMixer {
         if self.has_solo { track.is_solo() } else { true }
     }
 
+    /// Renders a fade over interleaved buffers starting at `start_ms`.
+    ///
+    /// Mixes `outgoing` and `incoming` into `output` using the fade's
+    /// per-side curves; missing samples are treated as silence.
+    pub fn render_fade(
+        &self, fade: &AudioFade, start_ms: f64, channels: usize, outgoing: &[f32],
+        incoming: &[f32], output: &mut [f32],
+    ) {
+        let channels = channels.max(1);
+        let ms_per_frame = 1000.0 / f64::from(self.sample_rate.max(1));
+
+        for (frame, out) in output.chunks_mut(channels).enumerate() {
+            let (out_gain, in_gain) = fade.gains_at(start_ms + frame as f64 * ms_per_frame);
+            for (ch, sample) in out.iter_mut().enumerate() {
+                let i = frame * channels + ch;
+                let a = outgoing.get(i).copied().unwrap_or(0.0);
+                let b = incoming.get(i).copied().unwrap_or(0.0);
+                *sample = a * out_gain + b * in_gain;
+            }
+        }
+    }
+
+    /// Returns the gain of a clip's edge fades at a timeline time in ms.
+    ///
+    /// A fade-out mirrors its curve in time, so an equal-power fade-out
+    /// meeting an equal-power fade-in sums to constant power.
+    #[must_use]
+    pub fn clip_fade_gain(&self, clip: &TimelineClip, time_ms: f64) -> f32 {
+        let offset = time_ms - clip.start.ms as f64;
+        let remaining = clip.end().ms as f64 - time_ms;
+        let mut gain = 1.0;
+        let fade_in = clip.audio_fade_in;
+        if !fade_in.is_none() && offset < fade_in.duration.ms as f64 {
+            gain *= FadeCurve::from(fade_in.shape)
+                .evaluate((offset / fade_in.duration.ms as f64) as f32);
+        }
+        let fade_out = clip.audio_fade_out;
+        if !fade_out.is_none() && remaining < fade_out.duration.ms as f64 {
+            gain *= FadeCurve::from(fade_out.shape)
+                .evaluate((remaining / fade_out.duration.ms as f64) as f32);
+        }
+        gain
+    }
+
+    /// Applies a clip's edge fades to a block of its interleaved audio
+    /// whose first frame plays at timeline time `start_ms`.
+    pub fn apply_clip_fades(
+        &self, clip: &TimelineClip, start_ms: f64, channels: usize, samples: &mut [f32],
+    ) {
+        if clip.audio_fade_in.is_none() && clip.audio_fade_out.is_none() {
+            return;
+        }
+        let ms_per_frame = 1000.0 / f64::from(self.sample_rate.max(1));
+        for (frame, samples) in samples.chunks_mut(channels.max(1)).enumerate() {
+            let gain = self.clip_fade_gain(clip, start_ms + frame as f64 * ms_per_frame);
+            samples.iter_mut().for_each(|s| *s *= gain);
+        }
+    }
+
+    /// Routes a clip's interleaved source audio through its channel map.
+    ///
+    /// Run this before `process_clip`; the result has
+    /// `clip.channel_map.output_channels(source_channels)` channels.
+    pub fn route_clip(
+        &self, clip: &TimelineClip, source_channels: usize, samples: &[f32],
+    ) -> Vec<f32> {
+        clip.channel_map.apply(samples, source_channels)
+    }
+
+    /// Applies a clip's pitch shift to its interleaved source audio.
+    ///
+    /// Shifter state is kept per clip across blocks, so call this for every
+    /// block of the clip in order, during playback and export alike.
+    pub fn process_clip(&mut self, clip: &TimelineClip, channels: usize, samples: &mut [f32]) {
+        if clip.pitch.is_identity() {
+            self.clip_shifters.retain(|(id, _)| *id != clip.id);
+            return;
+        }
+
+        let index = match self.clip_shifters.iter().position(|(id, _)| *id == clip.id) {
+            Some(index) if self.clip_shifters[index].1.channels() == channels.max(1) => index,
+            existing => {
+                let shifter = PitchShifter::new(clip.pitch, self.sample_rate, channels);
+                if let Some(index) = existing {
+                    self.clip_shifters[index].1 = shifter;
+                    index
+                } else {
+                    self.clip_shifters.push((clip.id, shifter));
+                    self.clip_shifters.len() - 1
+                }
+            },
+        };
+        let shifter = &mut self.clip_shifters[index].1;
+        if shifter.pitch() != clip.pitch {
+            shifter.set_pitch(clip.pitch);
+        }
+        shifter.process(samples);
+    }
+
+    /// Clears per-clip processing state, e.g. after a seek or before an
+    /// export bounce.
+    pub fn reset_clip_processing(&mut self) {
+        self.clip_shifters.clear();
+        for (_, denoiser) in &mut self.denoisers {
+            denoiser.reset();
+        }
+    }
+
+    /// Learns the noise profile of a spectral noise reduction insert from
+    /// a noise-only range of interleaved track audio. `source` starts at
+    /// time zero.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Timeline` if the track or insert does not
+    /// exist, or the range is shorter than one analysis frame.
+    pub fn learn_noise_profile(
+        &mut self, track_id: u64, slot: u8, source: &[f32], channels: usize, start: TimePosition,
+        end: TimePosition,
+    ) -> VideoEditorResult<()> {
+        let channels = channels.max(1);
+        let frame = |time: TimePosition| {
+            (time.ms as usize * self.sample_rate as usize / 1000 * channels).min(source.len())
+        };
+        let range = &source[frame(start)..frame(end).max(frame(start))];
+        let insert = self
+            .get_track_mut(track_id)
+            .ok_or_else(|| {
+                crate::VideoEditorError::timeline(
+                    "learn_noise_profile",
+                    format!("Track not found: {track_id}"),
+                )
+            })?
+            .get_insert_mut(slot)
+            .filter(|i| i.effect == AudioEffectType::SpectralNoiseReduction)
+            .ok_or_else(|| {
+                crate::VideoEditorError::timeline(
+                    "learn_noise_profile",
+                    format!("Noise reduction insert not found: {slot}"),
+                )
+            })?;
+        let profile = NoiseProfile::learn(range, channels).ok_or_else(|| {
+            crate::VideoEditorError::timeline(
+                "learn_noise_profile",
+                "Noise profile range is shorter than one analysis frame",
+            )
+        })?;
+        insert.noise_profile = Some(profile);
+        self.denoisers.retain(|(key, _)| *key != (track_id, slot));
+        Ok(())
+    }
+
+    /// Runs a track's spectral noise reduction inserts over a block of
+    /// interleaved track audio.
+    ///
+    /// Denoiser state is kept per insert across blocks and delays the
+    /// track by `SpectralDenoiser::LATENCY` samples. Bypassed inserts and
+    /// inserts without a learned profile pass audio through.
+    pub fn process_track_inserts(&mut self, track_id: u64, channels: usize, samples: &mut [f32]) {
+        let Some(track) = self.tracks.iter().find(|t| t.track_id() == track_id) else {
+            return;
+        };
+        for insert in track.inserts() {
+            let Some(profile) = insert.noise_profile.as_ref().filter(|_| {
+                insert.effect == AudioEffectType::SpectralNoiseReduction && !insert.bypassed
+            }) else {
+                continue;
+            };
+            let params = &insert.parameters;
+            let reduction = params.get("reduction").unwrap_or(12.0);
+            let sensitivity = params.get("sensitivity").unwrap_or(1.5);
+            let smoothing = params.get("smoothing").unwrap_or(0.5);
+
+            let key = (track_id, insert.slot);
+            let index = match self.denoisers.iter().position(|(k, _)| *k == key) {
+                Some(index)
+                    if self.denoisers[index].1.channels() == channels.max(1)
+                        && self.denoisers[index].1.profile() == profile =>
+                {
+                    index
+                },
+                existing => {
+                    let denoiser = SpectralDenoiser::new(
+                        profile.clone(),
+                        channels,
+                        reduction,
+                        sensitivity,
+                        smoothing,
+                    );
+                    if let Some(index) = existing {
+                        self.denoisers[index].1 = denoiser;
+                        index
+                    } else {
+                        self.denoisers.push((key, denoiser));
+                        self.denoisers.len() - 1
+                    }
+                },
+            };
+            let denoiser = &mut self.denoisers[index].1;
+            denoiser.set_parameters(reduction, sensitivity, smoothing);
+            denoiser.process(samples);
+        }
+    }
+
+    /// Renders the preview monitor output for the transport state.
+    ///
+    /// Scrubbing plays grains at the playhead when the monitor has scrub
+    /// audio enabled. Playback and shuttle between 0.25x and 2x are time
+    /// stretched so pitch is preserved; faster shuttle is silent. `source`
+    /// is interleaved stereo program audio starting at time zero.
+    pub fn render_monitor(
+        &mut self, state: PlaybackState, speed: PlaybackSpeed, position: TimePosition,
+        monitor: &AudioMonitor, source: &[f32], out: &mut [f32],
+    ) {
+        out.fill(0.0);
+        match state {
+            PlaybackState::Scrubbing => {
+                if monitor.scrub_audio {
+                    self.scrub.render(source, position, out);
+                }
+            },
+            PlaybackState::Playing
+            | PlaybackState::ShuttleForward
+            | PlaybackState::ShuttleBackward
+                if TimeStretcher::supports(speed.value()) =>
+            {
+                self.scrub.reset();
+                // Follow the transport if it jumped (seek, loop)
+                let frame = position.ms * u64::from(self.sample_rate) / 1000;
+                if self.stretcher.position().abs_diff(frame) > self.stretcher.window() as u64 {
+                    self.stretcher.seek(frame);
+                }
+                self.stretcher.set_speed(speed.value());
+                self.stretcher.render(source, out);
+            },
+            _ => self.scrub.reset(),
+        }
+
+        let gain = if monitor.muted { 0.0 } else { monitor.volume };
+        if (gain - 1.0).abs() > f32::EPSILON {
+            for sample in out.iter_mut() {
+                *sample *= gain;
+            }
+        }
+    }
+
     /// Processes audio through the mixer (stub for GPU/DSP implementation).
     pub fn process(&mut self, _input: &[f32], _output: &mut [f32]) -> VideoEditorResult<()> {
+        // Block size switches only happen between blocks
+        self.begin_block();
+
         // In a full implementation, this would:
         // 1. Route track audio through inserts
         // 2. Apply volume and pan
@@ -835,6 +2407,147 @@ mod tests {
         assert!(!mixer.is_track_audible(2));
     }
 
+    #[test]
+    fn test_fade_curves() {
+        assert!((FadeCurve::LINEAR.evaluate(0.25) - 0.25).abs() < 1e-3);
+        assert!(FadeCurve::EASE_IN.evaluate(0.5) < 0.5);
+        assert!(FadeCurve::EASE_OUT.evaluate(0.5) > 0.5);
+        assert!((FadeCurve::S_CURVE.evaluate(0.5) - 0.5).abs() < 1e-3);
+        assert_eq!(FadeCurve::S_CURVE.evaluate(1.5), 1.0);
+    }
+
+    #[test]
+    fn test_crossfade_render() {
+        let mixer = AudioMixer::new(1000, 64);
+        let mut fade = AudioFade::new(
+            AudioFadeId::new(1),
+            1,
+            AudioFadeKind::Cross,
+            TimePosition::from_ms(0),
+            TimePosition::from_ms(4),
+        );
+        fade.fade_in = FadeCurve::EASE_OUT;
+
+        let (out_gain, in_gain) = fade.gains_at(2.0);
+        assert!((out_gain - 0.5).abs() < 1e-3);
+        assert!(in_gain > 0.5);
+
+        let mut output = [0.0_f32; 5];
+        mixer.render_fade(&fade, 0.0, 1, &[1.0; 5], &[0.0; 5], &mut output);
+        assert!((output[0] - 1.0).abs() < 1e-6);
+        assert!(output[4].abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_clip_edge_fades() {
+        use crate::types::{ClipFade, TrackType};
+
+        let quarter_sine = |x: f32| (x * core::f32::consts::FRAC_PI_2).sin();
+        for x in [0.1, 0.25, 0.5, 0.75, 0.9] {
+            assert!((FadeCurve::EQUAL_POWER.evaluate(x) - quarter_sine(x)).abs() < 0.015);
+        }
+
+        let mut timeline = TimelineManager::new();
+        let video = timeline.add_track("V1", TrackType::Video);
+        let audio = timeline.add_track("A1", TrackType::Audio);
+        let ms = TimePosition::from_ms;
+        timeline.add_clip(video, TimelineClip::new(1, 1, ms(0), ms(1000))).expect("test assertion");
+        timeline.add_clip(audio, TimelineClip::new(2, 2, ms(0), ms(1000))).expect("test assertion");
+        let fade = |duration, shape| ClipFade::new(ms(duration), shape);
+        assert!(
+            timeline
+                .set_clip_audio_fades(1, fade(100, FadeShape::Linear), ClipFade::default())
+                .is_err()
+        );
+        timeline
+            .set_clip_audio_fades(
+                2,
+                fade(200, FadeShape::Linear),
+                fade(2000, FadeShape::EqualPower),
+            )
+            .expect("test assertion");
+        let clip = timeline.get_track(audio).expect("test assertion").clips[0].clone();
+        assert_eq!(clip.audio_fade_out.duration, ms(800));
+
+        let mixer = AudioMixer::new(1000, 64);
+        assert!((mixer.clip_fade_gain(&clip, 100.0) - 0.5 * quarter_sine(0.875)).abs() < 0.02);
+        assert!((mixer.clip_fade_gain(&clip, 600.0) - quarter_sine(0.5)).abs() < 0.02);
+        let mut block = vec![1.0; 2000];
+        mixer.apply_clip_fades(&clip, 0.0, 2, &mut block);
+        assert_eq!((block[0], block[1]), (0.0, 0.0));
+        assert!(block[1998] < 0.01);
+
+        // Splitting keeps the fade-in on the first part, the fade-out on
+        // the second
+        let (first, second) = clip.split_at(ms(100), 3).expect("test assertion");
+        assert_eq!(first.audio_fade_in.duration, ms(100));
+        assert!(first.audio_fade_out.is_none() && second.audio_fade_in.is_none());
+        assert_eq!(second.audio_fade_out.duration, ms(800));
+    }
+
+    #[test]
+    fn test_loudness_meter_reference_tone() {
+        // EBU Tech 3341: stereo 1 kHz sine at -23 dBFS reads -23 LUFS
+        let sample_rate = 48000;
+        let amplitude = 10f32.powf(-23.0 / 20.0);
+        let samples: Vec<f32> = (0..sample_rate * 5)
+            .flat_map(|i| {
+                let v = amplitude
+                    * (2.0 * core::f32::consts::PI * 1000.0 * i as f32 / sample_rate as f32).sin();
+                [v, v]
+            })
+            .collect();
+
+        let mut mixer = AudioMixer::new(sample_rate, 1024);
+        mixer.master_mut().update_loudness(&samples);
+        let reading = mixer.master_loudness();
+
+        assert!((reading.integrated_lufs + 23.0).abs() < 0.3);
+        assert!((reading.short_term_lufs + 23.0).abs() < 0.3);
+        assert!((reading.momentary_lufs + 23.0).abs() < 0.3);
+        assert!((reading.true_peak_dbtp + 23.0).abs() < 0.5);
+
+        let mut metrics = VideoEditorMetrics::default();
+        mixer.fill_metrics(&mut metrics);
+        assert!((metrics.loudness_integrated_lufs - reading.integrated_lufs).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_track_meters_in_metrics() {
+        let mut mixer = AudioMixer::default();
+        mixer.add_track(1, "Dialog").expect("test assertion");
+        mixer.add_track(2, "Music").expect("test assertion");
+        // Half-scale square wave on the left channel only
+        let block: Vec<f32> =
+            (0..48000).flat_map(|i| [if i % 2 == 0 { 0.5 } else { -0.5 }, 0.0]).collect();
+        mixer.get_track_mut(1).expect("test assertion").update_meters(&block);
+        mixer.master_mut().update_meters(&block);
+
+        let mut metrics = VideoEditorMetrics::default();
+        mixer.fill_metrics(&mut metrics);
+        assert_eq!(metrics.track_meters.iter().map(|m| m.0).collect::<Vec<_>>(), [1, 2]);
+        let dialog = metrics.track_meters[0].1;
+        assert!((dialog.peak_dbfs + 6.02).abs() < 0.01);
+        assert!((dialog.rms_dbfs + 6.02).abs() < 0.01);
+        assert!(dialog.short_term_lufs > MeterSnapshot::FLOOR_DB && !dialog.clipping);
+        assert_eq!(metrics.track_meters[1].1, MeterSnapshot::default());
+        assert!((metrics.master_meter.peak_dbfs + 6.02).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_loudness_gating() {
+        let mut meter = LoudnessMeter::new(48000, 1);
+        assert_eq!(meter.integrated(), LoudnessMeter::SILENCE_LUFS);
+
+        // Silence after programme must not drag the integrated value down
+        let tone: Vec<f32> = (0..48000 * 2).map(|i| 0.1 * (i as f32 * 0.13).sin()).collect();
+        meter.process(&tone);
+        let before = meter.integrated();
+        meter.process(&vec![0.0; 48000 * 4]);
+        assert!((meter.integrated() - before).abs() < 0.5);
+        assert_eq!(meter.momentary(), LoudnessMeter::SILENCE_LUFS);
+    }
+
     #[test]
     fn test_meter_levels() {
         let mut meters = AudioMeterLevels::new(2);
@@ -845,4 +2558,216 @@ mod tests {
         assert!(meters.peak[1] > 0.0);
         assert!(!meters.is_clipping);
     }
+
+    #[test]
+    fn test_latency_mode_switching() {
+        let mut mixer = AudioMixer::new(48000, 1000);
+        assert_eq!(mixer.buffer_size(), 1024);
+
+        mixer.follow_playback_state(PlaybackState::Scrubbing);
+        assert_eq!(mixer.latency_mode(), AudioLatencyMode::LowLatency);
+        // The running block keeps its size until the next boundary
+        assert_eq!(mixer.buffer_size(), 1024);
+        assert_eq!(mixer.begin_block(), 256);
+        assert!(mixer.latency_ms() < 6.0);
+
+        mixer.set_latency_mode(AudioLatencyMode::Export);
+        mixer.process(&[], &mut []).expect("test assertion");
+        assert_eq!(mixer.buffer_size(), 4096);
+
+        assert_eq!(mixer.request_buffer_size(100_000), AudioMixer::MAX_BUFFER_SIZE);
+        assert_eq!(mixer.request_buffer_size(4096), 4096);
+        assert_eq!(mixer.begin_block(), 4096);
+    }
+
+    #[test]
+    fn test_clip_pitch_shift() {
+        let rate = 48000;
+        let tone: Vec<f32> = (0..rate)
+            .map(|i| (core::f32::consts::TAU * 440.0 * i as f32 / rate as f32).sin() * 0.5)
+            .collect();
+        let crossings = |s: &[f32]| s.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+
+        let mut clip =
+            TimelineClip::new(1, 1, TimePosition::from_ms(0), TimePosition::from_secs(1));
+        let mut mixer = AudioMixer::default();
+        let mut untouched = tone.clone();
+        mixer.process_clip(&clip, 1, &mut untouched);
+        assert_eq!(untouched, tone);
+
+        clip.pitch = ClipPitch::new(12.0, 0.0);
+        let mut octave = tone.clone();
+        for block in octave.chunks_mut(1024) {
+            mixer.process_clip(&clip, 1, block);
+        }
+        let hz = crossings(&octave[rate as usize / 2..]) as f32 * 2.0;
+        assert!((hz - 880.0).abs() < 30.0, "{hz}");
+
+        // Preview-sized and export-sized blocks bounce the same samples
+        clip.pitch = ClipPitch::new(-3.0, 25.0).with_formants_preserved(true);
+        let bounce = |block_size: usize| {
+            let mut mixer = AudioMixer::default();
+            let mut out = tone.clone();
+            for block in out.chunks_mut(block_size) {
+                mixer.process_clip(&clip, 1, block);
+            }
+            out
+        };
+        let preview = bounce(AudioLatencyMode::LowLatency.block_size());
+        assert_eq!(preview, bounce(AudioLatencyMode::Export.block_size()));
+        let peak = preview.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!(peak.is_finite() && peak < 2.0, "{peak}");
+    }
+
+    #[test]
+    fn test_clip_channel_routing() {
+        use crate::types::ChannelMap;
+
+        let mut clip =
+            TimelineClip::new(1, 1, TimePosition::from_ms(0), TimePosition::from_secs(1));
+        let mixer = AudioMixer::default();
+        let boom_and_lav = [0.1, 0.9, 0.2, 0.8];
+        assert_eq!(mixer.route_clip(&clip, 2, &boom_and_lav), boom_and_lav);
+
+        clip.channel_map = ChannelMap::mono(1);
+        assert_eq!(mixer.route_clip(&clip, 2, &boom_and_lav), [0.9, 0.9, 0.8, 0.8]);
+        clip.channel_map = ChannelMap::new(vec![Some(1), None, Some(5)]);
+        assert_eq!(clip.channel_map.output_channels(2), 3);
+        assert_eq!(mixer.route_clip(&clip, 2, &boom_and_lav), [0.9, 0.0, 0.0, 0.8, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_output_routing() {
+        let mut mixer = AudioMixer::default();
+        let master = mixer.master().id();
+        assert_eq!(
+            mixer.output_routing().outputs_of(master).collect::<Vec<_>>(),
+            [HardwareOutput::Main]
+        );
+
+        // 5.1 master: discrete on SDI groups 1-2, folded to stereo on cue
+        let sdi = HardwareOutput::SdiGroup(1);
+        mixer.set_output_layout(sdi, AudioChannelConfig::Surround51);
+        assert!(mixer.assign_bus_output(master, sdi, AudioChannelConfig::Surround51));
+        let cue = HardwareOutput::Cue;
+        assert!(mixer.assign_bus_output(master, cue, AudioChannelConfig::Surround51));
+        assert!(!mixer.assign_bus_output(AudioBusId::new(99), sdi, AudioChannelConfig::Stereo));
+        let dialog = mixer.create_group_bus("Dialog");
+        assert!(mixer.assign_bus_output(dialog, cue, AudioChannelConfig::Mono));
+
+        let surround = [0.1, 0.2, 0.5, 0.9, 0.0, 0.0];
+        let voice = [0.25];
+        let outputs = mixer.render_outputs(&[(master, &surround[..]), (dialog, &voice[..])], 1);
+        let output = |o| &outputs.iter().find(|(out, _)| *out == o).expect("test assertion").1;
+        assert_eq!(output(sdi), &surround);
+        // Center at -3 dB, LFE dropped, mono dialog on both sides
+        let center = 0.5 * core::f32::consts::FRAC_1_SQRT_2;
+        assert!((output(cue)[0] - (0.1 + center + 0.25)).abs() < 1e-6);
+        assert!((output(cue)[1] - (0.2 + center + 0.25)).abs() < 1e-6);
+
+        let swap = DownmixMatrix::new(&[&[0.0, 1.0], &[1.0, 0.0]]).expect("test assertion");
+        assert!(!mixer.set_bus_output_matrix(master, sdi, swap.clone()));
+        assert!(mixer.set_bus_output_matrix(master, HardwareOutput::Main, swap));
+        assert!(mixer.unassign_bus_output(master, sdi));
+        assert!(!mixer.unassign_bus_output(master, sdi));
+    }
+
+    #[test]
+    fn test_time_stretch_preserves_pitch() {
+        let rate = 48000;
+        let tone: Vec<f32> = (0..rate * 2)
+            .map(|i| (core::f32::consts::TAU * 220.0 * i as f32 / rate as f32).sin() * 0.5)
+            .collect();
+        let crossings = |s: &[f32]| s.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count();
+
+        for speed in [0.5, 2.0] {
+            let mut stretcher = TimeStretcher::new(rate, 1);
+            stretcher.set_speed(speed);
+            let mut out = vec![0.0; rate as usize / 2];
+            for block in out.chunks_mut(512) {
+                stretcher.render(&tone, block);
+            }
+            // Half a second of output consumes speed * half a second of input
+            let consumed = stretcher.position() as f64 / f64::from(rate);
+            assert!((consumed - speed * 0.5).abs() < 0.05, "{speed}: {consumed}");
+            let hz = crossings(&out[4800..]) as f32 / 0.4;
+            assert!((hz - 220.0).abs() < 10.0, "{speed}: {hz}");
+        }
+    }
+
+    #[test]
+    fn test_scrub_audio() {
+        let mut mixer = AudioMixer::new(48000, 1024);
+        let source: Vec<f32> = (0..96000).map(|i| ((i as f32) * 0.05).sin() * 0.5).collect();
+        let mut monitor = AudioMonitor::default();
+        let mut out = vec![0.0; 2048];
+        let mut render = |mixer: &mut AudioMixer, monitor: &AudioMonitor, ms: u64| {
+            mixer.render_monitor(
+                PlaybackState::Scrubbing,
+                PlaybackSpeed::NORMAL,
+                TimePosition::from_ms(ms),
+                monitor,
+                &source,
+                &mut out,
+            );
+            out.iter().fold(0.0f32, |m, s| m.max(s.abs()))
+        };
+
+        render(&mut mixer, &monitor, 100);
+        assert!(render(&mut mixer, &monitor, 130) > 0.1);
+        // A stationary playhead decays to silence within a grain
+        for _ in 0..3 {
+            render(&mut mixer, &monitor, 130);
+        }
+        assert_eq!(render(&mut mixer, &monitor, 130), 0.0);
+
+        monitor.scrub_audio = false;
+        assert_eq!(render(&mut mixer, &monitor, 300), 0.0);
+    }
+
+    #[test]
+    fn test_noise_reduction_insert() {
+        let mut mixer = AudioMixer::new(48000, 1024);
+        let slot = mixer
+            .add_track(1, "Dialogue")
+            .expect("test assertion")
+            .add_insert(AudioEffectType::SpectralNoiseReduction);
+        let insert = &mixer.get_track(1).expect("test assertion").inserts()[0];
+        assert_eq!(insert.parameters.get("reduction"), Some(12.0));
+
+        // One second of stereo hiss, then hiss under a tone
+        let mut seed = 7u32;
+        let source: Vec<f32> = (0..192_000)
+            .map(|i| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let hiss = ((seed >> 8) as f32 / (1 << 24) as f32 - 0.5) * 0.1;
+                let tone = if i >= 96_000 { ((i / 2) as f32 * 0.05).sin() * 0.3 } else { 0.0 };
+                hiss + tone
+            })
+            .collect();
+        let ms = TimePosition::from_ms;
+        assert!(mixer.learn_noise_profile(1, slot, &source, 2, ms(0), ms(10)).is_err());
+        assert!(mixer.learn_noise_profile(2, slot, &source, 2, ms(0), ms(900)).is_err());
+        mixer.learn_noise_profile(1, slot, &source, 2, ms(0), ms(900)).expect("test assertion");
+
+        let energy = |samples: &[f32]| samples.iter().map(|s| s * s).sum::<f32>();
+        let mut out = source.clone();
+        for block in out.chunks_mut(2048) {
+            mixer.process_track_inserts(1, 2, block);
+        }
+        // Hiss alone is cut by well over 12 dB; the tone survives
+        assert!(energy(&out[20_000..90_000]) < energy(&source[20_000..90_000]) * 0.1);
+        assert!(energy(&out[120_000..]) > energy(&source[120_000..]) * 0.8);
+
+        // Bypassed inserts pass audio through untouched
+        mixer
+            .get_track_mut(1)
+            .expect("test assertion")
+            .get_insert_mut(slot)
+            .expect("test assertion")
+            .bypassed = true;
+        let mut block = source[..2048].to_vec();
+        mixer.process_track_inserts(1, 2, &mut block);
+        assert_eq!(block, source[..2048]);
+    }
 }
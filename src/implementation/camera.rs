@@ -0,0 +1,338 @@
+//! Timeline camera and 2.5D layer placement for 3D compositing.
+//!
+//! In perspective compositing every visual track is a frame-sized plane
+//! centered on the origin, +Y up, pushed away from the camera by its
+//! [`TimelineTrack::z_position`]. The default camera sits on +Z at the
+//! distance where a plane at Z 0 exactly fills the frame, so an untouched
+//! camera reproduces the flat composite and moving it produces parallax.
+//! Planes of rotated media are turned upright and still fill the frame.
+
+use super::keyframe_animation::{AnimatedValue, AnimationLayer};
+use crate::{
+    scene3d::Mat4,
+    types::{Resolution, Rotation, TimePosition, TimelineTrack},
+};
+
+/// How the GPU pipeline composites visual tracks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CompositingMode {
+    /// Tracks stack flat in index order; Z positions and the camera are ignored.
+    #[default]
+    Flat,
+    /// Tracks are planes in 3D space rendered through the timeline camera.
+    Perspective,
+}
+
+/// Camera pose at one instant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraState {
+    /// Position in frame pixels.
+    pub position: [f32; 3],
+    /// Euler rotation in degrees (pitch about X, yaw about Y, roll about Z).
+    pub rotation: [f32; 3],
+    /// Vertical field of view in degrees.
+    pub fov:      f32,
+}
+
+impl CameraState {
+    /// Camera-to-world transform; roll applies first, then pitch, then yaw.
+    #[must_use]
+    pub fn world(&self) -> Mat4 {
+        let axis = |index: usize| {
+            let half = self.rotation[index].to_radians() / 2.0;
+            let mut q = [0.0, 0.0, 0.0, half.cos()];
+            q[index] = half.sin();
+            Mat4::from_trs([0.0; 3], q, [1.0; 3])
+        };
+        Mat4::from_trs(self.position, [0.0, 0.0, 0.0, 1.0], [1.0; 3])
+            .mul(&axis(1))
+            .mul(&axis(0))
+            .mul(&axis(2))
+    }
+
+    /// World-to-camera transform.
+    #[must_use]
+    pub fn view(&self) -> Mat4 {
+        self.world().inverse().unwrap_or_default()
+    }
+
+    /// View-projection for a frame of `width` x `height` pixels.
+    ///
+    /// Clip planes scale with the camera distance so layers from far behind
+    /// the frame to just in front of the lens stay visible.
+    #[must_use]
+    pub fn view_projection(&self, width: u32, height: u32) -> Mat4 {
+        let aspect = width as f32 / height.max(1) as f32;
+        let distance = self.position.iter().map(|v| v * v).sum::<f32>().sqrt().max(1.0);
+        Mat4::perspective(
+            self.fov.clamp(1.0, 179.0).to_radians(),
+            aspect,
+            distance * 1e-3,
+            distance * 100.0,
+        )
+        .mul(&self.view())
+    }
+}
+
+/// Animatable camera for the timeline's 3D compositing mode.
+///
+/// Position, rotation and field of view are keyframed on the layer's
+/// `position`, `rotation` and `fov` animation tracks.
+#[derive(Debug)]
+pub struct CameraLayer {
+    animation: AnimationLayer,
+    rest:      CameraState,
+}
+
+impl CameraLayer {
+    /// Default vertical field of view in degrees.
+    pub const DEFAULT_FOV: f32 = 39.6;
+
+    /// Create a camera framing a composite `height` pixels tall at Z 0.
+    pub fn new(name: impl Into<String>, height: u32) -> Self {
+        let fov = Self::DEFAULT_FOV;
+        let distance = Self::framing_distance(height, fov);
+        let rest = CameraState { position: [0.0, 0.0, distance], rotation: [0.0; 3], fov };
+
+        let mut animation = AnimationLayer::new(name, 0);
+        animation.create_track("position", vec3(rest.position));
+        animation.create_track("rotation", vec3(rest.rotation));
+        animation.create_track("fov", AnimatedValue::Float(f64::from(fov)));
+        Self { animation, rest }
+    }
+
+    /// Distance at which a plane `height` pixels tall fills a `fov` view.
+    #[must_use]
+    pub fn framing_distance(height: u32, fov: f32) -> f32 {
+        height as f32 / 2.0 / (fov.clamp(1.0, 179.0).to_radians() / 2.0).tan()
+    }
+
+    /// Get the camera name.
+    pub fn name(&self) -> &str {
+        self.animation.name()
+    }
+
+    /// Get the pose used when a property has no keyframes.
+    pub fn rest(&self) -> CameraState {
+        self.rest
+    }
+
+    /// Get the keyframe tracks.
+    pub fn animation(&self) -> &AnimationLayer {
+        &self.animation
+    }
+
+    /// Get the mutable keyframe tracks.
+    pub fn animation_mut(&mut self) -> &mut AnimationLayer {
+        &mut self.animation
+    }
+
+    /// Keyframe the position.
+    pub fn set_position(&mut self, time: TimePosition, position: [f32; 3]) {
+        self.keyframe("position", time, vec3(position));
+    }
+
+    /// Keyframe the rotation, in degrees.
+    pub fn set_rotation(&mut self, time: TimePosition, rotation: [f32; 3]) {
+        self.keyframe("rotation", time, vec3(rotation));
+    }
+
+    /// Keyframe the vertical field of view, in degrees.
+    pub fn set_fov(&mut self, time: TimePosition, fov: f32) {
+        self.keyframe("fov", time, AnimatedValue::Float(f64::from(fov)));
+    }
+
+    fn keyframe(&mut self, property: &str, time: TimePosition, value: AnimatedValue) {
+        if let Some(track) = self.animation.get_track_by_property_mut(property) {
+            track.add_keyframe(time, value);
+        }
+    }
+
+    /// Evaluate the camera at a time.
+    pub fn state_at(&self, time: TimePosition) -> CameraState {
+        let mut state = self.rest;
+        for (property, value) in self.animation.evaluate_all(time) {
+            match (property, value) {
+                ("position", AnimatedValue::Vec3(x, y, z)) => {
+                    state.position = [x as f32, y as f32, z as f32];
+                },
+                ("rotation", AnimatedValue::Vec3(x, y, z)) => {
+                    state.rotation = [x as f32, y as f32, z as f32];
+                },
+                ("fov", AnimatedValue::Float(fov)) => state.fov = fov as f32,
+                _ => {},
+            }
+        }
+        state
+    }
+}
+
+fn vec3([x, y, z]: [f32; 3]) -> AnimatedValue {
+    AnimatedValue::Vec3(f64::from(x), f64::from(y), f64::from(z))
+}
+
+/// Model transform of a track's plane in a `width` x `height` composite.
+///
+/// Maps the unit quad `[-0.5, 0.5]²` to frame pixels at the track's depth,
+/// turned by the `rotation` of the media shown on it.
+pub(crate) fn track_plane(
+    track: &TimelineTrack, rotation: Rotation, width: u32, height: u32,
+) -> Mat4 {
+    // Clockwise on screen is negative about +Z
+    let half = -(rotation.degrees() as f32).to_radians() / 2.0;
+    let Resolution { width, height } = rotation.apply(Resolution::new(width, height));
+    Mat4::from_trs(
+        [0.0, 0.0, -track.z_position],
+        [0.0, 0.0, half.sin(), half.cos()],
+        [width as f32, height as f32, 1.0],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        implementation::{AssetLibrary, GpuPipeline, TimelineManager},
+        types::{TrackType, timeline::TimelineClip},
+    };
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn test_rest_camera_frames_composite() {
+        let camera = CameraLayer::new("Camera", 1080);
+        let track = TimelineTrack::new(1, "V1", TrackType::Video, 0);
+        let mvp = camera
+            .state_at(TimePosition::from_ms(0))
+            .view_projection(1920, 1080)
+            .mul(&track_plane(&track, Rotation::None, 1920, 1080));
+
+        let corner = mvp.transform_point([0.5, 0.5, 0.0]);
+        assert!(close(corner[0], 1.0) && close(corner[1], 1.0));
+        let corner = mvp.transform_point([-0.5, -0.5, 0.0]);
+        assert!(close(corner[0], -1.0) && close(corner[1], -1.0));
+    }
+
+    #[test]
+    fn test_camera_keyframes_and_parallax() {
+        let mut camera = CameraLayer::new("Camera", 1000);
+        let rest = camera.rest();
+        camera.set_position(TimePosition::from_ms(0), rest.position);
+        camera.set_position(TimePosition::from_ms(1000), [100.0, 0.0, rest.position[2]]);
+        camera.set_fov(TimePosition::from_ms(1000), 60.0);
+
+        let state = camera.state_at(TimePosition::from_ms(500));
+        assert!(close(state.position[0], 50.0));
+        assert_eq!(state.rotation, [0.0; 3]);
+
+        // Panning shifts near layers further across the frame than far ones
+        let state = camera.state_at(TimePosition::from_ms(1000));
+        assert!(close(state.fov, 60.0));
+        let view_projection = state.view_projection(1000, 1000);
+        let mut near = TimelineTrack::new(1, "Near", TrackType::Video, 1);
+        near.z_position = -200.0;
+        let mut far = TimelineTrack::new(2, "Far", TrackType::Video, 0);
+        far.z_position = 2000.0;
+        let shift = |track: &TimelineTrack| {
+            let plane = track_plane(track, Rotation::None, 1000, 1000);
+            view_projection.mul(&plane).transform_point([0.0; 3])[0]
+        };
+        assert!(shift(&near) < shift(&far));
+        assert!(shift(&far) < 0.0);
+
+        // Yaw turns the view: a 90° turn puts the frame center off to the side
+        camera.set_rotation(TimePosition::from_ms(0), [0.0, 90.0, 0.0]);
+        let world = camera.state_at(TimePosition::from_ms(0)).world();
+        let forward = world.transform_point([0.0, 0.0, -1.0]);
+        let origin = world.transform_point([0.0; 3]);
+        assert!(close(forward[0] - origin[0], -1.0));
+    }
+
+    #[test]
+    fn test_perspective_dispatch_orders_by_depth() {
+        let mut timeline = TimelineManager::new();
+        let background = timeline.add_track("Background", TrackType::Video);
+        let title = timeline.add_track("Title", TrackType::Video);
+        for (clip_id, track_id) in [(1, background), (2, title)] {
+            timeline
+                .add_clip(
+                    track_id,
+                    TimelineClip::new(
+                        clip_id,
+                        clip_id,
+                        TimePosition::from_ms(0),
+                        TimePosition::from_ms(2_000),
+                    ),
+                )
+                .expect("test assertion");
+        }
+        // Pull the bottom track in front of the title
+        assert!(timeline.set_track_z_position(background, -500.0));
+        assert!(!timeline.set_track_z_position(99, 1.0));
+
+        let assets = AssetLibrary::new();
+        let mut gpu = GpuPipeline::new(true);
+        let time = TimePosition::from_ms(500);
+        assert!(gpu.dispatch_layers(&timeline, &assets, time, 1920, 1080).is_err());
+        gpu.initialize();
+
+        let flat =
+            gpu.dispatch_layers(&timeline, &assets, time, 1920, 1080).expect("test assertion");
+        assert_eq!(flat.mode, CompositingMode::Flat);
+        let order: Vec<u64> = flat.layers.iter().map(|l| l.track_id).collect();
+        assert_eq!(order, vec![background, title]);
+        let corner = flat.layers[0].mvp.transform_point([0.5, 0.5, 0.0]);
+        assert!(close(corner[0], 1.0) && close(corner[1], 1.0));
+
+        gpu.set_compositing_mode(CompositingMode::Perspective);
+        let dispatch =
+            gpu.dispatch_layers(&timeline, &assets, time, 1920, 1080).expect("test assertion");
+        let order: Vec<u64> = dispatch.layers.iter().map(|l| l.track_id).collect();
+        assert_eq!(order, vec![title, background]);
+        // The nearer plane overfills the frame
+        let corner = dispatch.layers[1].mvp.transform_point([0.5, 0.5, 0.0]);
+        assert!(corner[0] > 1.0);
+
+        timeline.set_camera(Some(CameraLayer::new("Camera", 1080)));
+        let camera = timeline.camera_mut().expect("test assertion");
+        camera.set_position(TimePosition::from_ms(0), [0.0, 0.0, -5_000.0]);
+        camera.set_rotation(TimePosition::from_ms(0), [0.0, 180.0, 0.0]);
+        let dispatch =
+            gpu.dispatch_layers(&timeline, &assets, time, 1920, 1080).expect("test assertion");
+        let order: Vec<u64> = dispatch.layers.iter().map(|l| l.track_id).collect();
+        assert_eq!(order, vec![background, title]);
+    }
+    #[test]
+    fn test_rotated_media_plane() {
+        let mut timeline = TimelineManager::new();
+        let track = timeline.add_track("V1", TrackType::Video);
+        let mut assets = AssetLibrary::new();
+        let media = assets.import_video("portrait.mp4").expect("test assertion");
+        assets.video_clip_mut(media).expect("test assertion").rotation = Rotation::Clockwise90;
+        timeline
+            .add_clip(
+                track,
+                TimelineClip::new(1, media, TimePosition::from_ms(0), TimePosition::from_ms(1_000)),
+            )
+            .expect("test assertion");
+
+        let mut gpu = GpuPipeline::new(true);
+        gpu.initialize();
+        let time = TimePosition::from_ms(500);
+        // The quad's top right lands bottom right once turned clockwise
+        let dispatch =
+            gpu.dispatch_layers(&timeline, &assets, time, 1920, 1080).expect("test assertion");
+        let corner = dispatch.layers[0].mvp.transform_point([0.5, 0.5, 0.0]);
+        assert!(close(corner[0], 1.0) && close(corner[1], -1.0));
+
+        // An override corrects a wrong tag
+        assert!(assets.set_rotation_override(media, Some(Rotation::None)));
+        let dispatch =
+            gpu.dispatch_layers(&timeline, &assets, time, 1920, 1080).expect("test assertion");
+        let corner = dispatch.layers[0].mvp.transform_point([0.5, 0.5, 0.0]);
+        assert!(close(corner[0], 1.0) && close(corner[1], 1.0));
+        assert!(!assets.set_rotation_override(99, None));
+    }
+}
@@ -0,0 +1,633 @@
+//! Caption tracks: timed text cues with SRT/WebVTT interchange.
+//!
+//! A [`CaptionTrack`] holds the cues of one `TrackType::Subtitle` timeline
+//! track. Cues can be burned into exported frames through
+//! [`GpuPipeline::dispatch_captions`](super::GpuPipeline::dispatch_captions)
+//! or written next to the export as sidecar files.
+
+use super::timeline::TimelineManager;
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    types::TimePosition,
+};
+
+/// Where a cue sits in the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CaptionPosition {
+    /// Centered above the bottom title-safe margin.
+    #[default]
+    Bottom,
+    /// Centered below the top title-safe margin.
+    Top,
+    /// Cue box centered at a point, in fractions of the frame from the
+    /// top-left corner.
+    Custom {
+        /// Horizontal center (0.0 = left edge).
+        x: f32,
+        /// Vertical center (0.0 = top edge).
+        y: f32,
+    },
+}
+
+/// Cue appearance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaptionStyle {
+    /// Placement in the frame.
+    pub position:   CaptionPosition,
+    /// Text color (RGBA).
+    pub color:      [u8; 4],
+    /// Box drawn behind the text (RGBA), or `None` for bare text.
+    pub background: Option<[u8; 4]>,
+    /// Line height as a fraction of the frame height.
+    pub font_size:  f32,
+}
+
+impl Default for CaptionStyle {
+    fn default() -> Self {
+        Self {
+            position:   CaptionPosition::Bottom,
+            color:      [255, 255, 255, 255],
+            background: Some([0, 0, 0, 160]),
+            font_size:  0.05,
+        }
+    }
+}
+
+/// Timed text cue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptionCue {
+    /// Cue ID, unique within its track.
+    pub id:    u64,
+    /// Time the cue appears.
+    pub start: TimePosition,
+    /// Time the cue disappears (exclusive).
+    pub end:   TimePosition,
+    /// Cue text; lines are separated by `\n`.
+    pub text:  String,
+    /// Per-cue style overriding the track style.
+    pub style: Option<CaptionStyle>,
+}
+
+impl CaptionCue {
+    /// Returns whether the cue is showing at a time.
+    #[must_use]
+    pub fn contains(&self, time: TimePosition) -> bool {
+        time.ms >= self.start.ms && time.ms < self.end.ms
+    }
+}
+
+/// Caption interchange format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaptionFormat {
+    /// SubRip (`.srt`).
+    Srt,
+    /// WebVTT (`.vtt`).
+    WebVtt,
+}
+
+impl CaptionFormat {
+    /// File extension without the dot.
+    #[must_use]
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Srt => "srt",
+            Self::WebVtt => "vtt",
+        }
+    }
+}
+
+/// Timed cues of a subtitle track.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptionTrack {
+    track_id:    u64,
+    language:    Option<String>,
+    style:       CaptionStyle,
+    cues:        Vec<CaptionCue>,
+    next_cue_id: u64,
+}
+
+impl CaptionTrack {
+    /// Create an empty caption track for a timeline track.
+    pub fn new(track_id: u64) -> Self {
+        Self {
+            track_id,
+            language: None,
+            style: CaptionStyle::default(),
+            cues: Vec::new(),
+            next_cue_id: 1,
+        }
+    }
+
+    /// Get the timeline track ID.
+    pub fn track_id(&self) -> u64 {
+        self.track_id
+    }
+
+    /// Get the BCP 47 language tag.
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Set the BCP 47 language tag.
+    pub fn set_language(&mut self, language: Option<String>) {
+        self.language = language;
+    }
+
+    /// Get the default cue style.
+    pub fn style(&self) -> &CaptionStyle {
+        &self.style
+    }
+
+    /// Set the default cue style.
+    pub fn set_style(&mut self, style: CaptionStyle) {
+        self.style = style;
+    }
+
+    /// Get the cues in start order.
+    pub fn cues(&self) -> &[CaptionCue] {
+        &self.cues
+    }
+
+    /// Get a cue.
+    pub fn cue(&self, cue_id: u64) -> Option<&CaptionCue> {
+        self.cues.iter().find(|c| c.id == cue_id)
+    }
+
+    /// Add a cue, returning its ID.
+    pub fn add_cue(
+        &mut self, start: TimePosition, end: TimePosition, text: impl Into<String>,
+    ) -> VideoEditorResult<u64> {
+        if end.ms <= start.ms {
+            return Err(VideoEditorError::timeline(
+                "add_caption",
+                "Caption cue must end after it starts",
+            ));
+        }
+        let id = self.next_cue_id;
+        self.next_cue_id += 1;
+        let index = self.cues.partition_point(|c| c.start.ms <= start.ms);
+        self.cues.insert(index, CaptionCue { id, start, end, text: text.into(), style: None });
+        Ok(id)
+    }
+
+    /// Set a cue's per-cue style override.
+    pub fn set_cue_style(&mut self, cue_id: u64, style: Option<CaptionStyle>) -> bool {
+        self.cues.iter_mut().find(|c| c.id == cue_id).map(|c| c.style = style).is_some()
+    }
+
+    /// Replace a cue's text.
+    pub fn set_cue_text(&mut self, cue_id: u64, text: impl Into<String>) -> bool {
+        self.cues.iter_mut().find(|c| c.id == cue_id).map(|c| c.text = text.into()).is_some()
+    }
+
+    /// Remove a cue.
+    pub fn remove_cue(&mut self, cue_id: u64) -> Option<CaptionCue> {
+        let index = self.cues.iter().position(|c| c.id == cue_id)?;
+        Some(self.cues.remove(index))
+    }
+
+    /// Cues showing at a time, in start order.
+    pub fn active_cues(&self, time: TimePosition) -> impl Iterator<Item = &CaptionCue> {
+        self.cues
+            .iter()
+            .take_while(move |c| c.start.ms <= time.ms)
+            .filter(move |c| c.contains(time))
+    }
+
+    /// Style a cue is drawn with.
+    pub fn resolved_style(&self, cue: &CaptionCue) -> CaptionStyle {
+        cue.style.unwrap_or(self.style)
+    }
+
+    /// Copy of the cues overlapping `start..end`, clipped to the range and
+    /// shifted so `start` becomes zero.
+    pub fn trimmed(&self, start: TimePosition, end: TimePosition) -> Self {
+        let mut trimmed = Self { cues: Vec::new(), ..self.clone() };
+        trimmed.cues = self
+            .cues
+            .iter()
+            .filter(|c| c.end.ms > start.ms && c.start.ms < end.ms)
+            .map(|c| CaptionCue {
+                start: TimePosition::from_ms(c.start.ms.max(start.ms) - start.ms),
+                end: TimePosition::from_ms(c.end.ms.min(end.ms) - start.ms),
+                ..c.clone()
+            })
+            .collect();
+        trimmed
+    }
+
+    /// Parse cues in a format.
+    pub fn parse(track_id: u64, format: CaptionFormat, text: &str) -> VideoEditorResult<Self> {
+        match format {
+            CaptionFormat::Srt => Self::from_srt(track_id, text),
+            CaptionFormat::WebVtt => Self::from_vtt(track_id, text),
+        }
+    }
+
+    /// Write cues in a format.
+    pub fn write(&self, format: CaptionFormat) -> String {
+        match format {
+            CaptionFormat::Srt => self.to_srt(),
+            CaptionFormat::WebVtt => self.to_vtt(),
+        }
+    }
+
+    /// Parse a SubRip file. Formatting tags are stripped from cue text.
+    pub fn from_srt(track_id: u64, text: &str) -> VideoEditorResult<Self> {
+        let mut track = Self::new(track_id);
+        for block in blocks(text) {
+            // The counter line is optional in practice; find the timing line
+            let Some(timing) = block.iter().position(|l| l.contains("-->")) else {
+                return Err(VideoEditorError::conversion(format!(
+                    "SRT cue without timing: {}",
+                    block[0]
+                )));
+            };
+            let (start, end, _) = parse_timing(block[timing])?;
+            let body = strip_tags(&block[timing + 1..].join("\n"));
+            track.add_cue(start, end, body)?;
+        }
+        Ok(track)
+    }
+
+    /// Write a SubRip file.
+    pub fn to_srt(&self) -> String {
+        let mut out = String::new();
+        for (index, cue) in self.cues.iter().enumerate() {
+            out.push_str(&format!(
+                "{}\n{} --> {}\n{}\n\n",
+                index + 1,
+                timestamp(cue.start, ','),
+                timestamp(cue.end, ','),
+                cue.text
+            ));
+        }
+        out
+    }
+
+    /// Parse a WebVTT file.
+    ///
+    /// `line` and `position` cue settings map to a cue position, and the
+    /// color and background of a `::cue` style block become the track
+    /// style. Voice and formatting tags are stripped from cue text.
+    pub fn from_vtt(track_id: u64, text: &str) -> VideoEditorResult<Self> {
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        if !text.starts_with("WEBVTT") {
+            return Err(VideoEditorError::conversion("Missing WEBVTT header"));
+        }
+
+        let mut track = Self::new(track_id);
+        for block in blocks(text).skip(1) {
+            let Some(timing) = block.iter().position(|l| l.contains("-->")) else {
+                if block[0].starts_with("STYLE") {
+                    track.style = parse_cue_css(&block[1..].join("\n"), track.style);
+                }
+                // NOTE and REGION blocks carry nothing we keep
+                continue;
+            };
+            let (start, end, settings) = parse_timing(block[timing])?;
+            let body = strip_tags(&block[timing + 1..].join("\n"));
+            let id = track.add_cue(start, end, body)?;
+            if let Some(position) = parse_cue_settings(settings) {
+                track.set_cue_style(id, Some(CaptionStyle { position, ..track.style }));
+            }
+        }
+        Ok(track)
+    }
+
+    /// Write a WebVTT file, with the track style as a `::cue` block.
+    pub fn to_vtt(&self) -> String {
+        let mut out = String::from("WEBVTT\n\n");
+        let mut css = format!("::cue {{\n  color: {};\n", css_color(self.style.color));
+        let background = self.style.background.map_or_else(|| "transparent".into(), css_color);
+        css.push_str(&format!("  background-color: {background};\n"));
+        out.push_str(&format!("STYLE\n{css}}}\n\n"));
+
+        for cue in &self.cues {
+            out.push_str(&format!("{} --> {}", timestamp(cue.start, '.'), timestamp(cue.end, '.')));
+            match self.resolved_style(cue).position {
+                CaptionPosition::Bottom => {},
+                CaptionPosition::Top => out.push_str(" line:0"),
+                CaptionPosition::Custom { x, y } => out.push_str(&format!(
+                    " position:{}% line:{}%",
+                    (x * 100.0).round(),
+                    (y * 100.0).round()
+                )),
+            }
+            out.push_str(&format!("\n{}\n\n", cue.text));
+        }
+        out
+    }
+}
+
+/// Sidecar caption file written next to an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CaptionSidecar {
+    /// Subtitle track to write.
+    pub track_id: u64,
+    /// File format.
+    pub format:   CaptionFormat,
+}
+
+/// Caption handling for an export.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CaptionExportOptions {
+    /// Subtitle tracks burned into the picture, bottom to top.
+    pub burn_in:  Vec<u64>,
+    /// Sidecar files written alongside the output.
+    pub sidecars: Vec<CaptionSidecar>,
+}
+
+impl CaptionExportOptions {
+    /// Path of a sidecar file: the output path with its extension replaced
+    /// by the track language (when set) and the format extension.
+    #[must_use]
+    pub fn sidecar_path(
+        output_path: &str, language: Option<&str>, format: CaptionFormat,
+    ) -> String {
+        let name_start = output_path.rfind(['/', '\\']).map_or(0, |i| i + 1);
+        let stem = match output_path[name_start..].rfind('.') {
+            Some(dot) if dot > 0 => &output_path[..name_start + dot],
+            _ => output_path,
+        };
+        match language {
+            Some(language) => format!("{stem}.{language}.{}", format.extension()),
+            None => format!("{stem}.{}", format.extension()),
+        }
+    }
+
+    /// Render the sidecar files as `(path, contents)` pairs.
+    ///
+    /// With an export range, cues are clipped to it and retimed so the
+    /// range start is zero, matching the exported media.
+    pub fn render_sidecars(
+        &self, timeline: &TimelineManager, output_path: &str,
+        range: Option<(TimePosition, TimePosition)>,
+    ) -> VideoEditorResult<Vec<(String, String)>> {
+        self.sidecars
+            .iter()
+            .map(|sidecar| {
+                let track = timeline.caption_track(sidecar.track_id).ok_or_else(|| {
+                    VideoEditorError::Export(format!(
+                        "Track {} has no captions to export",
+                        sidecar.track_id
+                    ))
+                })?;
+                let path = Self::sidecar_path(output_path, track.language(), sidecar.format);
+                let contents = match range {
+                    Some((start, end)) => track.trimmed(start, end).write(sidecar.format),
+                    None => track.write(sidecar.format),
+                };
+                Ok((path, contents))
+            })
+            .collect()
+    }
+}
+
+/// Blank-line separated blocks of non-empty, right-trimmed lines.
+fn blocks(text: &str) -> impl Iterator<Item = Vec<&str>> {
+    let mut lines = text.lines().map(str::trim_end).peekable();
+    std::iter::from_fn(move || {
+        while lines.next_if(|l| l.is_empty()).is_some() {}
+        let block: Vec<&str> = std::iter::from_fn(|| lines.next_if(|l| !l.is_empty())).collect();
+        (!block.is_empty()).then_some(block)
+    })
+}
+
+/// Parse `start --> end [settings]`.
+fn parse_timing(line: &str) -> VideoEditorResult<(TimePosition, TimePosition, &str)> {
+    let invalid = || VideoEditorError::conversion(format!("Invalid cue timing: {line}"));
+    let (start, rest) = line.split_once("-->").ok_or_else(invalid)?;
+    let rest = rest.trim_start();
+    let (end, settings) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let start = parse_timestamp(start.trim()).ok_or_else(invalid)?;
+    let end = parse_timestamp(end).ok_or_else(invalid)?;
+    Ok((start, end, settings.trim()))
+}
+
+/// Parse `[HH:]MM:SS[,.]mmm`.
+fn parse_timestamp(text: &str) -> Option<TimePosition> {
+    let (clock, millis) = text.split_once([',', '.'])?;
+    let millis: u64 = millis.parse().ok().filter(|_| millis.len() == 3)?;
+    let mut seconds = 0u64;
+    let mut fields = 0;
+    for field in clock.split(':') {
+        seconds = seconds * 60 + field.parse::<u64>().ok()?;
+        fields += 1;
+    }
+    (2..=3).contains(&fields).then(|| TimePosition::from_ms(seconds * 1000 + millis))
+}
+
+fn timestamp(time: TimePosition, separator: char) -> String {
+    let ms = time.ms;
+    format!(
+        "{:02}:{:02}:{:02}{separator}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+/// Remove `<...>` markup and decode the entities WebVTT requires.
+fn strip_tags(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {},
+        }
+    }
+    out.replace("&lt;", "<").replace("&gt;", ">").replace("&nbsp;", "\u{a0}").replace("&amp;", "&")
+}
+
+/// Map `line:` and `position:` cue settings to a position.
+fn parse_cue_settings(settings: &str) -> Option<CaptionPosition> {
+    let percent = |value: &str| value.strip_suffix('%')?.parse::<f32>().ok().map(|v| v / 100.0);
+    let mut x = None;
+    let mut y = None;
+    let mut top = false;
+    for setting in settings.split_whitespace() {
+        // Alignment suffixes such as `line:10%,start` are ignored
+        let (key, value) = setting.split_once(':')?;
+        let value = value.split(',').next().unwrap_or(value);
+        match key {
+            "position" => x = percent(value),
+            "line" if value == "0" => top = true,
+            "line" => y = percent(value),
+            _ => {},
+        }
+    }
+    match (x, y) {
+        (None, None) if top => Some(CaptionPosition::Top),
+        (None, None) => None,
+        (x, y) => Some(CaptionPosition::Custom { x: x.unwrap_or(0.5), y: y.unwrap_or(0.9) }),
+    }
+}
+
+/// Apply `color` and `background-color` from a `::cue` rule.
+fn parse_cue_css(css: &str, mut style: CaptionStyle) -> CaptionStyle {
+    let Some(body) = css.split_once("::cue").and_then(|(_, rest)| rest.split_once('{')) else {
+        return style;
+    };
+    let body = body.1.split('}').next().unwrap_or_default();
+    for declaration in body.split(';') {
+        let Some((property, value)) = declaration.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match property.trim() {
+            "color" => style.color = parse_css_color(value).unwrap_or(style.color),
+            "background-color" | "background" if matches!(value, "transparent" | "none") => {
+                style.background = None;
+            },
+            "background-color" | "background" => {
+                style.background = parse_css_color(value).or(style.background);
+            },
+            _ => {},
+        }
+    }
+    style
+}
+
+/// Parse `#rgb`, `#rrggbb` or `#rrggbbaa`.
+fn parse_css_color(value: &str) -> Option<[u8; 4]> {
+    let hex = value.strip_prefix('#')?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    match hex.len() {
+        3 => {
+            let digit = |i: usize| u8::from_str_radix(hex.get(i..=i)?, 16).ok().map(|d| d * 17);
+            Some([digit(0)?, digit(1)?, digit(2)?, 255])
+        },
+        6 => Some([channel(0)?, channel(2)?, channel(4)?, 255]),
+        8 => Some([channel(0)?, channel(2)?, channel(4)?, channel(6)?]),
+        _ => None,
+    }
+}
+
+fn css_color([r, g, b, a]: [u8; 4]) -> String {
+    if a == 255 {
+        format!("#{r:02x}{g:02x}{b:02x}")
+    } else {
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{implementation::GpuPipeline, types::TrackType};
+
+    const SRT: &str = "1\r\n00:00:01,000 --> 00:00:02,500\r\nHello <i>there</i>\r\n\r\n2\r\n\
+                       00:00:03,000 --> 00:00:04,000\r\nTwo\r\nlines\r\n";
+
+    #[test]
+    fn test_srt_round_trip() {
+        let track = CaptionTrack::from_srt(1, SRT).expect("test assertion");
+        assert_eq!(track.cues().len(), 2);
+        assert_eq!(track.cues()[0].text, "Hello there");
+        assert_eq!(track.cues()[0].end, TimePosition::from_ms(2_500));
+        assert_eq!(track.cues()[1].text, "Two\nlines");
+
+        let written = track.to_srt();
+        assert!(written.starts_with("1\n00:00:01,000 --> 00:00:02,500\nHello there\n\n2\n"));
+        assert_eq!(CaptionTrack::from_srt(1, &written).expect("test assertion"), track);
+
+        assert_eq!(track.active_cues(TimePosition::from_ms(2_000)).count(), 1);
+        assert_eq!(track.active_cues(TimePosition::from_ms(2_500)).count(), 0);
+        assert!(CaptionTrack::from_srt(1, "1\n00:00:01 --> 00:00:02\nBad\n").is_err());
+    }
+
+    #[test]
+    fn test_vtt_styles_and_positions() {
+        let vtt = "WEBVTT\n\nSTYLE\n::cue {\n  color: #ff0;\n  background-color: transparent;\n}\n\n\
+                   NOTE ignored\n\nintro\n00:01.000 --> 00:02.000 line:0\n<v Ana>Top</v> &amp; \
+                   more\n\n00:02.000 --> 00:03.000 position:25% line:50%\nCustom\n\n\
+                   01:00:00.000 --> 01:00:01.000\nBottom\n";
+        let track = CaptionTrack::from_vtt(1, vtt).expect("test assertion");
+        assert_eq!(track.style().color, [255, 255, 0, 255]);
+        assert_eq!(track.style().background, None);
+
+        let cues = track.cues();
+        assert_eq!(cues[0].text, "Top & more");
+        assert_eq!(track.resolved_style(&cues[0]).position, CaptionPosition::Top);
+        assert_eq!(
+            track.resolved_style(&cues[1]).position,
+            CaptionPosition::Custom { x: 0.25, y: 0.5 }
+        );
+        assert_eq!(cues[2].start, TimePosition::from_ms(3_600_000));
+        assert_eq!(track.resolved_style(&cues[2]).position, CaptionPosition::Bottom);
+
+        let written = track.to_vtt();
+        assert!(written.contains("00:00:02.000 --> 00:00:03.000 position:25% line:50%\n"));
+        assert_eq!(CaptionTrack::from_vtt(1, &written).expect("test assertion"), track);
+        assert!(CaptionTrack::from_vtt(1, SRT).is_err());
+    }
+
+    #[test]
+    fn test_burn_in_and_sidecars() {
+        let mut timeline = TimelineManager::new();
+        let subtitles = timeline.add_caption_track("English");
+        let track = timeline.get_track(subtitles).expect("test assertion");
+        assert_eq!(track.track_type, TrackType::Subtitle);
+        let captions = timeline.caption_track_mut(subtitles).expect("test assertion");
+        captions.set_language(Some("en".into()));
+        captions
+            .add_cue(TimePosition::from_ms(1_000), TimePosition::from_ms(3_000), "One\nTwo")
+            .expect("test assertion");
+        let top = captions
+            .add_cue(TimePosition::from_ms(2_000), TimePosition::from_ms(5_000), "Top")
+            .expect("test assertion");
+        captions.set_cue_style(
+            top,
+            Some(CaptionStyle { position: CaptionPosition::Top, ..CaptionStyle::default() }),
+        );
+
+        let mut gpu = GpuPipeline::new(true);
+        gpu.initialize();
+        let time = TimePosition::from_ms(2_500);
+        let draws = gpu.dispatch_captions(&timeline, &[subtitles], time, 1920, 1080);
+        let draws = draws.expect("test assertion");
+        assert_eq!(draws.len(), 2);
+        assert_eq!(draws[0].lines, vec!["One", "Two"]);
+        // Bottom cue box sits in the lower half, top cue in the upper half
+        assert!(draws[0].mvp.transform_point([0.0; 3])[1] < -0.5);
+        assert!(draws[1].mvp.transform_point([0.0; 3])[1] > 0.5);
+        assert!((draws[0].font_px - 54.0).abs() < 1e-3);
+        assert!(
+            gpu.dispatch_captions(
+                &timeline,
+                &[subtitles],
+                TimePosition::from_ms(6_000),
+                1920,
+                1080
+            )
+            .expect("test assertion")
+            .is_empty()
+        );
+        assert!(gpu.dispatch_captions(&timeline, &[99], time, 1920, 1080).is_err());
+
+        let options = CaptionExportOptions {
+            burn_in:  vec![subtitles],
+            sidecars: vec![
+                CaptionSidecar { track_id: subtitles, format: CaptionFormat::Srt },
+                CaptionSidecar { track_id: subtitles, format: CaptionFormat::WebVtt },
+            ],
+        };
+        let range = Some((TimePosition::from_ms(2_000), TimePosition::from_ms(4_000)));
+        let files = options.render_sidecars(&timeline, "out/v1.0/cut.mp4", range);
+        let files = files.expect("test assertion");
+        assert_eq!(files[0].0, "out/v1.0/cut.en.srt");
+        assert_eq!(files[1].0, "out/v1.0/cut.en.vtt");
+        assert!(files[0].1.starts_with("1\n00:00:00,000 --> 00:00:01,000\nOne\nTwo\n\n"));
+        assert!(files[0].1.contains("00:00:00,000 --> 00:00:02,000\nTop"));
+        assert_eq!(CaptionExportOptions::sidecar_path("cut", None, CaptionFormat::Srt), "cut.srt");
+
+        assert!(timeline.remove_track(subtitles));
+        assert!(timeline.caption_track(subtitles).is_none());
+        assert!(options.render_sidecars(&timeline, "cut.mp4", None).is_err());
+    }
+}
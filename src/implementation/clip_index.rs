@@ -0,0 +1,291 @@
+//! Time index over timeline clips for range queries and zoomed-out drawing.
+//!
+//! Each track keeps its clips sorted by start with a running maximum of
+//! their ends, so the first clip reaching into a range is a binary search
+//! away and a range query costs `O(log n + k)` per track. The index is
+//! rebuilt lazily by [`TimelineManager`](super::TimelineManager) after
+//! edits.
+
+use crate::types::{TimePosition, TimelineTrack};
+
+/// Narrowest clip drawn on its own by
+/// [`visible_items`](super::TimelineManager::visible_items), in pixels.
+pub const MIN_ITEM_PX: f64 = 3.0;
+
+/// Something to draw in a timeline viewport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineItem {
+    /// A clip wide enough to draw on its own.
+    Clip {
+        /// Track ID.
+        track_id: u64,
+        /// Clip ID.
+        clip_id:  u64,
+        /// Clip start.
+        start:    TimePosition,
+        /// Clip end.
+        end:      TimePosition,
+    },
+    /// A run of clips too narrow to tell apart at the current zoom.
+    Summary {
+        /// Track ID.
+        track_id:   u64,
+        /// Start of the first clip.
+        start:      TimePosition,
+        /// Latest end among the clips.
+        end:        TimePosition,
+        /// Number of clips in the run.
+        clip_count: usize,
+    },
+}
+
+impl TimelineItem {
+    /// Track the item is drawn on.
+    #[must_use]
+    pub const fn track_id(&self) -> u64 {
+        match self {
+            Self::Clip { track_id, .. } | Self::Summary { track_id, .. } => *track_id,
+        }
+    }
+
+    /// Number of clips the item stands for.
+    #[must_use]
+    pub const fn clip_count(&self) -> usize {
+        match self {
+            Self::Clip { .. } => 1,
+            Self::Summary { clip_count, .. } => *clip_count,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    start:    u64,
+    end:      u64,
+    clip_pos: usize,
+}
+
+#[derive(Debug)]
+struct TrackIndex {
+    entries: Vec<Entry>,
+    /// `max_end[i]` is the latest end among `entries[..=i]`.
+    max_end: Vec<u64>,
+}
+
+impl TrackIndex {
+    fn new(track: &TimelineTrack) -> Self {
+        let mut entries: Vec<Entry> = track
+            .clips
+            .iter()
+            .enumerate()
+            .map(|(clip_pos, c)| Entry { start: c.start.ms, end: c.end().ms, clip_pos })
+            .collect();
+        entries.sort_by_key(|e| e.start);
+        let max_end = entries
+            .iter()
+            .scan(0, |max, e| {
+                *max = e.end.max(*max);
+                Some(*max)
+            })
+            .collect();
+        Self { entries, max_end }
+    }
+
+    /// Position of the first entry that could end after `ms`.
+    fn first_after(&self, ms: u64) -> usize {
+        self.max_end.partition_point(|&end| end <= ms)
+    }
+
+    /// Position of the first entry starting at or after `ms`.
+    fn first_starting(&self, ms: u64) -> usize {
+        self.entries.partition_point(|e| e.start < ms)
+    }
+}
+
+/// Per-track clip index; positions refer to the tracks it was built from.
+#[derive(Debug)]
+pub(crate) struct ClipIndex {
+    tracks: Vec<TrackIndex>,
+}
+
+impl ClipIndex {
+    pub(crate) fn new(tracks: &[TimelineTrack]) -> Self {
+        Self { tracks: tracks.iter().map(TrackIndex::new).collect() }
+    }
+
+    /// `(track position, clip position)` of clips overlapping
+    /// `start..end`, by track then start.
+    pub(crate) fn in_range(&self, start: u64, end: u64) -> Vec<(usize, usize)> {
+        let mut hits = Vec::new();
+        for (track_pos, track) in self.tracks.iter().enumerate() {
+            let first = track.first_after(start);
+            hits.extend(
+                track.entries[first..]
+                    .iter()
+                    .take_while(|e| e.start < end)
+                    .filter(|e| e.end > start)
+                    .map(|e| (track_pos, e.clip_pos)),
+            );
+        }
+        hits
+    }
+
+    /// Items to draw for `start..end` at `ms_per_px`.
+    ///
+    /// A run of clips narrower than [`MIN_ITEM_PX`] collapses into one
+    /// summary, skipped over a pixel at a time by binary search, so dense
+    /// tracks cost at most one search per pixel column.
+    pub(crate) fn visible(
+        &self, tracks: &[TimelineTrack], start: u64, end: u64, ms_per_px: f64,
+    ) -> Vec<TimelineItem> {
+        let min_ms = (ms_per_px * MIN_ITEM_PX).ceil() as u64;
+        let px_ms = ms_per_px.ceil().max(1.0) as u64;
+        let mut items = Vec::new();
+        for (track, index) in tracks.iter().zip(&self.tracks) {
+            let entries = &index.entries;
+            let mut pos = index.first_after(start);
+            while pos < entries.len() && entries[pos].start < end {
+                let entry = entries[pos];
+                if entry.end <= start {
+                    pos += 1;
+                    continue;
+                }
+                if entry.end - entry.start >= min_ms {
+                    let clip = &track.clips[entry.clip_pos];
+                    items.push(TimelineItem::Clip {
+                        track_id: track.id,
+                        clip_id:  clip.id,
+                        start:    clip.start,
+                        end:      clip.end(),
+                    });
+                    pos += 1;
+                    continue;
+                }
+
+                // Swallow every clip starting within a pixel of the run
+                let first = pos;
+                let mut run_end = entry.end;
+                loop {
+                    let next = index.first_starting(run_end + px_ms).max(pos + 1);
+                    run_end = run_end.max(index.max_end[next - 1]);
+                    pos = next;
+                    let Some(following) = entries.get(pos) else {
+                        break;
+                    };
+                    let narrow = following.end - following.start < min_ms;
+                    if !narrow || following.start >= end || following.start > run_end + px_ms {
+                        break;
+                    }
+                }
+                items.push(if pos - first == 1 {
+                    let clip = &track.clips[entry.clip_pos];
+                    TimelineItem::Clip {
+                        track_id: track.id,
+                        clip_id:  clip.id,
+                        start:    clip.start,
+                        end:      clip.end(),
+                    }
+                } else {
+                    TimelineItem::Summary {
+                        track_id:   track.id,
+                        start:      TimePosition::from_ms(entry.start),
+                        end:        TimePosition::from_ms(run_end),
+                        clip_count: pos - first,
+                    }
+                });
+            }
+        }
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        implementation::{TimelineManager, TimelineViewport},
+        types::{TrackType, timeline::TimelineClip},
+    };
+
+    /// Two tracks: 10k 40 ms clips with 10 ms gaps, and a few long clips.
+    fn dense_timeline() -> (TimelineManager, u64, u64) {
+        let mut timeline = TimelineManager::new();
+        let dense = timeline.add_track("Dense", TrackType::Video);
+        let sparse = timeline.add_track("Sparse", TrackType::Audio);
+        let track = timeline.get_track_mut(dense).expect("test assertion");
+        for i in 0..10_000u64 {
+            let start = TimePosition::from_ms(i * 50);
+            track.clips.push(TimelineClip::new(i + 1, 1, start, TimePosition::from_ms(40)));
+        }
+        for i in 0..5u64 {
+            let start = TimePosition::from_ms(i * 100_000);
+            let clip = TimelineClip::new(20_000 + i, 2, start, TimePosition::from_ms(90_000));
+            timeline.add_clip(sparse, clip).expect("test assertion");
+        }
+        (timeline, dense, sparse)
+    }
+
+    #[test]
+    fn test_clips_in_range_matches_scan() {
+        let (mut timeline, dense, sparse) = dense_timeline();
+        for (start, end) in [(0, 1), (95_041, 95_049), (123_456, 130_000), (499_990, 600_000)] {
+            let (start, end) = (TimePosition::from_ms(start), TimePosition::from_ms(end));
+            let found: Vec<u64> =
+                timeline.clips_in_range(start, end).iter().map(|(_, c)| c.id).collect();
+            let expected: Vec<u64> = timeline
+                .tracks()
+                .iter()
+                .flat_map(|t| &t.clips)
+                .filter(|c| c.start.ms < end.ms && c.end().ms > start.ms)
+                .map(|c| c.id)
+                .collect();
+            assert_eq!(found, expected);
+        }
+        let hits =
+            timeline.clips_in_range(TimePosition::from_ms(95_041), TimePosition::from_ms(95_049));
+        assert!(hits.is_empty());
+        let hits = timeline.clips_in_range(TimePosition::from_ms(0), TimePosition::from_ms(120));
+        assert_eq!(hits.len(), 4);
+        assert_eq!((hits[3].0, hits[3].1.id), (sparse, 20_000));
+
+        // Edits invalidate the index
+        timeline.move_clip(20_000, TimePosition::from_ms(700_000)).expect("test assertion");
+        let hits = timeline.clips_in_range(TimePosition::from_ms(0), TimePosition::from_ms(120));
+        assert!(hits.iter().all(|(track_id, _)| *track_id == dense));
+        let hits =
+            timeline.clips_in_range(TimePosition::from_ms(700_000), TimePosition::from_ms(700_001));
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[test]
+    fn test_visible_items_summarize_when_zoomed_out() {
+        let (timeline, dense, sparse) = dense_timeline();
+
+        // 1000 px across the whole timeline: 500 ms per pixel
+        let mut viewport = TimelineViewport::new(1_000.0, 2.0);
+        let items = timeline.visible_items(&viewport);
+        let dense_items: Vec<_> = items.iter().filter(|i| i.track_id() == dense).collect();
+        assert_eq!(dense_items.len(), 1);
+        assert_eq!(dense_items[0].clip_count(), 10_000);
+        let sparse_items: Vec<_> = items.iter().filter(|i| i.track_id() == sparse).collect();
+        assert_eq!(sparse_items.len(), 5);
+        assert!(sparse_items.iter().all(|i| matches!(i, TimelineItem::Clip { .. })));
+
+        // Zoomed in, every clip draws on its own
+        viewport.pixels_per_second = 200.0;
+        viewport.scroll_ms = 1_000.0;
+        let items = timeline.visible_items(&viewport);
+        let dense_items: Vec<_> = items.iter().filter(|i| i.track_id() == dense).collect();
+        assert_eq!(dense_items.len(), 100);
+        assert_eq!(
+            dense_items[0],
+            &TimelineItem::Clip {
+                track_id: dense,
+                clip_id:  21,
+                start:    TimePosition::from_ms(1_000),
+                end:      TimePosition::from_ms(1_040),
+            }
+        );
+        assert_eq!(items.iter().filter(|i| i.track_id() == sparse).count(), 1);
+    }
+}
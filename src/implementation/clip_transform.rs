@@ -0,0 +1,162 @@
+//! Per-clip transform.
+//!
+//! A [`ClipTransform`] keyframes the crop window a clip is framed
+//! through, in normalized source coordinates. Exports to another aspect
+//! ratio scale the window to fill the output frame, so an animated window
+//! pans across the source.
+
+use super::keyframe_animation::{AnimatedValue, AnimationLayer};
+use crate::{metadata::BoundingBox, types::TimePosition};
+
+/// Transform of a clip at one instant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClipTransformState {
+    /// Center of the crop window, normalized to the source frame.
+    pub crop_center: [f32; 2],
+    /// Size of the crop window, normalized to the source frame.
+    pub crop_size:   [f32; 2],
+}
+
+impl ClipTransformState {
+    /// Full frame, uncropped.
+    pub const IDENTITY: Self = Self { crop_center: [0.5, 0.5], crop_size: [1.0, 1.0] };
+
+    /// The crop window as a normalized box.
+    #[must_use]
+    pub fn crop_rect(&self) -> BoundingBox {
+        let [cx, cy] = self.crop_center;
+        let [w, h] = self.crop_size;
+        BoundingBox::new(cx - w / 2.0, cy - h / 2.0, w, h)
+    }
+
+    /// Resample the crop window of an RGBA8 frame to `out_width` x
+    /// `out_height`.
+    pub fn apply(
+        &self, pixels: &[u8], width: u32, height: u32, out_width: u32, out_height: u32,
+    ) -> Vec<u8> {
+        let (w, h) = (width as usize, height as usize);
+        let (ow, oh) = (out_width as usize, out_height as usize);
+        let mut out = vec![0; ow * oh * 4];
+        if pixels.len() < w * h * 4 || w == 0 || h == 0 {
+            return out;
+        }
+        let rect = self.crop_rect();
+        let fetch = |x: isize, y: isize, channel: usize| {
+            let x = x.clamp(0, w as isize - 1) as usize;
+            let y = y.clamp(0, h as isize - 1) as usize;
+            f32::from(pixels[(y * w + x) * 4 + channel])
+        };
+        for y in 0..oh {
+            for x in 0..ow {
+                // Output pixel center to normalized source coordinates
+                let u = rect.x + (x as f32 + 0.5) / ow as f32 * rect.width;
+                let v = rect.y + (y as f32 + 0.5) / oh as f32 * rect.height;
+                let sx = u * w as f32 - 0.5;
+                let sy = v * h as f32 - 0.5;
+                let (x0, y0) = (sx.floor(), sy.floor());
+                let (fx, fy) = (sx - x0, sy - y0);
+                let (x0, y0) = (x0 as isize, y0 as isize);
+                for channel in 0..4 {
+                    let top = fetch(x0, y0, channel) * (1.0 - fx) + fetch(x0 + 1, y0, channel) * fx;
+                    let bottom = fetch(x0, y0 + 1, channel) * (1.0 - fx)
+                        + fetch(x0 + 1, y0 + 1, channel) * fx;
+                    out[(y * ow + x) * 4 + channel] =
+                        (top * (1.0 - fy) + bottom * fy).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Default for ClipTransformState {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Animatable transform of one timeline clip.
+///
+/// The crop window is keyframed on the layer's `crop.center` and
+/// `crop.size` animation tracks, at times relative to the clip start.
+#[derive(Debug)]
+pub struct ClipTransform {
+    animation: AnimationLayer,
+}
+
+impl ClipTransform {
+    /// Crop window center track.
+    pub const CROP_CENTER: &'static str = "crop.center";
+    /// Crop window size track.
+    pub const CROP_SIZE: &'static str = "crop.size";
+
+    /// Create an identity transform for a clip.
+    pub fn new(clip_id: u64) -> Self {
+        let mut animation = AnimationLayer::new("Transform", clip_id);
+        let rest = ClipTransformState::IDENTITY;
+        animation.create_track(Self::CROP_CENTER, vec2(rest.crop_center));
+        animation.create_track(Self::CROP_SIZE, vec2(rest.crop_size));
+        Self { animation }
+    }
+
+    /// Get the clip the transform belongs to.
+    pub fn clip_id(&self) -> u64 {
+        self.animation.target_id()
+    }
+
+    /// Get the keyframe tracks.
+    pub fn animation(&self) -> &AnimationLayer {
+        &self.animation
+    }
+
+    /// Get the mutable keyframe tracks.
+    pub fn animation_mut(&mut self) -> &mut AnimationLayer {
+        &mut self.animation
+    }
+
+    /// Keyframe the crop window center.
+    pub fn set_crop_center(&mut self, time: TimePosition, center: [f32; 2]) {
+        self.keyframe(Self::CROP_CENTER, time, vec2(center));
+    }
+
+    /// Keyframe the crop window size.
+    pub fn set_crop_size(&mut self, time: TimePosition, size: [f32; 2]) {
+        self.keyframe(Self::CROP_SIZE, time, vec2(size));
+    }
+
+    /// Remove every crop keyframe.
+    pub fn clear_crop(&mut self) {
+        for property in [Self::CROP_CENTER, Self::CROP_SIZE] {
+            if let Some(track) = self.animation.get_track_by_property_mut(property) {
+                track.clear();
+            }
+        }
+    }
+
+    fn keyframe(&mut self, property: &str, time: TimePosition, value: AnimatedValue) {
+        if let Some(track) = self.animation.get_track_by_property_mut(property) {
+            track.add_keyframe(time, value);
+        }
+    }
+
+    /// Evaluate the transform at a time relative to the clip start.
+    pub fn state_at(&self, time: TimePosition) -> ClipTransformState {
+        let mut state = ClipTransformState::IDENTITY;
+        for (property, value) in self.animation.evaluate_all(time) {
+            match (property, value) {
+                (Self::CROP_CENTER, AnimatedValue::Vec2(x, y)) => {
+                    state.crop_center = [x as f32, y as f32];
+                },
+                (Self::CROP_SIZE, AnimatedValue::Vec2(w, h)) => {
+                    state.crop_size = [w as f32, h as f32];
+                },
+                _ => {},
+            }
+        }
+        state
+    }
+}
+
+fn vec2([x, y]: [f32; 2]) -> AnimatedValue {
+    AnimatedValue::Vec2(f64::from(x), f64::from(y))
+}
@@ -4,8 +4,20 @@
 //! Features: LUT support, color wheels, curves, scopes,
 //! HSL adjustment, color matching, and node-based grading.
 
+use std::{fmt::Write as _, path::Path};
+
 use essentia_color_types::{Color, Hsl};
 
+use crate::errors::{VideoEditorError, VideoEditorResult};
+
+/// Colors graded together by [`ColorGradingNode::apply_slice`]; eight f32
+/// lanes fill one AVX register.
+const LANES: usize = 8;
+
+/// Colors per thread below which [`ColorGradingNode::apply_slice`] stays
+/// on the calling thread.
+const PARALLEL_MIN_COLORS: usize = 32 * 1024;
+
 /// Color space for grading operations.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum ColorSpace {
@@ -80,6 +92,15 @@ impl ColorWheel {
         }
     }
 
+    /// Returns true if the wheel leaves colors unchanged.
+    #[must_use]
+    pub fn is_neutral(&self) -> bool {
+        self.hue == 0.0
+            && self.saturation == 0.0
+            && self.brightness == 0.0
+            && [self.offset.r, self.offset.g, self.offset.b] == [0.0; 3]
+    }
+
     /// Applies the color wheel to a color.
     #[must_use]
     pub fn apply(&self, color: &Color) -> Color {
@@ -157,9 +178,19 @@ impl ThreeWayCorrector {
         }
     }
 
-    /// Applies the three-way correction.
+    /// Returns true if all three wheels are neutral.
+    #[must_use]
+    pub fn is_neutral(&self) -> bool {
+        self.shadows.is_neutral() && self.midtones.is_neutral() && self.highlights.is_neutral()
+    }
+
+    /// Applies the three-way correction. Neutral wheels pass the color
+    /// through untouched.
     #[must_use]
     pub fn apply(&self, color: &Color) -> Color {
+        if self.is_neutral() {
+            return *color;
+        }
         let lum = color.luminance();
         let sw = self.shadow_weight(lum);
         let hw = self.highlight_weight(lum);
@@ -214,6 +245,15 @@ impl ColorCurve {
         curve
     }
 
+    /// Creates a curve through the given control points.
+    #[must_use]
+    pub fn from_points(mut points: Vec<CurvePoint>) -> Self {
+        points.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(core::cmp::Ordering::Equal));
+        let mut curve = Self { points, lut: Vec::new() };
+        curve.rebuild_lut();
+        curve
+    }
+
     /// Creates an S-curve for contrast.
     #[must_use]
     pub fn s_curve(strength: f32) -> Self {
@@ -376,7 +416,7 @@ impl ColorCurves {
 }
 
 /// LUT (Look-Up Table) for color grading.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Lut3D {
     /// LUT size (e.g., 33 for 33x33x33).
     size:   u32,
@@ -422,6 +462,18 @@ impl Lut3D {
         Self { size, data, name: "Identity".into(), interp: LutInterpolation::default() }
     }
 
+    /// Creates a LUT from `size`³ colors, red varying fastest. Returns
+    /// `None` if the data doesn't match the size.
+    #[must_use]
+    pub fn from_data(name: impl Into<String>, size: u32, data: Vec<Color>) -> Option<Self> {
+        (size >= 2 && data.len() == (size * size * size) as usize).then(|| Self {
+            size,
+            data,
+            name: name.into(),
+            interp: LutInterpolation::default(),
+        })
+    }
+
     /// Returns the LUT size.
     #[must_use]
     pub const fn size(&self) -> u32 {
@@ -439,11 +491,46 @@ impl Lut3D {
         self.name = name.into();
     }
 
+    /// Returns the LUT colors, red varying fastest.
+    #[must_use]
+    pub fn data(&self) -> &[Color] {
+        &self.data
+    }
+
+    /// Returns the interpolation mode.
+    #[must_use]
+    pub const fn interpolation(&self) -> LutInterpolation {
+        self.interp
+    }
+
     /// Sets the interpolation mode.
     pub fn set_interpolation(&mut self, interp: LutInterpolation) {
         self.interp = interp;
     }
 
+    /// Formats the LUT as an Adobe/Resolve `.cube` file.
+    #[must_use]
+    pub fn to_cube(&self) -> String {
+        let mut cube = String::new();
+        let _ = writeln!(cube, "TITLE \"{}\"", self.name.replace('"', "'"));
+        let _ = writeln!(cube, "LUT_3D_SIZE {}", self.size);
+        cube.push_str("DOMAIN_MIN 0.0 0.0 0.0\nDOMAIN_MAX 1.0 1.0 1.0\n");
+        for color in &self.data {
+            let _ = writeln!(cube, "{:.6} {:.6} {:.6}", color.r, color.g, color.b);
+        }
+        cube
+    }
+
+    /// Writes the LUT to a `.cube` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Io` if the file can't be written.
+    pub fn save_cube(&self, path: impl AsRef<Path>) -> VideoEditorResult<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_cube()).map_err(|e| VideoEditorError::io(path, e))
+    }
+
     /// Gets color at integer indices.
     fn get_at(&self, r: u32, g: u32, b: u32) -> &Color {
         let r = r.min(self.size - 1);
@@ -545,9 +632,11 @@ pub struct ColorGradingNode {
     pub contrast:      f32,
     /// Saturation (-1.0 to 1.0).
     pub saturation:    f32,
-    /// Temperature adjustment (Kelvin offset).
+    /// Temperature adjustment (Kelvin offset). +1000 raises the red gain
+    /// and lowers the blue gain by 10%.
     pub temperature:   f32,
-    /// Tint adjustment (green-magenta).
+    /// Tint adjustment (-1.0 to 1.0, green to magenta). Positive values
+    /// lower the green gain.
     pub tint:          f32,
 }
 
@@ -579,6 +668,14 @@ impl ColorGradingNode {
 
         let mut result = *color;
 
+        // Apply white balance
+        if self.temperature.abs() > f32::EPSILON || self.tint.abs() > f32::EPSILON {
+            let warm = self.temperature / 10_000.0;
+            result.r *= 1.0 + warm;
+            result.g *= 1.0 - self.tint;
+            result.b *= 1.0 - warm;
+        }
+
         // Apply exposure (in stops)
         if self.exposure.abs() > f32::EPSILON {
             let mult = 2.0_f32.powf(self.exposure);
@@ -621,6 +718,136 @@ impl ColorGradingNode {
 
         result
     }
+
+    /// Grades a buffer of colors in place, with the same result as
+    /// [`Self::apply`] on each color.
+    ///
+    /// Colors are graded in batches of eight held one plane per channel,
+    /// so the white balance, exposure, contrast, saturation and LUT blend
+    /// stages compile to SIMD arithmetic. Large buffers are split across
+    /// threads.
+    pub fn apply_slice(&self, colors: &mut [Color]) {
+        if !self.enabled {
+            return;
+        }
+        let threads = std::thread::available_parallelism()
+            .map_or(1, usize::from)
+            .min(colors.len() / PARALLEL_MIN_COLORS)
+            .max(1);
+        if threads == 1 {
+            self.apply_chunk(colors);
+            return;
+        }
+        let chunk = colors.len().div_ceil(threads);
+        std::thread::scope(|scope| {
+            for part in colors.chunks_mut(chunk) {
+                scope.spawn(|| self.apply_chunk(part));
+            }
+        });
+    }
+
+    fn apply_chunk(&self, colors: &mut [Color]) {
+        let mut batches = colors.chunks_exact_mut(LANES);
+        for batch in &mut batches {
+            self.apply_lanes(batch);
+        }
+        for color in batches.into_remainder() {
+            *color = self.apply(color);
+        }
+    }
+
+    /// Grades [`LANES`] colors, in the stage order of [`Self::apply`].
+    fn apply_lanes(&self, batch: &mut [Color]) {
+        let mut planes = [[0.0f32; LANES]; 3];
+        for (i, color) in batch.iter().enumerate() {
+            [planes[0][i], planes[1][i], planes[2][i]] = [color.r, color.g, color.b];
+        }
+        let scale = |plane: &mut [f32; LANES], gain: f32| plane.iter_mut().for_each(|v| *v *= gain);
+
+        if self.temperature.abs() > f32::EPSILON || self.tint.abs() > f32::EPSILON {
+            let warm = self.temperature / 10_000.0;
+            scale(&mut planes[0], 1.0 + warm);
+            scale(&mut planes[1], 1.0 - self.tint);
+            scale(&mut planes[2], 1.0 - warm);
+        }
+        if self.exposure.abs() > f32::EPSILON {
+            let mult = 2.0_f32.powf(self.exposure);
+            planes.iter_mut().for_each(|plane| scale(plane, mult));
+        }
+        if self.contrast.abs() > f32::EPSILON {
+            let factor = (1.0 + self.contrast).max(0.0);
+            for v in planes.iter_mut().flatten() {
+                *v = (*v - 0.5) * factor + 0.5;
+            }
+        }
+
+        // Color wheels and curves look values up per color
+        let mut lum = [0.0f32; LANES];
+        for (i, color) in batch.iter().enumerate() {
+            let color = Color::new(planes[0][i], planes[1][i], planes[2][i], color.a);
+            let graded = self.curves.apply(&self.three_way.apply(&color));
+            [planes[0][i], planes[1][i], planes[2][i]] = [graded.r, graded.g, graded.b];
+            lum[i] = graded.luminance();
+        }
+
+        if self.saturation.abs() > f32::EPSILON {
+            let sat = 1.0 + self.saturation;
+            for plane in &mut planes {
+                for (v, lum) in plane.iter_mut().zip(lum) {
+                    *v = lum + sat * (*v - lum);
+                }
+            }
+        }
+
+        if let Some(lut) = &self.lut {
+            let mut looked_up = [[0.0f32; LANES]; 3];
+            for i in 0..LANES {
+                let c = lut.apply(&Color::rgb(planes[0][i], planes[1][i], planes[2][i]));
+                [looked_up[0][i], looked_up[1][i], looked_up[2][i]] = [c.r, c.g, c.b];
+            }
+            for (plane, looked_up) in planes.iter_mut().zip(&looked_up) {
+                for (v, l) in plane.iter_mut().zip(looked_up) {
+                    *v += self.lut_intensity * (l - *v);
+                }
+            }
+        }
+
+        for (i, color) in batch.iter_mut().enumerate() {
+            [color.r, color.g, color.b] = [planes[0][i], planes[1][i], planes[2][i]];
+        }
+    }
+
+    /// Grades straight-alpha RGBA8 pixels in place.
+    pub fn apply_rgba8(&self, pixels: &mut [u8]) {
+        let mut colors: Vec<Color> = pixels
+            .chunks_exact(4)
+            .map(|p| {
+                let [r, g, b, a] = [0, 1, 2, 3].map(|i| f32::from(p[i]) / 255.0);
+                Color::new(r, g, b, a)
+            })
+            .collect();
+        self.apply_slice(&mut colors);
+        for (pixel, graded) in pixels.chunks_exact_mut(4).zip(&colors) {
+            for (out, v) in pixel.iter_mut().zip([graded.r, graded.g, graded.b]) {
+                *out = (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        }
+    }
+
+    /// Samples the whole grade into a `size`³ LUT named after the node.
+    ///
+    /// Inputs outside 0.0 to 1.0 clamp to the LUT's edge, so only graded
+    /// display-referred footage looks the same through the LUT.
+    #[must_use]
+    pub fn bake_to_lut(&self, size: u32) -> Lut3D {
+        let mut lut = Lut3D::identity(size.max(2));
+        for color in &mut lut.data {
+            *color = self.apply(color);
+            color.a = 1.0;
+        }
+        lut.name.clone_from(&self.name);
+        lut
+    }
 }
 
 impl Default for ColorGradingNode {
@@ -673,6 +900,71 @@ mod tests {
         assert!((result.b - color.b).abs() < 0.05);
     }
 
+    #[test]
+    fn test_bake_to_lut() {
+        let mut node = ColorGradingNode::new("Day for Night");
+        node.exposure = -0.5;
+        node.contrast = 0.2;
+        node.saturation = -0.3;
+        node.three_way.highlights.offset = Color::rgb(-0.05, 0.0, 0.08);
+        node.curves.master = ColorCurve::s_curve(0.6);
+
+        let lut = node.bake_to_lut(33);
+        assert_eq!((lut.size(), lut.name()), (33, "Day for Night"));
+        for i in 0..64 {
+            let color = Color::rgb(
+                (i % 4) as f32 / 3.0,
+                (i / 4 % 4) as f32 / 3.0 * 0.9 + 0.05,
+                (i / 16) as f32 / 3.0 * 0.8 + 0.1,
+            );
+            let (baked, direct) = (lut.apply(&color), node.apply(&color));
+            assert!((baked.r - direct.r).abs() < 0.03, "{color:?}: {baked:?} vs {direct:?}");
+            assert!((baked.g - direct.g).abs() < 0.03, "{color:?}: {baked:?} vs {direct:?}");
+            assert!((baked.b - direct.b).abs() < 0.03, "{color:?}: {baked:?} vs {direct:?}");
+        }
+
+        let cube = ColorGradingNode::default().bake_to_lut(2).to_cube();
+        let lines: Vec<&str> = cube.lines().collect();
+        assert_eq!(lines[..2], ["TITLE \"Color Grading\"", "LUT_3D_SIZE 2"]);
+        assert_eq!(lines.len(), 4 + 8);
+        assert_eq!(lines[5], "1.000000 0.000000 0.000000");
+        assert_eq!(lines[11], "1.000000 1.000000 1.000000");
+    }
+
+    #[test]
+    fn test_apply_slice_matches_apply() {
+        let mut node = ColorGradingNode::new("Teal and Orange");
+        node.temperature = 600.0;
+        node.tint = -0.04;
+        node.exposure = 0.3;
+        node.contrast = 0.25;
+        node.saturation = 0.2;
+        node.three_way.shadows.offset = Color::rgb(0.0, 0.02, 0.05);
+        node.curves.master = ColorCurve::s_curve(0.4);
+        node.lut = Some(ColorGradingNode::new("Look").bake_to_lut(17));
+        node.lut_intensity = 0.6;
+
+        // Enough colors to split across threads, with a ragged tail
+        let colors: Vec<Color> = (0..PARALLEL_MIN_COLORS * 2 + 5)
+            .map(|i| {
+                let t = i as f32 / (PARALLEL_MIN_COLORS * 2) as f32;
+                Color::new(t, (t * 7.0).fract(), 1.0 - t, 0.5)
+            })
+            .collect();
+        let mut graded = colors.clone();
+        node.apply_slice(&mut graded);
+        for (color, graded) in colors.iter().zip(&graded) {
+            let direct = node.apply(color);
+            assert!(
+                [direct.r - graded.r, direct.g - graded.g, direct.b - graded.b]
+                    .iter()
+                    .all(|d| d.abs() < 1e-5),
+                "{color:?}: {graded:?} vs {direct:?}"
+            );
+            assert_eq!(graded.a, color.a);
+        }
+    }
+
     #[test]
     fn test_grading_node_neutral() {
         let node = ColorGradingNode::default();
@@ -0,0 +1,246 @@
+//! Shot matching.
+//!
+//! [`ColorMatcher`] measures a reference frame and builds a grade that
+//! moves a target clip's per-channel distribution onto it, either by
+//! matching mean and standard deviation or by full histogram transfer.
+//! The result is expressed as RGB curves so it can be refined by hand,
+//! saved as a preset or baked to a LUT.
+
+use super::color_grading::{ColorCurve, ColorGradingNode, CurvePoint, Lut3D};
+
+/// Number of curve points used to express a transfer.
+const CURVE_POINTS: usize = 33;
+
+/// How the target distribution is mapped onto the reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MatchMethod {
+    /// Per-channel mean and standard deviation (Reinhard-style transfer).
+    #[default]
+    MeanVariance,
+    /// Per-channel cumulative histogram matching.
+    Histogram,
+}
+
+/// Per-channel statistics of an RGBA8 frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameStats {
+    /// Mean of R, G and B (0.0 to 1.0).
+    pub mean:    [f32; 3],
+    /// Standard deviation of R, G and B.
+    pub std_dev: [f32; 3],
+    /// Cumulative distribution of each channel over the 256 code values.
+    cdf:         [Vec<f32>; 3],
+}
+
+impl FrameStats {
+    /// Measures straight-alpha RGBA8 pixels. Fully transparent pixels are
+    /// ignored.
+    #[must_use]
+    pub fn from_rgba8(pixels: &[u8]) -> Self {
+        let mut histogram = [[0u64; 256]; 3];
+        let mut count = 0u64;
+        for pixel in pixels.chunks_exact(4).filter(|p| p[3] > 0) {
+            for (channel, &value) in histogram.iter_mut().zip(pixel) {
+                channel[usize::from(value)] += 1;
+            }
+            count += 1;
+        }
+        let total = count.max(1) as f64;
+
+        let mut mean = [0.0; 3];
+        let mut std_dev = [0.0; 3];
+        let cdf = std::array::from_fn(|c| {
+            let levels = histogram[c].iter().enumerate();
+            let m = levels.clone().map(|(v, &n)| v as f64 / 255.0 * n as f64).sum::<f64>() / total;
+            let variance =
+                levels.map(|(v, &n)| (v as f64 / 255.0 - m).powi(2) * n as f64).sum::<f64>()
+                    / total;
+            mean[c] = m as f32;
+            std_dev[c] = variance.sqrt() as f32;
+            histogram[c]
+                .iter()
+                .scan(0u64, |acc, &n| {
+                    *acc += n;
+                    Some((*acc as f64 / total) as f32)
+                })
+                .collect()
+        });
+        Self { mean, std_dev, cdf }
+    }
+
+    /// Distance between two distributions: the summed absolute
+    /// differences of the channel means and standard deviations.
+    #[must_use]
+    pub fn distance(&self, other: &Self) -> f32 {
+        (0..3)
+            .map(|c| {
+                (self.mean[c] - other.mean[c]).abs() + (self.std_dev[c] - other.std_dev[c]).abs()
+            })
+            .sum()
+    }
+}
+
+/// Before/after evaluation of a match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchReport {
+    /// Distance of the ungraded target to the reference.
+    pub before: f32,
+    /// Distance of the graded target to the reference.
+    pub after:  f32,
+}
+
+impl MatchReport {
+    /// Fraction of the original distance removed (1.0 = exact match).
+    #[must_use]
+    pub fn improvement(&self) -> f32 {
+        if self.before <= f32::EPSILON { 0.0 } else { 1.0 - self.after / self.before }
+    }
+}
+
+/// Matches clips to a reference frame.
+#[derive(Debug, Clone)]
+pub struct ColorMatcher {
+    reference: FrameStats,
+    method:    MatchMethod,
+    strength:  f32,
+}
+
+impl ColorMatcher {
+    /// Creates a matcher for a straight-alpha RGBA8 reference frame.
+    #[must_use]
+    pub fn new(reference: &[u8]) -> Self {
+        Self {
+            reference: FrameStats::from_rgba8(reference),
+            method:    MatchMethod::default(),
+            strength:  1.0,
+        }
+    }
+
+    /// Sets the transfer method.
+    #[must_use]
+    pub fn with_method(mut self, method: MatchMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Sets how far the match moves the target (0.0 = unchanged, 1.0 =
+    /// full match).
+    #[must_use]
+    pub fn with_strength(mut self, strength: f32) -> Self {
+        self.strength = strength.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Returns the reference statistics.
+    #[must_use]
+    pub fn reference(&self) -> &FrameStats {
+        &self.reference
+    }
+
+    /// Builds a grade that makes a target frame match the reference.
+    #[must_use]
+    pub fn match_grade(&self, target: &[u8]) -> ColorGradingNode {
+        let stats = FrameStats::from_rgba8(target);
+        let [red, green, blue] = [0, 1, 2].map(|channel| {
+            let points = (0..CURVE_POINTS)
+                .map(|i| {
+                    let x = i as f32 / (CURVE_POINTS - 1) as f32;
+                    let matched = self.transfer(&stats, channel, x);
+                    let y = x + self.strength * (matched - x);
+                    CurvePoint::new(x, y.clamp(0.0, 1.0))
+                })
+                .collect();
+            ColorCurve::from_points(points)
+        });
+
+        let mut grade = ColorGradingNode::new("Shot Match");
+        grade.curves.red = red;
+        grade.curves.green = green;
+        grade.curves.blue = blue;
+        grade
+    }
+
+    /// Builds the match as a `size`³ LUT.
+    #[must_use]
+    pub fn match_lut(&self, target: &[u8], size: u32) -> Lut3D {
+        self.match_grade(target).bake_to_lut(size)
+    }
+
+    /// Compares a target frame to the reference before and after a grade.
+    #[must_use]
+    pub fn evaluate(&self, target: &[u8], grade: &ColorGradingNode) -> MatchReport {
+        let before = FrameStats::from_rgba8(target).distance(&self.reference);
+        let mut graded = target.to_vec();
+        grade.apply_rgba8(&mut graded);
+        let after = FrameStats::from_rgba8(&graded).distance(&self.reference);
+        MatchReport { before, after }
+    }
+
+    /// Maps one channel value of the target onto the reference.
+    fn transfer(&self, target: &FrameStats, channel: usize, x: f32) -> f32 {
+        let reference = &self.reference;
+        match self.method {
+            MatchMethod::MeanVariance => {
+                let scale = if target.std_dev[channel] > 1e-4 {
+                    reference.std_dev[channel] / target.std_dev[channel]
+                } else {
+                    1.0
+                };
+                (x - target.mean[channel]) * scale + reference.mean[channel]
+            },
+            MatchMethod::Histogram => {
+                let level = (x.clamp(0.0, 1.0) * 255.0).round() as usize;
+                let quantile = target.cdf[channel][level];
+                let cdf = &reference.cdf[channel];
+                let matched = cdf.iter().position(|&c| c >= quantile).unwrap_or(255);
+                matched as f32 / 255.0
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gradient frame with per-channel gain and lift.
+    fn frame(gain: [f32; 3], lift: [f32; 3]) -> Vec<u8> {
+        (0..64 * 64)
+            .flat_map(|i| {
+                let t = [
+                    (i % 64) as f32 / 63.0,
+                    (i / 64) as f32 / 63.0,
+                    ((i % 64 + i / 64) % 64) as f32 / 63.0,
+                ];
+                let [r, g, b] =
+                    [0, 1, 2].map(|c| ((t[c] * gain[c] + lift[c]).clamp(0.0, 1.0) * 255.0) as u8);
+                [r, g, b, 255]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_color_match() {
+        let reference = frame([0.7, 0.6, 0.5], [0.25, 0.2, 0.1]);
+        let target = frame([0.5, 0.6, 0.8], [0.05, 0.1, 0.15]);
+
+        for method in [MatchMethod::MeanVariance, MatchMethod::Histogram] {
+            let matcher = ColorMatcher::new(&reference).with_method(method);
+            let grade = matcher.match_grade(&target);
+            let report = matcher.evaluate(&target, &grade);
+            assert!(report.improvement() > 0.85, "{method:?}: {report:?}");
+
+            let lut = matcher.match_lut(&target, 17);
+            let mut via_lut = ColorGradingNode::new("LUT");
+            via_lut.lut = Some(lut);
+            assert!(matcher.evaluate(&target, &via_lut).after < report.after + 0.02);
+        }
+
+        let matcher = ColorMatcher::new(&reference).with_strength(0.0);
+        let report = matcher.evaluate(&target, &matcher.match_grade(&target));
+        assert!((report.after - report.before).abs() < 0.01);
+        let half = ColorMatcher::new(&reference).with_strength(0.5);
+        let report = half.evaluate(&target, &half.match_grade(&target));
+        assert!(report.improvement() > 0.3 && report.improvement() < 0.7, "{report:?}");
+    }
+}
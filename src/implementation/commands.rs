@@ -0,0 +1,319 @@
+//! Editor command registry.
+//!
+//! Every editor operation is registered once as an [`EditorCommand`] with its
+//! ID, label, icon and default shortcut. The toolbar and host shortcut maps
+//! are derived from the registry, so they can't drift apart.
+
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use essentia_traits::plugin_contracts::EditorAction;
+
+use super::VideoEditorPlugin;
+use crate::{errors::VideoEditorResult, types::TrackType};
+
+/// Closure that runs a command against the editor.
+pub type CommandHandler =
+    Arc<dyn Fn(&mut VideoEditorPlugin) -> VideoEditorResult<()> + Send + Sync>;
+
+/// A keyboard-mappable editor operation.
+#[derive(Clone)]
+pub struct EditorCommand {
+    /// Stable command ID (e.g. "video_play").
+    pub id:               String,
+    /// Display label.
+    pub label:            String,
+    /// Toolbar icon glyph.
+    pub icon:             String,
+    /// Shortcut used unless the user remaps it (e.g. "Ctrl+X").
+    pub default_shortcut: Option<String>,
+    /// Whether the command appears in the toolbar.
+    pub toolbar:          bool,
+    /// Runs the command. `None` means the host handles it (e.g. opening a
+    /// panel).
+    pub handler:          Option<CommandHandler>,
+}
+
+impl EditorCommand {
+    /// Creates a host-handled command with no shortcut.
+    pub fn new(id: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            id:               id.into(),
+            label:            label.into(),
+            icon:             String::new(),
+            default_shortcut: None,
+            toolbar:          false,
+            handler:          None,
+        }
+    }
+
+    /// Sets the toolbar icon.
+    #[must_use]
+    pub fn with_icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = icon.into();
+        self
+    }
+
+    /// Sets the default shortcut.
+    #[must_use]
+    pub fn with_shortcut(mut self, shortcut: impl Into<String>) -> Self {
+        self.default_shortcut = Some(shortcut.into());
+        self
+    }
+
+    /// Shows the command in the toolbar.
+    #[must_use]
+    pub fn in_toolbar(mut self) -> Self {
+        self.toolbar = true;
+        self
+    }
+
+    /// Sets the closure that runs the command.
+    #[must_use]
+    pub fn with_handler(
+        mut self,
+        handler: impl Fn(&mut VideoEditorPlugin) -> VideoEditorResult<()> + Send + Sync + 'static,
+    ) -> Self {
+        self.handler = Some(Arc::new(handler));
+        self
+    }
+}
+
+impl fmt::Debug for EditorCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EditorCommand")
+            .field("id", &self.id)
+            .field("label", &self.label)
+            .field("default_shortcut", &self.default_shortcut)
+            .field("toolbar", &self.toolbar)
+            .field("handled", &self.handler.is_some())
+            .finish()
+    }
+}
+
+/// Registry of editor commands and user shortcut overrides.
+#[derive(Debug, Clone, Default)]
+pub struct CommandRegistry {
+    commands:  Vec<EditorCommand>,
+    /// User remaps by command ID (`None` = shortcut removed).
+    overrides: HashMap<String, Option<String>>,
+}
+
+impl CommandRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with the built-in editor commands.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        for command in default_commands() {
+            registry.register(command);
+        }
+        registry
+    }
+
+    /// Registers a command, replacing any command with the same ID.
+    pub fn register(&mut self, command: EditorCommand) {
+        if let Some(existing) = self.commands.iter_mut().find(|c| c.id == command.id) {
+            *existing = command;
+        } else {
+            self.commands.push(command);
+        }
+    }
+
+    /// Removes a command and its shortcut override.
+    pub fn unregister(&mut self, id: &str) -> Option<EditorCommand> {
+        self.overrides.remove(id);
+        let pos = self.commands.iter().position(|c| c.id == id)?;
+        Some(self.commands.remove(pos))
+    }
+
+    /// Get a command by ID.
+    pub fn get(&self, id: &str) -> Option<&EditorCommand> {
+        self.commands.iter().find(|c| c.id == id)
+    }
+
+    /// Get all commands in registration order.
+    pub fn commands(&self) -> &[EditorCommand] {
+        &self.commands
+    }
+
+    /// Get the commands shown in the toolbar.
+    pub fn toolbar_commands(&self) -> impl Iterator<Item = &EditorCommand> {
+        self.commands.iter().filter(|c| c.toolbar)
+    }
+
+    /// Get the effective shortcut of a command, honoring user remaps.
+    pub fn shortcut_for(&self, id: &str) -> Option<&str> {
+        match self.overrides.get(id) {
+            Some(shortcut) => shortcut.as_deref(),
+            None => self.get(id)?.default_shortcut.as_deref(),
+        }
+    }
+
+    /// Remap a command's shortcut. `None` removes the shortcut.
+    ///
+    /// Returns false if the command isn't registered.
+    pub fn set_shortcut(&mut self, id: &str, shortcut: Option<String>) -> bool {
+        if self.get(id).is_none() {
+            return false;
+        }
+        self.overrides.insert(id.to_string(), shortcut);
+        true
+    }
+
+    /// Restore a command's default shortcut.
+    pub fn reset_shortcut(&mut self, id: &str) {
+        self.overrides.remove(id);
+    }
+
+    /// Find the command bound to a shortcut. Matching ignores case and
+    /// modifier order, so "shift+ctrl+t" finds "Ctrl+Shift+T".
+    pub fn command_for_shortcut(&self, shortcut: &str) -> Option<&EditorCommand> {
+        let wanted = normalize_shortcut(shortcut);
+        self.commands
+            .iter()
+            .find(|c| self.shortcut_for(&c.id).is_some_and(|s| normalize_shortcut(s) == wanted))
+    }
+
+    /// List shortcuts bound to more than one command, with their command IDs.
+    pub fn conflicts(&self) -> Vec<(String, Vec<String>)> {
+        let mut bound: Vec<(String, Vec<String>)> = Vec::new();
+        for command in &self.commands {
+            let Some(shortcut) = self.shortcut_for(&command.id) else {
+                continue;
+            };
+            let key = normalize_shortcut(shortcut);
+            match bound.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, ids)) => ids.push(command.id.clone()),
+                None => bound.push((key, vec![command.id.clone()])),
+            }
+        }
+        bound.retain(|(_, ids)| ids.len() > 1);
+        bound
+    }
+
+    /// Build toolbar actions with their effective shortcuts, enabled while a
+    /// project is open.
+    pub fn toolbar_actions(&self, project_open: bool) -> Vec<EditorAction> {
+        self.toolbar_commands()
+            .map(|c| EditorAction {
+                id:       c.id.clone(),
+                label:    c.label.clone(),
+                icon:     c.icon.clone(),
+                shortcut: self.shortcut_for(&c.id).map(String::from),
+                enabled:  project_open,
+            })
+            .collect()
+    }
+}
+
+/// Canonical form of a shortcut: lowercase, modifiers sorted, key last.
+fn normalize_shortcut(shortcut: &str) -> String {
+    let mut parts: Vec<String> =
+        shortcut.split('+').map(|p| p.trim().to_ascii_lowercase()).collect();
+    let key = parts.pop().unwrap_or_default();
+    parts.sort();
+    parts.push(key);
+    parts.join("+")
+}
+
+fn default_commands() -> Vec<EditorCommand> {
+    vec![
+        EditorCommand::new("video_play", "Play/Pause")
+            .with_icon("\u{E768}")
+            .with_shortcut("Space")
+            .in_toolbar()
+            .with_handler(|editor| {
+                editor.preview_mut().toggle_playback();
+                Ok(())
+            }),
+        EditorCommand::new("video_cut", "Cut")
+            .with_icon("\u{E8C6}")
+            .with_shortcut("Ctrl+X")
+            .in_toolbar()
+            .with_handler(|editor| editor.split_at_playhead().map(|_| ())),
+        EditorCommand::new("video_delete", "Delete")
+            .with_shortcut("Delete")
+            .with_handler(VideoEditorPlugin::delete_selection),
+        EditorCommand::new("video_select_forward", "Select Forward")
+            .with_shortcut("A")
+            .with_handler(|editor| {
+                editor.select_forward_from_playhead(false);
+                Ok(())
+            }),
+        EditorCommand::new("video_ripple_delete", "Ripple Delete")
+            .with_shortcut("Shift+Delete")
+            .with_handler(VideoEditorPlugin::ripple_delete_selection),
+        EditorCommand::new("video_add_marker", "Add Marker").with_shortcut("M").with_handler(
+            |editor| {
+                editor.add_marker_at_playhead();
+                Ok(())
+            },
+        ),
+        EditorCommand::new("video_nudge_left", "Nudge Left")
+            .with_shortcut("Alt+Left")
+            .with_handler(|editor| editor.nudge_selection(-1)),
+        EditorCommand::new("video_nudge_right", "Nudge Right")
+            .with_shortcut("Alt+Right")
+            .with_handler(|editor| editor.nudge_selection(1)),
+        EditorCommand::new("video_add_track", "Add Track")
+            .with_icon("\u{E710}")
+            .with_shortcut("Ctrl+Shift+T")
+            .in_toolbar()
+            .with_handler(|editor| {
+                let count = editor.timeline().tracks().len();
+                editor.timeline_mut().add_track(format!("Track {}", count + 1), TrackType::Video);
+                Ok(())
+            }),
+        // Host-handled: these open panels/dialogs
+        EditorCommand::new("video_effects", "Effects")
+            .with_icon("\u{E7AC}")
+            .with_shortcut("Ctrl+E")
+            .in_toolbar(),
+        EditorCommand::new("video_export", "Export")
+            .with_icon("\u{E898}")
+            .with_shortcut("Ctrl+Shift+E")
+            .in_toolbar(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toolbar_matches_registry() {
+        let registry = CommandRegistry::with_defaults();
+        let actions = registry.toolbar_actions(true);
+        let ids: Vec<_> = actions.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            ["video_play", "video_cut", "video_add_track", "video_effects", "video_export"]
+        );
+        assert_eq!(actions[1].shortcut.as_deref(), Some("Ctrl+X"));
+        assert!(registry.conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_shortcut_remap() {
+        let mut registry = CommandRegistry::with_defaults();
+        assert_eq!(
+            registry.command_for_shortcut("shift+ctrl+t").map(|c| c.id.as_str()),
+            Some("video_add_track")
+        );
+
+        assert!(registry.set_shortcut("video_cut", Some("Ctrl+K".into())));
+        assert!(registry.command_for_shortcut("Ctrl+X").is_none());
+        assert_eq!(registry.shortcut_for("video_cut"), Some("Ctrl+K"));
+        assert_eq!(registry.toolbar_actions(true)[1].shortcut.as_deref(), Some("Ctrl+K"));
+
+        assert!(registry.set_shortcut("video_add_marker", Some("Ctrl+K".into())));
+        assert_eq!(registry.conflicts().len(), 1);
+
+        registry.reset_shortcut("video_cut");
+        assert_eq!(registry.shortcut_for("video_cut"), Some("Ctrl+X"));
+        assert!(!registry.set_shortcut("missing", None));
+    }
+}
@@ -1,6 +1,10 @@
 //! Video editor configuration.
 
-use crate::types::{FrameRate, Resolution};
+use super::{
+    render::RenderDeterminism,
+    track_templates::{TrackLayout, TrackTemplate},
+};
+use crate::types::{FieldOrder, FrameRate, PixelAspectRatio, Resolution};
 
 /// Configuration for the video editor plugin.
 #[derive(Debug, Clone)]
@@ -11,12 +15,24 @@ pub struct VideoEditorConfig {
     pub resolution:         Resolution,
     /// Project frame rate.
     pub frame_rate:         FrameRate,
+    /// Project scan. Interlaced clips are deinterlaced on a progressive
+    /// project.
+    pub field_order:        FieldOrder,
+    /// Shape of the project's pixels. Previews show the picture at its
+    /// display aspect.
+    pub pixel_aspect:       PixelAspectRatio,
     /// Enable GPU acceleration.
     pub gpu_acceleration:   bool,
     /// Preview quality (0.0 - 1.0).
     pub preview_quality:    f32,
     /// Auto-save interval (seconds, 0 = disabled).
     pub auto_save_interval: u64,
+    /// Byte-stable rendering for golden-frame tests.
+    pub determinism:        RenderDeterminism,
+    /// Saved track presets.
+    pub track_templates:    Vec<TrackTemplate>,
+    /// Saved track layouts.
+    pub track_layouts:      Vec<TrackLayout>,
 }
 
 impl Default for VideoEditorConfig {
@@ -25,9 +41,14 @@ impl Default for VideoEditorConfig {
             max_tracks:         32,
             resolution:         Resolution::FHD,
             frame_rate:         FrameRate::FPS_30,
+            field_order:        FieldOrder::Progressive,
+            pixel_aspect:       PixelAspectRatio::SQUARE,
             gpu_acceleration:   true,
             preview_quality:    0.5,
             auto_save_interval: 60,
+            determinism:        RenderDeterminism::default(),
+            track_templates:    Vec::new(),
+            track_layouts:      Vec::new(),
         }
     }
 }
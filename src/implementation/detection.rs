@@ -0,0 +1,290 @@
+//! Object and face detection ingestion.
+//!
+//! Detection models plug in through [`DetectionProvider`]. The
+//! [`DetectionPipeline`] renders every `stride`-th timeline frame, hands it
+//! to the provider, drops duplicate boxes and links detections of the same
+//! object across frames by bounding-box overlap (IoU tracking). The
+//! tracked detections are stored in the [`MetadataIndex`] under stable
+//! object IDs.
+
+use super::export_pipeline::FrameRenderer;
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    metadata::{BoundingBox, MetadataIndex, ObjectDetection, TrackingState},
+    types::TimePosition,
+};
+
+/// Rendered frame handed to a provider.
+#[derive(Debug, Clone, Copy)]
+pub struct DetectionFrame<'a> {
+    /// Timeline frame number.
+    pub frame:  u64,
+    /// Timeline time of the frame.
+    pub time:   TimePosition,
+    /// Frame width in pixels.
+    pub width:  u32,
+    /// Frame height in pixels.
+    pub height: u32,
+    /// Straight-alpha RGBA8 pixels.
+    pub pixels: &'a [u8],
+}
+
+/// Object detection model.
+pub trait DetectionProvider {
+    /// Provider name for diagnostics.
+    fn name(&self) -> &str;
+
+    /// Detect objects in a frame. Object IDs in the result are ignored;
+    /// the pipeline assigns them by tracking.
+    fn detect(&self, frame: &DetectionFrame<'_>) -> VideoEditorResult<Vec<ObjectDetection>>;
+}
+
+/// Result of a detection run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DetectionSummary {
+    /// Frames sent to the provider.
+    pub frames:     usize,
+    /// Detections kept after confidence filtering and de-duplication.
+    pub detections: usize,
+    /// IDs of the objects seen, in order of first appearance.
+    pub object_ids: Vec<u64>,
+}
+
+/// Object being followed across frames.
+struct ActiveTrack {
+    object_id: u64,
+    class:     String,
+    bbox:      BoundingBox,
+    tracking:  TrackingState,
+}
+
+/// Runs a [`DetectionProvider`] over the timeline.
+#[derive(Debug, Clone)]
+pub struct DetectionPipeline {
+    /// Analyze every n-th frame.
+    pub stride:         u32,
+    /// Detections below this confidence are dropped.
+    pub min_confidence: f32,
+    /// Overlap above which two boxes of one class are the same object,
+    /// within a frame and between analyzed frames.
+    pub iou_threshold:  f32,
+    /// Analyzed frames an object may be missing before a reappearance
+    /// counts as a new object.
+    pub max_missed:     u32,
+}
+
+impl Default for DetectionPipeline {
+    fn default() -> Self {
+        Self { stride: 5, min_confidence: 0.5, iou_threshold: 0.3, max_missed: 2 }
+    }
+}
+
+impl DetectionPipeline {
+    /// Create a pipeline analyzing every fifth frame.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Detect and track objects in `start..end` and store them in
+    /// `metadata`.
+    ///
+    /// Frame numbers are timeline frames at the renderer's frame rate. New
+    /// objects get IDs above the highest ID already in the index.
+    pub fn run(
+        &self, provider: &dyn DetectionProvider, renderer: &dyn FrameRenderer, start: TimePosition,
+        end: TimePosition, metadata: &mut MetadataIndex,
+    ) -> VideoEditorResult<DetectionSummary> {
+        let rate = renderer.frame_rate();
+        let resolution = renderer.resolution();
+        let stride = u64::from(self.stride.max(1));
+        let max_gap = stride * (u64::from(self.max_missed) + 1);
+        let mut next_id = metadata.max_object_id().map_or(1, |id| id + 1);
+        let mut tracks: Vec<ActiveTrack> = Vec::new();
+        let mut summary = DetectionSummary::default();
+
+        let (first, last) = (start.to_frame(&rate), end.to_frame(&rate));
+        for frame in (first..last).step_by(stride as usize) {
+            let time = TimePosition::from_frame(frame, &rate);
+            let pixels = renderer.render_frame(time)?;
+            let input = DetectionFrame {
+                frame,
+                time,
+                width: resolution.width,
+                height: resolution.height,
+                pixels: &pixels,
+            };
+            let detected = provider.detect(&input).map_err(|e| {
+                VideoEditorError::Asset(format!(
+                    "Detection by {} failed at frame {frame}: {e}",
+                    provider.name()
+                ))
+            })?;
+            summary.frames += 1;
+
+            tracks.retain(|t| frame - t.tracking.last_frame <= max_gap);
+            let detections = self.track(self.deduplicate(detected), frame, &mut tracks, || {
+                let id = next_id;
+                next_id += 1;
+                id
+            });
+            for detection in &detections {
+                if !summary.object_ids.contains(&detection.object_id) {
+                    summary.object_ids.push(detection.object_id);
+                }
+            }
+            summary.detections += detections.len();
+            metadata.set_detections(frame, detections);
+        }
+        Ok(summary)
+    }
+
+    /// Drop low-confidence detections and overlapping duplicates of the
+    /// same class, keeping the most confident box.
+    fn deduplicate(&self, mut detections: Vec<ObjectDetection>) -> Vec<ObjectDetection> {
+        detections.retain(|d| d.confidence >= self.min_confidence);
+        detections.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        let mut kept: Vec<ObjectDetection> = Vec::with_capacity(detections.len());
+        for detection in detections {
+            let duplicate = kept.iter().any(|k| {
+                k.class == detection.class && k.bbox.iou(&detection.bbox) > self.iou_threshold
+            });
+            if !duplicate {
+                kept.push(detection);
+            }
+        }
+        kept
+    }
+
+    /// Assign object IDs by matching against the active tracks, best
+    /// overlap first.
+    fn track(
+        &self, mut detections: Vec<ObjectDetection>, frame: u64, tracks: &mut Vec<ActiveTrack>,
+        mut new_id: impl FnMut() -> u64,
+    ) -> Vec<ObjectDetection> {
+        let mut pairs: Vec<(f32, usize, usize)> = detections
+            .iter()
+            .enumerate()
+            .flat_map(|(d, detection)| {
+                tracks.iter().enumerate().filter_map(move |(t, track)| {
+                    let iou = track.bbox.iou(&detection.bbox);
+                    (track.class == detection.class && iou >= self.iou_threshold)
+                        .then_some((iou, d, t))
+                })
+            })
+            .collect();
+        pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut matched: Vec<Option<usize>> = vec![None; detections.len()];
+        let mut taken = vec![false; tracks.len()];
+        for (_, d, t) in pairs {
+            if matched[d].is_none() && !taken[t] {
+                matched[d] = Some(t);
+                taken[t] = true;
+            }
+        }
+
+        for (detection, track) in detections.iter_mut().zip(matched) {
+            match track {
+                Some(t) => {
+                    let track = &mut tracks[t];
+                    track.tracking.update(frame, track.bbox.center(), detection.bbox.center());
+                    track.bbox = detection.bbox;
+                    detection.object_id = track.object_id;
+                    detection.tracking = Some(track.tracking);
+                },
+                None => {
+                    let tracking = TrackingState::new(frame);
+                    detection.object_id = new_id();
+                    detection.tracking = Some(tracking);
+                    tracks.push(ActiveTrack {
+                        object_id: detection.object_id,
+                        class: detection.class.clone(),
+                        bbox: detection.bbox,
+                        tracking,
+                    });
+                },
+            }
+        }
+        detections
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FrameRate, Resolution};
+
+    struct BlankRenderer;
+
+    impl FrameRenderer for BlankRenderer {
+        fn resolution(&self) -> Resolution {
+            Resolution { width: 4, height: 4 }
+        }
+
+        fn frame_rate(&self) -> FrameRate {
+            FrameRate::FPS_30
+        }
+
+        fn render_frame(&self, _time: TimePosition) -> VideoEditorResult<Vec<u8>> {
+            Ok(vec![0; 4 * 4 * 4])
+        }
+    }
+
+    /// A dog walking right, reported twice per frame, and a cat that
+    /// leaves after frame 10 and comes back at frame 40.
+    struct ScriptedModel;
+
+    impl DetectionProvider for ScriptedModel {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+
+        fn detect(&self, frame: &DetectionFrame<'_>) -> VideoEditorResult<Vec<ObjectDetection>> {
+            let x = frame.frame as f32 * 0.005;
+            let mut found = vec![
+                ObjectDetection::new(0, "dog", 0.9, BoundingBox::new(x, 0.5, 0.2, 0.2)),
+                ObjectDetection::new(0, "dog", 0.7, BoundingBox::new(x + 0.01, 0.5, 0.2, 0.2)),
+                ObjectDetection::new(0, "dog", 0.2, BoundingBox::new(0.0, 0.0, 0.1, 0.1)),
+            ];
+            if frame.frame <= 10 || frame.frame >= 40 {
+                found.push(ObjectDetection::new(
+                    0,
+                    "cat",
+                    0.8,
+                    BoundingBox::new(0.1, 0.1, 0.2, 0.2),
+                ));
+            }
+            Ok(found)
+        }
+    }
+
+    #[test]
+    fn test_detection_tracking() {
+        let mut metadata = MetadataIndex::new();
+        metadata.track_object(4, 0);
+        let pipeline = DetectionPipeline::new();
+        let summary = pipeline
+            .run(
+                &ScriptedModel,
+                &BlankRenderer,
+                TimePosition::default(),
+                TimePosition::from_secs(2),
+                &mut metadata,
+            )
+            .expect("test assertion");
+
+        assert_eq!(summary.frames, 12);
+        assert_eq!(summary.object_ids, [5, 6, 7]);
+        assert_eq!(summary.detections, 12 + 3 + 4);
+        let dog = metadata.frames_with_object(5).expect("test assertion");
+        assert_eq!(dog.len(), 12);
+        assert_eq!(metadata.frames_with_object(6), Some(&[0, 5, 10][..]));
+        assert_eq!(metadata.frames_with_object(7), Some(&[40, 45, 50, 55][..]));
+
+        let at_20: Vec<_> = metadata.detections_at(20).collect();
+        assert_eq!(at_20.len(), 1);
+        let tracking = at_20[0].tracking.expect("test assertion");
+        assert_eq!((tracking.first_frame, tracking.tracked_frames), (0, 5));
+        assert!((tracking.velocity.0 - 0.005).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,501 @@
+//! Append-only edit journal and three-way merge for shared projects.
+//!
+//! Every edit is recorded as a [`ScriptOperation`] stamped with a Lamport
+//! clock and its author, so journals from different machines order
+//! consistently. Two branches that diverged from a common base merge by
+//! interleaving their new entries by clock; when both branches touched the
+//! same clip, track or marker in different ways, those entries are held
+//! back as a [`MergeConflict`] for the user to resolve. Clip edits don't
+//! record their track, so one branch removing a track conflicts with every
+//! clip edit on the other.
+//!
+//! Objects created on both branches get their IDs from the timeline when
+//! the merged journal is replayed, so later entries referring to them are
+//! only meaningful on the branch that created them.
+
+use super::scripting::{
+    EditorScriptApi, OperationOutput, Reader, ScriptOperation, put_str, put_u64,
+};
+use crate::errors::{VideoEditorError, VideoEditorResult};
+
+/// Magic number of an encoded journal ("EVOJ").
+pub const JOURNAL_MAGIC: u32 = 0x4556_4F4A;
+
+/// One recorded edit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    /// Lamport timestamp.
+    pub lamport: u64,
+    /// Who made the edit.
+    pub author:  String,
+    /// The edit.
+    pub op:      ScriptOperation,
+}
+
+impl JournalEntry {
+    /// Entries are identified by clock and author.
+    fn same_entry(&self, other: &Self) -> bool {
+        self.lamport == other.lamport && self.author == other.author
+    }
+}
+
+/// What an operation edits, for conflict detection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpTarget {
+    /// An existing clip.
+    Clip(u64),
+    /// An existing track.
+    Track(u64),
+    /// An existing marker.
+    Marker(u64),
+}
+
+impl OpTarget {
+    /// Existing objects `op` changes; operations that only create objects
+    /// touch none.
+    pub fn of(op: &ScriptOperation) -> Vec<Self> {
+        match op {
+            ScriptOperation::AddTrack { .. } | ScriptOperation::AddMarker { .. } => Vec::new(),
+            ScriptOperation::RemoveTrack { track_id } => vec![Self::Track(*track_id)],
+            ScriptOperation::MoveClip { clip_id, .. }
+            | ScriptOperation::TrimClipStart { clip_id, .. }
+            | ScriptOperation::TrimClipEnd { clip_id, .. }
+            | ScriptOperation::SplitClip { clip_id, .. }
+            | ScriptOperation::RippleDelete { clip_id } => vec![Self::Clip(*clip_id)],
+            ScriptOperation::RemoveMarker { marker_id } => vec![Self::Marker(*marker_id)],
+            ScriptOperation::AddCrossfade { track_id, clip_a_id, clip_b_id, .. } => {
+                vec![Self::Track(*track_id), Self::Clip(*clip_a_id), Self::Clip(*clip_b_id)]
+            },
+            ScriptOperation::CrossfadeAllCuts { track_id, .. } => {
+                track_id.map(Self::Track).into_iter().collect()
+            },
+        }
+    }
+}
+
+/// Which branch of a merge to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeSide {
+    /// The local branch.
+    Ours,
+    /// The incoming branch.
+    Theirs,
+}
+
+/// An object both branches edited differently.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    /// Object edited on both branches.
+    pub target: OpTarget,
+    /// Local entries touching it.
+    pub ours:   Vec<JournalEntry>,
+    /// Incoming entries touching it.
+    pub theirs: Vec<JournalEntry>,
+}
+
+/// Outcome of a three-way merge.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JournalMerge {
+    /// Base followed by the merged entries in clock order; replaying it
+    /// from the base state gives the merged edit.
+    pub entries:   Vec<JournalEntry>,
+    /// Incoming entries to apply on top of the local branch.
+    pub incoming:  Vec<JournalEntry>,
+    /// Objects left for the user; their entries are in neither list.
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl JournalMerge {
+    /// Returns whether the branches merged without conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Settle a conflict by keeping one side's entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no conflict at `index`.
+    pub fn resolve(&mut self, index: usize, side: MergeSide) -> VideoEditorResult<()> {
+        if index >= self.conflicts.len() {
+            return Err(VideoEditorError::timeline(
+                "resolve_conflict",
+                format!("Merge conflict {index} not found"),
+            ));
+        }
+        let conflict = self.conflicts.remove(index);
+        let kept = match side {
+            MergeSide::Ours => conflict.ours,
+            MergeSide::Theirs => conflict.theirs,
+        };
+        for entry in kept {
+            if self.entries.iter().any(|e| e.same_entry(&entry)) {
+                continue;
+            }
+            if side == MergeSide::Theirs {
+                insert_ordered(&mut self.incoming, entry.clone());
+            }
+            insert_ordered(&mut self.entries, entry);
+        }
+        Ok(())
+    }
+}
+
+/// An author's append-only edit history.
+#[derive(Debug, Clone)]
+pub struct EditJournal {
+    author:  String,
+    clock:   u64,
+    entries: Vec<JournalEntry>,
+}
+
+impl EditJournal {
+    /// Create an empty journal for `author`.
+    pub fn new(author: impl Into<String>) -> Self {
+        Self { author: author.into(), clock: 0, entries: Vec::new() }
+    }
+
+    /// Author of new entries.
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    /// Current Lamport clock.
+    pub fn clock(&self) -> u64 {
+        self.clock
+    }
+
+    /// Recorded entries, oldest first.
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    /// Record a local edit.
+    pub fn record(&mut self, op: ScriptOperation) -> &JournalEntry {
+        self.clock += 1;
+        self.entries.push(JournalEntry { lamport: self.clock, author: self.author.clone(), op });
+        &self.entries[self.entries.len() - 1]
+    }
+
+    /// Run an edit and record it if it succeeds.
+    pub fn run(
+        &mut self, api: &mut EditorScriptApi<'_>, op: ScriptOperation,
+    ) -> VideoEditorResult<OperationOutput> {
+        let output = api.run(&op)?;
+        self.record(op);
+        Ok(output)
+    }
+
+    /// Append an entry received from another journal, advancing the clock
+    /// past it. Entries already present are ignored.
+    pub fn receive(&mut self, entry: JournalEntry) {
+        self.clock = self.clock.max(entry.lamport);
+        if !self.entries.iter().any(|e| e.same_entry(&entry)) {
+            self.entries.push(entry);
+        }
+    }
+
+    /// Number of leading entries shared with `other`: the merge base.
+    pub fn fork_point(&self, other: &Self) -> usize {
+        self.entries
+            .iter()
+            .zip(&other.entries)
+            .take_while(|(a, b)| a.same_entry(b) && a.op == b.op)
+            .count()
+    }
+
+    /// Merge `theirs` into this journal's history from their fork point.
+    ///
+    /// Nothing is applied: replay [`JournalMerge::incoming`] onto the
+    /// local edit, then adopt the merge with [`Self::adopt`].
+    pub fn merge_with(&self, theirs: &Self) -> JournalMerge {
+        let base = &self.entries[..self.fork_point(theirs)];
+        Self::merge(base, &self.entries, &theirs.entries)
+    }
+
+    /// Replace the history with a resolved merge and advance the clock past
+    /// every merged entry.
+    pub fn adopt(&mut self, merge: &JournalMerge) {
+        self.entries = merge.entries.clone();
+        let latest = self.entries.iter().map(|e| e.lamport).max().unwrap_or(0);
+        self.clock = self.clock.max(latest);
+    }
+
+    /// Three-way merge of two branches of `base`.
+    ///
+    /// Entries not in `base` are each branch's changes. An object touched
+    /// on only one branch, or by identical edits on both, merges cleanly;
+    /// otherwise every entry on either side touching it becomes part of a
+    /// conflict. Edits that don't name their track, of a clip or of every
+    /// video track, count as touching each track either branch removes.
+    pub fn merge(
+        base: &[JournalEntry], ours: &[JournalEntry], theirs: &[JournalEntry],
+    ) -> JournalMerge {
+        let in_base = |entry: &JournalEntry| base.iter().any(|b| b.same_entry(entry));
+        let ours: Vec<&JournalEntry> = ours.iter().filter(|e| !in_base(e)).collect();
+        let theirs: Vec<&JournalEntry> = theirs
+            .iter()
+            .filter(|e| !in_base(e) && !ours.iter().any(|o| o.same_entry(e)))
+            .collect();
+
+        let removed_tracks: Vec<u64> = ours
+            .iter()
+            .chain(&theirs)
+            .filter_map(|e| match e.op {
+                ScriptOperation::RemoveTrack { track_id } => Some(track_id),
+                _ => None,
+            })
+            .collect();
+        let targets_of = |op: &ScriptOperation| {
+            let mut targets = OpTarget::of(op);
+            let unplaced = matches!(op, ScriptOperation::CrossfadeAllCuts { track_id: None, .. })
+                || targets.iter().any(|t| matches!(t, OpTarget::Clip(_)));
+            if unplaced {
+                for &track_id in &removed_tracks {
+                    if !targets.contains(&OpTarget::Track(track_id)) {
+                        targets.push(OpTarget::Track(track_id));
+                    }
+                }
+            }
+            targets
+        };
+
+        let mut targets: Vec<OpTarget> = Vec::new();
+        for entry in ours.iter().chain(&theirs) {
+            for target in targets_of(&entry.op) {
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+        }
+
+        let mut merge = JournalMerge { entries: base.to_vec(), ..JournalMerge::default() };
+        let mut held: Vec<&JournalEntry> = Vec::new();
+        let mut duplicates: Vec<&JournalEntry> = Vec::new();
+        for target in targets {
+            let touching = |branch: &[&JournalEntry]| -> Vec<JournalEntry> {
+                branch
+                    .iter()
+                    .filter(|e| targets_of(&e.op).contains(&target))
+                    .map(|e| (*e).clone())
+                    .collect()
+            };
+            let (ours_touching, theirs_touching) = (touching(&ours), touching(&theirs));
+            if ours_touching.is_empty() || theirs_touching.is_empty() {
+                continue;
+            }
+            let same_edits = ours_touching.len() == theirs_touching.len()
+                && ours_touching.iter().zip(&theirs_touching).all(|(a, b)| a.op == b.op);
+            if same_edits {
+                // Both made the same change; keep our copy
+                duplicates.extend(
+                    theirs.iter().filter(|e| theirs_touching.iter().any(|t| t.same_entry(e))),
+                );
+                continue;
+            }
+            held.extend(
+                ours.iter().chain(&theirs).filter(|e| {
+                    ours_touching.iter().chain(&theirs_touching).any(|t| t.same_entry(e))
+                }),
+            );
+            merge.conflicts.push(MergeConflict {
+                target,
+                ours: ours_touching,
+                theirs: theirs_touching,
+            });
+        }
+
+        let clean = |entry: &&&JournalEntry| {
+            !held.iter().any(|h| h.same_entry(entry))
+                && !duplicates.iter().any(|d| d.same_entry(entry))
+        };
+        for entry in ours.iter().filter(clean) {
+            insert_ordered(&mut merge.entries, (*entry).clone());
+        }
+        for entry in theirs.iter().filter(clean) {
+            insert_ordered(&mut merge.entries, (*entry).clone());
+            insert_ordered(&mut merge.incoming, (*entry).clone());
+        }
+        merge
+    }
+
+    /// Encode the journal (magic, author, clock, then entries).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = JOURNAL_MAGIC.to_le_bytes().to_vec();
+        put_str(&mut bytes, &self.author);
+        put_u64(&mut bytes, self.clock);
+        bytes.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for entry in &self.entries {
+            put_u64(&mut bytes, entry.lamport);
+            put_str(&mut bytes, &entry.author);
+            bytes.extend_from_slice(&entry.op.to_bytes());
+        }
+        bytes
+    }
+
+    /// Decode a journal written by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.u32()? != JOURNAL_MAGIC {
+            return None;
+        }
+        let author = reader.string()?;
+        let clock = reader.u64()?;
+        let count = reader.u32()? as usize;
+        let mut entries = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            let lamport = reader.u64()?;
+            let entry_author = reader.string()?;
+            let (op, len) = ScriptOperation::from_bytes(&bytes[reader.offset..])?;
+            reader.offset += len;
+            entries.push(JournalEntry { lamport, author: entry_author, op });
+        }
+        Some(Self { author, clock, entries })
+    }
+}
+
+/// Insert after the base part of `entries`, ordered by clock then author.
+fn insert_ordered(entries: &mut Vec<JournalEntry>, entry: JournalEntry) {
+    let key = |e: &JournalEntry| (e.lamport, e.author.clone());
+    let pos = entries.iter().rposition(|e| key(e) <= key(&entry)).map_or(0, |i| i + 1);
+    entries.insert(pos, entry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        implementation::{
+            TimelineManager, marker_system::MarkerManager, transitions::TransitionManager,
+        },
+        types::{FrameRate, TimePosition, TrackType, timeline::TimelineClip},
+    };
+
+    fn at(ms: u64) -> TimePosition {
+        TimePosition::from_ms(ms)
+    }
+
+    #[test]
+    fn test_journal_records_and_round_trips() {
+        let mut timeline = TimelineManager::new();
+        let (mut transitions, mut markers) = (TransitionManager::new(), MarkerManager::new());
+        let mut api =
+            EditorScriptApi::new(&mut timeline, &mut transitions, &mut markers, FrameRate::FPS_30);
+        let mut journal = EditJournal::new("ana");
+        let add =
+            ScriptOperation::AddTrack { name: "V1".into(), track_type: TrackType::Video };
+        assert_eq!(journal.run(&mut api, add).expect("test assertion"), OperationOutput::Track(1));
+        assert!(journal.run(&mut api, ScriptOperation::RippleDelete { clip_id: 9 }).is_err());
+        assert_eq!(journal.entries().len(), 1);
+
+        journal.receive(JournalEntry {
+            lamport: 7,
+            author:  "ben".into(),
+            op:      ScriptOperation::AddMarker { at: at(500), name: "Hit".into() },
+        });
+        journal.record(ScriptOperation::MoveClip { clip_id: 1, start: at(100) });
+        assert_eq!(journal.entries()[2].lamport, 8);
+
+        let decoded = EditJournal::from_bytes(&journal.to_bytes()).expect("test assertion");
+        assert_eq!(decoded.entries(), journal.entries());
+        assert_eq!((decoded.author(), decoded.clock()), ("ana", 8));
+        assert!(EditJournal::from_bytes(&journal.to_bytes()[..20]).is_none());
+    }
+
+    #[test]
+    fn test_three_way_merge_flags_conflicts() {
+        let mut base = EditJournal::new("ana");
+        base.record(ScriptOperation::AddTrack {
+            name:       "V1".into(),
+            track_type: TrackType::Video,
+        });
+        let mut ours = base.clone();
+        let mut theirs = EditJournal::new("ben");
+        for entry in base.entries() {
+            theirs.receive(entry.clone());
+        }
+
+        ours.record(ScriptOperation::MoveClip { clip_id: 1, start: at(1_000) });
+        ours.record(ScriptOperation::TrimClipEnd { clip_id: 2, end: at(4_000) });
+        ours.record(ScriptOperation::RemoveMarker { marker_id: 5 });
+        theirs.record(ScriptOperation::MoveClip { clip_id: 1, start: at(2_000) });
+        theirs.record(ScriptOperation::SplitClip { clip_id: 3, at: at(500) });
+        theirs.record(ScriptOperation::RemoveMarker { marker_id: 5 });
+
+        let mut merge = ours.merge_with(&theirs);
+        assert_eq!(merge.conflicts.len(), 1);
+        assert_eq!(merge.conflicts[0].target, OpTarget::Clip(1));
+        // Base, our trim and marker removal, their split; the duplicate
+        // marker removal is dropped
+        let ops: Vec<&str> = merge.entries.iter().map(|e| e.op.name()).collect();
+        assert_eq!(ops, vec!["add_track", "trim_clip_end", "split_clip", "remove_marker"]);
+        assert_eq!(merge.incoming.len(), 1);
+
+        merge.resolve(0, MergeSide::Theirs).expect("test assertion");
+        assert!(merge.is_clean());
+        assert_eq!(merge.entries[1].op, ScriptOperation::MoveClip { clip_id: 1, start: at(2_000) });
+        assert_eq!(merge.incoming.len(), 2);
+        assert!(merge.resolve(0, MergeSide::Ours).is_err());
+
+        ours.adopt(&merge);
+        assert_eq!(ours.entries().len(), 5);
+        assert_eq!(ours.record(ScriptOperation::RippleDelete { clip_id: 2 }).lamport, 5);
+
+        // Replaying the incoming entries applies their move and split on
+        // our side
+        let mut timeline = TimelineManager::new();
+        let track = timeline.add_track("V1", TrackType::Video);
+        for (clip_id, start) in [(1, 3_000), (3, 0)] {
+            let clip = TimelineClip::new(clip_id, 1, at(start), at(1_000));
+            timeline.add_clip(track, clip).expect("test assertion");
+        }
+        let (mut transitions, mut markers) = (TransitionManager::new(), MarkerManager::new());
+        let mut api =
+            EditorScriptApi::new(&mut timeline, &mut transitions, &mut markers, FrameRate::FPS_30);
+        let incoming: Vec<ScriptOperation> = merge.incoming.iter().map(|e| e.op.clone()).collect();
+        let result = api.run_batch(incoming);
+        assert!(result.is_ok());
+        assert_eq!(result.outputs.len(), 2);
+    }
+
+    #[test]
+    fn test_track_removal_conflicts_with_clip_edits() {
+        let mut ours = EditJournal::new("ana");
+        let mut theirs = EditJournal::new("ben");
+        ours.record(ScriptOperation::RemoveTrack { track_id: 2 });
+        theirs.record(ScriptOperation::MoveClip { clip_id: 1, start: at(1_000) });
+        theirs.record(ScriptOperation::TrimClipStart { clip_id: 4, start: at(200) });
+        theirs.record(ScriptOperation::AddMarker { at: at(0), name: "Top".into() });
+
+        // The clips may sit on the removed track, so their edits wait with
+        // the removal; the marker merges
+        let mut merge = ours.merge_with(&theirs);
+        assert_eq!(merge.conflicts.len(), 1);
+        let conflict = &merge.conflicts[0];
+        assert_eq!(conflict.target, OpTarget::Track(2));
+        assert_eq!((conflict.ours.len(), conflict.theirs.len()), (1, 2));
+        let ops: Vec<&str> = merge.entries.iter().map(|e| e.op.name()).collect();
+        assert_eq!(ops, vec!["add_marker"]);
+        merge.resolve(0, MergeSide::Ours).expect("test assertion");
+        assert_eq!(merge.entries.len(), 2);
+        assert_eq!(merge.incoming.len(), 1);
+
+        // A crossfade names its track, and the same removal on both sides
+        // is no conflict
+        let mut theirs = EditJournal::new("ben");
+        theirs.record(ScriptOperation::RemoveTrack { track_id: 2 });
+        assert!(ours.merge_with(&theirs).is_clean());
+        let crossfade = ScriptOperation::AddCrossfade {
+            track_id:  3,
+            clip_a_id: 5,
+            clip_b_id: 6,
+            frames:    10,
+        };
+        assert_eq!(OpTarget::of(&crossfade)[0], OpTarget::Track(3));
+
+        // Crossfading every video track may reach the removed one
+        let mut theirs = EditJournal::new("ben");
+        theirs.record(ScriptOperation::CrossfadeAllCuts { track_id: None, frames: 10 });
+        assert_eq!(ours.merge_with(&theirs).conflicts.len(), 1);
+    }
+}
@@ -1,4 +1,27 @@
 //! Effects pipeline.
+//!
+//! Each effect type declares which implementations it ships (see
+//! [`EffectCapabilities`]). GPU kernels can still be missing on a given
+//! machine, so the pipeline probes the device and picks a backend per
+//! effect at run time; [`ExecutionMode::ForceCpu`] pins everything to the
+//! CPU path for deterministic tests.
+//!
+//! Parameters can be keyframed per clip. The animation of an effect on a
+//! clip lives in the pipeline, and [`EffectsPipeline::resolve_at`] bakes
+//! it into a copy of the effect for one frame.
+
+use std::collections::HashMap;
+
+use super::{
+    events::{EditorEvent, EventBus},
+    gpu_pipeline::GpuPipeline,
+    keyframe_animation::{AnimatedValue, AnimationLayer},
+};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    trace::{self, TracePhase},
+    types::TimePosition,
+};
 
 /// Video effect.
 #[derive(Debug, Clone)]
@@ -9,10 +32,47 @@ pub struct VideoEffect {
     pub effect_type: EffectType,
     /// Effect parameters.
     pub parameters:  Vec<(String, f64)>,
+    /// A/B comparison state.
+    pub ab:          AbCompare,
+}
+
+impl VideoEffect {
+    /// Get a parameter value.
+    pub fn parameter(&self, name: &str) -> Option<f64> {
+        self.parameters.iter().find(|(n, _)| n == name).map(|(_, v)| *v)
+    }
+
+    /// Set a parameter value, adding it if missing.
+    pub fn set_parameter(&mut self, name: &str, value: f64) {
+        if let Some(param) = self.parameters.iter_mut().find(|(n, _)| n == name) {
+            param.1 = value;
+        } else {
+            self.parameters.push((name.to_string(), value));
+        }
+    }
+
+    /// Processing quality, from the `quality` parameter.
+    pub fn quality(&self) -> EffectQuality {
+        self.parameter("quality").map_or(EffectQuality::default(), EffectQuality::from_parameter)
+    }
+
+    /// Implementations available for this effect.
+    pub fn capabilities(&self) -> EffectCapabilities {
+        self.effect_type.capabilities()
+    }
+
+    fn state(&self) -> EffectState {
+        EffectState { parameters: self.parameters.clone(), ab: self.ab.clone() }
+    }
+
+    fn restore(&mut self, state: EffectState) {
+        self.parameters = state.parameters;
+        self.ab = state.ab;
+    }
 }
 
 /// Effect type.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EffectType {
     /// Color correction.
     ColorCorrection,
@@ -26,26 +86,259 @@ pub enum EffectType {
     CrossDissolve,
     /// Custom shader.
     CustomShader,
+    /// Motion stabilization (see `Stabilizer`).
+    Stabilize,
+    /// Temporal and spatial noise reduction (see `VideoDenoiser`).
+    Denoise,
+    /// Interlaced to progressive conversion (see `Deinterlacer`).
+    Deinterlace,
+}
+
+impl EffectType {
+    /// All effect types. Saved presets and templates store the position in
+    /// this list, so new types go at the end.
+    pub const ALL: [Self; 9] = [
+        Self::ColorCorrection,
+        Self::Blur,
+        Self::Sharpen,
+        Self::Fade,
+        Self::CrossDissolve,
+        Self::CustomShader,
+        Self::Stabilize,
+        Self::Denoise,
+        Self::Deinterlace,
+    ];
+
+    /// Implementations shipped for this effect type.
+    pub fn capabilities(self) -> EffectCapabilities {
+        match self {
+            Self::CustomShader => EffectCapabilities { cpu: false, gpu: true },
+            _ => EffectCapabilities { cpu: true, gpu: true },
+        }
+    }
+}
+
+/// Implementations an effect provides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EffectCapabilities {
+    /// Has a CPU implementation.
+    pub cpu: bool,
+    /// Has a GPU kernel.
+    pub gpu: bool,
+}
+
+/// Where an effect runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EffectBackend {
+    /// CPU implementation.
+    Cpu,
+    /// GPU kernel.
+    Gpu,
+}
+
+/// Processing quality of filter effects, trading speed for fidelity.
+///
+/// Stored in an effect's `quality` parameter as 0, 1 or 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub enum EffectQuality {
+    /// Smallest kernels, for interactive preview.
+    Draft,
+    /// Balanced default.
+    #[default]
+    Good,
+    /// Largest kernels, for final export.
+    Best,
+}
+
+impl EffectQuality {
+    /// Value of the `quality` parameter.
+    pub fn to_parameter(self) -> f64 {
+        match self {
+            Self::Draft => 0.0,
+            Self::Good => 1.0,
+            Self::Best => 2.0,
+        }
+    }
+
+    /// Quality from a `quality` parameter value, rounding to the nearest
+    /// tier.
+    pub fn from_parameter(value: f64) -> Self {
+        match value.round() {
+            v if v <= 0.0 => Self::Draft,
+            v if v >= 2.0 => Self::Best,
+            _ => Self::Good,
+        }
+    }
+}
+
+/// How the pipeline chooses effect backends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ExecutionMode {
+    /// GPU where a kernel is available, CPU otherwise.
+    #[default]
+    Auto,
+    /// CPU only, for bit-exact output across machines. Effects without a
+    /// CPU implementation cannot run.
+    ForceCpu,
+}
+
+/// A/B comparison slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AbSlot {
+    /// Slot A.
+    #[default]
+    A,
+    /// Slot B.
+    B,
+}
+
+/// A/B comparison state of an effect instance.
+///
+/// The effect's `parameters` always hold the active slot; the inactive slot
+/// is kept here until the slots are swapped.
+#[derive(Debug, Clone, Default)]
+pub struct AbCompare {
+    /// Currently active slot.
+    pub active:   AbSlot,
+    /// Parameters of the inactive slot (None until first used).
+    pub inactive: Option<Vec<(String, f64)>>,
+}
+
+/// Named parameter preset for an effect type.
+#[derive(Debug, Clone)]
+pub struct EffectPreset {
+    /// Preset name.
+    pub name:        String,
+    /// Effect type the preset applies to.
+    pub effect_type: EffectType,
+    /// Saved parameters.
+    pub parameters:  Vec<(String, f64)>,
+}
+
+/// Undoable effect state.
+#[derive(Debug, Clone)]
+struct EffectState {
+    parameters: Vec<(String, f64)>,
+    ab:         AbCompare,
+}
+
+/// Recorded effect edit.
+#[derive(Debug, Clone)]
+struct EffectEdit {
+    effect_id: u64,
+    before:    EffectState,
+    after:     EffectState,
 }
 
 /// Effects pipeline for video processing.
 pub struct EffectsPipeline {
     effects:        Vec<VideoEffect>,
     next_effect_id: u64,
+    presets:        Vec<EffectPreset>,
+    undo_stack:     Vec<EffectEdit>,
+    redo_stack:     Vec<EffectEdit>,
+    max_undo:       usize,
+    events:         EventBus,
+    execution_mode: ExecutionMode,
+    gpu_kernels:    Vec<EffectType>,
+    /// Keyframed parameters by (effect, clip).
+    animations:     HashMap<(u64, u64), AnimationLayer>,
 }
 
 impl EffectsPipeline {
     /// Create a new effects pipeline.
     pub fn new() -> Self {
-        Self { effects: Vec::new(), next_effect_id: 1 }
+        Self {
+            effects:        Vec::new(),
+            next_effect_id: 1,
+            presets:        Vec::new(),
+            undo_stack:     Vec::new(),
+            redo_stack:     Vec::new(),
+            max_undo:       100,
+            events:         EventBus::new(),
+            execution_mode: ExecutionMode::Auto,
+            gpu_kernels:    Vec::new(),
+            animations:     HashMap::new(),
+        }
+    }
+
+    /// Emit parameter change events on a shared bus.
+    pub fn set_event_bus(&mut self, events: EventBus) {
+        self.events = events;
+    }
+
+    /// Get the backend selection mode.
+    pub fn execution_mode(&self) -> ExecutionMode {
+        self.execution_mode
+    }
+
+    /// Set the backend selection mode.
+    pub fn set_execution_mode(&mut self, mode: ExecutionMode) {
+        self.execution_mode = mode;
+    }
+
+    /// Record which GPU kernels the device can run.
+    ///
+    /// Without an available GPU every effect falls back to the CPU.
+    /// Returns the number of loaded kernels.
+    pub fn probe_gpu(&mut self, gpu: &GpuPipeline) -> usize {
+        self.gpu_kernels = if gpu.is_available() {
+            EffectType::ALL.into_iter().filter(|t| t.capabilities().gpu).collect()
+        } else {
+            Vec::new()
+        };
+        self.gpu_kernels.len()
+    }
+
+    /// Mark a GPU kernel as failing to load or compile on this device.
+    pub fn disable_gpu_kernel(&mut self, effect_type: EffectType) {
+        self.gpu_kernels.retain(|&t| t != effect_type);
+    }
+
+    /// Whether a GPU kernel for `effect_type` is loaded.
+    pub fn has_gpu_kernel(&self, effect_type: EffectType) -> bool {
+        self.gpu_kernels.contains(&effect_type)
+    }
+
+    /// Pick the backend an effect will run on.
+    pub fn backend_for(&self, effect_id: u64) -> VideoEditorResult<EffectBackend> {
+        let effect = self.find(effect_id)?;
+        let caps = effect.capabilities();
+        let gpu = caps.gpu
+            && self.execution_mode == ExecutionMode::Auto
+            && self.has_gpu_kernel(effect.effect_type);
+        if gpu {
+            Ok(EffectBackend::Gpu)
+        } else if caps.cpu {
+            Ok(EffectBackend::Cpu)
+        } else {
+            Err(VideoEditorError::Effect(format!(
+                "{:?} effect {effect_id} has no CPU implementation and no GPU kernel is available",
+                effect.effect_type
+            )))
+        }
+    }
+
+    /// Backends for every effect in stack order.
+    ///
+    /// Fails on the first effect that cannot run on this machine.
+    pub fn execution_plan(&self) -> VideoEditorResult<Vec<(u64, EffectBackend)>> {
+        self.effects.iter().map(|e| Ok((e.id, self.backend_for(e.id)?))).collect()
     }
 
     /// Add an effect.
     pub fn add_effect(&mut self, effect_type: EffectType) -> u64 {
+        self.add_effect_with(effect_type, Vec::new())
+    }
+
+    /// Add an effect with initial parameters, e.g. from a template.
+    pub fn add_effect_with(
+        &mut self, effect_type: EffectType, parameters: Vec<(String, f64)>,
+    ) -> u64 {
         let id = self.next_effect_id;
         self.next_effect_id += 1;
 
-        self.effects.push(VideoEffect { id, effect_type, parameters: Vec::new() });
+        self.effects.push(VideoEffect { id, effect_type, parameters, ab: AbCompare::default() });
 
         id
     }
@@ -54,6 +347,9 @@ impl EffectsPipeline {
     pub fn remove_effect(&mut self, effect_id: u64) -> bool {
         if let Some(pos) = self.effects.iter().position(|e| e.id == effect_id) {
             self.effects.remove(pos);
+            self.undo_stack.retain(|e| e.effect_id != effect_id);
+            self.redo_stack.retain(|e| e.effect_id != effect_id);
+            self.animations.retain(|(id, _), _| *id != effect_id);
             true
         } else {
             false
@@ -64,6 +360,223 @@ impl EffectsPipeline {
     pub fn effects(&self) -> &[VideoEffect] {
         &self.effects
     }
+
+    /// Get an effect by ID.
+    pub fn effect(&self, effect_id: u64) -> Option<&VideoEffect> {
+        self.effects.iter().find(|e| e.id == effect_id)
+    }
+
+    /// Set an effect parameter (undoable).
+    pub fn set_parameter(
+        &mut self, effect_id: u64, name: &str, value: f64,
+    ) -> VideoEditorResult<()> {
+        self.edit(effect_id, |effect| {
+            effect.set_parameter(name, value);
+            Ok(())
+        })
+    }
+
+    /// Keyframe an effect parameter on one clip, at a time relative to the
+    /// clip start. The first keyframe of a parameter also keys its static
+    /// value at the clip start.
+    pub fn animate_parameter(
+        &mut self, effect_id: u64, clip_id: u64, name: &str, time: TimePosition, value: f64,
+    ) -> VideoEditorResult<()> {
+        let effect = self.find(effect_id)?;
+        let rest = effect.parameter(name).unwrap_or(value);
+        let layer = self
+            .animations
+            .entry((effect_id, clip_id))
+            .or_insert_with(|| AnimationLayer::new(format!("Effect {effect_id}"), clip_id));
+        if layer.get_track_by_property(name).is_none() {
+            layer.create_track(name, AnimatedValue::Float(rest));
+            if let Some(track) = layer.get_track_by_property_mut(name) {
+                track.add_keyframe(TimePosition::from_ms(0), AnimatedValue::Float(rest));
+            }
+        }
+        if let Some(track) = layer.get_track_by_property_mut(name) {
+            track.add_keyframe(time, AnimatedValue::Float(value));
+        }
+        Ok(())
+    }
+
+    /// Get the parameter animation of an effect on a clip.
+    pub fn parameter_animation(&self, effect_id: u64, clip_id: u64) -> Option<&AnimationLayer> {
+        self.animations.get(&(effect_id, clip_id))
+    }
+
+    /// Get the mutable parameter animation of an effect on a clip.
+    pub fn parameter_animation_mut(
+        &mut self, effect_id: u64, clip_id: u64,
+    ) -> Option<&mut AnimationLayer> {
+        self.animations.get_mut(&(effect_id, clip_id))
+    }
+
+    /// Remove the parameter animation of an effect on a clip.
+    pub fn clear_parameter_animation(&mut self, effect_id: u64, clip_id: u64) -> bool {
+        self.animations.remove(&(effect_id, clip_id)).is_some()
+    }
+
+    /// Copy of an effect with its parameters animated on `clip_id`
+    /// evaluated at `time`, relative to the clip start.
+    pub fn resolve_at(
+        &self, effect_id: u64, clip_id: u64, time: TimePosition,
+    ) -> VideoEditorResult<VideoEffect> {
+        let _span = trace::span(TracePhase::Effect, "resolve parameters");
+        let mut effect = self.find(effect_id)?.clone();
+        if let Some(layer) = self.animations.get(&(effect_id, clip_id)) {
+            for (name, value) in layer.evaluate_all(time) {
+                if let Some(value) = value.as_float() {
+                    effect.set_parameter(name, value);
+                }
+            }
+        }
+        Ok(effect)
+    }
+
+    /// Save the current parameters of an effect as a named preset.
+    ///
+    /// Replaces an existing preset with the same name for the effect type.
+    pub fn save_preset(&mut self, effect_id: u64, name: &str) -> VideoEditorResult<()> {
+        let effect = self.find(effect_id)?;
+        let preset = EffectPreset {
+            name:        name.to_string(),
+            effect_type: effect.effect_type,
+            parameters:  effect.parameters.clone(),
+        };
+        self.presets.retain(|p| !(p.effect_type == preset.effect_type && p.name == name));
+        self.presets.push(preset);
+        Ok(())
+    }
+
+    /// Apply a named preset to an effect (undoable).
+    pub fn apply_preset(&mut self, effect_id: u64, name: &str) -> VideoEditorResult<()> {
+        let effect_type = self.find(effect_id)?.effect_type;
+        let parameters = self
+            .presets
+            .iter()
+            .find(|p| p.effect_type == effect_type && p.name == name)
+            .map(|p| p.parameters.clone())
+            .ok_or_else(|| VideoEditorError::Effect(format!("Preset not found: {name}")))?;
+
+        self.edit(effect_id, |effect| {
+            effect.parameters = parameters;
+            Ok(())
+        })
+    }
+
+    /// Remove a preset.
+    pub fn remove_preset(&mut self, effect_type: EffectType, name: &str) -> bool {
+        let before = self.presets.len();
+        self.presets.retain(|p| !(p.effect_type == effect_type && p.name == name));
+        self.presets.len() != before
+    }
+
+    /// Get presets for an effect type.
+    pub fn presets_for(&self, effect_type: EffectType) -> Vec<&EffectPreset> {
+        self.presets.iter().filter(|p| p.effect_type == effect_type).collect()
+    }
+
+    /// Swap the A/B slots of an effect (undoable). Returns the now active slot.
+    ///
+    /// The first swap starts the other slot as a copy of the current settings.
+    pub fn toggle_ab(&mut self, effect_id: u64) -> VideoEditorResult<AbSlot> {
+        self.edit(effect_id, |effect| {
+            let other = effect.ab.inactive.take().unwrap_or_else(|| effect.parameters.clone());
+            effect.ab.inactive = Some(std::mem::replace(&mut effect.parameters, other));
+            effect.ab.active = match effect.ab.active {
+                AbSlot::A => AbSlot::B,
+                AbSlot::B => AbSlot::A,
+            };
+            Ok(())
+        })?;
+        Ok(self.find(effect_id)?.ab.active)
+    }
+
+    /// Copy the active slot's parameters into the inactive slot (undoable).
+    pub fn copy_to_inactive_slot(&mut self, effect_id: u64) -> VideoEditorResult<()> {
+        self.edit(effect_id, |effect| {
+            effect.ab.inactive = Some(effect.parameters.clone());
+            Ok(())
+        })
+    }
+
+    /// Returns whether undo is available.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Returns whether redo is available.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Undo the last effect edit.
+    pub fn undo(&mut self) -> bool {
+        let Some(edit) = self.undo_stack.pop() else {
+            return false;
+        };
+        if let Some(effect) = self.effects.iter_mut().find(|e| e.id == edit.effect_id) {
+            effect.restore(edit.before.clone());
+        }
+        self.emit_changes(edit.effect_id, &edit.after.parameters, &edit.before.parameters);
+        self.redo_stack.push(edit);
+        true
+    }
+
+    /// Redo the last undone effect edit.
+    pub fn redo(&mut self) -> bool {
+        let Some(edit) = self.redo_stack.pop() else {
+            return false;
+        };
+        if let Some(effect) = self.effects.iter_mut().find(|e| e.id == edit.effect_id) {
+            effect.restore(edit.after.clone());
+        }
+        self.emit_changes(edit.effect_id, &edit.before.parameters, &edit.after.parameters);
+        self.undo_stack.push(edit);
+        true
+    }
+
+    fn find(&self, effect_id: u64) -> VideoEditorResult<&VideoEffect> {
+        self.effect(effect_id)
+            .ok_or_else(|| VideoEditorError::Effect(format!("Effect not found: {effect_id}")))
+    }
+
+    /// Emit an event for each parameter whose value differs after an edit.
+    fn emit_changes(&self, effect_id: u64, before: &[(String, f64)], after: &[(String, f64)]) {
+        for (name, value) in after {
+            if !before.iter().any(|(n, v)| n == name && v == value) {
+                self.events.emit(EditorEvent::EffectParamChanged {
+                    effect_id,
+                    name: name.clone(),
+                    value: *value,
+                });
+            }
+        }
+    }
+
+    /// Apply a change to an effect and record it for undo.
+    fn edit<F>(&mut self, effect_id: u64, change: F) -> VideoEditorResult<()>
+    where
+        F: FnOnce(&mut VideoEffect) -> VideoEditorResult<()>,
+    {
+        let effect =
+            self.effects.iter_mut().find(|e| e.id == effect_id).ok_or_else(|| {
+                VideoEditorError::Effect(format!("Effect not found: {effect_id}"))
+            })?;
+
+        let before = effect.state();
+        change(effect)?;
+        let after = effect.state();
+        self.emit_changes(effect_id, &before.parameters, &after.parameters);
+
+        self.undo_stack.push(EffectEdit { effect_id, before, after });
+        self.redo_stack.clear();
+        if self.undo_stack.len() > self.max_undo {
+            self.undo_stack.remove(0);
+        }
+        Ok(())
+    }
 }
 
 impl Default for EffectsPipeline {
@@ -71,3 +584,142 @@ impl Default for EffectsPipeline {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_selection() {
+        let mut pipeline = EffectsPipeline::new();
+        let blur = pipeline.add_effect(EffectType::Blur);
+        let shader = pipeline.add_effect(EffectType::CustomShader);
+
+        // No device probed yet: CPU fallback, GPU-only effects cannot run
+        assert_eq!(pipeline.backend_for(blur).expect("test assertion"), EffectBackend::Cpu);
+        assert!(pipeline.backend_for(shader).is_err());
+        assert!(pipeline.execution_plan().is_err());
+
+        let mut gpu = GpuPipeline::new(true);
+        gpu.initialize();
+        assert_eq!(pipeline.probe_gpu(&gpu), EffectType::ALL.len());
+        let plan = pipeline.execution_plan().expect("test assertion");
+        assert_eq!(plan, vec![(blur, EffectBackend::Gpu), (shader, EffectBackend::Gpu)]);
+
+        pipeline.disable_gpu_kernel(EffectType::Blur);
+        assert_eq!(pipeline.backend_for(blur).expect("test assertion"), EffectBackend::Cpu);
+
+        pipeline.probe_gpu(&gpu);
+        pipeline.set_execution_mode(ExecutionMode::ForceCpu);
+        assert_eq!(pipeline.backend_for(blur).expect("test assertion"), EffectBackend::Cpu);
+        assert!(pipeline.backend_for(shader).is_err());
+    }
+
+    #[test]
+    fn test_preset_roundtrip() {
+        let mut pipeline = EffectsPipeline::new();
+        let blur = pipeline.add_effect(EffectType::Blur);
+        pipeline.set_parameter(blur, "radius", 4.0).expect("test assertion");
+        pipeline.save_preset(blur, "Soft").expect("test assertion");
+
+        pipeline.set_parameter(blur, "radius", 12.0).expect("test assertion");
+        pipeline.apply_preset(blur, "Soft").expect("test assertion");
+        assert_eq!(pipeline.effect(blur).and_then(|e| e.parameter("radius")), Some(4.0));
+
+        let sharpen = pipeline.add_effect(EffectType::Sharpen);
+        assert!(pipeline.apply_preset(sharpen, "Soft").is_err());
+        assert_eq!(pipeline.presets_for(EffectType::Blur).len(), 1);
+    }
+
+    #[test]
+    fn test_ab_compare() {
+        let mut pipeline = EffectsPipeline::new();
+        let id = pipeline.add_effect(EffectType::ColorCorrection);
+        pipeline.set_parameter(id, "exposure", 0.5).expect("test assertion");
+
+        assert_eq!(pipeline.toggle_ab(id).expect("test assertion"), AbSlot::B);
+        pipeline.set_parameter(id, "exposure", 1.5).expect("test assertion");
+
+        assert_eq!(pipeline.toggle_ab(id).expect("test assertion"), AbSlot::A);
+        assert_eq!(pipeline.effect(id).and_then(|e| e.parameter("exposure")), Some(0.5));
+        assert_eq!(pipeline.toggle_ab(id).expect("test assertion"), AbSlot::B);
+        assert_eq!(pipeline.effect(id).and_then(|e| e.parameter("exposure")), Some(1.5));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut pipeline = EffectsPipeline::new();
+        let id = pipeline.add_effect(EffectType::Blur);
+        pipeline.set_parameter(id, "radius", 2.0).expect("test assertion");
+        pipeline.toggle_ab(id).expect("test assertion");
+        pipeline.set_parameter(id, "radius", 8.0).expect("test assertion");
+
+        assert!(pipeline.undo());
+        assert!(pipeline.undo());
+        let effect = pipeline.effect(id).expect("test assertion");
+        assert_eq!(effect.ab.active, AbSlot::A);
+        assert_eq!(effect.parameter("radius"), Some(2.0));
+
+        assert!(pipeline.redo());
+        assert_eq!(pipeline.effect(id).map(|e| e.ab.active), Some(AbSlot::B));
+        assert!(pipeline.can_redo());
+    }
+
+    #[test]
+    fn test_animated_filter_parameters() {
+        let mut pipeline = EffectsPipeline::new();
+        let id = pipeline.add_effect(EffectType::Denoise);
+        pipeline.set_parameter(id, "spatial", 0.2).expect("test assertion");
+        pipeline
+            .set_parameter(id, "quality", EffectQuality::Draft.to_parameter())
+            .expect("test assertion");
+        pipeline
+            .animate_parameter(id, 7, "spatial", TimePosition::from_ms(1000), 1.0)
+            .expect("test assertion");
+        assert!(
+            pipeline.animate_parameter(99, 7, "spatial", TimePosition::from_ms(0), 1.0).is_err()
+        );
+
+        let at = |ms| {
+            pipeline
+                .resolve_at(id, 7, TimePosition::from_ms(ms))
+                .expect("test assertion")
+                .parameter("spatial")
+                .expect("test assertion")
+        };
+        assert!((at(1000) - 1.0).abs() < 1e-9);
+        assert!(at(500) > 0.2 && at(500) < 1.0);
+        // Other clips keep the static value
+        let other =
+            pipeline.resolve_at(id, 8, TimePosition::from_ms(1000)).expect("test assertion");
+        assert_eq!(other.parameter("spatial"), Some(0.2));
+        assert_eq!(other.quality(), EffectQuality::Draft);
+
+        let mut gpu = GpuPipeline::new(true);
+        gpu.initialize();
+        let dispatch = gpu
+            .dispatch_filter(&other, 1920, 1080)
+            .expect("test assertion")
+            .expect("test assertion");
+        assert_eq!(
+            (dispatch.kernel, dispatch.passes, dispatch.reads_previous),
+            ("denoise_draft", 1, false)
+        );
+        let blur = pipeline.add_effect(EffectType::Blur);
+        let blur = pipeline.effect(blur).expect("test assertion");
+        assert!(gpu.dispatch_filter(blur, 1920, 1080).expect("test assertion").is_none());
+        let deinterlace = pipeline.add_effect(EffectType::Deinterlace);
+        let deinterlace = pipeline.effect(deinterlace).expect("test assertion");
+        let dispatch = gpu
+            .dispatch_filter(deinterlace, 1920, 1080)
+            .expect("test assertion")
+            .expect("test assertion");
+        assert_eq!(
+            (dispatch.kernel, dispatch.passes, dispatch.reads_previous),
+            ("deinterlace_adaptive", 1, true)
+        );
+
+        assert!(pipeline.remove_effect(id));
+        assert!(pipeline.parameter_animation(id, 7).is_none());
+    }
+}
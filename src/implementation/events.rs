@@ -0,0 +1,172 @@
+//! Editor change notifications.
+//!
+//! Managers emit [`EditorEvent`]s on a shared [`EventBus`] after each edit.
+//! Subscribers get them synchronously through a callback or queued on a
+//! channel for panels that poll from another thread.
+
+use std::sync::{
+    Arc, Mutex,
+    mpsc::{self, Receiver, Sender},
+};
+
+use crate::types::TimePosition;
+
+/// A change to the timeline or project.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditorEvent {
+    /// A track was added.
+    TrackAdded { track_id: u64 },
+    /// A track was removed.
+    TrackRemoved { track_id: u64 },
+    /// A track moved in the stack.
+    TrackMoved { track_id: u64, index: usize },
+    /// A clip was placed on a track.
+    ClipAdded { track_id: u64, clip_id: u64 },
+    /// A clip was removed from the timeline.
+    ClipRemoved { clip_id: u64 },
+    /// A clip moved.
+    ClipMoved { clip_id: u64, from: TimePosition, to: TimePosition },
+    /// A clip's start or end was trimmed.
+    ClipTrimmed { clip_id: u64 },
+    /// A clip was split; `new_clip_id` is the part after the split point.
+    ClipSplit { clip_id: u64, new_clip_id: u64 },
+    /// A ripple edit shifted material on these tracks.
+    TracksRippled { track_ids: Vec<u64> },
+    /// A marker was added.
+    MarkerAdded { marker_id: u64 },
+    /// A marker was moved or edited.
+    MarkerChanged { marker_id: u64 },
+    /// A marker was removed.
+    MarkerRemoved { marker_id: u64 },
+    /// An effect parameter changed.
+    EffectParamChanged { effect_id: u64, name: String, value: f64 },
+    /// A project was created.
+    ProjectCreated { name: String },
+    /// The current project was saved.
+    ProjectSaved { path: String },
+    /// The current project was closed.
+    ProjectClosed,
+}
+
+/// Callback receiving editor events.
+pub type EventCallback = Arc<dyn Fn(&EditorEvent) + Send + Sync>;
+
+/// Handle for removing a subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+enum Subscriber {
+    Callback(EventCallback),
+    Channel(Sender<EditorEvent>),
+}
+
+#[derive(Default)]
+struct Subscribers {
+    list:    Vec<(SubscriptionId, Subscriber)>,
+    next_id: u64,
+}
+
+/// Shared event dispatcher. Clones deliver to the same subscribers.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Subscribers>>,
+}
+
+impl EventBus {
+    /// Create a bus with no subscribers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribe a callback, called synchronously on the emitting thread.
+    pub fn subscribe(&self, callback: EventCallback) -> SubscriptionId {
+        self.add(Subscriber::Callback(callback))
+    }
+
+    /// Subscribe through a channel. The subscription ends when the
+    /// receiver is dropped.
+    pub fn subscribe_channel(&self) -> (SubscriptionId, Receiver<EditorEvent>) {
+        let (sender, receiver) = mpsc::channel();
+        (self.add(Subscriber::Channel(sender)), receiver)
+    }
+
+    fn add(&self, subscriber: Subscriber) -> SubscriptionId {
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(|p| p.into_inner());
+        subscribers.next_id += 1;
+        let id = SubscriptionId(subscribers.next_id);
+        subscribers.list.push((id, subscriber));
+        id
+    }
+
+    /// Remove a subscription.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(|p| p.into_inner());
+        let before = subscribers.list.len();
+        subscribers.list.retain(|(s, _)| *s != id);
+        subscribers.list.len() != before
+    }
+
+    /// Number of live subscriptions.
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().unwrap_or_else(|p| p.into_inner()).list.len()
+    }
+
+    /// Deliver an event to every subscriber.
+    pub fn emit(&self, event: EditorEvent) {
+        let callbacks: Vec<EventCallback> = {
+            let mut subscribers = self.subscribers.lock().unwrap_or_else(|p| p.into_inner());
+            // Drop channels whose receiver is gone
+            subscribers.list.retain(|(_, s)| match s {
+                Subscriber::Channel(sender) => sender.send(event.clone()).is_ok(),
+                Subscriber::Callback(_) => true,
+            });
+            subscribers
+                .list
+                .iter()
+                .filter_map(|(_, s)| match s {
+                    Subscriber::Callback(callback) => Some(Arc::clone(callback)),
+                    Subscriber::Channel(_) => None,
+                })
+                .collect()
+        };
+        // Called without the lock so callbacks may (un)subscribe
+        for callback in callbacks {
+            callback(&event);
+        }
+    }
+}
+
+impl std::fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBus").field("subscribers", &self.subscriber_count()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callback_and_channel_delivery() {
+        let bus = EventBus::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let callback = bus.subscribe(Arc::new(move |e: &EditorEvent| {
+            sink.lock().expect("test assertion").push(e.clone());
+        }));
+        let (_, receiver) = bus.subscribe_channel();
+
+        bus.clone().emit(EditorEvent::TrackAdded { track_id: 1 });
+        assert_eq!(seen.lock().expect("test assertion").len(), 1);
+        assert_eq!(
+            receiver.try_recv().expect("test assertion"),
+            EditorEvent::TrackAdded { track_id: 1 }
+        );
+
+        assert!(bus.unsubscribe(callback));
+        drop(receiver);
+        bus.emit(EditorEvent::ProjectClosed);
+        assert_eq!(seen.lock().expect("test assertion").len(), 1);
+        assert_eq!(bus.subscriber_count(), 0);
+    }
+}
@@ -0,0 +1,151 @@
+//! Content-adaptive bitrate.
+//!
+//! Before encoding, [`ContentAnalysis`] samples frames across the export
+//! range and measures detail and motion with the first-pass complexity
+//! metric. Quiet talking heads then get a lower bitrate than sports
+//! footage at the same resolution. The recommendation is a CRF for
+//! constant-quality exports, or a bitrate capped by a file size budget.
+
+use super::formats::{AudioEncodingSettings, ExportSettings, RateControl, VideoCodec};
+
+/// Complexity the reference bitrate and CRF are tuned for: typical
+/// handheld footage.
+const REFERENCE_COMPLEXITY: f32 = 12.0;
+
+/// H.264 bits per pixel at the reference complexity.
+const REFERENCE_BPP: f64 = 0.1;
+
+/// CRF at the reference complexity.
+const REFERENCE_CRF: f32 = 23.0;
+
+/// Share of a size budget left for container overhead.
+const CONTAINER_OVERHEAD: f64 = 0.02;
+
+/// Pre-encode analysis options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveBitrate {
+    /// Frames sampled across the range.
+    pub sample_frames:  u32,
+    /// Largest output file in bytes (None = pick by quality alone).
+    pub max_size_bytes: Option<u64>,
+}
+
+impl Default for AdaptiveBitrate {
+    fn default() -> Self {
+        Self { sample_frames: 16, max_size_bytes: None }
+    }
+}
+
+impl AdaptiveBitrate {
+    /// Target quality, kept under a file size, e.g. `2 << 30` for 2 GiB.
+    #[must_use]
+    pub fn within_size(max_size_bytes: u64) -> Self {
+        Self { max_size_bytes: Some(max_size_bytes), ..Self::default() }
+    }
+}
+
+/// Measured complexity of an export range and the settings it calls for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentAnalysis {
+    /// Frames sampled.
+    pub samples:          usize,
+    /// Mean sampled complexity.
+    pub mean_complexity:  f32,
+    /// Highest sampled complexity.
+    pub peak_complexity:  f32,
+    /// Duration of the range in seconds.
+    pub duration_secs:    f64,
+    /// Video bitrate for the target quality, in kbps.
+    pub recommended_kbps: u32,
+    /// CRF for the target quality.
+    pub recommended_crf:  u8,
+    /// Largest video bitrate that fits the size budget, in kbps.
+    pub budget_kbps:      Option<u32>,
+}
+
+impl ContentAnalysis {
+    /// Builds the recommendation for sampled complexities.
+    #[must_use]
+    pub fn from_samples(
+        complexity: &[f32], duration_secs: f64, settings: &ExportSettings,
+        options: &AdaptiveBitrate,
+    ) -> Self {
+        let samples = complexity.len();
+        let mean_complexity = if samples == 0 {
+            REFERENCE_COMPLEXITY
+        } else {
+            complexity.iter().sum::<f32>() / samples as f32
+        };
+        let peak_complexity = complexity.iter().copied().fold(mean_complexity, f32::max);
+        let ratio = (mean_complexity / REFERENCE_COMPLEXITY).max(0.05);
+
+        let video = &settings.video;
+        let pixels_per_sec = video.resolution.pixel_count() as f64 * video.frame_rate.as_f64();
+        let bpp = REFERENCE_BPP * codec_efficiency(video.codec) * f64::from(ratio).powf(0.6);
+        let recommended_kbps = (bpp * pixels_per_sec / 1000.0).round().max(1.0) as u32;
+        // Every 6 CRF steps halve the bitrate
+        let crf = REFERENCE_CRF - 6.0 * 0.6 * ratio.log2();
+        let recommended_crf = crf.round().clamp(16.0, 35.0) as u8;
+
+        let budget_kbps = options.max_size_bytes.map(|bytes| {
+            let total_kbps =
+                bytes as f64 * 8.0 * (1.0 - CONTAINER_OVERHEAD) / duration_secs.max(0.001) / 1000.0;
+            (total_kbps - audio_kbps(&settings.audio)).max(0.0) as u32
+        });
+
+        Self {
+            samples,
+            mean_complexity,
+            peak_complexity,
+            duration_secs,
+            recommended_kbps,
+            recommended_crf,
+            budget_kbps,
+        }
+    }
+
+    /// Returns whether the target quality fits the size budget.
+    #[must_use]
+    pub fn fits_budget(&self) -> bool {
+        self.budget_kbps.is_none_or(|budget| self.recommended_kbps <= budget)
+    }
+
+    /// Applies the recommendation: constant quality at the recommended
+    /// CRF without a size budget, otherwise VBR at the recommended bitrate
+    /// or the budget, whichever is lower.
+    pub fn apply(&self, settings: &mut ExportSettings) {
+        let video = &mut settings.video;
+        match self.budget_kbps {
+            None => {
+                video.rate_control = RateControl::ConstantQuality;
+                video.quality = self.recommended_crf;
+            },
+            Some(budget) => {
+                if video.rate_control == RateControl::ConstantQuality {
+                    video.rate_control = RateControl::Vbr;
+                }
+                video.bitrate = self.recommended_kbps.min(budget);
+            },
+        }
+    }
+}
+
+/// Bitrate relative to H.264 for the same quality.
+fn codec_efficiency(codec: VideoCodec) -> f64 {
+    match codec {
+        VideoCodec::H265 => 0.6,
+        VideoCodec::Vp9 => 0.65,
+        VideoCodec::Av1 => 0.5,
+        VideoCodec::Vp8 => 1.2,
+        _ => 1.0,
+    }
+}
+
+/// Audio bitrate in kbps, with PCM computed from the sample format.
+fn audio_kbps(audio: &AudioEncodingSettings) -> f64 {
+    if audio.bitrate > 0 {
+        f64::from(audio.bitrate)
+    } else {
+        f64::from(audio.sample_rate) * f64::from(audio.channels) * 16.0 / 1000.0
+    }
+}
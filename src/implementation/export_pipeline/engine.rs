@@ -0,0 +1,568 @@
+//! Still frame and frame-sequence export.
+//!
+//! Rendered frames arrive display-encoded in the working color space.
+//! [`ExportEngine`] linearizes them, converts primaries to the output
+//! space and either re-encodes the transfer curve (PNG, JPEG) or keeps
+//! scene-linear values (EXR).
+
+use std::path::Path;
+
+use super::{
+    content_analysis::ContentAnalysis,
+    environment::ExportEnvironment,
+    formats::{ContainerFormat, ExportStatus, PixelAspectMode, ProjectionOutput},
+    job::ExportJob,
+    overlay::BurnInOverlay,
+    range::ExportRange,
+    two_pass::FirstPassStats,
+};
+use crate::{
+    converter::SequencePattern,
+    errors::{VideoEditorError, VideoEditorResult},
+    implementation::{VideoEditorPlugin, color_grading::ColorSpace},
+    stills::{self, StillFormat},
+    tasks::CancellationToken,
+    trace::{self, TracePhase},
+    types::{
+        BufferDescriptor, FrameBuffer, FrameBufferPool, FrameRate, PixelAspectRatio, Resolution,
+        TimePosition,
+    },
+};
+
+/// Source of rendered RGBA8 frames.
+pub trait FrameRenderer {
+    /// Output frame size.
+    fn resolution(&self) -> Resolution;
+
+    /// Output frame rate.
+    fn frame_rate(&self) -> FrameRate;
+
+    /// Shape of the rendered pixels.
+    fn pixel_aspect(&self) -> PixelAspectRatio {
+        PixelAspectRatio::SQUARE
+    }
+
+    /// Render the frame at a timeline time.
+    fn render_frame(&self, time: TimePosition) -> VideoEditorResult<Vec<u8>>;
+
+    /// Render the frame at a timeline time into a buffer from `pool`.
+    ///
+    /// The default wraps [`render_frame`](Self::render_frame)'s result
+    /// without copying; renderers that can draw into caller memory
+    /// override it so export reuses its frames.
+    fn render_frame_pooled(
+        &self, time: TimePosition, pool: &FrameBufferPool,
+    ) -> VideoEditorResult<FrameBuffer> {
+        let _ = pool;
+        self.render_frame(time).map(FrameBuffer::from)
+    }
+
+    /// Render the frame at a timeline time into a buffer from `pool`, with
+    /// 360° clips left equirectangular instead of reframed.
+    ///
+    /// The default renders as [`render_frame_pooled`](Self::render_frame_pooled),
+    /// for renderers with nothing to reframe.
+    fn render_frame_equirectangular(
+        &self, time: TimePosition, pool: &FrameBufferPool,
+    ) -> VideoEditorResult<FrameBuffer> {
+        self.render_frame_pooled(time, pool)
+    }
+
+    /// Names of the clips in the picture at a timeline time, for burn-in.
+    fn clip_names_at(&self, _time: TimePosition) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+impl FrameRenderer for VideoEditorPlugin {
+    fn resolution(&self) -> Resolution {
+        self.config().resolution
+    }
+
+    fn frame_rate(&self) -> FrameRate {
+        self.config().frame_rate
+    }
+
+    fn pixel_aspect(&self) -> PixelAspectRatio {
+        self.config().pixel_aspect
+    }
+
+    fn render_frame(&self, time: TimePosition) -> VideoEditorResult<Vec<u8>> {
+        self.render_frame_to_buffer(time)
+    }
+
+    fn render_frame_pooled(
+        &self, time: TimePosition, pool: &FrameBufferPool,
+    ) -> VideoEditorResult<FrameBuffer> {
+        VideoEditorPlugin::render_frame_pooled(self, time, pool)
+    }
+
+    fn render_frame_equirectangular(
+        &self, time: TimePosition, pool: &FrameBufferPool,
+    ) -> VideoEditorResult<FrameBuffer> {
+        VideoEditorPlugin::render_frame_equirectangular(self, time, pool)
+    }
+
+    fn clip_names_at(&self, time: TimePosition) -> Vec<String> {
+        let timeline = self.timeline();
+        timeline
+            .active_clips_at(time)
+            .into_iter()
+            .filter(|(track, _)| {
+                timeline.get_track(*track).is_some_and(|t| t.track_type.is_visual())
+            })
+            .map(|(_, clip)| clip.name.clone())
+            .collect()
+    }
+}
+
+type Mat3 = [[f32; 3]; 3];
+
+/// Renders stills and image sequences.
+pub struct ExportEngine<'a> {
+    renderer:      &'a dyn FrameRenderer,
+    working_space: ColorSpace,
+    output_space:  ColorSpace,
+    overlay:       Option<BurnInOverlay>,
+    pixel_aspect:  PixelAspectMode,
+    projection:    ProjectionOutput,
+    frame_pool:    FrameBufferPool,
+}
+
+impl<'a> ExportEngine<'a> {
+    /// Export from `renderer`, Rec. 709 in and sRGB out.
+    pub fn new(renderer: &'a dyn FrameRenderer) -> Self {
+        Self {
+            renderer,
+            working_space: ColorSpace::Rec709,
+            output_space: ColorSpace::Srgb,
+            overlay: None,
+            pixel_aspect: PixelAspectMode::Preserve,
+            projection: ProjectionOutput::Reframe,
+            frame_pool: FrameBufferPool::default(),
+        }
+    }
+
+    /// Render into buffers from a shared pool, e.g. the plugin's, instead
+    /// of one of the engine's own.
+    #[must_use]
+    pub fn with_frame_pool(mut self, pool: FrameBufferPool) -> Self {
+        self.frame_pool = pool;
+        self
+    }
+
+    /// Set the space frames are rendered in and the space stills are
+    /// written in.
+    #[must_use]
+    pub fn with_color_spaces(mut self, working: ColorSpace, output: ColorSpace) -> Self {
+        self.working_space = working;
+        self.output_space = output;
+        self
+    }
+
+    /// Burn an overlay into every exported frame. Sequence jobs with an
+    /// overlay in their settings use that one instead.
+    #[must_use]
+    pub fn with_overlay(mut self, overlay: BurnInOverlay) -> Self {
+        self.overlay = Some(overlay);
+        self
+    }
+
+    /// Resample exported stills to another pixel aspect. Sequence jobs use
+    /// the mode in their video settings instead.
+    #[must_use]
+    pub fn with_pixel_aspect(mut self, mode: PixelAspectMode) -> Self {
+        self.pixel_aspect = mode;
+        self
+    }
+
+    /// Keep 360° clips equirectangular in exported stills, or reframe
+    /// them. Sequence jobs use the mode in their video settings instead.
+    #[must_use]
+    pub fn with_projection(mut self, projection: ProjectionOutput) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Render the frame at `time` with an overlay burned in.
+    pub fn render_with_overlay(
+        &self, time: TimePosition, overlay: Option<&BurnInOverlay>,
+    ) -> VideoEditorResult<FrameBuffer> {
+        self.render_projected(time, overlay, self.projection)
+    }
+
+    fn render_projected(
+        &self, time: TimePosition, overlay: Option<&BurnInOverlay>, projection: ProjectionOutput,
+    ) -> VideoEditorResult<FrameBuffer> {
+        let mut pixels = match projection {
+            ProjectionOutput::Reframe => self.render(time)?,
+            ProjectionOutput::Equirectangular => {
+                self.renderer.render_frame_equirectangular(time, &self.frame_pool)?
+            },
+        };
+        if let Some(overlay) = overlay.filter(|o| !o.is_empty()) {
+            let Resolution { width, height } = self.renderer.resolution();
+            let names = if overlay.clip_names.is_some() {
+                self.renderer.clip_names_at(time)
+            } else {
+                Vec::new()
+            };
+            overlay.apply(&mut pixels, width, height, time, &self.renderer.frame_rate(), &names);
+        }
+        Ok(pixels)
+    }
+
+    /// Render the frame at `time` and encode it as a still.
+    pub fn export_frame(
+        &self, time: TimePosition, format: StillFormat,
+    ) -> VideoEditorResult<Vec<u8>> {
+        self.encode_still(time, format, self.overlay.as_ref(), self.pixel_aspect, self.projection)
+    }
+
+    fn encode_still(
+        &self, time: TimePosition, format: StillFormat, overlay: Option<&BurnInOverlay>,
+        aspect: PixelAspectMode, projection: ProjectionOutput,
+    ) -> VideoEditorResult<Vec<u8>> {
+        let source = self.renderer.resolution();
+        let mut pixels = self.render_projected(time, overlay, projection)?;
+        let Resolution { width, height } =
+            aspect.output_resolution(source, self.renderer.pixel_aspect());
+        if width != source.width {
+            pixels = self.resample_width(&pixels, source, width);
+        }
+        let _span = trace::span(TracePhase::Encode, format.extension());
+
+        match format {
+            StillFormat::Exr => stills::encode_exr(width, height, &self.to_output_linear(&pixels)?),
+            StillFormat::Png => {
+                self.encode_display(&mut pixels)?;
+                stills::encode_png(width, height, &pixels)
+            },
+            StillFormat::Jpeg { quality } => {
+                self.encode_display(&mut pixels)?;
+                stills::encode_jpeg(width, height, &pixels, quality)
+            },
+        }
+    }
+
+    /// `pixels` at `source` stretched or squeezed to `width`, filtering
+    /// horizontally. Lines are kept as they are.
+    fn resample_width(&self, pixels: &[u8], source: Resolution, width: u32) -> FrameBuffer {
+        let (sw, w) = (source.width as usize, width as usize);
+        let mut out = self.frame_pool.acquire(BufferDescriptor::rgba8(width, source.height));
+        let scale = sw as f32 / w as f32;
+        for (src, dst) in pixels.chunks_exact(sw * 4).zip(out.chunks_exact_mut(w * 4)) {
+            for (x, pixel) in dst.chunks_exact_mut(4).enumerate() {
+                // Sample under the center of the output pixel
+                let at = ((x as f32 + 0.5) * scale - 0.5).clamp(0.0, (sw - 1) as f32);
+                let (left, t) = (at as usize, at.fract());
+                let right = (left + 1).min(sw - 1);
+                for (c, value) in pixel.iter_mut().enumerate() {
+                    let (a, b) = (f32::from(src[left * 4 + c]), f32::from(src[right * 4 + c]));
+                    *value = (a + (b - a) * t).round() as u8;
+                }
+            }
+        }
+        out
+    }
+
+    /// Render the frame at `time` into a buffer from the engine's pool.
+    fn render(&self, time: TimePosition) -> VideoEditorResult<FrameBuffer> {
+        self.renderer.render_frame_pooled(time, &self.frame_pool)
+    }
+
+    /// Render `time` as a still and write it to `path`.
+    pub fn save_frame(
+        &self, time: TimePosition, format: StillFormat, path: &Path,
+    ) -> VideoEditorResult<()> {
+        let bytes = self.export_frame(time, format)?;
+        std::fs::write(path, bytes).map_err(|e| VideoEditorError::io(path, e))
+    }
+
+    /// Run a frame-sequence job: every frame of the job's range is written
+    /// to the numbered path of its `output_path` pattern (`out_%05d.png`),
+    /// numbered by timeline frame.
+    ///
+    /// Returns the written paths. The job is completed on success and
+    /// failed on the first error.
+    pub fn export_sequence(&self, job: &mut ExportJob) -> VideoEditorResult<Vec<String>> {
+        self.export_sequence_until(job, &CancellationToken::new())
+    }
+
+    /// Run a queued job to the end: pick its bitrate from a content
+    /// analysis if it has adaptive options, then write its frames.
+    ///
+    /// `token` is checked before every rendered frame, analysis samples
+    /// included; a cancelled job is marked cancelled rather than failed.
+    pub fn run(
+        &self, job: &mut ExportJob, token: &CancellationToken,
+    ) -> VideoEditorResult<Vec<String>> {
+        if job.settings().adaptive.is_some()
+            && job.progress().status == ExportStatus::Queued
+            && let Err(err) = self.analyze_content_until(job, token)
+        {
+            if err.is_cancelled() {
+                job.cancel();
+            } else {
+                job.fail(err.to_string());
+            }
+            return Err(err);
+        }
+        self.export_sequence_until(job, token)
+    }
+
+    fn export_sequence_until(
+        &self, job: &mut ExportJob, token: &CancellationToken,
+    ) -> VideoEditorResult<Vec<String>> {
+        let result = self.write_sequence(job, token);
+        match &result {
+            Ok(_) => {
+                let environment = ExportEnvironment::capture(job.settings(), &[]);
+                job.complete(environment);
+            },
+            Err(err) if err.is_cancelled() => job.cancel(),
+            Err(err) => {
+                let id = job.id().inner();
+                trace::event(TracePhase::Encode, || format!("Export {id} failed: {err}"));
+                job.fail(err.to_string());
+            },
+        }
+        result
+    }
+
+    fn write_sequence(
+        &self, job: &mut ExportJob, token: &CancellationToken,
+    ) -> VideoEditorResult<Vec<String>> {
+        let settings = job.settings().clone();
+        let ContainerFormat::ImageSequence(format) = settings.container else {
+            return Err(VideoEditorError::Export(format!(
+                "{:?} is not an image sequence target",
+                settings.container
+            )));
+        };
+        settings.check_field_order()?;
+        settings.check_projection()?;
+        let pattern = SequencePattern::parse(&settings.output_path)
+            .filter(|p| p.suffix.eq_ignore_ascii_case(&format!(".{}", format.extension())))
+            .ok_or_else(|| {
+                VideoEditorError::Export(format!(
+                    "Output path {} is not a .{} sequence pattern",
+                    settings.output_path,
+                    format.extension()
+                ))
+            })?;
+
+        let rate = self.renderer.frame_rate();
+        let (first, count) = self.frame_span(job)?;
+        if job.progress().status == ExportStatus::Queued {
+            job.start();
+        }
+        let directory = pattern.directory();
+        if !directory.is_empty() {
+            std::fs::create_dir_all(directory).map_err(|e| VideoEditorError::io(directory, e))?;
+        }
+
+        let overlay = settings.overlay.as_ref().or(self.overlay.as_ref());
+        let video = &settings.video;
+        let started = std::time::Instant::now();
+        let mut written = Vec::with_capacity(count as usize);
+        for frame in first..first + count {
+            token.checkpoint("export")?;
+            let path = pattern.frame_path(frame);
+            let time = TimePosition::from_frame(frame, &rate);
+            let bytes =
+                self.encode_still(time, format, overlay, video.pixel_aspect, video.projection)?;
+            let span = trace::span(TracePhase::Mux, &path);
+            std::fs::write(&path, bytes).map_err(|e| VideoEditorError::io(&path, e))?;
+            drop(span);
+            written.push(path);
+            job.progress_mut().update(written.len() as u64, started.elapsed().as_secs_f64());
+        }
+        Ok(written)
+    }
+
+    /// Sample the job's range for detail and motion and pick its video
+    /// bitrate or CRF from the job's [`AdaptiveBitrate`] options, before
+    /// encoding starts. The analysis is kept in the job.
+    ///
+    /// [`AdaptiveBitrate`]: super::content_analysis::AdaptiveBitrate
+    pub fn analyze_content(&self, job: &mut ExportJob) -> VideoEditorResult<ContentAnalysis> {
+        self.analyze_content_until(job, &CancellationToken::new())
+    }
+
+    fn analyze_content_until(
+        &self, job: &mut ExportJob, token: &CancellationToken,
+    ) -> VideoEditorResult<ContentAnalysis> {
+        let options = job.settings().adaptive.ok_or_else(|| {
+            VideoEditorError::Export("Job has no adaptive bitrate options".into())
+        })?;
+        if job.progress().status != ExportStatus::Queued {
+            return Err(VideoEditorError::Export(
+                "Content analysis must run before encoding".into(),
+            ));
+        }
+        let (first, count) = self.frame_span(job)?;
+        let rate = self.renderer.frame_rate();
+        let width = self.renderer.resolution().width;
+        let samples = u64::from(options.sample_frames.max(1)).min(count);
+
+        // Each sample renders a frame and the one before it for motion
+        let mut complexity = Vec::with_capacity(samples as usize);
+        for i in 0..samples {
+            token.checkpoint("content analysis")?;
+            let frame = first + (2 * i + 1) * count / (2 * samples);
+            let render = |frame| self.render(TimePosition::from_frame(frame, &rate));
+            let pixels = render(frame)?;
+            let previous = if frame > first { Some(render(frame - 1)?) } else { None };
+            complexity.push(FirstPassStats::analyze_frame(&pixels, previous.as_deref(), width));
+        }
+
+        let duration_secs = count as f64 / rate.as_f64();
+        let analysis =
+            ContentAnalysis::from_samples(&complexity, duration_secs, job.settings(), &options);
+        analysis.apply(&mut job.settings);
+        job.analysis = Some(analysis.clone());
+        Ok(analysis)
+    }
+
+    /// Run the first pass of a two-pass job: render every frame of the
+    /// range, measure its complexity and write the stats file next to the
+    /// output (`out.mp4.2pass.log`). The job then moves on to its second
+    /// pass with the bitrate split over the frames.
+    ///
+    /// The job is failed on error.
+    pub fn run_first_pass(&self, job: &mut ExportJob) -> VideoEditorResult<FirstPassStats> {
+        let result = self.analyze_first_pass(job);
+        match &result {
+            Ok(stats) => job.finish_first_pass(stats.clone()),
+            Err(err) => job.fail(err.to_string()),
+        }
+        result
+    }
+
+    fn analyze_first_pass(&self, job: &mut ExportJob) -> VideoEditorResult<FirstPassStats> {
+        if !job.settings().is_two_pass() {
+            return Err(VideoEditorError::Export("Job is not a two-pass export".into()));
+        }
+        let (first, count) = self.frame_span(job)?;
+        if job.progress().status == ExportStatus::Queued {
+            job.start();
+        }
+        job.progress_mut().status = ExportStatus::FirstPass;
+
+        let rate = self.renderer.frame_rate();
+        let width = self.renderer.resolution().width;
+        let started = std::time::Instant::now();
+        let mut stats = FirstPassStats::default();
+        let mut previous: Option<FrameBuffer> = None;
+        for frame in first..first + count {
+            let pixels = self.render(TimePosition::from_frame(frame, &rate))?;
+            stats.complexity.push(FirstPassStats::analyze_frame(
+                &pixels,
+                previous.as_deref(),
+                width,
+            ));
+            previous = Some(pixels);
+            job.progress_mut()
+                .update(stats.complexity.len() as u64, started.elapsed().as_secs_f64());
+        }
+
+        let output = &job.settings().output_path;
+        if !output.is_empty() {
+            stats.write(Path::new(&FirstPassStats::stats_path(output)))?;
+        }
+        Ok(stats)
+    }
+
+    /// First frame and frame count of the job's range.
+    fn frame_span(&self, job: &ExportJob) -> VideoEditorResult<(u64, u64)> {
+        let rate = self.renderer.frame_rate();
+        match job.settings().range {
+            ExportRange::Span(start, end) => {
+                let first = start.to_frame(&rate);
+                Ok((first, end.to_frame(&rate).saturating_sub(first)))
+            },
+            ExportRange::Entire => Ok((0, job.progress().total_frames)),
+            ref range => Err(VideoEditorError::Export(format!(
+                "Export range {range:?} must be resolved before rendering"
+            ))),
+        }
+    }
+
+    /// Working primaries to output primaries, both linear.
+    fn output_matrix(&self) -> VideoEditorResult<Mat3> {
+        let to_709 = invert(&from_rec709(self.working_space)?);
+        let from_709 = from_rec709(self.output_space)?;
+        Ok(multiply(&from_709, &to_709))
+    }
+
+    /// Re-encode RGBA8 pixels from the working space to the output space's
+    /// primaries and transfer curve in place, for 8-bit stills.
+    fn encode_display(&self, pixels: &mut [u8]) -> VideoEditorResult<()> {
+        let matrix = self.output_matrix()?;
+        let gamma = self.working_space.gamma();
+        let encode_gamma = self.output_space.gamma().recip();
+        for p in pixels.chunks_exact_mut(4) {
+            let rgb = [0, 1, 2].map(|i| (f32::from(p[i]) / 255.0).powf(gamma));
+            let out = matrix.map(|row| row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]);
+            for (d, v) in p.iter_mut().zip(out) {
+                *d = (v.clamp(0.0, 1.0).powf(encode_gamma) * 255.0).round() as u8;
+            }
+        }
+        Ok(())
+    }
+
+    /// Linear RGBA in the output primaries.
+    fn to_output_linear(&self, pixels: &[u8]) -> VideoEditorResult<Vec<f32>> {
+        let matrix = self.output_matrix()?;
+        let gamma = self.working_space.gamma();
+
+        Ok(pixels
+            .chunks_exact(4)
+            .flat_map(|p| {
+                let rgb = [0, 1, 2].map(|i| (f32::from(p[i]) / 255.0).powf(gamma));
+                let [r, g, b] =
+                    matrix.map(|row| row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]);
+                [r, g, b, f32::from(p[3]) / 255.0]
+            })
+            .collect())
+    }
+}
+
+/// Linear Rec. 709 to linear `space` primaries.
+fn from_rec709(space: ColorSpace) -> VideoEditorResult<Mat3> {
+    match space {
+        ColorSpace::Srgb | ColorSpace::Rec709 => {
+            Ok([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+        },
+        ColorSpace::Rec2020 => {
+            Ok([[0.6274, 0.3293, 0.0433], [0.0691, 0.9195, 0.0114], [0.0164, 0.0880, 0.8956]])
+        },
+        ColorSpace::DciP3 => {
+            Ok([[0.8225, 0.1774, 0.0000], [0.0332, 0.9669, 0.0000], [0.0171, 0.0724, 0.9108]])
+        },
+        ColorSpace::AcesCg => {
+            Ok([[0.6131, 0.3395, 0.0474], [0.0702, 0.9164, 0.0134], [0.0206, 0.1096, 0.8698]])
+        },
+        ColorSpace::Aces2065 => {
+            Ok([[0.4397, 0.3830, 0.1773], [0.0898, 0.8134, 0.0968], [0.0175, 0.1116, 0.8709]])
+        },
+        log => Err(VideoEditorError::unsupported_format(format!(
+            "{log:?} stills need a conversion LUT"
+        ))),
+    }
+}
+
+fn multiply(a: &Mat3, b: &Mat3) -> Mat3 {
+    std::array::from_fn(|r| std::array::from_fn(|c| (0..3).map(|k| a[r][k] * b[k][c]).sum()))
+}
+
+fn invert(m: &Mat3) -> Mat3 {
+    let cofactor = |r: usize, c: usize| {
+        let (r1, r2) = ((r + 1) % 3, (r + 2) % 3);
+        let (c1, c2) = ((c + 1) % 3, (c + 2) % 3);
+        m[r1][c1] * m[r2][c2] - m[r1][c2] * m[r2][c1]
+    };
+    let det: f32 = (0..3).map(|c| m[0][c] * cofactor(0, c)).sum();
+    std::array::from_fn(|r| std::array::from_fn(|c| cofactor(c, r) / det))
+}
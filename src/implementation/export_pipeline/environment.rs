@@ -0,0 +1,197 @@
+//! Export environment snapshots for reproducible re-renders.
+
+use super::{
+    formats::{ExportSettings, HardwareAccel},
+    hardware::EncoderCapabilities,
+};
+use crate::{checksum, types::Timestamp};
+
+/// Version of an encoder used by an export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncoderVersion {
+    /// Encoder name (e.g. "x264", "libopus").
+    pub name:    String,
+    /// Encoder version string.
+    pub version: String,
+}
+
+/// GPU and driver an export rendered on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuEnvironment {
+    /// Device name.
+    pub device: String,
+    /// Driver version.
+    pub driver: String,
+}
+
+/// A requested setting replaced because it was unavailable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingFallback {
+    /// Setting that fell back (e.g. "video.hw_accel").
+    pub setting:   String,
+    /// Requested value.
+    pub requested: String,
+    /// Value actually used.
+    pub effective: String,
+    /// Why the fallback happened.
+    pub reason:    String,
+}
+
+/// Everything needed to re-render an export identically.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportEnvironment {
+    /// Host application version.
+    pub app_version:        String,
+    /// Version of this crate.
+    pub crate_version:      String,
+    /// Operating system and architecture (e.g. "linux-x86_64").
+    pub platform:           String,
+    /// Encoders used, with versions.
+    pub encoders:           Vec<EncoderVersion>,
+    /// GPU the export rendered on (None = CPU only).
+    pub gpu:                Option<GpuEnvironment>,
+    /// Settings after fallbacks were applied.
+    pub effective_settings: ExportSettings,
+    /// Requested settings that were replaced.
+    pub fallbacks:          Vec<SettingFallback>,
+    /// SHA-256 of the serialized project state, as hex.
+    pub project_hash:       String,
+    /// When the snapshot was taken.
+    pub recorded_at:        Timestamp,
+}
+
+impl ExportEnvironment {
+    /// Captures the environment for exporting `project_snapshot` with
+    /// `settings`. Fallbacks are applied afterwards with the `resolve_*`
+    /// methods.
+    #[must_use]
+    pub fn capture(settings: &ExportSettings, project_snapshot: &[u8]) -> Self {
+        Self {
+            app_version:        String::new(),
+            crate_version:      env!("CARGO_PKG_VERSION").to_string(),
+            platform:           format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+            encoders:           Vec::new(),
+            gpu:                None,
+            effective_settings: settings.clone(),
+            fallbacks:          Vec::new(),
+            project_hash:       checksum::to_hex(&checksum::sha256(project_snapshot)),
+            recorded_at:        Timestamp::now(),
+        }
+    }
+
+    /// Sets the host application version.
+    #[must_use]
+    pub fn with_app_version(mut self, version: impl Into<String>) -> Self {
+        self.app_version = version.into();
+        self
+    }
+
+    /// Adds an encoder and its version.
+    #[must_use]
+    pub fn with_encoder(mut self, name: impl Into<String>, version: impl Into<String>) -> Self {
+        self.encoders.push(EncoderVersion { name: name.into(), version: version.into() });
+        self
+    }
+
+    /// Sets the GPU and driver.
+    #[must_use]
+    pub fn with_gpu(mut self, device: impl Into<String>, driver: impl Into<String>) -> Self {
+        self.gpu = Some(GpuEnvironment { device: device.into(), driver: driver.into() });
+        self
+    }
+
+    /// Records a requested setting that was replaced.
+    pub fn record_fallback(
+        &mut self, setting: impl Into<String>, requested: impl Into<String>,
+        effective: impl Into<String>, reason: impl Into<String>,
+    ) {
+        self.fallbacks.push(SettingFallback {
+            setting:   setting.into(),
+            requested: requested.into(),
+            effective: effective.into(),
+            reason:    reason.into(),
+        });
+    }
+
+    /// Falls back to software encoding when the requested hardware encoder
+    /// isn't available. `Auto` picks the first available encoder.
+    pub fn resolve_hw_accel(&mut self, available: &[HardwareAccel]) {
+        let requested = self.effective_settings.video.hw_accel;
+        let effective = match requested {
+            HardwareAccel::Auto => available.first().copied().unwrap_or_default(),
+            accel if available.contains(&accel) => accel,
+            _ => HardwareAccel::None,
+        };
+        self.apply_hw_accel(requested, effective, "hardware encoder not available");
+    }
+
+    /// Picks the encoder for the export from the detected capabilities,
+    /// falling back to software when the requested encoder is missing or
+    /// can't take the codec, resolution or pixel format.
+    pub fn resolve_hw_encoder(&mut self, capabilities: &EncoderCapabilities) {
+        let requested = self.effective_settings.video.hw_accel;
+        let (effective, reason) = capabilities.resolve(&self.effective_settings.video);
+        self.apply_hw_accel(requested, effective, &reason.unwrap_or_default());
+    }
+
+    fn apply_hw_accel(&mut self, requested: HardwareAccel, effective: HardwareAccel, reason: &str) {
+        self.effective_settings.video.hw_accel = effective;
+        if requested != HardwareAccel::None && effective == HardwareAccel::None {
+            self.record_fallback(
+                "video.hw_accel",
+                format!("{requested:?}"),
+                format!("{effective:?}"),
+                reason,
+            );
+        }
+    }
+
+    /// Lists what differs between two environments. An empty list means a
+    /// re-render in `other` should reproduce this export.
+    #[must_use]
+    pub fn differences(&self, other: &Self) -> Vec<String> {
+        let mut differences = Vec::new();
+        let mut compare = |what: &str, a: String, b: String| {
+            if a != b {
+                differences.push(format!("{what}: {a} -> {b}"));
+            }
+        };
+        compare("app version", self.app_version.clone(), other.app_version.clone());
+        compare("crate version", self.crate_version.clone(), other.crate_version.clone());
+        compare("platform", self.platform.clone(), other.platform.clone());
+        compare("project", self.project_hash.clone(), other.project_hash.clone());
+
+        let gpu = |env: &Self| {
+            env.gpu
+                .as_ref()
+                .map_or_else(|| "none".into(), |g| format!("{} ({})", g.device, g.driver))
+        };
+        compare("gpu", gpu(self), gpu(other));
+
+        for encoder in &self.encoders {
+            let theirs = other.encoders.iter().find(|e| e.name == encoder.name);
+            let version = theirs.map_or_else(|| "missing".into(), |e| e.version.clone());
+            compare(&format!("encoder {}", encoder.name), encoder.version.clone(), version);
+        }
+        for encoder in &other.encoders {
+            if !self.encoders.iter().any(|e| e.name == encoder.name) {
+                compare(
+                    &format!("encoder {}", encoder.name),
+                    "missing".into(),
+                    encoder.version.clone(),
+                );
+            }
+        }
+
+        if self.effective_settings != other.effective_settings {
+            differences.push("effective settings differ".into());
+        }
+        differences
+    }
+
+    /// Returns whether a re-render in `other` should reproduce this export.
+    #[must_use]
+    pub fn is_reproducible_in(&self, other: &Self) -> bool {
+        self.differences(other).is_empty()
+    }
+}
@@ -1,6 +1,16 @@
 //! Video/audio format types, codecs, and encoding settings.
 
-use crate::types::{FrameRate, Resolution};
+use super::{
+    content_analysis::AdaptiveBitrate, naming::CollisionPolicy, overlay::BurnInOverlay,
+    packaging::StreamPackaging, range::ExportRange, seamless_loop::SeamlessLoop,
+    stems::StemExportOptions,
+};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    implementation::captions::CaptionExportOptions,
+    stills::StillFormat,
+    types::{FieldOrder, FrameRate, PixelAspectRatio, Resolution},
+};
 
 /// Unique identifier for an export job.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -38,6 +48,16 @@ pub enum ContainerFormat {
     MpegTs,
     /// Raw video (no container).
     Raw,
+    /// WAV (audio only, PCM).
+    Wav,
+    /// FLAC (audio only, lossless).
+    Flac,
+    /// MP3 (audio only).
+    Mp3,
+    /// M4A (audio only, AAC in MPEG-4).
+    M4a,
+    /// Numbered still images, one file per frame (video only).
+    ImageSequence(StillFormat),
 }
 
 impl ContainerFormat {
@@ -52,6 +72,11 @@ impl ContainerFormat {
             Self::Avi => "avi",
             Self::MpegTs => "ts",
             Self::Raw => "raw",
+            Self::Wav => "wav",
+            Self::Flac => "flac",
+            Self::Mp3 => "mp3",
+            Self::M4a => "m4a",
+            Self::ImageSequence(format) => format.extension(),
         }
     }
 
@@ -66,6 +91,37 @@ impl ContainerFormat {
             Self::Avi => "video/x-msvideo",
             Self::MpegTs => "video/mp2t",
             Self::Raw => "video/raw",
+            Self::Wav => "audio/wav",
+            Self::Flac => "audio/flac",
+            Self::Mp3 => "audio/mpeg",
+            Self::M4a => "audio/mp4",
+            Self::ImageSequence(StillFormat::Png) => "image/png",
+            Self::ImageSequence(StillFormat::Jpeg { .. }) => "image/jpeg",
+            Self::ImageSequence(StillFormat::Exr) => "image/x-exr",
+        }
+    }
+
+    /// Returns whether frames are written as individual images.
+    #[must_use]
+    pub const fn is_image_sequence(&self) -> bool {
+        matches!(self, Self::ImageSequence(_))
+    }
+
+    /// Returns whether this container only carries audio.
+    #[must_use]
+    pub const fn is_audio_only(&self) -> bool {
+        matches!(self, Self::Wav | Self::Flac | Self::Mp3 | Self::M4a)
+    }
+
+    /// Returns the audio codec an audio-only container is encoded with.
+    #[must_use]
+    pub const fn audio_only_codec(&self) -> Option<AudioCodec> {
+        match self {
+            Self::Wav => Some(AudioCodec::Pcm),
+            Self::Flac => Some(AudioCodec::Flac),
+            Self::Mp3 => Some(AudioCodec::Mp3),
+            Self::M4a => Some(AudioCodec::Aac),
+            _ => None,
         }
     }
 }
@@ -92,6 +148,14 @@ pub enum VideoCodec {
     Uncompressed,
 }
 
+impl VideoCodec {
+    /// Returns whether the codec can carry interlaced pictures.
+    #[must_use]
+    pub const fn supports_interlaced(&self) -> bool {
+        !matches!(self, Self::Vp8 | Self::Vp9 | Self::Av1)
+    }
+}
+
 /// ProRes profile variants.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum ProResProfile {
@@ -180,6 +244,41 @@ pub enum HardwareAccel {
     VideoToolbox,
     /// Vulkan Video.
     VulkanVideo,
+    /// Best available hardware encoder, or software if none fits.
+    Auto,
+}
+
+impl HardwareAccel {
+    /// Every mode, in declaration order.
+    pub const ALL: [Self; 7] = [
+        Self::None,
+        Self::Nvenc,
+        Self::QuickSync,
+        Self::AmdVce,
+        Self::VideoToolbox,
+        Self::VulkanVideo,
+        Self::Auto,
+    ];
+
+    /// Returns the settings label, e.g. `"nvenc"`.
+    #[must_use]
+    pub const fn label(&self) -> &'static str {
+        match self {
+            Self::None => "software",
+            Self::Nvenc => "nvenc",
+            Self::QuickSync => "quicksync",
+            Self::AmdVce => "vce",
+            Self::VideoToolbox => "videotoolbox",
+            Self::VulkanVideo => "vulkan",
+            Self::Auto => "auto",
+        }
+    }
+
+    /// Parses a settings label.
+    #[must_use]
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.label() == label)
+    }
 }
 
 /// Encoding preset (speed vs quality trade-off).
@@ -230,8 +329,55 @@ pub enum PixelFormat {
     Rgba,
 }
 
+/// How the pixel aspect of rendered frames carries into the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PixelAspectMode {
+    /// Keep the rendered pixel grid and tag the output with its aspect.
+    #[default]
+    Preserve,
+    /// Resample to square pixels at the same display aspect, e.g. to
+    /// desqueeze anamorphic footage for the web.
+    Square,
+    /// Resample to the given pixel aspect at the same display aspect,
+    /// e.g. to squeeze a square-pixel master onto an HDV raster.
+    Convert(PixelAspectRatio),
+}
+
+impl PixelAspectMode {
+    /// Returns the pixel aspect of the output for frames rendered with
+    /// `source` pixels.
+    #[must_use]
+    pub const fn output(&self, source: PixelAspectRatio) -> PixelAspectRatio {
+        match self {
+            Self::Preserve => source,
+            Self::Square => PixelAspectRatio::SQUARE,
+            Self::Convert(target) => *target,
+        }
+    }
+
+    /// Returns the output frame size for frames rendered at `source` with
+    /// `pixel_aspect` pixels. Only the width changes.
+    #[must_use]
+    pub fn output_resolution(
+        &self, source: Resolution, pixel_aspect: PixelAspectRatio,
+    ) -> Resolution {
+        source.with_pixel_aspect(pixel_aspect, self.output(pixel_aspect))
+    }
+}
+
+/// How 360° clips carry into the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ProjectionOutput {
+    /// Bake each clip's keyframed flat view, as in preview.
+    #[default]
+    Reframe,
+    /// Keep the equirectangular frames and tag the output as spherical
+    /// so players let viewers look around.
+    Equirectangular,
+}
+
 /// Video encoding settings.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct VideoEncodingSettings {
     /// Video codec.
     pub codec:        VideoCodec,
@@ -255,6 +401,12 @@ pub struct VideoEncodingSettings {
     pub preset:       EncodingPreset,
     /// Pixel format.
     pub pixel_format: PixelFormat,
+    /// Scan of the encoded pictures.
+    pub field_order:  FieldOrder,
+    /// Pixel aspect conversion.
+    pub pixel_aspect: PixelAspectMode,
+    /// Reframe or keep 360° clips.
+    pub projection:   ProjectionOutput,
 }
 
 impl Default for VideoEncodingSettings {
@@ -271,12 +423,49 @@ impl Default for VideoEncodingSettings {
             gop_size:     250,
             preset:       EncodingPreset::default(),
             pixel_format: PixelFormat::default(),
+            field_order:  FieldOrder::Progressive,
+            pixel_aspect: PixelAspectMode::Preserve,
+            projection:   ProjectionOutput::Reframe,
         }
     }
 }
 
+impl VideoEncodingSettings {
+    /// Spherical Video V1 metadata the muxer embeds for equirectangular
+    /// output, or `None` when 360° clips are reframed.
+    #[must_use]
+    pub fn spherical_metadata(&self) -> Option<String> {
+        if self.projection != ProjectionOutput::Equirectangular {
+            return None;
+        }
+        let Resolution { width, height } = self.resolution;
+        let tags = [
+            ("Spherical", "true".to_string()),
+            ("Stitched", "true".to_string()),
+            ("StitchingSoftware", "Essentia Video Editor".to_string()),
+            ("ProjectionType", "equirectangular".to_string()),
+            ("FullPanoWidthPixels", width.to_string()),
+            ("FullPanoHeightPixels", height.to_string()),
+            ("CroppedAreaImageWidthPixels", width.to_string()),
+            ("CroppedAreaImageHeightPixels", height.to_string()),
+            ("CroppedAreaLeftPixels", "0".to_string()),
+            ("CroppedAreaTopPixels", "0".to_string()),
+        ];
+        let mut xml = String::from(
+            "<?xml version=\"1.0\"?><rdf:SphericalVideo \
+             xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" \
+             xmlns:GSpherical=\"http://ns.google.com/videos/1.0/spherical/\">",
+        );
+        for (tag, value) in tags {
+            xml.push_str(&format!("<GSpherical:{tag}>{value}</GSpherical:{tag}>"));
+        }
+        xml.push_str("</rdf:SphericalVideo>");
+        Some(xml)
+    }
+}
+
 /// Audio encoding settings.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AudioEncodingSettings {
     /// Audio codec.
     pub codec:       AudioCodec,
@@ -299,27 +488,155 @@ impl Default for AudioEncodingSettings {
     }
 }
 
+/// Loudness normalization target applied to the audio mixdown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoudnessTarget {
+    /// Integrated loudness target in LUFS.
+    pub integrated_lufs: f32,
+    /// Maximum true peak in dBTP.
+    pub true_peak_dbtp:  f32,
+}
+
+impl LoudnessTarget {
+    /// EBU R128 broadcast loudness (-23 LUFS, -1 dBTP).
+    pub const EBU_R128: Self = Self { integrated_lufs: -23.0, true_peak_dbtp: -1.0 };
+    /// Streaming platform loudness (-14 LUFS, -1 dBTP).
+    pub const STREAMING: Self = Self { integrated_lufs: -14.0, true_peak_dbtp: -1.0 };
+
+    /// Creates a custom loudness target.
+    #[must_use]
+    pub const fn new(integrated_lufs: f32, true_peak_dbtp: f32) -> Self {
+        Self { integrated_lufs, true_peak_dbtp }
+    }
+
+    /// Returns the gain in dB needed to reach the target from a measured
+    /// integrated loudness, limited so the measured true peak stays under
+    /// the ceiling.
+    #[must_use]
+    pub fn gain_db(&self, measured_lufs: f32, measured_true_peak_dbtp: f32) -> f32 {
+        let gain = self.integrated_lufs - measured_lufs;
+        gain.min(self.true_peak_dbtp - measured_true_peak_dbtp)
+    }
+}
+
 /// Export settings combining all encoding options.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct ExportSettings {
     /// Container format.
-    pub container:   ContainerFormat,
+    pub container:     ContainerFormat,
     /// Video settings.
-    pub video:       VideoEncodingSettings,
+    pub video:         VideoEncodingSettings,
     /// Audio settings.
-    pub audio:       AudioEncodingSettings,
-    /// Output file path.
-    pub output_path: String,
-    /// Range to export (None = entire timeline).
-    pub range:       Option<(crate::types::TimePosition, crate::types::TimePosition)>,
+    pub audio:         AudioEncodingSettings,
+    /// Output file path; may contain name template tokens.
+    pub output_path:   String,
+    /// What to do when the output path is taken.
+    pub collision:     CollisionPolicy,
+    /// Range to export.
+    pub range:         ExportRange,
     /// Enable multi-pass encoding.
-    pub multi_pass:  bool,
+    pub multi_pass:    bool,
+    /// Pick bitrate or CRF from the content before encoding (None = use
+    /// the video settings as given).
+    pub adaptive:      Option<AdaptiveBitrate>,
     /// Metadata to embed.
-    pub metadata:    ExportMetadata,
+    pub metadata:      ExportMetadata,
+    /// Loudness normalization (None = leave mix level untouched).
+    pub loudness:      Option<LoudnessTarget>,
+    /// Seamless loop mode (None = export the range as is).
+    pub seamless_loop: Option<SeamlessLoop>,
+    /// Caption burn-in and sidecar files.
+    pub captions:      CaptionExportOptions,
+    /// Timecode, clip name, watermark and LUT burn-in (None = clean
+    /// picture).
+    pub overlay:       Option<BurnInOverlay>,
+    /// Audio stem files (None = main mix only).
+    pub stems:         Option<StemExportOptions>,
+    /// HLS/DASH packaging of the output (None = a single file).
+    pub packaging:     Option<StreamPackaging>,
+}
+
+impl ExportSettings {
+    /// Returns whether the video is encoded in two passes.
+    #[must_use]
+    pub fn is_two_pass(&self) -> bool {
+        self.renders_video() && (self.multi_pass || self.video.rate_control == RateControl::TwoPass)
+    }
+
+    /// Returns whether video has to be rendered for this export.
+    #[must_use]
+    pub const fn renders_video(&self) -> bool {
+        !self.container.is_audio_only()
+    }
+
+    /// Returns whether the main mix is written, i.e. the export isn't
+    /// stems only.
+    #[must_use]
+    pub fn writes_main_mix(&self) -> bool {
+        self.stems.as_ref().is_none_or(|s| !s.stems_only)
+    }
+
+    /// Checks the output scan can be written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Export` for interlaced output to an
+    /// image sequence, with a codec that only codes progressive pictures,
+    /// or at an odd height that doesn't split into two fields.
+    pub fn check_field_order(&self) -> VideoEditorResult<()> {
+        let video = &self.video;
+        if !video.field_order.is_interlaced() || !self.renders_video() {
+            return Ok(());
+        }
+        if self.container.is_image_sequence() {
+            return Err(VideoEditorError::Export(
+                "Image sequences are progressive; export interlaced video to a movie file".into(),
+            ));
+        }
+        if !video.codec.supports_interlaced() {
+            return Err(VideoEditorError::Export(format!(
+                "{:?} can't encode interlaced video",
+                video.codec
+            )));
+        }
+        if !video.resolution.height.is_multiple_of(2) {
+            return Err(VideoEditorError::Export(format!(
+                "Interlaced height {} doesn't split into two fields",
+                video.resolution.height
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks kept 360° frames can be written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Export` for equirectangular output that
+    /// isn't 2:1, which players can't wrap around the sphere.
+    pub fn check_projection(&self) -> VideoEditorResult<()> {
+        let video = &self.video;
+        if video.projection != ProjectionOutput::Equirectangular || !self.renders_video() {
+            return Ok(());
+        }
+        let Resolution { width, height } = video.resolution;
+        if width != height * 2 {
+            return Err(VideoEditorError::Export(format!(
+                "Equirectangular output must be 2:1, not {width}x{height}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the number of frames encoded for a source range.
+    #[must_use]
+    pub fn output_frames(&self, source_frames: u64) -> u64 {
+        self.seamless_loop.map_or(source_frames, |l| l.output_frames(source_frames))
+    }
 }
 
 /// Metadata to embed in exported file.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct ExportMetadata {
     /// Title.
     pub title:     Option<String>,
@@ -335,6 +652,8 @@ pub struct ExportMetadata {
     pub copyright: Option<String>,
     /// Custom key-value metadata.
     pub custom:    Vec<(String, String)>,
+    /// Broadcast Wave metadata written into WAV exports.
+    pub bwf:       Option<crate::types::BwfMetadata>,
 }
 
 /// Export job status.
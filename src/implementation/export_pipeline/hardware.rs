@@ -0,0 +1,177 @@
+//! Hardware encoder detection.
+//!
+//! [`EncoderCapabilities`] lists the hardware encoders on the machine
+//! with the codecs and largest frame each can encode. Exports requesting
+//! [`HardwareAccel::Auto`] get the first encoder that takes their
+//! settings; a specific encoder that can't falls back to software, and
+//! the fallback is recorded in the export environment.
+
+use super::formats::{HardwareAccel, PixelFormat, VideoCodec, VideoEncodingSettings};
+use crate::types::Resolution;
+
+/// A hardware encoder and what it can encode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HardwareEncoder {
+    /// Encoder family.
+    pub accel:          HardwareAccel,
+    /// Supported codecs. ProRes and DNx entries match every profile.
+    pub codecs:         Vec<VideoCodec>,
+    /// Largest frame in either orientation.
+    pub max_resolution: Resolution,
+    /// Whether 10-bit pixel formats are supported.
+    pub ten_bit:        bool,
+}
+
+impl HardwareEncoder {
+    /// Creates an encoder entry.
+    #[must_use]
+    pub fn new(
+        accel: HardwareAccel, codecs: &[VideoCodec], max_resolution: Resolution, ten_bit: bool,
+    ) -> Self {
+        Self { accel, codecs: codecs.to_vec(), max_resolution, ten_bit }
+    }
+
+    /// Returns whether the encoder supports a codec.
+    #[must_use]
+    pub fn supports_codec(&self, codec: VideoCodec) -> bool {
+        self.codecs.iter().any(|c| match (c, codec) {
+            (VideoCodec::ProRes(_), VideoCodec::ProRes(_))
+            | (VideoCodec::DnxHd(_), VideoCodec::DnxHd(_)) => true,
+            _ => *c == codec,
+        })
+    }
+
+    /// Checks whether the encoder can take the settings, returning why
+    /// not if it can't.
+    pub fn check(&self, video: &VideoEncodingSettings) -> Result<(), String> {
+        if !self.supports_codec(video.codec) {
+            return Err(format!("{} does not encode {:?}", self.accel.label(), video.codec));
+        }
+        let (width, height) = (video.resolution.width, video.resolution.height);
+        let (max_w, max_h) = (self.max_resolution.width, self.max_resolution.height);
+        let fits = (width <= max_w && height <= max_h) || (width <= max_h && height <= max_w);
+        if !fits {
+            return Err(format!(
+                "{width}x{height} exceeds the {} limit of {max_w}x{max_h}",
+                self.accel.label()
+            ));
+        }
+        let ten_bit = matches!(
+            video.pixel_format,
+            PixelFormat::Yuv420p10 | PixelFormat::Yuv422p10 | PixelFormat::Yuv444p10
+        );
+        if ten_bit && !self.ten_bit {
+            return Err(format!("{} does not encode 10-bit", self.accel.label()));
+        }
+        Ok(())
+    }
+}
+
+/// Hardware encoders available for export.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EncoderCapabilities {
+    encoders: Vec<HardwareEncoder>,
+}
+
+impl EncoderCapabilities {
+    /// Creates capabilities from encoders the host probed itself, in
+    /// order of preference.
+    #[must_use]
+    pub fn new(encoders: Vec<HardwareEncoder>) -> Self {
+        Self { encoders }
+    }
+
+    /// Detects encoders from the GPU device name and the platform.
+    ///
+    /// The device vendor picks NVENC, Quick Sync or VCE; VideoToolbox is
+    /// available on every macOS machine.
+    #[must_use]
+    pub fn probe(device_name: Option<&str>) -> Self {
+        use VideoCodec::{Av1, H264, H265, ProRes, Vp9};
+
+        let device = device_name.unwrap_or_default().to_lowercase();
+        let vendor = |names: &[&str]| names.iter().any(|n| device.contains(n));
+        let mut encoders = Vec::new();
+        if vendor(&["nvidia", "geforce", "quadro", "rtx"]) {
+            encoders.push(HardwareEncoder::new(
+                HardwareAccel::Nvenc,
+                &[H264, H265, Av1],
+                Resolution::new(8192, 8192),
+                true,
+            ));
+        }
+        if vendor(&["intel", "iris"]) {
+            encoders.push(HardwareEncoder::new(
+                HardwareAccel::QuickSync,
+                &[H264, H265, Vp9, Av1],
+                Resolution::new(8192, 8192),
+                true,
+            ));
+        }
+        if vendor(&["amd", "radeon"]) {
+            encoders.push(HardwareEncoder::new(
+                HardwareAccel::AmdVce,
+                &[H264, H265, Av1],
+                Resolution::new(7680, 4320),
+                true,
+            ));
+        }
+        if cfg!(target_os = "macos") || vendor(&["apple"]) {
+            encoders.push(HardwareEncoder::new(
+                HardwareAccel::VideoToolbox,
+                &[H264, H265, ProRes(Default::default())],
+                Resolution::new(8192, 8192),
+                true,
+            ));
+        }
+        Self { encoders }
+    }
+
+    /// Returns the encoders in order of preference.
+    #[must_use]
+    pub fn encoders(&self) -> &[HardwareEncoder] {
+        &self.encoders
+    }
+
+    /// Returns the available encoder families.
+    #[must_use]
+    pub fn available(&self) -> Vec<HardwareAccel> {
+        self.encoders.iter().map(|e| e.accel).collect()
+    }
+
+    /// Gets an encoder by family.
+    #[must_use]
+    pub fn get(&self, accel: HardwareAccel) -> Option<&HardwareEncoder> {
+        self.encoders.iter().find(|e| e.accel == accel)
+    }
+
+    /// Picks the first encoder that can take the settings, or software.
+    #[must_use]
+    pub fn select(&self, video: &VideoEncodingSettings) -> HardwareAccel {
+        self.encoders
+            .iter()
+            .find(|e| e.check(video).is_ok())
+            .map_or(HardwareAccel::None, |e| e.accel)
+    }
+
+    /// Resolves the encoder requested by the settings to the one to use,
+    /// with the reason if it fell back to software.
+    #[must_use]
+    pub fn resolve(&self, video: &VideoEncodingSettings) -> (HardwareAccel, Option<String>) {
+        match video.hw_accel {
+            HardwareAccel::None => (HardwareAccel::None, None),
+            HardwareAccel::Auto => match self.select(video) {
+                HardwareAccel::None => (
+                    HardwareAccel::None,
+                    Some("no hardware encoder supports these settings".into()),
+                ),
+                accel => (accel, None),
+            },
+            accel => match self.get(accel).map(|e| e.check(video)) {
+                Some(Ok(())) => (accel, None),
+                Some(Err(reason)) => (HardwareAccel::None, Some(reason)),
+                None => (HardwareAccel::None, Some("hardware encoder not available".into())),
+            },
+        }
+    }
+}
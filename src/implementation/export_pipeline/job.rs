@@ -1,19 +1,31 @@
 //! Export job and progress tracking.
 
-use super::formats::{ExportJobId, ExportSettings, ExportStatus};
-use crate::types::Timestamp;
+use super::{
+    content_analysis::ContentAnalysis,
+    environment::ExportEnvironment,
+    formats::{ExportJobId, ExportSettings, ExportStatus},
+    two_pass::{FIRST_PASS_WEIGHT, FirstPassStats},
+};
+use crate::{
+    implementation::gpu_scheduler::{GpuScheduler, GpuTimeSlice},
+    types::Timestamp,
+};
 
 /// Progress information for an export job.
 #[derive(Debug, Clone)]
 pub struct ExportProgress {
     /// Current status.
     pub status:          ExportStatus,
-    /// Progress percentage (0.0 to 1.0).
+    /// Progress percentage (0.0 to 1.0), over all passes.
     pub progress:        f64,
-    /// Frames encoded so far.
+    /// Frames encoded so far in the current pass.
     pub frames_encoded:  u64,
-    /// Total frames to encode.
+    /// Total frames to encode per pass.
     pub total_frames:    u64,
+    /// Current pass, starting at 1.
+    pub pass:            u8,
+    /// Number of passes (2 for two-pass encoding).
+    pub passes:          u8,
     /// Current frame rate (fps).
     pub encoding_fps:    f64,
     /// Estimated time remaining in seconds.
@@ -37,6 +49,8 @@ impl ExportProgress {
             progress: 0.0,
             frames_encoded: 0,
             total_frames,
+            pass: 1,
+            passes: 1,
             encoding_fps: 0.0,
             eta_seconds: None,
             current_size: 0,
@@ -47,14 +61,32 @@ impl ExportProgress {
     }
 
     /// Updates progress with new frame count.
+    ///
+    /// With two passes the first pass covers the first
+    /// [`FIRST_PASS_WEIGHT`] of the progress and `elapsed_seconds` is
+    /// counted from the start of the first pass.
     pub fn update(&mut self, frames_encoded: u64, elapsed_seconds: f64) {
         self.frames_encoded = frames_encoded;
 
         if self.total_frames > 0 {
-            self.progress = frames_encoded as f64 / self.total_frames as f64;
+            let pass_progress = frames_encoded as f64 / self.total_frames as f64;
+            self.progress = match (self.passes, self.pass) {
+                (2, 1) => pass_progress * FIRST_PASS_WEIGHT,
+                (2, _) => FIRST_PASS_WEIGHT + pass_progress * (1.0 - FIRST_PASS_WEIGHT),
+                _ => pass_progress,
+            };
         }
 
         if elapsed_seconds > 0.0 {
+            if self.passes > 1 {
+                let frames_done = frames_encoded + u64::from(self.pass - 1) * self.total_frames;
+                self.encoding_fps = frames_done as f64 / elapsed_seconds;
+                if self.progress > 0.0 {
+                    self.eta_seconds =
+                        Some(elapsed_seconds * (1.0 - self.progress) / self.progress);
+                }
+                return;
+            }
             self.encoding_fps = frames_encoded as f64 / elapsed_seconds;
 
             if self.encoding_fps > 0.0 {
@@ -64,6 +96,14 @@ impl ExportProgress {
         }
     }
 
+    /// Moves a two-pass export on to the second pass.
+    pub fn begin_second_pass(&mut self) {
+        self.status = ExportStatus::SecondPass;
+        self.pass = 2;
+        self.frames_encoded = 0;
+        self.progress = FIRST_PASS_WEIGHT;
+    }
+
     /// Returns whether the export is complete.
     #[must_use]
     pub fn is_complete(&self) -> bool {
@@ -75,24 +115,32 @@ impl ExportProgress {
 }
 
 /// An export job in the render queue.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ExportJob {
     /// Job identifier.
-    pub(super) id:         ExportJobId,
+    pub(super) id:          ExportJobId,
     /// Export settings.
-    pub(super) settings:   ExportSettings,
+    pub(super) settings:    ExportSettings,
     /// Progress information.
-    pub(super) progress:   ExportProgress,
+    pub(super) progress:    ExportProgress,
     /// Project ID this export is from.
-    pub(super) project_id: u64,
+    pub(super) project_id:  u64,
     /// When the job was created.
-    pub(super) created_at: Timestamp,
+    pub(super) created_at:  Timestamp,
     /// When encoding started.
-    pub(super) started_at: Option<Timestamp>,
+    pub(super) started_at:  Option<Timestamp>,
     /// When encoding completed.
-    pub(super) ended_at:   Option<Timestamp>,
+    pub(super) ended_at:    Option<Timestamp>,
     /// Priority (higher = more important).
-    pub(super) priority:   i32,
+    pub(super) priority:    i32,
+    /// Environment the job completed in.
+    pub(super) environment: Option<ExportEnvironment>,
+    /// First-pass analysis of a two-pass export.
+    pub(super) first_pass:  Option<FirstPassStats>,
+    /// Second-pass bits per frame, from the first-pass analysis.
+    pub(super) frame_bits:  Vec<u64>,
+    /// Pre-encode content analysis the settings were picked from.
+    pub(super) analysis:    Option<ContentAnalysis>,
 }
 
 impl ExportJob {
@@ -101,15 +149,24 @@ impl ExportJob {
     pub fn new(
         id: ExportJobId, project_id: u64, settings: ExportSettings, total_frames: u64,
     ) -> Self {
+        let total_frames = settings.output_frames(total_frames);
+        let mut progress = ExportProgress::new(total_frames);
+        if settings.is_two_pass() {
+            progress.passes = 2;
+        }
         Self {
             id,
             settings,
-            progress: ExportProgress::new(total_frames),
+            progress,
             project_id,
             created_at: Timestamp::now(),
             started_at: None,
             ended_at: None,
             priority: 0,
+            environment: None,
+            first_pass: None,
+            frame_bits: Vec::new(),
+            analysis: None,
         }
     }
 
@@ -165,8 +222,43 @@ impl ExportJob {
         self.progress.status = ExportStatus::Preparing;
     }
 
-    /// Marks the job as completed.
-    pub fn complete(&mut self) {
+    /// Returns the environment the job completed in.
+    #[must_use]
+    pub fn environment(&self) -> Option<&ExportEnvironment> {
+        self.environment.as_ref()
+    }
+
+    /// Returns the content analysis the video settings were picked from.
+    #[must_use]
+    pub fn content_analysis(&self) -> Option<&ContentAnalysis> {
+        self.analysis.as_ref()
+    }
+
+    /// Returns the first-pass analysis of a two-pass export.
+    #[must_use]
+    pub fn first_pass_stats(&self) -> Option<&FirstPassStats> {
+        self.first_pass.as_ref()
+    }
+
+    /// Returns the second-pass size of a frame of the range in bits, once
+    /// the first pass has run.
+    #[must_use]
+    pub fn frame_bits(&self, index: usize) -> Option<u64> {
+        self.frame_bits.get(index).copied()
+    }
+
+    /// Ends the first pass of a two-pass export: keeps the analysis, splits
+    /// the target bitrate over the frames and starts the second pass.
+    pub fn finish_first_pass(&mut self, stats: FirstPassStats) {
+        let video = &self.settings.video;
+        self.frame_bits = stats.allocate(video.bitrate, video.frame_rate);
+        self.first_pass = Some(stats);
+        self.progress.begin_second_pass();
+    }
+
+    /// Marks the job as completed, recording the environment it rendered in.
+    pub fn complete(&mut self, environment: ExportEnvironment) {
+        self.environment = Some(environment);
         self.ended_at = Some(Timestamp::now());
         self.progress.status = ExportStatus::Completed;
         self.progress.progress = 1.0;
@@ -185,6 +277,50 @@ impl ExportJob {
         self.progress.status = ExportStatus::Cancelled;
     }
 
+    /// Renders and encodes frames until the GPU scheduler asks for a yield.
+    ///
+    /// Each frame charges `frame_cost_ms` against `slice`. Returns the number
+    /// of frames encoded in this slice; the caller resumes the job with a new
+    /// slice once higher priority (preview) work has run.
+    pub fn encode_slice(
+        &mut self, scheduler: &mut GpuScheduler, slice: &mut GpuTimeSlice, frame_cost_ms: f64,
+    ) -> u64 {
+        if self.progress.is_complete() || matches!(self.progress.status, ExportStatus::Paused) {
+            return 0;
+        }
+        if matches!(self.progress.status, ExportStatus::Queued | ExportStatus::Preparing) {
+            self.progress.status = if self.progress.passes > 1 {
+                ExportStatus::FirstPass
+            } else {
+                ExportStatus::Encoding
+            };
+        }
+
+        let remaining = self.progress.total_frames.saturating_sub(self.progress.frames_encoded);
+        let encoded = if self.settings.renders_video() {
+            let mut encoded = 0;
+            while encoded < remaining && !scheduler.should_yield(slice) {
+                encoded += 1;
+                slice.charge(frame_cost_ms);
+            }
+            encoded
+        } else {
+            // Audio-only exports skip video rendering and never hold the GPU
+            remaining
+        };
+
+        let elapsed = self.elapsed_time().unwrap_or(0.0);
+        self.progress.update(self.progress.frames_encoded + encoded, elapsed);
+        if self.progress.frames_encoded >= self.progress.total_frames {
+            if self.progress.status == ExportStatus::FirstPass {
+                self.progress.begin_second_pass();
+            } else {
+                self.progress.status = ExportStatus::Finalizing;
+            }
+        }
+        encoded
+    }
+
     /// Returns elapsed encoding time.
     #[must_use]
     pub fn elapsed_time(&self) -> Option<f64> {
@@ -2,18 +2,83 @@
 //! GAP-220-B-003: Video Export System
 //!
 //! Features: Render queue, format encoding, codec configuration,
-//! progress tracking, and multi-format export.
+//! progress tracking, multi-format export, seamless loop export, and
+//! per-job environment snapshots for reproducible re-renders, remote
+//! render-farm submission, still frame and image sequence export,
+//! audio stem export, range presets for in/out points, selections and
+//! chapters, output name templates with collision policies, and
+//! hardware encoder detection with software fallback, two-pass VBR rate
+//! control, content-adaptive bitrate within a size budget, HLS/DASH
+//! packaging of a rendition ladder, timecode, watermark and LUT burn-in
+//! for review exports, and queued jobs run in place or as cancellable
+//! background tasks.
 
+mod content_analysis;
+mod engine;
+mod environment;
 mod formats;
+mod hardware;
 mod job;
+mod naming;
+mod overlay;
+mod packaging;
 mod queue;
+mod range;
+mod remote;
+mod seamless_loop;
+mod stems;
+mod two_pass;
+
+pub(crate) use engine::FrameRenderer;
+pub(crate) use formats::ExportSettings;
+pub(crate) use hardware::EncoderCapabilities;
+pub(crate) use overlay::draw_centered_lines;
+#[cfg(feature = "bench")]
+pub use queue::ExportQueue;
+pub(crate) use range::ExportRange;
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicU64, Ordering},
+        },
+        time::{Duration, Instant},
+    };
+
+    use essentia_color_types::Color;
+
     use super::{
+        content_analysis::*,
+        engine::{ExportEngine, FrameRenderer},
+        environment::ExportEnvironment,
         formats::*,
+        hardware::*,
         job::{ExportJob, ExportProgress},
+        naming::*,
+        overlay::*,
+        packaging::*,
         queue::{ExportPreset, ExportQueue},
+        range::*,
+        remote::*,
+        seamless_loop::SeamlessLoop,
+        stems::*,
+        two_pass::*,
+    };
+    use crate::{
+        errors::VideoEditorResult,
+        implementation::{
+            audio_mixer::AudioMixer,
+            color_grading::{ColorSpace, Lut3D},
+            gpu_scheduler::{GpuPriority, GpuScheduler},
+            render::RenderDeterminism,
+        },
+        stills::{EXR_MAGIC, PNG_SIGNATURE, StillFormat},
+        types::{
+            FieldOrder, FrameBuffer, FrameBufferPool, FrameRate, PixelAspectRatio, Resolution,
+            TimePosition,
+        },
     };
 
     #[test]
@@ -27,6 +92,18 @@ mod tests {
         assert_eq!(queue.queued_jobs().len(), 1);
     }
 
+    #[test]
+    fn test_ordered_export_queue() {
+        let mut queue = ExportQueue::new();
+        queue.set_determinism(&RenderDeterminism::strict(0));
+        queue.set_max_concurrent(4);
+        let first = queue.add_job(1, ExportSettings::default(), 10);
+        queue.add_job(1, ExportSettings::default(), 10);
+
+        assert_eq!(queue.start_next(), Some(first));
+        assert_eq!(queue.start_next(), None);
+    }
+
     #[test]
     fn test_export_progress() {
         let mut progress = ExportProgress::new(1000);
@@ -50,4 +127,999 @@ mod tests {
         assert_eq!(preset.settings.video.resolution.width, 1920);
         assert_eq!(preset.settings.video.resolution.height, 1080);
     }
+
+    #[test]
+    fn test_export_yields_to_preview() {
+        let mut scheduler = GpuScheduler::new();
+        scheduler.set_slice_ms(10.0);
+        let mut job = ExportJob::new(ExportJobId::new(1), 1, ExportSettings::default(), 100);
+        job.start();
+
+        let mut slice = scheduler.begin_slice(GpuPriority::Export);
+        assert_eq!(job.encode_slice(&mut scheduler, &mut slice, 2.0), 5);
+        assert_eq!(job.progress().status, ExportStatus::Encoding);
+
+        scheduler.submit(GpuPriority::Interactive, "preview", 4.0);
+        let mut slice = scheduler.begin_slice(GpuPriority::Export);
+        assert_eq!(job.encode_slice(&mut scheduler, &mut slice, 2.0), 0);
+        assert_eq!(job.progress().frames_encoded, 5);
+    }
+
+    #[test]
+    fn test_audio_only_export() {
+        let preset = ExportPreset::audio_m4a();
+        assert!(!preset.settings.renders_video());
+        assert_eq!(preset.settings.audio.codec, AudioCodec::Aac);
+        assert_eq!(preset.settings.container.extension(), "m4a");
+        assert_eq!(preset.settings.loudness, Some(LoudnessTarget::STREAMING));
+
+        // Audio-only jobs never wait for the GPU
+        let mut scheduler = GpuScheduler::new();
+        scheduler.submit(GpuPriority::Interactive, "preview", 4.0);
+        let mut job = ExportJob::new(ExportJobId::new(1), 1, preset.settings, 100);
+        job.start();
+        let mut slice = scheduler.begin_slice(GpuPriority::Export);
+        assert_eq!(job.encode_slice(&mut scheduler, &mut slice, 2.0), 100);
+    }
+
+    #[test]
+    fn test_loudness_gain() {
+        let gain = LoudnessTarget::EBU_R128.gain_db(-18.0, -3.0);
+        assert!((gain + 5.0).abs() < 0.001);
+
+        // Limited by the true-peak ceiling
+        let gain = LoudnessTarget::STREAMING.gain_db(-20.0, -4.0);
+        assert!((gain - 3.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_interlaced_export_settings() {
+        let mut settings = ExportSettings {
+            container: ContainerFormat::MpegTs,
+            video: VideoEncodingSettings {
+                field_order: FieldOrder::TopFieldFirst,
+                ..VideoEncodingSettings::default()
+            },
+            ..ExportSettings::default()
+        };
+        assert!(settings.check_field_order().is_ok());
+
+        settings.video.codec = VideoCodec::Vp9;
+        assert!(settings.check_field_order().is_err());
+        settings.video.codec = VideoCodec::ProRes(ProResProfile::Hq);
+        settings.video.resolution = Resolution::new(720, 487);
+        assert!(settings.check_field_order().is_err());
+        settings.video.resolution = Resolution::new(720, 486);
+        settings.container = ContainerFormat::ImageSequence(StillFormat::Png);
+        assert!(settings.check_field_order().is_err());
+
+        // Progressive output and audio-only exports don't care
+        settings.video.field_order = FieldOrder::Progressive;
+        assert!(settings.check_field_order().is_ok());
+        settings.video.field_order = FieldOrder::BottomFieldFirst;
+        settings.container = ContainerFormat::Wav;
+        assert!(settings.check_field_order().is_ok());
+    }
+
+    #[test]
+    fn test_spherical_export() {
+        /// Reframed frames are black, equirectangular ones white.
+        struct Spherical;
+
+        impl FrameRenderer for Spherical {
+            fn resolution(&self) -> Resolution {
+                Resolution { width: 4, height: 2 }
+            }
+
+            fn frame_rate(&self) -> FrameRate {
+                FrameRate::FPS_24
+            }
+
+            fn render_frame(&self, _time: TimePosition) -> VideoEditorResult<Vec<u8>> {
+                Ok([0, 0, 0, 255].repeat(8))
+            }
+
+            fn render_frame_equirectangular(
+                &self, _time: TimePosition, _pool: &FrameBufferPool,
+            ) -> VideoEditorResult<FrameBuffer> {
+                Ok([255; 4].repeat(8).into())
+            }
+        }
+
+        let at = TimePosition::from_secs(1);
+        let engine =
+            ExportEngine::new(&Spherical).with_color_spaces(ColorSpace::Srgb, ColorSpace::Srgb);
+        let red = |png: Vec<u8>| png[41 + 8];
+        let png = engine.export_frame(at, StillFormat::Png).expect("test assertion");
+        assert_eq!(red(png), 0);
+        let png = engine
+            .with_projection(ProjectionOutput::Equirectangular)
+            .export_frame(at, StillFormat::Png)
+            .expect("test assertion");
+        assert_eq!(red(png), 255);
+
+        let mut settings = ExportSettings::default();
+        assert!(settings.video.spherical_metadata().is_none());
+        settings.video.projection = ProjectionOutput::Equirectangular;
+        assert!(settings.check_projection().is_err());
+        settings.video.resolution = Resolution::new(3840, 1920);
+        assert!(settings.check_projection().is_ok());
+        let xml = settings.video.spherical_metadata().expect("test assertion");
+        assert!(xml.contains(">equirectangular</GSpherical:ProjectionType>"));
+        assert!(xml.contains("<GSpherical:FullPanoWidthPixels>3840<"));
+    }
+
+    #[test]
+    fn test_seamless_loop_frames() {
+        let looped = SeamlessLoop::new(10);
+        assert!(looped.validate(100).is_ok());
+        assert!(looped.validate(15).is_err());
+
+        let preset = ExportPreset::streaming_hd().with_seamless_loop(10);
+        let job = ExportJob::new(ExportJobId::new(1), 1, preset.settings, 100);
+        assert_eq!(job.progress().total_frames, 90);
+
+        // The first exported frame is mostly the tail, continuing from frame 89
+        let first = looped.frame_source(0, 100);
+        assert_eq!(first.tail, Some(90));
+        assert!(first.tail_weight > 0.9);
+        assert_eq!(looped.frame_source(9, 100).tail, Some(99));
+        assert_eq!(looped.frame_source(10, 100).tail, None);
+
+        let mut blended = Vec::new();
+        SeamlessLoop::blend_frame(&[0, 0, 0, 255], &[200, 100, 50, 255], 0.5, &mut blended);
+        assert_eq!(blended, vec![100, 50, 25, 255]);
+
+        let verification = looped.verify(&[10, 10, 10, 255], &[12, 10, 10, 0]);
+        assert!(verification.seamless);
+        assert!(!looped.verify(&[0, 0, 0, 255], &[255, 255, 255, 255]).seamless);
+    }
+
+    #[test]
+    fn test_seamless_loop_audio() {
+        let looped = SeamlessLoop::new(1);
+        // 10 fps at 100 Hz: one frame of overlap is 10 samples
+        let samples: Vec<f32> = (0..100).map(|i| if i < 90 { 0.0 } else { 1.0 }).collect();
+        let output = looped.crossfade_audio(&samples, 1, 100, FrameRate::new(10, 1));
+
+        assert_eq!(output.len(), 90);
+        // Starts on the tail and fades into the head
+        assert!(output[0] > 0.9);
+        assert!(output[9] < 0.1);
+        assert!(output[10..].iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_hardware_encoder_selection() {
+        let capabilities = EncoderCapabilities::probe(Some("NVIDIA GeForce RTX 4070"));
+        assert!(capabilities.available().contains(&HardwareAccel::Nvenc));
+        assert!(!capabilities.available().contains(&HardwareAccel::QuickSync));
+        assert!(
+            EncoderCapabilities::probe(Some("Intel(R) Iris(R) Xe"))
+                .available()
+                .contains(&HardwareAccel::QuickSync)
+        );
+
+        let mut video = VideoEncodingSettings {
+            hw_accel: HardwareAccel::Auto,
+            ..VideoEncodingSettings::default()
+        };
+        assert_eq!(capabilities.resolve(&video), (HardwareAccel::Nvenc, None));
+
+        // Too large for the encoder: software, with the reason
+        video.resolution = Resolution::new(10240, 4320);
+        video.hw_accel = HardwareAccel::Nvenc;
+        let (accel, reason) = capabilities.resolve(&video);
+        assert_eq!(accel, HardwareAccel::None);
+        assert!(reason.expect("test assertion").contains("8192x8192"));
+
+        // Portrait frames fit in either orientation
+        video.resolution = Resolution::new(2160, 3840);
+        assert_eq!(capabilities.resolve(&video).0, HardwareAccel::Nvenc);
+
+        let vce = HardwareEncoder::new(
+            HardwareAccel::AmdVce,
+            &[VideoCodec::H264],
+            Resolution::new(4096, 2304),
+            false,
+        );
+        let capabilities = EncoderCapabilities::new(vec![vce]);
+        video.pixel_format = PixelFormat::Yuv420p10;
+        assert!(capabilities.encoders()[0].check(&video).is_err());
+        video.hw_accel = HardwareAccel::Auto;
+        assert_eq!(capabilities.select(&video), HardwareAccel::None);
+
+        let mut settings = ExportSettings::default();
+        settings.video.hw_accel = HardwareAccel::Auto;
+        settings.video.codec = VideoCodec::Av1;
+        let mut environment = ExportEnvironment::capture(&settings, b"project");
+        environment.resolve_hw_encoder(&capabilities);
+        assert_eq!(environment.effective_settings.video.hw_accel, HardwareAccel::None);
+        assert_eq!(environment.fallbacks[0].requested, "Auto");
+
+        for accel in HardwareAccel::ALL {
+            assert_eq!(HardwareAccel::from_label(accel.label()), Some(accel));
+        }
+    }
+
+    #[test]
+    fn test_export_environment_snapshot() {
+        let mut queue = ExportQueue::new();
+        let mut settings = ExportSettings::default();
+        settings.video.hw_accel = HardwareAccel::Nvenc;
+        let id = queue.add_job(1, settings, 100);
+        queue.start_next();
+
+        let job = queue.get_job_mut(id).expect("test assertion");
+        let mut environment = ExportEnvironment::capture(job.settings(), b"project state")
+            .with_app_version("2.1.0")
+            .with_encoder("x264", "164")
+            .with_gpu("Simulated GPU", "550.54");
+        environment.resolve_hw_accel(&[HardwareAccel::QuickSync]);
+        job.complete(environment);
+
+        let recorded = job.environment().expect("test assertion");
+        assert_eq!(recorded.effective_settings.video.hw_accel, HardwareAccel::None);
+        assert_eq!(recorded.fallbacks.len(), 1);
+        assert_eq!(recorded.fallbacks[0].setting, "video.hw_accel");
+        assert_eq!(recorded.project_hash.len(), 64);
+        assert_eq!(recorded.crate_version, env!("CARGO_PKG_VERSION"));
+
+        // A re-render uses the settings that actually produced the file
+        let rerender = queue.rerender_job(id).expect("test assertion");
+        let settings = queue.get_job(rerender).map(|j| j.settings().video.hw_accel);
+        assert_eq!(settings, Some(HardwareAccel::None));
+        assert!(queue.rerender_job(rerender).is_err());
+
+        let recorded = queue.get_job(id).and_then(|j| j.environment()).expect("test assertion");
+        let mut later = recorded.clone();
+        assert!(recorded.is_reproducible_in(&later));
+        later.encoders[0].version = "165".into();
+        later.project_hash =
+            ExportEnvironment::capture(&later.effective_settings, b"edited").project_hash;
+        assert_eq!(
+            recorded.differences(&later),
+            vec![
+                format!("project: {} -> {}", recorded.project_hash, later.project_hash),
+                "encoder x264: 164 -> 165".to_string(),
+            ]
+        );
+    }
+
+    /// Backend replaying scripted responses.
+    struct ScriptedBackend {
+        submits: std::sync::Mutex<Vec<Result<RemoteJobHandle, RemoteError>>>,
+        polls:   std::sync::Mutex<Vec<Result<RemoteStatus, RemoteError>>>,
+    }
+
+    impl RemoteExportBackend for ScriptedBackend {
+        fn name(&self) -> &str {
+            "farm"
+        }
+
+        fn submit(&self, request: &RemoteJobRequest) -> Result<RemoteJobHandle, RemoteError> {
+            assert!(request.manifest().contains("container=mp4"));
+            self.submits.lock().expect("test assertion").remove(0)
+        }
+
+        fn poll(&self, _: &RemoteJobHandle) -> Result<RemoteStatus, RemoteError> {
+            self.polls.lock().expect("test assertion").remove(0)
+        }
+
+        fn download(
+            &self, _: &RemoteJobHandle, target: &std::path::Path,
+        ) -> Result<u64, RemoteError> {
+            std::fs::write(target, b"rendered")
+                .map_err(|e| RemoteError::Permanent(e.to_string()))?;
+            Ok(8)
+        }
+    }
+
+    #[test]
+    fn test_remote_export_retries_and_downloads() {
+        let dir = std::env::temp_dir().join(format!("evep_remote_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("test assertion");
+        let settings = ExportSettings {
+            output_path: dir.join("out.mp4").to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+        let mut job = ExportJob::new(ExportJobId::new(7), 1, settings, 100);
+
+        let request = RemoteJobRequest::from_job(&job, b"slice".to_vec()).expect("test assertion");
+        let payload = RemoteJobRequest::parse_bytes(&request.to_bytes()).expect("test assertion");
+        assert_eq!(payload.get("output_name"), Some("out.mp4"));
+        assert_eq!(payload.project_slice, b"slice");
+
+        let handle = RemoteJobHandle { remote_id: "r-1".into() };
+        let backend = ScriptedBackend {
+            submits: std::sync::Mutex::new(vec![
+                Err(RemoteError::Transient("timeout".into())),
+                Ok(handle.clone()),
+            ]),
+            polls:   std::sync::Mutex::new(vec![
+                Ok(RemoteStatus::Running {
+                    frames_done:   40,
+                    encoding_fps:  Some(20.0),
+                    bytes_written: 1_000,
+                }),
+                Ok(RemoteStatus::Completed { artifact_size: 8 }),
+            ]),
+        };
+        let retry = RetryPolicy { max_attempts: 3, base_delay_ms: 100, max_delay_ms: 1_000 };
+        let mut remote = RemoteExport::new(request, retry);
+
+        // The transient failure backs off before resubmitting
+        remote.tick(&backend, &mut job, 0).expect("test assertion");
+        assert_eq!((remote.failures(), remote.next_try_ms()), (1, 100));
+        remote.tick(&backend, &mut job, 50).expect("test assertion");
+        assert!(remote.handle().is_none());
+        remote.tick(&backend, &mut job, 100).expect("test assertion");
+        assert_eq!(remote.handle(), Some(&handle));
+
+        remote.tick(&backend, &mut job, 2_100).expect("test assertion");
+        assert_eq!(job.progress().status, ExportStatus::Encoding);
+        assert!((job.progress().progress - 0.4).abs() < 1e-9);
+        assert_eq!(job.progress().eta_seconds, Some(3.0));
+
+        remote.tick(&backend, &mut job, 3_000).expect("test assertion");
+        let phase = remote.tick(&backend, &mut job, 3_100).expect("test assertion");
+        assert_eq!(phase, &RemotePhase::Done);
+        assert_eq!(job.progress().status, ExportStatus::Completed);
+        let environment = job.environment().expect("test assertion");
+        assert_eq!(environment.platform, "remote:farm");
+        assert_eq!(std::fs::read(dir.join("out.mp4")).expect("test assertion"), b"rendered");
+
+        // Out of retries
+        let mut job = ExportJob::new(ExportJobId::new(8), 1, ExportSettings::default(), 100);
+        let request = RemoteJobRequest::from_job(&job, Vec::new()).expect("test assertion");
+        let mut remote = RemoteExport::new(request, RetryPolicy { max_attempts: 1, ..retry });
+        let backend = ScriptedBackend {
+            submits: std::sync::Mutex::new(vec![Err(RemoteError::Transient("down".into()))]),
+            polls:   std::sync::Mutex::new(Vec::new()),
+        };
+        assert!(remote.tick(&backend, &mut job, 0).is_err());
+        assert_eq!(job.progress().status, ExportStatus::Failed);
+        assert_eq!(remote.phase(), &RemotePhase::Stopped);
+        assert_eq!(retry.delay_ms(3), 400);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_remote_manifest_carries_all_settings() {
+        // Ranges that need the timeline are resolved before submission
+        let job = |range| {
+            ExportJob::new(
+                ExportJobId::new(1),
+                1,
+                ExportSettings { range, ..Default::default() },
+                10,
+            )
+        };
+        let marker = crate::implementation::marker_system::MarkerId::new(1);
+        for range in [
+            ExportRange::InOut,
+            ExportRange::Selection,
+            ExportRange::BetweenMarkers(marker, marker),
+            ExportRange::Chapter(marker),
+            ExportRange::Multiple(vec![("a".into(), ExportRange::Entire)]),
+        ] {
+            assert!(RemoteJobRequest::from_job(&job(range), Vec::new()).is_err());
+        }
+        let span = ExportRange::Span(TimePosition::from_ms(1000), TimePosition::from_ms(3000));
+        assert!(RemoteJobRequest::from_job(&job(ExportRange::Entire), Vec::new()).is_ok());
+
+        let watermark = Watermark::new(vec![9; 16], 2, 2).expect("test assertion");
+        let mut settings = ExportSettings {
+            range: span,
+            adaptive: Some(AdaptiveBitrate::within_size(1 << 30)),
+            seamless_loop: Some(SeamlessLoop {
+                overlap_frames: 12,
+                curve:          Default::default(),
+                max_seam_delta: 0.02,
+            }),
+            overlay: Some(BurnInOverlay {
+                watermark: Some(watermark),
+                lut: Some(Lut3D::identity(2)),
+                ..Default::default()
+            }),
+            stems: Some(StemExportOptions::new(StemLayout::PerTrack)),
+            packaging: Some(StreamPackaging::with_ladder(
+                StreamingFormat::Dash,
+                &Default::default(),
+            )),
+            ..Default::default()
+        };
+        settings.metadata.title = Some("Line one\nLine two \\ end".into());
+        settings.metadata.custom.push(("take=".into(), "3".into()));
+        settings.captions.burn_in = vec![4, 5];
+        settings.captions.sidecars.push(crate::implementation::captions::CaptionSidecar {
+            track_id: 4,
+            format:   crate::implementation::captions::CaptionFormat::WebVtt,
+        });
+        let job = ExportJob::new(ExportJobId::new(2), 1, settings, 48);
+        let request = RemoteJobRequest::from_job(&job, b"slice".to_vec()).expect("test assertion");
+        let payload = RemoteJobRequest::parse_bytes(&request.to_bytes()).expect("test assertion");
+
+        assert_eq!(payload.get("range_ms"), Some("1000-3000"));
+        assert_eq!(payload.get("adaptive.max_size_bytes"), Some("1073741824"));
+        assert_eq!(payload.get("seamless_loop.overlap_frames"), Some("12"));
+        assert_eq!(payload.get("metadata.title"), Some("Line one\nLine two \\ end"));
+        assert_eq!(payload.get("metadata.custom.0.key"), Some("take="));
+        assert_eq!(payload.get("captions.burn_in"), Some("4,5"));
+        assert_eq!(payload.get("captions.sidecar.0"), Some("4:WebVtt"));
+        assert_eq!(payload.get("overlay.timecode"), Some("TopLeft"));
+        assert_eq!(payload.get("overlay.watermark.size"), Some("2x2"));
+        assert_eq!(payload.attachment("overlay.watermark"), Some(&[9; 16][..]));
+        let cube = payload.attachment("overlay.lut").expect("test assertion");
+        assert!(std::str::from_utf8(cube).expect("test assertion").contains("LUT_3D_SIZE 2"));
+        assert_eq!(payload.get("stems.layout"), Some("PerTrack"));
+        assert_eq!(payload.get("packaging.format"), Some("Dash"));
+        assert!(payload.get("packaging.rendition.0.video.resolution").is_some());
+        assert_eq!(payload.project_slice, b"slice");
+
+        // Truncated attachments don't parse
+        let bytes = request.to_bytes();
+        assert!(RemoteJobRequest::parse_bytes(&bytes[..bytes.len() - 1]).is_none());
+        assert!(RemoteJobRequest::parse_bytes(b"EVRJ").is_none());
+    }
+
+    struct FlatRenderer([u8; 4]);
+
+    impl FrameRenderer for FlatRenderer {
+        fn resolution(&self) -> Resolution {
+            Resolution { width: 4, height: 2 }
+        }
+
+        fn frame_rate(&self) -> FrameRate {
+            FrameRate::FPS_24
+        }
+
+        fn render_frame(&self, _time: TimePosition) -> VideoEditorResult<Vec<u8>> {
+            Ok(self.0.repeat(8))
+        }
+    }
+
+    #[test]
+    fn test_export_frame_stills() {
+        let renderer = FlatRenderer([255, 128, 0, 255]);
+        let at = TimePosition::from_secs(1);
+
+        // Same space in and out round-trips the pixels; the stored PNG
+        // rows start after the 2-byte zlib and 5-byte block headers
+        let engine =
+            ExportEngine::new(&renderer).with_color_spaces(ColorSpace::Srgb, ColorSpace::Srgb);
+        let png = engine.export_frame(at, StillFormat::Png).expect("test assertion");
+        assert_eq!(&png[..8], PNG_SIGNATURE);
+        assert_eq!(&png[41 + 7..41 + 12], &[0, 255, 128, 0, 255]);
+
+        // Rec. 709 (gamma 2.4) to sRGB (gamma 2.2) darkens mid tones
+        let png = ExportEngine::new(&renderer).export_frame(at, StillFormat::Png);
+        let green = png.expect("test assertion")[41 + 9];
+        assert!((118..=122).contains(&green));
+
+        let exr = engine.export_frame(at, StillFormat::Exr).expect("test assertion");
+        assert_eq!(&exr[..4], EXR_MAGIC);
+        let jpeg = engine.export_frame(at, StillFormat::Jpeg { quality: 90 });
+        assert_eq!(&jpeg.expect("test assertion")[..2], &[0xFF, 0xD8]);
+        let log =
+            ExportEngine::new(&renderer).with_color_spaces(ColorSpace::Rec709, ColorSpace::SLog3);
+        assert!(log.export_frame(at, StillFormat::Exr).is_err());
+    }
+
+    #[test]
+    fn test_pixel_aspect_export() {
+        /// 2x anamorphic frames, dark on the left and bright on the right.
+        struct Anamorphic;
+
+        impl FrameRenderer for Anamorphic {
+            fn resolution(&self) -> Resolution {
+                Resolution { width: 4, height: 2 }
+            }
+
+            fn frame_rate(&self) -> FrameRate {
+                FrameRate::FPS_24
+            }
+
+            fn pixel_aspect(&self) -> PixelAspectRatio {
+                PixelAspectRatio::ANAMORPHIC_2X
+            }
+
+            fn render_frame(&self, _time: TimePosition) -> VideoEditorResult<Vec<u8>> {
+                Ok([[0, 0, 0, 255], [0, 0, 0, 255], [255; 4], [255; 4]].concat().repeat(2))
+            }
+        }
+
+        let at = TimePosition::from_secs(1);
+        let engine = |mode| {
+            ExportEngine::new(&Anamorphic)
+                .with_color_spaces(ColorSpace::Srgb, ColorSpace::Srgb)
+                .with_pixel_aspect(mode)
+        };
+        let width = |png: &[u8]| u32::from_be_bytes([png[16], png[17], png[18], png[19]]);
+
+        let png = engine(PixelAspectMode::Preserve).export_frame(at, StillFormat::Png);
+        assert_eq!(width(&png.expect("test assertion")), 4);
+        // Desqueezed to 8 square pixels, filtered across the edge
+        let png = engine(PixelAspectMode::Square)
+            .export_frame(at, StillFormat::Png)
+            .expect("test assertion");
+        assert_eq!(width(&png), 8);
+        let reds: Vec<u8> = png[41 + 8..41 + 8 + 32].iter().step_by(4).copied().collect();
+        assert_eq!(reds, [0, 0, 0, 64, 191, 255, 255, 255]);
+        let png = engine(PixelAspectMode::Convert(PixelAspectRatio::HDV))
+            .export_frame(at, StillFormat::Png);
+        assert_eq!(width(&png.expect("test assertion")), 6);
+
+        let hdv = Resolution::new(1440, 1080);
+        assert_eq!(
+            PixelAspectMode::Square.output_resolution(hdv, PixelAspectRatio::HDV),
+            Resolution::new(1920, 1080)
+        );
+        let squeeze = PixelAspectMode::Convert(PixelAspectRatio::HDV);
+        assert_eq!(squeeze.output_resolution(Resolution::FHD, PixelAspectRatio::SQUARE), hdv);
+        assert_eq!(
+            PixelAspectMode::Preserve.output(PixelAspectRatio::NTSC),
+            PixelAspectRatio::NTSC
+        );
+    }
+
+    #[test]
+    fn test_export_image_sequence() {
+        let dir = std::env::temp_dir().join(format!("evep_sequence_{}", std::process::id()));
+        let renderer = FlatRenderer([10, 20, 30, 255]);
+        let engine = ExportEngine::new(&renderer);
+
+        let settings = ExportSettings {
+            container: ContainerFormat::ImageSequence(StillFormat::Png),
+            output_path: dir.join("out_%05d.png").to_string_lossy().into_owned(),
+            range: ExportRange::Span(TimePosition::from_secs(1), TimePosition::from_ms(1125)),
+            ..ExportSettings::default()
+        };
+        assert_eq!(settings.container.extension(), "png");
+        let mut job = ExportJob::new(ExportJobId::new(1), 1, settings, 3);
+        let written = engine.export_sequence(&mut job).expect("test assertion");
+        assert_eq!(written.len(), 3);
+        assert!(written[0].ends_with("out_00024.png"));
+        assert!(written.iter().all(|p| std::path::Path::new(p).is_file()));
+        assert_eq!(job.progress().status, ExportStatus::Completed);
+
+        let mut video = ExportJob::new(ExportJobId::new(2), 1, ExportSettings::default(), 3);
+        assert!(engine.export_sequence(&mut video).is_err());
+        assert_eq!(video.progress().status, ExportStatus::Failed);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export_queue_runs_async() {
+        let dir = std::env::temp_dir().join(format!("evep_queue_run_{}", std::process::id()));
+        let sequence = |name: &str| ExportSettings {
+            container: ContainerFormat::ImageSequence(StillFormat::Png),
+            output_path: dir.join(name).to_string_lossy().into_owned(),
+            range: ExportRange::Span(TimePosition::from_secs(1), TimePosition::from_ms(1125)),
+            ..ExportSettings::default()
+        };
+        let mut queue = ExportQueue::new();
+        let first = queue.add_job(1, sequence("a_%03d.png"), 3);
+        let second = queue.add_job(1, sequence("b_%03d.png"), 3);
+
+        let renderer = Arc::new(FlatRenderer([10, 20, 30, 255]));
+        let (id, handle) = queue.run_async(renderer.clone()).expect("test assertion");
+        assert_eq!(id, first);
+        // One job at a time until the first is handed back
+        assert!(queue.run_async(renderer.clone()).is_none());
+        queue.finish_job(handle.wait().expect("test assertion"));
+        assert_eq!(queue.completed_jobs().len(), 1);
+
+        let written = queue.run_next(renderer.as_ref()).expect("test assertion");
+        assert_eq!(written.expect("test assertion").len(), 3);
+        let job = queue.get_job(second).expect("test assertion");
+        assert_eq!(job.progress().status, ExportStatus::Completed);
+        assert!(queue.run_next(renderer.as_ref()).is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// Takes a millisecond per frame, counting the frames rendered.
+    #[derive(Default)]
+    struct SlowRenderer(AtomicU64);
+
+    impl FrameRenderer for SlowRenderer {
+        fn resolution(&self) -> Resolution {
+            Resolution { width: 4, height: 2 }
+        }
+
+        fn frame_rate(&self) -> FrameRate {
+            FrameRate::FPS_24
+        }
+
+        fn render_frame(&self, _time: TimePosition) -> VideoEditorResult<Vec<u8>> {
+            std::thread::sleep(Duration::from_millis(1));
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Ok([0, 0, 0, 255].repeat(8))
+        }
+    }
+
+    #[test]
+    fn test_export_pause_resume_cancel() {
+        let dir = std::env::temp_dir().join(format!("evep_export_control_{}", std::process::id()));
+        let settings = ExportSettings {
+            container: ContainerFormat::ImageSequence(StillFormat::Png),
+            output_path: dir.join("long_%05d.png").to_string_lossy().into_owned(),
+            range: ExportRange::Span(TimePosition::from_secs(0), TimePosition::from_secs(600)),
+            ..ExportSettings::default()
+        };
+        let mut queue = ExportQueue::new();
+        let id = queue.add_job(1, settings, 14_400);
+        let renderer = Arc::new(SlowRenderer::default());
+        let rendered = || renderer.0.load(Ordering::Relaxed);
+        let wait_for = |count: u64| {
+            while rendered() < count {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        };
+
+        let (_, handle) = queue.run_async(renderer.clone()).expect("test assertion");
+        let control = handle.control();
+        wait_for(5);
+        control.pause();
+        std::thread::sleep(Duration::from_millis(20));
+        let held = rendered();
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(rendered(), held);
+
+        control.resume();
+        wait_for(held + 5);
+        let cancelled_at = Instant::now();
+        control.cancel();
+        queue.finish_job(handle.wait().expect("test assertion"));
+        assert!(cancelled_at.elapsed() < Duration::from_secs(1));
+        let job = queue.get_job(id).expect("test assertion");
+        assert_eq!(job.progress().status, ExportStatus::Cancelled);
+        assert!(job.progress().frames_encoded < 14_400);
+        assert!(queue.active_jobs().is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_burn_in_overlay() {
+        let rate = FrameRate::FPS_24;
+        let at = TimePosition::from_secs(1);
+        let overlay = BurnInOverlay {
+            timecode_offset: TimePosition::from_secs(3600),
+            ..BurnInOverlay::default()
+        };
+        assert_eq!(overlay.timecode_text(at, &rate), "01:00:01:00");
+        assert!(!overlay.is_empty());
+
+        // 64x16 frame: 1x font, timecode box at (4, 4) with 1 px padding
+        let pixel = |frame: &[u8], x: usize, y: usize| frame[(y * 64 + x) * 4];
+        let mut frame = [0, 0, 0, 255].repeat(64 * 16);
+        overlay.apply(&mut frame, 64, 16, at, &rate, &[]);
+        assert_eq!(pixel(&frame, 5, 5), 255);
+        assert_eq!(pixel(&frame, 4, 4), 0);
+        assert_eq!(pixel(&frame, 0, 0), 0);
+
+        let watermark = Watermark {
+            margin: 0,
+            ..Watermark::new([255, 0, 0, 255].repeat(4), 2, 2).expect("test assertion")
+        };
+        assert!(Watermark::new(vec![0; 3], 2, 2).is_none());
+        let names = BurnInOverlay {
+            timecode: None,
+            clip_names: Some(OverlayPosition::BottomLeft),
+            watermark: Some(watermark),
+            ..BurnInOverlay::default()
+        };
+        let mut frame = [100, 100, 100, 255].repeat(64 * 16);
+        names.apply(&mut frame, 64, 16, at, &rate, &["A".to_string()]);
+        assert_eq!(&frame[(15 * 64 + 63) * 4..][..3], &[178, 50, 50]);
+        // 'A' starts with its middle pixel; the name box sits at (4, 5)
+        assert_eq!(pixel(&frame, 6, 6), 255);
+        assert_eq!(pixel(&frame, 5, 6), 40);
+
+        // Review LUT inverts the picture before anything is drawn
+        let corners = (0..8)
+            .map(|i| {
+                let v = |bit: u32| if i & bit == 0 { 1.0 } else { 0.0 };
+                Color::rgb(v(1), v(2), v(4))
+            })
+            .collect();
+        let lut = BurnInOverlay {
+            timecode: None,
+            lut: Lut3D::from_data("invert", 2, corners),
+            ..BurnInOverlay::default()
+        };
+        let renderer = FlatRenderer([10, 20, 30, 255]);
+        let engine = ExportEngine::new(&renderer);
+        let graded = engine.render_with_overlay(at, Some(&lut)).expect("test assertion");
+        assert_eq!(&graded[..4], &[245, 235, 225, 255]);
+        let clean = engine.render_with_overlay(at, None).expect("test assertion");
+        assert_eq!(&clean[..4], &[10, 20, 30, 255]);
+    }
+
+    /// Flat grey for the first second, then a moving checkerboard.
+    struct BusyRenderer;
+
+    impl FrameRenderer for BusyRenderer {
+        fn resolution(&self) -> Resolution {
+            Resolution { width: 8, height: 8 }
+        }
+
+        fn frame_rate(&self) -> FrameRate {
+            FrameRate::FPS_24
+        }
+
+        fn render_frame(&self, time: TimePosition) -> VideoEditorResult<Vec<u8>> {
+            let frame = time.to_frame(&self.frame_rate()) as usize;
+            Ok((0..64)
+                .flat_map(|i| {
+                    let v =
+                        if frame < 24 || (i + i / 8 + frame).is_multiple_of(2) { 128 } else { 0 };
+                    [v, v, v, 255]
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_two_pass_encode() {
+        let dir = std::env::temp_dir().join(format!("evep_two_pass_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("test assertion");
+        let output = dir.join("out.mp4").to_string_lossy().into_owned();
+        let mut settings = ExportSettings {
+            output_path: output.clone(),
+            range: ExportRange::Span(TimePosition::from_ms(0), TimePosition::from_secs(2)),
+            ..ExportSettings::default()
+        };
+        settings.video.rate_control = RateControl::TwoPass;
+        settings.video.frame_rate = FrameRate::FPS_24;
+        settings.video.bitrate = 4800;
+        assert!(settings.is_two_pass());
+
+        let mut job = ExportJob::new(ExportJobId::new(1), 1, settings.clone(), 48);
+        let engine = ExportEngine::new(&BusyRenderer);
+        let stats = engine.run_first_pass(&mut job).expect("test assertion");
+        assert_eq!(stats.complexity.len(), 48);
+        assert!(stats.complexity[30] > stats.complexity[10] * 10.0);
+        assert_eq!(job.progress().status, ExportStatus::SecondPass);
+        assert!((job.progress().progress - FIRST_PASS_WEIGHT).abs() < 1e-9);
+
+        // Busy frames get more of the budget, which adds up to the target
+        let path = FirstPassStats::stats_path(&output);
+        let read = FirstPassStats::read(std::path::Path::new(&path)).expect("test assertion");
+        assert_eq!(read.complexity.len(), 48);
+        let bits: u64 = (0..48).map(|i| job.frame_bits(i).expect("test assertion")).sum();
+        assert!(bits.abs_diff(9_600_000) < 48);
+        let (quiet, busy) = (job.frame_bits(10), job.frame_bits(30));
+        assert!(busy.expect("test assertion") > quiet.expect("test assertion") * 3);
+        assert!(FirstPassStats::parse_log("frame=0 complexity=1").is_err());
+        assert!(FirstPassStats::parse_log("#two-pass v1 frames=2\nframe=0 complexity=1").is_err());
+
+        // Both passes through the scheduler; progress runs 0.3 then 1.0
+        let mut scheduler = GpuScheduler::new();
+        let mut job = ExportJob::new(ExportJobId::new(2), 1, settings, 48);
+        job.start();
+        let mut slice = scheduler.begin_slice(GpuPriority::Export);
+        assert_eq!(job.encode_slice(&mut scheduler, &mut slice, 0.1), 48);
+        assert_eq!(job.progress().status, ExportStatus::SecondPass);
+        assert_eq!(job.progress().pass, 2);
+        let mut slice = scheduler.begin_slice(GpuPriority::Export);
+        job.encode_slice(&mut scheduler, &mut slice, 0.1);
+        assert_eq!(job.progress().status, ExportStatus::Finalizing);
+        assert!((job.progress().progress - 1.0).abs() < 1e-9);
+
+        let mut single = ExportJob::new(ExportJobId::new(3), 1, ExportSettings::default(), 48);
+        assert!(engine.run_first_pass(&mut single).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_content_adaptive_bitrate() {
+        let secs = TimePosition::from_secs;
+        let settings = |from, to, adaptive| ExportSettings {
+            range: ExportRange::Span(secs(from), secs(to)),
+            adaptive: Some(adaptive),
+            ..ExportSettings::default()
+        };
+        let engine = ExportEngine::new(&BusyRenderer);
+        let analyze = |settings| {
+            let mut job = ExportJob::new(ExportJobId::new(1), 1, settings, 48);
+            engine.analyze_content(&mut job).expect("test assertion");
+            job
+        };
+
+        // Quiet content gets a higher CRF than busy content
+        let quiet = analyze(settings(0, 1, AdaptiveBitrate::default()));
+        let busy = analyze(settings(1, 3, AdaptiveBitrate::default()));
+        let (quiet_analysis, busy_analysis) = (
+            quiet.content_analysis().expect("test assertion"),
+            busy.content_analysis().expect("test assertion"),
+        );
+        assert_eq!(busy_analysis.samples, 16);
+        assert!(busy_analysis.mean_complexity > quiet_analysis.mean_complexity);
+        assert!(busy_analysis.recommended_kbps > quiet_analysis.recommended_kbps);
+        assert_eq!(quiet.settings().video.rate_control, RateControl::ConstantQuality);
+        assert!(quiet.settings().video.quality > busy.settings().video.quality);
+
+        // 2 s under 500 kB: VBR capped by the budget
+        let budget = analyze(settings(1, 3, AdaptiveBitrate::within_size(500_000)));
+        let analysis = budget.content_analysis().expect("test assertion");
+        assert!(!analysis.fits_budget());
+        assert_eq!(analysis.budget_kbps, Some(1768));
+        assert_eq!(budget.settings().video.bitrate, 1768);
+        assert_eq!(budget.settings().video.rate_control, RateControl::Vbr);
+
+        let roomy = analyze(settings(1, 3, AdaptiveBitrate::within_size(2 << 30)));
+        let analysis = roomy.content_analysis().expect("test assertion");
+        assert!(analysis.fits_budget());
+        assert_eq!(roomy.settings().video.bitrate, analysis.recommended_kbps);
+
+        let mut started =
+            ExportJob::new(ExportJobId::new(2), 1, settings(0, 1, AdaptiveBitrate::default()), 24);
+        started.start();
+        assert!(engine.analyze_content(&mut started).is_err());
+        let mut fixed = ExportJob::new(ExportJobId::new(3), 1, ExportSettings::default(), 24);
+        assert!(engine.analyze_content(&mut fixed).is_err());
+    }
+
+    #[test]
+    fn test_stream_packaging() {
+        let mut settings = ExportSettings {
+            output_path: "/srv/show/master.m3u8".into(),
+            ..ExportSettings::default()
+        };
+        settings.video.resolution = Resolution::new(1920, 800);
+        settings.video.bitrate = 5000;
+        let packaging = StreamPackaging::with_ladder(StreamingFormat::HlsFmp4, &settings.video);
+        let names: Vec<_> = packaging.renditions.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["720p", "480p", "360p"]);
+        settings.video.resolution = Resolution::new(1920, 1080);
+        let packaging = StreamPackaging::with_ladder(StreamingFormat::HlsFmp4, &settings.video);
+        assert_eq!(packaging.renditions.len(), 4);
+        assert_eq!(packaging.renditions[0].video.bitrate, 5000);
+        assert_eq!(packaging.renditions[3].video.resolution, Resolution::new(640, 360));
+
+        // 30 fps, 6 s segments; 15 s leaves a 3 s tail
+        let segments = packaging.plan_segments(450, FrameRate::new(30, 1));
+        assert_eq!(segments.len(), 3);
+        assert_eq!((segments[2].start_frame, segments[2].frames), (360, 90));
+
+        let encodes = packaging.rendition_settings(&settings);
+        assert_eq!(encodes[1].output_path, "/srv/show/720p/segment_%05d.m4s");
+        assert!(encodes.iter().all(|e| e.video.gop_size == 180 && e.packaging.is_none()));
+
+        let package = packaging.package(&settings, 450).expect("test assertion");
+        assert_eq!(package.manifests.len(), 5);
+        let master = &package.manifests[0].contents;
+        assert!(master.contains("BANDWIDTH=5192000,RESOLUTION=1920x1080"));
+        assert!(master.contains("CODECS=\"avc1.640028,mp4a.40.2\""));
+        let playlist = &package.manifests[2];
+        assert_eq!(playlist.path, "/srv/show/720p/index.m3u8");
+        assert!(playlist.contents.contains("#EXT-X-MAP:URI=\"init.mp4\""));
+        assert!(playlist.contents.contains("#EXTINF:3.000,\nsegment_00002.m4s"));
+        assert_eq!(package.segments[3][0], "/srv/show/360p/segment_00000.m4s");
+
+        let dash = StreamPackaging { format: StreamingFormat::Dash, ..packaging.clone() };
+        settings.output_path = "/srv/show/manifest.mpd".into();
+        let mpd = &dash.package(&settings, 450).expect("test assertion").manifests[0].contents;
+        assert!(mpd.contains("mediaPresentationDuration=\"PT15.000S\""));
+        assert!(mpd.contains("timescale=\"30\" duration=\"180\""));
+        assert_eq!(mpd.matches("<Representation ").count(), 4);
+
+        // MPEG-TS carries neither AV1 nor PCM audio
+        let mut ts = StreamPackaging { format: StreamingFormat::HlsTs, ..packaging };
+        ts.renditions[0].video.codec = VideoCodec::Av1;
+        assert!(ts.package(&settings, 450).is_err());
+        ts.renditions[0].video.codec = VideoCodec::H264;
+        settings.audio.codec = AudioCodec::Pcm;
+        assert!(ts.package(&settings, 450).is_err());
+
+        let dir = std::env::temp_dir().join(format!("evep_hls_{}", std::process::id()));
+        settings.audio.codec = AudioCodec::Aac;
+        settings.output_path = dir.join("master.m3u8").to_string_lossy().into_owned();
+        ts.package(&settings, 450).expect("test assertion").write().expect("test assertion");
+        assert!(dir.join("1080p").join("index.m3u8").is_file());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_audio_stems() {
+        let mut mixer = AudioMixer::new(48000, 1024);
+        let dialogue = mixer.create_group_bus("Dialogue");
+        let music = mixer.create_group_bus("Music & Score");
+        for (track, bus) in [(1, dialogue), (2, music), (3, dialogue)] {
+            let strip = mixer.add_track(track, format!("Track {track}")).expect("test assertion");
+            strip.set_output_bus(bus);
+        }
+        mixer.get_track_mut(3).expect("test assertion").set_muted(true);
+
+        let mut options = StemExportOptions::new(StemLayout::PerBus);
+        let stems = options.plan(&mixer, "/exports/reel1.mov");
+        assert_eq!(stems.len(), 2);
+        assert_eq!(stems[0].track_ids, [1, 3]);
+        assert_eq!(stems[1].path, "/exports/reel1_Music___Score.wav");
+
+        // Track 2 is twice as long; dialogue is padded to match
+        let audio = |track: u64| vec![0.5; 2 * 100 * track as usize];
+        let mixes = mix_stems(&mixer, &stems, &audio, 50);
+        assert_eq!(mixes[0].len(), 400);
+        assert_eq!(mixes[1].len(), 400);
+        assert!(mixes[0][198] > 0.3 && mixes[0][200] == 0.0);
+
+        options.layout = StemLayout::PerTrack;
+        options.name_template = "{index}-{stem}".into();
+        let stems = options.plan(&mixer, "reel1.mov");
+        assert_eq!(stems.len(), 3);
+        assert_eq!(stems[2].path, "03-Track_3.wav");
+
+        let wav = encode_wav(&mixes[1], 2, 48000, None).expect("test assertion");
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(wav.len(), 44 + 400 * 3);
+
+        let settings = ExportSettings {
+            stems: Some(StemExportOptions { stems_only: true, ..options }),
+            ..ExportSettings::default()
+        };
+        assert!(!settings.writes_main_mix());
+        assert!(ExportSettings::default().writes_main_mix());
+    }
+
+    #[test]
+    fn test_output_naming() {
+        let names = OutputNameContext::new("My Film", "Streaming HD", "2024-05-01");
+        let range = ExportRange::Span(TimePosition::from_secs(2), TimePosition::from_secs(75));
+        assert_eq!(
+            expand_output_template("/out/{project}_{preset}_{date}_{range}.mp4", &names, &range)
+                .expect("test assertion"),
+            "/out/My_Film_Streaming_HD_2024-05-01_00h00m02s-00h01m15s.mp4"
+        );
+        assert_eq!(
+            expand_output_template("{project}.mov", &names, &ExportRange::Entire)
+                .expect("test assertion"),
+            "My_Film.mov"
+        );
+        assert!(expand_output_template("{title}.mp4", &names, &range).is_err());
+        assert!(expand_output_template("{project.mp4", &names, &range).is_err());
+        assert!(expand_output_template("/out/", &names, &range).is_err());
+        let unnamed = OutputNameContext::default();
+        assert!(expand_output_template("{project}.mp4", &unnamed, &range).is_err());
+
+        // Names are final once queued; later jobs avoid queued paths
+        let mut queue = ExportQueue::new();
+        let mut settings = ExportSettings {
+            output_path: "/nonexistent/{project}.mp4".into(),
+            collision: CollisionPolicy::AutoIncrement,
+            ..ExportSettings::default()
+        };
+        let first = queue.add_named_job(1, settings.clone(), 100, &names).expect("test assertion");
+        let second = queue.add_named_job(1, settings.clone(), 100, &names).expect("test assertion");
+        let path = |queue: &ExportQueue, id| {
+            queue.get_job(id).expect("test assertion").settings().output_path.clone()
+        };
+        assert_eq!(path(&queue, first), "/nonexistent/My_Film.mp4");
+        assert_eq!(path(&queue, second), "/nonexistent/My_Film_2.mp4");
+
+        settings.collision = CollisionPolicy::Fail;
+        assert!(queue.add_named_job(1, settings.clone(), 100, &names).is_err());
+        settings.collision = CollisionPolicy::Overwrite;
+        let third = queue.add_named_job(1, settings.clone(), 100, &names).expect("test assertion");
+        assert_eq!(path(&queue, third), "/nonexistent/My_Film.mp4");
+
+        // A cancelled job frees its path
+        queue.cancel_job(first).expect("test assertion");
+        queue.cancel_job(third).expect("test assertion");
+        settings.collision = CollisionPolicy::Fail;
+        assert!(queue.add_named_job(1, settings, 100, &names).is_ok());
+
+        let part = ResolvedExportRange {
+            name:  Some("Act 1".into()),
+            start: TimePosition::from_secs(0),
+            end:   TimePosition::from_secs(1),
+        };
+        assert_eq!(part.output_path("/out/{range}_{project}.mp4"), "/out/Act_1_{project}.mp4");
+    }
 }
@@ -0,0 +1,161 @@
+//! Output file naming.
+//!
+//! Export paths may contain `{project}`, `{preset}`, `{date}` and
+//! `{range}` tokens. The queue expands them when a job is added and then
+//! applies the job's [`CollisionPolicy`] against files on disk and the
+//! outputs of jobs already queued, so every queued job shows the path it
+//! will be written to before encoding starts.
+
+use super::range::ExportRange;
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    types::TimePosition,
+};
+
+/// What to do when an export's output path is already taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CollisionPolicy {
+    /// Replace the existing file.
+    #[default]
+    Overwrite,
+    /// Append `_2`, `_3`, ... to the file name until it is free.
+    AutoIncrement,
+    /// Refuse to queue the job.
+    Fail,
+}
+
+/// Values of the output name tokens that come from the host.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputNameContext {
+    /// Project name, for `{project}`.
+    pub project: String,
+    /// Export preset name, for `{preset}`.
+    pub preset:  String,
+    /// Export date, for `{date}`, e.g. `2024-05-01`.
+    pub date:    String,
+}
+
+impl OutputNameContext {
+    /// Creates a naming context.
+    pub fn new(
+        project: impl Into<String>, preset: impl Into<String>, date: impl Into<String>,
+    ) -> Self {
+        Self { project: project.into(), preset: preset.into(), date: date.into() }
+    }
+}
+
+/// Expands the tokens of an output path template.
+///
+/// Token values are sanitized for use in file names. `{range}` is the
+/// export range as `00h00m02s-00h00m10s`, or `full` for the entire
+/// timeline.
+///
+/// # Errors
+///
+/// Returns `VideoEditorError::Export` for an unknown or unclosed token, a
+/// token without a value, or an empty file name.
+pub fn expand_output_template(
+    template: &str, context: &OutputNameContext, range: &ExportRange,
+) -> VideoEditorResult<String> {
+    let mut expanded = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        expanded.push_str(&rest[..open]);
+        let close = rest[open..].find('}').ok_or_else(|| {
+            VideoEditorError::Export(format!("Unclosed token in output name: {template}"))
+        })? + open;
+        let token = &rest[open + 1..close];
+        let value = match token {
+            "project" => context.project.clone(),
+            "preset" => context.preset.clone(),
+            "date" => context.date.clone(),
+            "range" => range_label(range),
+            _ => {
+                return Err(VideoEditorError::Export(format!(
+                    "Unknown output name token: {{{token}}}"
+                )));
+            },
+        };
+        if value.is_empty() {
+            return Err(VideoEditorError::Export(format!(
+                "Output name token {{{token}}} has no value"
+            )));
+        }
+        expanded.push_str(&sanitize_file_name(&value));
+        rest = &rest[close + 1..];
+    }
+    expanded.push_str(rest);
+
+    if file_name_start(&expanded) == expanded.len() {
+        return Err(VideoEditorError::Export(format!("Output path has no file name: {template}")));
+    }
+    Ok(expanded)
+}
+
+/// Applies a collision policy to an output path, where `taken` reports
+/// whether a path is already in use.
+///
+/// # Errors
+///
+/// Returns `VideoEditorError::Export` if the path is taken under
+/// [`CollisionPolicy::Fail`].
+pub fn resolve_collision(
+    path: &str, policy: CollisionPolicy, taken: impl Fn(&str) -> bool,
+) -> VideoEditorResult<String> {
+    match policy {
+        CollisionPolicy::Overwrite => Ok(path.to_string()),
+        CollisionPolicy::Fail if taken(path) => {
+            Err(VideoEditorError::Export(format!("Output already exists: {path}")))
+        },
+        CollisionPolicy::Fail => Ok(path.to_string()),
+        CollisionPolicy::AutoIncrement => {
+            if !taken(path) {
+                return Ok(path.to_string());
+            }
+            let (stem, extension) = split_extension(path);
+            Ok((2..)
+                .map(|n| format!("{stem}_{n}{extension}"))
+                .find(|p| !taken(p))
+                .unwrap_or_else(|| path.to_string()))
+        },
+    }
+}
+
+/// Replaces characters other than letters, digits, `-` and `_` with `_`.
+pub(super) fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// Splits a path before the extension of its file name, keeping the dot
+/// with the extension.
+pub(super) fn split_extension(path: &str) -> (&str, &str) {
+    let name_start = file_name_start(path);
+    match path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => path.split_at(name_start + dot),
+        _ => (path, ""),
+    }
+}
+
+/// Byte offset of the file name in a path.
+fn file_name_start(path: &str) -> usize {
+    path.rfind(['/', '\\']).map_or(0, |i| i + 1)
+}
+
+/// File name label of an export range.
+fn range_label(range: &ExportRange) -> String {
+    let time = |t: TimePosition| {
+        let secs = t.ms / 1000;
+        format!("{:02}h{:02}m{:02}s", secs / 3600, secs / 60 % 60, secs % 60)
+    };
+    match range {
+        ExportRange::Entire => "full".into(),
+        ExportRange::Span(start, end) => format!("{}-{}", time(*start), time(*end)),
+        ExportRange::InOut => "in-out".into(),
+        ExportRange::Selection => "selection".into(),
+        ExportRange::BetweenMarkers(..) => "markers".into(),
+        ExportRange::Chapter(_) => "chapter".into(),
+        ExportRange::Multiple(_) => "batch".into(),
+    }
+}
@@ -0,0 +1,340 @@
+//! Burn-in overlays for review exports.
+//!
+//! [`BurnInOverlay`] composites SMPTE timecode, the names of the clips
+//! under the playhead, a watermark image and a review LUT onto rendered
+//! frames as they are exported. The timeline is never touched: the
+//! overlay only exists in the exported pixels.
+
+use essentia_color_types::Color;
+
+use crate::{
+    implementation::color_grading::Lut3D,
+    types::{FrameRate, TimePosition},
+};
+
+/// Glyph width in font pixels.
+const GLYPH_WIDTH: u32 = 3;
+
+/// Glyph height in font pixels.
+const GLYPH_HEIGHT: u32 = 5;
+
+/// Opacity of the box behind burned-in text.
+const TEXT_BACKING_OPACITY: f32 = 0.6;
+
+/// Corner or center of the frame an overlay element is anchored to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OverlayPosition {
+    /// Top-left corner.
+    TopLeft,
+    /// Top-right corner.
+    TopRight,
+    /// Bottom-left corner.
+    BottomLeft,
+    /// Bottom-right corner.
+    #[default]
+    BottomRight,
+    /// Frame center.
+    Center,
+}
+
+impl OverlayPosition {
+    /// Top-left pixel of a `size` box placed `margin` pixels in from the
+    /// anchor of a `frame`.
+    #[must_use]
+    pub fn place(self, size: (u32, u32), frame: (u32, u32), margin: u32) -> (i64, i64) {
+        let (w, h) = (i64::from(size.0), i64::from(size.1));
+        let (fw, fh) = (i64::from(frame.0), i64::from(frame.1));
+        let margin = i64::from(margin);
+        match self {
+            Self::TopLeft => (margin, margin),
+            Self::TopRight => (fw - w - margin, margin),
+            Self::BottomLeft => (margin, fh - h - margin),
+            Self::BottomRight => (fw - w - margin, fh - h - margin),
+            Self::Center => ((fw - w) / 2, (fh - h) / 2),
+        }
+    }
+}
+
+/// Watermark image composited over every frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watermark {
+    /// Straight-alpha RGBA8 pixels.
+    pub pixels:   Vec<u8>,
+    /// Image width.
+    pub width:    u32,
+    /// Image height.
+    pub height:   u32,
+    /// Opacity, multiplied with the image alpha (0.0 to 1.0).
+    pub opacity:  f32,
+    /// Anchor in the frame.
+    pub position: OverlayPosition,
+    /// Distance from the frame edges in pixels.
+    pub margin:   u32,
+}
+
+impl Watermark {
+    /// Creates a watermark at 50% opacity in the bottom-right corner.
+    /// Returns `None` if the pixels don't match the size.
+    #[must_use]
+    pub fn new(pixels: Vec<u8>, width: u32, height: u32) -> Option<Self> {
+        (pixels.len() == width as usize * height as usize * 4).then_some(Self {
+            pixels,
+            width,
+            height,
+            opacity: 0.5,
+            position: OverlayPosition::BottomRight,
+            margin: 16,
+        })
+    }
+}
+
+/// Burn-in overlay options for an export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BurnInOverlay {
+    /// Where SMPTE timecode is drawn (None = no timecode).
+    pub timecode:        Option<OverlayPosition>,
+    /// Timecode of the first timeline frame, e.g. 01:00:00:00.
+    pub timecode_offset: TimePosition,
+    /// Where the names of the clips under the playhead are drawn (None =
+    /// no clip names).
+    pub clip_names:      Option<OverlayPosition>,
+    /// Watermark image.
+    pub watermark:       Option<Watermark>,
+    /// Review LUT applied to the picture before text and watermark.
+    pub lut:             Option<Lut3D>,
+    /// Font pixel size in frame pixels (0 = scale with frame height).
+    pub text_scale:      u32,
+}
+
+impl Default for BurnInOverlay {
+    fn default() -> Self {
+        Self {
+            timecode:        Some(OverlayPosition::TopLeft),
+            timecode_offset: TimePosition::from_ms(0),
+            clip_names:      None,
+            watermark:       None,
+            lut:             None,
+            text_scale:      0,
+        }
+    }
+}
+
+impl BurnInOverlay {
+    /// Returns whether the overlay changes any pixels.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.timecode.is_none()
+            && self.clip_names.is_none()
+            && self.watermark.is_none()
+            && self.lut.is_none()
+    }
+
+    /// Timecode text burned into the frame at a timeline time.
+    #[must_use]
+    pub fn timecode_text(&self, time: TimePosition, rate: &FrameRate) -> String {
+        TimePosition::from_ms(time.ms + self.timecode_offset.ms).to_timecode(rate)
+    }
+
+    /// Composites the overlay onto an RGBA8 frame rendered at `time`:
+    /// the LUT first, then the watermark, then timecode and clip names.
+    pub fn apply(
+        &self, pixels: &mut [u8], width: u32, height: u32, time: TimePosition, rate: &FrameRate,
+        clip_names: &[String],
+    ) {
+        if pixels.len() != width as usize * height as usize * 4 {
+            return;
+        }
+        if let Some(lut) = &self.lut {
+            for pixel in pixels.chunks_exact_mut(4) {
+                let [r, g, b] = [0, 1, 2].map(|i| f32::from(pixel[i]) / 255.0);
+                let graded = lut.apply(&Color::new(r, g, b, 1.0));
+                for (out, v) in pixel.iter_mut().zip([graded.r, graded.g, graded.b]) {
+                    *out = (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+                }
+            }
+        }
+        let mut frame = Canvas { pixels, width, height };
+        if let Some(watermark) = &self.watermark {
+            frame.draw_watermark(watermark);
+        }
+
+        let scale = self.font_scale(height);
+        let margin = scale * 4;
+        if let Some(position) = self.timecode {
+            frame.draw_text(&self.timecode_text(time, rate), position, scale, margin);
+        }
+        if let Some(position) = self.clip_names
+            && !clip_names.is_empty()
+        {
+            frame.draw_text(&clip_names.join(" / "), position, scale, margin);
+        }
+    }
+
+    /// Frame pixels per font pixel.
+    fn font_scale(&self, height: u32) -> u32 {
+        if self.text_scale > 0 { self.text_scale } else { (height / 180).max(1) }
+    }
+}
+
+/// RGBA8 frame being composited.
+struct Canvas<'a> {
+    pixels: &'a mut [u8],
+    width:  u32,
+    height: u32,
+}
+
+impl Canvas<'_> {
+    /// Blends `color` over the pixel at `(x, y)`, ignoring pixels outside
+    /// the frame.
+    fn blend(&mut self, x: i64, y: i64, color: [u8; 3], alpha: f32) {
+        if x < 0 || y < 0 || x >= i64::from(self.width) || y >= i64::from(self.height) {
+            return;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        for (out, c) in self.pixels[i..i + 3].iter_mut().zip(color) {
+            *out = (f32::from(*out) * (1.0 - alpha) + f32::from(c) * alpha).round() as u8;
+        }
+    }
+
+    fn draw_watermark(&mut self, watermark: &Watermark) {
+        let (x0, y0) = watermark.position.place(
+            (watermark.width, watermark.height),
+            (self.width, self.height),
+            watermark.margin,
+        );
+        let opacity = watermark.opacity.clamp(0.0, 1.0);
+        for (i, p) in watermark.pixels.chunks_exact(4).enumerate() {
+            let (x, y) = (i as u32 % watermark.width, i as u32 / watermark.width);
+            let alpha = f32::from(p[3]) / 255.0 * opacity;
+            if alpha > 0.0 {
+                self.blend(x0 + i64::from(x), y0 + i64::from(y), [p[0], p[1], p[2]], alpha);
+            }
+        }
+    }
+
+    /// Draws white text on a translucent black box.
+    fn draw_text(&mut self, text: &str, position: OverlayPosition, scale: u32, margin: u32) {
+        let size = text_box_size(text, scale);
+        let (x0, y0) = position.place(size, (self.width, self.height), margin);
+        self.draw_text_at(text, x0, y0, scale);
+    }
+
+    /// Draws white text on a translucent black box whose top-left corner
+    /// is at `(x0, y0)`.
+    fn draw_text_at(&mut self, text: &str, x0: i64, y0: i64, scale: u32) {
+        let size = text_box_size(text, scale);
+        if size.0 == 0 {
+            return;
+        }
+        let pad = scale;
+        let advance = (GLYPH_WIDTH + 1) * scale;
+        for y in 0..size.1 {
+            for x in 0..size.0 {
+                self.blend(x0 + i64::from(x), y0 + i64::from(y), [0; 3], TEXT_BACKING_OPACITY);
+            }
+        }
+        for (n, c) in text.chars().enumerate() {
+            let rows = glyph(c);
+            let left = x0 + i64::from(pad + n as u32 * advance);
+            for (row, bits) in rows.iter().enumerate() {
+                for col in 0..GLYPH_WIDTH {
+                    if bits & (0b100 >> col) == 0 {
+                        continue;
+                    }
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            self.blend(
+                                left + i64::from(col * scale + dx),
+                                y0 + i64::from(pad + row as u32 * scale + dy),
+                                [255; 3],
+                                1.0,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Size of the box [`Canvas::draw_text`] draws for `text`, or zero for
+/// no text.
+fn text_box_size(text: &str, scale: u32) -> (u32, u32) {
+    let chars = text.chars().count() as u32;
+    if chars == 0 {
+        return (0, 0);
+    }
+    let (pad, advance) = (scale, (GLYPH_WIDTH + 1) * scale);
+    (chars * advance - scale + 2 * pad, GLYPH_HEIGHT * scale + 2 * pad)
+}
+
+/// Draws lines of text centered in an RGBA8 frame, one below the other,
+/// e.g. for slates. The font scales with the frame height.
+pub(crate) fn draw_centered_lines(pixels: &mut [u8], width: u32, height: u32, lines: &[&str]) {
+    if pixels.len() != width as usize * height as usize * 4 {
+        return;
+    }
+    let scale = (height / 120).max(1);
+    let line_height = i64::from((GLYPH_HEIGHT + 4) * scale);
+    let mut y = (i64::from(height) - line_height * lines.len() as i64) / 2;
+    let mut frame = Canvas { pixels, width, height };
+    for line in lines {
+        let (w, _) = text_box_size(line, scale);
+        frame.draw_text_at(line, (i64::from(width) - i64::from(w)) / 2, y, scale);
+        y += line_height;
+    }
+}
+
+/// 3x5 bitmap of a character, one row per byte with the leftmost pixel
+/// in bit 2. Letters are drawn in upper case; unknown characters as `?`.
+fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        ' ' => [0; 5],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        ';' => [0b000, 0b010, 0b000, 0b010, 0b100],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '(' => [0b010, 0b100, 0b100, 0b100, 0b010],
+        ')' => [0b010, 0b001, 0b001, 0b001, 0b010],
+        _ => [0b111, 0b001, 0b010, 0b000, 0b010],
+    }
+}
@@ -0,0 +1,396 @@
+//! Adaptive streaming packaging.
+//!
+//! [`StreamPackaging`] turns one export into an HLS or DASH set: the
+//! export is encoded once per rendition of a ladder, with keyframes forced
+//! on segment boundaries so that every rendition cuts at the same frames,
+//! and the manifests tie the renditions together. The manifest is written
+//! at the job's output path and each rendition's playlist and segments go
+//! into a directory named after the rendition.
+
+use std::path::Path;
+
+use super::formats::{
+    AudioCodec, ContainerFormat, ExportSettings, RateControl, VideoCodec, VideoEncodingSettings,
+};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    trace::{self, TracePhase},
+    types::{FrameRate, Resolution},
+};
+
+/// Streaming package format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StreamingFormat {
+    /// HLS with MPEG-TS segments.
+    HlsTs,
+    /// HLS with fragmented MP4 segments.
+    #[default]
+    HlsFmp4,
+    /// MPEG-DASH with fragmented MP4 segments.
+    Dash,
+}
+
+impl StreamingFormat {
+    /// Returns the segment file extension.
+    #[must_use]
+    pub const fn segment_extension(&self) -> &'static str {
+        match self {
+            Self::HlsTs => "ts",
+            Self::HlsFmp4 | Self::Dash => "m4s",
+        }
+    }
+
+    /// Returns whether segments share an initialization segment.
+    #[must_use]
+    pub const fn has_init_segment(&self) -> bool {
+        !matches!(self, Self::HlsTs)
+    }
+}
+
+/// One quality level of a streaming ladder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rendition {
+    /// Name, also the directory of its segments (e.g. "720p").
+    pub name:  String,
+    /// Video settings of the rendition.
+    pub video: VideoEncodingSettings,
+}
+
+/// A segment of the packaged timeline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamSegment {
+    /// Segment number, from 0.
+    pub index:       u64,
+    /// First output frame.
+    pub start_frame: u64,
+    /// Frame count.
+    pub frames:      u64,
+    /// Duration in seconds.
+    pub duration:    f64,
+}
+
+/// A manifest or playlist of a package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageFile {
+    /// Path of the file.
+    pub path:     String,
+    /// File contents.
+    pub contents: String,
+}
+
+/// Manifests and expected segment files of a streaming package.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamPackage {
+    /// Master manifest first, then rendition playlists.
+    pub manifests: Vec<PackageFile>,
+    /// Segment paths per rendition, in ladder order.
+    pub segments:  Vec<Vec<String>>,
+}
+
+impl StreamPackage {
+    /// Writes the manifests, creating rendition directories.
+    pub fn write(&self) -> VideoEditorResult<()> {
+        for file in &self.manifests {
+            let _span = trace::span(TracePhase::Mux, &file.path);
+            if let Some(parent) = Path::new(&file.path).parent() {
+                std::fs::create_dir_all(parent).map_err(|e| VideoEditorError::io(parent, e))?;
+            }
+            std::fs::write(&file.path, &file.contents)
+                .map_err(|e| VideoEditorError::io(&file.path, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// HLS/DASH packaging options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamPackaging {
+    /// Package format.
+    pub format:          StreamingFormat,
+    /// Target segment length in seconds.
+    pub segment_seconds: f64,
+    /// Renditions, highest quality first.
+    pub renditions:      Vec<Rendition>,
+}
+
+impl StreamPackaging {
+    /// Heights and bitrates (kbps) of the standard ladder.
+    pub const LADDER: [(u32, u32); 5] =
+        [(2160, 16000), (1080, 6000), (720, 3000), (480, 1200), (360, 700)];
+
+    /// Package with the standard ladder rungs no taller than `source`,
+    /// scaled to its aspect ratio and capped at its bitrate, and 6 s
+    /// segments.
+    #[must_use]
+    pub fn with_ladder(format: StreamingFormat, source: &VideoEncodingSettings) -> Self {
+        let renditions = Self::LADDER
+            .iter()
+            .filter(|(height, _)| *height <= source.resolution.height)
+            .map(|&(height, bitrate)| {
+                let mut resolution = source.resolution.scaled_to_height(height);
+                // Chroma subsampling needs even dimensions
+                resolution.width += resolution.width % 2;
+                Rendition {
+                    name:  format!("{height}p"),
+                    video: VideoEncodingSettings {
+                        resolution,
+                        bitrate: bitrate.min(source.bitrate),
+                        rate_control: RateControl::Vbr,
+                        ..source.clone()
+                    },
+                }
+            })
+            .collect();
+        Self { format, segment_seconds: 6.0, renditions }
+    }
+
+    /// Frames per segment at a frame rate, at least 1.
+    #[must_use]
+    pub fn segment_frames(&self, rate: FrameRate) -> u64 {
+        (self.segment_seconds * rate.as_f64()).round().max(1.0) as u64
+    }
+
+    /// Splits `total_frames` into segments; the last one may be shorter.
+    #[must_use]
+    pub fn plan_segments(&self, total_frames: u64, rate: FrameRate) -> Vec<StreamSegment> {
+        let length = self.segment_frames(rate);
+        (0..total_frames.div_ceil(length))
+            .map(|index| {
+                let start_frame = index * length;
+                let frames = length.min(total_frames - start_frame);
+                StreamSegment {
+                    index,
+                    start_frame,
+                    frames,
+                    duration: frames as f64 / rate.as_f64(),
+                }
+            })
+            .collect()
+    }
+
+    /// Checks the ladder can be packaged.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Export` for an empty ladder, a codec the
+    /// format can't carry, a rendition frame rate that differs from the
+    /// export, or audio that can't be streamed.
+    pub fn validate(&self, settings: &ExportSettings) -> VideoEditorResult<()> {
+        if self.renditions.is_empty() {
+            return Err(VideoEditorError::Export("Streaming ladder is empty".into()));
+        }
+        if self.segment_seconds <= 0.0 {
+            return Err(VideoEditorError::Export("Segment length must be positive".into()));
+        }
+        for rendition in &self.renditions {
+            let codec = rendition.video.codec;
+            let supported = match self.format {
+                StreamingFormat::HlsTs => matches!(codec, VideoCodec::H264 | VideoCodec::H265),
+                _ => video_codec_tag(&rendition.video).is_some(),
+            };
+            if !supported {
+                return Err(VideoEditorError::Export(format!(
+                    "Rendition {} uses {codec:?}, which {:?} can't carry",
+                    rendition.name, self.format
+                )));
+            }
+            if rendition.video.frame_rate != settings.video.frame_rate {
+                return Err(VideoEditorError::Export(format!(
+                    "Rendition {} frame rate differs from the export",
+                    rendition.name
+                )));
+            }
+        }
+        if audio_codec_tag(settings.audio.codec).is_none() {
+            return Err(VideoEditorError::Export(format!(
+                "{:?} audio can't be streamed",
+                settings.audio.codec
+            )));
+        }
+        Ok(())
+    }
+
+    /// Encode settings of each rendition: the export settings with the
+    /// rendition's video, one keyframe per segment and the segment files
+    /// as output pattern (`720p/segment_%05d.m4s`).
+    pub fn rendition_settings(&self, settings: &ExportSettings) -> Vec<ExportSettings> {
+        let base = manifest_dir(&settings.output_path);
+        let segment_frames = self.segment_frames(settings.video.frame_rate);
+        self.renditions
+            .iter()
+            .map(|rendition| ExportSettings {
+                container: match self.format {
+                    StreamingFormat::HlsTs => ContainerFormat::MpegTs,
+                    _ => ContainerFormat::Mp4,
+                },
+                video: VideoEncodingSettings {
+                    gop_size: segment_frames as u32,
+                    ..rendition.video.clone()
+                },
+                output_path: format!(
+                    "{base}{}/segment_%05d.{}",
+                    rendition.name,
+                    self.format.segment_extension()
+                ),
+                packaging: None,
+                ..settings.clone()
+            })
+            .collect()
+    }
+
+    /// Builds the manifests for an export of `total_frames` frames.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Export` if [`validate`](Self::validate)
+    /// fails.
+    pub fn package(
+        &self, settings: &ExportSettings, total_frames: u64,
+    ) -> VideoEditorResult<StreamPackage> {
+        self.validate(settings)?;
+        let rate = settings.video.frame_rate;
+        let segments = self.plan_segments(total_frames, rate);
+        let base = manifest_dir(&settings.output_path);
+        let extension = self.format.segment_extension();
+        let segment_paths = self
+            .renditions
+            .iter()
+            .map(|r| {
+                segments
+                    .iter()
+                    .map(|s| format!("{base}{}/segment_{:05}.{extension}", r.name, s.index))
+                    .collect()
+            })
+            .collect();
+
+        let manifests = match self.format {
+            StreamingFormat::Dash => vec![PackageFile {
+                path:     settings.output_path.clone(),
+                contents: self.dash_manifest(settings, &segments),
+            }],
+            StreamingFormat::HlsTs | StreamingFormat::HlsFmp4 => {
+                let mut files = vec![PackageFile {
+                    path:     settings.output_path.clone(),
+                    contents: self.hls_master(settings),
+                }];
+                files.extend(self.renditions.iter().map(|r| PackageFile {
+                    path:     format!("{base}{}/index.m3u8", r.name),
+                    contents: self.hls_playlist(&segments),
+                }));
+                files
+            },
+        };
+        Ok(StreamPackage { manifests, segments: segment_paths })
+    }
+
+    fn hls_version(&self) -> u32 {
+        if self.format.has_init_segment() { 7 } else { 3 }
+    }
+
+    fn hls_master(&self, settings: &ExportSettings) -> String {
+        let audio = audio_codec_tag(settings.audio.codec).unwrap_or_default();
+        let mut m3u8 = format!("#EXTM3U\n#EXT-X-VERSION:{}\n", self.hls_version());
+        m3u8.push_str("#EXT-X-INDEPENDENT-SEGMENTS\n");
+        for rendition in &self.renditions {
+            let video = &rendition.video;
+            let Resolution { width, height } = video.resolution;
+            m3u8.push_str(&format!(
+                "#EXT-X-STREAM-INF:BANDWIDTH={},RESOLUTION={width}x{height},CODECS=\"{},{audio}\",FRAME-RATE={:.3}\n{}/index.m3u8\n",
+                bandwidth(video.bitrate, settings.audio.bitrate),
+                video_codec_tag(video).unwrap_or_default(),
+                video.frame_rate.as_f64(),
+                rendition.name
+            ));
+        }
+        m3u8
+    }
+
+    fn hls_playlist(&self, segments: &[StreamSegment]) -> String {
+        let target = segments.iter().map(|s| s.duration.ceil() as u64).max().unwrap_or(0);
+        let mut m3u8 = format!(
+            "#EXTM3U\n#EXT-X-VERSION:{}\n#EXT-X-TARGETDURATION:{target}\n#EXT-X-MEDIA-SEQUENCE:0\n#EXT-X-PLAYLIST-TYPE:VOD\n",
+            self.hls_version()
+        );
+        if self.format.has_init_segment() {
+            m3u8.push_str("#EXT-X-MAP:URI=\"init.mp4\"\n");
+        }
+        let extension = self.format.segment_extension();
+        for segment in segments {
+            m3u8.push_str(&format!(
+                "#EXTINF:{:.3},\nsegment_{:05}.{extension}\n",
+                segment.duration, segment.index
+            ));
+        }
+        m3u8.push_str("#EXT-X-ENDLIST\n");
+        m3u8
+    }
+
+    fn dash_manifest(&self, settings: &ExportSettings, segments: &[StreamSegment]) -> String {
+        let rate = settings.video.frame_rate;
+        let duration: f64 = segments.iter().map(|s| s.duration).sum();
+        let audio = audio_codec_tag(settings.audio.codec).unwrap_or_default();
+        let mut mpd = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        mpd.push_str(&format!(
+            "<MPD xmlns=\"urn:mpeg:dash:schema:mpd:2011\" type=\"static\" profiles=\"urn:mpeg:dash:profile:isoff-on-demand:2011\" mediaPresentationDuration=\"PT{duration:.3}S\" minBufferTime=\"PT{:.1}S\">\n",
+            self.segment_seconds
+        ));
+        mpd.push_str("  <Period id=\"0\" start=\"PT0S\">\n");
+        mpd.push_str("    <AdaptationSet contentType=\"video\" segmentAlignment=\"true\">\n");
+        mpd.push_str(&format!(
+            "      <SegmentTemplate timescale=\"{}\" duration=\"{}\" startNumber=\"0\" initialization=\"$RepresentationID$/init.mp4\" media=\"$RepresentationID$/segment_$Number%05d$.m4s\"/>\n",
+            rate.numerator,
+            self.segment_frames(rate) * u64::from(rate.denominator)
+        ));
+        for rendition in &self.renditions {
+            let video = &rendition.video;
+            mpd.push_str(&format!(
+                "      <Representation id=\"{}\" bandwidth=\"{}\" width=\"{}\" height=\"{}\" frameRate=\"{}/{}\" codecs=\"{},{audio}\"/>\n",
+                rendition.name,
+                bandwidth(video.bitrate, settings.audio.bitrate),
+                video.resolution.width,
+                video.resolution.height,
+                rate.numerator,
+                rate.denominator,
+                video_codec_tag(video).unwrap_or_default()
+            ));
+        }
+        mpd.push_str("    </AdaptationSet>\n  </Period>\n</MPD>\n");
+        mpd
+    }
+}
+
+/// Directory prefix of the manifest path, with trailing separator.
+fn manifest_dir(output_path: &str) -> &str {
+    output_path.rfind(['/', '\\']).map_or("", |i| &output_path[..=i])
+}
+
+/// Peak bandwidth in bits per second.
+fn bandwidth(video_kbps: u32, audio_kbps: u32) -> u64 {
+    (u64::from(video_kbps) + u64::from(audio_kbps)) * 1000
+}
+
+/// RFC 6381 codec string of a video stream.
+fn video_codec_tag(video: &VideoEncodingSettings) -> Option<&'static str> {
+    let tall = video.resolution.height > 1080;
+    match video.codec {
+        VideoCodec::H264 if tall => Some("avc1.640033"),
+        VideoCodec::H264 => Some("avc1.640028"),
+        VideoCodec::H265 if tall => Some("hvc1.1.6.L153.90"),
+        VideoCodec::H265 => Some("hvc1.1.6.L120.90"),
+        VideoCodec::Vp9 => Some("vp09.00.40.08"),
+        VideoCodec::Av1 => Some("av01.0.08M.08"),
+        _ => None,
+    }
+}
+
+/// RFC 6381 codec string of an audio stream.
+fn audio_codec_tag(codec: AudioCodec) -> Option<&'static str> {
+    match codec {
+        AudioCodec::Aac => Some("mp4a.40.2"),
+        AudioCodec::Ac3 => Some("ac-3"),
+        AudioCodec::Eac3 => Some("ec-3"),
+        AudioCodec::Opus => Some("opus"),
+        AudioCodec::Flac => Some("fLaC"),
+        _ => None,
+    }
+}
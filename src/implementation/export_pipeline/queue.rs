@@ -1,20 +1,29 @@
 //! Export queue manager and presets.
 
-use crate::errors::{VideoEditorError, VideoEditorResult};
-use crate::types::Resolution;
-
-use super::formats::{
-    AudioCodec, AudioEncodingSettings, ContainerFormat, EncodingPreset, ExportJobId,
-    ExportSettings, ExportStatus, PixelFormat, ProResProfile, RateControl, VideoCodec,
-    VideoEncodingSettings,
+use std::{path::Path, sync::Arc};
+
+use super::{
+    engine::{ExportEngine, FrameRenderer},
+    formats::{
+        AudioCodec, AudioEncodingSettings, ContainerFormat, EncodingPreset, ExportJobId,
+        ExportSettings, ExportStatus, LoudnessTarget, PixelFormat, ProResProfile, RateControl,
+        VideoCodec, VideoEncodingSettings,
+    },
+    job::ExportJob,
+    naming::{OutputNameContext, expand_output_template, resolve_collision},
+    seamless_loop::SeamlessLoop,
+};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    implementation::{id_index::IdVec, render::RenderDeterminism},
+    tasks::{CancellationToken, TaskHandle, TaskPool},
+    types::{FrameBufferPool, FrameRate, Resolution},
 };
-use super::job::ExportJob;
-use crate::types::FrameRate;
 
 /// Export queue manager.
 pub struct ExportQueue {
-    /// All export jobs.
-    jobs:           Vec<ExportJob>,
+    /// All export jobs, in queue order.
+    jobs:           IdVec<ExportJobId, ExportJob>,
     /// Next job ID.
     next_id:        u64,
     /// Currently encoding job.
@@ -23,6 +32,10 @@ pub struct ExportQueue {
     max_concurrent: usize,
     /// Active job count.
     active_count:   usize,
+    /// Run one job at a time in queue order.
+    ordered:        bool,
+    /// Buffers jobs render into.
+    frame_pool:     FrameBufferPool,
 }
 
 impl ExportQueue {
@@ -30,11 +43,13 @@ impl ExportQueue {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            jobs:           Vec::new(),
+            jobs:           IdVec::new(),
             next_id:        1,
             current:        None,
             max_concurrent: 1,
             active_count:   0,
+            ordered:        false,
+            frame_pool:     FrameBufferPool::default(),
         }
     }
 
@@ -51,42 +66,65 @@ impl ExportQueue {
     ) -> ExportJobId {
         let id = self.next_id();
         let job = ExportJob::new(id, project_id, settings, total_frames);
-        self.jobs.push(job);
+        self.jobs.push(id, job);
         id
     }
 
+    /// Adds a job after expanding the name template in its output path
+    /// and applying its collision policy, so the queued job carries its
+    /// final output path.
+    ///
+    /// Paths count as taken if the file exists or another job that has
+    /// not failed or been cancelled writes to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Export` if the template is invalid or
+    /// the path is taken under `CollisionPolicy::Fail`.
+    pub fn add_named_job(
+        &mut self, project_id: u64, mut settings: ExportSettings, total_frames: u64,
+        names: &OutputNameContext,
+    ) -> VideoEditorResult<ExportJobId> {
+        let path = expand_output_template(&settings.output_path, names, &settings.range)?;
+        settings.output_path = resolve_collision(&path, settings.collision, |p| {
+            Path::new(p).exists()
+                || self.jobs.iter().any(|j| {
+                    j.settings().output_path == p
+                        && !matches!(
+                            j.progress().status,
+                            ExportStatus::Failed | ExportStatus::Cancelled
+                        )
+                })
+        })?;
+        Ok(self.add_job(project_id, settings, total_frames))
+    }
+
     /// Removes a job from the queue.
     pub fn remove_job(&mut self, id: ExportJobId) -> bool {
-        if let Some(pos) = self.jobs.iter().position(|j| j.id() == id) {
-            let job = &self.jobs[pos];
-            // Can't remove active jobs
-            if !job.progress().is_complete()
-                && !matches!(job.progress().status, ExportStatus::Queued)
-            {
-                return false;
-            }
-            self.jobs.remove(pos);
-            true
-        } else {
-            false
+        let Some(job) = self.jobs.get(id) else {
+            return false;
+        };
+        // Can't remove active jobs
+        if !job.progress().is_complete() && !matches!(job.progress().status, ExportStatus::Queued) {
+            return false;
         }
+        self.jobs.remove(id).is_some()
     }
 
     /// Gets a job by ID.
     #[must_use]
     pub fn get_job(&self, id: ExportJobId) -> Option<&ExportJob> {
-        self.jobs.iter().find(|j| j.id() == id)
+        self.jobs.get(id)
     }
 
     /// Gets a mutable job by ID.
     pub fn get_job_mut(&mut self, id: ExportJobId) -> Option<&mut ExportJob> {
-        self.jobs.iter_mut().find(|j| j.id() == id)
+        self.jobs.get_mut(id)
     }
 
-    /// Returns all jobs.
-    #[must_use]
-    pub fn jobs(&self) -> &[ExportJob] {
-        &self.jobs
+    /// Returns all jobs, in queue order.
+    pub fn jobs(&self) -> impl DoubleEndedIterator<Item = &ExportJob> + Clone {
+        self.jobs.iter()
     }
 
     /// Returns queued jobs in priority order.
@@ -156,9 +194,95 @@ impl ExportQueue {
         }
     }
 
+    /// Takes a copy of the next queued job for a runner and marks the
+    /// queued one as started. The copy is still queued, so pre-encode
+    /// analysis can run on it.
+    fn claim_next(&mut self) -> Option<ExportJob> {
+        if self.active_count >= self.max_concurrent {
+            return None;
+        }
+        let next_id = self.queued_jobs().first().map(|j| j.id())?;
+        let job = self.get_job_mut(next_id)?;
+        let claimed = job.clone();
+        job.start();
+        self.current = Some(next_id);
+        self.active_count += 1;
+        Some(claimed)
+    }
+
+    /// Runs the next queued job on the calling thread, rendering frames
+    /// from `renderer`.
+    ///
+    /// Returns `None` if no job can start, otherwise the paths the job
+    /// wrote or the error it failed with.
+    pub fn run_next(
+        &mut self, renderer: &dyn FrameRenderer,
+    ) -> Option<VideoEditorResult<Vec<String>>> {
+        let mut job = self.claim_next()?;
+        let engine = ExportEngine::new(renderer).with_frame_pool(self.frame_pool.clone());
+        let result = engine.run(&mut job, &CancellationToken::new());
+        self.finish_job(job);
+        Some(result)
+    }
+
+    /// Runs the next queued job on the background task pool.
+    ///
+    /// Returns `None` if no job can start. The handle resolves to the
+    /// finished job, to be handed back with [`Self::finish_job`]; cancelling
+    /// it stops the export at the next frame. A handle cancelled before the
+    /// job started resolves to an error instead, and the job should be
+    /// cancelled in the queue with [`Self::cancel_job`].
+    pub fn run_async(
+        &mut self, renderer: Arc<dyn FrameRenderer + Send + Sync>,
+    ) -> Option<(ExportJobId, TaskHandle<ExportJob>)> {
+        let mut job = self.claim_next()?;
+        let id = job.id();
+        let pool = self.frame_pool.clone();
+        let handle = TaskPool::global().spawn(move |token| {
+            // The job records the outcome; only a cancelled start has no job
+            let _ = ExportEngine::new(&*renderer).with_frame_pool(pool).run(&mut job, token);
+            Ok(job)
+        });
+        Some((id, handle))
+    }
+
+    /// Puts a job that ran outside the queue back in place of its queued
+    /// entry and frees its slot.
+    pub fn finish_job(&mut self, job: ExportJob) {
+        let id = job.id();
+        let Some(entry) = self.get_job_mut(id) else {
+            return;
+        };
+        let was_active = !entry.progress().is_complete();
+        *entry = job;
+        if was_active {
+            self.active_count = self.active_count.saturating_sub(1);
+        }
+        if self.current == Some(id) {
+            self.current = None;
+        }
+    }
+
+    /// Renders jobs into buffers from `pool`, e.g. the plugin's
+    /// [`frame_pool`](crate::VideoEditorPlugin::frame_pool).
+    pub fn set_frame_pool(&mut self, pool: FrameBufferPool) {
+        self.frame_pool = pool;
+    }
+
     /// Sets maximum concurrent exports.
+    ///
+    /// Ignored while the queue runs in deterministic order.
     pub fn set_max_concurrent(&mut self, max: usize) {
-        self.max_concurrent = max.max(1);
+        self.max_concurrent = if self.ordered { 1 } else { max.max(1) };
+    }
+
+    /// Apply the render determinism settings; ordered threads pin the
+    /// queue to one job at a time, started in queue order.
+    pub fn set_determinism(&mut self, determinism: &RenderDeterminism) {
+        self.ordered = determinism.ordered_threads;
+        if self.ordered {
+            self.max_concurrent = 1;
+        }
     }
 
     /// Cancels a job.
@@ -205,6 +329,22 @@ impl ExportQueue {
         Ok(self.add_job(project_id, settings, total_frames))
     }
 
+    /// Queues a re-render of a completed job with the effective settings
+    /// recorded in its environment.
+    pub fn rerender_job(&mut self, id: ExportJobId) -> VideoEditorResult<ExportJobId> {
+        let job =
+            self.get_job(id).ok_or_else(|| VideoEditorError::Export("Job not found".into()))?;
+        let environment = job
+            .environment()
+            .ok_or_else(|| VideoEditorError::Export("Job has no recorded environment".into()))?;
+
+        let settings = environment.effective_settings.clone();
+        let total_frames = job.progress().total_frames;
+        let project_id = job.project_id();
+
+        Ok(self.add_job(project_id, settings, total_frames))
+    }
+
     /// Clears completed jobs from the queue.
     pub fn clear_completed(&mut self) {
         self.jobs.retain(|j| !matches!(j.progress().status, ExportStatus::Completed));
@@ -256,6 +396,8 @@ pub enum PresetCategory {
     Archive,
     /// Mobile devices.
     Mobile,
+    /// Audio-only mixdown.
+    Audio,
     /// Custom presets.
     Custom,
 }
@@ -368,4 +510,62 @@ impl ExportPreset {
             },
         }
     }
+
+    /// Creates a WAV mixdown preset (PCM, no normalization).
+    #[must_use]
+    pub fn audio_wav() -> Self {
+        Self::audio_only("WAV Mixdown", "Uncompressed audio master", ContainerFormat::Wav, 0)
+    }
+
+    /// Creates a FLAC mixdown preset (lossless, no normalization).
+    #[must_use]
+    pub fn audio_flac() -> Self {
+        Self::audio_only("FLAC Mixdown", "Lossless compressed audio", ContainerFormat::Flac, 0)
+    }
+
+    /// Creates an MP3 mixdown preset normalized for streaming (-14 LUFS).
+    #[must_use]
+    pub fn audio_mp3() -> Self {
+        Self::audio_only("MP3 320", "Compressed audio for sharing", ContainerFormat::Mp3, 320)
+            .with_loudness(LoudnessTarget::STREAMING)
+    }
+
+    /// Creates an AAC (M4A) mixdown preset normalized for streaming (-14 LUFS).
+    #[must_use]
+    pub fn audio_m4a() -> Self {
+        Self::audio_only("AAC 256 (M4A)", "AAC audio for streaming", ContainerFormat::M4a, 256)
+            .with_loudness(LoudnessTarget::STREAMING)
+    }
+
+    /// Sets the loudness normalization target.
+    #[must_use]
+    pub fn with_loudness(mut self, target: LoudnessTarget) -> Self {
+        self.settings.loudness = Some(target);
+        self
+    }
+
+    /// Enables seamless loop export with the given overlap in frames.
+    #[must_use]
+    pub fn with_seamless_loop(mut self, overlap_frames: u32) -> Self {
+        self.settings.seamless_loop = Some(SeamlessLoop::new(overlap_frames));
+        self
+    }
+
+    fn audio_only(name: &str, description: &str, container: ContainerFormat, bitrate: u32) -> Self {
+        Self {
+            name:        name.into(),
+            description: description.into(),
+            category:    PresetCategory::Audio,
+            settings:    ExportSettings {
+                container,
+                audio: AudioEncodingSettings {
+                    codec: container.audio_only_codec().unwrap_or_default(),
+                    bitrate,
+                    sample_rate: 48000,
+                    channels: 2,
+                },
+                ..Default::default()
+            },
+        }
+    }
 }
@@ -0,0 +1,200 @@
+//! Export range presets.
+//!
+//! An [`ExportRange`] names the part of the timeline an export covers:
+//! the whole edit, the preview in/out points, the selected clips, the
+//! stretch between chapter markers, or several of these as a batch with
+//! one output file each. Ranges are resolved against the timeline when
+//! the export is planned. Resolution is gap-aware: empty timeline at
+//! either end of a range is trimmed off, so an export never starts or
+//! ends in black because a marker or in point sits past the last clip.
+
+use super::naming::{sanitize_file_name, split_extension};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    implementation::{
+        marker_system::{MarkerId, MarkerManager, MarkerType},
+        preview_manager::InOutPoints,
+        timeline::TimelineManager,
+    },
+    types::TimePosition,
+};
+
+/// Part of the timeline an export covers.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ExportRange {
+    /// Everything from the first clip to the end of the last.
+    #[default]
+    Entire,
+    /// Fixed span, end exclusive.
+    Span(TimePosition, TimePosition),
+    /// The preview in/out points; a missing point falls back to the
+    /// timeline start or end.
+    InOut,
+    /// From the start of the earliest selected clip to the end of the
+    /// latest.
+    Selection,
+    /// Between two markers, in either order.
+    BetweenMarkers(MarkerId, MarkerId),
+    /// From a chapter marker to the next chapter marker, or the end of
+    /// the timeline for the last chapter.
+    Chapter(MarkerId),
+    /// Several ranges, each exported to its own file under its name.
+    Multiple(Vec<(String, ExportRange)>),
+}
+
+/// Timeline span of one export file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedExportRange {
+    /// Name of the range for batch exports, `None` for a single file.
+    pub name:  Option<String>,
+    /// Start time.
+    pub start: TimePosition,
+    /// End time, exclusive.
+    pub end:   TimePosition,
+}
+
+impl ResolvedExportRange {
+    /// Output file of the range. For a batch part the range name replaces
+    /// a `{range}` token in `output_path`, or is appended to the file name
+    /// if there is none, e.g. `out_Intro.mp4`.
+    #[must_use]
+    pub fn output_path(&self, output_path: &str) -> String {
+        let Some(name) = &self.name else {
+            return output_path.to_string();
+        };
+        let name = sanitize_file_name(name);
+        if output_path.contains("{range}") {
+            return output_path.replace("{range}", &name);
+        }
+        let (stem, extension) = split_extension(output_path);
+        format!("{stem}_{name}{extension}")
+    }
+}
+
+impl ExportRange {
+    /// One range per chapter marker, named after the chapters, for
+    /// exporting every chapter as its own file.
+    #[must_use]
+    pub fn each_chapter(markers: &MarkerManager) -> Self {
+        Self::Multiple(
+            markers
+                .chapters()
+                .into_iter()
+                .map(|m| (m.name().to_string(), Self::Chapter(m.id())))
+                .collect(),
+        )
+    }
+
+    /// Returns whether the range exports several files.
+    #[must_use]
+    pub fn is_batch(&self) -> bool {
+        matches!(self, Self::Multiple(_))
+    }
+
+    /// Resolves the range to the timeline spans of its output files.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Export` if the selection is empty, a
+    /// marker is missing or not a chapter where one is needed, or a range
+    /// covers no clips.
+    pub fn resolve(
+        &self, timeline: &TimelineManager, in_out: &InOutPoints, selection: &[u64],
+        markers: &MarkerManager,
+    ) -> VideoEditorResult<Vec<ResolvedExportRange>> {
+        if let Self::Multiple(ranges) = self {
+            let mut resolved = Vec::with_capacity(ranges.len());
+            for (name, range) in ranges {
+                if range.is_batch() {
+                    return Err(VideoEditorError::Export(format!(
+                        "Export range {name} cannot nest a batch"
+                    )));
+                }
+                for mut part in range.resolve(timeline, in_out, selection, markers)? {
+                    part.name = Some(name.clone());
+                    resolved.push(part);
+                }
+            }
+            return Ok(resolved);
+        }
+
+        let marker_position = |id: MarkerId| {
+            markers.get_marker(id).map(|m| m.position()).ok_or_else(|| {
+                VideoEditorError::Export(format!("Marker not found: {}", id.inner()))
+            })
+        };
+        let (start, end) = match self {
+            Self::Entire => (TimePosition::from_ms(0), timeline_end(timeline)),
+            Self::Span(start, end) => (*start, *end),
+            Self::InOut => (
+                in_out.in_point.unwrap_or_default(),
+                in_out.out_point.unwrap_or_else(|| timeline_end(timeline)),
+            ),
+            Self::Selection => {
+                let clips: Vec<_> = timeline
+                    .tracks()
+                    .iter()
+                    .flat_map(|t| &t.clips)
+                    .filter(|c| selection.contains(&c.id))
+                    .collect();
+                let start = clips.iter().map(|c| c.start.ms).min().ok_or_else(|| {
+                    VideoEditorError::Export("No clips selected to export".into())
+                })?;
+                let end = clips.iter().map(|c| c.end().ms).max().unwrap_or(start);
+                (TimePosition::from_ms(start), TimePosition::from_ms(end))
+            },
+            Self::BetweenMarkers(a, b) => {
+                let (a, b) = (marker_position(*a)?, marker_position(*b)?);
+                if a.ms <= b.ms { (a, b) } else { (b, a) }
+            },
+            Self::Chapter(id) => {
+                let marker = markers
+                    .get_marker(*id)
+                    .filter(|m| m.marker_type() == MarkerType::Chapter)
+                    .ok_or_else(|| {
+                        VideoEditorError::Export(format!("Chapter not found: {}", id.inner()))
+                    })?;
+                let start = marker.position();
+                let end = markers
+                    .chapters()
+                    .into_iter()
+                    .filter(|m| m.position().ms > start.ms)
+                    .map(|m| m.position())
+                    .min_by_key(|p| p.ms)
+                    .unwrap_or_else(|| timeline_end(timeline));
+                (start, end)
+            },
+            Self::Multiple(_) => unreachable!("batches are resolved above"),
+        };
+
+        let (start, end) = trim_to_content(timeline, start, end).ok_or_else(|| {
+            VideoEditorError::Export(format!(
+                "Export range {}-{} ms covers no clips",
+                start.ms, end.ms
+            ))
+        })?;
+        Ok(vec![ResolvedExportRange { name: None, start, end }])
+    }
+}
+
+/// End of the last clip.
+fn timeline_end(timeline: &TimelineManager) -> TimePosition {
+    let end = timeline.tracks().iter().flat_map(|t| &t.clips).map(|c| c.end().ms).max();
+    TimePosition::from_ms(end.unwrap_or(0))
+}
+
+/// Narrows a span to the clips it overlaps, or `None` if it overlaps none.
+fn trim_to_content(
+    timeline: &TimelineManager, start: TimePosition, end: TimePosition,
+) -> Option<(TimePosition, TimePosition)> {
+    let overlapping = timeline
+        .tracks()
+        .iter()
+        .flat_map(|t| &t.clips)
+        .filter(|c| c.start.ms < end.ms && c.end().ms > start.ms);
+    let (first, last) = overlapping.fold(None, |span: Option<(u64, u64)>, c| {
+        let (first, last) = span.unwrap_or((c.start.ms, c.end().ms));
+        Some((first.min(c.start.ms), last.max(c.end().ms)))
+    })?;
+    Some((TimePosition::from_ms(start.ms.max(first)), TimePosition::from_ms(end.ms.min(last))))
+}
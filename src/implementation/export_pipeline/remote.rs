@@ -0,0 +1,634 @@
+//! Remote export: submitting jobs to a render farm.
+//!
+//! A job is packaged as a [`RemoteJobRequest`] (a text manifest of its
+//! settings, the serialized project slice it renders and attachments for
+//! binary settings such as a watermark image) and handed to a host-supplied
+//! [`RemoteExportBackend`]. [`RemoteExport`] drives one job
+//! through submission, progress polling and artifact download; the host
+//! calls [`RemoteExport::tick`] periodically with its clock, so retries
+//! back off without this crate sleeping or spawning threads.
+
+use std::path::Path;
+
+use super::{
+    environment::ExportEnvironment,
+    formats::{ExportJobId, ExportMetadata, ExportSettings, ExportStatus, VideoEncodingSettings},
+    job::ExportJob,
+    overlay::BurnInOverlay,
+    range::ExportRange,
+};
+use crate::{
+    checksum,
+    errors::{VideoEditorError, VideoEditorResult},
+    implementation::captions::CaptionExportOptions,
+};
+
+/// Magic number of an encoded remote job ("EVRJ").
+pub const REMOTE_JOB_MAGIC: u32 = 0x4556_524A;
+
+/// A remote backend failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteError {
+    /// Worth retrying: timeouts, dropped connections, busy workers.
+    Transient(String),
+    /// Retrying won't help: rejected job, missing artifact.
+    Permanent(String),
+}
+
+impl RemoteError {
+    fn message(&self) -> &str {
+        match self {
+            Self::Transient(message) | Self::Permanent(message) => message,
+        }
+    }
+}
+
+/// Backend's reference to a submitted job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteJobHandle {
+    /// Job ID assigned by the backend.
+    pub remote_id: String,
+}
+
+/// Job state reported by the backend.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteStatus {
+    /// Waiting for a worker.
+    Queued,
+    /// Rendering on a worker.
+    Running {
+        /// Frames rendered so far.
+        frames_done:   u64,
+        /// Worker encoding speed, if reported.
+        encoding_fps:  Option<f64>,
+        /// Output written so far, in bytes.
+        bytes_written: u64,
+    },
+    /// Rendered; the artifact is ready to download.
+    Completed {
+        /// Artifact size in bytes.
+        artifact_size: u64,
+    },
+    /// The worker gave up.
+    Failed(String),
+    /// Cancelled on the backend.
+    Cancelled,
+}
+
+/// Render farm or cloud service that runs export jobs.
+pub trait RemoteExportBackend: Send + Sync {
+    /// Backend name for logs and the recorded export environment.
+    fn name(&self) -> &str;
+
+    /// Submit a job.
+    fn submit(&self, request: &RemoteJobRequest) -> Result<RemoteJobHandle, RemoteError>;
+
+    /// Report the state of a submitted job.
+    fn poll(&self, handle: &RemoteJobHandle) -> Result<RemoteStatus, RemoteError>;
+
+    /// Download a completed job's output to `target`, returning its size.
+    fn download(&self, handle: &RemoteJobHandle, target: &Path) -> Result<u64, RemoteError>;
+
+    /// Cancel a submitted job.
+    fn cancel(&self, handle: &RemoteJobHandle) -> Result<(), RemoteError> {
+        let _ = handle;
+        Ok(())
+    }
+}
+
+/// A job packaged for a remote worker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteJobRequest {
+    /// Local job ID.
+    pub job_id:        ExportJobId,
+    /// Project the job renders.
+    pub project_id:    u64,
+    /// Frames to render.
+    pub total_frames:  u64,
+    /// Export settings.
+    pub settings:      ExportSettings,
+    /// Serialized part of the project the job renders.
+    pub project_slice: Vec<u8>,
+}
+
+impl RemoteJobRequest {
+    /// Package `job` with the project slice it renders.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Export` if the job's range still refers
+    /// to the timeline (in/out points, selection, markers or chapters); the
+    /// worker has no timeline to resolve it against.
+    pub fn from_job(job: &ExportJob, project_slice: Vec<u8>) -> VideoEditorResult<Self> {
+        let range = &job.settings().range;
+        if !matches!(range, ExportRange::Span(..) | ExportRange::Entire) {
+            return Err(VideoEditorError::Export(format!(
+                "Export range {range:?} must be resolved before submitting a remote job"
+            )));
+        }
+        Ok(Self {
+            job_id: job.id(),
+            project_id: job.project_id(),
+            total_frames: job.progress().total_frames,
+            settings: job.settings().clone(),
+            project_slice,
+        })
+    }
+
+    /// `key=value` lines describing the job for the worker.
+    ///
+    /// Values are escaped so they fit on one line: backslash, newline and
+    /// carriage return become `\\`, `\n` and `\r`. Binary settings such as
+    /// the watermark image and the review LUT are not in the manifest; their
+    /// key holds the index of the attachment carrying them, see
+    /// [`Self::to_bytes`].
+    pub fn manifest(&self) -> String {
+        self.encode().lines.join("\n")
+    }
+
+    /// Encode for transport: magic, manifest, project slice, then the
+    /// attachment count and each attachment, all length-prefixed.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let Manifest { lines, attachments } = self.encode();
+        let manifest = lines.join("\n");
+        let mut bytes = REMOTE_JOB_MAGIC.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&(manifest.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(manifest.as_bytes());
+        bytes.extend_from_slice(&(self.project_slice.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.project_slice);
+        bytes.extend_from_slice(&(attachments.len() as u32).to_le_bytes());
+        for attachment in attachments {
+            bytes.extend_from_slice(&(attachment.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&attachment);
+        }
+        bytes
+    }
+
+    /// Read [`Self::to_bytes`] output back, as a worker would.
+    pub fn parse_bytes(bytes: &[u8]) -> Option<RemoteJobPayload> {
+        let mut offset = 0usize;
+        let mut take = |len: usize| {
+            let field = bytes.get(offset..offset.checked_add(len)?)?;
+            offset += len;
+            Some(field)
+        };
+        if take(4)? != REMOTE_JOB_MAGIC.to_le_bytes() {
+            return None;
+        }
+        let manifest_len = u32::from_le_bytes(take(4)?.try_into().ok()?) as usize;
+        let manifest = std::str::from_utf8(take(manifest_len)?).ok()?;
+        let slice_len = usize::try_from(u64::from_le_bytes(take(8)?.try_into().ok()?)).ok()?;
+        let project_slice = take(slice_len)?.to_vec();
+        let count = u32::from_le_bytes(take(4)?.try_into().ok()?);
+        let attachments = (0..count)
+            .map(|_| {
+                let len = usize::try_from(u64::from_le_bytes(take(8)?.try_into().ok()?)).ok()?;
+                take(len).map(<[u8]>::to_vec)
+            })
+            .collect::<Option<_>>()?;
+        let manifest = manifest
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.to_string(), unescape(value)))
+            .collect();
+        Some(RemoteJobPayload { manifest, project_slice, attachments })
+    }
+
+    /// Manifest lines and attachments of the job.
+    fn encode(&self) -> Manifest {
+        let settings = &self.settings;
+        let mut m = Manifest::default();
+        m.set("job_id", self.job_id.inner());
+        m.set("project_id", self.project_id);
+        m.set("total_frames", self.total_frames);
+        m.set("project_sha256", checksum::to_hex(&checksum::sha256(&self.project_slice)));
+        m.set("container", settings.container.extension());
+        m.set(
+            "output_name",
+            Path::new(&settings.output_path)
+                .file_name()
+                .map_or_else(String::new, |n| n.to_string_lossy().into_owned()),
+        );
+        if settings.renders_video() {
+            m.video("video", &settings.video);
+        }
+        let audio = &settings.audio;
+        m.set("audio.codec", format_args!("{:?}", audio.codec));
+        m.set("audio.bitrate", audio.bitrate);
+        m.set("audio.sample_rate", audio.sample_rate);
+        m.set("audio.channels", audio.channels);
+        m.set("multi_pass", settings.multi_pass);
+        if let ExportRange::Span(start, end) = settings.range {
+            m.set("range_ms", format_args!("{}-{}", start.ms, end.ms));
+        }
+        if let Some(loudness) = settings.loudness {
+            m.set(
+                "loudness",
+                format_args!("{}/{}", loudness.integrated_lufs, loudness.true_peak_dbtp),
+            );
+        }
+        if let Some(adaptive) = settings.adaptive {
+            m.set("adaptive.sample_frames", adaptive.sample_frames);
+            if let Some(max_size) = adaptive.max_size_bytes {
+                m.set("adaptive.max_size_bytes", max_size);
+            }
+        }
+        if let Some(seamless) = settings.seamless_loop {
+            m.set("seamless_loop.overlap_frames", seamless.overlap_frames);
+            m.set("seamless_loop.curve", format_args!("{:?}", seamless.curve));
+            m.set("seamless_loop.max_seam_delta", seamless.max_seam_delta);
+        }
+        m.metadata(&settings.metadata);
+        m.captions(&settings.captions);
+        if let Some(overlay) = &settings.overlay {
+            m.overlay(overlay);
+        }
+        if let Some(stems) = &settings.stems {
+            m.set("stems.layout", format_args!("{:?}", stems.layout));
+            m.set("stems.name_template", &stems.name_template);
+            m.set("stems.stems_only", stems.stems_only);
+        }
+        if let Some(packaging) = &settings.packaging {
+            m.set("packaging.format", format_args!("{:?}", packaging.format));
+            m.set("packaging.segment_seconds", packaging.segment_seconds);
+            for (i, rendition) in packaging.renditions.iter().enumerate() {
+                m.set(&format!("packaging.rendition.{i}.name"), &rendition.name);
+                m.video(&format!("packaging.rendition.{i}.video"), &rendition.video);
+            }
+        }
+        m
+    }
+}
+
+/// Manifest of a job being encoded.
+#[derive(Default)]
+struct Manifest {
+    lines:       Vec<String>,
+    attachments: Vec<Vec<u8>>,
+}
+
+impl Manifest {
+    /// Adds `key=value`, escaping the value.
+    fn set(&mut self, key: &str, value: impl std::fmt::Display) {
+        let value = value.to_string();
+        let value = value.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r");
+        self.lines.push(format!("{key}={value}"));
+    }
+
+    /// Adds `bytes` as an attachment, naming its index under `key`.
+    fn attach(&mut self, key: &str, bytes: Vec<u8>) {
+        self.set(key, self.attachments.len());
+        self.attachments.push(bytes);
+    }
+
+    fn video(&mut self, prefix: &str, video: &VideoEncodingSettings) {
+        let mut set = |key: &str, value: &dyn std::fmt::Display| {
+            self.set(&format!("{prefix}.{key}"), value);
+        };
+        set("codec", &format_args!("{:?}", video.codec));
+        set("resolution", &format_args!("{}x{}", video.resolution.width, video.resolution.height));
+        set(
+            "frame_rate",
+            &format_args!("{}/{}", video.frame_rate.numerator, video.frame_rate.denominator),
+        );
+        set("bitrate", &video.bitrate);
+        set("quality", &video.quality);
+        set("rate_control", &format_args!("{:?}", video.rate_control));
+        set("hw_accel", &format_args!("{:?}", video.hw_accel));
+        set("gop_size", &video.gop_size);
+        set("b_frames", &video.b_frames);
+        set("preset", &format_args!("{:?}", video.preset));
+        set("pixel_format", &format_args!("{:?}", video.pixel_format));
+        set("field_order", &format_args!("{:?}", video.field_order));
+        set("pixel_aspect", &format_args!("{:?}", video.pixel_aspect));
+        set("projection", &format_args!("{:?}", video.projection));
+    }
+
+    fn metadata(&mut self, metadata: &ExportMetadata) {
+        let text = [
+            ("title", &metadata.title),
+            ("artist", &metadata.artist),
+            ("album", &metadata.album),
+            ("comment", &metadata.comment),
+            ("copyright", &metadata.copyright),
+        ];
+        for (key, value) in text {
+            if let Some(value) = value {
+                self.set(&format!("metadata.{key}"), value);
+            }
+        }
+        if let Some(year) = metadata.year {
+            self.set("metadata.year", year);
+        }
+        for (i, (key, value)) in metadata.custom.iter().enumerate() {
+            self.set(&format!("metadata.custom.{i}.key"), key);
+            self.set(&format!("metadata.custom.{i}.value"), value);
+        }
+        let Some(bwf) = &metadata.bwf else {
+            return;
+        };
+        self.set("metadata.bwf.description", &bwf.description);
+        self.set("metadata.bwf.originator", &bwf.originator);
+        self.set("metadata.bwf.originator_reference", &bwf.originator_reference);
+        self.set("metadata.bwf.origination_date", &bwf.origination_date);
+        self.set("metadata.bwf.origination_time", &bwf.origination_time);
+        self.set("metadata.bwf.time_reference", bwf.time_reference);
+        self.set("metadata.bwf.coding_history", &bwf.coding_history);
+        self.set("metadata.bwf.sample_rate", bwf.sample_rate);
+        self.set("metadata.bwf.channels", bwf.channels);
+        if let Some(ixml) = &bwf.ixml {
+            self.attach("metadata.bwf.ixml", ixml.to_xml().into_bytes());
+        }
+    }
+
+    fn captions(&mut self, captions: &CaptionExportOptions) {
+        if !captions.burn_in.is_empty() {
+            let tracks: Vec<_> = captions.burn_in.iter().map(u64::to_string).collect();
+            self.set("captions.burn_in", tracks.join(","));
+        }
+        for (i, sidecar) in captions.sidecars.iter().enumerate() {
+            self.set(
+                &format!("captions.sidecar.{i}"),
+                format_args!("{}:{:?}", sidecar.track_id, sidecar.format),
+            );
+        }
+    }
+
+    fn overlay(&mut self, overlay: &BurnInOverlay) {
+        if let Some(position) = overlay.timecode {
+            self.set("overlay.timecode", format_args!("{position:?}"));
+        }
+        self.set("overlay.timecode_offset_ms", overlay.timecode_offset.ms);
+        if let Some(position) = overlay.clip_names {
+            self.set("overlay.clip_names", format_args!("{position:?}"));
+        }
+        self.set("overlay.text_scale", overlay.text_scale);
+        if let Some(watermark) = &overlay.watermark {
+            self.attach("overlay.watermark", watermark.pixels.clone());
+            self.set(
+                "overlay.watermark.size",
+                format_args!("{}x{}", watermark.width, watermark.height),
+            );
+            self.set("overlay.watermark.opacity", watermark.opacity);
+            self.set("overlay.watermark.position", format_args!("{:?}", watermark.position));
+            self.set("overlay.watermark.margin", watermark.margin);
+        }
+        if let Some(lut) = &overlay.lut {
+            self.attach("overlay.lut", lut.to_cube().into_bytes());
+            self.set("overlay.lut.interpolation", format_args!("{:?}", lut.interpolation()));
+        }
+    }
+}
+
+/// Reverses the escaping of [`Manifest::set`].
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// A job as received by a worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteJobPayload {
+    /// Manifest entries in order, unescaped.
+    pub manifest:      Vec<(String, String)>,
+    /// Serialized project slice.
+    pub project_slice: Vec<u8>,
+    /// Binary settings the manifest refers to by index.
+    pub attachments:   Vec<Vec<u8>>,
+}
+
+impl RemoteJobPayload {
+    /// Value of a manifest entry.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.manifest.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Attachment named by a manifest entry.
+    pub fn attachment(&self, key: &str) -> Option<&[u8]> {
+        let index: usize = self.get(key)?.parse().ok()?;
+        self.attachments.get(index).map(Vec::as_slice)
+    }
+}
+
+/// How transient failures are retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Consecutive transient failures tolerated before the job fails.
+    pub max_attempts:  u32,
+    /// Wait after the first failure, in milliseconds; doubles after each.
+    pub base_delay_ms: u64,
+    /// Longest wait between attempts, in milliseconds.
+    pub max_delay_ms:  u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_attempts: 5, base_delay_ms: 1_000, max_delay_ms: 60_000 }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait after `failures` consecutive failures.
+    #[must_use]
+    pub fn delay_ms(&self, failures: u32) -> u64 {
+        let doublings = failures.saturating_sub(1).min(32);
+        self.base_delay_ms.saturating_mul(1 << doublings).min(self.max_delay_ms)
+    }
+}
+
+/// Where a remote export stands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemotePhase {
+    /// Not yet accepted by the backend.
+    Submitting,
+    /// Accepted; polling for progress.
+    Rendering,
+    /// Finished rendering; fetching the artifact.
+    Downloading,
+    /// Artifact downloaded to the job's output path.
+    Done,
+    /// Failed, cancelled or out of retries.
+    Stopped,
+}
+
+/// Drives one export job on a remote backend.
+#[derive(Debug)]
+pub struct RemoteExport {
+    request:      RemoteJobRequest,
+    retry:        RetryPolicy,
+    handle:       Option<RemoteJobHandle>,
+    phase:        RemotePhase,
+    failures:     u32,
+    next_try_ms:  u64,
+    submitted_ms: Option<u64>,
+}
+
+impl RemoteExport {
+    /// Prepare `request` for submission.
+    pub fn new(request: RemoteJobRequest, retry: RetryPolicy) -> Self {
+        Self {
+            request,
+            retry,
+            handle: None,
+            phase: RemotePhase::Submitting,
+            failures: 0,
+            next_try_ms: 0,
+            submitted_ms: None,
+        }
+    }
+
+    /// Current phase.
+    pub fn phase(&self) -> &RemotePhase {
+        &self.phase
+    }
+
+    /// Backend handle, once submitted.
+    pub fn handle(&self) -> Option<&RemoteJobHandle> {
+        self.handle.as_ref()
+    }
+
+    /// Consecutive transient failures so far.
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Earliest clock time the next backend call is made, in milliseconds.
+    pub fn next_try_ms(&self) -> u64 {
+        self.next_try_ms
+    }
+
+    /// Advance the job by at most one backend call.
+    ///
+    /// `now_ms` is the host's monotonic clock. Remote progress is copied
+    /// into `job`; on completion the artifact is downloaded to the job's
+    /// output path and the job completes with an environment naming the
+    /// backend. Transient failures are retried per the policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the job fails for good; the job is marked
+    /// failed as well.
+    pub fn tick(
+        &mut self, backend: &dyn RemoteExportBackend, job: &mut ExportJob, now_ms: u64,
+    ) -> VideoEditorResult<&RemotePhase> {
+        if matches!(self.phase, RemotePhase::Done | RemotePhase::Stopped)
+            || now_ms < self.next_try_ms
+        {
+            return Ok(&self.phase);
+        }
+        let outcome = match (&self.phase, self.handle.clone()) {
+            (RemotePhase::Submitting, _) => backend.submit(&self.request).map(|handle| {
+                self.handle = Some(handle);
+                self.submitted_ms = Some(now_ms);
+                self.phase = RemotePhase::Rendering;
+                if job.progress().status == ExportStatus::Queued {
+                    job.start();
+                }
+            }),
+            (RemotePhase::Rendering, Some(handle)) => {
+                backend.poll(&handle).map(|status| self.apply_status(status, job, now_ms))
+            },
+            (RemotePhase::Downloading, Some(handle)) => {
+                let target = job.settings().output_path.clone();
+                backend.download(&handle, Path::new(&target)).map(|size| {
+                    job.progress_mut().current_size = size;
+                    let mut environment =
+                        ExportEnvironment::capture(job.settings(), &self.request.project_slice);
+                    environment.platform = format!("remote:{}", backend.name());
+                    job.complete(environment);
+                    self.phase = RemotePhase::Done;
+                })
+            },
+            _ => Ok(()),
+        };
+
+        match outcome {
+            Ok(()) => self.failures = 0,
+            Err(RemoteError::Transient(message)) if self.failures + 1 < self.retry.max_attempts => {
+                self.failures += 1;
+                self.next_try_ms = now_ms + self.retry.delay_ms(self.failures);
+                job.progress_mut().error_message = Some(message);
+            },
+            Err(error) => return Err(self.stop(job, error.message())),
+        }
+        if self.phase == RemotePhase::Stopped {
+            let message = job.progress().error_message.clone().unwrap_or_default();
+            return Err(VideoEditorError::Export(message));
+        }
+        Ok(&self.phase)
+    }
+
+    /// Cancel the job on the backend and locally.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend refuses the cancellation.
+    pub fn cancel(
+        &mut self, backend: &dyn RemoteExportBackend, job: &mut ExportJob,
+    ) -> VideoEditorResult<()> {
+        if let Some(handle) = &self.handle {
+            backend
+                .cancel(handle)
+                .map_err(|e| VideoEditorError::Export(e.message().to_string()))?;
+        }
+        job.cancel();
+        self.phase = RemotePhase::Stopped;
+        Ok(())
+    }
+
+    fn apply_status(&mut self, status: RemoteStatus, job: &mut ExportJob, now_ms: u64) {
+        let progress = job.progress_mut();
+        progress.error_message = None;
+        match status {
+            RemoteStatus::Queued => {},
+            RemoteStatus::Running { frames_done, encoding_fps, bytes_written } => {
+                progress.status = ExportStatus::Encoding;
+                let elapsed = now_ms.saturating_sub(self.submitted_ms.unwrap_or(now_ms));
+                progress.update(frames_done, elapsed as f64 / 1000.0);
+                if let Some(fps) = encoding_fps.filter(|fps| *fps > 0.0) {
+                    progress.encoding_fps = fps;
+                    let remaining = progress.total_frames.saturating_sub(frames_done);
+                    progress.eta_seconds = Some(remaining as f64 / fps);
+                }
+                progress.current_size = bytes_written;
+            },
+            RemoteStatus::Completed { artifact_size } => {
+                progress.status = ExportStatus::Finalizing;
+                progress.update(progress.total_frames, 0.0);
+                progress.estimated_size = Some(artifact_size);
+                self.phase = RemotePhase::Downloading;
+            },
+            RemoteStatus::Failed(message) => {
+                job.fail(message);
+                self.phase = RemotePhase::Stopped;
+            },
+            RemoteStatus::Cancelled => {
+                job.cancel();
+                self.phase = RemotePhase::Stopped;
+                job.progress_mut().error_message = Some("Cancelled on the render farm".into());
+            },
+        }
+    }
+
+    fn stop(&mut self, job: &mut ExportJob, message: &str) -> VideoEditorError {
+        job.fail(message);
+        self.phase = RemotePhase::Stopped;
+        VideoEditorError::Export(format!(
+            "Remote export of job {} failed: {message}",
+            self.request.job_id.inner()
+        ))
+    }
+}
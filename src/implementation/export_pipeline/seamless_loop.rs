@@ -0,0 +1,170 @@
+//! Seamless loop export for motion backgrounds.
+//!
+//! The tail of the export range is blended into its head over an overlap, so
+//! the last exported frame flows into the first one. The exported length is
+//! shortened by the overlap.
+
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    implementation::audio_mixer::FadeCurve,
+    types::FrameRate,
+};
+
+/// Seamless loop export settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeamlessLoop {
+    /// Overlap blended from the tail into the head, in frames.
+    pub overlap_frames: u32,
+    /// Crossfade curve shared by the video and audio blend.
+    pub curve:          FadeCurve,
+    /// Maximum mean pixel difference (0.0 to 1.0) accepted at the seam.
+    pub max_seam_delta: f32,
+}
+
+/// Source frames that make up one exported loop frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoopFrameSource {
+    /// Source frame from the head of the range.
+    pub head:        u64,
+    /// Source frame from the tail blended over the head, if in the overlap.
+    pub tail:        Option<u64>,
+    /// Weight of the tail frame (0.0 to 1.0).
+    pub tail_weight: f32,
+}
+
+/// Result of the first/last frame continuity check.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoopVerification {
+    /// Mean pixel difference between the last and first frame (0.0 to 1.0).
+    pub seam_delta: f32,
+    /// Whether the seam is within the accepted threshold.
+    pub seamless:   bool,
+}
+
+impl SeamlessLoop {
+    /// Default seam threshold (about 5/255 per channel).
+    pub const DEFAULT_MAX_SEAM_DELTA: f32 = 0.02;
+
+    /// Creates loop settings with a linear crossfade.
+    #[must_use]
+    pub const fn new(overlap_frames: u32) -> Self {
+        Self {
+            overlap_frames,
+            curve: FadeCurve::LINEAR,
+            max_seam_delta: Self::DEFAULT_MAX_SEAM_DELTA,
+        }
+    }
+
+    /// Sets the crossfade curve.
+    #[must_use]
+    pub const fn with_curve(mut self, curve: FadeCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Checks that the overlap fits the source range.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Export` if the overlap is zero or longer
+    /// than half of the source range.
+    pub fn validate(&self, source_frames: u64) -> VideoEditorResult<()> {
+        let overlap = u64::from(self.overlap_frames);
+        if overlap == 0 || overlap * 2 > source_frames {
+            return Err(VideoEditorError::Export(format!(
+                "Loop overlap of {overlap} frames does not fit {source_frames} source frames"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the number of exported frames for a source range.
+    #[must_use]
+    pub fn output_frames(&self, source_frames: u64) -> u64 {
+        source_frames.saturating_sub(u64::from(self.overlap_frames))
+    }
+
+    /// Returns the source frames blended into an exported frame.
+    ///
+    /// Inside the overlap the tail fades out while the head fades in, so the
+    /// first exported frame continues from the last one.
+    #[must_use]
+    pub fn frame_source(&self, output_frame: u64, source_frames: u64) -> LoopFrameSource {
+        let overlap = u64::from(self.overlap_frames);
+        if output_frame >= overlap || overlap * 2 > source_frames {
+            return LoopFrameSource {
+                head:        output_frame,
+                tail:        None,
+                tail_weight: 0.0,
+            };
+        }
+
+        let progress = (output_frame + 1) as f32 / (overlap + 1) as f32;
+        LoopFrameSource {
+            head:        output_frame,
+            tail:        Some(source_frames - overlap + output_frame),
+            tail_weight: 1.0 - self.curve.evaluate(progress),
+        }
+    }
+
+    /// Blends a tail frame over a head frame (RGBA8) into `output`.
+    pub fn blend_frame(head: &[u8], tail: &[u8], tail_weight: f32, output: &mut Vec<u8>) {
+        let weight = tail_weight.clamp(0.0, 1.0);
+        output.clear();
+        output.extend(
+            head.iter().zip(tail).map(|(&h, &t)| {
+                (f32::from(h) * (1.0 - weight) + f32::from(t) * weight).round() as u8
+            }),
+        );
+    }
+
+    /// Crossfades the audio tail into the head of an interleaved buffer.
+    ///
+    /// Returns the loop-ready audio, shortened by the overlap.
+    #[must_use]
+    pub fn crossfade_audio(
+        &self, samples: &[f32], channels: usize, sample_rate: u32, frame_rate: FrameRate,
+    ) -> Vec<f32> {
+        let channels = channels.max(1);
+        let total = samples.len() / channels;
+        let overlap = ((f64::from(self.overlap_frames) * f64::from(sample_rate)
+            / frame_rate.as_f64())
+        .round() as usize)
+            .min(total / 2);
+        let out_len = total - overlap;
+
+        let mut output = samples[..out_len * channels].to_vec();
+        for frame in 0..overlap {
+            let incoming = self.curve.evaluate((frame + 1) as f32 / (overlap + 1) as f32);
+            let tail = (out_len + frame) * channels;
+            for ch in 0..channels {
+                let idx = frame * channels + ch;
+                output[idx] = samples[idx] * incoming + samples[tail + ch] * (1.0 - incoming);
+            }
+        }
+        output
+    }
+
+    /// Measures the seam between the last and first exported frames (RGBA8).
+    #[must_use]
+    pub fn verify(&self, last_frame: &[u8], first_frame: &[u8]) -> LoopVerification {
+        let seam_delta = Self::seam_delta(last_frame, first_frame);
+        LoopVerification { seam_delta, seamless: seam_delta <= self.max_seam_delta }
+    }
+
+    /// Mean absolute color difference of two RGBA8 frames (alpha ignored).
+    #[must_use]
+    pub fn seam_delta(a: &[u8], b: &[u8]) -> f32 {
+        if a.len() != b.len() {
+            return 1.0;
+        }
+        let (sum, count) = a.chunks_exact(4).zip(b.chunks_exact(4)).fold(
+            (0u64, 0u64),
+            |(sum, count), (pa, pb)| {
+                let diff: u64 = (0..3).map(|i| u64::from(pa[i].abs_diff(pb[i]))).sum();
+                (sum + diff, count + 3)
+            },
+        );
+        if count == 0 { 0.0 } else { sum as f32 / (count as f32 * 255.0) }
+    }
+}
@@ -0,0 +1,234 @@
+//! Audio stem export.
+//!
+//! Stems are separate mixdowns of parts of the mix, either one per bus
+//! (dialogue, music and effects groups) or one per track. Every stem of an
+//! export is padded with silence to the same length so they line up when
+//! laid back against the picture.
+
+use super::naming::sanitize_file_name;
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    implementation::audio_mixer::{AudioBusId, AudioMixer},
+    types::BwfMetadata,
+};
+
+/// How the mix is split into stems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StemLayout {
+    /// One stem per output bus; tracks routed straight to master share
+    /// the master stem.
+    #[default]
+    PerBus,
+    /// One stem per track.
+    PerTrack,
+}
+
+/// Stem files written by an export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StemExportOptions {
+    /// How tracks are grouped.
+    pub layout:        StemLayout,
+    /// File name template. `{output}` is the output file name without
+    /// extension, `{stem}` the bus or track name and `{index}` the
+    /// 1-based stem number, zero padded to two digits.
+    pub name_template: String,
+    /// Write only the stems and skip the main mix.
+    pub stems_only:    bool,
+}
+
+impl Default for StemExportOptions {
+    fn default() -> Self {
+        Self {
+            layout:        StemLayout::default(),
+            name_template: Self::DEFAULT_TEMPLATE.to_string(),
+            stems_only:    false,
+        }
+    }
+}
+
+/// One planned stem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stem {
+    /// Bus or track name.
+    pub name:      String,
+    /// Output file path.
+    pub path:      String,
+    /// Tracks mixed into the stem.
+    pub track_ids: Vec<u64>,
+    /// Bus whose fader applies to the stem, for per-bus stems.
+    pub bus:       Option<AudioBusId>,
+}
+
+impl StemExportOptions {
+    /// Template used when none is set: `{output}_{stem}`.
+    pub const DEFAULT_TEMPLATE: &'static str = "{output}_{stem}";
+
+    /// Creates options for a layout with the default name template.
+    #[must_use]
+    pub fn new(layout: StemLayout) -> Self {
+        Self { layout, ..Self::default() }
+    }
+
+    /// Path of a stem file next to `output_path`.
+    ///
+    /// Characters other than letters, digits, `-` and `_` in the stem name
+    /// are replaced with `_`.
+    #[must_use]
+    pub fn stem_path(&self, output_path: &str, index: usize, name: &str) -> String {
+        let name_start = output_path.rfind(['/', '\\']).map_or(0, |i| i + 1);
+        let file_name = &output_path[name_start..];
+        let output = match file_name.rfind('.') {
+            Some(dot) if dot > 0 => &file_name[..dot],
+            _ => file_name,
+        };
+        let stem = sanitize_file_name(name);
+        let file = self
+            .name_template
+            .replace("{output}", output)
+            .replace("{stem}", &stem)
+            .replace("{index}", &format!("{index:02}"));
+        format!("{}{file}.wav", &output_path[..name_start])
+    }
+
+    /// Groups the mixer's tracks into stems, in track order.
+    ///
+    /// Per-bus stems are ordered by the first track routed to them.
+    #[must_use]
+    pub fn plan(&self, mixer: &AudioMixer, output_path: &str) -> Vec<Stem> {
+        let mut stems: Vec<Stem> = Vec::new();
+        for strip in mixer.tracks() {
+            match self.layout {
+                StemLayout::PerTrack => stems.push(Stem {
+                    name:      strip.name().to_string(),
+                    path:      String::new(),
+                    track_ids: vec![strip.track_id()],
+                    bus:       None,
+                }),
+                StemLayout::PerBus => {
+                    let bus = strip.output_bus();
+                    if let Some(stem) = stems.iter_mut().find(|s| s.bus == Some(bus)) {
+                        stem.track_ids.push(strip.track_id());
+                    } else {
+                        let name = mixer.get_bus(bus).map_or("Master", |b| b.name());
+                        stems.push(Stem {
+                            name:      name.to_string(),
+                            path:      String::new(),
+                            track_ids: vec![strip.track_id()],
+                            bus:       Some(bus),
+                        });
+                    }
+                },
+            }
+        }
+        for (index, stem) in stems.iter_mut().enumerate() {
+            stem.path = self.stem_path(output_path, index + 1, &stem.name);
+        }
+        stems
+    }
+}
+
+/// Mixes each stem to interleaved stereo.
+///
+/// `track_audio` returns a track's rendered interleaved stereo audio,
+/// starting at the export start. Track faders, pan, mute and solo apply,
+/// then the fader of the stem's bus. Every stem is padded to the longest
+/// stem and at least `min_frames` sample frames.
+pub fn mix_stems(
+    mixer: &AudioMixer, stems: &[Stem], track_audio: &dyn Fn(u64) -> Vec<f32>, min_frames: usize,
+) -> Vec<Vec<f32>> {
+    let mut mixes: Vec<Vec<f32>> = stems
+        .iter()
+        .map(|stem| {
+            let bus_gain = stem
+                .bus
+                .and_then(|id| mixer.get_bus(id))
+                .map_or(1.0, |b| if b.is_muted() { 0.0 } else { b.volume() });
+            let mut mix: Vec<f32> = Vec::new();
+            for &track_id in &stem.track_ids {
+                let Some(strip) = mixer.get_track(track_id) else {
+                    continue;
+                };
+                if !mixer.is_track_audible(track_id) {
+                    continue;
+                }
+                let (left, right) = strip.effective_gain(mixer.pan_law());
+                let audio = track_audio(track_id);
+                if mix.len() < audio.len() {
+                    mix.resize(audio.len(), 0.0);
+                }
+                for (out, frame) in mix.chunks_exact_mut(2).zip(audio.chunks_exact(2)) {
+                    out[0] += frame[0] * left * bus_gain;
+                    out[1] += frame[1] * right * bus_gain;
+                }
+            }
+            mix
+        })
+        .collect();
+
+    let frames = mixes.iter().map(|m| m.len() / 2).max().unwrap_or(0).max(min_frames);
+    for mix in &mut mixes {
+        mix.resize(frames * 2, 0.0);
+    }
+    mixes
+}
+
+/// Encodes interleaved samples as a 24-bit PCM WAV file, tagged with
+/// Broadcast Wave metadata when given.
+///
+/// # Errors
+///
+/// Returns `VideoEditorError::Export` if the audio is too long for a RIFF
+/// file.
+pub fn encode_wav(
+    samples: &[f32], channels: u16, sample_rate: u32, bwf: Option<&BwfMetadata>,
+) -> VideoEditorResult<Vec<u8>> {
+    let data_len = u32::try_from(samples.len() * 3)
+        .ok()
+        .filter(|len| *len <= u32::MAX - 36)
+        .ok_or_else(|| VideoEditorError::Export("Stem is too long for a WAV file".into()))?;
+    let block_align = channels * 3;
+
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * u32::from(block_align)).to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&24u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for &sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * 8_388_607.0).round() as i32;
+        wav.extend_from_slice(&value.to_le_bytes()[..3]);
+    }
+
+    match bwf {
+        Some(bwf) => bwf.write_wav(&wav),
+        None => Ok(wav),
+    }
+}
+
+/// Renders and writes every stem, returning the written paths.
+///
+/// # Errors
+///
+/// Returns `VideoEditorError::Io` if a file can't be written.
+pub fn write_stems(
+    mixer: &AudioMixer, stems: &[Stem], track_audio: &dyn Fn(u64) -> Vec<f32>, min_frames: usize,
+    bwf: Option<&BwfMetadata>,
+) -> VideoEditorResult<Vec<String>> {
+    let mixes = mix_stems(mixer, stems, track_audio, min_frames);
+    stems
+        .iter()
+        .zip(mixes)
+        .map(|(stem, mix)| {
+            let wav = encode_wav(&mix, 2, mixer.sample_rate(), bwf)?;
+            std::fs::write(&stem.path, wav).map_err(|e| VideoEditorError::io(&stem.path, e))?;
+            Ok(stem.path.clone())
+        })
+        .collect()
+}
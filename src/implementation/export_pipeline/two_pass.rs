@@ -0,0 +1,153 @@
+//! Two-pass VBR rate control.
+//!
+//! The first pass renders every frame without encoding and measures how
+//! hard each one is to compress: spatial detail plus motion against the
+//! previous frame. The measurements are written to a stats file next to
+//! the output. The second pass spends the bitrate budget in proportion to
+//! the complexity, compressed by [`QCOMPRESS`] so that busy frames get
+//! more bits without starving quiet ones.
+
+use std::path::Path;
+
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    types::FrameRate,
+};
+
+/// Share of overall progress given to the first pass, which only renders
+/// and analyzes frames.
+pub const FIRST_PASS_WEIGHT: f64 = 0.3;
+
+/// Exponent applied to frame complexity when allocating bits; 0 gives
+/// every frame the same size, 1 makes size proportional to complexity.
+pub const QCOMPRESS: f32 = 0.6;
+
+/// Frame size bounds, as multiples of the average frame size.
+const FRAME_BITS_RANGE: (f32, f32) = (0.25, 4.0);
+
+/// Stats file header line.
+const STATS_HEADER: &str = "#two-pass v1";
+
+/// Per-frame complexity measured by the first pass.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FirstPassStats {
+    /// Complexity of each frame, in range order.
+    pub complexity: Vec<f32>,
+}
+
+impl FirstPassStats {
+    /// Path of the stats file for an output, e.g. `out.mp4.2pass.log`.
+    #[must_use]
+    pub fn stats_path(output_path: &str) -> String {
+        format!("{output_path}.2pass.log")
+    }
+
+    /// Measures an RGBA8 frame: mean luma gradient plus mean luma change
+    /// from the previous frame. Never below 1, so flat black still gets a
+    /// share of the budget.
+    #[must_use]
+    pub fn analyze_frame(pixels: &[u8], previous: Option<&[u8]>, width: u32) -> f32 {
+        let luma = |p: &[u8]| {
+            0.2126 * f32::from(p[0]) + 0.7152 * f32::from(p[1]) + 0.0722 * f32::from(p[2])
+        };
+        let width = width.max(1) as usize;
+        let count = pixels.len() / 4;
+        if count == 0 {
+            return 1.0;
+        }
+
+        let mut spatial = 0.0;
+        for i in 0..count {
+            let y = luma(&pixels[i * 4..]);
+            if (i + 1) % width != 0 && i + 1 < count {
+                spatial += (y - luma(&pixels[(i + 1) * 4..])).abs();
+            }
+            if i + width < count {
+                spatial += (y - luma(&pixels[(i + width) * 4..])).abs();
+            }
+        }
+        let temporal: f32 = previous.filter(|p| p.len() == pixels.len()).map_or(0.0, |previous| {
+            pixels
+                .chunks_exact(4)
+                .zip(previous.chunks_exact(4))
+                .map(|(a, b)| (luma(a) - luma(b)).abs())
+                .sum()
+        });
+        1.0 + (spatial + temporal) / count as f32
+    }
+
+    /// Serializes the stats file.
+    #[must_use]
+    pub fn to_log(&self) -> String {
+        let mut log = format!("{STATS_HEADER} frames={}\n", self.complexity.len());
+        for (frame, complexity) in self.complexity.iter().enumerate() {
+            log.push_str(&format!("frame={frame} complexity={complexity:.4}\n"));
+        }
+        log
+    }
+
+    /// Parses a stats file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Export` if the header is missing, a line
+    /// is malformed or frames are missing.
+    pub fn parse_log(log: &str) -> VideoEditorResult<Self> {
+        let invalid =
+            |what: &str| VideoEditorError::Export(format!("Invalid two-pass stats: {what}"));
+        let mut lines = log.lines();
+        let frames: usize = lines
+            .next()
+            .and_then(|header| header.strip_prefix(STATS_HEADER))
+            .and_then(|rest| rest.trim().strip_prefix("frames="))
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(|| invalid("missing header"))?;
+        let mut complexity = Vec::with_capacity(frames);
+        for line in lines.filter(|l| !l.trim().is_empty()) {
+            let value = line
+                .split_once(" complexity=")
+                .and_then(|(_, v)| v.trim().parse().ok())
+                .ok_or_else(|| invalid(line))?;
+            complexity.push(value);
+        }
+        if complexity.len() != frames {
+            return Err(invalid("frame count mismatch"));
+        }
+        Ok(Self { complexity })
+    }
+
+    /// Writes the stats file.
+    pub fn write(&self, path: &Path) -> VideoEditorResult<()> {
+        std::fs::write(path, self.to_log()).map_err(|e| VideoEditorError::io(path, e))
+    }
+
+    /// Reads a stats file.
+    pub fn read(path: &Path) -> VideoEditorResult<Self> {
+        let log = std::fs::read_to_string(path).map_err(|e| VideoEditorError::io(path, e))?;
+        Self::parse_log(&log)
+    }
+
+    /// Splits a target bitrate over the frames for the second pass.
+    ///
+    /// Returns bits per frame. Sizes follow complexity raised to
+    /// [`QCOMPRESS`], held within a quarter and four times the average,
+    /// and sum to the target for the whole range.
+    #[must_use]
+    pub fn allocate(&self, target_kbps: u32, rate: FrameRate) -> Vec<u64> {
+        let frames = self.complexity.len();
+        if frames == 0 {
+            return Vec::new();
+        }
+        let fps = rate.as_f64().max(f64::EPSILON);
+        let total_bits = f64::from(target_kbps) * 1000.0 * frames as f64 / fps;
+
+        let weights: Vec<f32> =
+            self.complexity.iter().map(|c| c.max(1.0).powf(QCOMPRESS)).collect();
+        let mean = weights.iter().sum::<f32>() / frames as f32;
+        let (low, high) = FRAME_BITS_RANGE;
+        let clamped: Vec<f64> =
+            weights.iter().map(|w| f64::from((w / mean).clamp(low, high))).collect();
+        let scale = total_bits / clamped.iter().sum::<f64>();
+        clamped.iter().map(|w| (w * scale).round() as u64).collect()
+    }
+}
@@ -0,0 +1,296 @@
+//! Test pattern and tone generators.
+//!
+//! Generator sources need no media on disk: SMPTE color bars, gradient
+//! ramps, zone plates and a reference tone. Rendering them through the
+//! output pipeline and measuring the result checks a user's color and audio
+//! setup end to end.
+
+use super::{
+    audio_mixer::AudioMixer,
+    render_target::{RenderTargetDesc, RenderTargetHandle, RenderTargetRegistry},
+};
+
+/// SMPTE 75% color bars (RGB), left to right.
+pub const SMPTE_BARS: [[u8; 3]; 7] = [
+    [191, 191, 191],
+    [191, 191, 0],
+    [0, 191, 191],
+    [0, 191, 0],
+    [191, 0, 191],
+    [191, 0, 0],
+    [0, 0, 191],
+];
+
+/// Video test pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TestPattern {
+    /// SMPTE 75% color bars with castellations and PLUGE row.
+    #[default]
+    SmpteBars,
+    /// Black to white ramp.
+    GradientRamp {
+        /// Ramp runs left to right (`true`) or top to bottom (`false`).
+        horizontal: bool,
+    },
+    /// Circular zone plate reaching Nyquist at the frame edge.
+    ZonePlate,
+}
+
+impl TestPattern {
+    /// Returns the pattern name.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::SmpteBars => "SMPTE Bars",
+            Self::GradientRamp { .. } => "Gradient Ramp",
+            Self::ZonePlate => "Zone Plate",
+        }
+    }
+
+    /// Renders the pattern as RGBA8 pixels.
+    #[must_use]
+    pub fn render(&self, width: u32, height: u32) -> Vec<u8> {
+        let (w, h) = (width as usize, height as usize);
+        let mut pixels = Vec::with_capacity(w * h * 4);
+        for y in 0..h {
+            for x in 0..w {
+                let [r, g, b] = self.pixel(x, y, w, h);
+                pixels.extend_from_slice(&[r, g, b, 255]);
+            }
+        }
+        pixels
+    }
+
+    fn pixel(&self, x: usize, y: usize, w: usize, h: usize) -> [u8; 3] {
+        match self {
+            Self::SmpteBars => {
+                let bar = (x * 7 / w.max(1)).min(6);
+                if y * 3 < h * 2 {
+                    SMPTE_BARS[bar]
+                } else if y * 4 < h * 3 {
+                    // Castellations: reversed blue bars
+                    if bar.is_multiple_of(2) { SMPTE_BARS[6 - bar] } else { [0, 0, 0] }
+                } else {
+                    // -I, 100% white, +Q, black, PLUGE (sub-black, black, above black)
+                    match x * 28 / w.max(1) {
+                        0..=4 => [0, 33, 76],
+                        5..=9 => [255, 255, 255],
+                        10..=14 => [50, 0, 106],
+                        20 => [0, 0, 0],
+                        22 => [10, 10, 10],
+                        _ => [4, 4, 4],
+                    }
+                }
+            },
+            Self::GradientRamp { horizontal } => {
+                let (pos, len) = if *horizontal { (x, w) } else { (y, h) };
+                let v = (pos * 255 / len.saturating_sub(1).max(1)) as u8;
+                [v, v, v]
+            },
+            Self::ZonePlate => {
+                let dx = x as f64 - w as f64 / 2.0;
+                let dy = y as f64 - h as f64 / 2.0;
+                let phase = core::f64::consts::PI * (dx * dx + dy * dy) / w.max(h).max(1) as f64;
+                let v = (127.5 * (1.0 + phase.cos())).round() as u8;
+                [v, v, v]
+            },
+        }
+    }
+}
+
+/// Sine tone generator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneGenerator {
+    /// Frequency in Hz.
+    pub frequency_hz: f32,
+    /// Peak level in dBFS.
+    pub level_dbfs:   f32,
+    /// Channel count (the same tone on every channel).
+    pub channels:     usize,
+}
+
+impl ToneGenerator {
+    /// 1 kHz stereo line-up tone at -23 dBFS (reads -23 LUFS).
+    pub const REFERENCE: Self = Self { frequency_hz: 1000.0, level_dbfs: -23.0, channels: 2 };
+
+    /// Creates a tone generator.
+    #[must_use]
+    pub const fn new(frequency_hz: f32, level_dbfs: f32, channels: usize) -> Self {
+        Self { frequency_hz, level_dbfs, channels }
+    }
+
+    /// Renders interleaved samples starting at sample frame `offset`.
+    #[must_use]
+    pub fn render(&self, sample_rate: u32, offset: u64, frames: usize) -> Vec<f32> {
+        let amplitude = 10f64.powf(f64::from(self.level_dbfs) / 20.0);
+        let step =
+            core::f64::consts::TAU * f64::from(self.frequency_hz) / f64::from(sample_rate.max(1));
+        let channels = self.channels.max(1);
+        (0..frames as u64)
+            .flat_map(|i| {
+                let v = (amplitude * ((offset + i) as f64 * step).sin()) as f32;
+                core::iter::repeat_n(v, channels)
+            })
+            .collect()
+    }
+}
+
+impl Default for ToneGenerator {
+    fn default() -> Self {
+        Self::REFERENCE
+    }
+}
+
+/// Media produced by a generator clip.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeneratorSource {
+    /// Video test pattern.
+    Pattern(TestPattern),
+    /// Audio tone.
+    Tone(ToneGenerator),
+}
+
+impl GeneratorSource {
+    /// Returns a display name for the generator.
+    #[must_use]
+    pub fn name(&self) -> String {
+        match self {
+            Self::Pattern(pattern) => pattern.name().to_string(),
+            Self::Tone(tone) => format!("Tone {} Hz", tone.frequency_hz),
+        }
+    }
+}
+
+/// A single pipeline validation measurement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineCheck {
+    /// What was measured.
+    pub name:     String,
+    /// Expected value.
+    pub expected: f64,
+    /// Measured value.
+    pub measured: f64,
+    /// Whether the measurement is within tolerance.
+    pub passed:   bool,
+}
+
+/// Result of rendering generators through the output pipeline.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineValidation {
+    /// Individual checks.
+    pub checks: Vec<PipelineCheck>,
+}
+
+impl PipelineValidation {
+    /// Returns whether every check passed.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|c| c.passed)
+    }
+
+    /// Returns the failed checks.
+    pub fn failures(&self) -> impl Iterator<Item = &PipelineCheck> {
+        self.checks.iter().filter(|c| !c.passed)
+    }
+
+    fn check(&mut self, name: impl Into<String>, expected: f64, measured: f64, tolerance: f64) {
+        self.checks.push(PipelineCheck {
+            name: name.into(),
+            expected,
+            measured,
+            passed: (expected - measured).abs() <= tolerance,
+        });
+    }
+}
+
+/// Renders bars, a ramp, a zone plate and the reference tone through the
+/// output pipeline and checks the levels and colors that come out.
+///
+/// Video is rendered at `width`x`height` and presented into a half-size CPU
+/// render target; audio runs two seconds of tone through a mixer master bus
+/// at `sample_rate`.
+#[must_use]
+pub fn validate_pipeline(width: u32, height: u32, sample_rate: u32) -> PipelineValidation {
+    let mut report = PipelineValidation::default();
+    let (tw, th) = ((width / 2).max(28), (height / 2).max(4));
+    let mut targets = RenderTargetRegistry::new();
+    let Ok(target) = targets.attach(RenderTargetDesc::new(RenderTargetHandle::CpuBuffer, tw, th))
+    else {
+        return report;
+    };
+
+    let mut present = |pattern: TestPattern| -> Vec<u8> {
+        targets.present(0, &pattern.render(width, height), width, height, false);
+        targets.get(target).map(|t| t.pixels().to_vec()).unwrap_or_default()
+    };
+    let sample = |pixels: &[u8], x: u32, y: u32| -> [u8; 3] {
+        let i = (y as usize * tw as usize + x as usize) * 4;
+        pixels.get(i..i + 3).map_or([0; 3], |p| [p[0], p[1], p[2]])
+    };
+
+    let bars = present(TestPattern::SmpteBars);
+    for (i, expected) in SMPTE_BARS.iter().enumerate() {
+        let x = (2 * i as u32 + 1) * tw / 14;
+        let measured = sample(&bars, x, th / 3);
+        for (c, channel) in ["R", "G", "B"].iter().enumerate() {
+            report.check(
+                format!("bar {} {channel}", i + 1),
+                f64::from(expected[c]),
+                f64::from(measured[c]),
+                2.0,
+            );
+        }
+    }
+
+    let ramp = present(TestPattern::GradientRamp { horizontal: true });
+    report.check("ramp black", 0.0, f64::from(sample(&ramp, 0, th / 2)[0]), 2.0);
+    report.check("ramp white", 255.0, f64::from(sample(&ramp, tw - 1, th / 2)[0]), 2.0);
+    report.check("ramp mid", 127.5, f64::from(sample(&ramp, tw / 2, th / 2)[0]), 4.0);
+
+    let zone = present(TestPattern::ZonePlate);
+    report.check("zone plate center", 255.0, f64::from(sample(&zone, tw / 2, th / 2)[0]), 2.0);
+
+    let tone = ToneGenerator::REFERENCE;
+    let mut mixer = AudioMixer::new(sample_rate, 1024);
+    mixer.master_mut().update_loudness(&tone.render(sample_rate, 0, sample_rate as usize * 2));
+    let reading = mixer.master_loudness();
+    let level = f64::from(tone.level_dbfs);
+    report.check("tone loudness (LUFS)", level, f64::from(reading.integrated_lufs), 0.5);
+    report.check("tone true peak (dBTP)", level, f64::from(reading.true_peak_dbtp), 0.5);
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patterns() {
+        let bars = TestPattern::SmpteBars.render(70, 30);
+        assert_eq!(bars.len(), 70 * 30 * 4);
+        assert_eq!(&bars[..4], &[191, 191, 191, 255]);
+        let last = (10 * 70 + 69) * 4;
+        assert_eq!(&bars[last..last + 3], &[0, 0, 191]);
+
+        let ramp = TestPattern::GradientRamp { horizontal: false }.render(2, 256);
+        assert_eq!(ramp[0], 0);
+        assert_eq!(ramp[(255 * 2) * 4], 255);
+    }
+
+    #[test]
+    fn test_tone_level() {
+        let tone = ToneGenerator::new(1000.0, -6.0, 1);
+        let samples = tone.render(48000, 0, 48);
+        let peak = samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((peak - 0.501).abs() < 0.01);
+        assert_eq!(ToneGenerator::REFERENCE.render(48000, 0, 10).len(), 20);
+    }
+
+    #[test]
+    fn test_validate_pipeline() {
+        let report = validate_pipeline(640, 360, 48000);
+        assert!(report.passed(), "{:?}", report.failures().collect::<Vec<_>>());
+        assert_eq!(report.checks.len(), 7 * 3 + 3 + 1 + 2);
+    }
+}
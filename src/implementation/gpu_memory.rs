@@ -0,0 +1,423 @@
+//! GPU memory budget and texture/buffer pool.
+//!
+//! Released textures and buffers are kept for reuse by later allocations of
+//! the same shape. Cached intermediates (e.g. rendered effect passes) stay
+//! resident until the budget runs out, then the least recently used ones are
+//! evicted. Pressure callbacks fire when the share of memory in active use
+//! crosses a threshold, so hosts can lower preview quality before
+//! allocations start failing.
+
+use super::render_target::RenderTargetFormat;
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    flexforge::VideoEditorMetrics,
+};
+
+/// Shape of a pooled GPU resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GpuResourceDesc {
+    /// 2D texture.
+    Texture {
+        /// Width in pixels.
+        width:  u32,
+        /// Height in pixels.
+        height: u32,
+        /// Pixel format.
+        format: RenderTargetFormat,
+    },
+    /// Untyped buffer.
+    Buffer {
+        /// Size in bytes.
+        size: u64,
+    },
+}
+
+impl GpuResourceDesc {
+    /// Creates an RGBA8 texture description.
+    #[must_use]
+    pub const fn texture(width: u32, height: u32) -> Self {
+        Self::Texture { width, height, format: RenderTargetFormat::Rgba8 }
+    }
+
+    /// Returns the memory footprint in bytes.
+    #[must_use]
+    pub const fn size_bytes(&self) -> u64 {
+        match self {
+            Self::Texture { width, height, format } => {
+                *width as u64 * *height as u64 * format.bytes_per_pixel() as u64
+            },
+            Self::Buffer { size } => *size,
+        }
+    }
+}
+
+/// Unique identifier for a pooled GPU allocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GpuAllocationId(u64);
+
+impl GpuAllocationId {
+    /// Returns the inner ID value.
+    #[must_use]
+    pub const fn inner(&self) -> u64 {
+        self.0
+    }
+}
+
+/// GPU memory pressure level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum MemoryPressure {
+    /// Plenty of headroom.
+    #[default]
+    Normal,
+    /// Active allocations use most of the budget.
+    Elevated,
+    /// Budget nearly exhausted or an allocation failed.
+    Critical,
+}
+
+impl MemoryPressure {
+    /// Share of the budget in active use that raises pressure to elevated.
+    pub const ELEVATED_RATIO: f64 = 0.75;
+    /// Share of the budget in active use that raises pressure to critical.
+    pub const CRITICAL_RATIO: f64 = 0.9;
+
+    /// Returns the pressure for a share of the budget in active use.
+    #[must_use]
+    pub fn from_usage(ratio: f64) -> Self {
+        if ratio >= Self::CRITICAL_RATIO {
+            Self::Critical
+        } else if ratio >= Self::ELEVATED_RATIO {
+            Self::Elevated
+        } else {
+            Self::Normal
+        }
+    }
+}
+
+/// Callback invoked when the memory pressure level changes.
+pub type MemoryPressureCallback = Box<dyn Fn(MemoryPressure) + Send + Sync>;
+
+/// Pool allocation statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GpuMemoryStats {
+    /// Memory budget in bytes.
+    pub budget_bytes:    u64,
+    /// Bytes held by the pool (in use, cached and free).
+    pub allocated_bytes: u64,
+    /// Bytes in active use.
+    pub in_use_bytes:    u64,
+    /// Bytes held by cached intermediates.
+    pub cached_bytes:    u64,
+    /// Bytes released and waiting for reuse.
+    pub free_bytes:      u64,
+    /// New allocations made.
+    pub allocations:     u64,
+    /// Allocations served from released resources.
+    pub reuses:          u64,
+    /// Resources evicted to stay within budget.
+    pub evictions:       u64,
+    /// Allocations refused for lack of memory.
+    pub failures:        u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SlotState {
+    InUse,
+    Cached(u64),
+    Free,
+}
+
+#[derive(Debug, Clone)]
+struct PoolSlot {
+    id:        GpuAllocationId,
+    desc:      GpuResourceDesc,
+    state:     SlotState,
+    last_used: u64,
+}
+
+/// Texture and buffer pool with a memory budget.
+pub struct GpuMemoryPool {
+    budget_bytes: u64,
+    slots:        Vec<PoolSlot>,
+    next_id:      u64,
+    clock:        u64,
+    stats:        GpuMemoryStats,
+    pressure:     MemoryPressure,
+    callbacks:    Vec<MemoryPressureCallback>,
+}
+
+impl GpuMemoryPool {
+    /// Default budget (2 GiB).
+    pub const DEFAULT_BUDGET_BYTES: u64 = 2 << 30;
+
+    /// Creates a pool with a memory budget in bytes.
+    #[must_use]
+    pub fn new(budget_bytes: u64) -> Self {
+        Self {
+            budget_bytes,
+            slots: Vec::new(),
+            next_id: 1,
+            clock: 0,
+            stats: GpuMemoryStats::default(),
+            pressure: MemoryPressure::Normal,
+            callbacks: Vec::new(),
+        }
+    }
+
+    /// Returns the memory budget in bytes.
+    #[must_use]
+    pub const fn budget_bytes(&self) -> u64 {
+        self.budget_bytes
+    }
+
+    /// Changes the budget, evicting free and cached resources to fit.
+    pub fn set_budget(&mut self, budget_bytes: u64) {
+        self.budget_bytes = budget_bytes;
+        self.evict_to_fit(0);
+        self.update_pressure();
+    }
+
+    /// Registers a callback for pressure level changes.
+    pub fn on_pressure(&mut self, callback: MemoryPressureCallback) {
+        self.callbacks.push(callback);
+    }
+
+    /// Returns the current pressure level.
+    #[must_use]
+    pub const fn pressure(&self) -> MemoryPressure {
+        self.pressure
+    }
+
+    /// Allocates a resource, reusing a released one of the same shape.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Gpu` if the resource doesn't fit the
+    /// budget even after evicting every free and cached resource.
+    pub fn allocate(&mut self, desc: GpuResourceDesc) -> VideoEditorResult<GpuAllocationId> {
+        self.clock += 1;
+        if let Some(slot) =
+            self.slots.iter_mut().find(|s| s.state == SlotState::Free && s.desc == desc)
+        {
+            slot.state = SlotState::InUse;
+            slot.last_used = self.clock;
+            self.stats.reuses += 1;
+            let id = slot.id;
+            self.update_pressure();
+            return Ok(id);
+        }
+
+        let size = desc.size_bytes();
+        if !self.evict_to_fit(size) {
+            self.stats.failures += 1;
+            self.set_pressure(MemoryPressure::Critical);
+            return Err(VideoEditorError::Gpu(format!(
+                "GPU memory budget exceeded: {size} bytes requested, {} of {} in use",
+                self.bytes_in(|s| s == SlotState::InUse),
+                self.budget_bytes
+            )));
+        }
+
+        let id = GpuAllocationId(self.next_id);
+        self.next_id += 1;
+        self.slots.push(PoolSlot { id, desc, state: SlotState::InUse, last_used: self.clock });
+        self.stats.allocations += 1;
+        self.update_pressure();
+        Ok(id)
+    }
+
+    /// Returns a resource to the pool for reuse.
+    pub fn release(&mut self, id: GpuAllocationId) -> bool {
+        let Some(slot) = self.slots.iter_mut().find(|s| s.id == id) else {
+            return false;
+        };
+        slot.state = SlotState::Free;
+        self.update_pressure();
+        true
+    }
+
+    /// Keeps a resource resident as the cached intermediate for `key`.
+    ///
+    /// A resource previously cached under the same key is released.
+    pub fn cache(&mut self, id: GpuAllocationId, key: u64) -> bool {
+        if !self.slots.iter().any(|s| s.id == id) {
+            return false;
+        }
+        self.clock += 1;
+        for slot in &mut self.slots {
+            if slot.id == id {
+                slot.state = SlotState::Cached(key);
+                slot.last_used = self.clock;
+            } else if slot.state == SlotState::Cached(key) {
+                slot.state = SlotState::Free;
+            }
+        }
+        self.update_pressure();
+        true
+    }
+
+    /// Looks up a cached intermediate and marks it recently used.
+    pub fn cached(&mut self, key: u64) -> Option<GpuAllocationId> {
+        self.clock += 1;
+        let slot = self.slots.iter_mut().find(|s| s.state == SlotState::Cached(key))?;
+        slot.last_used = self.clock;
+        Some(slot.id)
+    }
+
+    /// Drops every free and cached resource. Returns the bytes freed.
+    pub fn trim(&mut self) -> u64 {
+        let before = self.bytes_in(|_| true);
+        let evicted = self.slots.iter().filter(|s| s.state != SlotState::InUse).count();
+        self.slots.retain(|s| s.state == SlotState::InUse);
+        self.stats.evictions += evicted as u64;
+        self.update_pressure();
+        before - self.bytes_in(|_| true)
+    }
+
+    /// Returns allocation statistics.
+    #[must_use]
+    pub fn stats(&self) -> GpuMemoryStats {
+        GpuMemoryStats {
+            budget_bytes: self.budget_bytes,
+            allocated_bytes: self.bytes_in(|_| true),
+            in_use_bytes: self.bytes_in(|s| s == SlotState::InUse),
+            cached_bytes: self.bytes_in(|s| matches!(s, SlotState::Cached(_))),
+            free_bytes: self.bytes_in(|s| s == SlotState::Free),
+            ..self.stats
+        }
+    }
+
+    /// Copies GPU memory usage into plugin metrics.
+    pub fn fill_metrics(&self, metrics: &mut VideoEditorMetrics) {
+        metrics.gpu_memory_bytes = self.bytes_in(|_| true);
+    }
+
+    fn bytes_in(&self, filter: impl Fn(SlotState) -> bool) -> u64 {
+        self.slots.iter().filter(|s| filter(s.state)).map(|s| s.desc.size_bytes()).sum()
+    }
+
+    /// Evicts free resources, then cached ones least recently used first,
+    /// until `size` more bytes fit. Returns whether they fit.
+    fn evict_to_fit(&mut self, size: u64) -> bool {
+        let mut allocated = self.bytes_in(|_| true);
+        while allocated + size > self.budget_bytes {
+            let victim = self
+                .slots
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| s.state != SlotState::InUse)
+                .min_by_key(|(_, s)| (s.state != SlotState::Free, s.last_used))
+                .map(|(i, _)| i);
+            let Some(index) = victim else {
+                return false;
+            };
+            allocated -= self.slots.remove(index).desc.size_bytes();
+            self.stats.evictions += 1;
+        }
+        true
+    }
+
+    fn update_pressure(&mut self) {
+        let in_use = self.bytes_in(|s| s == SlotState::InUse);
+        let ratio = in_use as f64 / self.budget_bytes.max(1) as f64;
+        self.set_pressure(MemoryPressure::from_usage(ratio));
+    }
+
+    fn set_pressure(&mut self, pressure: MemoryPressure) {
+        if pressure != self.pressure {
+            self.pressure = pressure;
+            for callback in &self.callbacks {
+                callback(pressure);
+            }
+        }
+    }
+}
+
+impl Default for GpuMemoryPool {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_BUDGET_BYTES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{
+        implementation::preview_manager::{PreviewManager, PreviewQuality},
+        types::{FrameRate, Resolution, TimePosition},
+    };
+
+    const MB: u64 = 1 << 20;
+
+    #[test]
+    fn test_reuse_and_budget() {
+        let mut pool = GpuMemoryPool::new(64 * MB);
+        let frame = GpuResourceDesc::texture(1920, 1080);
+        let a = pool.allocate(frame).expect("test assertion");
+        assert!(pool.release(a));
+        let b = pool.allocate(frame).expect("test assertion");
+        assert_eq!(a, b);
+        assert_eq!(pool.stats().reuses, 1);
+
+        let mut metrics = VideoEditorMetrics::default();
+        pool.fill_metrics(&mut metrics);
+        assert_eq!(metrics.gpu_memory_bytes, frame.size_bytes());
+
+        assert!(pool.allocate(GpuResourceDesc::Buffer { size: 64 * MB }).is_err());
+        assert_eq!(pool.stats().failures, 1);
+        assert_eq!(pool.pressure(), MemoryPressure::Critical);
+    }
+
+    #[test]
+    fn test_lru_eviction_of_cached_intermediates() {
+        let mut pool = GpuMemoryPool::new(3 * MB);
+        let desc = GpuResourceDesc::Buffer { size: MB };
+        for key in 0..3 {
+            let id = pool.allocate(desc).expect("test assertion");
+            pool.cache(id, key);
+        }
+        // Touch key 0 so key 1 is the least recently used
+        assert!(pool.cached(0).is_some());
+
+        pool.allocate(GpuResourceDesc::Buffer { size: 2 * MB }).expect("test assertion");
+        assert!(pool.cached(0).is_some());
+        assert!(pool.cached(1).is_none());
+        assert!(pool.cached(2).is_none());
+        let stats = pool.stats();
+        assert_eq!(stats.evictions, 2);
+        assert_eq!(stats.in_use_bytes, 2 * MB);
+
+        pool.set_budget(2 * MB);
+        assert!(pool.cached(0).is_none());
+        assert_eq!(pool.trim(), 0);
+    }
+
+    #[test]
+    fn test_pressure_downgrades_preview() {
+        let mut pool = GpuMemoryPool::new(10 * MB);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        pool.on_pressure(Box::new(move |p| sink.lock().expect("test assertion").push(p)));
+
+        let big = pool.allocate(GpuResourceDesc::Buffer { size: 8 * MB }).expect("test assertion");
+        pool.allocate(GpuResourceDesc::Buffer { size: MB }).expect("test assertion");
+        pool.release(big);
+
+        let events = events.lock().expect("test assertion").clone();
+        assert_eq!(
+            events,
+            vec![MemoryPressure::Elevated, MemoryPressure::Critical, MemoryPressure::Normal]
+        );
+
+        let mut preview =
+            PreviewManager::new(TimePosition::from_secs(10), FrameRate::FPS_30, Resolution::UHD);
+        preview.set_quality(PreviewQuality::Full);
+        assert!(preview.apply_memory_pressure(events[0]));
+        assert_eq!(preview.quality(), PreviewQuality::Half);
+        assert!(preview.apply_memory_pressure(events[1]));
+        assert_eq!(preview.quality(), PreviewQuality::Draft);
+        assert!(!preview.apply_memory_pressure(events[2]));
+    }
+}
@@ -1,15 +1,132 @@
 //! GPU pipeline for accelerated rendering.
 
+use super::{
+    assets::AssetLibrary,
+    camera::{CameraLayer, CompositingMode, track_plane},
+    captions::{CaptionPosition, CaptionStyle},
+    effects::{EffectQuality, EffectType, VideoEffect},
+    gpu_memory::GpuMemoryPool,
+    gpu_scheduler::GpuScheduler,
+    keyframe_animation::AnimationManager,
+    spherical::SphericalView,
+    timeline::TimelineManager,
+    transitions::{ShaderLanguage, Transition, TransitionShaderRegistry, TransitionType},
+    video_filters::{DeinterlaceMode, Deinterlacer, UnsharpMask, VideoDenoiser},
+};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    flexforge::VideoEditorMetrics,
+    scene3d::{AlphaMode, DrawItem, Mat4, Scene3D},
+    trace::{self, TracePhase},
+    types::{Rotation, TimePosition, TrackType, VideoClip},
+};
+
+/// Prepared draw of a custom transition shader.
+#[derive(Debug, Clone)]
+pub struct TransitionDispatch {
+    /// Shader ID.
+    pub shader_id: u64,
+    /// Shader language.
+    pub language:  ShaderLanguage,
+    /// Eased transition progress bound to the `progress` uniform.
+    pub progress:  f32,
+    /// Resolved user uniforms.
+    pub uniforms:  Vec<(String, [f32; 4])>,
+}
+
+/// Prepared compute passes of a denoise or sharpen effect.
+#[derive(Debug, Clone)]
+pub struct FilterDispatch {
+    /// Effect ID.
+    pub effect_id:      u64,
+    /// Kernel name, e.g. `"denoise_good"`.
+    pub kernel:         &'static str,
+    /// Compute passes, e.g. 2 for a separable blur.
+    pub passes:         u32,
+    /// Whether the kernel reads the previous output frame.
+    pub reads_previous: bool,
+    /// Uniforms bound to the kernel.
+    pub uniforms:       Vec<(String, [f32; 4])>,
+}
+
+/// Prepared draws of a 3D layer viewed through its camera.
+#[derive(Debug, Clone)]
+pub struct SceneDispatch {
+    /// Camera view-projection.
+    pub view_projection: Mat4,
+    /// Mesh draws: opaque first, then blended back to front.
+    pub draws:           Vec<DrawItem>,
+}
+
+/// Track plane drawn by the compositor.
+#[derive(Debug, Clone)]
+pub struct LayerDraw {
+    /// Track ID.
+    pub track_id: u64,
+    /// Maps the unit quad `[-0.5, 0.5]²` into clip space.
+    pub mvp:      Mat4,
+}
+
+/// Prepared composite of the timeline's visual tracks at one time.
+#[derive(Debug, Clone)]
+pub struct LayerDispatch {
+    /// Mode the layers were placed with.
+    pub mode:   CompositingMode,
+    /// Track planes in draw order.
+    pub layers: Vec<LayerDraw>,
+}
+
+/// Prepared reprojection of a 360° clip to a flat view.
+#[derive(Debug, Clone)]
+pub struct ReframeDispatch {
+    /// Timeline clip ID.
+    pub clip_id:  u64,
+    /// Kernel name.
+    pub kernel:   &'static str,
+    /// View at the dispatched time.
+    pub view:     SphericalView,
+    /// Uniforms bound to the kernel: the view rotation rows and the lens.
+    pub uniforms: Vec<(String, [f32; 4])>,
+}
+
+/// Caption cue burned into a composited frame.
+#[derive(Debug, Clone)]
+pub struct CaptionDraw {
+    /// Subtitle track ID.
+    pub track_id:   u64,
+    /// Cue ID.
+    pub cue_id:     u64,
+    /// Text lines, top to bottom.
+    pub lines:      Vec<String>,
+    /// Text color (RGBA).
+    pub color:      [u8; 4],
+    /// Background box color (RGBA).
+    pub background: Option<[u8; 4]>,
+    /// Line height in pixels.
+    pub font_px:    f32,
+    /// Maps the unit quad `[-0.5, 0.5]²` onto the cue box in clip space.
+    pub mvp:        Mat4,
+}
+
 /// GPU rendering pipeline.
 pub struct GpuPipeline {
-    enabled:     bool,
-    device_name: Option<String>,
+    enabled:          bool,
+    device_name:      Option<String>,
+    scheduler:        GpuScheduler,
+    memory:           GpuMemoryPool,
+    compositing_mode: CompositingMode,
 }
 
 impl GpuPipeline {
     /// Create a new GPU pipeline.
     pub fn new(enabled: bool) -> Self {
-        Self { enabled, device_name: None }
+        Self {
+            enabled,
+            device_name: None,
+            scheduler: GpuScheduler::new(),
+            memory: GpuMemoryPool::default(),
+            compositing_mode: CompositingMode::default(),
+        }
     }
 
     /// Initialize GPU.
@@ -32,6 +149,333 @@ impl GpuPipeline {
     pub fn device_name(&self) -> Option<&str> {
         self.device_name.as_deref()
     }
+
+    /// Get the GPU work scheduler.
+    pub fn scheduler(&self) -> &GpuScheduler {
+        &self.scheduler
+    }
+
+    /// Get the mutable GPU work scheduler.
+    pub fn scheduler_mut(&mut self) -> &mut GpuScheduler {
+        &mut self.scheduler
+    }
+
+    /// Get the texture and buffer pool.
+    pub fn memory(&self) -> &GpuMemoryPool {
+        &self.memory
+    }
+
+    /// Get the mutable texture and buffer pool.
+    pub fn memory_mut(&mut self) -> &mut GpuMemoryPool {
+        &mut self.memory
+    }
+
+    /// Get the track compositing mode.
+    pub fn compositing_mode(&self) -> CompositingMode {
+        self.compositing_mode
+    }
+
+    /// Set the track compositing mode.
+    pub fn set_compositing_mode(&mut self, mode: CompositingMode) {
+        self.compositing_mode = mode;
+    }
+
+    /// Copy GPU memory usage into plugin metrics.
+    pub fn fill_metrics(&self, metrics: &mut VideoEditorMetrics) {
+        self.memory.fill_metrics(metrics);
+    }
+
+    /// Prepare the draw for a custom shader transition.
+    ///
+    /// Returns `None` for built-in transition types, which the compositor
+    /// renders itself.
+    pub fn dispatch_transition(
+        &self, shaders: &TransitionShaderRegistry, transition: &Transition,
+    ) -> VideoEditorResult<Option<TransitionDispatch>> {
+        let TransitionType::CustomShader(shader_id) = transition.transition_type() else {
+            return Ok(None);
+        };
+        let _span = trace::span(TracePhase::GpuSubmit, "transition");
+        if !self.is_available() {
+            return Err(VideoEditorError::Gpu("GPU not initialized".into()));
+        }
+
+        let shader = shaders.get(shader_id).ok_or_else(|| {
+            VideoEditorError::Gpu(format!("Transition shader not registered: {shader_id}"))
+        })?;
+        let uniforms = shaders.resolve_uniforms(shader_id, transition.parameters())?;
+
+        Ok(Some(TransitionDispatch {
+            shader_id,
+            language: shader.language,
+            progress: transition.eased_progress() as f32,
+            uniforms,
+        }))
+    }
+
+    /// Prepare the compute passes of a denoise, sharpen or deinterlace
+    /// effect.
+    ///
+    /// Pass an effect resolved at the frame time so animated parameters
+    /// apply. Returns `None` for other effect types and for weave, which
+    /// leaves the frame as it is. Quality tiers pick the same kernel sizes
+    /// as the CPU reference in `video_filters`.
+    pub fn dispatch_filter(
+        &self, effect: &VideoEffect, width: u32, height: u32,
+    ) -> VideoEditorResult<Option<FilterDispatch>> {
+        let _span = trace::span(TracePhase::GpuSubmit, "filter");
+        let size =
+            [width as f32, height as f32, 1.0 / width.max(1) as f32, 1.0 / height.max(1) as f32];
+        let quality = effect.quality();
+        let (kernel, passes, reads_previous, params) = match effect.effect_type {
+            EffectType::Denoise => {
+                let denoiser = VideoDenoiser::from_effect(effect)?;
+                let kernel = match quality {
+                    EffectQuality::Draft => "denoise_draft",
+                    EffectQuality::Good => "denoise_good",
+                    EffectQuality::Best => "denoise_best",
+                };
+                let temporal = quality != EffectQuality::Draft && denoiser.temporal > 0.0;
+                let params = [
+                    denoiser.spatial,
+                    if temporal { denoiser.temporal } else { 0.0 },
+                    denoiser.radius() as f32,
+                    0.0,
+                ];
+                (kernel, 1 + u32::from(temporal), temporal, params)
+            },
+            EffectType::Sharpen => {
+                let mask = UnsharpMask::from_effect(effect)?;
+                let kernel = match quality {
+                    EffectQuality::Draft => "unsharp_box",
+                    EffectQuality::Good => "unsharp_gaussian",
+                    EffectQuality::Best => "unsharp_gaussian_luma",
+                };
+                (kernel, 3, false, [mask.amount, mask.radius, mask.threshold, 0.0])
+            },
+            EffectType::Deinterlace => {
+                let deinterlacer = Deinterlacer::from_effect(effect)?;
+                let Some(parity) = deinterlacer.field_order.first_field_parity() else {
+                    return Ok(None);
+                };
+                let (kernel, adaptive) = match deinterlacer.mode {
+                    DeinterlaceMode::Weave => return Ok(None),
+                    DeinterlaceMode::Bob => ("deinterlace_bob", false),
+                    DeinterlaceMode::Adaptive => ("deinterlace_adaptive", true),
+                };
+                (kernel, 1, adaptive, [parity as f32, 0.0, 0.0, 0.0])
+            },
+            _ => return Ok(None),
+        };
+        if !self.is_available() {
+            return Err(VideoEditorError::Gpu("GPU not initialized".into()));
+        }
+
+        Ok(Some(FilterDispatch {
+            effect_id: effect.id,
+            kernel,
+            passes,
+            reads_previous,
+            uniforms: vec![("params".into(), params), ("size".into(), size)],
+        }))
+    }
+
+    /// Prepare the draws for a 3D layer rendered at `aspect`.
+    ///
+    /// `animation` selects an animation index and time in seconds.
+    pub fn dispatch_scene(
+        &self, scene: &Scene3D, aspect: f32, animation: Option<(usize, f32)>,
+    ) -> VideoEditorResult<SceneDispatch> {
+        let _span = trace::span(TracePhase::GpuSubmit, "scene");
+        if !self.is_available() {
+            return Err(VideoEditorError::Gpu("GPU not initialized".into()));
+        }
+
+        let pose = animation.map(|(index, time)| scene.pose(index, time));
+        let view_projection = scene.view_projection(aspect, pose.as_deref());
+        let blended = |draw: &DrawItem| {
+            draw.material
+                .and_then(|m| scene.materials.get(m))
+                .is_some_and(|m| m.alpha_mode == AlphaMode::Blend)
+        };
+        // Clip-space depth of the draw's origin, far first
+        let depth =
+            |draw: &DrawItem| -view_projection.mul(&draw.transform).transform_point([0.0; 3])[2];
+        let mut draws = scene.draw_list(pose.as_deref());
+        draws.sort_by(|a, b| match (blended(a), blended(b)) {
+            (false, true) => std::cmp::Ordering::Less,
+            (true, false) => std::cmp::Ordering::Greater,
+            (true, true) => depth(a).total_cmp(&depth(b)),
+            (false, false) => std::cmp::Ordering::Equal,
+        });
+
+        Ok(SceneDispatch { view_projection, draws })
+    }
+
+    /// Prepare the track planes composited into a `width` x `height` frame.
+    ///
+    /// Flat mode draws visible video tracks with an active clip bottom to
+    /// top, each filling the frame. Perspective mode places them at their Z
+    /// positions, views them through the timeline camera and draws them far
+    /// to near, falling back to track order at equal depth. Planes showing
+    /// rotated media from `assets` are turned upright.
+    pub fn dispatch_layers(
+        &self, timeline: &TimelineManager, assets: &AssetLibrary, time: TimePosition, width: u32,
+        height: u32,
+    ) -> VideoEditorResult<LayerDispatch> {
+        let _span = trace::span(TracePhase::GpuSubmit, "layers");
+        if !self.is_available() {
+            return Err(VideoEditorError::Gpu("GPU not initialized".into()));
+        }
+
+        let mut tracks: Vec<_> = timeline
+            .tracks()
+            .iter()
+            .filter(|t| t.track_type == TrackType::Video && timeline.is_track_visible(t.id))
+            .filter_map(|t| Some((t, t.clips.iter().find(|c| c.enabled && c.contains(time))?)))
+            .collect();
+        tracks.sort_by_key(|(t, _)| t.index);
+
+        let view_projection = match self.compositing_mode {
+            CompositingMode::Flat => {
+                Mat4::orthographic(width as f32 / 2.0, height as f32 / 2.0, -1.0, 1.0)
+            },
+            CompositingMode::Perspective => match timeline.camera() {
+                Some(camera) => camera.state_at(time),
+                None => CameraLayer::new("Camera", height).rest(),
+            }
+            .view_projection(width, height),
+        };
+        let mut layers: Vec<(f32, LayerDraw)> = tracks
+            .into_iter()
+            .map(|(track, clip)| {
+                let rotation = assets
+                    .video_clip(clip.source_id)
+                    .map_or(Rotation::None, VideoClip::effective_rotation);
+                let mut plane = track_plane(track, rotation, width, height);
+                if self.compositing_mode == CompositingMode::Flat {
+                    plane.0[14] = 0.0;
+                }
+                let mvp = view_projection.mul(&plane);
+                (mvp.transform_point([0.0; 3])[2], LayerDraw { track_id: track.id, mvp })
+            })
+            .collect();
+        if self.compositing_mode == CompositingMode::Perspective {
+            // Stable, so equal depths keep track order
+            layers.sort_by(|a, b| b.0.total_cmp(&a.0));
+        }
+
+        Ok(LayerDispatch {
+            mode:   self.compositing_mode,
+            layers: layers.into_iter().map(|(_, draw)| draw).collect(),
+        })
+    }
+
+    /// Prepare the reprojection of the 360° clips shown at `time` to flat
+    /// `width` x `height` views, ahead of compositing.
+    ///
+    /// Visible video tracks are taken bottom to top; each view is read
+    /// from the clip's reframe keyframes in `animation`.
+    pub fn dispatch_reframes(
+        &self, timeline: &TimelineManager, assets: &AssetLibrary, animation: &AnimationManager,
+        time: TimePosition, width: u32, height: u32,
+    ) -> VideoEditorResult<Vec<ReframeDispatch>> {
+        let _span = trace::span(TracePhase::GpuSubmit, "reframe");
+        if !self.is_available() {
+            return Err(VideoEditorError::Gpu("GPU not initialized".into()));
+        }
+
+        let mut tracks: Vec<_> = timeline
+            .tracks()
+            .iter()
+            .filter(|t| t.track_type == TrackType::Video && timeline.is_track_visible(t.id))
+            .collect();
+        tracks.sort_by_key(|t| t.index);
+        let dispatches = tracks
+            .into_iter()
+            .filter_map(|t| t.clips.iter().find(|c| c.enabled && c.contains(time)))
+            .filter(|c| assets.video_clip(c.source_id).is_some_and(|m| m.projection.is_spherical()))
+            .map(|clip| {
+                let view = SphericalView::at(animation, clip.id, time - clip.start);
+                let [r0, r1, r2] = view.rotation();
+                let half_width = view.half_width();
+                let half_height = half_width * height as f32 / width.max(1) as f32;
+                ReframeDispatch {
+                    clip_id: clip.id,
+                    kernel: "equirect_reframe",
+                    view,
+                    uniforms: vec![
+                        ("rotation0".into(), [r0[0], r0[1], r0[2], 0.0]),
+                        ("rotation1".into(), [r1[0], r1[1], r1[2], 0.0]),
+                        ("rotation2".into(), [r2[0], r2[1], r2[2], 0.0]),
+                        ("lens".into(), [half_width, half_height, 0.0, 0.0]),
+                    ],
+                }
+            })
+            .collect();
+        Ok(dispatches)
+    }
+
+    /// Prepare the caption cues burned into a `width` x `height` frame.
+    ///
+    /// Tracks draw in the given order, cues within a track in start order.
+    /// Cue boxes are sized from an average glyph advance of half the line
+    /// height; the text rasterizer lays the lines out inside them.
+    pub fn dispatch_captions(
+        &self, timeline: &TimelineManager, track_ids: &[u64], time: TimePosition, width: u32,
+        height: u32,
+    ) -> VideoEditorResult<Vec<CaptionDraw>> {
+        let _span = trace::span(TracePhase::GpuSubmit, "captions");
+        if !self.is_available() {
+            return Err(VideoEditorError::Gpu("GPU not initialized".into()));
+        }
+
+        let (width, height) = (width as f32, height as f32);
+        let projection = Mat4::orthographic(width / 2.0, height / 2.0, -1.0, 1.0);
+        let mut draws = Vec::new();
+        for &track_id in track_ids {
+            let captions = timeline.caption_track(track_id).ok_or_else(|| {
+                VideoEditorError::timeline(
+                    "render_captions",
+                    format!("Track {track_id} has no captions"),
+                )
+            })?;
+            if !timeline.is_track_visible(track_id) {
+                continue;
+            }
+            for cue in captions.active_cues(time) {
+                let CaptionStyle { position, color, background, font_size } =
+                    captions.resolved_style(cue);
+                let font_px = font_size * height;
+                let lines: Vec<String> = cue.text.lines().map(String::from).collect();
+                let columns = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+                let padding = font_px / 4.0;
+                let box_width = columns as f32 * font_px / 2.0 + padding * 2.0;
+                let box_height = lines.len() as f32 * font_px + padding * 2.0;
+                // Box center from the top-left corner, title-safe at 10%
+                let (x, y) = match position {
+                    CaptionPosition::Bottom => (width / 2.0, height * 0.9 - box_height / 2.0),
+                    CaptionPosition::Top => (width / 2.0, height * 0.1 + box_height / 2.0),
+                    CaptionPosition::Custom { x, y } => (x * width, y * height),
+                };
+                let model = Mat4::from_trs(
+                    [x - width / 2.0, height / 2.0 - y, 0.0],
+                    [0.0, 0.0, 0.0, 1.0],
+                    [box_width, box_height, 1.0],
+                );
+                draws.push(CaptionDraw {
+                    track_id,
+                    cue_id: cue.id,
+                    lines,
+                    color,
+                    background,
+                    font_px,
+                    mvp: projection.mul(&model),
+                });
+            }
+        }
+        Ok(draws)
+    }
 }
 
 impl Default for GpuPipeline {
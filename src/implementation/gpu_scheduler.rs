@@ -0,0 +1,300 @@
+//! GPU work scheduler.
+//!
+//! Arbitrates GPU access between interactive preview rendering and
+//! background work such as exports. Preview work always runs first; export
+//! rendering is split into time slices and checks [`GpuScheduler::should_yield`]
+//! between frames so the UI stays responsive while an export is running.
+
+/// Priority class of GPU work (lower value runs first).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum GpuPriority {
+    /// Interactive preview rendering.
+    Interactive = 0,
+    /// Export rendering.
+    #[default]
+    Export      = 1,
+    /// Background tasks (proxies, thumbnails, analysis).
+    Background  = 2,
+}
+
+impl GpuPriority {
+    /// Returns the display name.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Interactive => "Interactive",
+            Self::Export => "Export",
+            Self::Background => "Background",
+        }
+    }
+}
+
+/// Unique identifier for submitted GPU work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GpuWorkId(u64);
+
+impl GpuWorkId {
+    /// Returns the inner ID value.
+    #[must_use]
+    pub const fn inner(&self) -> u64 {
+        self.0
+    }
+}
+
+/// A unit of GPU work waiting for or holding the GPU.
+#[derive(Debug, Clone)]
+pub struct GpuWorkItem {
+    /// Work ID.
+    pub id:           GpuWorkId,
+    /// Priority class.
+    pub priority:     GpuPriority,
+    /// Description for diagnostics.
+    pub label:        String,
+    /// Estimated GPU time in milliseconds.
+    pub estimated_ms: f64,
+}
+
+/// Time budget for a run of GPU work.
+///
+/// Work charges the time it used; the scheduler decides when the slice must
+/// hand the GPU back.
+#[derive(Debug, Clone, Copy)]
+pub struct GpuTimeSlice {
+    /// Priority class of the work using this slice.
+    pub priority:  GpuPriority,
+    /// Budget in milliseconds.
+    pub budget_ms: f64,
+    /// Time consumed so far in milliseconds.
+    pub used_ms:   f64,
+}
+
+impl GpuTimeSlice {
+    /// Charges GPU time against the slice.
+    pub fn charge(&mut self, ms: f64) {
+        self.used_ms += ms.max(0.0);
+    }
+
+    /// Returns whether the budget is used up.
+    #[must_use]
+    pub fn is_exhausted(&self) -> bool {
+        self.used_ms >= self.budget_ms
+    }
+
+    /// Returns the remaining budget in milliseconds.
+    #[must_use]
+    pub fn remaining_ms(&self) -> f64 {
+        (self.budget_ms - self.used_ms).max(0.0)
+    }
+}
+
+/// Scheduler statistics.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GpuSchedulerStats {
+    /// Interactive work items dispatched.
+    pub interactive_dispatched: u64,
+    /// Export work items dispatched.
+    pub export_dispatched:      u64,
+    /// Background work items dispatched.
+    pub background_dispatched:  u64,
+    /// Times lower priority work yielded the GPU.
+    pub yields:                 u64,
+}
+
+/// Priority-based GPU work scheduler.
+#[derive(Debug)]
+pub struct GpuScheduler {
+    /// Pending work, in submission order.
+    queue:           Vec<GpuWorkItem>,
+    /// Dispatched work not yet completed.
+    running:         Vec<GpuWorkItem>,
+    /// Next work ID.
+    next_id:         u64,
+    /// Time slice for non-interactive work (ms).
+    slice_ms:        f64,
+    /// Frame budget for interactive work (ms).
+    frame_budget_ms: f64,
+    /// Statistics.
+    stats:           GpuSchedulerStats,
+}
+
+impl GpuScheduler {
+    /// Default export time slice in milliseconds.
+    pub const DEFAULT_SLICE_MS: f64 = 8.0;
+
+    /// Creates a new scheduler with the default time slice and a 60 fps
+    /// interactive frame budget.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            queue:           Vec::new(),
+            running:         Vec::new(),
+            next_id:         1,
+            slice_ms:        Self::DEFAULT_SLICE_MS,
+            frame_budget_ms: 1000.0 / 60.0,
+            stats:           GpuSchedulerStats::default(),
+        }
+    }
+
+    /// Sets the non-interactive time slice.
+    pub fn set_slice_ms(&mut self, ms: f64) {
+        self.slice_ms = ms.max(0.5);
+    }
+
+    /// Returns the non-interactive time slice.
+    #[must_use]
+    pub fn slice_ms(&self) -> f64 {
+        self.slice_ms
+    }
+
+    /// Sets the interactive frame budget from a preview frame rate.
+    pub fn set_preview_fps(&mut self, fps: f64) {
+        if fps > 0.0 {
+            self.frame_budget_ms = 1000.0 / fps;
+        }
+    }
+
+    /// Submits GPU work.
+    pub fn submit(
+        &mut self, priority: GpuPriority, label: impl Into<String>, estimated_ms: f64,
+    ) -> GpuWorkId {
+        let id = GpuWorkId(self.next_id);
+        self.next_id += 1;
+        self.queue.push(GpuWorkItem { id, priority, label: label.into(), estimated_ms });
+        id
+    }
+
+    /// Cancels pending work. Returns false if it was not queued.
+    pub fn cancel(&mut self, id: GpuWorkId) -> bool {
+        let before = self.queue.len();
+        self.queue.retain(|w| w.id != id);
+        self.queue.len() != before
+    }
+
+    /// Dispatches the highest priority pending work (FIFO within a class).
+    pub fn next_work(&mut self) -> Option<GpuWorkItem> {
+        let pos =
+            self.queue.iter().enumerate().min_by_key(|(i, w)| (w.priority, *i)).map(|(i, _)| i)?;
+        let item = self.queue.remove(pos);
+
+        match item.priority {
+            GpuPriority::Interactive => self.stats.interactive_dispatched += 1,
+            GpuPriority::Export => self.stats.export_dispatched += 1,
+            GpuPriority::Background => self.stats.background_dispatched += 1,
+        }
+        self.running.push(item.clone());
+        Some(item)
+    }
+
+    /// Marks dispatched work as finished.
+    pub fn complete(&mut self, id: GpuWorkId) -> bool {
+        let before = self.running.len();
+        self.running.retain(|w| w.id != id);
+        self.running.len() != before
+    }
+
+    /// Returns whether work of a strictly higher priority than `priority`
+    /// is pending or running.
+    #[must_use]
+    pub fn has_higher_priority_work(&self, priority: GpuPriority) -> bool {
+        self.queue.iter().chain(self.running.iter()).any(|w| w.priority < priority)
+    }
+
+    /// Returns whether interactive work is pending or running.
+    #[must_use]
+    pub fn has_interactive_work(&self) -> bool {
+        self.has_higher_priority_work(GpuPriority::Export)
+    }
+
+    /// Returns the number of pending work items.
+    #[must_use]
+    pub fn pending_count(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Starts a time slice for work of the given priority.
+    #[must_use]
+    pub fn begin_slice(&self, priority: GpuPriority) -> GpuTimeSlice {
+        let budget_ms = match priority {
+            GpuPriority::Interactive => self.frame_budget_ms,
+            GpuPriority::Export | GpuPriority::Background => self.slice_ms,
+        };
+        GpuTimeSlice { priority, budget_ms, used_ms: 0.0 }
+    }
+
+    /// Yield point: returns true if the work running in `slice` must hand
+    /// the GPU back before doing more.
+    ///
+    /// Interactive work never yields. Other work yields when its slice is
+    /// exhausted or higher priority work is waiting.
+    pub fn should_yield(&mut self, slice: &GpuTimeSlice) -> bool {
+        if slice.priority == GpuPriority::Interactive {
+            return false;
+        }
+        let yield_now = slice.is_exhausted() || self.has_higher_priority_work(slice.priority);
+        if yield_now {
+            self.stats.yields += 1;
+        }
+        yield_now
+    }
+
+    /// Returns scheduler statistics.
+    #[must_use]
+    pub fn stats(&self) -> &GpuSchedulerStats {
+        &self.stats
+    }
+}
+
+impl Default for GpuScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_order() {
+        let mut scheduler = GpuScheduler::new();
+        let export = scheduler.submit(GpuPriority::Export, "export frame", 20.0);
+        let thumb = scheduler.submit(GpuPriority::Background, "thumbnail", 2.0);
+        let preview = scheduler.submit(GpuPriority::Interactive, "preview frame", 5.0);
+
+        assert_eq!(scheduler.next_work().map(|w| w.id), Some(preview));
+        assert_eq!(scheduler.next_work().map(|w| w.id), Some(export));
+        assert_eq!(scheduler.next_work().map(|w| w.id), Some(thumb));
+        assert!(scheduler.next_work().is_none());
+    }
+
+    #[test]
+    fn test_export_yields_to_preview() {
+        let mut scheduler = GpuScheduler::new();
+        let mut slice = scheduler.begin_slice(GpuPriority::Export);
+        slice.charge(1.0);
+        assert!(!scheduler.should_yield(&slice));
+
+        let preview = scheduler.submit(GpuPriority::Interactive, "preview frame", 5.0);
+        assert!(scheduler.should_yield(&slice));
+
+        scheduler.next_work();
+        assert!(scheduler.should_yield(&slice));
+        scheduler.complete(preview);
+        assert!(!scheduler.should_yield(&slice));
+        assert_eq!(scheduler.stats().yields, 2);
+    }
+
+    #[test]
+    fn test_slice_exhaustion() {
+        let mut scheduler = GpuScheduler::new();
+        scheduler.set_slice_ms(4.0);
+        let mut slice = scheduler.begin_slice(GpuPriority::Export);
+        slice.charge(3.0);
+        assert!(!scheduler.should_yield(&slice));
+        slice.charge(1.5);
+        assert!(scheduler.should_yield(&slice));
+
+        let interactive = scheduler.begin_slice(GpuPriority::Interactive);
+        assert!(!scheduler.should_yield(&interactive));
+    }
+}
@@ -0,0 +1,252 @@
+//! Ordered storage with lookup by ID for the managers.
+//!
+//! Export jobs run in queue order, markers sort by position and layers
+//! and transitions keep creation order, so those managers keep their
+//! entries in an [`IdVec`]: a `Vec` in that order plus a map from each ID
+//! to its slot. Getting an entry by ID is a hash lookup. Removing one
+//! leaves a tombstone in its slot instead of shifting the entries after
+//! it, and tombstones are compacted away once they outnumber the live
+//! entries, so removal is amortized O(1) as well. Inserting anywhere but
+//! the end still shifts the entries after it.
+
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, hash_map::Entry},
+    hash::Hash,
+};
+
+/// Entries in order, with lookup by ID.
+///
+/// Like a front-to-back search, the first entry with a given ID wins;
+/// removing it hands the ID over to the next entry that has it.
+#[derive(Debug, Clone)]
+pub(crate) struct IdVec<K, T> {
+    /// Entries in order, `None` where one was removed. Never ends in a
+    /// tombstone.
+    slots:   Vec<Option<(K, T)>>,
+    /// Slot of the first entry with each ID, and how many entries have it.
+    ids:     HashMap<K, (usize, usize)>,
+    /// Tombstones in `slots`.
+    removed: usize,
+}
+
+impl<K: Copy + Eq + Hash, T> IdVec<K, T> {
+    /// Creates an empty list.
+    pub(crate) fn new() -> Self {
+        Self { slots: Vec::new(), ids: HashMap::new(), removed: 0 }
+    }
+
+    /// Number of entries.
+    pub(crate) fn len(&self) -> usize {
+        self.slots.len() - self.removed
+    }
+
+    /// Entry with `id`.
+    pub(crate) fn get(&self, id: K) -> Option<&T> {
+        let &(slot, _) = self.ids.get(&id)?;
+        self.slots[slot].as_ref().map(|(_, entry)| entry)
+    }
+
+    /// Mutable entry with `id`.
+    pub(crate) fn get_mut(&mut self, id: K) -> Option<&mut T> {
+        let &(slot, _) = self.ids.get(&id)?;
+        self.slots[slot].as_mut().map(|(_, entry)| entry)
+    }
+
+    /// Last entry.
+    pub(crate) fn last_mut(&mut self) -> Option<&mut T> {
+        self.slots.last_mut().and_then(Option::as_mut).map(|(_, entry)| entry)
+    }
+
+    /// Entries in order.
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + Clone {
+        self.slots.iter().filter_map(|slot| slot.as_ref().map(|(_, entry)| entry))
+    }
+
+    /// Mutable entries in order.
+    pub(crate) fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> {
+        self.slots.iter_mut().filter_map(|slot| slot.as_mut().map(|(_, entry)| entry))
+    }
+
+    /// Appends `entry` with `id`.
+    pub(crate) fn push(&mut self, id: K, entry: T) {
+        let slot = self.slots.len();
+        self.ids.entry(id).and_modify(|(_, count)| *count += 1).or_insert((slot, 1));
+        self.slots.push(Some((id, entry)));
+    }
+
+    /// Inserts `entry` with `id` after the leading entries for which
+    /// `before` holds, moving the rest back by one.
+    pub(crate) fn insert_after(&mut self, id: K, entry: T, before: impl Fn(&T) -> bool) {
+        self.compact();
+        let position =
+            self.slots.partition_point(|slot| slot.as_ref().is_some_and(|(_, e)| before(e)));
+        self.slots.insert(position, Some((id, entry)));
+        for (slot, _) in self.ids.values_mut() {
+            if *slot >= position {
+                *slot += 1;
+            }
+        }
+        match self.ids.entry(id) {
+            Entry::Occupied(mut first) => {
+                let (slot, count) = first.get_mut();
+                *slot = (*slot).min(position);
+                *count += 1;
+            },
+            Entry::Vacant(first) => {
+                first.insert((position, 1));
+            },
+        }
+    }
+
+    /// Removes the entry with `id`.
+    pub(crate) fn remove(&mut self, id: K) -> Option<T> {
+        let (slot, count) = self.ids.remove(&id)?;
+        let (_, entry) = self.slots[slot].take()?;
+        self.removed += 1;
+        if count > 1 {
+            // Only repeated IDs pay for finding the entry that takes over
+            let next = self.slots[slot + 1..]
+                .iter()
+                .position(|s| s.as_ref().is_some_and(|(k, _)| *k == id))
+                .map(|offset| slot + 1 + offset);
+            if let Some(next) = next {
+                self.ids.insert(id, (next, count - 1));
+            }
+        }
+        while matches!(self.slots.last(), Some(None)) {
+            self.slots.pop();
+            self.removed -= 1;
+        }
+        if self.removed > self.len() {
+            self.compact();
+        }
+        Some(entry)
+    }
+
+    /// Keeps only the entries for which `keep` holds.
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        self.slots.retain(|slot| slot.as_ref().is_some_and(|(_, entry)| keep(entry)));
+        self.removed = 0;
+        self.reindex();
+    }
+
+    /// Stable-sorts the entries with `compare`.
+    pub(crate) fn sort_by(&mut self, mut compare: impl FnMut(&T, &T) -> Ordering) {
+        self.compact();
+        self.slots.sort_by(|a, b| match (a, b) {
+            (Some((_, a)), Some((_, b))) => compare(a, b),
+            _ => Ordering::Equal,
+        });
+        self.reindex();
+    }
+
+    /// Drops the tombstones.
+    fn compact(&mut self) {
+        if self.removed > 0 {
+            self.slots.retain(Option::is_some);
+            self.removed = 0;
+            self.reindex();
+        }
+    }
+
+    /// Rebuilds the ID map from the slots.
+    fn reindex(&mut self) {
+        self.ids.clear();
+        for (slot, entry) in self.slots.iter().enumerate() {
+            if let Some((id, _)) = entry {
+                self.ids.entry(*id).and_modify(|(_, count)| *count += 1).or_insert((slot, 1));
+            }
+        }
+    }
+}
+
+impl<K: Copy + Eq + Hash, T> Default for IdVec<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(list: &IdVec<u64, u64>) -> Vec<u64> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_entries_keep_order() {
+        let mut list = IdVec::new();
+        for id in [10, 20, 30, 40] {
+            list.push(id, id);
+        }
+        list.insert_after(15, 15, |&e| e < 15);
+        assert_eq!(list.remove(30), Some(30));
+        assert_eq!(list.remove(30), None);
+        assert_eq!(ids(&list), [10, 15, 20, 40]);
+        assert_eq!(list.len(), 4);
+        for id in [10, 15, 20, 40] {
+            assert_eq!(list.get(id), Some(&id));
+        }
+
+        // Inserting after a removal lands between the live entries
+        list.insert_after(25, 25, |&e| e < 25);
+        assert_eq!(ids(&list), [10, 15, 20, 25, 40]);
+        *list.get_mut(25).expect("test assertion") += 1;
+        assert_eq!(list.get(25), Some(&26));
+
+        list.retain(|&e| e != 15);
+        list.sort_by(|a, b| b.cmp(a));
+        assert_eq!(ids(&list), [40, 26, 20, 10]);
+        assert_eq!(list.get(10), Some(&10));
+        assert_eq!(list.remove(10), Some(10));
+        assert_eq!(list.last_mut(), Some(&mut 20));
+    }
+
+    #[test]
+    fn test_repeated_ids_resolve_to_the_first() {
+        // Entries are (id, value) with the value telling them apart
+        let mut list = IdVec::new();
+        for (id, value) in [(1, 10), (2, 20), (1, 11), (1, 12)] {
+            list.push(id, value);
+        }
+        assert_eq!(list.get(1), Some(&10));
+        assert_eq!(list.remove(1), Some(10));
+        assert_eq!(list.get(1), Some(&11));
+
+        // An insert before the first entry with an ID takes it over
+        list.insert_after(1, 9, |_| false);
+        assert_eq!(list.get(1), Some(&9));
+        assert_eq!(list.remove(1), Some(9));
+        assert_eq!(list.remove(1), Some(11));
+        assert_eq!(list.remove(1), Some(12));
+        assert_eq!(list.remove(1), None);
+        assert_eq!(ids(&list), [20]);
+    }
+
+    #[test]
+    fn test_removal_at_scale() {
+        const SIZE: u64 = 20_000;
+        let mut list = IdVec::new();
+        for id in 0..SIZE {
+            list.push(id, id);
+        }
+        // Every other entry, front to back, then the back half
+        for id in (0..SIZE).step_by(2) {
+            assert_eq!(list.remove(id), Some(id));
+        }
+        assert_eq!(list.len(), SIZE as usize / 2);
+        for id in (SIZE / 2..SIZE).rev().filter(|id| id % 2 == 1) {
+            assert_eq!(list.remove(id), Some(id));
+        }
+        // Compaction keeps tombstones from outnumbering the entries
+        assert!(list.removed <= list.len());
+        assert!(list.iter().copied().eq((1..SIZE / 2).step_by(2)));
+        for id in (1..SIZE / 2).step_by(2) {
+            assert_eq!(list.get(id), Some(&id));
+        }
+        assert_eq!(list.get(0), None);
+        assert_eq!(list.get(SIZE - 1), None);
+    }
+}
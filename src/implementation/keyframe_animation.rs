@@ -4,6 +4,9 @@
 //! Features: Keyframe management, interpolation, bezier curves,
 //! expression support, and animated parameter control.
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::id_index::IdVec;
 use crate::types::TimePosition;
 
 /// Unique identifier for an animation track.
@@ -153,6 +156,33 @@ impl BezierHandle {
     }
 }
 
+/// How bezier handle lengths are treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TangentMode {
+    /// Handle lengths are free, so a long handle holds the curve near the
+    /// keyframe value for longer.
+    #[default]
+    Weighted,
+    /// Handle lengths are fixed at a third of the segment; only the angle
+    /// can be edited.
+    Unweighted,
+}
+
+/// How a keyframe's incoming and outgoing handles relate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HandleMode {
+    /// Both handles share one slope, so the curve is smooth through the
+    /// keyframe.
+    #[default]
+    Unified,
+    /// Handles are edited independently, allowing a corner.
+    Broken,
+    /// Handles are computed from the neighboring keyframes and follow
+    /// them as keyframes are added, moved or removed. Editing a handle
+    /// switches the keyframe to `Unified`.
+    Auto,
+}
+
 /// Value type for animated properties.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AnimatedValue {
@@ -221,6 +251,70 @@ impl AnimatedValue {
             _ => None,
         }
     }
+
+    /// Components as floats (booleans as 0 or 1).
+    fn components(&self) -> Vec<f64> {
+        match *self {
+            Self::Float(v) => vec![v],
+            Self::Vec2(x, y) => vec![x, y],
+            Self::Vec3(x, y, z) => vec![x, y, z],
+            Self::Vec4(x, y, z, w) => vec![x, y, z, w],
+            Self::Color(r, g, b, a) => vec![r, g, b, a].into_iter().map(f64::from).collect(),
+            Self::Bool(v) => vec![f64::from(u8::from(v))],
+            Self::Int(v) => vec![v as f64],
+        }
+    }
+
+    /// Converts to the type of `like` for pasting onto another property.
+    ///
+    /// Scalars (float, int, bool) convert to each other, rounding to int
+    /// and treating non-zero as true. A scalar fills every component of a
+    /// vector or color, and a vector or color gives its first component as
+    /// a scalar. Vectors and colors convert component-wise, dropping extra
+    /// components and filling missing ones from `like`.
+    #[must_use]
+    pub fn coerce_to(&self, like: &Self) -> Self {
+        let source = self.components();
+        let scalar = source.len() == 1;
+        let target = like.components();
+        let component = |i: usize| {
+            if scalar { source[0] } else { source.get(i).copied().unwrap_or(target[i]) }
+        };
+        match like {
+            Self::Float(_) => Self::Float(source[0]),
+            Self::Int(_) => Self::Int(source[0].round() as i64),
+            Self::Bool(_) => Self::Bool(source[0] != 0.0),
+            Self::Vec2(..) => Self::Vec2(component(0), component(1)),
+            Self::Vec3(..) => Self::Vec3(component(0), component(1), component(2)),
+            Self::Vec4(..) => Self::Vec4(component(0), component(1), component(2), component(3)),
+            Self::Color(..) => Self::Color(
+                component(0) as f32,
+                component(1) as f32,
+                component(2) as f32,
+                component(3) as f32,
+            ),
+        }
+    }
+
+    /// Reflects the value around `pivot`, component-wise. Booleans flip
+    /// when the pivot is false.
+    fn mirrored(&self, pivot: &Self) -> Self {
+        match (*self, *pivot) {
+            (Self::Bool(v), Self::Bool(p)) => Self::Bool(if p { v } else { !v }),
+            _ => {
+                let pivot = pivot.components();
+                let mirrored: Vec<f64> =
+                    self.components().iter().zip(&pivot).map(|(v, p)| 2.0 * p - v).collect();
+                Self::Vec4(
+                    mirrored[0],
+                    mirrored.get(1).copied().unwrap_or(0.0),
+                    mirrored.get(2).copied().unwrap_or(0.0),
+                    mirrored.get(3).copied().unwrap_or(0.0),
+                )
+                .coerce_to(self)
+            },
+        }
+    }
 }
 
 impl Default for AnimatedValue {
@@ -242,6 +336,10 @@ pub struct Keyframe {
     handle_in:     BezierHandle,
     /// Outgoing bezier handle.
     handle_out:    BezierHandle,
+    /// Handle length behavior.
+    tangent_mode:  TangentMode,
+    /// Relation between the two handles.
+    handle_mode:   HandleMode,
     /// Whether keyframe is selected (for UI).
     selected:      bool,
 }
@@ -256,6 +354,8 @@ impl Keyframe {
             interpolation: InterpolationType::default(),
             handle_in: BezierHandle::flat(),
             handle_out: BezierHandle::flat(),
+            tangent_mode: TangentMode::default(),
+            handle_mode: HandleMode::default(),
             selected: false,
         }
     }
@@ -311,6 +411,30 @@ impl Keyframe {
         self.handle_out = handle_out;
     }
 
+    /// Returns the tangent mode.
+    #[must_use]
+    pub const fn tangent_mode(&self) -> TangentMode {
+        self.tangent_mode
+    }
+
+    /// Sets the tangent mode. Use `AnimationTrack::set_tangent_mode` to
+    /// also refit existing handles.
+    pub fn set_tangent_mode(&mut self, mode: TangentMode) {
+        self.tangent_mode = mode;
+    }
+
+    /// Returns the handle mode.
+    #[must_use]
+    pub const fn handle_mode(&self) -> HandleMode {
+        self.handle_mode
+    }
+
+    /// Sets the handle mode. Use `AnimationTrack::set_handle_mode` to also
+    /// realign existing handles.
+    pub fn set_handle_mode(&mut self, mode: HandleMode) {
+        self.handle_mode = mode;
+    }
+
     /// Returns whether keyframe is selected.
     #[must_use]
     pub const fn is_selected(&self) -> bool {
@@ -340,6 +464,32 @@ pub struct AnimationTrack {
     default_value: AnimatedValue,
     /// Loop mode.
     loop_mode:     AnimationLoopMode,
+    /// Keyframe index found by the last lookup.
+    cursor:        KeyframeCursor,
+}
+
+/// Index of the keyframe segment found by the last lookup of a track.
+///
+/// Playback evaluates a track a frame apart, so the next lookup usually
+/// falls in the same segment or the one after it. The index is only a
+/// hint and is checked before use, so edits never need to reset it.
+#[derive(Debug, Default)]
+struct KeyframeCursor(AtomicUsize);
+
+impl KeyframeCursor {
+    fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn set(&self, index: usize) {
+        self.0.store(index, Ordering::Relaxed);
+    }
+}
+
+impl Clone for KeyframeCursor {
+    fn clone(&self) -> Self {
+        Self(AtomicUsize::new(self.get()))
+    }
 }
 
 /// Loop mode for animation tracks.
@@ -370,6 +520,7 @@ impl AnimationTrack {
             muted: false,
             default_value,
             loop_mode: AnimationLoopMode::default(),
+            cursor: KeyframeCursor::default(),
         }
     }
 
@@ -385,6 +536,13 @@ impl AnimationTrack {
         &self.property
     }
 
+    /// Returns the value used without keyframes; it also sets the
+    /// property's value type.
+    #[must_use]
+    pub const fn default_value(&self) -> &AnimatedValue {
+        &self.default_value
+    }
+
     /// Returns whether the track is enabled.
     #[must_use]
     pub const fn is_enabled(&self) -> bool {
@@ -436,24 +594,169 @@ impl AnimationTrack {
             .unwrap_or(self.keyframes.len());
 
         // Check if keyframe already exists at this time
-        if pos > 0 && self.keyframes[pos - 1].time().ms == time.ms {
+        let index = if pos > 0 && self.keyframes[pos - 1].time().ms == time.ms {
             self.keyframes[pos - 1] = keyframe;
             pos - 1
         } else {
             self.keyframes.insert(pos, keyframe);
             pos
-        }
+        };
+        self.refresh_auto_handles();
+        index
     }
 
     /// Removes a keyframe at the specified index.
     pub fn remove_keyframe(&mut self, index: usize) -> Option<Keyframe> {
         if index < self.keyframes.len() {
-            Some(self.keyframes.remove(index))
+            let removed = self.keyframes.remove(index);
+            self.refresh_auto_handles();
+            Some(removed)
         } else {
             None
         }
     }
 
+    /// Sets the outgoing handle of a keyframe, keeping the curve monotonic
+    /// in time.
+    ///
+    /// The handle's time offset is clamped to the segment, or fixed at a
+    /// third of it for unweighted tangents. A unified keyframe turns its
+    /// incoming handle to the same slope, keeping that handle's length;
+    /// an auto keyframe becomes unified. Returns false for a bad index.
+    pub fn set_handle_out(&mut self, index: usize, handle: BezierHandle) -> bool {
+        self.set_handle(index, handle, Side::Out)
+    }
+
+    /// Sets the incoming handle of a keyframe. See `set_handle_out`.
+    pub fn set_handle_in(&mut self, index: usize, handle: BezierHandle) -> bool {
+        self.set_handle(index, handle, Side::In)
+    }
+
+    /// Sets a keyframe's tangent mode, fixing handle lengths at a third of
+    /// the segment when switching to unweighted.
+    pub fn set_tangent_mode(&mut self, index: usize, mode: TangentMode) -> bool {
+        let Some(keyframe) = self.keyframes.get_mut(index) else {
+            return false;
+        };
+        keyframe.tangent_mode = mode;
+        if mode == TangentMode::Unweighted {
+            keyframe.handle_in = Self::fit_handle(keyframe.handle_in, mode);
+            let handle_out = keyframe.handle_out;
+            self.set_handle(index, handle_out, Side::Out);
+        }
+        true
+    }
+
+    /// Sets a keyframe's handle mode. Switching to unified turns the
+    /// incoming handle to the outgoing slope; switching to auto recomputes
+    /// both handles.
+    pub fn set_handle_mode(&mut self, index: usize, mode: HandleMode) -> bool {
+        let Some(keyframe) = self.keyframes.get_mut(index) else {
+            return false;
+        };
+        keyframe.handle_mode = mode;
+        match mode {
+            HandleMode::Unified => self.align_handle(index, Side::In),
+            HandleMode::Auto => self.auto_smooth(index),
+            HandleMode::Broken => {},
+        }
+        true
+    }
+
+    /// Sets the handles of a keyframe from its neighbors (Catmull-Rom
+    /// slopes), flat at the first and last keyframe and at local extremes.
+    /// Slopes are limited so the curve doesn't overshoot either segment.
+    fn auto_smooth(&mut self, index: usize) {
+        let value = |i: usize| self.keyframes[i].value.as_float().unwrap_or(i as f64);
+        let time = |i: usize| self.keyframes[i].time.ms as f64;
+        let slope = if index == 0 || index + 1 >= self.keyframes.len() {
+            0.0
+        } else {
+            let (before, here, after) = (value(index - 1), value(index), value(index + 1));
+            if (here - before) * (after - here) <= 0.0 {
+                0.0
+            } else {
+                (after - before) / (time(index + 1) - time(index - 1)).max(1.0)
+            }
+        };
+
+        let length = 1.0 / 3.0;
+        for side in [Side::In, Side::Out] {
+            let y = match self.segment(index, side) {
+                Some((duration, delta)) if delta != 0.0 => {
+                    (slope * length * duration / delta).clamp(0.0, 1.0)
+                },
+                _ => 0.0,
+            };
+            let keyframe = &mut self.keyframes[index];
+            *side.handle_mut(keyframe) = BezierHandle::new(length, y);
+        }
+    }
+
+    fn refresh_auto_handles(&mut self) {
+        for index in 0..self.keyframes.len() {
+            if self.keyframes[index].handle_mode == HandleMode::Auto {
+                self.auto_smooth(index);
+            }
+        }
+    }
+
+    fn set_handle(&mut self, index: usize, handle: BezierHandle, side: Side) -> bool {
+        let Some(keyframe) = self.keyframes.get_mut(index) else {
+            return false;
+        };
+        *side.handle_mut(keyframe) = Self::fit_handle(handle, keyframe.tangent_mode);
+        if keyframe.handle_mode == HandleMode::Auto {
+            keyframe.handle_mode = HandleMode::Unified;
+        }
+        if keyframe.handle_mode == HandleMode::Unified {
+            self.align_handle(index, side.opposite());
+        }
+        true
+    }
+
+    /// Clamps a handle's time offset so the curve can't run backwards.
+    fn fit_handle(handle: BezierHandle, mode: TangentMode) -> BezierHandle {
+        let x = match mode {
+            TangentMode::Weighted => handle.x.clamp(0.0, 1.0),
+            TangentMode::Unweighted => 1.0 / 3.0,
+        };
+        BezierHandle::new(x, handle.y)
+    }
+
+    /// Turns the `side` handle of a keyframe to the slope of the other one,
+    /// keeping its length.
+    fn align_handle(&mut self, index: usize, side: Side) {
+        let source = side.opposite();
+        let slope = match self.segment(index, source) {
+            Some((duration, delta)) => {
+                let handle = *source.handle_mut(&mut self.keyframes[index]);
+                if handle.x > 0.0 { handle.y * delta / (handle.x * duration) } else { 0.0 }
+            },
+            None => return,
+        };
+        let Some((duration, delta)) = self.segment(index, side) else {
+            return;
+        };
+        let handle = side.handle_mut(&mut self.keyframes[index]);
+        handle.y = if delta == 0.0 { 0.0 } else { slope * handle.x * duration / delta };
+    }
+
+    /// Duration in ms and value change of the segment on one side of a
+    /// keyframe. Non-scalar values change by 1 (handle space).
+    fn segment(&self, index: usize, side: Side) -> Option<(f64, f64)> {
+        let (a, b) = match side {
+            Side::In => (self.keyframes.get(index.checked_sub(1)?)?, self.keyframes.get(index)?),
+            Side::Out => (self.keyframes.get(index)?, self.keyframes.get(index + 1)?),
+        };
+        let duration = (b.time.ms.saturating_sub(a.time.ms) as f64).max(1.0);
+        let delta = match (a.value.as_float(), b.value.as_float()) {
+            (Some(a), Some(b)) => b - a,
+            _ => 1.0,
+        };
+        Some((duration, delta))
+    }
+
     /// Gets keyframe at index.
     #[must_use]
     pub fn get_keyframe(&self, index: usize) -> Option<&Keyframe> {
@@ -471,14 +774,36 @@ impl AnimationTrack {
             return (None, None);
         }
 
-        // Find the first keyframe after or at this time
-        let next_idx = self.keyframes.iter().position(|k| k.time().ms >= time.ms);
+        match self.next_index(time) {
+            i if i == self.keyframes.len() => (self.keyframes.last(), None), // After all keyframes
+            0 => (None, self.keyframes.first()),                             // Before all keyframes
+            i => (Some(&self.keyframes[i - 1]), Some(&self.keyframes[i])),
+        }
+    }
 
-        match next_idx {
-            None => (self.keyframes.last(), None), // After all keyframes
-            Some(0) => (None, self.keyframes.first()), // Before all keyframes
-            Some(i) => (Some(&self.keyframes[i - 1]), Some(&self.keyframes[i])),
+    /// Index of the first keyframe at or after `time`, or the keyframe
+    /// count if all are before it.
+    ///
+    /// Tries the cached segment and the one after it before falling back
+    /// to a binary search.
+    fn next_index(&self, time: TimePosition) -> usize {
+        let keyframes = &self.keyframes;
+        let holds = |i: usize| {
+            i <= keyframes.len()
+                && (i == 0 || keyframes[i - 1].time().ms < time.ms)
+                && (i == keyframes.len() || keyframes[i].time().ms >= time.ms)
+        };
+        let cursor = self.cursor.get();
+        if holds(cursor) {
+            return cursor;
         }
+        let index = if holds(cursor + 1) {
+            cursor + 1
+        } else {
+            keyframes.partition_point(|k| k.time().ms < time.ms)
+        };
+        self.cursor.set(index);
+        index
     }
 
     /// Evaluates the track at a time position.
@@ -531,6 +856,16 @@ impl AnimationTrack {
         }
     }
 
+    /// Evaluates the track at each of `times`, e.g. every frame of an
+    /// export range.
+    ///
+    /// Times in order are looked up from the previous segment, so a batch
+    /// of consecutive frames costs one search.
+    #[must_use]
+    pub fn evaluate_many(&self, times: &[TimePosition]) -> Vec<AnimatedValue> {
+        times.iter().map(|&time| self.evaluate(time)).collect()
+    }
+
     /// Evaluates bezier interpolation.
     fn evaluate_bezier(&self, t: f64, prev: &Keyframe, next: &Keyframe) -> f64 {
         // Cubic bezier evaluation
@@ -606,6 +941,29 @@ impl AnimationTrack {
     }
 }
 
+/// Handle of a keyframe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    In,
+    Out,
+}
+
+impl Side {
+    const fn opposite(self) -> Self {
+        match self {
+            Self::In => Self::Out,
+            Self::Out => Self::In,
+        }
+    }
+
+    fn handle_mut(self, keyframe: &mut Keyframe) -> &mut BezierHandle {
+        match self {
+            Self::In => &mut keyframe.handle_in,
+            Self::Out => &mut keyframe.handle_out,
+        }
+    }
+}
+
 /// Animation layer containing multiple tracks.
 #[derive(Debug)]
 pub struct AnimationLayer {
@@ -717,12 +1075,30 @@ impl AnimationLayer {
     }
 }
 
+/// Options for pasting keyframes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PasteOptions {
+    /// Reflect values around the first pasted value, e.g. turning a fade
+    /// in into a fade out.
+    pub mirror_values: bool,
+    /// Factor applied to the copied keyframe spacing.
+    pub time_scale:    f64,
+}
+
+impl Default for PasteOptions {
+    fn default() -> Self {
+        Self { mirror_values: false, time_scale: 1.0 }
+    }
+}
+
 /// Animation manager for the entire project.
 pub struct AnimationManager {
-    /// Animation layers.
-    layers:   Vec<AnimationLayer>,
+    /// Animation layers by target, in creation order.
+    layers:    IdVec<u64, AnimationLayer>,
     /// Global animation settings.
-    settings: AnimationSettings,
+    settings:  AnimationSettings,
+    /// Copied keyframes, timed relative to the first one.
+    clipboard: Vec<Keyframe>,
 }
 
 /// Global animation settings.
@@ -753,7 +1129,11 @@ impl AnimationManager {
     /// Creates a new animation manager.
     #[must_use]
     pub fn new() -> Self {
-        Self { layers: Vec::new(), settings: AnimationSettings::default() }
+        Self {
+            layers:    IdVec::new(),
+            settings:  AnimationSettings::default(),
+            clipboard: Vec::new(),
+        }
     }
 
     /// Creates a new animation layer.
@@ -766,38 +1146,33 @@ impl AnimationManager {
     pub fn create_layer(
         &mut self, name: impl Into<String>, target_id: u64,
     ) -> crate::errors::VideoEditorResult<&mut AnimationLayer> {
-        self.layers.push(AnimationLayer::new(name, target_id));
+        self.layers.push(target_id, AnimationLayer::new(name, target_id));
         // SAFETY: Element was just pushed, so last_mut will always succeed
         self.layers.last_mut().ok_or_else(|| {
-            crate::VideoEditorError::Timeline("Layer was just added but not found".to_string())
+            crate::VideoEditorError::timeline("add_layer", "Layer was just added but not found")
         })
     }
 
     /// Gets an animation layer by target ID.
     #[must_use]
     pub fn get_layer(&self, target_id: u64) -> Option<&AnimationLayer> {
-        self.layers.iter().find(|l| l.target_id() == target_id)
+        self.layers.get(target_id)
     }
 
     /// Gets a mutable animation layer by target ID.
     pub fn get_layer_mut(&mut self, target_id: u64) -> Option<&mut AnimationLayer> {
-        self.layers.iter_mut().find(|l| l.target_id() == target_id)
+        self.layers.get_mut(target_id)
     }
 
-    /// Returns all layers.
-    #[must_use]
-    pub fn layers(&self) -> &[AnimationLayer] {
-        &self.layers
+    /// Returns all layers, in creation order.
+    pub fn layers(&self) -> impl DoubleEndedIterator<Item = &AnimationLayer> + Clone {
+        self.layers.iter()
     }
 
     /// Removes a layer by target ID.
     pub fn remove_layer(&mut self, target_id: u64) -> bool {
-        if let Some(pos) = self.layers.iter().position(|l| l.target_id() == target_id) {
-            self.layers.remove(pos);
-            true
-        } else {
-            false
-        }
+        // A later layer for the same target takes over
+        self.layers.remove(target_id).is_some()
     }
 
     /// Returns the animation settings.
@@ -816,6 +1191,117 @@ impl AnimationManager {
     pub fn evaluate(&self, target_id: u64, time: TimePosition) -> Vec<(&str, AnimatedValue)> {
         self.get_layer(target_id).map(|l| l.evaluate_all(time)).unwrap_or_default()
     }
+
+    /// Copies the selected keyframes of a property, keeping their spacing,
+    /// values and handles. Returns how many were copied.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Timeline` if the property doesn't exist
+    /// or has no selected keyframes; the clipboard is left unchanged.
+    pub fn copy_keyframes(
+        &mut self, target_id: u64, property: &str,
+    ) -> crate::errors::VideoEditorResult<usize> {
+        let track = self.track(target_id, property)?;
+        let selected: Vec<Keyframe> =
+            track.keyframes.iter().filter(|k| k.selected).cloned().collect();
+        let Some(first) = selected.first().map(Keyframe::time) else {
+            return Err(crate::VideoEditorError::timeline(
+                "copy_keyframes",
+                format!("No keyframes selected on {property}"),
+            ));
+        };
+        self.clipboard = selected
+            .into_iter()
+            .map(|mut k| {
+                k.time = TimePosition::from_ms(k.time.ms - first.ms);
+                k.selected = false;
+                k
+            })
+            .collect();
+        Ok(self.clipboard.len())
+    }
+
+    /// Returns whether keyframes are on the clipboard.
+    #[must_use]
+    pub fn has_copied_keyframes(&self) -> bool {
+        !self.clipboard.is_empty()
+    }
+
+    /// Pastes the clipboard onto a property of any target, starting at
+    /// `at` (usually the playhead).
+    ///
+    /// Values are converted to the property's type with
+    /// `AnimatedValue::coerce_to`. Keyframes at the same time as existing
+    /// ones replace them. Pasted keyframes end up selected. Returns their
+    /// indices.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Timeline` if the clipboard is empty or
+    /// the property doesn't exist.
+    pub fn paste_keyframes(
+        &mut self, target_id: u64, property: &str, at: TimePosition, options: PasteOptions,
+    ) -> crate::errors::VideoEditorResult<Vec<usize>> {
+        if self.clipboard.is_empty() {
+            return Err(crate::VideoEditorError::timeline(
+                "paste_keyframes",
+                "No keyframes copied",
+            ));
+        }
+        let pivot = self.clipboard[0].value;
+        let scale = if options.time_scale.is_finite() { options.time_scale.max(0.0) } else { 1.0 };
+        let pasted: Vec<Keyframe> = self
+            .clipboard
+            .iter()
+            .map(|k| {
+                let mut keyframe = k.clone();
+                keyframe.time =
+                    TimePosition::from_ms(at.ms + (k.time.ms as f64 * scale).round() as u64);
+                if options.mirror_values {
+                    keyframe.value = k.value.mirrored(&pivot);
+                }
+                keyframe.selected = true;
+                keyframe
+            })
+            .collect();
+
+        let track = self.track_mut(target_id, property)?;
+        track.clear_selection();
+        let like = track.default_value;
+        let times: Vec<TimePosition> = pasted.iter().map(Keyframe::time).collect();
+        for mut keyframe in pasted {
+            keyframe.value = keyframe.value.coerce_to(&like);
+            let index = track.add_keyframe(keyframe.time, keyframe.value);
+            track.keyframes[index] = keyframe;
+        }
+        track.refresh_auto_handles();
+        Ok(times.iter().filter_map(|t| track.keyframes.iter().position(|k| k.time == *t)).collect())
+    }
+
+    fn track(
+        &self, target_id: u64, property: &str,
+    ) -> crate::errors::VideoEditorResult<&AnimationTrack> {
+        self.get_layer(target_id).and_then(|l| l.get_track_by_property(property)).ok_or_else(|| {
+            crate::VideoEditorError::timeline(
+                "keyframe_track",
+                format!("No {property} animation on target {target_id}"),
+            )
+        })
+    }
+
+    fn track_mut(
+        &mut self, target_id: u64, property: &str,
+    ) -> crate::errors::VideoEditorResult<&mut AnimationTrack> {
+        self.get_layer_mut(target_id)
+            .and_then(|l| l.get_track_by_property_mut(property))
+            .ok_or_else(|| {
+                crate::VideoEditorError::timeline(
+                    "keyframe_track",
+                    format!("No {property} animation on target {target_id}"),
+                )
+            })
+    }
 }
 
 impl Default for AnimationManager {
@@ -880,6 +1366,43 @@ mod tests {
         assert_eq!(track.keyframes()[2].time().ms, 1000);
     }
 
+    #[test]
+    fn test_keyframe_lookup() {
+        let mut track =
+            AnimationTrack::new(AnimationTrackId::new(1), "scale", AnimatedValue::Float(1.0));
+        for i in 0..200u64 {
+            track.add_keyframe(TimePosition::from_ms(i * 40), AnimatedValue::Float(i as f64));
+        }
+        // First keyframe at or after the time, found by a linear scan
+        let expected = |ms: u64| {
+            let next = track.keyframes().iter().position(|k| k.time().ms >= ms);
+            match next {
+                None => 199.0,
+                Some(i) if track.keyframes()[i].time().ms == ms => i as f64,
+                Some(i) => (i - 1) as f64 + (ms % 40) as f64 / 40.0,
+            }
+        };
+
+        let playback: Vec<_> = (0..8200).step_by(10).map(TimePosition::from_ms).collect();
+        let scattered: Vec<_> =
+            (0..500u64).map(|i| TimePosition::from_ms(i * 7919 % 8200)).collect();
+        for times in [&playback, &scattered] {
+            for (time, value) in times.iter().zip(track.evaluate_many(times)) {
+                let expected = expected(time.ms);
+                assert!(
+                    matches!(value, AnimatedValue::Float(v) if (v - expected).abs() < 1e-9),
+                    "{} ms: {value:?}",
+                    time.ms
+                );
+            }
+        }
+
+        // A stale cursor past the end after removing keyframes
+        track.keyframes_mut().truncate(2);
+        let value = track.evaluate(TimePosition::from_ms(20));
+        assert!(matches!(value, AnimatedValue::Float(v) if (v - 0.5).abs() < 1e-9));
+    }
+
     #[test]
     fn test_animation_layer() {
         let mut layer = AnimationLayer::new("Transform", 1);
@@ -888,4 +1411,125 @@ mod tests {
         assert!(layer.get_track(track_id).is_some());
         assert!(layer.get_track_by_property("position.x").is_some());
     }
+
+    #[test]
+    fn test_layer_lookups_at_scale() {
+        let mut manager = AnimationManager::new();
+        for target_id in 1..=10_000 {
+            manager.create_layer(format!("Layer {target_id}"), target_id).expect("test assertion");
+        }
+        // A second layer for a target stays behind the first
+        manager.create_layer("Second", 5000).expect("test assertion");
+        for target_id in (1..=10_000).filter(|id| id % 3 != 0) {
+            assert!(manager.remove_layer(target_id));
+        }
+        assert_eq!(manager.layers().count(), 3334);
+        assert!(manager.get_layer(9999).is_some());
+        assert!(manager.get_layer(10_000).is_none());
+        assert_eq!(manager.get_layer(5000).map(AnimationLayer::name), Some("Second"));
+        assert!(manager.remove_layer(5000));
+        assert!(!manager.remove_layer(5000));
+        assert_eq!(manager.layers().last().map(AnimationLayer::target_id), Some(9999));
+    }
+
+    #[test]
+    fn test_bezier_handle_editing() {
+        let mut track =
+            AnimationTrack::new(AnimationTrackId::new(1), "opacity", AnimatedValue::Float(0.0));
+        for (ms, value) in [(0, 0.0), (1000, 10.0), (2000, 30.0), (3000, 20.0)] {
+            let index = track.add_keyframe(TimePosition::from_ms(ms), AnimatedValue::Float(value));
+            track.keyframes_mut()[index].set_interpolation(InterpolationType::Bezier);
+        }
+
+        // Handles can't reach past the segment, so time stays monotonic
+        assert!(track.set_handle_out(0, BezierHandle::new(1.7, 0.5)));
+        assert!((track.keyframes()[0].handle_out().x - 1.0).abs() < 1e-9);
+        assert!(!track.set_handle_out(9, BezierHandle::flat()));
+
+        // Unified: a 10/1000 slope out of key 1 (segment delta 20) shows up
+        // on the incoming side (delta 10) with the same slope
+        assert!(track.set_handle_out(1, BezierHandle::new(0.5, 0.25)));
+        let handle_in = *track.keyframes()[1].handle_in();
+        assert!((handle_in.y / handle_in.x * 10.0 - 0.5 * 20.0).abs() < 1e-9);
+
+        // Broken handles move on their own
+        track.set_handle_mode(1, HandleMode::Broken);
+        track.set_handle_out(1, BezierHandle::new(0.5, 0.0));
+        assert_eq!(track.keyframes()[1].handle_in().y, handle_in.y);
+
+        // Unweighted tangents fix the length at a third
+        track.set_tangent_mode(2, TangentMode::Unweighted);
+        track.set_handle_in(2, BezierHandle::new(0.9, 0.2));
+        assert!((track.keyframes()[2].handle_in().x - 1.0 / 3.0).abs() < 1e-9);
+
+        // Auto: key 2 is a peak so it goes flat; key 1 follows its neighbors
+        track.set_handle_mode(1, HandleMode::Auto);
+        track.set_handle_mode(2, HandleMode::Auto);
+        assert_eq!(track.keyframes()[2].handle_out().y, 0.0);
+        let before = track.keyframes()[1].handle_out().y;
+        assert!(before > 0.0 && before <= 1.0);
+        track.add_keyframe(TimePosition::from_ms(500), AnimatedValue::Float(9.0));
+        assert_ne!(track.keyframes()[2].handle_out().y, before);
+
+        // Editing an auto handle makes it unified
+        track.set_handle_out(2, BezierHandle::new(0.3, 0.1));
+        assert_eq!(track.keyframes()[2].handle_mode(), HandleMode::Unified);
+        let sampled: Vec<f64> = (0..=30)
+            .filter_map(|i| track.evaluate(TimePosition::from_ms(i * 100)).as_float())
+            .collect();
+        assert!(sampled.iter().all(|v| v.is_finite()));
+    }
+
+    #[test]
+    fn test_keyframe_clipboard() {
+        let mut manager = AnimationManager::new();
+        let layer = manager.create_layer("Title", 1).expect("test assertion");
+        layer.create_track("opacity", AnimatedValue::Float(1.0));
+        let track = layer.get_track_by_property_mut("opacity").expect("test assertion");
+        for (ms, value) in [(1000, 0.0), (1500, 1.0), (3000, 0.25)] {
+            track.add_keyframe(TimePosition::from_ms(ms), AnimatedValue::Float(value));
+        }
+        let layer = manager.create_layer("Logo", 2).expect("test assertion");
+        layer.create_track("scale", AnimatedValue::Vec2(1.0, 1.0));
+        layer.create_track("visible", AnimatedValue::Bool(true));
+
+        assert!(manager.copy_keyframes(1, "opacity").is_err());
+        let track = manager.get_layer_mut(1).and_then(|l| l.get_track_by_property_mut("opacity"));
+        track
+            .expect("test assertion")
+            .select_range(TimePosition::from_ms(1000), TimePosition::from_ms(1500));
+        assert_eq!(manager.copy_keyframes(1, "opacity").expect("test assertion"), 2);
+
+        // Floats fill both components of a vector, at the playhead
+        let at = TimePosition::from_secs(10);
+        let pasted = manager
+            .paste_keyframes(2, "scale", at, PasteOptions::default())
+            .expect("test assertion");
+        let scale = manager.get_layer(2).and_then(|l| l.get_track_by_property("scale"));
+        let keys = scale.expect("test assertion").keyframes();
+        assert_eq!(pasted, [0, 1]);
+        assert_eq!(keys[1].time(), TimePosition::from_ms(10_500));
+        assert_eq!(*keys[1].value(), AnimatedValue::Vec2(1.0, 1.0));
+
+        // Mirrored and stretched: the fade in becomes a slower fade out
+        let options = PasteOptions { mirror_values: true, time_scale: 2.0 };
+        manager
+            .paste_keyframes(1, "opacity", TimePosition::from_secs(4), options)
+            .expect("test assertion");
+        let opacity = manager.get_layer(1).and_then(|l| l.get_track_by_property("opacity"));
+        let keys = opacity.expect("test assertion").keyframes();
+        assert_eq!(keys.len(), 5);
+        assert_eq!(keys[4].time(), TimePosition::from_secs(5));
+        assert_eq!(*keys[4].value(), AnimatedValue::Float(-1.0));
+        assert!(keys[3].is_selected() && !keys[0].is_selected());
+
+        manager.paste_keyframes(2, "visible", at, PasteOptions::default()).expect("test assertion");
+        let visible = manager.get_layer(2).and_then(|l| l.get_track_by_property("visible"));
+        let keys = visible.expect("test assertion").keyframes();
+        assert_eq!(
+            (*keys[0].value(), *keys[1].value()),
+            (AnimatedValue::Bool(false), AnimatedValue::Bool(true))
+        );
+        assert!(manager.paste_keyframes(3, "scale", at, PasteOptions::default()).is_err());
+    }
 }
@@ -4,6 +4,12 @@
 //! Features: Marker types, marker filtering, chapters,
 //! import/export, and navigation helpers.
 
+use std::collections::HashSet;
+
+use super::{
+    events::{EditorEvent, EventBus},
+    id_index::IdVec,
+};
 use crate::{
     errors::{VideoEditorError, VideoEditorResult},
     types::{TimePosition, Timestamp},
@@ -93,6 +99,22 @@ impl MarkerType {
     }
 }
 
+/// A note attached to a marker, e.g. a reply in a review thread.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MarkerNote {
+    /// Note author.
+    pub author: Option<String>,
+    /// Note text.
+    pub text:   String,
+}
+
+impl MarkerNote {
+    /// Creates a note.
+    pub fn new(author: Option<&str>, text: impl Into<String>) -> Self {
+        Self { author: author.map(String::from), text: text.into() }
+    }
+}
+
 /// A marker on the timeline.
 #[derive(Debug, Clone)]
 pub struct Marker {
@@ -120,6 +142,8 @@ pub struct Marker {
     modified_at: Timestamp,
     /// Author/creator.
     author:      Option<String>,
+    /// Notes, oldest first.
+    notes:       Vec<MarkerNote>,
 }
 
 impl Marker {
@@ -140,6 +164,7 @@ impl Marker {
             created_at: now,
             modified_at: now,
             author: None,
+            notes: Vec::new(),
         }
     }
 
@@ -321,6 +346,23 @@ impl Marker {
         self.author = Some(author.into());
     }
 
+    /// Returns the notes, oldest first.
+    #[must_use]
+    pub fn notes(&self) -> &[MarkerNote] {
+        &self.notes
+    }
+
+    /// Appends a note unless the marker already has the same one.
+    /// Returns whether it was added.
+    pub fn add_note(&mut self, note: MarkerNote) -> bool {
+        if self.notes.contains(&note) {
+            return false;
+        }
+        self.notes.push(note);
+        self.modified_at = Timestamp::now();
+        true
+    }
+
     /// Checks if position falls within this marker's range.
     #[must_use]
     pub fn contains(&self, position: TimePosition) -> bool {
@@ -433,13 +475,15 @@ impl MarkerFilter {
 /// Manager for timeline markers.
 pub struct MarkerManager {
     /// All markers (sorted by position).
-    markers:    Vec<Marker>,
+    markers:    IdVec<MarkerId, Marker>,
     /// Next marker ID.
     next_id:    u64,
     /// Available tags (for autocomplete).
     known_tags: Vec<String>,
     /// Selection state (selected marker IDs).
     selection:  Vec<MarkerId>,
+    /// Change notifications.
+    events:     EventBus,
 }
 
 impl MarkerManager {
@@ -447,13 +491,19 @@ impl MarkerManager {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            markers:    Vec::new(),
+            markers:    IdVec::new(),
             next_id:    1,
             known_tags: Vec::new(),
             selection:  Vec::new(),
+            events:     EventBus::new(),
         }
     }
 
+    /// Emits change events on a shared bus.
+    pub fn set_event_bus(&mut self, events: EventBus) {
+        self.events = events;
+    }
+
     /// Generates a new marker ID.
     fn next_id(&mut self) -> MarkerId {
         let id = MarkerId::new(self.next_id);
@@ -464,64 +514,70 @@ impl MarkerManager {
     /// Adds a marker at the specified position.
     pub fn add_marker(&mut self, position: TimePosition, marker_type: MarkerType) -> MarkerId {
         let id = self.next_id();
-        let marker = Marker::new(id, position, marker_type);
-
-        // Insert in sorted order
-        let pos = self
-            .markers
-            .iter()
-            .position(|m| m.position().ms > position.ms)
-            .unwrap_or(self.markers.len());
-        self.markers.insert(pos, marker);
-
+        self.insert_sorted(Marker::new(id, position, marker_type));
         id
     }
 
     /// Adds a chapter marker.
     pub fn add_chapter(&mut self, position: TimePosition, name: impl Into<String>) -> MarkerId {
         let id = self.next_id();
-        let marker = Marker::chapter(id, position, name);
-
-        let pos = self
-            .markers
-            .iter()
-            .position(|m| m.position().ms > position.ms)
-            .unwrap_or(self.markers.len());
-        self.markers.insert(pos, marker);
-
+        self.insert_sorted(Marker::chapter(id, position, name));
         id
     }
 
+    /// Inserts a new marker after any others at its position.
+    fn insert_sorted(&mut self, marker: Marker) {
+        let id = marker.id();
+        let ms = marker.position().ms;
+        self.markers.insert_after(id, marker, |m| m.position().ms <= ms);
+        self.events.emit(EditorEvent::MarkerAdded { marker_id: id.inner() });
+    }
+
     /// Removes a marker by ID.
     pub fn remove_marker(&mut self, id: MarkerId) -> bool {
-        if let Some(pos) = self.markers.iter().position(|m| m.id() == id) {
-            let marker = &self.markers[pos];
-            if marker.is_locked() {
-                return false;
-            }
-            self.markers.remove(pos);
-            self.selection.retain(|&mid| mid != id);
-            true
-        } else {
-            false
+        if self.get_marker(id).is_none_or(Marker::is_locked) {
+            return false;
         }
+        self.markers.remove(id);
+        self.selection.retain(|&mid| mid != id);
+        self.events.emit(EditorEvent::MarkerRemoved { marker_id: id.inner() });
+        true
     }
 
     /// Gets a marker by ID.
     #[must_use]
     pub fn get_marker(&self, id: MarkerId) -> Option<&Marker> {
-        self.markers.iter().find(|m| m.id() == id)
+        self.markers.get(id)
     }
 
     /// Gets a mutable marker by ID.
     pub fn get_marker_mut(&mut self, id: MarkerId) -> Option<&mut Marker> {
-        self.markers.iter_mut().find(|m| m.id() == id)
+        self.markers.get_mut(id)
     }
 
-    /// Returns all markers.
-    #[must_use]
-    pub fn markers(&self) -> &[Marker] {
-        &self.markers
+    /// Edits an unlocked marker in place and announces the change.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Timeline` if the marker doesn't exist or
+    /// is locked.
+    pub fn update_marker(
+        &mut self, id: MarkerId, edit: impl FnOnce(&mut Marker),
+    ) -> VideoEditorResult<()> {
+        let marker = self
+            .get_marker_mut(id)
+            .ok_or_else(|| VideoEditorError::timeline("update_marker", "Marker not found"))?;
+        if marker.is_locked() {
+            return Err(VideoEditorError::timeline("update_marker", "Marker is locked"));
+        }
+        edit(marker);
+        self.events.emit(EditorEvent::MarkerChanged { marker_id: id.inner() });
+        Ok(())
+    }
+
+    /// Returns all markers, sorted by position.
+    pub fn markers(&self) -> impl DoubleEndedIterator<Item = &Marker> + Clone {
+        self.markers.iter()
     }
 
     /// Returns markers matching a filter.
@@ -568,13 +624,11 @@ impl MarkerManager {
     ) -> VideoEditorResult<()> {
         // Find and update marker
         let marker = self
-            .markers
-            .iter_mut()
-            .find(|m| m.id() == id)
-            .ok_or_else(|| VideoEditorError::Timeline("Marker not found".into()))?;
+            .get_marker_mut(id)
+            .ok_or_else(|| VideoEditorError::timeline("move_marker", "Marker not found"))?;
 
         if marker.is_locked() {
-            return Err(VideoEditorError::Timeline("Marker is locked".into()));
+            return Err(VideoEditorError::timeline("move_marker", "Marker is locked"));
         }
 
         marker.position = new_position;
@@ -582,6 +636,7 @@ impl MarkerManager {
 
         // Re-sort markers
         self.markers.sort_by(|a, b| a.position().ms.cmp(&b.position().ms));
+        self.events.emit(EditorEvent::MarkerChanged { marker_id: id.inner() });
 
         Ok(())
     }
@@ -627,8 +682,10 @@ impl MarkerManager {
             .copied()
             .collect();
 
+        let deleted: HashSet<_> = to_delete.iter().copied().collect();
+        self.markers.retain(|m| !deleted.contains(&m.id()));
         for id in to_delete {
-            self.markers.retain(|m| m.id() != id);
+            self.events.emit(EditorEvent::MarkerRemoved { marker_id: id.inner() });
         }
         self.selection.clear();
     }
@@ -664,17 +721,19 @@ impl MarkerManager {
         let to_remove: Vec<_> =
             self.markers.iter().filter(|m| !m.is_locked()).map(|m| m.id()).collect();
 
+        self.markers.retain(Marker::is_locked);
         for id in to_remove {
-            self.markers.retain(|m| m.id() != id);
+            self.events.emit(EditorEvent::MarkerRemoved { marker_id: id.inner() });
         }
         self.selection.clear();
     }
 
     /// Creates chapters from markers.
     pub fn create_chapters_from_markers(&mut self, marker_type: MarkerType) {
-        for marker in &mut self.markers {
+        for marker in self.markers.iter_mut() {
             if marker.marker_type() == marker_type && !marker.is_locked() {
                 marker.set_marker_type(MarkerType::Chapter);
+                self.events.emit(EditorEvent::MarkerChanged { marker_id: marker.id().inner() });
             }
         }
     }
@@ -699,6 +758,31 @@ mod tests {
         assert_eq!(manager.count(), 1);
     }
 
+    #[test]
+    fn test_lookups_at_scale() {
+        // Inserted back to front, so every marker lands at the start
+        let mut manager = MarkerManager::new();
+        let ids: Vec<_> = (0..10_000u64)
+            .rev()
+            .map(|i| manager.add_marker(TimePosition::from_ms(i * 40), MarkerType::Standard))
+            .collect();
+        for &id in ids.iter().step_by(2) {
+            assert!(manager.remove_marker(id));
+            assert!(!manager.remove_marker(id));
+        }
+        assert_eq!(manager.count(), 5000);
+        for &id in ids.iter().skip(1).step_by(2) {
+            assert_eq!(manager.get_marker(id).map(Marker::id), Some(id));
+        }
+        let positions: Vec<_> = manager.markers().map(|m| m.position().ms).collect();
+        assert!(positions.is_sorted());
+
+        // A marker added after removals sorts in among the survivors
+        let id = manager.add_marker(TimePosition::from_ms(60), MarkerType::Standard);
+        assert_eq!(manager.markers().nth(1).map(Marker::id), Some(id));
+        assert_eq!(manager.next_marker(TimePosition::from_ms(40)).map(Marker::id), Some(id));
+    }
+
     #[test]
     fn test_chapter_markers() {
         let mut manager = MarkerManager::new();
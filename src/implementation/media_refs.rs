@@ -0,0 +1,257 @@
+//! Portable media references.
+//!
+//! A project records each media file by absolute path, by path relative to
+//! the project file and by content digest. Moving a project together with
+//! its media keeps the relative path valid; media found elsewhere is only
+//! relinked when its digest matches. Consolidation rewrites the references
+//! to trimmed copies holding only the ranges a timeline uses.
+
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    checksum::FileDigest,
+    errors::{VideoEditorError, VideoEditorResult},
+    types::TimePosition,
+};
+
+/// Where a media file referenced by a project lives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaReference {
+    /// Absolute path when last seen.
+    pub absolute: String,
+    /// Path relative to the project file's directory, `/`-separated.
+    pub relative: Option<String>,
+    /// Content digest, if the file has been hashed.
+    pub digest:   Option<FileDigest>,
+}
+
+impl MediaReference {
+    /// Reference a path; relative paths resolve against `project_dir`.
+    pub fn new(path: &str, project_dir: Option<&Path>) -> Self {
+        let absolute = match project_dir {
+            Some(dir) if Path::new(path).is_relative() => normalize(&dir.join(path)),
+            _ => normalize(Path::new(path)),
+        };
+        let relative = project_dir.and_then(|dir| relative_path(&absolute, dir));
+        Self { absolute: absolute.to_string_lossy().into_owned(), relative, digest: None }
+    }
+
+    /// Reference a file and record its digest.
+    pub fn hashed(path: &str, project_dir: Option<&Path>) -> VideoEditorResult<Self> {
+        let mut reference = Self::new(path, project_dir);
+        reference.digest = Some(
+            FileDigest::of_file(&reference.absolute)
+                .map_err(|e| VideoEditorError::io(&reference.absolute, e))?,
+        );
+        Ok(reference)
+    }
+
+    /// Recompute the relative path for a project saved in `project_dir`.
+    pub fn rebase(&mut self, project_dir: Option<&Path>) {
+        self.relative = project_dir.and_then(|dir| relative_path(Path::new(&self.absolute), dir));
+    }
+
+    /// Point the reference at a new location, keeping its digest.
+    pub fn relocate(&mut self, path: &Path, project_dir: Option<&Path>) {
+        self.absolute = normalize(path).to_string_lossy().into_owned();
+        self.rebase(project_dir);
+    }
+
+    /// Find the file: at its absolute path, else relative to `project_dir`.
+    pub fn resolve(&self, project_dir: Option<&Path>) -> Option<PathBuf> {
+        let absolute = PathBuf::from(&self.absolute);
+        if absolute.is_file() {
+            return Some(absolute);
+        }
+        let relative = normalize(&project_dir?.join(self.relative.as_deref()?));
+        relative.is_file().then_some(relative)
+    }
+
+    /// Returns whether a file is this media: same digest, or same file name
+    /// when the reference was never hashed.
+    pub fn matches(&self, path: &Path) -> bool {
+        match self.digest {
+            Some(digest) => {
+                // Size is free to check and rules out most candidates
+                fs::metadata(path).is_ok_and(|m| m.len() == digest.size)
+                    && FileDigest::of_file(path).is_ok_and(|d| d == digest)
+            },
+            None => path.file_name() == Path::new(&self.absolute).file_name(),
+        }
+    }
+
+    /// File name of the media.
+    pub fn file_name(&self) -> Option<&str> {
+        Path::new(&self.absolute).file_name().and_then(|n| n.to_str())
+    }
+}
+
+/// Outcome of gathering project media into one folder.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CollectReport {
+    /// `(from, to)` for each file written.
+    pub collected: Vec<(String, String)>,
+    /// References whose media could not be found.
+    pub missing:   Vec<String>,
+    /// Bytes written.
+    pub bytes:     u64,
+}
+
+/// Outcome of searching for missing media.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelinkReport {
+    /// `(old, new)` absolute paths of relinked references.
+    pub relinked: Vec<(String, String)>,
+    /// References still missing.
+    pub missing:  Vec<String>,
+}
+
+/// How much of each media file consolidation keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsolidateOptions {
+    /// Source media kept before and after each used range, for trimming
+    /// later.
+    pub handles: TimePosition,
+}
+
+impl Default for ConsolidateOptions {
+    fn default() -> Self {
+        Self { handles: TimePosition::from_secs(1) }
+    }
+}
+
+/// Used range of a media file, written to its own file by consolidation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsolidateSegment {
+    /// Asset the media belongs to.
+    pub asset_id: u64,
+    /// Source media file.
+    pub source:   PathBuf,
+    /// Start of the kept source range, handles included.
+    pub start:    TimePosition,
+    /// End of the kept source range, handles included.
+    pub end:      TimePosition,
+}
+
+impl ConsolidateSegment {
+    /// File name for the segment: the source file stem followed by the
+    /// kept range, e.g. `take_4000-9500ms.evlf`.
+    #[must_use]
+    pub fn file_name(&self, extension: &str) -> String {
+        let stem = self.source.file_stem().map_or_else(|| "media".into(), |s| s.to_string_lossy());
+        format!("{stem}_{}-{}ms.{extension}", self.start.ms, self.end.ms)
+    }
+}
+
+/// Outcome of consolidating a project to the media it uses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConsolidateReport {
+    /// `(old asset, new asset, written path)` for each segment.
+    pub segments:     Vec<(u64, u64, String)>,
+    /// Used media that could not be found.
+    pub missing:      Vec<String>,
+    /// Size of the source files that were consolidated.
+    pub source_bytes: u64,
+    /// Bytes written.
+    pub bytes:        u64,
+}
+
+impl ConsolidateReport {
+    /// Disk space freed once the consolidated source files are removed.
+    #[must_use]
+    pub fn reclaimed_bytes(&self) -> u64 {
+        self.source_bytes.saturating_sub(self.bytes)
+    }
+}
+
+/// Lexically resolve `.` and `..` without touching the filesystem.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            },
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// `/`-separated path from `base` to `path`, if they share a root.
+pub(crate) fn relative_path(path: &Path, base: &Path) -> Option<String> {
+    let path = normalize(path);
+    let base = normalize(base);
+    let (path, base): (Vec<_>, Vec<_>) = (path.components().collect(), base.components().collect());
+    if path.first() != base.first() {
+        return None;
+    }
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    let parts: Vec<String> = std::iter::repeat_n("..".to_string(), base.len() - common)
+        .chain(path[common..].iter().map(|c| c.as_os_str().to_string_lossy().into_owned()))
+        .collect();
+    Some(parts.join("/"))
+}
+
+/// Every file under `root`, depth first. Unreadable directories are skipped.
+pub(crate) fn walk_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut entries: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+        entries.sort();
+        for path in entries {
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+/// Pick a name in `dir` that doesn't collide with an existing file.
+pub(crate) fn unique_target(dir: &Path, file_name: &str) -> PathBuf {
+    let candidate = dir.join(file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+        _ => (file_name, String::new()),
+    };
+    (2..)
+        .map(|n| dir.join(format!("{stem}_{n}{extension}")))
+        .find(|p| !p.exists())
+        .unwrap_or(candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_paths() {
+        let dir = Path::new("/projects/show/edit");
+        let media = MediaReference::new("../media/./a.mov", Some(dir));
+        assert_eq!(media.absolute, Path::new("/projects/show/media/a.mov").to_string_lossy());
+        assert_eq!(media.relative.as_deref(), Some("../media/a.mov"));
+
+        let mut media = MediaReference::new("/projects/show/edit/renders/b.mov", Some(dir));
+        assert_eq!(media.relative.as_deref(), Some("renders/b.mov"));
+        media.rebase(None);
+        assert_eq!(media.relative, None);
+        assert_eq!(media.file_name(), Some("b.mov"));
+        assert_eq!(MediaReference::new("c.wav", None).relative, None);
+    }
+}
@@ -0,0 +1,323 @@
+//! Timeline search by metadata.
+//!
+//! A [`MetadataQuery`] filters the [`MetadataIndex`] by detected object
+//! class, tracking ID, scene classification and annotation text, and
+//! returns the matching stretches of the timeline as [`MetadataMatch`]
+//! frame ranges. Matches convert into range markers or the clips they
+//! cover, which is enough to assemble a rough cut from, say, every shot
+//! with a dog in it.
+
+use super::{
+    marker_system::{MarkerId, MarkerManager, MarkerType},
+    timeline::TimelineManager,
+};
+use crate::{
+    metadata::{MetadataIndex, ObjectDetection},
+    types::{FrameRate, TimePosition},
+};
+
+/// Stretch of timeline frames matching a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MetadataMatch {
+    /// First matching frame.
+    pub start_frame: u64,
+    /// Frame after the last matching frame.
+    pub end_frame:   u64,
+}
+
+impl MetadataMatch {
+    /// Number of frames in the match.
+    #[must_use]
+    pub fn frames(&self) -> u64 {
+        self.end_frame - self.start_frame
+    }
+
+    /// Start time at a frame rate.
+    #[must_use]
+    pub fn start(&self, rate: &FrameRate) -> TimePosition {
+        TimePosition::from_frame(self.start_frame, rate)
+    }
+
+    /// End time at a frame rate.
+    #[must_use]
+    pub fn end(&self, rate: &FrameRate) -> TimePosition {
+        TimePosition::from_frame(self.end_frame, rate)
+    }
+
+    /// Adds a range marker spanning the match.
+    pub fn to_marker(&self, markers: &mut MarkerManager, rate: &FrameRate, name: &str) -> MarkerId {
+        let (start, end) = (self.start(rate), self.end(rate));
+        let id = markers.add_marker(start, MarkerType::Standard);
+        if let Some(marker) = markers.get_marker_mut(id) {
+            marker.set_name(name);
+            marker.set_duration(TimePosition::from_ms(end.ms - start.ms));
+        }
+        id
+    }
+
+    /// IDs of the clips overlapping the match, by track then start.
+    #[must_use]
+    pub fn clips(&self, timeline: &TimelineManager, rate: &FrameRate) -> Vec<u64> {
+        timeline
+            .clips_in_range(self.start(rate), self.end(rate))
+            .into_iter()
+            .map(|(_, clip)| clip.id)
+            .collect()
+    }
+}
+
+/// Search criteria over a [`MetadataIndex`]. Every criterion set must
+/// match; text comparisons ignore case.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataQuery {
+    /// Detected object class, e.g. `"dog"`.
+    pub object_class:   Option<String>,
+    /// Tracked object ID.
+    pub object_id:      Option<u64>,
+    /// Minimum detection confidence for the object criteria.
+    pub min_confidence: f32,
+    /// Scene classification.
+    pub scene:          Option<String>,
+    /// Text contained in an annotation.
+    pub annotation:     Option<String>,
+    /// Gap in frames bridged between hits, so objects sampled every few
+    /// frames read as one continuous match.
+    pub max_gap:        u64,
+}
+
+impl MetadataQuery {
+    /// Creates a query matching everything, bridging gaps of up to 15
+    /// frames.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { max_gap: 15, ..Default::default() }
+    }
+
+    /// Filters by detected object class.
+    #[must_use]
+    pub fn with_object_class(mut self, class: impl Into<String>) -> Self {
+        self.object_class = Some(class.into());
+        self
+    }
+
+    /// Filters by tracked object ID.
+    #[must_use]
+    pub fn with_object_id(mut self, object_id: u64) -> Self {
+        self.object_id = Some(object_id);
+        self
+    }
+
+    /// Ignores detections below a confidence.
+    #[must_use]
+    pub fn with_min_confidence(mut self, confidence: f32) -> Self {
+        self.min_confidence = confidence;
+        self
+    }
+
+    /// Filters by scene classification.
+    #[must_use]
+    pub fn with_scene(mut self, scene: impl Into<String>) -> Self {
+        self.scene = Some(scene.into());
+        self
+    }
+
+    /// Filters by annotation text.
+    #[must_use]
+    pub fn with_annotation(mut self, text: impl Into<String>) -> Self {
+        self.annotation = Some(text.into());
+        self
+    }
+
+    /// Sets the gap in frames bridged between hits.
+    #[must_use]
+    pub fn with_max_gap(mut self, frames: u64) -> Self {
+        self.max_gap = frames;
+        self
+    }
+
+    /// Checks if a detection matches the object criteria.
+    #[must_use]
+    pub fn matches_detection(&self, detection: &ObjectDetection) -> bool {
+        if let Some(class) = &self.object_class
+            && !detection.class.eq_ignore_ascii_case(class)
+        {
+            return false;
+        }
+
+        if let Some(id) = self.object_id
+            && detection.object_id != id
+        {
+            return false;
+        }
+
+        detection.confidence >= self.min_confidence
+    }
+
+    /// Finds the matching frame ranges, sorted by start.
+    ///
+    /// A query without criteria matches everything from frame 0 to the
+    /// last indexed frame.
+    #[must_use]
+    pub fn search(&self, index: &MetadataIndex) -> Vec<MetadataMatch> {
+        let Some(last) = index.last_frame() else {
+            return Vec::new();
+        };
+        let mut result = vec![(0, last + 1)];
+
+        if self.object_class.is_some() || self.object_id.is_some() {
+            let hits = index
+                .detections()
+                .iter()
+                .filter(|(_, d)| self.matches_detection(d))
+                .map(|(frame, _)| *frame);
+            result = intersect(&result, &self.bridge(hits));
+        }
+
+        if let Some(scene) = &self.scene {
+            let transitions = index.scene_transitions();
+            let scenes: Vec<_> = transitions
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, name))| name.eq_ignore_ascii_case(scene))
+                .map(|(i, (start, _))| {
+                    let end = transitions.get(i + 1).map_or(last + 1, |(next, _)| *next);
+                    (*start, end)
+                })
+                .filter(|(start, end)| start < end)
+                .collect();
+            result = intersect(&result, &scenes);
+        }
+
+        if let Some(text) = &self.annotation {
+            let text = text.to_lowercase();
+            let hits = index
+                .annotations()
+                .iter()
+                .filter(|(_, a)| a.value.to_lowercase().contains(&text))
+                .map(|(frame, _)| *frame);
+            result = intersect(&result, &self.bridge(hits));
+        }
+
+        result
+            .into_iter()
+            .map(|(start_frame, end_frame)| MetadataMatch { start_frame, end_frame })
+            .collect()
+    }
+
+    /// Merges sorted hit frames into ranges, bridging short gaps.
+    fn bridge(&self, frames: impl Iterator<Item = u64>) -> Vec<(u64, u64)> {
+        let mut ranges: Vec<(u64, u64)> = Vec::new();
+        for frame in frames {
+            match ranges.last_mut() {
+                Some((_, end)) if frame < *end + self.max_gap + 1 => *end = (*end).max(frame + 1),
+                _ => ranges.push((frame, frame + 1)),
+            }
+        }
+        ranges
+    }
+}
+
+/// Intersection of two sorted lists of disjoint half-open ranges.
+fn intersect(a: &[(u64, u64)], b: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let start = a[i].0.max(b[j].0);
+        let end = a[i].1.min(b[j].1);
+        if start < end {
+            result.push((start, end));
+        }
+        if a[i].1 < b[j].1 {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        metadata::{Annotation, AnnotationType, BoundingBox},
+        types::{TrackType, timeline::TimelineClip},
+    };
+
+    fn index() -> MetadataIndex {
+        let mut index = MetadataIndex::new();
+        index.add_scene_transition(60, "park");
+        index.add_scene_transition(0, "beach");
+        for frame in (0..=100).step_by(5) {
+            let mut detections = Vec::new();
+            if frame <= 30 || frame >= 70 {
+                detections.push(ObjectDetection::new(1, "Dog", 0.9, BoundingBox::default()));
+            }
+            if (20..=40).contains(&frame) {
+                detections.push(ObjectDetection::new(2, "person", 0.4, BoundingBox::default()));
+            }
+            index.set_detections(frame, detections);
+        }
+        index.add_annotation(
+            80,
+            Annotation {
+                annotation_type: AnnotationType::Comment,
+                value:           "Great dog shot".into(),
+                region:          None,
+                author:          "editor".into(),
+                timestamp_ms:    0,
+            },
+        );
+        index
+    }
+
+    #[test]
+    fn test_metadata_search() {
+        let index = index();
+        assert_eq!(index.scene_at(59), Some("beach"));
+
+        let span = |start_frame, end_frame| MetadataMatch { start_frame, end_frame };
+        let dogs = MetadataQuery::new().with_object_class("dog").search(&index);
+        assert_eq!(dogs, [span(0, 31), span(70, 101)]);
+        assert_eq!(
+            MetadataQuery::new().with_object_class("dog").with_max_gap(0).search(&index).len(),
+            14
+        );
+        assert_eq!(
+            MetadataQuery::new().with_object_class("dog").with_scene("park").search(&index),
+            [span(70, 101)]
+        );
+        assert_eq!(MetadataQuery::new().with_scene("beach").search(&index), [span(0, 60)]);
+        assert_eq!(MetadataQuery::new().with_object_id(2).search(&index), [span(20, 41)]);
+        assert!(
+            MetadataQuery::new()
+                .with_object_id(2)
+                .with_min_confidence(0.5)
+                .search(&index)
+                .is_empty()
+        );
+        assert_eq!(MetadataQuery::new().with_annotation("DOG SHOT").search(&index), [span(80, 81)]);
+        assert_eq!(MetadataQuery::new().search(&index), [span(0, 101)]);
+
+        let rate = FrameRate::FPS_25;
+        let mut markers = MarkerManager::new();
+        let id = dogs[1].to_marker(&mut markers, &rate, "dog");
+        let marker = markers.get_marker(id).expect("test assertion");
+        assert_eq!((marker.position().ms, marker.end_position().ms), (2800, 4040));
+        assert_eq!(marker.name(), "dog");
+
+        let mut timeline = TimelineManager::new();
+        let track = timeline.add_track("V1", TrackType::Video);
+        for (id, start) in [(1, 0), (2, 2000), (3, 4000)] {
+            let clip = TimelineClip::new(
+                id,
+                id,
+                TimePosition::from_ms(start),
+                TimePosition::from_ms(2000),
+            );
+            timeline.add_clip(track, clip).expect("test assertion");
+        }
+        assert_eq!(dogs[0].clips(&timeline, &rate), [1]);
+        assert_eq!(dogs[1].clips(&timeline, &rate), [2, 3]);
+    }
+}
@@ -3,37 +3,190 @@
 //! This module contains all implementations for the Video Editor plugin:
 //! - `VideoEditorConfig` - Configuration
 //! - `AssetLibrary` - Asset management
+//! - `WatchFolder` - Auto-import from host-watched folders
 //! - `EffectsPipeline` - Effects processing
 //! - `GpuPipeline` - GPU-accelerated rendering
+//! - `GpuMemoryPool` - GPU memory budget and texture/buffer pool
+//! - `GpuScheduler` - Preview/export GPU work scheduling
 //! - `TimelineManager` - Timeline operations
+//! - `SelectionModel` - Clip and range selection for timeline edits
+//! - `CameraLayer` - Timeline camera for 2.5D perspective compositing
+//! - `ClipTransform` - Keyframed per-clip crop window
+//! - `CaptionTrack` - Subtitle cues with SRT/WebVTT import and export
+//! - `PlayheadFollow` - Timeline autoscroll during playback
+//! - `TestPattern` / `ToneGenerator` - Generator clips and pipeline validation
+//! - `RenderDeterminism` - Byte-stable CPU rendering for golden-frame tests
+//! - `RenderTargetRegistry` - Render-to-texture hooks for host compositing
+//! - `SnapEngine` - Timeline snapping and magnetic edit points
+//! - `VideoDenoiser` / `UnsharpMask` - Denoise and sharpen effects with quality tiers
+//! - `Deinterlacer` - Bob and motion-adaptive deinterlacing of interlaced clips
+//! - `Stabilizer` - Two-pass motion analysis and stabilization
+//! - `SmartReframe` - Detection-driven crop for vertical and square exports
+//! - `SphericalView` - Keyframed flat view into 360° equirectangular clips
+//! - `ProjectDoctor` - Project diagnostics and safe fixes
 //! - `VideoEditorPlugin` - Main plugin interface
+//! - `CommandRegistry` - Editor commands, shortcuts and toolbar actions
+//! - `EventBus` - Timeline and project change notifications
+//! - `EditorScriptApi` - Scriptable batch edits for host automation
+//! - `EditJournal` - Lamport-stamped edit history with three-way merge
 //! - `TransitionManager` - Video transitions (GAP-220-B-001)
 //! - `AudioMixer` - Audio mixing (GAP-220-B-002)
+//! - `SpectralDenoiser` - Spectral noise reduction from a learned noise profile
+//! - `OutputRouting` - Bus assignment to hardware outputs with downmix matrices
+//! - `VoiceActivityDetector` - Silence detection and cut suggestions
+//! - `BeatDetector` - Tempo estimation and beat markers for cutting to music
+//! - `TranscriptionOrchestrator` - Speech-to-text captions via pluggable providers
+//! - `TranscriptEditor` - Text-based editing through the transcript
 //! - `ExportQueue` - Export pipeline (GAP-220-B-003)
 //! - `PreviewManager` - Preview system (GAP-220-B-004)
+//! - `FramePrefetcher` - Playback read-ahead into the preview frame cache
 //! - `ColorGradingNode` - Color grading (GAP-220-B-005)
+//! - `DetectionPipeline` - Object detection ingestion with IoU tracking
+//! - `ColorMatcher` - Shot matching against a reference frame
+//! - `WhiteBalance` - Auto white balance and skin-tone line checks
 //! - `AnimationManager` - Keyframe animation (GAP-220-B-006)
 //! - `MarkerManager` - Marker system (GAP-220-B-007)
+//! - `ReviewImport` - Review comment JSON exchange for markers
+//! - `MetadataQuery` - Timeline search by detected objects, scenes and annotations
 //! - `ProjectManager` - Project management (GAP-220-B-008)
+//! - `ProjectSnapshot` - Named project versions and structural diffs
+//! - `TrackTemplate` - Reusable track setups and saved track layouts
+//! - `PresetLibrary` - Saved grade, effect and transition looks
 
 mod assets;
+mod audio_analysis;
 mod audio_mixer;
+mod camera;
+mod captions;
+mod clip_index;
+mod clip_transform;
 mod color_grading;
+mod color_match;
+mod commands;
 mod config;
+mod detection;
+mod edit_journal;
 mod effects;
+mod events;
 mod export_pipeline;
+mod generators;
+mod gpu_memory;
 mod gpu_pipeline;
+mod gpu_scheduler;
+mod id_index;
 mod keyframe_animation;
 mod marker_system;
+mod media_refs;
+mod metadata_search;
+mod noise_reduction;
+mod output_routing;
+mod playhead_follow;
 mod plugin;
+mod prefetch;
+mod preset_library;
 mod preview_manager;
+mod project_doctor;
+mod project_history;
 mod project_manager;
+mod reframe;
+mod render;
+mod render_target;
+mod review_comments;
+mod scripting;
+mod selection;
+mod snapping;
+mod spherical;
+mod stabilizer;
 mod timeline;
+mod track_templates;
+mod transcript_editor;
+mod transcription;
 mod transitions;
+mod video_filters;
+mod watch_folder;
+mod white_balance;
 
 pub use assets::AssetLibrary;
+pub use audio_analysis::{
+    ActivityRange, Beat, BeatDetector, BeatGrid, ClipAudio, VoiceActivityDetector, WaveformSync,
+    sync_by_waveform,
+};
+pub use audio_mixer::{AudioBusId, AudioChannelConfig};
+// Internal types measured by the benchmarks
+#[cfg(feature = "bench")]
+pub use audio_mixer::{AudioEffectType, AudioMixer};
+pub use camera::{CameraLayer, CameraState, CompositingMode};
+pub use captions::{
+    CaptionCue, CaptionExportOptions, CaptionFormat, CaptionPosition, CaptionSidecar, CaptionStyle,
+    CaptionTrack,
+};
+pub use clip_index::{MIN_ITEM_PX, TimelineItem};
+pub use clip_transform::{ClipTransform, ClipTransformState};
+#[cfg(feature = "bench")]
+pub use color_grading::{ColorGradingNode, Lut3D, LutInterpolation};
+pub use commands::{CommandHandler, CommandRegistry, EditorCommand};
 pub use config::VideoEditorConfig;
-pub use effects::{EffectType, EffectsPipeline, VideoEffect};
+pub use detection::{DetectionFrame, DetectionPipeline, DetectionProvider, DetectionSummary};
+pub use edit_journal::{
+    EditJournal, JOURNAL_MAGIC, JournalEntry, JournalMerge, MergeConflict, MergeSide, OpTarget,
+};
+pub use effects::{
+    AbCompare, AbSlot, EffectBackend, EffectCapabilities, EffectPreset, EffectQuality, EffectType,
+    EffectsPipeline, ExecutionMode, VideoEffect,
+};
+pub use events::{EditorEvent, EventBus, EventCallback, SubscriptionId};
+#[cfg(feature = "bench")]
+pub use export_pipeline::ExportQueue;
+pub use generators::{
+    GeneratorSource, PipelineCheck, PipelineValidation, SMPTE_BARS, TestPattern, ToneGenerator,
+};
+pub use gpu_memory::{
+    GpuAllocationId, GpuMemoryPool, GpuMemoryStats, GpuResourceDesc, MemoryPressure,
+    MemoryPressureCallback,
+};
 pub use gpu_pipeline::GpuPipeline;
+pub use gpu_scheduler::{
+    GpuPriority, GpuScheduler, GpuSchedulerStats, GpuTimeSlice, GpuWorkId, GpuWorkItem,
+};
+#[cfg(feature = "bench")]
+pub use keyframe_animation::{
+    AnimatedValue, AnimationManager, AnimationTrack, AnimationTrackId, InterpolationType,
+};
+#[cfg(feature = "bench")]
+pub use marker_system::{MarkerManager, MarkerType};
+pub use metadata_search::{MetadataMatch, MetadataQuery};
+pub use noise_reduction::{NoiseProfile, SpectralDenoiser};
+pub use output_routing::{
+    DownmixMatrix, HardwareOutput, OutputAssignment, OutputRouting, SDI_GROUP_CHANNELS,
+};
+pub use playhead_follow::{FollowMode, PlayheadFollow, TimelineViewport};
 pub use plugin::VideoEditorPlugin;
-pub use timeline::TimelineManager;
+pub use preset_library::{PRESET_PACK_MAGIC, Preset, PresetLibrary};
+pub use project_doctor::{
+    Diagnostic, DiagnosticIssue, DiagnosticSeverity, DoctorFix, DoctorReport, ProjectDoctor,
+};
+pub use reframe::{SmartReframe, saliency_center};
+pub use render::{ClipFrameSource, RenderDeterminism};
+pub use render_target::{
+    RenderScaleMode, RenderTarget, RenderTargetDesc, RenderTargetFormat, RenderTargetHandle,
+    RenderTargetId, RenderTargetRegistry,
+};
+pub use scripting::{
+    EditorScriptApi, OperationOutput, SCRIPT_BATCH_MAGIC, ScriptBatchResult, ScriptOperation,
+};
+pub use selection::{SelectionModel, SelectionRange};
+pub use snapping::{SnapCandidate, SnapEngine, SnapSource, SnappedPosition};
+pub use spherical::SphericalView;
+pub use stabilizer::{
+    StabilizeTransform, Stabilizer, StabilizerPhase, StabilizerProgress, StabilizerProgressCallback,
+};
+pub use timeline::{RippleSync, TimelineManager};
+pub use track_templates::{TrackLayout, TrackStripSettings, TrackTemplate};
+pub use transcript_editor::TranscriptEditor;
+pub use transcription::{
+    AudioChunk, TranscriptionFuture, TranscriptionOrchestrator, TranscriptionProvider,
+};
+#[cfg(feature = "bench")]
+pub use transitions::TransitionManager;
+pub use video_filters::{DeinterlaceMode, Deinterlacer, UnsharpMask, VideoDenoiser};
+pub use watch_folder::{FolderEvent, FolderEventSource, MediaPreparer, WatchFolder, WatchTarget};
@@ -0,0 +1,302 @@
+//! Spectral noise reduction.
+//!
+//! A [`NoiseProfile`] is the average magnitude spectrum of a stretch of
+//! noise on its own (room tone, hum, hiss). [`SpectralDenoiser`] runs a
+//! Hann-windowed STFT at 75% overlap and attenuates every bin whose level
+//! is close to the profile, down to a floor set by the reduction amount.
+//! Gains are smoothed over time to keep the residual noise from
+//! "warbling".
+
+use std::f32::consts::PI;
+
+/// STFT frame length in samples.
+pub const FFT_SIZE: usize = 2048;
+
+/// Samples between STFT frames.
+const HOP: usize = FFT_SIZE / 4;
+
+/// Hann² at 75% overlap sums to 1.5; output is scaled back to unity.
+const OVERLAP_GAIN: f32 = 1.0 / 1.5;
+
+/// Average noise magnitude per FFT bin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoiseProfile {
+    /// Magnitudes of bins `0..=FFT_SIZE / 2`.
+    pub magnitudes: Vec<f32>,
+}
+
+impl NoiseProfile {
+    /// Learn a profile from interleaved samples containing only noise.
+    /// Channels are averaged. Returns `None` if the samples are shorter
+    /// than one FFT frame.
+    pub fn learn(samples: &[f32], channels: usize) -> Option<Self> {
+        let channels = channels.max(1);
+        let frames = samples.len() / channels;
+        if frames < FFT_SIZE {
+            return None;
+        }
+        let window = hann();
+        let mut magnitudes = vec![0.0; FFT_SIZE / 2 + 1];
+        let mut count = 0;
+        let (mut re, mut im) = (vec![0.0; FFT_SIZE], vec![0.0; FFT_SIZE]);
+        for channel in 0..channels {
+            for start in (0..=frames - FFT_SIZE).step_by(FFT_SIZE / 2) {
+                for (i, (r, w)) in re.iter_mut().zip(&window).enumerate() {
+                    *r = samples[(start + i) * channels + channel] * w;
+                }
+                im.fill(0.0);
+                fft(&mut re, &mut im, false);
+                for (bin, magnitude) in magnitudes.iter_mut().enumerate() {
+                    *magnitude += re[bin].hypot(im[bin]);
+                }
+                count += 1;
+            }
+        }
+        magnitudes.iter_mut().for_each(|m| *m /= count as f32);
+        Some(Self { magnitudes })
+    }
+}
+
+/// Per-channel STFT state.
+#[derive(Debug, Clone)]
+struct ChannelState {
+    /// Last `FFT_SIZE` input samples.
+    input:  Vec<f32>,
+    /// Overlap-add accumulator; the first `HOP` samples are complete.
+    output: Vec<f32>,
+    /// Smoothed bin gains of the previous frame.
+    gains:  Vec<f32>,
+}
+
+/// Streaming spectral gate for interleaved audio.
+///
+/// Output is delayed by [`SpectralDenoiser::LATENCY`] samples.
+#[derive(Debug, Clone)]
+pub struct SpectralDenoiser {
+    profile:      NoiseProfile,
+    /// Attenuation of pure noise, in dB.
+    reduction_db: f32,
+    /// Noise profile multiplier; bins this many times the profile level
+    /// count as noise.
+    sensitivity:  f32,
+    /// Gain smoothing between frames (0.0 - 0.95).
+    smoothing:    f32,
+    window:       Vec<f32>,
+    states:       Vec<ChannelState>,
+    /// Samples collected since the last frame.
+    filled:       usize,
+}
+
+impl SpectralDenoiser {
+    /// Delay in samples between input and output.
+    pub const LATENCY: usize = FFT_SIZE;
+
+    /// Create a denoiser for a profile and channel count.
+    pub fn new(
+        profile: NoiseProfile, channels: usize, reduction_db: f32, sensitivity: f32, smoothing: f32,
+    ) -> Self {
+        let state = ChannelState {
+            input:  vec![0.0; FFT_SIZE],
+            output: vec![0.0; FFT_SIZE],
+            gains:  vec![1.0; FFT_SIZE / 2 + 1],
+        };
+        Self {
+            profile,
+            reduction_db: reduction_db.max(0.0),
+            sensitivity: sensitivity.max(0.0),
+            smoothing: smoothing.clamp(0.0, 0.95),
+            window: hann(),
+            states: vec![state; channels.max(1)],
+            filled: 0,
+        }
+    }
+
+    /// Returns the channel count.
+    pub fn channels(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns the noise profile.
+    pub fn profile(&self) -> &NoiseProfile {
+        &self.profile
+    }
+
+    /// Update the reduction settings, keeping the stream state.
+    pub fn set_parameters(&mut self, reduction_db: f32, sensitivity: f32, smoothing: f32) {
+        self.reduction_db = reduction_db.max(0.0);
+        self.sensitivity = sensitivity.max(0.0);
+        self.smoothing = smoothing.clamp(0.0, 0.95);
+    }
+
+    /// Clears the stream state (e.g. after a seek).
+    pub fn reset(&mut self) {
+        for state in &mut self.states {
+            state.input.fill(0.0);
+            state.output.fill(0.0);
+            state.gains.fill(1.0);
+        }
+        self.filled = 0;
+    }
+
+    /// Denoises an interleaved buffer in place.
+    pub fn process(&mut self, samples: &mut [f32]) {
+        let channels = self.states.len();
+        for frame in samples.chunks_mut(channels) {
+            let slot = FFT_SIZE - HOP + self.filled;
+            for (state, sample) in self.states.iter_mut().zip(frame.iter_mut()) {
+                state.input[slot] = *sample;
+                *sample = state.output[self.filled];
+            }
+            self.filled += 1;
+            if self.filled == HOP {
+                self.filled = 0;
+                for channel in 0..channels {
+                    self.process_frame(channel);
+                }
+            }
+        }
+    }
+
+    /// Filter the buffered frame of one channel and overlap-add it.
+    fn process_frame(&mut self, channel: usize) {
+        let floor = 10f32.powf(-self.reduction_db / 20.0);
+        let (sensitivity, smoothing) = (self.sensitivity, self.smoothing);
+        let noise = &self.profile.magnitudes;
+        let state = &mut self.states[channel];
+
+        let mut re: Vec<f32> = state.input.iter().zip(&self.window).map(|(x, w)| x * w).collect();
+        let mut im = vec![0.0; FFT_SIZE];
+        fft(&mut re, &mut im, false);
+        for bin in 0..=FFT_SIZE / 2 {
+            let magnitude = re[bin].hypot(im[bin]);
+            let noise = noise.get(bin).copied().unwrap_or(0.0) * sensitivity;
+            let target =
+                if magnitude > f32::EPSILON { (1.0 - noise / magnitude).max(floor) } else { floor };
+            let gain = smoothing * state.gains[bin] + (1.0 - smoothing) * target;
+            state.gains[bin] = gain;
+            re[bin] *= gain;
+            im[bin] *= gain;
+            if bin != 0 && bin != FFT_SIZE / 2 {
+                re[FFT_SIZE - bin] *= gain;
+                im[FFT_SIZE - bin] *= gain;
+            }
+        }
+        fft(&mut re, &mut im, true);
+
+        state.output.copy_within(HOP.., 0);
+        state.output[FFT_SIZE - HOP..].fill(0.0);
+        for ((out, y), w) in state.output.iter_mut().zip(&re).zip(&self.window) {
+            *out += y * w * OVERLAP_GAIN;
+        }
+        state.input.copy_within(HOP.., 0);
+    }
+}
+
+/// Periodic Hann window of `FFT_SIZE` samples.
+fn hann() -> Vec<f32> {
+    (0..FFT_SIZE).map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / FFT_SIZE as f32).cos()).collect()
+}
+
+/// In-place radix-2 complex FFT; the inverse is scaled by `1 / n`.
+/// `re.len()` must be a power of two.
+fn fft(re: &mut [f32], im: &mut [f32], inverse: bool) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let (tr, ti) = (re[b] * cos - im[b] * sin, re[b] * sin + im[b] * cos);
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len <<= 1;
+    }
+
+    if inverse {
+        let scale = 1.0 / n as f32;
+        re.iter_mut().chain(im.iter_mut()).for_each(|v| *v *= scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noise(len: usize, seed: u32) -> Vec<f32> {
+        let mut seed = seed;
+        (0..len)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                ((seed >> 8) as f32 / (1 << 24) as f32 - 0.5) * 0.1
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_fft_round_trip() {
+        let signal = noise(64, 3);
+        let (mut re, mut im) = (signal.clone(), vec![0.0; 64]);
+        fft(&mut re, &mut im, false);
+        // A DC signal lands entirely in bin 0
+        let (mut dc, mut dc_im) = (vec![1.0; 8], vec![0.0; 8]);
+        fft(&mut dc, &mut dc_im, false);
+        assert!((dc[0] - 8.0).abs() < 1e-5 && dc[1..].iter().all(|v| v.abs() < 1e-5));
+        fft(&mut re, &mut im, true);
+        assert!(re.iter().zip(&signal).all(|(a, b)| (a - b).abs() < 1e-5));
+    }
+
+    #[test]
+    fn test_spectral_denoise() {
+        let rate = 48_000;
+        let profile = NoiseProfile::learn(&noise(rate, 11), 1).expect("test assertion");
+        assert_eq!(profile.magnitudes.len(), FFT_SIZE / 2 + 1);
+        assert!(NoiseProfile::learn(&[0.0; 100], 1).is_none());
+
+        let tone: Vec<f32> =
+            (0..rate).map(|i| (2.0 * PI * 440.0 * i as f32 / rate as f32).sin() * 0.3).collect();
+        let hiss = noise(rate, 99);
+        let mut noisy: Vec<f32> = tone.iter().zip(&hiss).map(|(t, n)| t + n).collect();
+
+        // Transparent with no reduction
+        let mut passthrough = noisy.clone();
+        SpectralDenoiser::new(profile.clone(), 1, 0.0, 1.0, 0.0).process(&mut passthrough);
+        let delay = SpectralDenoiser::LATENCY;
+        let error: Vec<f32> = passthrough[delay..].iter().zip(&noisy).map(|(a, b)| a - b).collect();
+        assert!(rms(&error) < 1e-4, "{}", rms(&error));
+
+        let mut denoiser = SpectralDenoiser::new(profile, 1, 24.0, 2.0, 0.5);
+        for block in noisy.chunks_mut(480) {
+            denoiser.process(block);
+        }
+        let settled = delay + FFT_SIZE;
+        let residual: Vec<f32> =
+            noisy[settled..].iter().zip(&tone[settled - delay..]).map(|(a, b)| a - b).collect();
+        assert!(rms(&residual) < rms(&hiss) * 0.5, "{} vs {}", rms(&residual), rms(&hiss));
+        assert!((rms(&noisy[settled..]) - rms(&tone)).abs() < 0.03);
+    }
+}
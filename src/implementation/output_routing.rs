@@ -0,0 +1,322 @@
+//! Bus output routing to audio hardware.
+//!
+//! [`OutputRouting`] assigns mixer buses to logical hardware outputs: the
+//! main monitors, a cue feed, or a group of SDI embedded channels. Each
+//! assignment carries a [`DownmixMatrix`] from the bus layout to the output
+//! layout, so a 5.1 mix can be monitored on stereo speakers while the
+//! discrete channels go out over SDI.
+
+use super::audio_mixer::{AudioBusId, AudioChannelConfig};
+
+/// Gain of the center and surround channels in an ITU-R BS.775 downmix.
+const MINUS_3_DB: f32 = core::f32::consts::FRAC_1_SQRT_2;
+
+/// Channels in one SDI embedded audio group.
+pub const SDI_GROUP_CHANNELS: u8 = 4;
+
+/// Logical hardware output a bus can be sent to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HardwareOutput {
+    /// Main monitor outputs.
+    Main,
+    /// Cue/headphone feed.
+    Cue,
+    /// SDI embedded audio group (1 to 4), four channels each.
+    SdiGroup(u8),
+}
+
+impl HardwareOutput {
+    /// Channel layout the output starts with: stereo for main and cue,
+    /// four discrete channels for an SDI group.
+    #[must_use]
+    pub const fn default_layout(&self) -> AudioChannelConfig {
+        match self {
+            Self::Main | Self::Cue => AudioChannelConfig::Stereo,
+            Self::SdiGroup(_) => AudioChannelConfig::Custom(SDI_GROUP_CHANNELS),
+        }
+    }
+
+    /// First 1-based SDI embedded channel of the output, if it is an SDI
+    /// group.
+    #[must_use]
+    pub const fn sdi_first_channel(&self) -> Option<u8> {
+        match self {
+            Self::SdiGroup(group) => Some(group.saturating_sub(1) * SDI_GROUP_CHANNELS + 1),
+            _ => None,
+        }
+    }
+}
+
+/// Gains from each input channel to each output channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownmixMatrix {
+    inputs:  usize,
+    outputs: usize,
+    /// Row-major, one row of `inputs` gains per output channel.
+    gains:   Vec<f32>,
+}
+
+impl DownmixMatrix {
+    /// Creates a matrix from rows of gains, one row per output channel.
+    /// Returns `None` if the rows are empty or of different lengths.
+    #[must_use]
+    pub fn new(rows: &[&[f32]]) -> Option<Self> {
+        let inputs = rows.first()?.len();
+        if inputs == 0 || rows.iter().any(|r| r.len() != inputs) {
+            return None;
+        }
+        Some(Self { inputs, outputs: rows.len(), gains: rows.concat() })
+    }
+
+    /// Passes `channels` channels through unchanged.
+    #[must_use]
+    pub fn identity(channels: usize) -> Self {
+        let channels = channels.max(1);
+        let mut gains = vec![0.0; channels * channels];
+        for c in 0..channels {
+            gains[c * channels + c] = 1.0;
+        }
+        Self { inputs: channels, outputs: channels, gains }
+    }
+
+    /// Matrix from one layout to another.
+    ///
+    /// 5.1 and 7.1 fold down to stereo per ITU-R BS.775 with the LFE
+    /// dropped, stereo folds to mono at half gain each and mono feeds both
+    /// sides of stereo. Other pairs map channel `i` to channel `i`, so
+    /// extra output channels stay silent and extra inputs are dropped.
+    #[must_use]
+    pub fn between(from: AudioChannelConfig, to: AudioChannelConfig) -> Self {
+        use AudioChannelConfig::{Mono, Stereo, Surround51, Surround71};
+
+        // Surround order is L R C LFE Ls Rs (Lb Rb)
+        let c = MINUS_3_DB;
+        let standard = match (from, to) {
+            (Surround51, Stereo) => {
+                Self::new(&[&[1.0, 0.0, c, 0.0, c, 0.0], &[0.0, 1.0, c, 0.0, 0.0, c]])
+            },
+            (Surround71, Stereo) => Self::new(&[
+                &[1.0, 0.0, c, 0.0, c, 0.0, c, 0.0],
+                &[0.0, 1.0, c, 0.0, 0.0, c, 0.0, c],
+            ]),
+            (Stereo, Mono) => Self::new(&[&[0.5, 0.5]]),
+            (Mono, Stereo) => Self::new(&[&[1.0], &[1.0]]),
+            _ => None,
+        };
+        if let Some(matrix) = standard {
+            return matrix;
+        }
+        let (inputs, outputs) =
+            (usize::from(from.channel_count()).max(1), usize::from(to.channel_count()).max(1));
+        let mut gains = vec![0.0; inputs * outputs];
+        for c in 0..inputs.min(outputs) {
+            gains[c * inputs + c] = 1.0;
+        }
+        Self { inputs, outputs, gains }
+    }
+
+    /// Number of input channels.
+    #[must_use]
+    pub const fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    /// Number of output channels.
+    #[must_use]
+    pub const fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    /// Gain from an input channel to an output channel.
+    #[must_use]
+    pub fn gain(&self, output: usize, input: usize) -> f32 {
+        if output >= self.outputs || input >= self.inputs {
+            return 0.0;
+        }
+        self.gains[output * self.inputs + input]
+    }
+
+    /// Sets the gain from an input channel to an output channel. Returns
+    /// `false` if either channel is out of range.
+    pub fn set_gain(&mut self, output: usize, input: usize, gain: f32) -> bool {
+        if output >= self.outputs || input >= self.inputs {
+            return false;
+        }
+        self.gains[output * self.inputs + input] = gain;
+        true
+    }
+
+    /// Mixes interleaved input frames into interleaved output frames,
+    /// adding to what `out` already holds.
+    pub fn mix_into(&self, samples: &[f32], out: &mut [f32]) {
+        let frames = samples.chunks_exact(self.inputs).zip(out.chunks_exact_mut(self.outputs));
+        for (frame, out) in frames {
+            for (o, row) in out.iter_mut().zip(self.gains.chunks_exact(self.inputs)) {
+                *o += row.iter().zip(frame).map(|(g, s)| g * s).sum::<f32>();
+            }
+        }
+    }
+}
+
+/// A bus sent to a hardware output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputAssignment {
+    /// Bus feeding the output.
+    pub bus:    AudioBusId,
+    /// Hardware output.
+    pub output: HardwareOutput,
+    /// Bus channels to output channels.
+    pub matrix: DownmixMatrix,
+}
+
+/// Routing matrix from buses to hardware outputs.
+///
+/// A bus may feed several outputs and an output may be fed by several
+/// buses, which are summed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputRouting {
+    layouts:     Vec<(HardwareOutput, AudioChannelConfig)>,
+    assignments: Vec<OutputAssignment>,
+}
+
+impl OutputRouting {
+    /// Creates an empty routing with every output in its default layout.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { layouts: Vec::new(), assignments: Vec::new() }
+    }
+
+    /// Channel layout of an output.
+    #[must_use]
+    pub fn layout(&self, output: HardwareOutput) -> AudioChannelConfig {
+        self.layouts
+            .iter()
+            .find(|(o, _)| *o == output)
+            .map_or_else(|| output.default_layout(), |(_, layout)| *layout)
+    }
+
+    /// Sets the channel layout of an output. Assignments to it are
+    /// re-matrixed from their input channel count.
+    pub fn set_layout(&mut self, output: HardwareOutput, layout: AudioChannelConfig) {
+        match self.layouts.iter_mut().find(|(o, _)| *o == output) {
+            Some(entry) => entry.1 = layout,
+            None => self.layouts.push((output, layout)),
+        }
+        for assignment in self.assignments.iter_mut().filter(|a| a.output == output) {
+            let from = layout_of(assignment.matrix.inputs());
+            assignment.matrix = DownmixMatrix::between(from, layout);
+        }
+    }
+
+    /// Sends a bus with `bus_layout` to an output, replacing an earlier
+    /// assignment of the same pair. The matrix folds the bus layout into
+    /// the output layout.
+    pub fn assign(
+        &mut self, bus: AudioBusId, output: HardwareOutput, bus_layout: AudioChannelConfig,
+    ) {
+        let matrix = DownmixMatrix::between(bus_layout, self.layout(output));
+        self.assign_matrix(bus, output, matrix);
+    }
+
+    /// Sends a bus to an output through a custom matrix, replacing an
+    /// earlier assignment of the same pair. Returns `false` if the matrix
+    /// doesn't produce the output's channel count.
+    pub fn assign_matrix(
+        &mut self, bus: AudioBusId, output: HardwareOutput, matrix: DownmixMatrix,
+    ) -> bool {
+        if matrix.outputs() != usize::from(self.layout(output).channel_count()) {
+            return false;
+        }
+        self.unassign(bus, output);
+        self.assignments.push(OutputAssignment { bus, output, matrix });
+        true
+    }
+
+    /// Stops sending a bus to an output. Returns whether it was sent.
+    pub fn unassign(&mut self, bus: AudioBusId, output: HardwareOutput) -> bool {
+        let before = self.assignments.len();
+        self.assignments.retain(|a| a.bus != bus || a.output != output);
+        self.assignments.len() != before
+    }
+
+    /// Removes every assignment of a bus, e.g. when it is deleted.
+    pub fn remove_bus(&mut self, bus: AudioBusId) {
+        self.assignments.retain(|a| a.bus != bus);
+    }
+
+    /// All assignments, in the order they were made.
+    #[must_use]
+    pub fn assignments(&self) -> &[OutputAssignment] {
+        &self.assignments
+    }
+
+    /// Outputs a bus is sent to.
+    pub fn outputs_of(&self, bus: AudioBusId) -> impl Iterator<Item = HardwareOutput> + '_ {
+        self.assignments.iter().filter(move |a| a.bus == bus).map(|a| a.output)
+    }
+
+    /// Renders the hardware outputs from interleaved bus audio, `frames`
+    /// frames long. Each assigned output gets a buffer in its own layout;
+    /// buses without audio in `buses` contribute silence.
+    #[must_use]
+    pub fn render(
+        &self, buses: &[(AudioBusId, &[f32])], frames: usize,
+    ) -> Vec<(HardwareOutput, Vec<f32>)> {
+        let mut outputs: Vec<(HardwareOutput, Vec<f32>)> = Vec::new();
+        for assignment in &self.assignments {
+            let index = match outputs.iter().position(|(o, _)| *o == assignment.output) {
+                Some(index) => index,
+                None => {
+                    let channels = assignment.matrix.outputs();
+                    outputs.push((assignment.output, vec![0.0; frames * channels]));
+                    outputs.len() - 1
+                },
+            };
+            if let Some((_, samples)) = buses.iter().find(|(id, _)| *id == assignment.bus) {
+                assignment.matrix.mix_into(samples, &mut outputs[index].1);
+            }
+        }
+        outputs
+    }
+}
+
+impl Default for OutputRouting {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Standard layout with `channels` channels.
+fn layout_of(channels: usize) -> AudioChannelConfig {
+    match channels {
+        1 => AudioChannelConfig::Mono,
+        2 => AudioChannelConfig::Stereo,
+        6 => AudioChannelConfig::Surround51,
+        8 => AudioChannelConfig::Surround71,
+        n => AudioChannelConfig::Custom(n.min(usize::from(u8::MAX)) as u8),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_surround_downmix() {
+        let matrix =
+            DownmixMatrix::between(AudioChannelConfig::Surround51, AudioChannelConfig::Stereo);
+        assert_eq!((matrix.inputs(), matrix.outputs()), (6, 2));
+        // Center only: -3 dB in both speakers; LFE is dropped
+        let mut out = [0.0; 2];
+        matrix.mix_into(&[0.0, 0.0, 1.0, 1.0, 0.0, 0.0], &mut out);
+        assert!((out[0] - MINUS_3_DB).abs() < 1e-6 && (out[1] - MINUS_3_DB).abs() < 1e-6);
+
+        let wide =
+            DownmixMatrix::between(AudioChannelConfig::Stereo, AudioChannelConfig::Custom(4));
+        let mut out = [0.0; 4];
+        wide.mix_into(&[0.3, 0.7], &mut out);
+        assert_eq!(out, [0.3, 0.7, 0.0, 0.0]);
+        assert!(DownmixMatrix::new(&[&[1.0], &[1.0, 0.0]]).is_none());
+        assert_eq!(HardwareOutput::SdiGroup(2).sdi_first_channel(), Some(5));
+    }
+}
@@ -0,0 +1,227 @@
+//! Timeline autoscroll / playhead-follow helpers.
+//!
+//! Computes where the timeline viewport should scroll to keep the playhead
+//! visible during playback. Hosts own the actual scroll widget; these helpers
+//! only do the timing math so every host follows the playhead the same way.
+
+use crate::types::TimePosition;
+
+/// How the viewport follows the playhead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FollowMode {
+    /// Viewport does not move.
+    Off,
+    /// Jump a full page when the playhead leaves the view.
+    #[default]
+    PageFlip,
+    /// Ease towards keeping the playhead at the anchor point.
+    Smooth,
+    /// Keep the playhead locked at the anchor point.
+    Centered,
+}
+
+/// Visible region of the timeline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimelineViewport {
+    /// Time at the left edge in milliseconds.
+    pub scroll_ms:         f64,
+    /// Zoom level in pixels per second.
+    pub pixels_per_second: f64,
+    /// Viewport width in pixels.
+    pub width_px:          f64,
+}
+
+impl TimelineViewport {
+    /// Creates a viewport scrolled to the timeline start.
+    #[must_use]
+    pub fn new(width_px: f64, pixels_per_second: f64) -> Self {
+        Self { scroll_ms: 0.0, pixels_per_second: pixels_per_second.max(f64::EPSILON), width_px }
+    }
+
+    /// Returns the visible duration in milliseconds.
+    #[must_use]
+    pub fn visible_ms(&self) -> f64 {
+        self.width_px / self.pixels_per_second * 1000.0
+    }
+
+    /// Returns the time at the right edge in milliseconds.
+    #[must_use]
+    pub fn end_ms(&self) -> f64 {
+        self.scroll_ms + self.visible_ms()
+    }
+
+    /// Returns whether a time is inside the viewport.
+    #[must_use]
+    pub fn contains(&self, ms: f64) -> bool {
+        ms >= self.scroll_ms && ms < self.end_ms()
+    }
+
+    /// Converts a timeline time to a viewport x coordinate.
+    #[must_use]
+    pub fn time_to_x(&self, ms: f64) -> f64 {
+        (ms - self.scroll_ms) / 1000.0 * self.pixels_per_second
+    }
+
+    /// Converts a viewport x coordinate to a timeline time.
+    #[must_use]
+    pub fn x_to_time(&self, x: f64) -> f64 {
+        self.scroll_ms + x / self.pixels_per_second * 1000.0
+    }
+
+    /// Changes the zoom level keeping the time under `anchor_x` fixed.
+    pub fn zoom_at(&mut self, pixels_per_second: f64, anchor_x: f64) {
+        let anchor_ms = self.x_to_time(anchor_x);
+        self.pixels_per_second = pixels_per_second.max(f64::EPSILON);
+        self.scroll_ms = (anchor_ms - anchor_x / self.pixels_per_second * 1000.0).max(0.0);
+    }
+}
+
+/// Playhead-follow behavior.
+#[derive(Debug, Clone, Copy)]
+pub struct PlayheadFollow {
+    /// Follow mode.
+    pub mode:           FollowMode,
+    /// Playhead anchor for smooth/centered modes (0.0 = left, 1.0 = right).
+    pub anchor:         f64,
+    /// Fraction of the view kept before the playhead after a page flip.
+    pub page_margin:    f64,
+    /// Smooth-scroll time constant in milliseconds.
+    pub smooth_time_ms: f64,
+}
+
+impl Default for PlayheadFollow {
+    fn default() -> Self {
+        Self {
+            mode:           FollowMode::default(),
+            anchor:         0.5,
+            page_margin:    0.05,
+            smooth_time_ms: 150.0,
+        }
+    }
+}
+
+impl PlayheadFollow {
+    /// Creates follow settings for a mode.
+    #[must_use]
+    pub fn new(mode: FollowMode) -> Self {
+        Self { mode, ..Self::default() }
+    }
+
+    /// Returns the scroll position the viewport should end up at, or `None`
+    /// if it should stay where it is.
+    #[must_use]
+    pub fn target_scroll(
+        &self, viewport: &TimelineViewport, playhead: TimePosition,
+    ) -> Option<f64> {
+        let ms = playhead.ms as f64;
+        let visible = viewport.visible_ms();
+
+        let target = match self.mode {
+            FollowMode::Off => return None,
+            FollowMode::PageFlip => {
+                if viewport.contains(ms) {
+                    return None;
+                }
+                if ms >= viewport.end_ms() {
+                    ms - visible * self.page_margin
+                } else {
+                    ms - visible * (1.0 - self.page_margin)
+                }
+            },
+            FollowMode::Smooth | FollowMode::Centered => ms - visible * self.anchor.clamp(0.0, 1.0),
+        };
+
+        Some(target.max(0.0))
+    }
+
+    /// Advances the viewport scroll by `delta_ms` of wall-clock time and
+    /// returns the new scroll position.
+    ///
+    /// Smooth mode eases towards the target but jumps if the playhead would
+    /// otherwise leave the view.
+    #[must_use]
+    pub fn step(&self, viewport: &TimelineViewport, playhead: TimePosition, delta_ms: f64) -> f64 {
+        let Some(target) = self.target_scroll(viewport, playhead) else {
+            return viewport.scroll_ms;
+        };
+
+        if self.mode != FollowMode::Smooth
+            || self.smooth_time_ms <= 0.0
+            || !viewport.contains(playhead.ms as f64)
+        {
+            return target;
+        }
+
+        let alpha = 1.0 - (-delta_ms.max(0.0) / self.smooth_time_ms).exp();
+        viewport.scroll_ms + (target - viewport.scroll_ms) * alpha
+    }
+
+    /// Applies [`Self::step`] to the viewport. Returns true if it scrolled.
+    pub fn apply(
+        &self, viewport: &mut TimelineViewport, playhead: TimePosition, delta_ms: f64,
+    ) -> bool {
+        let scroll = self.step(viewport, playhead, delta_ms);
+        let changed = (scroll - viewport.scroll_ms).abs() > f64::EPSILON;
+        viewport.scroll_ms = scroll;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_flip() {
+        // 1000 px at 100 px/s = 10 s visible
+        let mut viewport = TimelineViewport::new(1000.0, 100.0);
+        let follow =
+            PlayheadFollow { page_margin: 0.0, ..PlayheadFollow::new(FollowMode::PageFlip) };
+
+        assert!(!follow.apply(&mut viewport, TimePosition::from_secs(5), 16.0));
+        assert!(follow.apply(&mut viewport, TimePosition::from_secs(10), 16.0));
+        assert!((viewport.scroll_ms - 10_000.0).abs() < 1e-6);
+
+        // Jumping backwards flips to the page ending at the playhead
+        assert!(follow.apply(&mut viewport, TimePosition::from_secs(8), 16.0));
+        assert!(viewport.contains(8_000.0));
+    }
+
+    #[test]
+    fn test_centered() {
+        let viewport = TimelineViewport::new(1000.0, 100.0);
+        let follow = PlayheadFollow::new(FollowMode::Centered);
+
+        let target = follow.target_scroll(&viewport, TimePosition::from_secs(20));
+        assert_eq!(target, Some(15_000.0));
+        // Never scrolls before the timeline start
+        assert_eq!(follow.target_scroll(&viewport, TimePosition::from_secs(1)), Some(0.0));
+    }
+
+    #[test]
+    fn test_smooth_eases_towards_target() {
+        let mut viewport = TimelineViewport::new(1000.0, 100.0);
+        let follow = PlayheadFollow::new(FollowMode::Smooth);
+        let playhead = TimePosition::from_secs(8);
+
+        let first = follow.step(&viewport, playhead, 16.0);
+        assert!(first > 0.0 && first < 3_000.0);
+        viewport.scroll_ms = first;
+        assert!(follow.step(&viewport, playhead, 16.0) > first);
+
+        // Playhead far outside the view jumps straight to the target
+        let jump = follow.step(&viewport, TimePosition::from_secs(100), 16.0);
+        assert!((jump - 95_000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_zoom_keeps_anchor() {
+        let mut viewport = TimelineViewport::new(1000.0, 100.0);
+        viewport.scroll_ms = 2_000.0;
+        let before = viewport.x_to_time(500.0);
+        viewport.zoom_at(200.0, 500.0);
+
+        assert!((viewport.x_to_time(500.0) - before).abs() < 1e-6);
+        assert!((viewport.visible_ms() - 5_000.0).abs() < 1e-6);
+    }
+}
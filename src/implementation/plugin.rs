@@ -1,34 +1,203 @@
 //! Video editor plugin implementation.
 
-use super::{AssetLibrary, EffectsPipeline, GpuPipeline, TimelineManager, VideoEditorConfig};
-use crate::types::TrackType;
+use std::{collections::HashMap, sync::mpsc::Receiver};
+
+use super::{
+    AssetLibrary, CommandRegistry, DoctorFix, DoctorReport, EditorEvent, EffectsPipeline, EventBus,
+    EventCallback, ExecutionMode, GpuPipeline, OutputRouting, PipelineValidation, ProjectDoctor,
+    RenderTargetDesc, RenderTargetId, RenderTargetRegistry, SubscriptionId, TimelineManager,
+    VideoEditorConfig,
+    color_grading::ColorGradingNode,
+    export_pipeline::{EncoderCapabilities, ExportRange, ExportSettings},
+    generators,
+    keyframe_animation::AnimationManager,
+    marker_system::{MarkerId, MarkerManager, MarkerType},
+    media_refs::RelinkReport,
+    metadata_search::MetadataMatch,
+    preset_library::{Preset, PresetLibrary},
+    preview_manager::PreviewManager,
+    project_manager::ProjectManager,
+    reframe::SmartReframe,
+    render::{self, ClipFrameSource},
+    review_comments::{self, ReviewImport},
+    scripting::{EditorScriptApi, ScriptBatchResult, ScriptOperation},
+    timeline::RippleSync,
+    track_templates::TrackTemplate,
+    transitions::TransitionManager,
+};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    flexforge::VideoEditorMetrics,
+    metadata::MetadataIndex,
+    types::{FrameBuffer, FrameBufferPool, TimePosition, TrackType, TrackView, TrackViewState},
+};
 
 /// Main video editor plugin interface.
 pub struct VideoEditorPlugin {
-    config:   VideoEditorConfig,
-    timeline: TimelineManager,
-    assets:   AssetLibrary,
-    effects:  EffectsPipeline,
-    gpu:      GpuPipeline,
+    config:      VideoEditorConfig,
+    timeline:    TimelineManager,
+    assets:      AssetLibrary,
+    effects:     EffectsPipeline,
+    gpu:         GpuPipeline,
+    targets:     RenderTargetRegistry,
+    commands:    CommandRegistry,
+    preview:     PreviewManager,
+    markers:     MarkerManager,
+    projects:    ProjectManager,
+    transitions: TransitionManager,
+    grades:      HashMap<u64, ColorGradingNode>,
+    animation:   AnimationManager,
+    events:      EventBus,
+    frames:      Option<ClipFrameSource>,
+    frame_pool:  FrameBufferPool,
+    routing:     OutputRouting,
 }
 
 impl VideoEditorPlugin {
     /// Create a new video editor plugin.
     pub fn new(config: VideoEditorConfig) -> Self {
         let gpu = GpuPipeline::new(config.gpu_acceleration);
+        let mut preview =
+            PreviewManager::new(TimePosition::default(), config.frame_rate, config.resolution);
+        preview.set_pixel_aspect(config.pixel_aspect);
 
-        Self {
+        let mut plugin = Self {
             config,
             timeline: TimelineManager::new(),
             assets: AssetLibrary::new(),
             effects: EffectsPipeline::new(),
             gpu,
+            targets: RenderTargetRegistry::new(),
+            commands: CommandRegistry::with_defaults(),
+            preview,
+            markers: MarkerManager::new(),
+            projects: ProjectManager::new(),
+            transitions: TransitionManager::new(),
+            grades: HashMap::new(),
+            animation: AnimationManager::new(),
+            events: EventBus::new(),
+            frames: None,
+            frame_pool: FrameBufferPool::default(),
+            routing: OutputRouting::new(),
+        };
+        if plugin.config.determinism.strict_float {
+            plugin.effects.set_execution_mode(ExecutionMode::ForceCpu);
+        }
+        plugin.attach_event_bus();
+        plugin
+    }
+
+    /// Point every emitting manager at the plugin's event bus.
+    fn attach_event_bus(&mut self) {
+        self.timeline.set_event_bus(self.events.clone());
+        self.effects.set_event_bus(self.events.clone());
+        self.markers.set_event_bus(self.events.clone());
+        self.projects.set_event_bus(self.events.clone());
+    }
+
+    /// Subscribe to timeline and project changes. The callback runs
+    /// synchronously on the thread that made the edit.
+    pub fn subscribe(&self, callback: EventCallback) -> SubscriptionId {
+        self.events.subscribe(callback)
+    }
+
+    /// Subscribe to timeline and project changes through a channel, for
+    /// panels that poll from another thread.
+    pub fn subscribe_channel(&self) -> (SubscriptionId, Receiver<EditorEvent>) {
+        self.events.subscribe_channel()
+    }
+
+    /// Remove a subscription.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.events.unsubscribe(id)
+    }
+
+    /// Get the project manager.
+    pub fn projects(&self) -> &ProjectManager {
+        &self.projects
+    }
+
+    /// Get the mutable project manager.
+    pub fn projects_mut(&mut self) -> &mut ProjectManager {
+        &mut self.projects
+    }
+
+    /// Get how a track is drawn in the track list: the view saved with the
+    /// current project, else the track's own height and color.
+    ///
+    /// Returns `None` if the track does not exist.
+    pub fn track_view(&self, track_id: u64) -> Option<TrackView> {
+        let track = self.timeline.get_track(track_id)?;
+        let saved = self.projects.current_project().and_then(|p| p.track_view().view(track_id));
+        Some(saved.copied().unwrap_or(TrackView {
+            height:    track.height,
+            color:     track.color,
+            collapsed: false,
+        }))
+    }
+
+    /// Save how a track is drawn with the current project. This is view
+    /// state only; the edit and its undo history are untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no project is open or the track does not exist.
+    pub fn set_track_view(&mut self, track_id: u64, view: TrackView) -> VideoEditorResult<()> {
+        self.track_view_state_mut("set_track_view", &[track_id])?.set_view(track_id, view);
+        Ok(())
+    }
+
+    /// Get the timeline's track IDs, bottom to top, in the order the user
+    /// arranged the track list.
+    pub fn track_list_order(&self) -> Vec<u64> {
+        let order = self.timeline.track_order();
+        match self.projects.current_project() {
+            Some(project) => project.track_view().ordered(&order),
+            None => order,
+        }
+    }
+
+    /// Save a custom track list order, bottom to top, with the current
+    /// project. Compositing order is unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no project is open or a track does not exist.
+    pub fn set_track_list_order(&mut self, order: &[u64]) -> VideoEditorResult<()> {
+        self.track_view_state_mut("set_track_list_order", order)?.set_order(order);
+        Ok(())
+    }
+
+    /// Current project's track view state after checking `track_ids`
+    /// exist, with state of deleted tracks dropped.
+    fn track_view_state_mut(
+        &mut self, operation: &'static str, track_ids: &[u64],
+    ) -> VideoEditorResult<&mut TrackViewState> {
+        let timeline = &self.timeline;
+        if let Some(missing) = track_ids.iter().find(|&&id| timeline.get_track(id).is_none()) {
+            return Err(VideoEditorError::timeline(
+                operation,
+                format!("Track {missing} not found"),
+            ));
         }
+        let existing = self.timeline.track_order();
+        let project = self
+            .projects
+            .current_project_mut()
+            .ok_or_else(|| VideoEditorError::Project("No project open".into()))?;
+        let state = project.track_view_mut();
+        state.retain_tracks(&existing);
+        Ok(state)
     }
 
     /// Initialize the editor (including GPU).
+    ///
+    /// Effects are re-probed so they fall back to the CPU when no device
+    /// came up.
     pub fn initialize(&mut self) -> bool {
-        self.gpu.initialize()
+        let available = self.gpu.initialize();
+        self.effects.probe_gpu(&self.gpu);
+        available
     }
 
     /// Get configuration.
@@ -46,6 +215,17 @@ impl VideoEditorPlugin {
         &mut self.timeline
     }
 
+    /// Get the keyframe animation of timeline clips, e.g. the reframe
+    /// views of 360° clips.
+    pub fn animation(&self) -> &AnimationManager {
+        &self.animation
+    }
+
+    /// Get the mutable keyframe animation.
+    pub fn animation_mut(&mut self) -> &mut AnimationManager {
+        &mut self.animation
+    }
+
     /// Get asset library.
     pub fn assets(&self) -> &AssetLibrary {
         &self.assets
@@ -66,16 +246,612 @@ impl VideoEditorPlugin {
         &mut self.effects
     }
 
+    /// Get preview/playback state.
+    pub fn preview(&self) -> &PreviewManager {
+        &self.preview
+    }
+
+    /// Get mutable preview/playback state.
+    pub fn preview_mut(&mut self) -> &mut PreviewManager {
+        &mut self.preview
+    }
+
+    /// Get timeline markers.
+    pub fn markers(&self) -> &MarkerManager {
+        &self.markers
+    }
+
+    /// Get mutable timeline markers.
+    pub fn markers_mut(&mut self) -> &mut MarkerManager {
+        &mut self.markers
+    }
+
+    /// Export the markers as review comment JSON at the project frame
+    /// rate.
+    pub fn export_review_comments(&self, project: &str) -> String {
+        review_comments::export_review_comments(&self.markers, &self.config.frame_rate, project)
+    }
+
+    /// Import review comment JSON returned by reviewers into the markers.
+    pub fn import_review_comments(&mut self, json: &str) -> VideoEditorResult<ReviewImport> {
+        review_comments::import_review_comments(&mut self.markers, json, &self.config.frame_rate)
+    }
+
+    /// Get the playhead position.
+    pub fn playhead(&self) -> TimePosition {
+        self.preview.position()
+    }
+
+    /// Replace the clip selection.
+    pub fn select_clips(&mut self, clip_ids: &[u64]) {
+        self.timeline.selection_mut().select_clips(clip_ids);
+    }
+
+    /// Get the picked clip IDs; see [`TimelineManager::selected_clips`]
+    /// for every clip the selection covers.
+    pub fn selected_clips(&self) -> &[u64] {
+        self.timeline.selection().clips()
+    }
+
+    /// Select the clips under the playhead and every clip after it on all
+    /// tracks.
+    pub fn select_forward_from_playhead(&mut self, add_to_selection: bool) {
+        let at = self.playhead();
+        self.timeline.select_forward(at, &[], add_to_selection);
+    }
+
+    /// Select the clips overlapping metadata search matches.
+    pub fn select_metadata_matches(&mut self, matches: &[MetadataMatch]) {
+        let rate = self.config.frame_rate;
+        let clip_ids: Vec<u64> =
+            matches.iter().flat_map(|m| m.clips(&self.timeline, &rate)).collect();
+        self.timeline.selection_mut().select_clips(&clip_ids);
+    }
+
+    /// Add a named range marker for each metadata search match.
+    pub fn mark_metadata_matches(
+        &mut self, matches: &[MetadataMatch], name: &str,
+    ) -> Vec<MarkerId> {
+        let rate = self.config.frame_rate;
+        matches.iter().map(|m| m.to_marker(&mut self.markers, &rate, name)).collect()
+    }
+
+    /// Get the transition manager.
+    pub fn transitions(&self) -> &TransitionManager {
+        &self.transitions
+    }
+
+    /// Get the mutable transition manager.
+    pub fn transitions_mut(&mut self) -> &mut TransitionManager {
+        &mut self.transitions
+    }
+
+    /// Get the bus assignments to hardware outputs, for the host's mixer
+    /// to monitor through.
+    pub fn output_routing(&self) -> &OutputRouting {
+        &self.routing
+    }
+
+    /// Get the mutable bus assignments to hardware outputs.
+    pub fn output_routing_mut(&mut self) -> &mut OutputRouting {
+        &mut self.routing
+    }
+
+    /// Get the scripting facade over the timeline, transitions and markers.
+    pub fn script_api(&mut self) -> EditorScriptApi<'_> {
+        EditorScriptApi::new(
+            &mut self.timeline,
+            &mut self.transitions,
+            &mut self.markers,
+            self.config.frame_rate,
+        )
+    }
+
+    /// Run a batch of scripted operations, stopping at the first failure.
+    pub fn run_batch(&mut self, ops: Vec<ScriptOperation>) -> ScriptBatchResult {
+        let result = self.script_api().run_batch(ops);
+        self.preview.set_duration(self.timeline.duration());
+        result
+    }
+
+    /// Get the command registry.
+    pub fn commands(&self) -> &CommandRegistry {
+        &self.commands
+    }
+
+    /// Get the mutable command registry (register commands, remap shortcuts).
+    pub fn commands_mut(&mut self) -> &mut CommandRegistry {
+        &mut self.commands
+    }
+
+    /// Run a command by ID.
+    ///
+    /// Returns false if the command is unknown or handled by the host.
+    pub fn execute_command(&mut self, id: &str) -> VideoEditorResult<bool> {
+        let Some(handler) = self.commands.get(id).and_then(|c| c.handler.clone()) else {
+            return Ok(false);
+        };
+        self.preview.set_duration(self.timeline.duration());
+        handler(self)?;
+        self.preview.set_duration(self.timeline.duration());
+        Ok(true)
+    }
+
+    /// Run the command bound to a shortcut (e.g. "Ctrl+X").
+    ///
+    /// Returns false if nothing is bound or the host handles the command.
+    pub fn execute_shortcut(&mut self, shortcut: &str) -> VideoEditorResult<bool> {
+        match self.commands.command_for_shortcut(shortcut) {
+            Some(command) => {
+                let id = command.id.clone();
+                self.execute_command(&id)
+            },
+            None => Ok(false),
+        }
+    }
+
+    /// Split clips under the playhead: the selected ones, or every clip on
+    /// an unlocked track when nothing is selected. Returns the new clip IDs.
+    pub fn split_at_playhead(&mut self) -> VideoEditorResult<Vec<u64>> {
+        let at = self.playhead();
+        let selection = self.timeline.selected_clips();
+        let targets: Vec<u64> = self
+            .timeline
+            .tracks()
+            .iter()
+            .filter(|t| !self.timeline.is_track_locked(t.id))
+            .flat_map(|t| &t.clips)
+            .filter(|c| c.start.ms < at.ms && at.ms < c.end().ms)
+            .filter(|c| selection.is_empty() || selection.contains(&c.id))
+            .map(|c| c.id)
+            .collect();
+        targets.into_iter().map(|id| self.timeline.split_clip(id, at)).collect()
+    }
+
+    /// Remove the selected clips, leaving gaps. Nothing is removed if one
+    /// is on a locked track.
+    pub fn delete_selection(&mut self) -> VideoEditorResult<()> {
+        self.timeline.delete_selection().map(|_| ())
+    }
+
+    /// Ripple delete the selected clips and clear the selection.
+    pub fn ripple_delete_selection(&mut self) -> VideoEditorResult<()> {
+        let clip_ids = self.timeline.selected_clips();
+        self.timeline.selection_mut().clear();
+        for id in clip_ids {
+            // Linked clips may already have gone with an earlier selection
+            if self.clip_start(id).is_some() {
+                self.timeline.ripple_delete(id, RippleSync::Track)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the selected clips together by whole frames, ignoring
+    /// snapping. Nothing moves if any of them can't.
+    pub fn nudge_selection(&mut self, frames: i64) -> VideoEditorResult<()> {
+        let frame_ms = (self.config.frame_rate.frame_duration_us() + 500) / 1000;
+        self.timeline.nudge_selection(frames.saturating_mul(frame_ms as i64))
+    }
+
+    /// Add a standard marker at the playhead.
+    pub fn add_marker_at_playhead(&mut self) -> MarkerId {
+        self.markers.add_marker(self.playhead(), MarkerType::Standard)
+    }
+
+    fn clip_start(&self, clip_id: u64) -> Option<u64> {
+        self.timeline
+            .tracks()
+            .iter()
+            .find_map(|t| t.clips.iter().find(|c| c.id == clip_id))
+            .map(|c| c.start.ms)
+    }
+
+    /// Move a clip without letting snapping pull it.
+    fn move_clip_exact(&mut self, clip_id: u64, start: TimePosition) -> VideoEditorResult<()> {
+        let snapping = self.timeline.snap_engine().enabled;
+        self.timeline.snap_engine_mut().enabled = false;
+        let moved = self.timeline.move_clip(clip_id, start);
+        self.timeline.snap_engine_mut().enabled = snapping;
+        moved.map(|_| ())
+    }
+
     /// Check if GPU is available.
     pub fn gpu_available(&self) -> bool {
         self.gpu.is_available()
     }
 
+    /// Detect the hardware encoders of the GPU and platform for export.
+    pub fn hardware_encoders(&self) -> EncoderCapabilities {
+        EncoderCapabilities::probe(self.gpu.device_name())
+    }
+
+    /// Attach a host render target that receives the program output.
+    pub fn attach_render_target(
+        &mut self, desc: RenderTargetDesc,
+    ) -> VideoEditorResult<RenderTargetId> {
+        self.targets.attach(desc)
+    }
+
+    /// Detach a host render target.
+    pub fn detach_render_target(&mut self, id: RenderTargetId) -> bool {
+        self.targets.detach(id)
+    }
+
+    /// Get render targets.
+    pub fn render_targets(&self) -> &RenderTargetRegistry {
+        &self.targets
+    }
+
+    /// Get mutable render targets.
+    pub fn render_targets_mut(&mut self) -> &mut RenderTargetRegistry {
+        &mut self.targets
+    }
+
+    /// Render a program frame (RGBA8) into all attached render targets.
+    ///
+    /// Returns the number of targets updated.
+    pub fn render_to_targets(
+        &mut self, frame: u64, pixels: &[u8], width: u32, height: u32,
+    ) -> usize {
+        let gpu_available = self.gpu.is_available();
+        self.targets.present(frame, pixels, width, height, gpu_available)
+    }
+
+    /// Supply decoded frames for media clips in [`Self::render_frame_to_buffer`].
+    pub fn set_frame_source(&mut self, source: ClipFrameSource) {
+        self.frames = Some(source);
+    }
+
+    /// Frame buffers shared by rendering, the preview cache and export.
+    ///
+    /// Frame sources and decoders (through
+    /// [`DecoderRegistry::set_frame_pool`](crate::DecoderRegistry::set_frame_pool))
+    /// should take their buffers from here, so frames are recycled rather
+    /// than allocated per frame.
+    pub fn frame_pool(&self) -> &FrameBufferPool {
+        &self.frame_pool
+    }
+
+    /// Render the program frame at `time` as RGBA8 at the project
+    /// resolution, for golden-image tests and thumbnails.
+    ///
+    /// With [`RenderDeterminism::strict`](super::RenderDeterminism::strict)
+    /// in the config the bytes are identical on every run.
+    pub fn render_frame_to_buffer(&self, time: TimePosition) -> VideoEditorResult<Vec<u8>> {
+        self.render_frame_pooled(time, &self.frame_pool).map(FrameBuffer::into_vec)
+    }
+
+    /// [`Self::render_frame_to_buffer`] into a buffer from `pool`, which
+    /// goes back to the pool when dropped.
+    pub fn render_frame_pooled(
+        &self, time: TimePosition, pool: &FrameBufferPool,
+    ) -> VideoEditorResult<FrameBuffer> {
+        self.render_with(time, Some(&self.animation), pool)
+    }
+
+    /// [`Self::render_frame_pooled`] with 360° clips left equirectangular
+    /// instead of reframed, for spherical exports.
+    pub fn render_frame_equirectangular(
+        &self, time: TimePosition, pool: &FrameBufferPool,
+    ) -> VideoEditorResult<FrameBuffer> {
+        self.render_with(time, None, pool)
+    }
+
+    fn render_with(
+        &self, time: TimePosition, reframe: Option<&AnimationManager>, pool: &FrameBufferPool,
+    ) -> VideoEditorResult<FrameBuffer> {
+        render::render_frame(
+            &self.timeline,
+            &self.assets,
+            reframe,
+            self.frames.as_ref(),
+            &self.config.determinism,
+            time,
+            time.to_frame(&self.config.frame_rate),
+            self.config.resolution,
+            self.config.field_order,
+            pool,
+        )
+    }
+
+    /// Split an export into the jobs to queue, one per output file, with
+    /// the range resolved to a fixed span against the timeline, the
+    /// preview in/out points, the clip selection and the markers.
+    ///
+    /// Parts of a batch range are written next to `output_path` with the
+    /// part name appended to the file name. Fails if the settings ask for
+    /// interlaced output the container or codec can't carry, or for
+    /// equirectangular output that isn't 2:1.
+    pub fn plan_exports(
+        &self, settings: &ExportSettings,
+    ) -> VideoEditorResult<Vec<ExportSettings>> {
+        settings.check_field_order()?;
+        settings.check_projection()?;
+        let ranges = settings.range.resolve(
+            &self.timeline,
+            self.preview.in_out(),
+            &self.timeline.selected_clips(),
+            &self.markers,
+        )?;
+        Ok(ranges
+            .into_iter()
+            .map(|range| ExportSettings {
+                output_path: range.output_path(&settings.output_path),
+                range: ExportRange::Span(range.start, range.end),
+                ..settings.clone()
+            })
+            .collect())
+    }
+
+    /// Render test patterns and the reference tone through the output
+    /// pipeline at the project resolution and check levels and colors.
+    pub fn validate_pipeline(&self) -> PipelineValidation {
+        let resolution = self.config.resolution;
+        generators::validate_pipeline(resolution.width, resolution.height, 48000)
+    }
+
+    /// Copy preview, cache, GPU and timeline figures into plugin metrics,
+    /// e.g. from a FlexForge metrics source. Audio meters come from the
+    /// host's mixer.
+    pub fn fill_metrics(&self, metrics: &mut VideoEditorMetrics) {
+        self.preview.fill_metrics(metrics);
+        self.gpu.fill_metrics(metrics);
+        metrics.playback_position_ms = self.preview.position().ms;
+        metrics.timeline_duration_ms = self.timeline.duration().ms;
+        metrics.active_tracks = self.timeline.tracks().len() as u32;
+    }
+
+    /// Scan the project for common problems. Media counts as offline when
+    /// its path doesn't exist on disk.
+    pub fn diagnose_project(&self, doctor: &ProjectDoctor) -> DoctorReport {
+        doctor.diagnose(&self.timeline, &self.assets, |path| std::path::Path::new(path).exists())
+    }
+
+    /// Apply one-click fixes from a doctor report. Returns how many applied.
+    pub fn apply_doctor_fixes(&mut self, fixes: &[DoctorFix]) -> usize {
+        ProjectDoctor::apply_fixes(fixes, &mut self.timeline, &mut self.assets)
+    }
+
+    /// Mark assets whose media is missing on disk as offline, and bring
+    /// back ones whose media has returned. Clips using offline assets stay
+    /// editable and render a slate in preview and export. Returns the
+    /// offline asset IDs.
+    pub fn refresh_offline_media(&mut self) -> Vec<u64> {
+        self.assets.refresh_offline(|path| std::path::Path::new(path).is_file())
+    }
+
+    /// Search for the current project's missing media and point the assets
+    /// using relinked media at the new files, bringing them back online.
+    ///
+    /// Returns `None` if no project is open.
+    pub fn relink_media(
+        &mut self, search_roots: &[impl AsRef<std::path::Path>],
+    ) -> Option<RelinkReport> {
+        let report = self.projects.current_project_mut()?.relink(search_roots);
+        for (old, new) in &report.relinked {
+            self.assets.relink(old, new);
+        }
+        Some(report)
+    }
+
+    /// Move a production sound clip so its BWF timecode lines up with the
+    /// camera timecode of an anchor video clip.
+    ///
+    /// Both are timeline clip IDs. The anchor's source needs a start
+    /// timecode and the audio source needs BWF metadata.
+    pub fn sync_by_timecode(&mut self, anchor_clip_id: u64, clip_id: u64) -> VideoEditorResult<()> {
+        let timeline_clip = |id: u64| {
+            self.timeline
+                .tracks()
+                .iter()
+                .find_map(|t| t.clips.iter().find(|c| c.id == id))
+                .ok_or_else(|| {
+                    VideoEditorError::timeline_clip(
+                        "sync_by_timecode",
+                        id,
+                        format!("Clip not found: {id}"),
+                    )
+                })
+        };
+        let anchor = timeline_clip(anchor_clip_id)?;
+        let clip = timeline_clip(clip_id)?;
+
+        let anchor_tc =
+            self.assets.video_clip(anchor.source_id).and_then(|v| v.start_timecode).ok_or_else(
+                || VideoEditorError::Asset(format!("Clip {anchor_clip_id} has no source timecode")),
+            )?;
+        let clip_tc =
+            self.assets.audio_clip(clip.source_id).and_then(|a| a.start_timecode()).ok_or_else(
+                || VideoEditorError::Asset(format!("Clip {clip_id} has no BWF timecode")),
+            )?;
+
+        // Timecode at a clip's timeline start is source start + in point
+        let anchor_at_start = anchor_tc.ms + anchor.in_point.ms;
+        let clip_at_start = clip_tc.ms + clip.in_point.ms;
+        let start =
+            (anchor.start.ms + clip_at_start).checked_sub(anchor_at_start).ok_or_else(|| {
+                VideoEditorError::timeline_clip(
+                    "sync_by_timecode",
+                    clip_id,
+                    format!("Clip {clip_id} would start before the timeline"),
+                )
+            })?;
+        // Timecode sync is exact
+        self.move_clip_exact(clip_id, TimePosition::from_ms(start))
+    }
+
+    /// Save a track's setup as a track preset in the user config,
+    /// replacing a preset with the same name.
+    pub fn save_track_template(&mut self, track_id: u64) -> VideoEditorResult<()> {
+        let track = self.timeline.get_track(track_id).ok_or_else(|| {
+            VideoEditorError::timeline(
+                "save_track_template",
+                format!("Track not found: {track_id}"),
+            )
+        })?;
+        let template = TrackTemplate::from_track(track, &self.effects);
+        self.config.track_templates.retain(|t| t.name != template.name);
+        self.config.track_templates.push(template);
+        Ok(())
+    }
+
+    /// Add a track from a saved track preset. Returns the new track ID.
+    pub fn apply_track_template(&mut self, name: &str) -> VideoEditorResult<u64> {
+        let template =
+            self.config.track_templates.iter().find(|t| t.name == name).ok_or_else(|| {
+                VideoEditorError::timeline(
+                    "apply_track_template",
+                    format!("Track template not found: {name}"),
+                )
+            })?;
+        Ok(self.timeline.apply_track_template(template, &mut self.effects))
+    }
+
+    /// Save the current track layout in the user config, replacing a
+    /// layout with the same name.
+    pub fn save_track_layout(&mut self, name: &str) {
+        let layout = self.timeline.save_track_layout(name, &self.effects);
+        self.config.track_layouts.retain(|l| l.name != name);
+        self.config.track_layouts.push(layout);
+    }
+
+    /// Add the tracks of a saved layout. Returns the new track IDs.
+    pub fn apply_track_layout(&mut self, name: &str) -> VideoEditorResult<Vec<u64>> {
+        let layout =
+            self.config.track_layouts.iter().find(|l| l.name == name).ok_or_else(|| {
+                VideoEditorError::timeline(
+                    "apply_track_layout",
+                    format!("Track layout not found: {name}"),
+                )
+            })?;
+        Ok(self.timeline.apply_track_layout(layout, &mut self.effects))
+    }
+
+    /// Get the color grade of a clip.
+    pub fn clip_grade(&self, clip_id: u64) -> Option<&ColorGradingNode> {
+        self.grades.get(&clip_id)
+    }
+
+    /// Keyframe a clip's crop window for a different output aspect ratio,
+    /// following detected objects in `metadata` and falling back to image
+    /// saliency. Replaces the clip's crop keyframes and returns how many
+    /// center keyframes were written.
+    pub fn reframe_clip(
+        &mut self, clip_id: u64, reframe: &SmartReframe, metadata: &MetadataIndex,
+    ) -> VideoEditorResult<usize> {
+        let clip = self
+            .timeline
+            .tracks()
+            .iter()
+            .find_map(|t| t.clips.iter().find(|c| c.id == clip_id))
+            .ok_or_else(|| {
+                VideoEditorError::timeline_clip(
+                    "reframe_clip",
+                    clip_id,
+                    format!("Clip not found: {clip_id}"),
+                )
+            })?;
+        let points = reframe.analyze(clip, metadata, self)?;
+        let resolution = self.config.resolution;
+        let transform = self.timeline.clip_transform_mut(clip_id).ok_or_else(|| {
+            VideoEditorError::timeline_clip(
+                "reframe_clip",
+                clip_id,
+                format!("Clip not found: {clip_id}"),
+            )
+        })?;
+        Ok(reframe.write_keyframes(&points, resolution, transform))
+    }
+
+    /// Apply a library preset to a clip.
+    ///
+    /// The preset's grade replaces the clip's grade, its effects are added
+    /// to the end of the clip's effect stack and its transition is placed
+    /// on the cut to the clip that follows. Nothing changes if the preset
+    /// has a transition and no clip is cut against the end of this one.
+    pub fn apply_preset(
+        &mut self, library: &PresetLibrary, name: &str, clip_id: u64,
+    ) -> VideoEditorResult<()> {
+        let preset = library
+            .get(name)
+            .ok_or_else(|| VideoEditorError::Effect(format!("Preset not found: {name}")))?;
+        let cut = match &preset.transition {
+            Some(transition) => {
+                let (track_id, next) = self.timeline.following_clip(clip_id).ok_or_else(|| {
+                    VideoEditorError::timeline_clip(
+                        "apply_preset",
+                        clip_id,
+                        format!("No clip follows clip {clip_id}"),
+                    )
+                })?;
+                Some((track_id, next.id, next.start, transition))
+            },
+            None => None,
+        };
+
+        let effect_ids: Vec<u64> = preset
+            .effects
+            .iter()
+            .map(|(effect_type, parameters)| {
+                self.effects.add_effect_with(*effect_type, parameters.clone())
+            })
+            .collect();
+        if let Err(err) = self.timeline.add_clip_effects(clip_id, &effect_ids) {
+            for id in effect_ids {
+                self.effects.remove_effect(id);
+            }
+            return Err(err);
+        }
+        if let Some(grade) = &preset.grade {
+            self.grades.insert(clip_id, grade.clone());
+        }
+        if let Some((track_id, next_id, at, transition)) = cut {
+            let start = TimePosition::from_ms(at.ms.saturating_sub(transition.duration.ms / 2));
+            self.transitions.add_with_preset(track_id, clip_id, next_id, start, transition);
+        }
+        Ok(())
+    }
+
+    /// Capture a clip's grade and effect stack as a preset.
+    pub fn capture_preset(
+        &self, clip_id: u64, name: impl Into<String>, category: impl Into<String>,
+    ) -> VideoEditorResult<Preset> {
+        let clip = self
+            .timeline
+            .tracks()
+            .iter()
+            .find_map(|t| t.clips.iter().find(|c| c.id == clip_id))
+            .ok_or_else(|| {
+                VideoEditorError::timeline_clip(
+                    "capture_preset",
+                    clip_id,
+                    format!("Clip not found: {clip_id}"),
+                )
+            })?;
+        let mut preset = Preset::new(name, category);
+        preset.grade = self.grades.get(&clip_id).cloned();
+        preset.effects = clip
+            .effect_ids
+            .iter()
+            .filter_map(|&id| self.effects.effect(id))
+            .map(|e| (e.effect_type, e.parameters.clone()))
+            .collect();
+        Ok(preset)
+    }
+
     /// Create a new project.
     pub fn new_project(&mut self) {
         self.timeline = TimelineManager::new();
         self.assets = AssetLibrary::new();
         self.effects = EffectsPipeline::new();
+        self.markers = MarkerManager::new();
+        self.transitions = TransitionManager::new();
+        self.grades.clear();
+        self.animation = AnimationManager::new();
+        self.preview.stop();
+        self.preview.set_duration(TimePosition::default());
+        self.attach_event_bus();
 
         // Add default tracks
         self.timeline.add_track("Video 1", TrackType::Video);
@@ -106,10 +882,1225 @@ mod tests {
         assert_eq!(plugin.timeline().tracks().len(), 2);
     }
 
+    #[test]
+    fn test_plan_exports() {
+        use crate::types::timeline::TimelineClip;
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let video = plugin.timeline().tracks()[0].id;
+        let track = plugin.timeline_mut().get_track_mut(video).expect("test assertion");
+        track.add_clip(TimelineClip::new(
+            1,
+            1,
+            TimePosition::from_secs(2),
+            TimePosition::from_secs(3),
+        ));
+        track.add_clip(TimelineClip::new(
+            2,
+            2,
+            TimePosition::from_secs(8),
+            TimePosition::from_secs(2),
+        ));
+        let secs = |s| TimePosition::from_secs(s);
+        let plan = |plugin: &VideoEditorPlugin, range| {
+            let settings = ExportSettings {
+                output_path: "out/show.mp4".into(),
+                range,
+                ..ExportSettings::default()
+            };
+            plugin.plan_exports(&settings)
+        };
+        let spans = |plugin: &VideoEditorPlugin, range| {
+            plan(plugin, range)
+                .expect("test assertion")
+                .into_iter()
+                .map(|s| (s.output_path, s.range))
+                .collect::<Vec<_>>()
+        };
+
+        // Leading and trailing gaps are trimmed
+        assert_eq!(
+            spans(&plugin, ExportRange::Entire),
+            vec![("out/show.mp4".into(), ExportRange::Span(secs(2), secs(10)))]
+        );
+
+        plugin.preview_mut().in_out_mut().set_in(secs(1));
+        plugin.preview_mut().in_out_mut().set_out(secs(4));
+        assert_eq!(spans(&plugin, ExportRange::InOut)[0].1, ExportRange::Span(secs(2), secs(4)));
+
+        assert!(plan(&plugin, ExportRange::Selection).is_err());
+        plugin.select_clips(&[2]);
+        assert_eq!(
+            spans(&plugin, ExportRange::Selection)[0].1,
+            ExportRange::Span(secs(8), secs(10))
+        );
+
+        let intro = plugin.markers_mut().add_chapter(secs(0), "Intro");
+        let main = plugin.markers_mut().add_chapter(secs(6), "Main Part");
+        let note = plugin.markers_mut().add_marker(secs(9), MarkerType::Standard);
+        assert_eq!(
+            spans(&plugin, ExportRange::BetweenMarkers(note, intro))[0].1,
+            ExportRange::Span(secs(2), secs(9))
+        );
+
+        // Every chapter to its own file; the gap before the next chapter
+        // is trimmed off the first
+        let chapters = ExportRange::each_chapter(plugin.markers());
+        assert!(chapters.is_batch());
+        assert_eq!(
+            spans(&plugin, chapters.clone()),
+            vec![
+                ("out/show_Intro.mp4".into(), ExportRange::Span(secs(2), secs(5))),
+                ("out/show_Main_Part.mp4".into(), ExportRange::Span(secs(8), secs(10))),
+            ]
+        );
+
+        assert!(plan(&plugin, ExportRange::Chapter(note)).is_err());
+        assert!(plan(&plugin, ExportRange::Chapter(main)).is_ok());
+        assert!(plan(&plugin, ExportRange::Span(secs(11), secs(12))).is_err());
+        assert!(plan(&plugin, ExportRange::Multiple(vec![("All".into(), chapters)])).is_err());
+    }
+
+    #[test]
+    fn test_adjustment_clip() {
+        use crate::types::{AdjustmentClip, TimePosition, timeline::TimelineClip};
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let video = plugin.timeline().tracks()[0].id;
+        let fx = plugin.timeline_mut().add_track("Adjust", TrackType::Effect);
+
+        let timeline = plugin.timeline_mut();
+        timeline.get_track_mut(video).expect("test assertion").add_clip(TimelineClip::new(
+            1,
+            1,
+            TimePosition::from_ms(0),
+            TimePosition::from_secs(10),
+        ));
+
+        let mut adjustment =
+            AdjustmentClip::new(7, TimePosition::from_secs(2), TimePosition::from_secs(3));
+        adjustment.effect_ids = vec![1, 2];
+        assert!(timeline.add_adjustment_clip(video, adjustment.clone()).is_err());
+        timeline.add_adjustment_clip(fx, adjustment.clone()).expect("test assertion");
+        assert!(timeline.add_adjustment_clip(fx, adjustment.clone()).is_err());
+
+        assert_eq!(timeline.adjustments_for(video, TimePosition::from_secs(3)).len(), 1);
+        assert!(timeline.adjustments_for(video, TimePosition::from_secs(6)).is_empty());
+        assert!(timeline.adjustments_for(fx, TimePosition::from_secs(3)).is_empty());
+
+        // video composite, adjustment post-process, output
+        assert_eq!(timeline.build_render_graph(TimePosition::from_secs(3)).pass_count(), 3);
+        assert_eq!(timeline.build_render_graph(TimePosition::from_secs(6)).pass_count(), 2);
+
+        assert_eq!(AdjustmentClip::from_bytes(&adjustment.to_bytes()), Some(adjustment));
+    }
+
+    #[test]
+    fn test_snapped_move_and_trim() {
+        use crate::types::{TimePosition, timeline::TimelineClip};
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let video = plugin.timeline().tracks()[0].id;
+        let timeline = plugin.timeline_mut();
+        let track = timeline.get_track_mut(video).expect("test assertion");
+        track.add_clip(TimelineClip::new(
+            1,
+            1,
+            TimePosition::from_ms(0),
+            TimePosition::from_secs(2),
+        ));
+        track.add_clip(TimelineClip::new(
+            2,
+            1,
+            TimePosition::from_secs(5),
+            TimePosition::from_secs(2),
+        ));
+        timeline.snap_engine_mut().snap_to_grid = false;
+
+        // Start lands 30 ms after clip 1's end and snaps onto it
+        let snapped = timeline.move_clip(2, TimePosition::from_ms(2_030)).expect("test assertion");
+        assert_eq!(snapped.position.ms, 2_000);
+        assert!(timeline.move_clip(2, TimePosition::from_ms(1_000)).is_err());
+
+        let snapped =
+            timeline.trim_clip_end(1, TimePosition::from_ms(1_900)).expect("test assertion");
+        assert!(!snapped.is_snapped());
+        assert_eq!(timeline.get_track(video).expect("test assertion").clips[0].duration.ms, 1_900);
+
+        timeline.get_track_mut(video).expect("test assertion").locked = true;
+        assert!(timeline.trim_clip_start(1, TimePosition::from_ms(500)).is_err());
+    }
+
+    #[test]
+    fn test_split_clip_ids_are_not_reused() {
+        use crate::types::{TimePosition, timeline::TimelineClip};
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let timeline = plugin.timeline_mut();
+        let v1 = timeline.tracks()[0].id;
+        for (id, start) in [(1, 0), (2, 10)] {
+            let clip = TimelineClip::new(
+                id,
+                1,
+                TimePosition::from_secs(start),
+                TimePosition::from_secs(4),
+            );
+            timeline.add_clip(v1, clip).expect("test assertion");
+        }
+
+        // The removed clip's ID stays retired
+        timeline.remove_clip(2).expect("test assertion");
+        let second = timeline.split_clip(1, TimePosition::from_secs(2)).expect("test assertion");
+        assert_eq!(second, 3);
+        let third =
+            timeline.split_clip(second, TimePosition::from_secs(3)).expect("test assertion");
+        assert_eq!(third, 4);
+        assert!(timeline.split_clip(1, TimePosition::from_secs(9)).is_err());
+
+        // Added clips move the counter past their IDs
+        let late = TimelineClip::new(9, 1, TimePosition::from_secs(20), TimePosition::from_secs(4));
+        timeline.add_clip(v1, late).expect("test assertion");
+        assert_eq!(
+            timeline.split_clip(9, TimePosition::from_secs(21)).expect("test assertion"),
+            10
+        );
+    }
+
+    #[test]
+    fn test_track_lock_and_solo() {
+        use crate::types::{TimePosition, timeline::TimelineClip};
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let timeline = plugin.timeline_mut();
+        let (v1, a1) = (timeline.tracks()[0].id, timeline.tracks()[1].id);
+        let v2 = timeline.add_track("Video 2", TrackType::Video);
+        let clip =
+            |id| TimelineClip::new(id, 1, TimePosition::from_ms(0), TimePosition::from_secs(4));
+        timeline.add_clip(v1, clip(1)).expect("test assertion");
+        timeline.add_clip(v2, clip(2)).expect("test assertion");
+        timeline.add_clip(a1, clip(3)).expect("test assertion");
+        assert!(timeline.add_clip(v1, clip(4)).is_err());
+
+        timeline.set_track_locked(v1, true);
+        assert!(timeline.remove_clip(1).is_err());
+        assert!(timeline.move_clip(1, TimePosition::from_secs(10)).is_err());
+        assert!(!timeline.remove_track(v1));
+
+        // Video solo hides the other video track but keeps audio playing
+        timeline.set_track_solo(v2, true);
+        assert!(!timeline.is_track_visible(v1));
+        assert!(timeline.is_track_visible(v2));
+        assert!(timeline.is_track_audible(a1));
+        assert_eq!(timeline.active_clips_at(TimePosition::from_secs(1)).len(), 2);
+        assert_eq!(timeline.build_render_graph(TimePosition::from_secs(1)).pass_count(), 2);
+
+        timeline.set_track_enabled(v2, false);
+        assert!(timeline.is_track_visible(v1));
+        timeline.set_track_muted(a1, true);
+        assert!(!timeline.is_track_audible(a1));
+        assert_eq!(timeline.active_clips_at(TimePosition::from_secs(1)).len(), 1);
+    }
+
+    #[test]
+    fn test_ripple_edits() {
+        use crate::{
+            implementation::RippleSync,
+            types::{TimePosition, timeline::TimelineClip},
+        };
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let timeline = plugin.timeline_mut();
+        let (video, audio) = (timeline.tracks()[0].id, timeline.tracks()[1].id);
+        let clip = |id, start| {
+            TimelineClip::new(id, 1, TimePosition::from_secs(start), TimePosition::from_secs(2))
+        };
+        timeline.add_clip(video, clip(1, 0)).expect("test assertion");
+        timeline.add_clip(video, clip(2, 2)).expect("test assertion");
+        timeline.add_clip(video, clip(3, 6)).expect("test assertion");
+        timeline.add_clip(audio, clip(4, 6)).expect("test assertion");
+
+        timeline.ripple_delete(2, RippleSync::AllTracks).expect("test assertion");
+        let starts = |tl: &TimelineManager, id| {
+            tl.get_track(id)
+                .expect("test assertion")
+                .clips
+                .iter()
+                .map(|c| c.start.ms)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(starts(timeline, video), vec![0, 4_000]);
+        assert_eq!(starts(timeline, audio), vec![4_000]);
+
+        // Gap 2..4 s is empty on both tracks
+        assert_eq!(timeline.close_gaps(video, RippleSync::AllTracks).expect("test assertion"), 1);
+        assert_eq!(starts(timeline, audio), vec![2_000]);
+
+        timeline
+            .insert_gap(
+                video,
+                TimePosition::from_secs(2),
+                TimePosition::from_secs(1),
+                RippleSync::Track,
+            )
+            .expect("test assertion");
+        assert_eq!(starts(timeline, video), vec![0, 3_000]);
+        assert_eq!(starts(timeline, audio), vec![2_000]);
+        assert!(
+            timeline
+                .insert_gap(
+                    video,
+                    TimePosition::from_secs(1),
+                    TimePosition::from_secs(1),
+                    RippleSync::Track
+                )
+                .is_err()
+        );
+
+        // Audio occupies the gap, so the synced close leaves it alone
+        assert_eq!(timeline.close_gaps(video, RippleSync::AllTracks).expect("test assertion"), 0);
+        assert_eq!(timeline.close_gaps(video, RippleSync::Track).expect("test assertion"), 1);
+    }
+
+    #[test]
+    fn test_ripple_edit_failures_and_edges() {
+        use crate::{
+            implementation::RippleSync,
+            types::{TimePosition, timeline::TimelineClip},
+        };
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let timeline = plugin.timeline_mut();
+        let (video, audio) = (timeline.tracks()[0].id, timeline.tracks()[1].id);
+        let music = timeline.add_track("Music", TrackType::Audio);
+        let clip = |id, start| {
+            TimelineClip::new(id, 1, TimePosition::from_secs(start), TimePosition::from_secs(2))
+        };
+        for (track, id, start) in [(video, 1, 0), (video, 2, 2), (video, 3, 6), (audio, 4, 3)] {
+            timeline.add_clip(track, clip(id, start)).expect("test assertion");
+        }
+        timeline.add_clip(music, clip(5, 6)).expect("test assertion");
+        let starts = |tl: &TimelineManager, id| {
+            tl.get_track(id)
+                .expect("test assertion")
+                .clips
+                .iter()
+                .map(|c| c.start.ms)
+                .collect::<Vec<_>>()
+        };
+
+        // Audio inside the removed range would fall out of sync; nothing moves
+        assert!(timeline.ripple_delete(2, RippleSync::AllTracks).is_err());
+        assert_eq!(starts(timeline, video), vec![0, 2_000, 6_000]);
+        assert!(timeline.ripple_delete(99, RippleSync::Track).is_err());
+
+        // Locked tracks can't be edited and don't follow a synced ripple
+        timeline.set_track_locked(music, true);
+        assert!(timeline.ripple_delete(5, RippleSync::Track).is_err());
+        timeline.remove_clip(4).expect("test assertion");
+        timeline.ripple_delete(2, RippleSync::AllTracks).expect("test assertion");
+        assert_eq!(starts(timeline, video), vec![0, 4_000]);
+        assert_eq!(starts(timeline, music), vec![6_000]);
+        assert!(timeline.close_gaps(99, RippleSync::Track).is_err());
+        assert!(timeline.close_gaps(music, RippleSync::Track).is_err());
+
+        // A gap at the timeline start closes like any other
+        let second = TimePosition::from_secs(1);
+        timeline
+            .insert_gap(video, TimePosition::default(), second, RippleSync::Track)
+            .expect("test assertion");
+        assert_eq!(starts(timeline, video), vec![1_000, 5_000]);
+        assert_eq!(timeline.close_gaps(video, RippleSync::Track).expect("test assertion"), 2);
+        assert_eq!(starts(timeline, video), vec![0, 2_000]);
+
+        // A clip spanning the insert point on a synced track blocks it; an
+        // insert at a cut doesn't need a split
+        timeline.add_clip(audio, clip(6, 1)).expect("test assertion");
+        let cut = TimePosition::from_secs(2);
+        assert!(timeline.insert_gap(video, cut, second, RippleSync::AllTracks).is_err());
+        assert_eq!(starts(timeline, video), vec![0, 2_000]);
+        timeline.insert_gap(video, cut, second, RippleSync::Track).expect("test assertion");
+        assert_eq!(starts(timeline, video), vec![0, 3_000]);
+
+        // Linked clips go with the ripple and close their own tracks
+        timeline.link_clips(&[1, 6]).expect("test assertion");
+        let removed = timeline.ripple_delete(1, RippleSync::Track).expect("test assertion");
+        assert_eq!(removed.iter().map(|c| c.id).collect::<Vec<_>>(), vec![1, 6]);
+        assert_eq!(starts(timeline, video), vec![1_000]);
+        assert!(starts(timeline, audio).is_empty());
+    }
+
+    #[test]
+    fn test_linked_clip_group() {
+        use crate::types::{ClipGroup, TimePosition, timeline::TimelineClip};
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let timeline = plugin.timeline_mut();
+        timeline.snap_engine_mut().enabled = false;
+        let (video, audio) = (timeline.tracks()[0].id, timeline.tracks()[1].id);
+        let clip = |id, start| {
+            TimelineClip::new(id, 1, TimePosition::from_secs(start), TimePosition::from_secs(2))
+        };
+        timeline.add_clip(video, clip(1, 0)).expect("test assertion");
+        timeline.add_clip(audio, clip(2, 0)).expect("test assertion");
+        timeline.add_clip(video, clip(3, 5)).expect("test assertion");
+        assert!(timeline.link_clips(&[1]).is_err());
+        let group = timeline.link_clips(&[1, 2]).expect("test assertion");
+        assert_eq!(timeline.resolve_selection(2), vec![1, 2]);
+
+        // Moving the audio drags the video along
+        timeline.move_clip(2, TimePosition::from_secs(1)).expect("test assertion");
+        let start = |tl: &TimelineManager, track, id| {
+            tl.get_track(track)
+                .and_then(|t| t.clips.iter().find(|c| c.id == id))
+                .map(|c| c.start.ms)
+        };
+        assert_eq!(start(timeline, video, 1), Some(1_000));
+        assert_eq!(start(timeline, audio, 2), Some(1_000));
+
+        // The video would overlap clip 3, so neither clip moves
+        assert!(timeline.move_clip(2, TimePosition::from_secs(4)).is_err());
+        assert_eq!(start(timeline, audio, 2), Some(1_000));
+
+        timeline.trim_clip_end(1, TimePosition::from_ms(2_500)).expect("test assertion");
+        assert!(timeline.tracks()[..2].iter().all(|t| t.clips[0].end().ms == 2_500));
+
+        let saved = timeline.groups().to_vec();
+        let bytes = saved[0].to_bytes();
+        assert_eq!(ClipGroup::from_bytes(&bytes).map(|g| g.clip_ids), Some(vec![1, 2]));
+
+        timeline.set_linked_selection(false);
+        timeline.move_clip(2, TimePosition::from_secs(3)).expect("test assertion");
+        assert_eq!(start(timeline, video, 1), Some(1_000));
+        timeline.move_clip(2, TimePosition::from_secs(1)).expect("test assertion");
+        timeline.set_linked_selection(true);
+
+        let removed = timeline.remove_clip(1).expect("test assertion");
+        assert_eq!(removed.iter().map(|c| c.id).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(timeline.group_of(1).is_none());
+        assert!(!timeline.unlink_group(group));
+
+        let mut project = crate::implementation::project_manager::Project::new(
+            crate::implementation::project_manager::ProjectId::new(1),
+            "Groups",
+        );
+        project.set_clip_groups(saved);
+        assert_eq!(project.clip_groups().len(), 1);
+    }
+
+    #[test]
+    fn test_clip_group_edge_cases() {
+        use crate::types::{ClipGroup, TimePosition, timeline::TimelineClip};
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let timeline = plugin.timeline_mut();
+        timeline.snap_engine_mut().enabled = false;
+        let (video, audio) = (timeline.tracks()[0].id, timeline.tracks()[1].id);
+        let music = timeline.add_track("Music", TrackType::Audio);
+        let clip = |id, start| {
+            TimelineClip::new(id, 1, TimePosition::from_secs(start), TimePosition::from_secs(2))
+        };
+        for (track, id, start) in
+            [(video, 1, 0), (audio, 2, 0), (video, 3, 4), (audio, 4, 4), (music, 5, 0)]
+        {
+            timeline.add_clip(track, clip(id, start)).expect("test assertion");
+        }
+
+        // Repeated and unknown clips don't make a group
+        assert!(timeline.link_clips(&[1, 1]).is_err());
+        assert!(timeline.link_clips(&[1, 99]).is_err());
+        assert!(timeline.groups().is_empty());
+
+        // Relinking moves a clip out of its old group, which dissolves
+        // once it links a single clip
+        let first = timeline.link_clips(&[1, 2]).expect("test assertion");
+        timeline.link_clips(&[2, 4]).expect("test assertion");
+        assert!(timeline.group_of(1).is_none());
+        assert!(!timeline.unlink_group(first));
+        assert!(timeline.unlink_clip(4));
+        assert!(!timeline.unlink_clip(4));
+        assert!(timeline.group_of(2).is_none());
+
+        // A member on a locked track holds the whole group in place
+        timeline.link_clips(&[1, 5]).expect("test assertion");
+        timeline.set_track_locked(music, true);
+        assert!(timeline.move_clip(1, TimePosition::from_secs(8)).is_err());
+        assert!(timeline.trim_clip_end(1, TimePosition::from_secs(1)).is_err());
+        assert!(timeline.remove_clip(1).is_err());
+        let clip_1 =
+            timeline.get_track(video).map(|t| (t.clips[0].start.ms, t.clips[0].duration.ms));
+        assert_eq!(clip_1, Some((0, 2_000)));
+        timeline.set_track_locked(music, false);
+
+        // Loaded groups drop links to missing clips and keep their IDs clear
+        // of new groups
+        timeline.set_groups(vec![ClipGroup::new(9, &[3, 4]), ClipGroup::new(10, &[2, 99])]);
+        assert_eq!(timeline.groups().len(), 1);
+        assert_eq!(timeline.group_of(4).map(|g| g.id), Some(9));
+        assert_eq!(timeline.link_clips(&[1, 2]).expect("test assertion"), 11);
+
+        let bytes = ClipGroup::new(3, &[1, 2]).to_bytes();
+        assert!(ClipGroup::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+        assert!(ClipGroup::from_bytes(&[2]).is_none());
+    }
+
+    #[test]
+    fn test_track_view_state() {
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let (video, audio) = (plugin.timeline().tracks()[0].id, plugin.timeline().tracks()[1].id);
+        let tall =
+            TrackView { height: 120, color: Some([1.0, 0.5, 0.0, 1.0]), collapsed: true };
+        // View state lives with a project
+        assert!(plugin.set_track_view(video, tall).is_err());
+        assert_eq!(plugin.track_view(video), Some(TrackView::default()));
+
+        plugin.projects_mut().new_project("Show").expect("test assertion");
+        plugin.set_track_view(video, tall).expect("test assertion");
+        assert!(plugin.set_track_view(99, tall).is_err());
+        plugin.set_track_list_order(&[audio, video]).expect("test assertion");
+        assert!(plugin.set_track_list_order(&[99]).is_err());
+        assert_eq!(plugin.track_view(video), Some(tall));
+        assert_eq!(plugin.track_list_order(), vec![audio, video]);
+
+        // The edit itself is untouched
+        assert_eq!(plugin.timeline().track_order(), vec![video, audio]);
+        assert_eq!(plugin.timeline().get_track(video).map(|t| t.height), Some(64));
+        let project = plugin.projects().current_project().expect("test assertion");
+        assert!(!project.can_undo());
+        let saved = project.track_view().clone();
+        assert_eq!(TrackViewState::from_bytes(&saved.to_bytes()), Some(saved));
+
+        // Tracks added later are listed above the arranged ones
+        let titles = plugin.timeline_mut().add_track("Titles", TrackType::Video);
+        assert_eq!(plugin.track_list_order(), vec![audio, video, titles]);
+    }
+
+    #[test]
+    fn test_track_reorder_and_groups() {
+        use crate::{
+            implementation::audio_mixer::AudioMixer,
+            types::{TimePosition, TrackGroup, timeline::TimelineClip},
+        };
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let timeline = plugin.timeline_mut();
+        let (video, dialogue) = (timeline.tracks()[0].id, timeline.tracks()[1].id);
+        let v2 = timeline.add_track("Video 2", TrackType::Video);
+        let dialogue2 = timeline.add_track("Dialogue 2", TrackType::Audio);
+        let music = timeline.add_track("Music", TrackType::Audio);
+
+        // Moving a track changes the render order
+        timeline.move_track(v2, 0).expect("test assertion");
+        assert_eq!(timeline.track_order(), vec![v2, video, dialogue, dialogue2, music]);
+        let clip =
+            |id| TimelineClip::new(id, 1, TimePosition::from_ms(0), TimePosition::from_secs(1));
+        timeline.add_clip(video, clip(1)).expect("test assertion");
+        timeline.add_clip(v2, clip(2)).expect("test assertion");
+        let graph = timeline.build_render_graph(TimePosition::from_ms(500));
+        let names: Vec<_> = graph.passes().iter().map(|p| p.name.clone()).collect();
+        assert_eq!(names[..2], [format!("track_{v2}"), format!("track_{video}")]);
+
+        let group =
+            timeline.group_tracks("Dialogue", &[dialogue, dialogue2]).expect("test assertion");
+        assert!(timeline.group_tracks("Empty", &[]).is_err());
+        timeline.move_track_group(group, 0).expect("test assertion");
+        assert_eq!(timeline.track_order(), vec![dialogue, dialogue2, v2, video, music]);
+
+        // Group mute, solo and lock apply to every member
+        timeline.set_track_group_solo(group, true);
+        assert!(timeline.is_track_audible(dialogue2));
+        assert!(!timeline.is_track_audible(music));
+        let mut mixer = AudioMixer::new(48000, 1024);
+        for id in [dialogue, dialogue2, music] {
+            let _ = mixer.add_track(id, "strip");
+        }
+        mixer.sync_track_states(timeline);
+        assert!(mixer.is_track_audible(dialogue) && !mixer.is_track_audible(music));
+
+        timeline.set_track_group_solo(group, false);
+        timeline.set_track_group_muted(group, true);
+        assert!(!timeline.is_track_audible(dialogue));
+        timeline.set_track_group_locked(group, true);
+        assert!(timeline.add_clip(dialogue, clip(3)).is_err());
+        timeline.set_track_group_collapsed(group, true);
+        assert!(timeline.is_track_collapsed(dialogue2));
+
+        let saved = timeline.track_groups()[0].clone();
+        assert_eq!(TrackGroup::from_bytes(&saved.to_bytes()), Some(saved.clone()));
+        let order = timeline.track_order();
+        timeline.move_track(music, 0).expect("test assertion");
+        timeline.set_track_order(&order);
+        assert_eq!(timeline.track_order(), order);
+        assert!(!timeline.remove_track(dialogue));
+    }
+
+    #[test]
+    fn test_bwf_timecode_sync() {
+        use crate::types::{
+            BwfMetadata, IxmlMetadata, IxmlTrack, TimePosition, timeline::TimelineClip,
+        };
+
+        // 10:00:05 at 48 kHz, two named channels
+        let mut bwf =
+            BwfMetadata::for_export("Scene 12", TimePosition::from_ms(36_005_000), 48000, 2);
+        bwf.ixml = Some(IxmlMetadata {
+            scene: "12".into(),
+            take: "3".into(),
+            tracks: vec![
+                IxmlTrack { channel_index: 1, name: "Boom".into() },
+                IxmlTrack { channel_index: 2, name: "Lav <1>".into() },
+            ],
+            ..Default::default()
+        });
+        let mut wav = b"RIFF\0\0\0\0WAVEfmt \x10\0\0\0\x01\0\x02\0\x80\xbb\0\0".to_vec();
+        wav.extend_from_slice(&[0, 0xee, 2, 0, 4, 0, 16, 0]);
+        wav.extend_from_slice(b"data\x04\0\0\0\0\0\0\0");
+        let tagged = bwf.write_wav(&wav).expect("test assertion");
+        let parsed = BwfMetadata::parse_wav(&tagged).expect("test assertion");
+        assert_eq!(parsed, bwf);
+        assert_eq!(parsed.ixml.as_ref().and_then(|x| x.track_name(2)), Some("Lav <1>"));
+        assert_eq!(
+            BwfMetadata::parse_wav(&parsed.write_wav(&tagged).expect("test assertion")),
+            Some(bwf)
+        );
+
+        let path = std::env::temp_dir().join("evep_bwf_sync.wav");
+        std::fs::write(&path, &tagged).expect("test assertion");
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let audio_asset = plugin
+            .assets_mut()
+            .import_audio(path.to_str().expect("test assertion"))
+            .expect("test assertion");
+        let _ = std::fs::remove_file(&path);
+        let video_asset = plugin.assets_mut().import_video("camera.mov").expect("test assertion");
+        let audio = plugin.assets().audio_clip(audio_asset).expect("test assertion");
+        assert_eq!(audio.metadata.title, "12 / 3");
+        assert_eq!(audio.start_timecode(), Some(TimePosition::from_ms(36_005_000)));
+
+        // Camera rolled at 10:00:00, so sound belongs 5 s into the clip
+        plugin.assets_mut().video_clip_mut(video_asset).expect("test assertion").start_timecode =
+            Some(TimePosition::from_ms(36_000_000));
+        let timeline = plugin.timeline_mut();
+        let (video, sound) = (timeline.tracks()[0].id, timeline.tracks()[1].id);
+        let clip = |id, source, start| {
+            TimelineClip::new(
+                id,
+                source,
+                TimePosition::from_secs(start),
+                TimePosition::from_secs(20),
+            )
+        };
+        timeline.add_clip(video, clip(1, video_asset, 2)).expect("test assertion");
+        timeline.add_clip(sound, clip(2, audio_asset, 0)).expect("test assertion");
+        plugin.sync_by_timecode(1, 2).expect("test assertion");
+        let start = plugin.timeline().get_track(sound).map(|t| t.clips[0].start.ms);
+        assert_eq!(start, Some(7_000));
+        assert!(plugin.sync_by_timecode(2, 1).is_err());
+    }
+
     #[test]
     fn test_asset_import() {
         let mut plugin = VideoEditorPlugin::default();
         let result = plugin.assets_mut().import_video("test.mp4");
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_editor_commands() {
+        use crate::types::timeline::TimelineClip;
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let video = plugin.timeline().tracks()[0].id;
+        plugin
+            .timeline_mut()
+            .add_clip(
+                video,
+                TimelineClip::new(1, 1, TimePosition::from_ms(0), TimePosition::from_secs(4)),
+            )
+            .expect("test assertion");
+
+        // Play via shortcut, host-handled commands report back
+        assert!(plugin.execute_shortcut("Space").expect("test assertion"));
+        assert!(plugin.preview().is_playing());
+        assert!(!plugin.execute_command("video_export").expect("test assertion"));
+        assert!(!plugin.execute_shortcut("Ctrl+Alt+Q").expect("test assertion"));
+
+        // Cut at the playhead
+        plugin.preview_mut().seek(TimePosition::from_secs(1));
+        assert!(plugin.execute_command("video_cut").expect("test assertion"));
+        let clips = &plugin.timeline().tracks()[0].clips;
+        assert_eq!(clips.len(), 2);
+        assert_eq!(clips[0].duration.ms, 1000);
+        assert_eq!(clips[1].start.ms, 1000);
+        assert_eq!(clips[1].in_point.ms, 1000);
+
+        // Marker at the playhead
+        plugin.execute_shortcut("m").expect("test assertion");
+        assert_eq!(plugin.markers().markers().next().map(|m| m.position().ms), Some(1000));
+
+        // Nudge the second piece one frame right (30 fps)
+        let second = plugin.timeline().tracks()[0].clips[1].id;
+        plugin.select_clips(&[second]);
+        plugin.execute_shortcut("Alt+Right").expect("test assertion");
+        assert_eq!(plugin.timeline().tracks()[0].clips[1].start.ms, 1033);
+
+        // Remapped shortcut
+        plugin.commands_mut().set_shortcut("video_ripple_delete", Some("Ctrl+D".into()));
+        assert!(!plugin.execute_shortcut("Shift+Delete").expect("test assertion"));
+        assert!(plugin.execute_shortcut("Ctrl+D").expect("test assertion"));
+        assert_eq!(plugin.timeline().tracks()[0].clips.len(), 1);
+        assert!(plugin.selected_clips().is_empty());
+    }
+
+    #[test]
+    fn test_editor_events() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::types::timeline::TimelineClip;
+
+        let mut plugin = VideoEditorPlugin::default();
+        let (_, receiver) = plugin.subscribe_channel();
+        let saved = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&saved);
+        plugin.subscribe(Arc::new(move |event: &EditorEvent| {
+            if let EditorEvent::ProjectSaved { path } = event {
+                sink.lock().expect("test assertion").push(path.clone());
+            }
+        }));
+
+        // Managers replaced by a new project stay connected
+        plugin.new_project();
+        let video = plugin.timeline().tracks()[0].id;
+        let clip = TimelineClip::new(1, 1, TimePosition::from_ms(0), TimePosition::from_secs(2));
+        plugin.timeline_mut().add_clip(video, clip).expect("test assertion");
+        plugin.select_clips(&[1]);
+        plugin.nudge_selection(1).expect("test assertion");
+        plugin.add_marker_at_playhead();
+        let effect = plugin.effects_mut().add_effect(crate::implementation::EffectType::Blur);
+        plugin.effects_mut().set_parameter(effect, "radius", 4.0).expect("test assertion");
+        plugin.projects_mut().new_project("Demo").expect("test assertion");
+        plugin.projects_mut().mark_project_saved("/tmp/demo.evproj").expect("test assertion");
+
+        let events: Vec<EditorEvent> = receiver.try_iter().collect();
+        assert!(events.contains(&EditorEvent::TrackAdded { track_id: video }));
+        assert!(events.contains(&EditorEvent::ClipAdded { track_id: video, clip_id: 1 }));
+        assert!(events.contains(&EditorEvent::ClipMoved {
+            clip_id: 1,
+            from:    TimePosition::from_ms(0),
+            to:      TimePosition::from_ms(33),
+        }));
+        assert!(events.contains(&EditorEvent::MarkerAdded { marker_id: 1 }));
+        assert!(events.contains(&EditorEvent::EffectParamChanged {
+            effect_id: effect,
+            name:      "radius".into(),
+            value:     4.0,
+        }));
+        assert!(events.contains(&EditorEvent::ProjectCreated { name: "Demo".into() }));
+        assert_eq!(*saved.lock().expect("test assertion"), ["/tmp/demo.evproj"]);
+    }
+
+    #[test]
+    fn test_script_batch() {
+        use crate::{implementation::OperationOutput, types::timeline::TimelineClip};
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let video = plugin.timeline().tracks()[0].id;
+        for id in 1..=3 {
+            let start = TimePosition::from_secs((id - 1) * 2);
+            let clip = TimelineClip::new(id, id, start, TimePosition::from_secs(2));
+            plugin.timeline_mut().add_clip(video, clip).expect("test assertion");
+        }
+
+        // A 10-frame crossfade at every cut (30 fps)
+        let ops = vec![
+            ScriptOperation::CrossfadeAllCuts { track_id: None, frames: 10 },
+            ScriptOperation::AddMarker { at: TimePosition::from_secs(1), name: "Intro".into() },
+            ScriptOperation::SplitClip { clip_id: 3, at: TimePosition::from_secs(5) },
+        ];
+        let bytes = ScriptOperation::encode_batch(&ops);
+        let result =
+            plugin.run_batch(ScriptOperation::decode_batch(&bytes).expect("test assertion"));
+        assert!(result.is_ok());
+        let transitions = plugin.transitions().transitions_for_track(video);
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(transitions[0].start_time.ms, 2000 - 166);
+        assert_eq!(transitions[0].transition.duration().ms, 333);
+        assert_eq!(plugin.markers().markers().next().map(|m| m.name()), Some("Intro"));
+        assert_eq!(result.outputs[2], OperationOutput::Clip(4));
+
+        // Re-running skips existing cuts but crossfades the new split; the
+        // batch stops at the missing clip
+        let result = plugin.run_batch(vec![
+            ScriptOperation::CrossfadeAllCuts { track_id: Some(video), frames: 10 },
+            ScriptOperation::RippleDelete { clip_id: 99 },
+            ScriptOperation::RippleDelete { clip_id: 1 },
+        ]);
+        assert_eq!(result.outputs, [OperationOutput::Transitions(vec![3])]);
+        assert_eq!(result.error.as_ref().map(|(index, _)| *index), Some(1));
+        assert_eq!(plugin.timeline().tracks()[0].clips.len(), 4);
+    }
+
+    #[test]
+    fn test_render_frame_deterministic() {
+        use crate::{
+            decoder::DecodedFrame,
+            implementation::{GeneratorSource, RenderDeterminism, TestPattern},
+            types::{Resolution, timeline::TimelineClip},
+        };
+
+        let config = VideoEditorConfig {
+            resolution: Resolution { width: 64, height: 36 },
+            determinism: RenderDeterminism::strict(7),
+            ..VideoEditorConfig::default()
+        };
+        let mut plugin = VideoEditorPlugin::new(config);
+        assert_eq!(plugin.effects().execution_mode(), ExecutionMode::ForceCpu);
+        plugin.new_project();
+        let bars =
+            plugin.assets_mut().add_generator(GeneratorSource::Pattern(TestPattern::SmpteBars));
+        let media = plugin.assets_mut().import_video("overlay.mov").expect("test assertion");
+        let v1 = plugin.timeline().tracks()[0].id;
+        let v2 = plugin.timeline_mut().add_track("V2", TrackType::Video);
+        let clip = |id, source| {
+            TimelineClip::new(id, source, TimePosition::default(), TimePosition::from_secs(2))
+        };
+        plugin.timeline_mut().add_clip(v1, clip(1, bars)).expect("test assertion");
+        plugin.timeline_mut().add_clip(v2, clip(2, media)).expect("test assertion");
+
+        let at = TimePosition::from_ms(500);
+        assert!(plugin.render_frame_to_buffer(at).is_err());
+
+        // Half-transparent grey over the bars leaves fractional values to dither
+        plugin.set_frame_source(std::sync::Arc::new(|_, time| {
+            Ok(DecodedFrame {
+                index:    0,
+                pts_ms:   time.ms,
+                keyframe: true,
+                width:    8,
+                height:   8,
+                data:     [100, 100, 100, 127].repeat(64).into(),
+            })
+        }));
+        let golden = plugin.render_frame_to_buffer(at).expect("test assertion");
+        assert_eq!(golden.len(), 64 * 36 * 4);
+        assert_eq!(plugin.render_frame_to_buffer(at).expect("test assertion"), golden);
+
+        // Red of the -I patch (bottom left) is 0 under the overlay: 100 * 127/255
+        let bottom_left = (35 * 64) * 4;
+        assert!((49..=51).contains(&golden[bottom_left]));
+        assert_eq!(golden[bottom_left + 3], 255);
+    }
+
+    #[test]
+    fn test_interlaced_clip_on_progressive_project() {
+        use crate::{
+            decoder::DecodedFrame,
+            types::{FieldOrder, Resolution, timeline::TimelineClip},
+        };
+
+        let config = VideoEditorConfig {
+            resolution: Resolution { width: 16, height: 8 },
+            ..VideoEditorConfig::default()
+        };
+        let mut plugin = VideoEditorPlugin::new(config);
+        plugin.new_project();
+        let media = plugin.assets_mut().import_video("archive.mov").expect("test assertion");
+        let v1 = plugin.timeline().tracks()[0].id;
+        let clip = TimelineClip::new(1, media, TimePosition::default(), TimePosition::from_secs(2));
+        plugin.timeline_mut().add_clip(v1, clip).expect("test assertion");
+
+        // Fields a frame apart: even lines white, odd lines black
+        plugin.set_frame_source(std::sync::Arc::new(|_, time| {
+            let data: Vec<u8> =
+                (0..8).flat_map(|y| if y % 2 == 0 { [255; 16 * 4] } else { [0; 16 * 4] }).collect();
+            Ok(DecodedFrame {
+                index:    0,
+                pts_ms:   time.ms,
+                keyframe: true,
+                width:    16,
+                height:   8,
+                data:     data.into(),
+            })
+        }));
+        let at = TimePosition::from_ms(500);
+        let row = |frame: &[u8], y: usize| frame[y * 16 * 4];
+
+        // Progressive media is shown as decoded
+        let frame = plugin.render_frame_to_buffer(at).expect("test assertion");
+        assert_eq!((row(&frame, 2), row(&frame, 3)), (255, 0));
+
+        // Interlaced media keeps the top field and fills in the bottom one
+        plugin.assets_mut().video_clip_mut(media).expect("test assertion").field_order =
+            FieldOrder::TopFieldFirst;
+        let frame = plugin.render_frame_to_buffer(at).expect("test assertion");
+        assert_eq!((row(&frame, 2), row(&frame, 3)), (255, 255));
+        assert_eq!(frame[3 * 16 * 4 + 3], 255);
+
+        // An interlaced project takes the fields as they are
+        plugin.config.field_order = FieldOrder::TopFieldFirst;
+        let frame = plugin.render_frame_to_buffer(at).expect("test assertion");
+        assert_eq!(row(&frame, 3), 0);
+    }
+
+    #[test]
+    fn test_rotated_phone_clip() {
+        use crate::{
+            converter::ConversionStats,
+            decoder::DecodedFrame,
+            types::{Resolution, Rotation, timeline::TimelineClip},
+        };
+
+        let config = VideoEditorConfig {
+            resolution: Resolution { width: 8, height: 16 },
+            ..VideoEditorConfig::default()
+        };
+        let mut plugin = VideoEditorPlugin::new(config);
+        plugin.new_project();
+        let media = plugin.assets_mut().import_video("portrait.mp4").expect("test assertion");
+        let v1 = plugin.timeline().tracks()[0].id;
+        let clip = TimelineClip::new(1, media, TimePosition::default(), TimePosition::from_secs(2));
+        plugin.timeline_mut().add_clip(v1, clip).expect("test assertion");
+
+        // Stored sideways: left half white, right half black
+        plugin.set_frame_source(std::sync::Arc::new(|_, time| {
+            let data: Vec<u8> =
+                (0..8 * 16).flat_map(|i| if i % 16 < 8 { [255; 4] } else { [0; 4] }).collect();
+            Ok(DecodedFrame {
+                index:    0,
+                pts_ms:   time.ms,
+                keyframe: true,
+                width:    16,
+                height:   8,
+                data:     data.into(),
+            })
+        }));
+        let at = TimePosition::from_ms(500);
+        let pixel = |frame: &[u8], x: usize, y: usize| frame[(y * 8 + x) * 4];
+
+        // Turned clockwise, the stored left half becomes the top
+        let stats = ConversionStats { rotation: Rotation::Clockwise90, ..Default::default() };
+        assert!(plugin.assets_mut().apply_conversion(media, &stats));
+        let frame = plugin.render_frame_to_buffer(at).expect("test assertion");
+        assert_eq!((pixel(&frame, 7, 0), pixel(&frame, 0, 15)), (255, 0));
+
+        // Overriding a wrong tag shows the media as stored
+        assert!(plugin.assets_mut().set_rotation_override(media, Some(Rotation::None)));
+        let frame = plugin.render_frame_to_buffer(at).expect("test assertion");
+        assert_eq!((pixel(&frame, 7, 0), pixel(&frame, 0, 15)), (0, 255));
+    }
+
+    #[test]
+    fn test_spherical_clip_preview() {
+        use crate::{
+            decoder::DecodedFrame,
+            implementation::SphericalView,
+            types::{ProjectionMode, Resolution, timeline::TimelineClip},
+        };
+
+        let config = VideoEditorConfig {
+            resolution: Resolution { width: 9, height: 9 },
+            ..VideoEditorConfig::default()
+        };
+        let mut plugin = VideoEditorPlugin::new(config);
+        plugin.new_project();
+        let media = plugin.assets_mut().import_video("sphere.mp4").expect("test assertion");
+        plugin.assets_mut().video_clip_mut(media).expect("test assertion").projection =
+            ProjectionMode::Equirectangular;
+        let v1 = plugin.timeline().tracks()[0].id;
+        let clip = TimelineClip::new(1, media, TimePosition::default(), TimePosition::from_secs(2));
+        plugin.timeline_mut().add_clip(v1, clip).expect("test assertion");
+
+        // 64x32 equirectangular frame, red counting columns
+        plugin.set_frame_source(std::sync::Arc::new(|_, time| {
+            let data: Vec<u8> =
+                (0..32u8).flat_map(|_| (0..64u8).flat_map(|x| [x * 4, 0, 0, 255])).collect();
+            Ok(DecodedFrame {
+                index:    0,
+                pts_ms:   time.ms,
+                keyframe: true,
+                width:    64,
+                height:   32,
+                data:     data.into(),
+            })
+        }));
+        let at = TimePosition::from_ms(500);
+        let center = |frame: &[u8]| frame[(4 * 9 + 4) * 4];
+
+        // Preview looks at the middle of the sphere, then where it's keyframed
+        let frame = plugin.render_frame_to_buffer(at).expect("test assertion");
+        assert_eq!(center(&frame), 126);
+        SphericalView { yaw: 90.0, ..SphericalView::FORWARD }
+            .keyframe(plugin.animation_mut(), 1, TimePosition::default())
+            .expect("test assertion");
+        let frame = plugin.render_frame_to_buffer(at).expect("test assertion");
+        assert_eq!(center(&frame), 190);
+
+        // Spherical exports get the whole frame, scaled
+        let frame =
+            plugin.render_frame_equirectangular(at, plugin.frame_pool()).expect("test assertion");
+        assert_eq!(center(&frame), 112);
+    }
+
+    #[test]
+    fn test_offline_media_placeholder() {
+        use crate::types::{Resolution, timeline::TimelineClip};
+
+        let root = std::env::temp_dir().join(format!("evep_offline_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("shoot")).expect("test assertion");
+        std::fs::create_dir_all(root.join("backup")).expect("test assertion");
+        let take = root.join("shoot/take.mov");
+        std::fs::write(&take, b"take one").expect("test assertion");
+
+        let config = VideoEditorConfig {
+            resolution: Resolution { width: 240, height: 135 },
+            ..VideoEditorConfig::default()
+        };
+        let mut plugin = VideoEditorPlugin::new(config);
+        assert!(plugin.relink_media(&[&root]).is_none());
+        plugin.new_project();
+        let project = plugin.projects_mut().new_project("Offline").expect("test assertion");
+        project.add_media(&take.to_string_lossy()).expect("test assertion");
+        let asset =
+            plugin.assets_mut().import_video(&take.to_string_lossy()).expect("test assertion");
+        let v1 = plugin.timeline().tracks()[0].id;
+        let clip = TimelineClip::new(1, asset, TimePosition::default(), TimePosition::from_secs(4));
+        plugin.timeline_mut().add_clip(v1, clip).expect("test assertion");
+        assert!(plugin.refresh_offline_media().is_empty());
+
+        // Missing media renders a slate instead of failing
+        let moved = root.join("backup/take.mov");
+        std::fs::rename(&take, &moved).expect("test assertion");
+        assert_eq!(plugin.refresh_offline_media(), [asset]);
+        assert!(plugin.assets().is_offline(asset));
+        let at = TimePosition::from_ms(500);
+        let slate = plugin.render_frame_to_buffer(at).expect("test assertion");
+        assert_eq!(slate[..4], [96, 16, 16, 255]);
+        let center = (67 * 240 + 120) * 4;
+        assert_ne!(slate[center..center + 4], [96, 16, 16, 255]);
+
+        // Offline clips stay editable
+        let end = TimePosition::from_secs(3);
+        plugin.timeline_mut().trim_clip_end(1, end).expect("test assertion");
+        assert_eq!(plugin.timeline().tracks()[0].clips[0].end(), end);
+
+        // Relinking brings the asset back; it now needs a frame source again
+        let report = plugin.relink_media(&[root.join("backup")]).expect("test assertion");
+        assert_eq!(report.relinked.len(), 1);
+        assert!(!plugin.assets().is_offline(asset));
+        let video = plugin.assets().video_clip(asset).expect("test assertion");
+        assert_eq!(video.path, moved.to_string_lossy());
+        assert!(plugin.render_frame_to_buffer(at).is_err());
+        assert!(plugin.refresh_offline_media().is_empty());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_track_templates() {
+        use crate::implementation::{EffectType, TrackLayout};
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let dialogue = plugin.timeline_mut().add_track("Dialogue", TrackType::Audio);
+        let grade = plugin.timeline_mut().add_track("Grade", TrackType::Effect);
+        let effect = plugin.effects_mut().add_effect(EffectType::ColorCorrection);
+        plugin.effects_mut().set_parameter(effect, "saturation", 1.2).expect("test assertion");
+        let track = plugin.timeline_mut().get_track_mut(grade).expect("test assertion");
+        track.color = Some([0.2, 0.4, 0.9, 1.0]);
+        track.height = 96;
+        track.effect_ids.push(effect);
+
+        plugin.save_track_template(grade).expect("test assertion");
+        let copy = plugin.apply_track_template("Grade").expect("test assertion");
+        let track = plugin.timeline().get_track(copy).expect("test assertion");
+        assert_eq!((track.height, track.track_type), (96, TrackType::Effect));
+        assert_eq!(track.color, Some([0.2, 0.4, 0.9, 1.0]));
+        let copied = plugin.effects().effect(track.effect_ids[0]).expect("test assertion");
+        assert_ne!(copied.id, effect);
+        assert_eq!(copied.parameter("saturation"), Some(1.2));
+        assert!(plugin.apply_track_template("Music").is_err());
+
+        plugin.timeline_mut().set_track_muted(dialogue, true);
+        plugin.save_track_layout("Interview");
+        let layout = plugin.config().track_layouts[0].clone();
+        assert_eq!(layout.tracks.len(), 5);
+        assert_eq!(TrackLayout::from_bytes(&layout.to_bytes()), Some(layout));
+
+        plugin.new_project();
+        let ids = plugin.apply_track_layout("Interview").expect("test assertion");
+        let tracks = plugin.timeline().tracks();
+        assert_eq!(tracks.len(), 7);
+        assert!(tracks.iter().any(|t| t.id == ids[2] && t.muted && t.name == "Dialogue"));
+    }
+
+    #[test]
+    fn test_channel_breakout() {
+        use crate::types::{ChannelMap, timeline::TimelineClip};
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let a1 = plugin.timeline().tracks()[1].id;
+        let timeline = plugin.timeline_mut();
+        let clip = TimelineClip::new(1, 7, TimePosition::from_secs(1), TimePosition::from_secs(4));
+        timeline.add_clip(a1, clip).expect("test assertion");
+        let a2 = timeline.add_track("Audio 2", TrackType::Audio);
+        let busy = TimelineClip::new(2, 8, TimePosition::default(), TimePosition::from_secs(2));
+        timeline.add_clip(a2, busy).expect("test assertion");
+        let a3 = timeline.add_track("Audio 3", TrackType::Audio);
+
+        // A failed break-out leaves the clip's routing alone
+        assert!(timeline.break_out_channels(1, 1).is_err());
+        let routing = |timeline: &TimelineManager| {
+            timeline.get_track(a1).map(|t| t.clips[0].channel_map.clone())
+        };
+        assert_eq!(routing(timeline), Some(ChannelMap::default()));
+        let ids = timeline.break_out_channels(1, 3).expect("test assertion");
+        assert_eq!(ids, [1, 3, 4]);
+        assert_eq!(routing(timeline), Some(ChannelMap::mono(0)));
+        assert_eq!(timeline.tracks().len(), 5);
+        assert!(timeline.get_track(a3).is_some_and(|t| t.clips.iter().any(|c| c.id == 3)));
+        let clip = |id| {
+            timeline
+                .active_clips_at(TimePosition::from_secs(3))
+                .into_iter()
+                .find(|(_, c)| c.id == id)
+        };
+        let (track, mono) = clip(4).expect("test assertion");
+        assert_eq!(mono.channel_map, ChannelMap::mono(2));
+        assert!(timeline.get_track(track).is_some_and(|t| t.name == "Audio 1 Ch 3"));
+        assert_eq!(clip(1).map(|(_, c)| c.channel_map.clone()), Some(ChannelMap::mono(0)));
+        assert_eq!(timeline.group_of(3).map(|g| g.clip_ids.len()), Some(3));
+
+        timeline.set_clip_channel_map(1, ChannelMap::mono(1)).expect("test assertion");
+        assert!(timeline.set_clip_channel_map(99, ChannelMap::default()).is_err());
+    }
+
+    #[test]
+    fn test_preset_library() {
+        use crate::{
+            implementation::{
+                EffectType, Preset, PresetLibrary,
+                color_grading::{ColorCurve, ColorGradingNode, Lut3D},
+                transitions::{
+                    TransitionEasing, TransitionParameters, TransitionPreset, TransitionType,
+                    WipeDirection,
+                },
+            },
+            types::timeline::TimelineClip,
+        };
+
+        let dir = std::env::temp_dir().join(format!("evep_presets_{}", std::process::id()));
+        let mut library = PresetLibrary::open(dir.join("user")).expect("test assertion");
+        let mut grade = ColorGradingNode::new("Teal");
+        grade.exposure = 0.5;
+        grade.curves.master = ColorCurve::s_curve(0.8);
+        grade.lut = Lut3D::from_data("Cool", 2, Lut3D::identity(2).data().to_vec());
+        let mut teal = Preset::new("Teal & Orange", "Film");
+        teal.tags = vec!["warm".into(), "Cinematic".into()];
+        teal.grade = Some(grade);
+        teal.effects = vec![(EffectType::Blur, vec![("radius".into(), 2.5)])];
+        let mut wipe = Preset::new("Soft Wipe", "Transitions");
+        wipe.transition = Some(TransitionPreset {
+            name:            "Soft Wipe".into(),
+            transition_type: TransitionType::Wipe(WipeDirection::CenterOut),
+            duration:        TimePosition::from_ms(400),
+            easing:          TransitionEasing::CubicInOut,
+            parameters:      TransitionParameters { edge_softness: 0.3, ..Default::default() },
+        });
+        library.save(wipe).expect("test assertion");
+        library.save(teal).expect("test assertion");
+
+        let library = PresetLibrary::open(dir.join("user")).expect("test assertion");
+        assert_eq!(library.categories(), ["Film", "Transitions"]);
+        assert_eq!(library.tagged("cinematic").len(), 1);
+        let loaded = library.get("Teal & Orange").expect("test assertion");
+        let grade = loaded.grade.as_ref().expect("test assertion");
+        assert_eq!(grade.exposure, 0.5);
+        assert_eq!(grade.curves.master.points().len(), 5);
+        assert_eq!(grade.lut.as_ref().map(|l| (l.name(), l.size())), Some(("Cool", 2)));
+        let transition = library.in_category("Transitions")[0].transition.as_ref();
+        assert_eq!(
+            transition.map(|t| (t.transition_type, t.easing)),
+            Some((TransitionType::Wipe(WipeDirection::CenterOut), TransitionEasing::CubicInOut))
+        );
+
+        let pack = dir.join("looks.evpack");
+        library.export_pack(&["Teal & Orange"], &pack).expect("test assertion");
+        assert!(library.export_pack(&["Missing"], &pack).is_err());
+        let mut shared = PresetLibrary::open(dir.join("shared")).expect("test assertion");
+        assert_eq!(shared.import_pack(&pack).expect("test assertion"), ["Teal & Orange"]);
+        assert!(shared.import_pack(dir.join("user/Soft_Wipe.evpreset")).is_err());
+        assert!(shared.remove("Teal & Orange").expect("test assertion"));
+        assert!(
+            PresetLibrary::open(dir.join("shared")).expect("test assertion").presets().is_empty()
+        );
+
+        let mut plugin = VideoEditorPlugin::default();
+        plugin.new_project();
+        let v1 = plugin.timeline().tracks()[0].id;
+        for (id, start) in [(1, 0), (2, 2)] {
+            let clip = TimelineClip::new(
+                id,
+                7,
+                TimePosition::from_secs(start),
+                TimePosition::from_secs(2),
+            );
+            plugin.timeline_mut().add_clip(v1, clip).expect("test assertion");
+        }
+        plugin.apply_preset(&library, "Teal & Orange", 1).expect("test assertion");
+        plugin.apply_preset(&library, "Soft Wipe", 1).expect("test assertion");
+        assert_eq!(plugin.clip_grade(1).map(|g| g.name.as_str()), Some("Teal"));
+        let placed = plugin.transitions().transitions_for_track(v1);
+        assert_eq!((placed.len(), placed[0].clip_b_id), (1, 2));
+        assert_eq!(placed[0].start_time, TimePosition::from_ms(1800));
+        assert!(plugin.apply_preset(&library, "Soft Wipe", 2).is_err());
+        assert!(plugin.apply_preset(&library, "Bleach", 1).is_err());
+
+        let captured = plugin.capture_preset(1, "Copy", "Film").expect("test assertion");
+        assert_eq!(captured.effects, [(EffectType::Blur, vec![("radius".into(), 2.5)])]);
+        assert_eq!(captured.grade.map(|g| g.exposure), Some(0.5));
+        assert_eq!(plugin.effects().effects().len(), 1);
+
+        std::fs::remove_dir_all(&dir).expect("test assertion");
+    }
+
+    #[test]
+    fn test_output_routing() {
+        use crate::{AudioBusId, AudioChannelConfig, HardwareOutput};
+
+        let mut plugin = VideoEditorPlugin::default();
+        assert!(plugin.output_routing().assignments().is_empty());
+
+        // A 5.1 mix on the stereo cue feed and discretely over SDI
+        let (mix, stems) = (AudioBusId::new(0), AudioBusId::new(1));
+        let routing = plugin.output_routing_mut();
+        routing.assign(mix, HardwareOutput::Cue, AudioChannelConfig::Surround51);
+        routing.set_layout(HardwareOutput::SdiGroup(1), AudioChannelConfig::Surround51);
+        routing.assign(mix, HardwareOutput::SdiGroup(1), AudioChannelConfig::Surround51);
+        routing.assign(stems, HardwareOutput::Main, AudioChannelConfig::Stereo);
+        let outputs: Vec<_> = plugin.output_routing().outputs_of(mix).collect();
+        assert_eq!(outputs, [HardwareOutput::Cue, HardwareOutput::SdiGroup(1)]);
+
+        let samples = [0.5; 6];
+        let rendered = plugin.output_routing().render(&[(mix, &samples)], 1);
+        assert_eq!(rendered.len(), 3);
+        assert_eq!(rendered[0].1.len(), 2);
+        assert_eq!(rendered[1].1, samples);
+        // Stems have no audio this block, so main plays silence
+        assert_eq!(rendered[2], (HardwareOutput::Main, vec![0.0; 2]));
+    }
 }
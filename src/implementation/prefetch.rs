@@ -0,0 +1,315 @@
+//! Frame prefetching and read-ahead for smooth playback.
+//!
+//! The prefetcher decodes frames ahead of the playhead on worker threads so
+//! they are in the [`FrameCache`] before playback reaches them. The playhead
+//! frame is always decoded first. Seeking or scrubbing cancels outstanding
+//! work: queued requests are dropped and frames still being decoded for an
+//! old position are discarded when they arrive.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex, mpsc},
+    thread::JoinHandle,
+};
+
+use super::preview_manager::{FrameCache, PlaybackSpeed};
+use crate::{decoder::DecodedFrame, errors::VideoEditorResult, types::Resolution};
+
+/// Decodes a single frame for the prefetcher. Called from worker threads.
+pub type FrameLoader = Arc<dyn Fn(u64) -> VideoEditorResult<DecodedFrame> + Send + Sync>;
+
+/// Work shared between the prefetcher and its workers.
+#[derive(Default)]
+struct PrefetchQueue {
+    /// Frames waiting for a worker, most urgent first.
+    pending:    VecDeque<u64>,
+    /// Frames a worker is decoding, with the generation they belong to.
+    in_flight:  Vec<(u64, u64)>,
+    /// Bumped on cancel; results from older generations are dropped.
+    generation: u64,
+    /// Set when the prefetcher is dropped.
+    shutdown:   bool,
+}
+
+/// A decoded frame tagged with the generation it was requested in.
+struct PrefetchResult {
+    generation: u64,
+    frame:      u64,
+    decoded:    VideoEditorResult<DecodedFrame>,
+}
+
+/// Counters for prefetch activity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrefetchStats {
+    /// Frames decoded and inserted into the cache.
+    pub frames_cached:   u64,
+    /// Frames discarded because a seek cancelled them.
+    pub frames_stale:    u64,
+    /// Frames that failed to decode.
+    pub decode_failures: u64,
+    /// Times outstanding work was cancelled.
+    pub cancellations:   u64,
+}
+
+/// Read-ahead scheduler decoding frames on worker threads.
+pub struct FramePrefetcher {
+    queue:     Arc<(Mutex<PrefetchQueue>, Condvar)>,
+    results:   mpsc::Receiver<PrefetchResult>,
+    workers:   Vec<JoinHandle<()>>,
+    lookahead: usize,
+    stats:     PrefetchStats,
+}
+
+impl FramePrefetcher {
+    /// Default number of frames decoded ahead of the playhead.
+    pub const DEFAULT_LOOKAHEAD: usize = 12;
+
+    /// Creates a prefetcher with `workers` decode threads.
+    #[must_use]
+    pub fn new(loader: FrameLoader, workers: usize, lookahead: usize) -> Self {
+        let queue = Arc::new((Mutex::new(PrefetchQueue::default()), Condvar::new()));
+        let (sender, results) = mpsc::channel();
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                let loader = Arc::clone(&loader);
+                let sender = sender.clone();
+                std::thread::spawn(move || Self::worker(&queue, &loader, &sender))
+            })
+            .collect();
+
+        Self { queue, results, workers, lookahead, stats: PrefetchStats::default() }
+    }
+
+    fn worker(
+        queue: &(Mutex<PrefetchQueue>, Condvar), loader: &FrameLoader,
+        sender: &mpsc::Sender<PrefetchResult>,
+    ) {
+        let (lock, ready) = queue;
+        loop {
+            let (frame, generation) = {
+                let Ok(mut state) = lock.lock() else {
+                    return;
+                };
+                loop {
+                    if state.shutdown {
+                        return;
+                    }
+                    if let Some(frame) = state.pending.pop_front() {
+                        let generation = state.generation;
+                        state.in_flight.push((frame, generation));
+                        break (frame, generation);
+                    }
+                    state = match ready.wait(state) {
+                        Ok(state) => state,
+                        Err(_) => return,
+                    };
+                }
+            };
+
+            let decoded = loader(frame);
+            if let Ok(mut state) = lock.lock() {
+                state.in_flight.retain(|&(f, g)| f != frame || g != generation);
+            }
+            if sender.send(PrefetchResult { generation, frame, decoded }).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Returns the number of frames decoded ahead of the playhead.
+    #[must_use]
+    pub const fn lookahead(&self) -> usize {
+        self.lookahead
+    }
+
+    /// Sets the number of frames decoded ahead of the playhead.
+    pub fn set_lookahead(&mut self, lookahead: usize) {
+        self.lookahead = lookahead;
+    }
+
+    /// Returns prefetch counters.
+    #[must_use]
+    pub const fn stats(&self) -> PrefetchStats {
+        self.stats
+    }
+
+    /// Returns the frames to decode for a playhead position: the playhead
+    /// frame, then `lookahead` frames in the playback direction.
+    ///
+    /// At shuttle speeds frames are spaced by the speed, matching the
+    /// frames playback will actually show.
+    #[must_use]
+    pub fn plan(&self, playhead: u64, speed: PlaybackSpeed, total_frames: u64) -> Vec<u64> {
+        let step = speed.abs().round().max(1.0) as u64;
+        let mut frames = vec![playhead.min(total_frames.saturating_sub(1))];
+        let mut frame = frames[0];
+        for _ in 0..self.lookahead {
+            let next = if speed.is_reverse() {
+                frame.checked_sub(step)
+            } else {
+                frame.checked_add(step).filter(|&f| f < total_frames)
+            };
+            let Some(next) = next else {
+                break;
+            };
+            frames.push(next);
+            frame = next;
+        }
+        frames
+    }
+
+    /// Queues the read-ahead for a playhead position, skipping cached and
+    /// in-flight frames. Replaces previously queued requests.
+    pub fn schedule(
+        &mut self, playhead: u64, speed: PlaybackSpeed, total_frames: u64, cache: &FrameCache,
+    ) {
+        if total_frames == 0 {
+            return;
+        }
+        let plan = self.plan(playhead, speed, total_frames);
+        let (lock, ready) = &*self.queue;
+        let Ok(mut state) = lock.lock() else {
+            return;
+        };
+        let pending: VecDeque<u64> = plan
+            .into_iter()
+            .filter(|&f| !cache.contains(f) && !state.in_flight.contains(&(f, state.generation)))
+            .collect();
+        state.pending = pending;
+        ready.notify_all();
+    }
+
+    /// Cancels outstanding work, e.g. after a seek or while scrubbing.
+    pub fn cancel(&mut self) {
+        let (lock, _) = &*self.queue;
+        if let Ok(mut state) = lock.lock() {
+            state.pending.clear();
+            state.generation += 1;
+            self.stats.cancellations += 1;
+        }
+    }
+
+    /// Returns the number of frames queued or being decoded.
+    #[must_use]
+    pub fn outstanding(&self) -> usize {
+        let (lock, _) = &*self.queue;
+        lock.lock().map_or(0, |s| s.pending.len() + s.in_flight.len())
+    }
+
+    /// Moves decoded frames into the cache. Returns how many were added.
+    pub fn collect(&mut self, cache: &mut FrameCache) -> usize {
+        let generation = {
+            let (lock, _) = &*self.queue;
+            lock.lock().map_or(u64::MAX, |s| s.generation)
+        };
+
+        let mut added = 0;
+        while let Ok(result) = self.results.try_recv() {
+            if result.generation != generation {
+                self.stats.frames_stale += 1;
+                continue;
+            }
+            match result.decoded {
+                Ok(decoded) => {
+                    let resolution = Resolution::new(decoded.width, decoded.height);
+                    cache.put(result.frame, decoded.data, resolution);
+                    self.stats.frames_cached += 1;
+                    added += 1;
+                },
+                Err(_) => self.stats.decode_failures += 1,
+            }
+        }
+        added
+    }
+}
+
+impl Drop for FramePrefetcher {
+    fn drop(&mut self) {
+        let (lock, ready) = &*self.queue;
+        if let Ok(mut state) = lock.lock() {
+            state.shutdown = true;
+            state.pending.clear();
+        }
+        ready.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    fn loader() -> FrameLoader {
+        Arc::new(|index| {
+            Ok(DecodedFrame {
+                index,
+                pts_ms: index * 40,
+                keyframe: true,
+                width: 2,
+                height: 1,
+                data: vec![index as u8; 8].into(),
+            })
+        })
+    }
+
+    fn collect_until(prefetcher: &mut FramePrefetcher, cache: &mut FrameCache, frames: usize) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut added = 0;
+        while added < frames && Instant::now() < deadline {
+            added += prefetcher.collect(cache);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_plan_follows_direction_and_speed() {
+        let prefetcher = FramePrefetcher::new(loader(), 1, 3);
+        assert_eq!(prefetcher.plan(10, PlaybackSpeed::NORMAL, 100), vec![10, 11, 12, 13]);
+        assert_eq!(prefetcher.plan(10, PlaybackSpeed::new(-4.0), 100), vec![10, 6, 2]);
+        assert_eq!(prefetcher.plan(98, PlaybackSpeed::DOUBLE, 100), vec![98]);
+    }
+
+    #[test]
+    fn test_prefetch_fills_cache() {
+        let mut prefetcher = FramePrefetcher::new(loader(), 2, 4);
+        let mut cache = FrameCache::new(1);
+        prefetcher.schedule(20, PlaybackSpeed::NORMAL, 100, &cache);
+        collect_until(&mut prefetcher, &mut cache, 5);
+
+        assert!((20..25).all(|f| cache.contains(f)));
+        assert_eq!(cache.get(22).map(|f| f.data[0]), Some(22));
+        assert_eq!(prefetcher.stats().frames_cached, 5);
+
+        // Cached frames are not decoded again
+        prefetcher.schedule(21, PlaybackSpeed::NORMAL, 100, &cache);
+        collect_until(&mut prefetcher, &mut cache, 1);
+        assert_eq!(prefetcher.stats().frames_cached, 6);
+    }
+
+    #[test]
+    fn test_cancel_discards_stale_frames() {
+        let slow: FrameLoader = Arc::new(|index| {
+            std::thread::sleep(Duration::from_millis(20));
+            loader()(index)
+        });
+        let mut prefetcher = FramePrefetcher::new(slow, 1, 8);
+        let mut cache = FrameCache::new(1);
+        prefetcher.schedule(0, PlaybackSpeed::NORMAL, 100, &cache);
+        std::thread::sleep(Duration::from_millis(5));
+        prefetcher.cancel();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while prefetcher.outstanding() > 0 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(prefetcher.collect(&mut cache), 0);
+        assert!(prefetcher.stats().frames_stale <= 1);
+        assert!(!cache.contains(8));
+    }
+}
@@ -0,0 +1,621 @@
+//! Grade, effect and transition presets ("looks").
+//!
+//! A [`Preset`] bundles any of a color grade, an effect stack and a
+//! transition configuration under a name, category and tags. A
+//! [`PresetLibrary`] keeps presets in a user directory, one file each, and
+//! moves them between machines as preset packs.
+
+use std::path::{Path, PathBuf};
+
+use essentia_color_types::Color;
+
+use super::{
+    color_grading::{
+        ColorCurve, ColorCurves, ColorGradingNode, ColorWheel, CurvePoint, Lut3D, LutInterpolation,
+        ThreeWayCorrector,
+    },
+    effects::EffectType,
+    track_templates::{read_str, write_str},
+    transitions::{
+        ClockDirection, CubeAxis, IrisShape, PageTurnDirection, PushDirection, SlideDirection,
+        TransitionEasing, TransitionParameters, TransitionPreset, TransitionType, WipeDirection,
+        ZoomType,
+    },
+};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    types::{TimePosition, timeline::ByteReader},
+};
+
+/// Magic number of an encoded preset pack ("EVPP").
+pub const PRESET_PACK_MAGIC: u32 = 0x4556_5050;
+
+/// Named look: a grade, effect stack and/or transition.
+#[derive(Debug, Clone)]
+pub struct Preset {
+    /// Preset name, unique within a library.
+    pub name:       String,
+    /// Category shown in the preset browser.
+    pub category:   String,
+    /// Search tags.
+    pub tags:       Vec<String>,
+    /// Color grade.
+    pub grade:      Option<ColorGradingNode>,
+    /// Effect stack with parameters, in application order.
+    pub effects:    Vec<(EffectType, Vec<(String, f64)>)>,
+    /// Transition into the following clip.
+    pub transition: Option<TransitionPreset>,
+}
+
+impl Preset {
+    /// Serialized format version.
+    const VERSION: u8 = 1;
+
+    /// Creates an empty preset.
+    #[must_use]
+    pub fn new(name: impl Into<String>, category: impl Into<String>) -> Self {
+        Self {
+            name:       name.into(),
+            category:   category.into(),
+            tags:       Vec::new(),
+            grade:      None,
+            effects:    Vec::new(),
+            transition: None,
+        }
+    }
+
+    /// Whether the preset carries a tag, ignoring case.
+    #[must_use]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Converts to bytes for the preset file.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![Self::VERSION];
+        write_str(&mut bytes, &self.name);
+        write_str(&mut bytes, &self.category);
+        bytes.extend_from_slice(&(self.tags.len() as u32).to_le_bytes());
+        for tag in &self.tags {
+            write_str(&mut bytes, tag);
+        }
+        match &self.grade {
+            Some(grade) => {
+                bytes.push(1);
+                write_grade(&mut bytes, grade);
+            },
+            None => bytes.push(0),
+        }
+        bytes.extend_from_slice(&(self.effects.len() as u32).to_le_bytes());
+        for (effect_type, parameters) in &self.effects {
+            let code = EffectType::ALL.iter().position(|t| t == effect_type).unwrap_or(0);
+            bytes.push(code as u8);
+            bytes.extend_from_slice(&(parameters.len() as u32).to_le_bytes());
+            for (name, value) in parameters {
+                write_str(&mut bytes, name);
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        match &self.transition {
+            Some(transition) => {
+                bytes.push(1);
+                write_transition(&mut bytes, transition);
+            },
+            None => bytes.push(0),
+        }
+        bytes
+    }
+
+    /// Parses a preset from bytes.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let reader = &mut ByteReader { bytes, offset: 0 };
+        if reader.u8()? != Self::VERSION {
+            return None;
+        }
+        let name = read_str(reader)?;
+        let category = read_str(reader)?;
+        let tag_count = reader.u32()? as usize;
+        let tags = (0..tag_count).map(|_| read_str(reader)).collect::<Option<Vec<_>>>()?;
+        let grade = match reader.u8()? {
+            0 => None,
+            _ => Some(read_grade(reader)?),
+        };
+        let effect_count = reader.u32()? as usize;
+        let effects = (0..effect_count)
+            .map(|_| {
+                let effect_type = *EffectType::ALL.get(usize::from(reader.u8()?))?;
+                let count = reader.u32()? as usize;
+                let parameters = (0..count)
+                    .map(|_| Some((read_str(reader)?, f64::from_bits(reader.u64()?))))
+                    .collect::<Option<Vec<_>>>()?;
+                Some((effect_type, parameters))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let transition = match reader.u8()? {
+            0 => None,
+            _ => Some(read_transition(reader)?),
+        };
+
+        Some(Self { name, category, tags, grade, effects, transition })
+    }
+
+    /// Encodes presets as a preset pack.
+    #[must_use]
+    pub fn encode_pack(presets: &[&Self]) -> Vec<u8> {
+        let mut bytes = PRESET_PACK_MAGIC.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&(presets.len() as u32).to_le_bytes());
+        for preset in presets {
+            let preset = preset.to_bytes();
+            bytes.extend_from_slice(&(preset.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&preset);
+        }
+        bytes
+    }
+
+    /// Decodes a pack written by [`Self::encode_pack`].
+    #[must_use]
+    pub fn decode_pack(bytes: &[u8]) -> Option<Vec<Self>> {
+        let mut reader = ByteReader { bytes, offset: 0 };
+        if reader.u32()? != PRESET_PACK_MAGIC {
+            return None;
+        }
+        let count = reader.u32()? as usize;
+        (0..count)
+            .map(|_| {
+                let len = reader.u32()? as usize;
+                Self::from_bytes(reader.take(len)?)
+            })
+            .collect()
+    }
+}
+
+/// Presets stored in a user directory.
+///
+/// Each preset is a `.evpreset` file named after the preset, with
+/// characters other than letters, digits, `-` and `_` replaced by `_`.
+#[derive(Debug, Clone)]
+pub struct PresetLibrary {
+    directory: PathBuf,
+    presets:   Vec<Preset>,
+}
+
+impl PresetLibrary {
+    /// Extension of preset files.
+    pub const EXTENSION: &'static str = "evpreset";
+
+    /// Opens the library in `directory`, creating the directory if needed.
+    /// Files that aren't valid presets are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Io` if the directory can't be created or
+    /// listed.
+    pub fn open(directory: impl Into<PathBuf>) -> VideoEditorResult<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory).map_err(|e| VideoEditorError::io(&directory, e))?;
+        let entries =
+            std::fs::read_dir(&directory).map_err(|e| VideoEditorError::io(&directory, e))?;
+
+        let mut presets: Vec<Preset> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == Self::EXTENSION))
+            .filter_map(|path| Preset::from_bytes(&std::fs::read(path).ok()?))
+            .collect();
+        presets.sort_by(|a, b| (&a.category, &a.name).cmp(&(&b.category, &b.name)));
+        Ok(Self { directory, presets })
+    }
+
+    /// Returns the library directory.
+    #[must_use]
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns all presets, ordered by category and name.
+    #[must_use]
+    pub fn presets(&self) -> &[Preset] {
+        &self.presets
+    }
+
+    /// Gets a preset by name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|p| p.name == name)
+    }
+
+    /// Returns the category names in order, without duplicates.
+    #[must_use]
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = self.presets.iter().map(|p| p.category.as_str()).collect();
+        categories.dedup();
+        categories
+    }
+
+    /// Gets the presets of a category.
+    #[must_use]
+    pub fn in_category(&self, category: &str) -> Vec<&Preset> {
+        self.presets.iter().filter(|p| p.category == category).collect()
+    }
+
+    /// Gets the presets carrying a tag, ignoring case.
+    #[must_use]
+    pub fn tagged(&self, tag: &str) -> Vec<&Preset> {
+        self.presets.iter().filter(|p| p.has_tag(tag)).collect()
+    }
+
+    /// Writes a preset to the library, replacing a preset with the same
+    /// name.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Io` if the file can't be written.
+    pub fn save(&mut self, preset: Preset) -> VideoEditorResult<()> {
+        let path = self.path_of(&preset.name);
+        std::fs::write(&path, preset.to_bytes()).map_err(|e| VideoEditorError::io(path, e))?;
+        self.presets.retain(|p| p.name != preset.name);
+        let index = self
+            .presets
+            .iter()
+            .position(|p| (&p.category, &p.name) > (&preset.category, &preset.name))
+            .unwrap_or(self.presets.len());
+        self.presets.insert(index, preset);
+        Ok(())
+    }
+
+    /// Deletes a preset and its file. Returns whether it existed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Io` if the file can't be deleted.
+    pub fn remove(&mut self, name: &str) -> VideoEditorResult<bool> {
+        let Some(index) = self.presets.iter().position(|p| p.name == name) else {
+            return Ok(false);
+        };
+        let path = self.path_of(name);
+        match std::fs::remove_file(&path) {
+            Ok(()) => {},
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+            Err(e) => return Err(VideoEditorError::io(path, e)),
+        }
+        self.presets.remove(index);
+        Ok(true)
+    }
+
+    /// Writes the named presets to a preset pack file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Effect` if a preset is missing and
+    /// `VideoEditorError::Io` if the file can't be written.
+    pub fn export_pack(&self, names: &[&str], path: impl AsRef<Path>) -> VideoEditorResult<()> {
+        let presets = names
+            .iter()
+            .map(|name| {
+                self.get(name)
+                    .ok_or_else(|| VideoEditorError::Effect(format!("Preset not found: {name}")))
+            })
+            .collect::<VideoEditorResult<Vec<_>>>()?;
+        let path = path.as_ref();
+        std::fs::write(path, Preset::encode_pack(&presets))
+            .map_err(|e| VideoEditorError::io(path, e))
+    }
+
+    /// Saves every preset of a pack file into the library, replacing
+    /// presets with the same names. Returns the imported names.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Io` if a file can't be read or written
+    /// and `VideoEditorError::UnsupportedFormat` if the file isn't a
+    /// preset pack.
+    pub fn import_pack(&mut self, path: impl AsRef<Path>) -> VideoEditorResult<Vec<String>> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| VideoEditorError::io(path, e))?;
+        let presets = Preset::decode_pack(&bytes).ok_or_else(|| {
+            VideoEditorError::unsupported_format(format!("{} is not a preset pack", path.display()))
+        })?;
+        presets
+            .into_iter()
+            .map(|preset| {
+                let name = preset.name.clone();
+                self.save(preset)?;
+                Ok(name)
+            })
+            .collect()
+    }
+
+    fn path_of(&self, name: &str) -> PathBuf {
+        let stem: String = name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.directory.join(format!("{stem}.{}", Self::EXTENSION))
+    }
+}
+
+const WIPES: [WipeDirection; 8] = [
+    WipeDirection::LeftToRight,
+    WipeDirection::RightToLeft,
+    WipeDirection::TopToBottom,
+    WipeDirection::BottomToTop,
+    WipeDirection::DiagonalTLBR,
+    WipeDirection::DiagonalTRBL,
+    WipeDirection::CenterOut,
+    WipeDirection::OutsideIn,
+];
+const PUSHES: [PushDirection; 4] =
+    [PushDirection::Left, PushDirection::Right, PushDirection::Top, PushDirection::Bottom];
+const SLIDES: [SlideDirection; 4] =
+    [SlideDirection::Left, SlideDirection::Right, SlideDirection::Top, SlideDirection::Bottom];
+const ZOOMS: [ZoomType; 3] = [ZoomType::ZoomIn, ZoomType::ZoomOut, ZoomType::CrossZoom];
+const IRISES: [IrisShape; 5] = [
+    IrisShape::Circle,
+    IrisShape::Rectangle,
+    IrisShape::Diamond,
+    IrisShape::Star,
+    IrisShape::Heart,
+];
+const CLOCKS: [ClockDirection; 2] = [ClockDirection::Clockwise, ClockDirection::CounterClockwise];
+const PAGE_TURNS: [PageTurnDirection; 4] = [
+    PageTurnDirection::Right,
+    PageTurnDirection::Left,
+    PageTurnDirection::Top,
+    PageTurnDirection::Bottom,
+];
+const CUBE_AXES: [CubeAxis; 2] = [CubeAxis::Horizontal, CubeAxis::Vertical];
+const EASINGS: [TransitionEasing; 14] = [
+    TransitionEasing::Linear,
+    TransitionEasing::EaseIn,
+    TransitionEasing::EaseOut,
+    TransitionEasing::EaseInOut,
+    TransitionEasing::QuadIn,
+    TransitionEasing::QuadOut,
+    TransitionEasing::QuadInOut,
+    TransitionEasing::CubicIn,
+    TransitionEasing::CubicOut,
+    TransitionEasing::CubicInOut,
+    TransitionEasing::ExpoIn,
+    TransitionEasing::ExpoOut,
+    TransitionEasing::Elastic,
+    TransitionEasing::Bounce,
+];
+const INTERPOLATIONS: [LutInterpolation; 3] =
+    [LutInterpolation::Nearest, LutInterpolation::Trilinear, LutInterpolation::Tetrahedral];
+
+fn code_of<T: PartialEq>(table: &[T], value: &T) -> u64 {
+    table.iter().position(|v| v == value).unwrap_or(0) as u64
+}
+
+fn from_code<T: Copy>(table: &[T], code: u64) -> Option<T> {
+    table.get(usize::try_from(code).ok()?).copied()
+}
+
+fn transition_type_code(transition_type: TransitionType) -> (u8, u64) {
+    match transition_type {
+        TransitionType::CrossFade => (0, 0),
+        TransitionType::CrossDissolve => (1, 0),
+        TransitionType::FadeToBlack => (2, 0),
+        TransitionType::FadeToWhite => (3, 0),
+        TransitionType::Wipe(d) => (4, code_of(&WIPES, &d)),
+        TransitionType::Push(d) => (5, code_of(&PUSHES, &d)),
+        TransitionType::Slide(d) => (6, code_of(&SLIDES, &d)),
+        TransitionType::Zoom(z) => (7, code_of(&ZOOMS, &z)),
+        TransitionType::Iris(s) => (8, code_of(&IRISES, &s)),
+        TransitionType::ClockWipe(d) => (9, code_of(&CLOCKS, &d)),
+        TransitionType::PageTurn(d) => (10, code_of(&PAGE_TURNS, &d)),
+        TransitionType::CubeRotate(a) => (11, code_of(&CUBE_AXES, &a)),
+        TransitionType::CustomShader(id) => (12, id),
+    }
+}
+
+fn transition_type_from_code(kind: u8, code: u64) -> Option<TransitionType> {
+    Some(match kind {
+        0 => TransitionType::CrossFade,
+        1 => TransitionType::CrossDissolve,
+        2 => TransitionType::FadeToBlack,
+        3 => TransitionType::FadeToWhite,
+        4 => TransitionType::Wipe(from_code(&WIPES, code)?),
+        5 => TransitionType::Push(from_code(&PUSHES, code)?),
+        6 => TransitionType::Slide(from_code(&SLIDES, code)?),
+        7 => TransitionType::Zoom(from_code(&ZOOMS, code)?),
+        8 => TransitionType::Iris(from_code(&IRISES, code)?),
+        9 => TransitionType::ClockWipe(from_code(&CLOCKS, code)?),
+        10 => TransitionType::PageTurn(from_code(&PAGE_TURNS, code)?),
+        11 => TransitionType::CubeRotate(from_code(&CUBE_AXES, code)?),
+        12 => TransitionType::CustomShader(code),
+        _ => return None,
+    })
+}
+
+fn write_f32s(bytes: &mut Vec<u8>, values: &[f32]) {
+    values.iter().for_each(|v| bytes.extend_from_slice(&v.to_le_bytes()));
+}
+
+fn read_f32s<const N: usize>(reader: &mut ByteReader<'_>) -> Option<[f32; N]> {
+    let mut values = [0.0; N];
+    for value in &mut values {
+        *value = f32::from_bits(reader.u32()?);
+    }
+    Some(values)
+}
+
+fn write_f64s(bytes: &mut Vec<u8>, values: &[f64]) {
+    values.iter().for_each(|v| bytes.extend_from_slice(&v.to_le_bytes()));
+}
+
+fn read_f64s<const N: usize>(reader: &mut ByteReader<'_>) -> Option<[f64; N]> {
+    let mut values = [0.0; N];
+    for value in &mut values {
+        *value = f64::from_bits(reader.u64()?);
+    }
+    Some(values)
+}
+
+fn write_grade(bytes: &mut Vec<u8>, grade: &ColorGradingNode) {
+    write_str(bytes, &grade.name);
+    bytes.push(u8::from(grade.enabled));
+    let three_way = &grade.three_way;
+    for wheel in [&three_way.shadows, &three_way.midtones, &three_way.highlights] {
+        let ColorWheel { hue, saturation, brightness, offset } = *wheel;
+        write_f32s(bytes, &[hue, saturation, brightness, offset.r, offset.g, offset.b, offset.a]);
+    }
+    write_f32s(bytes, &[three_way.shadow_range, three_way.highlight_range]);
+    let curves = &grade.curves;
+    for curve in [&curves.master, &curves.red, &curves.green, &curves.blue] {
+        bytes.extend_from_slice(&(curve.points().len() as u32).to_le_bytes());
+        curve.points().iter().for_each(|p| write_f32s(bytes, &[p.x, p.y]));
+    }
+    match &grade.lut {
+        Some(lut) => {
+            bytes.push(1);
+            write_str(bytes, lut.name());
+            bytes.extend_from_slice(&lut.size().to_le_bytes());
+            bytes.push(code_of(&INTERPOLATIONS, &lut.interpolation()) as u8);
+            lut.data().iter().for_each(|c| write_f32s(bytes, &[c.r, c.g, c.b, c.a]));
+        },
+        None => bytes.push(0),
+    }
+    write_f32s(
+        bytes,
+        &[
+            grade.lut_intensity,
+            grade.exposure,
+            grade.contrast,
+            grade.saturation,
+            grade.temperature,
+            grade.tint,
+        ],
+    );
+}
+
+fn read_grade(reader: &mut ByteReader<'_>) -> Option<ColorGradingNode> {
+    let name = read_str(reader)?;
+    let enabled = reader.u8()? != 0;
+    let mut wheel = || {
+        let [hue, saturation, brightness, r, g, b, a] = read_f32s(reader)?;
+        Some(ColorWheel { hue, saturation, brightness, offset: Color::new(r, g, b, a) })
+    };
+    let (shadows, midtones, highlights) = (wheel()?, wheel()?, wheel()?);
+    let [shadow_range, highlight_range] = read_f32s(reader)?;
+    let three_way =
+        ThreeWayCorrector { shadows, midtones, highlights, shadow_range, highlight_range };
+    let mut curve = || {
+        let count = reader.u32()? as usize;
+        let points = (0..count)
+            .map(|_| read_f32s(reader).map(|[x, y]| CurvePoint::new(x, y)))
+            .collect::<Option<Vec<_>>>()?;
+        Some(ColorCurve::from_points(points))
+    };
+    let curves =
+        ColorCurves { master: curve()?, red: curve()?, green: curve()?, blue: curve()? };
+    let lut = match reader.u8()? {
+        0 => None,
+        _ => {
+            let name = read_str(reader)?;
+            let size = reader.u32()?;
+            let interpolation = from_code(&INTERPOLATIONS, u64::from(reader.u8()?))?;
+            let count = (size as usize).checked_pow(3)?;
+            let data = (0..count)
+                .map(|_| read_f32s(reader).map(|[r, g, b, a]| Color::new(r, g, b, a)))
+                .collect::<Option<Vec<_>>>()?;
+            let mut lut = Lut3D::from_data(name, size, data)?;
+            lut.set_interpolation(interpolation);
+            Some(lut)
+        },
+    };
+    let [lut_intensity, exposure, contrast, saturation, temperature, tint] = read_f32s(reader)?;
+
+    Some(ColorGradingNode {
+        name,
+        enabled,
+        three_way,
+        curves,
+        lut,
+        lut_intensity,
+        exposure,
+        contrast,
+        saturation,
+        temperature,
+        tint,
+    })
+}
+
+fn write_transition(bytes: &mut Vec<u8>, preset: &TransitionPreset) {
+    write_str(bytes, &preset.name);
+    let (kind, code) = transition_type_code(preset.transition_type);
+    bytes.push(kind);
+    bytes.extend_from_slice(&code.to_le_bytes());
+    bytes.extend_from_slice(&preset.duration.ms.to_le_bytes());
+    bytes.push(code_of(&EASINGS, &preset.easing) as u8);
+
+    let parameters = &preset.parameters;
+    write_f64s(
+        bytes,
+        &[
+            parameters.edge_softness,
+            parameters.blur_amount,
+            parameters.feather,
+            parameters.border_width,
+            parameters.center[0],
+            parameters.center[1],
+            parameters.rotation,
+        ],
+    );
+    write_f32s(bytes, &parameters.border_color);
+    match parameters.custom_shader {
+        Some(id) => {
+            bytes.push(1);
+            bytes.extend_from_slice(&id.to_le_bytes());
+        },
+        None => bytes.push(0),
+    }
+    bytes.extend_from_slice(&(parameters.shader_uniforms.len() as u32).to_le_bytes());
+    for (name, value) in &parameters.shader_uniforms {
+        write_str(bytes, name);
+        write_f32s(bytes, value);
+    }
+    bytes.push(u8::from(parameters.audio_crossfade));
+}
+
+fn read_transition(reader: &mut ByteReader<'_>) -> Option<TransitionPreset> {
+    let name = read_str(reader)?;
+    let kind = reader.u8()?;
+    let transition_type = transition_type_from_code(kind, reader.u64()?)?;
+    let duration = TimePosition::from_ms(reader.u64()?);
+    let easing = from_code(&EASINGS, u64::from(reader.u8()?))?;
+
+    let [edge_softness, blur_amount, feather, border_width, center_x, center_y, rotation] =
+        read_f64s(reader)?;
+    let border_color = read_f32s(reader)?;
+    let custom_shader = match reader.u8()? {
+        0 => None,
+        _ => Some(reader.u64()?),
+    };
+    let uniform_count = reader.u32()? as usize;
+    let shader_uniforms = (0..uniform_count)
+        .map(|_| Some((read_str(reader)?, read_f32s(reader)?)))
+        .collect::<Option<Vec<_>>>()?;
+    let audio_crossfade = reader.u8()? != 0;
+
+    Some(TransitionPreset {
+        name,
+        transition_type,
+        duration,
+        easing,
+        parameters: TransitionParameters {
+            edge_softness,
+            blur_amount,
+            feather,
+            border_width,
+            border_color,
+            center: [center_x, center_y],
+            rotation,
+            custom_shader,
+            shader_uniforms,
+            audio_crossfade,
+        },
+    })
+}
@@ -4,9 +4,11 @@
 //! Features: Playback control, scrubbing, proxy preview,
 //! frame caching, multi-resolution preview, and real-time monitoring.
 
+use super::{gpu_memory::MemoryPressure, prefetch::FramePrefetcher};
 use crate::{
     errors::VideoEditorResult,
-    types::{FrameRate, Resolution, TimePosition},
+    flexforge::VideoEditorMetrics,
+    types::{FrameBuffer, FrameRate, PixelAspectRatio, Resolution, TimePosition},
 };
 
 /// Playback state.
@@ -107,11 +109,46 @@ impl PreviewQuality {
             Self::Half => 0.5,
             Self::Quarter => 0.25,
             Self::Eighth => 0.125,
-            Self::Auto => 1.0, // Determined at runtime
+            Self::Auto => 1.0, // Resolved by `AdaptiveQuality` at runtime
             Self::Draft => 0.125,
         }
     }
 
+    /// Returns the next lower quality, or `None` at the lowest.
+    #[must_use]
+    pub const fn downgraded(&self) -> Option<Self> {
+        match self {
+            Self::Full | Self::Auto => Some(Self::Half),
+            Self::Half => Some(Self::Quarter),
+            Self::Quarter => Some(Self::Eighth),
+            Self::Eighth => Some(Self::Draft),
+            Self::Draft => None,
+        }
+    }
+
+    /// Returns the next higher quality, or `None` at full.
+    #[must_use]
+    pub const fn upgraded(&self) -> Option<Self> {
+        match self {
+            Self::Full | Self::Auto => None,
+            Self::Half => Some(Self::Full),
+            Self::Quarter => Some(Self::Half),
+            Self::Eighth => Some(Self::Quarter),
+            Self::Draft => Some(Self::Eighth),
+        }
+    }
+
+    /// Position on the quality ladder (0 = draft, 4 = full).
+    const fn rank(&self) -> u8 {
+        match self {
+            Self::Draft => 0,
+            Self::Eighth => 1,
+            Self::Quarter => 2,
+            Self::Half => 3,
+            Self::Full | Self::Auto => 4,
+        }
+    }
+
     /// Calculates the preview resolution.
     #[must_use]
     pub fn calculate_resolution(&self, source: Resolution) -> Resolution {
@@ -203,6 +240,12 @@ impl InOutPoints {
 }
 
 /// Frame cache for preview performance.
+///
+/// Frames are kept in the buffers they were decoded into; evicted pooled
+/// buffers go straight back to their [`FrameBufferPool`] for the next
+/// decode.
+///
+/// [`FrameBufferPool`]: crate::FrameBufferPool
 #[derive(Debug)]
 pub struct FrameCache {
     /// Maximum cache size in bytes.
@@ -223,7 +266,7 @@ pub struct CachedFrame {
     /// Frame number.
     pub frame:       u64,
     /// Frame data (raw pixels).
-    pub data:        Vec<u8>,
+    pub data:        FrameBuffer,
     /// Frame resolution.
     pub resolution:  Resolution,
     /// Last access timestamp.
@@ -256,8 +299,15 @@ impl FrameCache {
         }
     }
 
-    /// Puts a frame in the cache.
-    pub fn put(&mut self, frame: u64, data: Vec<u8>, resolution: Resolution) {
+    /// Returns whether a frame is cached, without counting a hit or miss.
+    #[must_use]
+    pub fn contains(&self, frame: u64) -> bool {
+        self.entries.iter().any(|e| e.frame == frame)
+    }
+
+    /// Puts a frame in the cache, taking over its buffer.
+    pub fn put(&mut self, frame: u64, data: impl Into<FrameBuffer>, resolution: Resolution) {
+        let data = data.into();
         let frame_size = data.len();
 
         // Evict old frames if necessary
@@ -363,6 +413,12 @@ impl PreviewStats {
         }
     }
 
+    /// Copies render rate and dropped frames into plugin metrics.
+    pub fn fill_metrics(&self, metrics: &mut VideoEditorMetrics) {
+        metrics.render_fps = self.fps as f32;
+        metrics.dropped_frames = self.dropped_frames;
+    }
+
     /// Returns the dropped frame percentage.
     #[must_use]
     pub fn drop_percentage(&self) -> f64 {
@@ -374,6 +430,145 @@ impl PreviewStats {
     }
 }
 
+/// Adaptive resolution for `PreviewQuality::Auto`.
+///
+/// Frame timings are evaluated in windows. Quality steps down after
+/// `downgrade_windows` windows below the target frame rate (or with dropped
+/// frames) and back up after `upgrade_windows` windows with enough headroom
+/// to afford the next step. Stepping up takes longer than stepping down so
+/// the preview doesn't oscillate.
+#[derive(Debug, Clone)]
+pub struct AdaptiveQuality {
+    /// Lowest quality Auto may pick.
+    pub min_quality:       PreviewQuality,
+    /// Highest quality Auto may pick.
+    pub max_quality:       PreviewQuality,
+    /// Frame rate the preview must sustain.
+    pub target_fps:        f64,
+    /// Render rate needed, as a multiple of the target, before stepping up.
+    pub upgrade_headroom:  f64,
+    /// Frames per evaluation window.
+    pub window_frames:     u32,
+    /// Consecutive slow windows before stepping down.
+    pub downgrade_windows: u32,
+    /// Consecutive fast windows before stepping up.
+    pub upgrade_windows:   u32,
+    current:               PreviewQuality,
+    window_time_ms:        f64,
+    window_count:          u32,
+    window_drops:          u32,
+    slow_windows:          u32,
+    fast_windows:          u32,
+}
+
+impl AdaptiveQuality {
+    /// Creates adaptive settings for a target frame rate.
+    #[must_use]
+    pub fn new(target_fps: f64) -> Self {
+        Self {
+            min_quality: PreviewQuality::Draft,
+            max_quality: PreviewQuality::Full,
+            target_fps,
+            upgrade_headroom: 2.5,
+            window_frames: 30,
+            downgrade_windows: 2,
+            upgrade_windows: 6,
+            current: PreviewQuality::Full,
+            window_time_ms: 0.0,
+            window_count: 0,
+            window_drops: 0,
+            slow_windows: 0,
+            fast_windows: 0,
+        }
+    }
+
+    /// Returns the quality Auto currently renders at.
+    #[must_use]
+    pub const fn current(&self) -> PreviewQuality {
+        self.current
+    }
+
+    /// Sets the quality bounds and clamps the current quality into them.
+    pub fn set_bounds(&mut self, min: PreviewQuality, max: PreviewQuality) {
+        let (min, max) = if min.rank() <= max.rank() { (min, max) } else { (max, min) };
+        self.min_quality = min;
+        self.max_quality = max;
+        self.current = self.clamp(self.current);
+    }
+
+    /// Starts over at the highest allowed quality.
+    pub fn reset(&mut self) {
+        self.current = self.max_quality;
+        self.reset_window();
+        self.slow_windows = 0;
+        self.fast_windows = 0;
+    }
+
+    /// Records a rendered frame. Returns the new quality when it changed.
+    pub fn record(&mut self, render_time_ms: f64, dropped: bool) -> Option<PreviewQuality> {
+        self.window_time_ms += render_time_ms.max(0.0);
+        self.window_count += 1;
+        self.window_drops += u32::from(dropped);
+        if self.window_count < self.window_frames.max(1) {
+            return None;
+        }
+
+        let fps = if self.window_time_ms > 0.0 {
+            1000.0 * f64::from(self.window_count) / self.window_time_ms
+        } else {
+            f64::INFINITY
+        };
+        let slow = fps < self.target_fps || self.window_drops > 0;
+        let fast = fps >= self.target_fps * self.upgrade_headroom;
+        self.reset_window();
+
+        if slow {
+            self.fast_windows = 0;
+            self.slow_windows += 1;
+            if self.slow_windows >= self.downgrade_windows {
+                self.slow_windows = 0;
+                return self.step(self.current.downgraded());
+            }
+        } else if fast {
+            self.slow_windows = 0;
+            self.fast_windows += 1;
+            if self.fast_windows >= self.upgrade_windows {
+                self.fast_windows = 0;
+                return self.step(self.current.upgraded());
+            }
+        } else {
+            self.slow_windows = 0;
+            self.fast_windows = 0;
+        }
+        None
+    }
+
+    fn step(&mut self, next: Option<PreviewQuality>) -> Option<PreviewQuality> {
+        let next = self.clamp(next?);
+        if next == self.current {
+            return None;
+        }
+        self.current = next;
+        Some(next)
+    }
+
+    fn clamp(&self, quality: PreviewQuality) -> PreviewQuality {
+        if quality.rank() < self.min_quality.rank() {
+            self.min_quality
+        } else if quality.rank() > self.max_quality.rank() {
+            self.max_quality
+        } else {
+            quality
+        }
+    }
+
+    fn reset_window(&mut self) {
+        self.window_time_ms = 0.0;
+        self.window_count = 0;
+        self.window_drops = 0;
+    }
+}
+
 /// Audio monitoring settings.
 #[derive(Debug, Clone)]
 pub struct AudioMonitor {
@@ -419,8 +614,14 @@ pub struct PreviewManager {
     audio:              AudioMonitor,
     /// Source resolution.
     source_resolution:  Resolution,
+    /// Pixel aspect of the source frames.
+    pixel_aspect:       PixelAspectRatio,
     /// Preview resolution.
     preview_resolution: Resolution,
+    /// Adaptive resolution for Auto quality.
+    adaptive:           AdaptiveQuality,
+    /// Read-ahead decoding into the frame cache.
+    prefetcher:         Option<FramePrefetcher>,
 }
 
 impl PreviewManager {
@@ -443,7 +644,10 @@ impl PreviewManager {
             stats: PreviewStats::default(),
             audio: AudioMonitor::default(),
             source_resolution: resolution,
+            pixel_aspect: PixelAspectRatio::SQUARE,
             preview_resolution: preview_res,
+            adaptive: AdaptiveQuality::new(frame_rate.as_f64()),
+            prefetcher: None,
         }
     }
 
@@ -537,6 +741,7 @@ impl PreviewManager {
     /// Seeks to a specific position.
     pub fn seek(&mut self, position: TimePosition) {
         self.position = TimePosition::from_ms(position.ms.min(self.duration.ms));
+        self.cancel_prefetch();
     }
 
     /// Seeks to a specific frame.
@@ -593,6 +798,9 @@ impl PreviewManager {
 
     /// Sets playback speed.
     pub fn set_speed(&mut self, speed: PlaybackSpeed) {
+        if speed.is_reverse() != self.speed.is_reverse() || speed.abs() != self.speed.abs() {
+            self.cancel_prefetch();
+        }
         self.speed = speed;
         if speed.is_reverse() {
             self.state = PlaybackState::ShuttleBackward;
@@ -609,9 +817,102 @@ impl PreviewManager {
 
     /// Sets preview quality.
     pub fn set_quality(&mut self, quality: PreviewQuality) {
+        if quality == PreviewQuality::Auto {
+            self.adaptive.reset();
+        }
         self.quality = quality;
-        self.preview_resolution = quality.calculate_resolution(self.source_resolution);
+        self.apply_resolution();
+    }
+
+    /// Sets the pixel aspect of the source frames. The preview is sized
+    /// in square pixels, so anamorphic frames show at their display
+    /// aspect.
+    pub fn set_pixel_aspect(&mut self, pixel_aspect: PixelAspectRatio) {
+        if pixel_aspect != self.pixel_aspect {
+            self.pixel_aspect = pixel_aspect;
+            self.apply_resolution();
+        }
+    }
+
+    /// Returns the pixel aspect of the source frames.
+    #[must_use]
+    pub const fn pixel_aspect(&self) -> PixelAspectRatio {
+        self.pixel_aspect
+    }
+
+    /// Returns the shape of the picture as shown.
+    #[must_use]
+    pub fn display_aspect_ratio(&self) -> f64 {
+        self.source_resolution.display_aspect_ratio(self.pixel_aspect)
+    }
+
+    /// Returns the quality frames render at, resolving Auto.
+    #[must_use]
+    pub const fn effective_quality(&self) -> PreviewQuality {
+        match self.quality {
+            PreviewQuality::Auto => self.adaptive.current(),
+            quality => quality,
+        }
+    }
+
+    /// Returns the adaptive quality settings.
+    #[must_use]
+    pub fn adaptive(&self) -> &AdaptiveQuality {
+        &self.adaptive
+    }
+
+    /// Returns mutable adaptive quality settings.
+    pub fn adaptive_mut(&mut self) -> &mut AdaptiveQuality {
+        &mut self.adaptive
+    }
+
+    /// Sets the session bounds for Auto quality.
+    pub fn set_auto_bounds(&mut self, min: PreviewQuality, max: PreviewQuality) {
+        let before = self.effective_quality();
+        self.adaptive.set_bounds(min, max);
+        if self.effective_quality() != before {
+            self.apply_resolution();
+        }
+    }
+
+    /// Records a rendered frame in the stats and, in Auto mode, adapts the
+    /// preview resolution. Returns whether the resolution changed.
+    pub fn record_frame(&mut self, render_time_ms: f64, dropped: bool) -> bool {
+        self.stats.update(render_time_ms, dropped);
+        if self.quality != PreviewQuality::Auto
+            || self.adaptive.record(render_time_ms, dropped).is_none()
+        {
+            return false;
+        }
+        self.apply_resolution();
+        true
+    }
+
+    fn apply_resolution(&mut self) {
+        let display =
+            self.source_resolution.with_pixel_aspect(self.pixel_aspect, PixelAspectRatio::SQUARE);
+        self.preview_resolution = self.effective_quality().calculate_resolution(display);
         self.cache.clear(); // Clear cache when quality changes
+        self.cancel_prefetch();
+    }
+
+    /// Lowers preview quality in response to GPU memory pressure.
+    ///
+    /// Elevated pressure drops one step, critical pressure drops to draft.
+    /// Returns whether the quality changed.
+    pub fn apply_memory_pressure(&mut self, pressure: MemoryPressure) -> bool {
+        let target = match pressure {
+            MemoryPressure::Normal => None,
+            MemoryPressure::Elevated => self.effective_quality().downgraded(),
+            MemoryPressure::Critical => {
+                (self.quality != PreviewQuality::Draft).then_some(PreviewQuality::Draft)
+            },
+        };
+        let Some(quality) = target else {
+            return false;
+        };
+        self.set_quality(quality);
+        true
     }
 
     /// Returns preview quality.
@@ -671,12 +972,51 @@ impl PreviewManager {
         &mut self.cache
     }
 
+    /// Installs a prefetcher that decodes ahead of the playhead.
+    pub fn set_prefetcher(&mut self, prefetcher: Option<FramePrefetcher>) {
+        self.prefetcher = prefetcher;
+    }
+
+    /// Returns the prefetcher, if one is installed.
+    #[must_use]
+    pub fn prefetcher(&self) -> Option<&FramePrefetcher> {
+        self.prefetcher.as_ref()
+    }
+
+    /// Moves decoded frames into the cache and schedules the read-ahead for
+    /// the current playhead. Returns how many frames were added.
+    pub fn pump_prefetch(&mut self) -> usize {
+        let playhead = self.current_frame();
+        let total = self.total_frames();
+        let Some(prefetcher) = self.prefetcher.as_mut() else {
+            return 0;
+        };
+        let added = prefetcher.collect(&mut self.cache);
+        if self.state != PlaybackState::Scrubbing {
+            prefetcher.schedule(playhead, self.speed, total, &self.cache);
+        }
+        added
+    }
+
+    fn cancel_prefetch(&mut self) {
+        if let Some(prefetcher) = self.prefetcher.as_mut() {
+            prefetcher.cancel();
+        }
+    }
+
     /// Returns performance stats.
     #[must_use]
     pub fn stats(&self) -> &PreviewStats {
         &self.stats
     }
 
+    /// Copies render rate, dropped frames and the frame cache hit ratio
+    /// into plugin metrics.
+    pub fn fill_metrics(&self, metrics: &mut VideoEditorMetrics) {
+        self.stats.fill_metrics(metrics);
+        metrics.cache_hit_ratio = self.cache.hit_ratio();
+    }
+
     /// Returns audio monitor.
     #[must_use]
     pub fn audio(&self) -> &AudioMonitor {
@@ -701,9 +1041,13 @@ impl PreviewManager {
         let (final_pos, should_loop) = self.calculate_loop_position(new_pos);
         self.position = TimePosition::from_ms(final_pos);
 
-        if should_loop && self.loop_mode == LoopMode::PingPong {
-            self.speed = PlaybackSpeed::new(-self.speed.value());
+        if should_loop {
+            self.cancel_prefetch();
+            if self.loop_mode == LoopMode::PingPong {
+                self.speed = PlaybackSpeed::new(-self.speed.value());
+            }
         }
+        self.pump_prefetch();
 
         Ok(())
     }
@@ -800,6 +1144,23 @@ mod tests {
         assert!(cache.get(1).is_none());
     }
 
+    #[test]
+    fn test_preview_metrics() {
+        let mut manager = PreviewManager::new(
+            TimePosition::from_secs(60),
+            FrameRate::FPS_30,
+            Resolution::new(1920, 1080),
+        );
+        let cache = manager.cache_mut();
+        cache.put(0, vec![0u8; 1024], Resolution::new(100, 100));
+        assert!(cache.get(0).is_some());
+        assert!(cache.get(1).is_none());
+
+        let mut metrics = VideoEditorMetrics::default();
+        manager.fill_metrics(&mut metrics);
+        assert!((metrics.cache_hit_ratio - 0.5).abs() < f64::EPSILON);
+    }
+
     #[test]
     fn test_preview_quality() {
         let full = PreviewQuality::Full;
@@ -809,4 +1170,59 @@ mod tests {
         assert_eq!(full.calculate_resolution(source).width, 1920);
         assert_eq!(half.calculate_resolution(source).width, 960);
     }
+
+    #[test]
+    fn test_auto_quality_adapts() {
+        let mut manager = PreviewManager::new(
+            TimePosition::from_secs(60),
+            FrameRate::FPS_30,
+            Resolution::new(1920, 1080),
+        );
+        manager.set_quality(PreviewQuality::Auto);
+        manager.set_auto_bounds(PreviewQuality::Quarter, PreviewQuality::Full);
+        assert_eq!(manager.preview_resolution().width, 1920);
+
+        // 50 ms frames miss 30 fps; one slow window is not enough to react
+        let window = manager.adaptive().window_frames;
+        let mut changed = (0..window).any(|_| manager.record_frame(50.0, false));
+        assert!(!changed);
+        changed = (0..window).any(|_| manager.record_frame(50.0, false));
+        assert!(changed);
+        assert_eq!(manager.effective_quality(), PreviewQuality::Half);
+
+        // Never below the session minimum
+        for _ in 0..window * 10 {
+            manager.record_frame(50.0, true);
+        }
+        assert_eq!(manager.effective_quality(), PreviewQuality::Quarter);
+
+        // Headroom steps back up, one rung at a time
+        let fast = window * manager.adaptive().upgrade_windows;
+        for _ in 0..fast {
+            manager.record_frame(5.0, false);
+        }
+        assert_eq!(manager.effective_quality(), PreviewQuality::Half);
+        assert_eq!(manager.preview_resolution().width, 960);
+        assert_eq!(manager.quality(), PreviewQuality::Auto);
+    }
+
+    #[test]
+    fn test_anamorphic_preview_resolution() {
+        let mut manager = PreviewManager::new(
+            TimePosition::from_secs(60),
+            FrameRate::FPS_25,
+            Resolution::new(1440, 1080),
+        );
+        manager.set_quality(PreviewQuality::Half);
+        assert_eq!(manager.preview_resolution(), Resolution::new(720, 540));
+
+        // HDV pixels are 4:3, so the picture is 16:9
+        manager.set_pixel_aspect(PixelAspectRatio::HDV);
+        assert_eq!(manager.preview_resolution(), Resolution::new(960, 540));
+        assert!((manager.display_aspect_ratio() - 16.0 / 9.0).abs() < 1e-9);
+
+        // Narrow NTSC pixels squeeze the picture
+        manager.set_pixel_aspect(PixelAspectRatio::NTSC);
+        assert_eq!(manager.preview_resolution().width, 654);
+    }
 }
@@ -0,0 +1,388 @@
+//! Project diagnostics ("project doctor").
+//!
+//! Scans a timeline and its asset library for common problems: offline
+//! media, frame rates that don't match the sequence, clipped audio, missing
+//! fonts and LUTs, empty tracks and unused assets. Each finding carries a
+//! severity and, where the fix cannot lose work, a one-click fix.
+
+use core::fmt;
+
+use super::{AssetLibrary, TimelineManager};
+use crate::types::{FrameRate, clip::ClipState};
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DiagnosticSeverity {
+    /// Housekeeping only.
+    Info,
+    /// Likely to affect output quality.
+    Warning,
+    /// Will break playback or export.
+    Error,
+}
+
+/// A problem found in the project.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticIssue {
+    /// Asset media cannot be found.
+    OfflineMedia {
+        /// Asset ID.
+        asset_id: u64,
+        /// Media path.
+        path:     String,
+    },
+    /// Asset frame rate differs from the sequence frame rate.
+    FrameRateMismatch {
+        /// Asset ID.
+        asset_id:      u64,
+        /// Asset frame rate.
+        asset_rate:    FrameRate,
+        /// Sequence frame rate.
+        sequence_rate: FrameRate,
+    },
+    /// Audio asset peaks at or above the clip threshold.
+    ClippedAudio {
+        /// Asset ID.
+        asset_id:  u64,
+        /// Measured peak in dBFS.
+        peak_dbfs: f32,
+    },
+    /// A font used by the project is not installed.
+    MissingFont(String),
+    /// A LUT used by the project cannot be found.
+    MissingLut(String),
+    /// Track without clips or adjustment clips.
+    EmptyTrack {
+        /// Track ID.
+        track_id: u64,
+    },
+    /// Asset not used by any timeline clip.
+    UnusedAsset {
+        /// Asset ID.
+        asset_id: u64,
+    },
+}
+
+impl DiagnosticIssue {
+    /// Returns the severity of the issue.
+    #[must_use]
+    pub const fn severity(&self) -> DiagnosticSeverity {
+        match self {
+            Self::OfflineMedia { .. } | Self::MissingFont(_) | Self::MissingLut(_) => {
+                DiagnosticSeverity::Error
+            },
+            Self::FrameRateMismatch { .. } | Self::ClippedAudio { .. } => {
+                DiagnosticSeverity::Warning
+            },
+            Self::EmptyTrack { .. } | Self::UnusedAsset { .. } => DiagnosticSeverity::Info,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OfflineMedia { asset_id, path } => {
+                write!(f, "asset {asset_id} is offline ({path})")
+            },
+            Self::FrameRateMismatch { asset_id, asset_rate, sequence_rate } => write!(
+                f,
+                "asset {asset_id} runs at {:.3} fps, sequence at {:.3} fps",
+                asset_rate.as_f64(),
+                sequence_rate.as_f64()
+            ),
+            Self::ClippedAudio { asset_id, peak_dbfs } => {
+                write!(f, "asset {asset_id} clips (peak {peak_dbfs:.1} dBFS)")
+            },
+            Self::MissingFont(name) => write!(f, "font \"{name}\" is not installed"),
+            Self::MissingLut(path) => write!(f, "LUT {path} is missing"),
+            Self::EmptyTrack { track_id } => write!(f, "track {track_id} is empty"),
+            Self::UnusedAsset { asset_id } => write!(f, "asset {asset_id} is not used"),
+        }
+    }
+}
+
+/// Safe one-click fix for a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DoctorFix {
+    /// Remove an empty, unlocked track.
+    RemoveTrack(u64),
+    /// Remove an asset no clip uses.
+    RemoveAsset(u64),
+}
+
+/// A single finding with its fix, if one is safe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// The problem found.
+    pub issue: DiagnosticIssue,
+    /// Fix that can be applied without losing work.
+    pub fix:   Option<DoctorFix>,
+}
+
+impl Diagnostic {
+    /// Returns the severity of the finding.
+    #[must_use]
+    pub const fn severity(&self) -> DiagnosticSeverity {
+        self.issue.severity()
+    }
+}
+
+/// Result of a project diagnostic pass.
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    /// Findings, most severe first.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl DoctorReport {
+    /// Returns whether nothing was found.
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Returns the findings of a severity.
+    pub fn with_severity(&self, severity: DiagnosticSeverity) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(move |d| d.severity() == severity)
+    }
+
+    /// Returns whether any finding will break playback or export.
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        self.with_severity(DiagnosticSeverity::Error).next().is_some()
+    }
+
+    /// Returns the fixes that can be applied in one click.
+    #[must_use]
+    pub fn fixes(&self) -> Vec<DoctorFix> {
+        self.diagnostics.iter().filter_map(|d| d.fix).collect()
+    }
+
+    fn push(&mut self, issue: DiagnosticIssue, fix: Option<DoctorFix>) {
+        self.diagnostics.push(Diagnostic { issue, fix });
+    }
+}
+
+/// Project diagnostic pass.
+///
+/// The doctor doesn't read media itself: hosts report the fonts and LUTs
+/// the project uses, what is installed and the measured audio peaks.
+#[derive(Debug, Clone)]
+pub struct ProjectDoctor {
+    /// Sequence frame rate assets are compared against.
+    pub sequence_rate:       FrameRate,
+    /// Peak level at or above which audio counts as clipped (dBFS).
+    pub clip_threshold_dbfs: f32,
+    /// Fonts installed on the system.
+    pub installed_fonts:     Vec<String>,
+    /// LUT files available on the system.
+    pub available_luts:      Vec<String>,
+    used_fonts:              Vec<String>,
+    used_luts:               Vec<String>,
+    audio_peaks:             Vec<(u64, f32)>,
+}
+
+impl ProjectDoctor {
+    /// Default clip threshold in dBFS.
+    pub const DEFAULT_CLIP_THRESHOLD_DBFS: f32 = -0.1;
+
+    /// Creates a doctor for a sequence frame rate.
+    #[must_use]
+    pub fn new(sequence_rate: FrameRate) -> Self {
+        Self {
+            sequence_rate,
+            clip_threshold_dbfs: Self::DEFAULT_CLIP_THRESHOLD_DBFS,
+            installed_fonts: Vec::new(),
+            available_luts: Vec::new(),
+            used_fonts: Vec::new(),
+            used_luts: Vec::new(),
+            audio_peaks: Vec::new(),
+        }
+    }
+
+    /// Records a font the project uses.
+    pub fn use_font(&mut self, name: impl Into<String>) {
+        let name = name.into();
+        if !self.used_fonts.contains(&name) {
+            self.used_fonts.push(name);
+        }
+    }
+
+    /// Records a LUT the project uses.
+    pub fn use_lut(&mut self, path: impl Into<String>) {
+        let path = path.into();
+        if !self.used_luts.contains(&path) {
+            self.used_luts.push(path);
+        }
+    }
+
+    /// Records the measured peak level of an audio asset.
+    pub fn set_audio_peak(&mut self, asset_id: u64, peak_dbfs: f32) {
+        self.audio_peaks.retain(|(id, _)| *id != asset_id);
+        self.audio_peaks.push((asset_id, peak_dbfs));
+    }
+
+    /// Scans the project. `media_online` reports whether a media path can
+    /// be read.
+    #[must_use]
+    pub fn diagnose(
+        &self, timeline: &TimelineManager, assets: &AssetLibrary,
+        media_online: impl Fn(&str) -> bool,
+    ) -> DoctorReport {
+        let mut report = DoctorReport::default();
+        let used =
+            |id: u64| timeline.tracks().iter().any(|t| t.clips.iter().any(|c| c.source_id == id));
+
+        let mut media = Vec::new();
+        media.extend(assets.video_clips().iter().map(|c| (c.id, c.path.clone(), c.state)));
+        media.extend(assets.audio_clips().iter().map(|c| (c.id, c.path.clone(), c.state)));
+        media.extend(
+            assets
+                .image_sequences()
+                .iter()
+                .map(|c| (c.id, c.pattern.frame_path(c.first_frame), c.state)),
+        );
+        for (asset_id, path, state) in media {
+            if matches!(state, ClipState::Error | ClipState::Offline) || !media_online(&path) {
+                report.push(DiagnosticIssue::OfflineMedia { asset_id, path }, None);
+            }
+        }
+
+        let rates = assets
+            .video_clips()
+            .iter()
+            .map(|c| (c.id, c.frame_rate))
+            .chain(assets.image_sequences().iter().map(|c| (c.id, c.frame_rate)));
+        for (asset_id, asset_rate) in rates {
+            if used(asset_id) && asset_rate != self.sequence_rate {
+                report.push(
+                    DiagnosticIssue::FrameRateMismatch {
+                        asset_id,
+                        asset_rate,
+                        sequence_rate: self.sequence_rate,
+                    },
+                    None,
+                );
+            }
+        }
+
+        for &(asset_id, peak_dbfs) in &self.audio_peaks {
+            if peak_dbfs >= self.clip_threshold_dbfs {
+                report.push(DiagnosticIssue::ClippedAudio { asset_id, peak_dbfs }, None);
+            }
+        }
+        for font in self.used_fonts.iter().filter(|f| !self.installed_fonts.contains(f)) {
+            report.push(DiagnosticIssue::MissingFont(font.clone()), None);
+        }
+        for lut in self.used_luts.iter().filter(|l| !self.available_luts.contains(l)) {
+            report.push(DiagnosticIssue::MissingLut(lut.clone()), None);
+        }
+
+        for track in timeline.tracks() {
+            if track.clips.is_empty() && track.adjustments.is_empty() {
+                let fix = (!timeline.is_track_locked(track.id))
+                    .then_some(DoctorFix::RemoveTrack(track.id));
+                report.push(DiagnosticIssue::EmptyTrack { track_id: track.id }, fix);
+            }
+        }
+        for asset_id in assets.asset_ids().into_iter().filter(|&id| !used(id)) {
+            report.push(
+                DiagnosticIssue::UnusedAsset { asset_id },
+                Some(DoctorFix::RemoveAsset(asset_id)),
+            );
+        }
+
+        report.diagnostics.sort_by_key(|d| core::cmp::Reverse(d.severity()));
+        report
+    }
+
+    /// Applies fixes and returns how many took effect.
+    ///
+    /// Fixes are re-checked first, so a stale report never removes a track
+    /// or asset that has gained clips since it was made.
+    pub fn apply_fixes(
+        fixes: &[DoctorFix], timeline: &mut TimelineManager, assets: &mut AssetLibrary,
+    ) -> usize {
+        let mut applied = 0;
+        for &fix in fixes {
+            let done = match fix {
+                DoctorFix::RemoveTrack(track_id) => {
+                    timeline
+                        .get_track(track_id)
+                        .is_some_and(|t| t.clips.is_empty() && t.adjustments.is_empty())
+                        && timeline.remove_track(track_id)
+                },
+                DoctorFix::RemoveAsset(asset_id) => {
+                    !timeline
+                        .tracks()
+                        .iter()
+                        .any(|t| t.clips.iter().any(|c| c.source_id == asset_id))
+                        && assets.remove_asset(asset_id)
+                },
+            };
+            applied += usize::from(done);
+        }
+        applied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{TimePosition, TrackType, timeline::TimelineClip};
+
+    fn project() -> (TimelineManager, AssetLibrary, u64, u64) {
+        let mut timeline = TimelineManager::new();
+        let video = timeline.add_track("V1", TrackType::Video);
+        let empty = timeline.add_track("A1", TrackType::Audio);
+        let mut assets = AssetLibrary::new();
+        let used = assets.import_video("/media/used.mp4").expect("test assertion");
+        assets.import_audio("/media/unused.wav").expect("test assertion");
+        timeline
+            .add_clip(
+                video,
+                TimelineClip::new(1, used, TimePosition::from_ms(0), TimePosition::from_secs(2)),
+            )
+            .expect("test assertion");
+        (timeline, assets, used, empty)
+    }
+
+    #[test]
+    fn test_diagnose_finds_issues() {
+        let (timeline, assets, used, empty) = project();
+        let mut doctor = ProjectDoctor::new(FrameRate::FPS_25);
+        doctor.use_font("Inter");
+        doctor.use_lut("/luts/film.cube");
+        doctor.available_luts.push("/luts/film.cube".into());
+        doctor.set_audio_peak(2, 0.0);
+
+        let report = doctor.diagnose(&timeline, &assets, |path| path != "/media/used.mp4");
+        let issues: Vec<_> = report.diagnostics.iter().map(|d| &d.issue).collect();
+        assert!(issues.contains(&&DiagnosticIssue::OfflineMedia {
+            asset_id: used,
+            path:     "/media/used.mp4".into(),
+        }));
+        assert!(issues.contains(&&DiagnosticIssue::MissingFont("Inter".into())));
+        assert!(issues.contains(&&DiagnosticIssue::EmptyTrack { track_id: empty }));
+        assert!(issues.iter().any(|i| matches!(i, DiagnosticIssue::FrameRateMismatch { .. })));
+        assert!(issues.iter().any(|i| matches!(i, DiagnosticIssue::ClippedAudio { .. })));
+        assert!(!issues.iter().any(|i| matches!(i, DiagnosticIssue::MissingLut(_))));
+        assert!(report.has_errors());
+        assert_eq!(report.diagnostics[0].severity(), DiagnosticSeverity::Error);
+    }
+
+    #[test]
+    fn test_apply_safe_fixes() {
+        let (mut timeline, mut assets, used, empty) = project();
+        let doctor = ProjectDoctor::new(FrameRate::FPS_30);
+        let report = doctor.diagnose(&timeline, &assets, |_| true);
+        assert_eq!(report.fixes(), vec![DoctorFix::RemoveTrack(empty), DoctorFix::RemoveAsset(2)]);
+
+        // A fix for an asset that is in use is refused
+        let mut fixes = report.fixes();
+        fixes.push(DoctorFix::RemoveAsset(used));
+        assert_eq!(ProjectDoctor::apply_fixes(&fixes, &mut timeline, &mut assets), 2);
+        assert!(doctor.diagnose(&timeline, &assets, |_| true).is_healthy());
+    }
+}
@@ -0,0 +1,313 @@
+//! Named project snapshots and structural diffs between them.
+//!
+//! A snapshot copies the timeline tracks together with the project settings
+//! and groups, so it can be restored without replaying undo history. Two
+//! snapshots compare track by track and clip by clip, matching clips on ID:
+//! a clip that changed track or start counts as moved, one whose trim,
+//! speed or state changed counts as modified.
+
+use std::fmt;
+
+use super::project_manager::ProjectSettings;
+use crate::types::{
+    ClipGroup, TimePosition, TimelineTrack, Timestamp, TrackGroup, timeline::TimelineClip,
+};
+
+/// Saved copy of a project's edit.
+#[derive(Debug, Clone)]
+pub struct ProjectSnapshot {
+    /// Snapshot ID, unique within the project.
+    pub id:           u64,
+    /// User-facing name.
+    pub name:         String,
+    /// When the snapshot was taken.
+    pub created_at:   Timestamp,
+    /// Project settings at the time.
+    pub settings:     ProjectSettings,
+    /// Timeline tracks with their clips.
+    pub tracks:       Vec<TimelineTrack>,
+    /// Linked clip groups.
+    pub clip_groups:  Vec<ClipGroup>,
+    /// Track groups.
+    pub track_groups: Vec<TrackGroup>,
+}
+
+impl ProjectSnapshot {
+    /// Clip count across all tracks.
+    #[must_use]
+    pub fn clip_count(&self) -> usize {
+        self.tracks.iter().map(|t| t.clips.len()).sum()
+    }
+
+    /// Changes that turn `self` into `newer`.
+    #[must_use]
+    pub fn diff(&self, newer: &Self) -> ProjectDiff {
+        let mut diff = ProjectDiff::default();
+
+        for track in &newer.tracks {
+            match self.tracks.iter().find(|t| t.id == track.id) {
+                None => diff
+                    .tracks
+                    .push(TrackChange::Added { track_id: track.id, name: track.name.clone() }),
+                Some(old) if old.name != track.name => diff.tracks.push(TrackChange::Renamed {
+                    track_id: track.id,
+                    from:     old.name.clone(),
+                    to:       track.name.clone(),
+                }),
+                Some(_) => {},
+            }
+        }
+        for track in self.tracks.iter().filter(|t| !newer.tracks.iter().any(|n| n.id == t.id)) {
+            diff.tracks
+                .push(TrackChange::Removed { track_id: track.id, name: track.name.clone() });
+        }
+
+        let old_clips: Vec<(u64, &TimelineClip)> = clips(&self.tracks).collect();
+        let new_clips: Vec<(u64, &TimelineClip)> = clips(&newer.tracks).collect();
+        for &(track_id, clip) in &new_clips {
+            let Some(&(old_track, old)) = old_clips.iter().find(|(_, c)| c.id == clip.id) else {
+                diff.clips.push(ClipChange::Added {
+                    track_id,
+                    clip_id: clip.id,
+                    start: clip.start,
+                    duration: clip.duration,
+                });
+                continue;
+            };
+            if old_track != track_id || old.start != clip.start {
+                diff.clips.push(ClipChange::Moved {
+                    clip_id:    clip.id,
+                    from_track: old_track,
+                    to_track:   track_id,
+                    from:       old.start,
+                    to:         clip.start,
+                });
+            }
+            let fields = changed_fields(old, clip);
+            if !fields.is_empty() {
+                diff.clips.push(ClipChange::Modified { track_id, clip_id: clip.id, fields });
+            }
+        }
+        for &(track_id, clip) in &old_clips {
+            if !new_clips.iter().any(|(_, c)| c.id == clip.id) {
+                diff.clips.push(ClipChange::Removed {
+                    track_id,
+                    clip_id: clip.id,
+                    start: clip.start,
+                    duration: clip.duration,
+                });
+            }
+        }
+
+        diff.settings = setting_changes(&self.settings, &newer.settings);
+        diff
+    }
+}
+
+/// A track-level difference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackChange {
+    /// Track only in the newer snapshot.
+    Added {
+        /// Track ID.
+        track_id: u64,
+        /// Track name.
+        name:     String,
+    },
+    /// Track only in the older snapshot.
+    Removed {
+        /// Track ID.
+        track_id: u64,
+        /// Track name.
+        name:     String,
+    },
+    /// Track renamed.
+    Renamed {
+        /// Track ID.
+        track_id: u64,
+        /// Old name.
+        from:     String,
+        /// New name.
+        to:       String,
+    },
+}
+
+/// A clip-level difference.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClipChange {
+    /// Clip only in the newer snapshot.
+    Added {
+        /// Track holding the clip.
+        track_id: u64,
+        /// Clip ID.
+        clip_id:  u64,
+        /// Clip start.
+        start:    TimePosition,
+        /// Clip duration.
+        duration: TimePosition,
+    },
+    /// Clip only in the older snapshot.
+    Removed {
+        /// Track that held the clip.
+        track_id: u64,
+        /// Clip ID.
+        clip_id:  u64,
+        /// Clip start.
+        start:    TimePosition,
+        /// Clip duration.
+        duration: TimePosition,
+    },
+    /// Clip changed track or start.
+    Moved {
+        /// Clip ID.
+        clip_id:    u64,
+        /// Old track.
+        from_track: u64,
+        /// New track.
+        to_track:   u64,
+        /// Old start.
+        from:       TimePosition,
+        /// New start.
+        to:         TimePosition,
+    },
+    /// Clip trimmed, retimed or otherwise edited in place.
+    Modified {
+        /// Track holding the clip.
+        track_id: u64,
+        /// Clip ID.
+        clip_id:  u64,
+        /// Names of the changed fields.
+        fields:   Vec<&'static str>,
+    },
+}
+
+/// A changed project setting, with both values rendered as text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingChange {
+    /// Setting name.
+    pub field: &'static str,
+    /// Old value.
+    pub from:  String,
+    /// New value.
+    pub to:    String,
+}
+
+/// Structural difference between two snapshots.
+///
+/// `Display` renders it as a change report, one line per change.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectDiff {
+    /// Track changes.
+    pub tracks:   Vec<TrackChange>,
+    /// Clip changes.
+    pub clips:    Vec<ClipChange>,
+    /// Setting changes.
+    pub settings: Vec<SettingChange>,
+}
+
+impl ProjectDiff {
+    /// Returns whether the snapshots are structurally identical.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty() && self.clips.is_empty() && self.settings.is_empty()
+    }
+}
+
+impl fmt::Display for ProjectDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes");
+        }
+        for change in &self.tracks {
+            match change {
+                TrackChange::Added { track_id, name } => {
+                    writeln!(f, "+ track {track_id} \"{name}\"")?;
+                },
+                TrackChange::Removed { track_id, name } => {
+                    writeln!(f, "- track {track_id} \"{name}\"")?;
+                },
+                TrackChange::Renamed { track_id, from, to } => {
+                    writeln!(f, "~ track {track_id} renamed \"{from}\" -> \"{to}\"")?;
+                },
+            }
+        }
+        for change in &self.clips {
+            match change {
+                ClipChange::Added { track_id, clip_id, start, duration } => writeln!(
+                    f,
+                    "+ clip {clip_id} on track {track_id} at {} ms ({} ms)",
+                    start.ms, duration.ms
+                )?,
+                ClipChange::Removed { track_id, clip_id, start, duration } => writeln!(
+                    f,
+                    "- clip {clip_id} on track {track_id} at {} ms ({} ms)",
+                    start.ms, duration.ms
+                )?,
+                ClipChange::Moved { clip_id, from_track, to_track, from, to } => writeln!(
+                    f,
+                    "> clip {clip_id} track {from_track} at {} ms -> track {to_track} at {} ms",
+                    from.ms, to.ms
+                )?,
+                ClipChange::Modified { track_id, clip_id, fields } => {
+                    writeln!(f, "~ clip {clip_id} on track {track_id}: {}", fields.join(", "))?
+                },
+            }
+        }
+        for change in &self.settings {
+            writeln!(f, "~ {}: {} -> {}", change.field, change.from, change.to)?;
+        }
+        Ok(())
+    }
+}
+
+fn clips(tracks: &[TimelineTrack]) -> impl Iterator<Item = (u64, &TimelineClip)> {
+    tracks.iter().flat_map(|t| t.clips.iter().map(move |c| (t.id, c)))
+}
+
+/// In-place clip edits; position is reported as a move.
+fn changed_fields(old: &TimelineClip, new: &TimelineClip) -> Vec<&'static str> {
+    [
+        ("duration", old.duration != new.duration),
+        ("in_point", old.in_point != new.in_point),
+        ("out_point", old.out_point != new.out_point),
+        ("speed", old.speed != new.speed),
+        ("pitch", old.pitch != new.pitch),
+        ("channel_map", old.channel_map != new.channel_map),
+        (
+            "audio_fades",
+            old.audio_fade_in != new.audio_fade_in || old.audio_fade_out != new.audio_fade_out,
+        ),
+        ("effect_ids", old.effect_ids != new.effect_ids),
+        ("enabled", old.enabled != new.enabled),
+        ("name", old.name != new.name),
+        ("source_id", old.source_id != new.source_id),
+    ]
+    .into_iter()
+    .filter_map(|(field, changed)| changed.then_some(field))
+    .collect()
+}
+
+fn setting_changes(old: &ProjectSettings, new: &ProjectSettings) -> Vec<SettingChange> {
+    let fields = [
+        ("timeline_width", old.timeline_width.to_string(), new.timeline_width.to_string()),
+        ("timeline_height", old.timeline_height.to_string(), new.timeline_height.to_string()),
+        (
+            "frame_rate",
+            format!("{}/{}", old.frame_rate_num, old.frame_rate_den),
+            format!("{}/{}", new.frame_rate_num, new.frame_rate_den),
+        ),
+        ("sample_rate", old.sample_rate.to_string(), new.sample_rate.to_string()),
+        ("color_space", old.color_space.clone(), new.color_space.clone()),
+        ("pixel_aspect", old.pixel_aspect.to_string(), new.pixel_aspect.to_string()),
+        ("color_depth", old.color_depth.to_string(), new.color_depth.to_string()),
+        ("preview_quality", old.preview_quality.to_string(), new.preview_quality.to_string()),
+        ("use_proxies", old.use_proxies.to_string(), new.use_proxies.to_string()),
+        ("autosave_interval", old.autosave_interval.to_string(), new.autosave_interval.to_string()),
+        ("max_undo_history", old.max_undo_history.to_string(), new.max_undo_history.to_string()),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, from, to)| from != to)
+        .map(|(field, from, to)| SettingChange { field, from, to })
+        .collect()
+}
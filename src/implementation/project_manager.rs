@@ -4,9 +4,28 @@
 //! Features: Project save/load, autosave, version control,
 //! recovery, project templates, and recent files.
 
+use std::path::{Path, PathBuf};
+
 use crate::{
+    bundle::{
+        BUNDLE_PROJECT_MEMBER, BUNDLE_TRACK_VIEW_MEMBER, BundleAssets, BundleMemberKind,
+        BundleSaveStats, ProjectBundle,
+    },
+    checksum::FileDigest,
+    converter::FormatConverter,
     errors::{VideoEditorError, VideoEditorResult},
-    types::Timestamp,
+    implementation::{
+        assets::AssetLibrary,
+        audio_mixer::{AudioFade, AudioFadeId, AudioFadeKind},
+        events::{EditorEvent, EventBus},
+        media_refs::{
+            CollectReport, ConsolidateOptions, ConsolidateReport, ConsolidateSegment,
+            MediaReference, RelinkReport, unique_target, walk_files,
+        },
+        project_history::{ProjectDiff, ProjectSnapshot},
+        timeline::TimelineManager,
+    },
+    types::{ClipGroup, PixelAspectRatio, TimePosition, Timestamp, TrackGroup, TrackViewState},
 };
 
 /// Unique identifier for a project.
@@ -191,6 +210,12 @@ impl ProjectSettings {
         }
     }
 
+    /// Returns the pixel aspect as a ratio.
+    #[must_use]
+    pub fn pixel_aspect_ratio(&self) -> PixelAspectRatio {
+        PixelAspectRatio::from_f64(self.pixel_aspect)
+    }
+
     /// Creates 4K settings.
     #[must_use]
     pub fn uhd_4k() -> Self {
@@ -230,27 +255,43 @@ pub struct AutosaveInfo {
 #[derive(Debug)]
 pub struct Project {
     /// Project identifier.
-    id:              ProjectId,
+    id:               ProjectId,
     /// File path (None if never saved).
-    path:            Option<String>,
+    path:             Option<String>,
     /// Project metadata.
-    metadata:        ProjectMetadata,
+    metadata:         ProjectMetadata,
     /// Project settings.
-    settings:        ProjectSettings,
+    settings:         ProjectSettings,
     /// Current state.
-    state:           ProjectState,
+    state:            ProjectState,
     /// Undo stack (serialized states).
-    undo_stack:      Vec<Vec<u8>>,
+    undo_stack:       Vec<Vec<u8>>,
     /// Redo stack (serialized states).
-    redo_stack:      Vec<Vec<u8>>,
+    redo_stack:       Vec<Vec<u8>>,
     /// Current undo index.
-    undo_index:      usize,
+    undo_index:       usize,
     /// Last autosave info.
-    last_autosave:   Option<AutosaveInfo>,
-    /// Asset paths referenced by project.
-    asset_paths:     Vec<String>,
+    last_autosave:    Option<AutosaveInfo>,
+    /// Media referenced by project.
+    media:            Vec<MediaReference>,
     /// Linked projects (for team workflows).
-    linked_projects: Vec<String>,
+    linked_projects:  Vec<String>,
+    /// Audio fades and crossfades with their curves.
+    audio_fades:      Vec<AudioFade>,
+    /// Next audio fade ID.
+    next_fade_id:     u64,
+    /// Linked clip groups.
+    clip_groups:      Vec<ClipGroup>,
+    /// Track groups.
+    track_groups:     Vec<TrackGroup>,
+    /// Track IDs bottom to top.
+    track_order:      Vec<u64>,
+    /// Track list display state, kept out of snapshots.
+    track_view:       TrackViewState,
+    /// Named snapshots, oldest first.
+    snapshots:        Vec<ProjectSnapshot>,
+    /// Next snapshot ID.
+    next_snapshot_id: u64,
 }
 
 impl Project {
@@ -267,8 +308,16 @@ impl Project {
             redo_stack: Vec::new(),
             undo_index: 0,
             last_autosave: None,
-            asset_paths: Vec::new(),
+            media: Vec::new(),
             linked_projects: Vec::new(),
+            audio_fades: Vec::new(),
+            next_fade_id: 1,
+            clip_groups: Vec::new(),
+            track_groups: Vec::new(),
+            track_order: Vec::new(),
+            track_view: TrackViewState::new(),
+            snapshots: Vec::new(),
+            next_snapshot_id: 1,
         }
     }
 
@@ -285,8 +334,22 @@ impl Project {
     }
 
     /// Sets the file path.
+    ///
+    /// References to media that is still in place are rebased so their
+    /// relative paths start from the new project directory. Missing media
+    /// keeps its relative path: the project was likely moved along with it.
     pub fn set_path(&mut self, path: impl Into<String>) {
         self.path = Some(path.into());
+        let dir = self.project_dir();
+        for media in self.media.iter_mut().filter(|m| Path::new(&m.absolute).is_file()) {
+            media.rebase(dir.as_deref());
+        }
+    }
+
+    /// Directory holding the project file.
+    fn project_dir(&self) -> Option<PathBuf> {
+        let path = Path::new(self.path.as_deref()?);
+        Some(path.parent().map(Path::to_path_buf).unwrap_or_default())
     }
 
     /// Returns the project metadata.
@@ -402,30 +465,360 @@ impl Project {
         self.undo_index = 0;
     }
 
-    /// Returns asset paths.
+    /// Returns the absolute paths of referenced media.
+    #[must_use]
+    pub fn asset_paths(&self) -> Vec<&str> {
+        self.media.iter().map(|m| m.absolute.as_str()).collect()
+    }
+
+    /// Returns the media references.
     #[must_use]
-    pub fn asset_paths(&self) -> &[String] {
-        &self.asset_paths
+    pub fn media(&self) -> &[MediaReference] {
+        &self.media
     }
 
-    /// Adds an asset path.
+    /// Adds an asset path without hashing it. Relative paths resolve
+    /// against the project directory.
     pub fn add_asset_path(&mut self, path: impl Into<String>) {
-        let path = path.into();
-        if !self.asset_paths.contains(&path) {
-            self.asset_paths.push(path);
+        let reference = MediaReference::new(&path.into(), self.project_dir().as_deref());
+        if !self.media.iter().any(|m| m.absolute == reference.absolute) {
+            self.media.push(reference);
         }
     }
 
+    /// Adds a media file and records its content digest, so relinking can
+    /// tell it apart from other files with the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn add_media(&mut self, path: &str) -> VideoEditorResult<()> {
+        let reference = MediaReference::hashed(path, self.project_dir().as_deref())?;
+        match self.media.iter_mut().find(|m| m.absolute == reference.absolute) {
+            Some(existing) => *existing = reference,
+            None => self.media.push(reference),
+        }
+        Ok(())
+    }
+
     /// Removes an asset path.
     pub fn remove_asset_path(&mut self, path: &str) -> bool {
-        if let Some(pos) = self.asset_paths.iter().position(|p| p == path) {
-            self.asset_paths.remove(pos);
+        let absolute = MediaReference::new(path, self.project_dir().as_deref()).absolute;
+        if let Some(pos) = self.media.iter().position(|m| m.absolute == absolute) {
+            self.media.remove(pos);
             true
         } else {
             false
         }
     }
 
+    /// Copies every referenced file into `target_dir` and points the
+    /// references at the copies, making the folder portable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the folder cannot be created or a copy fails.
+    pub fn collect_media(
+        &mut self, target_dir: impl AsRef<Path>,
+    ) -> VideoEditorResult<CollectReport> {
+        self.collect_media_with(target_dir, |source, dir| {
+            let name = source.file_name().map_or_else(|| "media".into(), |n| n.to_string_lossy());
+            let target = unique_target(dir, &name);
+            std::fs::copy(source, &target).map_err(|e| VideoEditorError::io(source, e))?;
+            Ok(target)
+        })
+    }
+
+    /// Like [`collect_media`](Self::collect_media), with `write` producing
+    /// each file in the target folder from its source (to transcode into a
+    /// delivery codec, say) and returning the written path.
+    ///
+    /// Media found at neither its absolute nor its relative path is listed
+    /// as missing and left untouched. Files referenced twice are written
+    /// once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the folder cannot be created or `write` fails.
+    pub fn collect_media_with(
+        &mut self, target_dir: impl AsRef<Path>,
+        mut write: impl FnMut(&Path, &Path) -> VideoEditorResult<PathBuf>,
+    ) -> VideoEditorResult<CollectReport> {
+        let target_dir = target_dir.as_ref();
+        std::fs::create_dir_all(target_dir).map_err(|e| VideoEditorError::io(target_dir, e))?;
+        let project_dir = self.project_dir();
+        let mut report = CollectReport::default();
+        let mut written: Vec<(PathBuf, PathBuf)> = Vec::new();
+
+        for media in &mut self.media {
+            let Some(source) = media.resolve(project_dir.as_deref()) else {
+                report.missing.push(media.absolute.clone());
+                continue;
+            };
+            let target = match written.iter().find(|(from, _)| *from == source) {
+                Some((_, to)) => to.clone(),
+                None => {
+                    let target = write(&source, target_dir)?;
+                    report.bytes += std::fs::metadata(&target).map_or(0, |m| m.len());
+                    report.collected.push((
+                        source.to_string_lossy().into_owned(),
+                        target.to_string_lossy().into_owned(),
+                    ));
+                    written.push((source, target.clone()));
+                    target
+                },
+            };
+            media.relocate(&target, project_dir.as_deref());
+            if media.digest.is_some() {
+                media.digest = FileDigest::of_file(&target).ok();
+            }
+        }
+        if !report.collected.is_empty() {
+            self.mark_modified();
+        }
+        Ok(report)
+    }
+
+    /// Reduces the project to the media its timeline uses, for archiving.
+    ///
+    /// Each used range of a video or audio asset, plus handles, is
+    /// transcoded by `converter` into `target_dir`. Clips are pointed at
+    /// the trimmed copies and the original assets and references are
+    /// dropped; the original files themselves are left on disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the folder cannot be created or a conversion
+    /// fails.
+    pub fn consolidate(
+        &mut self, timeline: &mut TimelineManager, assets: &mut AssetLibrary,
+        target_dir: impl AsRef<Path>, options: &ConsolidateOptions, converter: &FormatConverter,
+    ) -> VideoEditorResult<ConsolidateReport> {
+        let extension = converter.options().output_format.extension();
+        self.consolidate_with(timeline, assets, target_dir, options, |segment, dir| {
+            let target = unique_target(dir, &segment.file_name(extension));
+            converter.convert_range(
+                &segment.source.to_string_lossy(),
+                &target.to_string_lossy(),
+                segment.start,
+                segment.end,
+            )?;
+            Ok(target)
+        })
+    }
+
+    /// Like [`consolidate`](Self::consolidate), with `write` producing
+    /// each segment in the target folder and returning the written path.
+    ///
+    /// Clip source ranges overlapping once handles are added share one
+    /// segment. Used media that cannot be found is listed as missing and
+    /// left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the folder cannot be created or `write` fails.
+    pub fn consolidate_with(
+        &mut self, timeline: &mut TimelineManager, assets: &mut AssetLibrary,
+        target_dir: impl AsRef<Path>, options: &ConsolidateOptions,
+        mut write: impl FnMut(&ConsolidateSegment, &Path) -> VideoEditorResult<PathBuf>,
+    ) -> VideoEditorResult<ConsolidateReport> {
+        let target_dir = target_dir.as_ref();
+        std::fs::create_dir_all(target_dir).map_err(|e| VideoEditorError::io(target_dir, e))?;
+        let project_dir = self.project_dir();
+        let mut report = ConsolidateReport::default();
+
+        let sources: Vec<(u64, String, TimePosition)> = assets
+            .video_clips()
+            .iter()
+            .map(|c| (c.id, c.path.clone(), c.duration))
+            .chain(assets.audio_clips().iter().map(|c| (c.id, c.path.clone(), c.duration)))
+            .collect();
+        for (asset_id, path, duration) in sources {
+            let mut ranges: Vec<(u64, u64)> = timeline
+                .tracks()
+                .iter()
+                .flat_map(|t| &t.clips)
+                .filter(|c| c.source_id == asset_id)
+                .map(|c| {
+                    let start = c.in_point.ms.saturating_sub(options.handles.ms);
+                    let end = c.out_point.ms.max(c.in_point.ms) + options.handles.ms;
+                    (start, if duration.ms > 0 { end.min(duration.ms) } else { end })
+                })
+                .collect();
+            if ranges.is_empty() {
+                continue;
+            }
+            let reference = MediaReference::new(&path, project_dir.as_deref());
+            let existing = self.media.iter().find(|m| m.absolute == reference.absolute);
+            let hashed = existing.is_some_and(|m| m.digest.is_some());
+            let Some(source) = existing.unwrap_or(&reference).resolve(project_dir.as_deref())
+            else {
+                report.missing.push(reference.absolute);
+                continue;
+            };
+
+            ranges.sort_unstable();
+            let mut merged: Vec<(u64, u64)> = Vec::new();
+            for (start, end) in ranges {
+                match merged.last_mut() {
+                    Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                    _ => merged.push((start, end)),
+                }
+            }
+
+            report.source_bytes += std::fs::metadata(&source).map_or(0, |m| m.len());
+            for (start, end) in merged {
+                let segment = ConsolidateSegment {
+                    asset_id,
+                    source: source.clone(),
+                    start: TimePosition::from_ms(start),
+                    end: TimePosition::from_ms(end),
+                };
+                let target = write(&segment, target_dir)?;
+                report.bytes += std::fs::metadata(&target).map_or(0, |m| m.len());
+                let target = target.to_string_lossy().into_owned();
+                let new_id = assets
+                    .add_trimmed_copy(asset_id, &target, TimePosition::from_ms(end - start))
+                    .ok_or_else(|| VideoEditorError::Asset(format!("Asset {asset_id} vanished")))?;
+
+                let clips = timeline.tracks_mut().iter_mut().flat_map(|t| &mut t.clips);
+                for clip in clips.filter(|c| {
+                    c.source_id == asset_id && c.in_point.ms >= start && c.out_point.ms <= end
+                }) {
+                    clip.source_id = new_id;
+                    clip.in_point = TimePosition::from_ms(clip.in_point.ms - start);
+                    clip.out_point = TimePosition::from_ms(clip.out_point.ms - start);
+                }
+                if hashed && Path::new(&target).is_file() {
+                    self.add_media(&target)?;
+                } else {
+                    self.add_asset_path(target.clone());
+                }
+                report.segments.push((asset_id, new_id, target));
+            }
+            assets.remove_asset(asset_id);
+            self.media.retain(|m| m.absolute != reference.absolute);
+        }
+        if !report.segments.is_empty() {
+            self.mark_modified();
+        }
+        Ok(report)
+    }
+
+    /// Fixes references whose file has moved.
+    ///
+    /// Media is first looked for at its project-relative path, then by
+    /// file name under `search_roots`, and finally, for hashed media, by
+    /// digest so renamed files are found too. Hashed media is only relinked
+    /// to a file with the same digest.
+    pub fn relink(&mut self, search_roots: &[impl AsRef<Path>]) -> RelinkReport {
+        let project_dir = self.project_dir();
+        let mut report = RelinkReport::default();
+        let mut candidates: Option<Vec<PathBuf>> = None;
+
+        for media in &mut self.media {
+            if Path::new(&media.absolute).is_file() {
+                continue;
+            }
+            let found = media.resolve(project_dir.as_deref()).or_else(|| {
+                let files = candidates.get_or_insert_with(|| {
+                    search_roots.iter().flat_map(|root| walk_files(root.as_ref())).collect()
+                });
+                let name = media.file_name();
+                files
+                    .iter()
+                    .filter(|f| f.file_name().and_then(|n| n.to_str()) == name)
+                    .find(|f| media.matches(f))
+                    .or_else(|| media.digest.and(files.iter().find(|f| media.matches(f))))
+                    .cloned()
+            });
+            match found {
+                Some(path) => {
+                    let old = media.absolute.clone();
+                    media.relocate(&path, project_dir.as_deref());
+                    report.relinked.push((old, media.absolute.clone()));
+                },
+                None => report.missing.push(media.absolute.clone()),
+            }
+        }
+        if !report.relinked.is_empty() {
+            self.mark_modified();
+        }
+        report
+    }
+
+    /// Saves the project document, track list display state and assets
+    /// into an `.evproj` bundle, creating it if needed.
+    ///
+    /// Assets are stored under their kind's folder by file name. Members
+    /// that are no longer part of the project are dropped, and only changed
+    /// members are written; hashed media is compared by digest without
+    /// being read. Media that cannot be found is left out of the bundle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bundle or an asset cannot be accessed.
+    pub fn save_bundle(
+        &self, path: impl AsRef<Path>, project_json: &str, assets: &BundleAssets,
+    ) -> VideoEditorResult<BundleSaveStats> {
+        let mut bundle = ProjectBundle::open_or_create(path)?;
+        let mut names = vec![BUNDLE_PROJECT_MEMBER.to_string()];
+        if !self.track_view.is_empty() {
+            names.push(BUNDLE_TRACK_VIEW_MEMBER.to_string());
+        }
+        let mut member_name = |kind: BundleMemberKind, file: &Path| {
+            let folder = kind.folder().unwrap_or_default();
+            let file_name =
+                file.file_name().map_or_else(|| "asset".into(), |n| n.to_string_lossy());
+            let (stem, extension) = match file_name.rsplit_once('.') {
+                Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+                _ => (file_name.as_ref(), String::new()),
+            };
+            let name = std::iter::once(format!("{folder}/{file_name}"))
+                .chain((2..).map(|n| format!("{folder}/{stem}_{n}{extension}")))
+                .find(|name| !names.contains(name))
+                .unwrap_or_default();
+            names.push(name.clone());
+            name
+        };
+
+        bundle.put_project(project_json);
+        if !self.track_view.is_empty() {
+            bundle.put(BUNDLE_TRACK_VIEW_MEMBER, self.track_view.to_bytes())?;
+        }
+        for (kind, files) in [
+            (BundleMemberKind::Proxy, &assets.proxies),
+            (BundleMemberKind::Thumbnail, &assets.thumbnails),
+            (BundleMemberKind::Lut, &assets.luts),
+        ] {
+            for file in files {
+                bundle.put_file(&member_name(kind, file), file)?;
+            }
+        }
+        if assets.include_media {
+            let project_dir = self.project_dir();
+            let mut packed: Vec<PathBuf> = Vec::new();
+            for media in &self.media {
+                let Some(source) = media.resolve(project_dir.as_deref()) else {
+                    continue;
+                };
+                if packed.contains(&source) {
+                    continue;
+                }
+                let name = member_name(BundleMemberKind::Media, &source);
+                match media.digest {
+                    Some(digest) => {
+                        bundle.put_file_hashed(&name, &source, digest.size, digest.xxh64)?
+                    },
+                    None => bundle.put_file(&name, &source)?,
+                }
+                packed.push(source);
+            }
+        }
+        bundle.retain(|name| names.iter().any(|n| n == name));
+        bundle.commit()
+    }
+
     /// Records autosave.
     pub fn record_autosave(&mut self, path: impl Into<String>) {
         self.last_autosave = Some(AutosaveInfo {
@@ -440,6 +833,194 @@ impl Project {
     pub fn last_autosave(&self) -> Option<&AutosaveInfo> {
         self.last_autosave.as_ref()
     }
+
+    /// Adds an audio fade with linear curves.
+    pub fn add_audio_fade(
+        &mut self, track_id: u64, kind: AudioFadeKind, start: TimePosition, duration: TimePosition,
+    ) -> AudioFadeId {
+        let id = AudioFadeId::new(self.next_fade_id);
+        self.next_fade_id += 1;
+        self.audio_fades.push(AudioFade::new(id, track_id, kind, start, duration));
+        self.mark_modified();
+        id
+    }
+
+    /// Gets an audio fade by ID.
+    #[must_use]
+    pub fn audio_fade(&self, id: AudioFadeId) -> Option<&AudioFade> {
+        self.audio_fades.iter().find(|f| f.id == id)
+    }
+
+    /// Gets a mutable audio fade by ID (for curve editing).
+    pub fn audio_fade_mut(&mut self, id: AudioFadeId) -> Option<&mut AudioFade> {
+        let pos = self.audio_fades.iter().position(|f| f.id == id)?;
+        self.mark_modified();
+        self.audio_fades.get_mut(pos)
+    }
+
+    /// Removes an audio fade.
+    pub fn remove_audio_fade(&mut self, id: AudioFadeId) -> bool {
+        if let Some(pos) = self.audio_fades.iter().position(|f| f.id == id) {
+            self.audio_fades.remove(pos);
+            self.mark_modified();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns all audio fades.
+    #[must_use]
+    pub fn audio_fades(&self) -> &[AudioFade] {
+        &self.audio_fades
+    }
+
+    /// Returns the fades active on a track at a timeline time.
+    pub fn audio_fades_at(
+        &self, track_id: u64, time: TimePosition,
+    ) -> impl Iterator<Item = &AudioFade> {
+        self.audio_fades.iter().filter(move |f| f.track_id == track_id && f.contains(time))
+    }
+
+    /// Returns the linked clip groups saved with the project.
+    #[must_use]
+    pub fn clip_groups(&self) -> &[ClipGroup] {
+        &self.clip_groups
+    }
+
+    /// Replaces the saved clip groups.
+    pub fn set_clip_groups(&mut self, groups: Vec<ClipGroup>) {
+        self.clip_groups = groups;
+        self.mark_modified();
+    }
+
+    /// Returns the track groups saved with the project.
+    #[must_use]
+    pub fn track_groups(&self) -> &[TrackGroup] {
+        &self.track_groups
+    }
+
+    /// Replaces the saved track groups.
+    pub fn set_track_groups(&mut self, groups: Vec<TrackGroup>) {
+        self.track_groups = groups;
+        self.mark_modified();
+    }
+
+    /// Returns the saved track order, bottom to top.
+    #[must_use]
+    pub fn track_order(&self) -> &[u64] {
+        &self.track_order
+    }
+
+    /// Replaces the saved track order.
+    pub fn set_track_order(&mut self, order: Vec<u64>) {
+        self.track_order = order;
+        self.mark_modified();
+    }
+
+    /// Returns the track list display state.
+    #[must_use]
+    pub fn track_view(&self) -> &TrackViewState {
+        &self.track_view
+    }
+
+    /// Returns the mutable track list display state. Changes are saved with
+    /// the project but stay out of undo history.
+    pub fn track_view_mut(&mut self) -> &mut TrackViewState {
+        self.mark_modified();
+        &mut self.track_view
+    }
+
+    /// Loads the track list display state saved in a bundle. Bundles
+    /// without one reset it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the saved state cannot be read or parsed.
+    pub fn load_track_view(&mut self, bundle: &ProjectBundle) -> VideoEditorResult<()> {
+        self.track_view = if bundle.contains(BUNDLE_TRACK_VIEW_MEMBER) {
+            TrackViewState::from_bytes(&bundle.read(BUNDLE_TRACK_VIEW_MEMBER)?).ok_or_else(
+                || VideoEditorError::Project("Bundled track view state is corrupt".into()),
+            )?
+        } else {
+            TrackViewState::new()
+        };
+        Ok(())
+    }
+
+    /// Returns the named snapshots, oldest first.
+    #[must_use]
+    pub fn snapshots(&self) -> &[ProjectSnapshot] {
+        &self.snapshots
+    }
+
+    /// Gets a snapshot by ID.
+    #[must_use]
+    pub fn snapshot(&self, id: u64) -> Option<&ProjectSnapshot> {
+        self.snapshots.iter().find(|s| s.id == id)
+    }
+
+    /// Records the current edit of `timeline` under `name` and returns the
+    /// snapshot ID.
+    pub fn create_snapshot(&mut self, name: impl Into<String>, timeline: &TimelineManager) -> u64 {
+        let id = self.next_snapshot_id;
+        self.next_snapshot_id += 1;
+        self.snapshots.push(ProjectSnapshot {
+            id,
+            name: name.into(),
+            created_at: Timestamp::now(),
+            settings: self.settings.clone(),
+            tracks: timeline.tracks().to_vec(),
+            clip_groups: self.clip_groups.clone(),
+            track_groups: self.track_groups.clone(),
+        });
+        id
+    }
+
+    /// Removes a snapshot.
+    pub fn remove_snapshot(&mut self, id: u64) -> bool {
+        let before = self.snapshots.len();
+        self.snapshots.retain(|s| s.id != id);
+        self.snapshots.len() != before
+    }
+
+    /// Puts a snapshot's edit back into `timeline` and the project.
+    ///
+    /// The current state is snapshotted first as "Before restoring <name>",
+    /// so a restore can itself be undone; returns that snapshot's ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no snapshot with that ID.
+    pub fn restore_snapshot(
+        &mut self, id: u64, timeline: &mut TimelineManager,
+    ) -> VideoEditorResult<u64> {
+        let snapshot = self.snapshot(id).cloned().ok_or_else(|| {
+            VideoEditorError::timeline("restore_snapshot", format!("Snapshot {id} not found"))
+        })?;
+        let backup = self.create_snapshot(format!("Before restoring {}", snapshot.name), timeline);
+        *timeline.tracks_mut() = snapshot.tracks;
+        timeline.recalculate_duration();
+        self.settings = snapshot.settings;
+        self.clip_groups = snapshot.clip_groups;
+        self.track_groups = snapshot.track_groups;
+        self.mark_modified();
+        Ok(backup)
+    }
+
+    /// Structural changes from snapshot `from` to snapshot `to`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either snapshot does not exist.
+    pub fn diff_snapshots(&self, from: u64, to: u64) -> VideoEditorResult<ProjectDiff> {
+        let find = |id| {
+            self.snapshot(id).ok_or_else(|| {
+                VideoEditorError::timeline("diff_snapshots", format!("Snapshot {id} not found"))
+            })
+        };
+        Ok(find(from)?.diff(find(to)?))
+    }
 }
 
 /// Recent file entry.
@@ -565,6 +1146,8 @@ pub struct ProjectManager {
     autosave_enabled:    bool,
     /// Last autosave check time.
     last_autosave_check: Option<Timestamp>,
+    /// Change notifications.
+    events:              EventBus,
 }
 
 impl ProjectManager {
@@ -579,9 +1162,15 @@ impl ProjectManager {
             max_recent:          20,
             autosave_enabled:    true,
             last_autosave_check: None,
+            events:              EventBus::new(),
         }
     }
 
+    /// Emits change events on a shared bus.
+    pub fn set_event_bus(&mut self, events: EventBus) {
+        self.events = events;
+    }
+
     /// Returns built-in templates.
     fn builtin_templates() -> Vec<ProjectTemplate> {
         vec![
@@ -605,17 +1194,16 @@ impl ProjectManager {
         if let Some(project) = &self.current_project
             && project.has_unsaved_changes()
         {
-            return Err(VideoEditorError::Io(
-                "Current project has unsaved changes".into(),
-            ));
+            return Err(VideoEditorError::Project("Current project has unsaved changes".into()));
         }
 
         let id = self.next_id();
         self.current_project = Some(Project::new(id, name));
 
+        self.emit_created();
         self.current_project
             .as_mut()
-            .ok_or_else(|| VideoEditorError::Io("Failed to create project".into()))
+            .ok_or_else(|| VideoEditorError::Project("Failed to create project".into()))
     }
 
     /// Creates a project from a template.
@@ -626,15 +1214,15 @@ impl ProjectManager {
             .templates
             .iter()
             .find(|t| t.name == template_name)
-            .ok_or_else(|| VideoEditorError::Io(format!("Template not found: {template_name}")))?
+            .ok_or_else(|| {
+                VideoEditorError::Project(format!("Template not found: {template_name}"))
+            })?
             .clone();
 
         if let Some(project) = &self.current_project
             && project.has_unsaved_changes()
         {
-            return Err(VideoEditorError::Io(
-                "Current project has unsaved changes".into(),
-            ));
+            return Err(VideoEditorError::Project("Current project has unsaved changes".into()));
         }
 
         let id = self.next_id();
@@ -643,9 +1231,10 @@ impl ProjectManager {
 
         self.current_project = Some(project);
 
+        self.emit_created();
         self.current_project
             .as_mut()
-            .ok_or_else(|| VideoEditorError::Io("Failed to create project".into()))
+            .ok_or_else(|| VideoEditorError::Project("Failed to create project".into()))
     }
 
     /// Returns the current project.
@@ -664,12 +1253,36 @@ impl ProjectManager {
         if let Some(project) = &self.current_project
             && project.has_unsaved_changes()
         {
-            return Err(VideoEditorError::Io("Project has unsaved changes".into()));
+            return Err(VideoEditorError::Project("Project has unsaved changes".into()));
         }
-        self.current_project = None;
+        if self.current_project.take().is_some() {
+            self.events.emit(EditorEvent::ProjectClosed);
+        }
+        Ok(())
+    }
+
+    /// Records that the host saved the current project to `path`.
+    pub fn mark_project_saved(&mut self, path: impl Into<String>) -> VideoEditorResult<()> {
+        let path = path.into();
+        let project = self
+            .current_project
+            .as_mut()
+            .ok_or_else(|| VideoEditorError::Project("No project open".into()))?;
+        project.set_path(path.clone());
+        project.mark_saved();
+        let name = project.metadata().name.clone();
+        self.add_recent(path.clone(), name);
+        self.events.emit(EditorEvent::ProjectSaved { path });
         Ok(())
     }
 
+    fn emit_created(&self) {
+        if let Some(project) = &self.current_project {
+            let name = project.metadata().name.clone();
+            self.events.emit(EditorEvent::ProjectCreated { name });
+        }
+    }
+
     /// Adds a file to recent files.
     pub fn add_recent(&mut self, path: impl Into<String>, name: impl Into<String>) {
         let path = path.into();
@@ -763,6 +1376,7 @@ impl Default for ProjectManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{TrackType, timeline::TimelineClip};
 
     #[test]
     fn test_project_creation() {
@@ -810,6 +1424,363 @@ mod tests {
         assert!(project.can_redo());
     }
 
+    #[test]
+    fn test_collect_and_relink_media() {
+        let root = std::env::temp_dir().join(format!("evep_relink_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let media = root.join("shoot");
+        std::fs::create_dir_all(media.join("day1")).expect("test assertion");
+        std::fs::create_dir_all(media.join("day2")).expect("test assertion");
+        let take = media.join("day1/take.mov");
+        let other_take = media.join("day2/take.mov");
+        std::fs::write(&take, b"take one").expect("test assertion");
+        std::fs::write(&other_take, b"a different take").expect("test assertion");
+        std::fs::write(media.join("day1/music.wav"), b"music").expect("test assertion");
+
+        let mut project = Project::new(ProjectId::new(1), "Portable");
+        project.set_path(root.join("edit/show.evproj").to_string_lossy());
+        project.add_media(&take.to_string_lossy()).expect("test assertion");
+        project.add_asset_path("../shoot/day1/music.wav");
+        project.add_asset_path("../shoot/day1/music.wav");
+        assert_eq!(project.media().len(), 2);
+        assert_eq!(project.media()[0].relative.as_deref(), Some("../shoot/day1/take.mov"));
+        assert!(project.media()[1].absolute.ends_with("music.wav"));
+        assert!(project.add_media("/nonexistent/clip.mov").is_err());
+
+        // Gather into a portable folder next to the project
+        let collected = project.collect_media(root.join("edit/media")).expect("test assertion");
+        assert_eq!(collected.collected.len(), 2);
+        assert_eq!(collected.bytes, 13);
+        assert_eq!(project.media()[0].relative.as_deref(), Some("media/take.mov"));
+
+        // Move the whole edit folder; relative paths still resolve
+        std::fs::rename(root.join("edit"), root.join("moved")).expect("test assertion");
+        project.set_path(root.join("moved/show.evproj").to_string_lossy());
+        let report = project.relink(&[&media]);
+        assert_eq!(report.relinked.len(), 2);
+        assert!(report.missing.is_empty());
+        assert!(project.media()[0].absolute.contains("moved"));
+
+        // Lost media: the same name under a search root only relinks when
+        // the digest matches
+        std::fs::remove_file(project.media()[0].absolute.clone()).expect("test assertion");
+        std::fs::remove_file(project.media()[1].absolute.clone()).expect("test assertion");
+        let report = project.relink(&[&media]);
+        assert_eq!(report.relinked.len(), 2);
+        assert_eq!(Path::new(&project.media()[0].absolute), take);
+        assert!(project.media()[1].absolute.ends_with("music.wav"));
+
+        std::fs::remove_file(&take).expect("test assertion");
+        let report = project.relink(&[&media]);
+        assert_eq!(report.missing.len(), 1);
+        assert!(project.remove_asset_path(&take.to_string_lossy()));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_consolidate_media() {
+        let root = std::env::temp_dir().join(format!("evep_consolidate_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("shoot")).expect("test assertion");
+        let take = root.join("shoot/take.mov");
+        std::fs::write(&take, vec![7u8; 10_000]).expect("test assertion");
+
+        let mut assets = AssetLibrary::new();
+        let take_id = assets.import_video(&take.to_string_lossy()).expect("test assertion");
+        let unused = assets.import_audio("/nonexistent/room_tone.wav").expect("test assertion");
+        assets.add_to_bin("Selects", take_id);
+        let mut timeline = TimelineManager::new();
+        let track = timeline.add_track("V1", TrackType::Video);
+        for (id, start, source_in, source_out) in
+            [(1, 0, 1000, 3000), (2, 2000, 2500, 4000), (3, 3500, 8000, 9000)]
+        {
+            let mut clip = TimelineClip::new(
+                id,
+                take_id,
+                TimePosition::from_ms(start),
+                TimePosition::from_ms(source_out - source_in),
+            );
+            clip.in_point = TimePosition::from_ms(source_in);
+            clip.out_point = TimePosition::from_ms(source_out);
+            timeline.add_clip(track, clip).expect("test assertion");
+        }
+
+        let mut project = Project::new(ProjectId::new(1), "Archive");
+        project.set_path(root.join("edit/show.evproj").to_string_lossy());
+        project.add_media(&take.to_string_lossy()).expect("test assertion");
+
+        // Writes a tenth of a byte per kept millisecond
+        let mut written = Vec::new();
+        let report = project
+            .consolidate_with(
+                &mut timeline,
+                &mut assets,
+                root.join("edit/consolidated"),
+                &ConsolidateOptions::default(),
+                |segment, dir| {
+                    written.push((segment.start.ms, segment.end.ms));
+                    let target = dir.join(segment.file_name("mov"));
+                    let size = (segment.end.ms - segment.start.ms) / 10;
+                    std::fs::write(&target, vec![0u8; size as usize])
+                        .map_err(|e| VideoEditorError::io(&target, e))?;
+                    Ok(target)
+                },
+            )
+            .expect("test assertion");
+
+        // Overlapping handles merge; the last range stops at the media end
+        assert_eq!(written, [(0, 5000), (7000, 10000)]);
+        assert_eq!(report.segments.len(), 2);
+        assert!(report.segments[1].2.ends_with("take_7000-10000ms.mov"));
+        assert_eq!((report.source_bytes, report.bytes), (10_000, 800));
+        assert_eq!(report.reclaimed_bytes(), 9200);
+        assert!(report.missing.is_empty());
+
+        let new_ids: Vec<u64> = report.segments.iter().map(|s| s.1).collect();
+        assert!(assets.video_clip(take_id).is_none());
+        assert!(assets.audio_clip(unused).is_some());
+        assert_eq!(assets.bin("Selects"), Some(&new_ids[..]));
+        let tail = assets.video_clip(new_ids[1]).expect("test assertion");
+        assert_eq!(tail.duration, TimePosition::from_ms(3000));
+        assert_eq!(tail.frame_count, 90);
+        let clips = &timeline.tracks()[0].clips;
+        assert_eq!(clips[1].source_id, new_ids[0]);
+        assert_eq!(clips[2].source_id, new_ids[1]);
+        assert_eq!((clips[2].in_point.ms, clips[2].out_point.ms), (1000, 2000));
+
+        assert_eq!(project.media().len(), 2);
+        assert!(project.media().iter().all(|m| m.digest.is_some()));
+        assert_eq!(project.media()[0].relative.as_deref(), Some("consolidated/take_0-5000ms.mov"));
+        assert!(project.has_unsaved_changes());
+
+        let converter = FormatConverter::new();
+        let at = TimePosition::from_secs(5);
+        assert!(converter.convert_range(&take.to_string_lossy(), "out.evlf", at, at).is_err());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_save_bundle() {
+        let root = std::env::temp_dir().join(format!("evep_bundle_save_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("a")).expect("test assertion");
+        std::fs::create_dir_all(root.join("b")).expect("test assertion");
+        for (path, data) in [("a/clip.mov", "clip a"), ("b/clip.mov", "clip b"), ("a.cube", "lut")]
+        {
+            std::fs::write(root.join(path), data).expect("test assertion");
+        }
+
+        let mut project = Project::new(ProjectId::new(1), "Bundled");
+        project.add_media(&root.join("a/clip.mov").to_string_lossy()).expect("test assertion");
+        project.add_media(&root.join("b/clip.mov").to_string_lossy()).expect("test assertion");
+        let bundle_path = root.join("show.evproj");
+        let mut assets = BundleAssets { luts: vec![root.join("a.cube")], ..Default::default() };
+        assets.include_media = true;
+
+        let stats = project.save_bundle(&bundle_path, "{}", &assets).expect("test assertion");
+        assert_eq!(
+            stats.written,
+            vec!["project.json", "luts/a.cube", "media/clip.mov", "media/clip_2.mov"]
+        );
+
+        // Unchanged media is skipped; dropped assets leave the bundle
+        assets.luts.clear();
+        let stats = project.save_bundle(&bundle_path, "{}", &assets).expect("test assertion");
+        assert!(stats.written.is_empty());
+        assert_eq!((stats.unchanged, stats.removed), (3, 1));
+        let bundle = ProjectBundle::open(&bundle_path).expect("test assertion");
+        assert_eq!(bundle.read("media/clip_2.mov").expect("test assertion"), b"clip b");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_bundle_track_view() {
+        use crate::types::TrackView;
+
+        let root = std::env::temp_dir().join(format!("evep_bundle_view_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).expect("test assertion");
+        let bundle_path = root.join("show.evproj");
+
+        let mut project = Project::new(ProjectId::new(1), "Bundled");
+        let assets = BundleAssets::default();
+        let stats = project.save_bundle(&bundle_path, "{}", &assets).expect("test assertion");
+        assert_eq!(stats.written, vec!["project.json"]);
+
+        let view =
+            TrackView { height: 96, color: Some([0.2, 0.4, 0.6, 1.0]), collapsed: true };
+        project.track_view_mut().set_view(3, view);
+        project.track_view_mut().set_order(&[3, 1, 3]);
+        let stats = project.save_bundle(&bundle_path, "{}", &assets).expect("test assertion");
+        assert_eq!(stats.written, vec![BUNDLE_TRACK_VIEW_MEMBER]);
+
+        let mut reopened = Project::new(ProjectId::new(2), "Reopened");
+        let bundle = ProjectBundle::open(&bundle_path).expect("test assertion");
+        reopened.load_track_view(&bundle).expect("test assertion");
+        assert_eq!(reopened.track_view().view(3), Some(&view));
+        assert_eq!(reopened.track_view().order(), [3, 1]);
+
+        // Snapshots carry the edit, not the view
+        let timeline = TimelineManager::new();
+        let snapshot = project.create_snapshot("Cut", &timeline);
+        project.track_view_mut().clear_view(3);
+        project.restore_snapshot(snapshot, &mut TimelineManager::new()).expect("test assertion");
+        assert!(project.track_view().view(3).is_none());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_snapshot_diff_and_restore() {
+        use crate::{
+            implementation::project_history::ClipChange,
+            types::{TrackType, timeline::TimelineClip},
+        };
+
+        let mut timeline = TimelineManager::new();
+        let v1 = timeline.add_track("V1", TrackType::Video);
+        for id in 1..=3 {
+            let start = TimePosition::from_ms((id - 1) * 1_000);
+            let clip = TimelineClip::new(id, 1, start, TimePosition::from_ms(1_000));
+            timeline.add_clip(v1, clip).expect("test assertion");
+        }
+        let mut project = Project::new(ProjectId::new(1), "History");
+        let rough = project.create_snapshot("Rough cut", &timeline);
+
+        let v2 = timeline.add_track("V2", TrackType::Video);
+        timeline.remove_clip(2).expect("test assertion");
+        timeline.move_clip(3, TimePosition::from_ms(5_000)).expect("test assertion");
+        timeline.get_track_mut(v1).expect("test assertion").clips[0].speed = 2.0;
+        let clip = TimelineClip::new(4, 2, TimePosition::from_ms(0), TimePosition::from_ms(500));
+        timeline.add_clip(v2, clip).expect("test assertion");
+        project.settings_mut().frame_rate_num = 24;
+        let fine = project.create_snapshot("Fine cut", &timeline);
+
+        let diff = project.diff_snapshots(rough, fine).expect("test assertion");
+        assert_eq!(diff.tracks.len(), 1);
+        assert_eq!(
+            diff.clips,
+            vec![
+                ClipChange::Modified { track_id: v1, clip_id: 1, fields: vec!["speed"] },
+                ClipChange::Moved {
+                    clip_id:    3,
+                    from_track: v1,
+                    to_track:   v1,
+                    from:       TimePosition::from_ms(2_000),
+                    to:         TimePosition::from_ms(5_000),
+                },
+                ClipChange::Added {
+                    track_id: v2,
+                    clip_id:  4,
+                    start:    TimePosition::from_ms(0),
+                    duration: TimePosition::from_ms(500),
+                },
+                ClipChange::Removed {
+                    track_id: v1,
+                    clip_id:  2,
+                    start:    TimePosition::from_ms(1_000),
+                    duration: TimePosition::from_ms(1_000),
+                },
+            ]
+        );
+        let report = diff.to_string();
+        assert!(report.contains("+ track 2 \"V2\""));
+        assert!(report.contains("~ frame_rate: 30/1 -> 24/1"));
+        assert!(project.diff_snapshots(rough, rough).expect("test assertion").is_empty());
+
+        let backup = project.restore_snapshot(rough, &mut timeline).expect("test assertion");
+        assert_eq!(timeline.tracks().len(), 1);
+        assert_eq!(project.settings().frame_rate_num, 30);
+        assert!(project.diff_snapshots(rough, backup).expect("test assertion") == diff);
+        assert!(project.restore_snapshot(99, &mut timeline).is_err());
+        assert_eq!(project.snapshots().len(), 3);
+    }
+
+    #[test]
+    fn test_snapshot_edge_cases() {
+        use crate::{
+            implementation::project_history::{ClipChange, TrackChange},
+            types::ClipGroup,
+        };
+
+        let mut timeline = TimelineManager::new();
+        let v1 = timeline.add_track("V1", TrackType::Video);
+        let v2 = timeline.add_track("V2", TrackType::Video);
+        for id in 1..=2 {
+            let start = TimePosition::from_ms((id - 1) * 1_000);
+            let clip = TimelineClip::new(id, 1, start, TimePosition::from_ms(1_000));
+            timeline.add_clip(v1, clip).expect("test assertion");
+        }
+        let mut project = Project::new(ProjectId::new(1), "History");
+        project.set_clip_groups(vec![ClipGroup::new(1, &[1, 2])]);
+        let first = project.create_snapshot("First", &timeline);
+
+        // Move clip 2 to the other track and rename its old one
+        timeline.set_linked_selection(false);
+        let mut clip = timeline.remove_clip(2).expect("test assertion").remove(0);
+        clip.start = TimePosition::from_ms(3_000);
+        timeline.add_clip(v2, clip).expect("test assertion");
+        timeline.tracks_mut()[0].name = "Main".into();
+        project.set_clip_groups(Vec::new());
+        let second = project.create_snapshot("Second", &timeline);
+
+        // Later edits leave a snapshot as it was taken
+        let snapshot = project.snapshot(first).expect("test assertion");
+        assert_eq!(snapshot.tracks[0].clips.len(), 2);
+        assert_eq!(snapshot.tracks[0].name, "V1");
+
+        let diff = project.diff_snapshots(first, second).expect("test assertion");
+        assert_eq!(
+            diff.tracks,
+            vec![TrackChange::Renamed {
+                track_id: v1,
+                from:     "V1".into(),
+                to:       "Main".into(),
+            }]
+        );
+        assert_eq!(
+            diff.clips,
+            vec![ClipChange::Moved {
+                clip_id:    2,
+                from_track: v1,
+                to_track:   v2,
+                from:       TimePosition::from_ms(1_000),
+                to:         TimePosition::from_ms(3_000),
+            }]
+        );
+        assert!(diff.to_string().contains("> clip 2 track 1 at 1000 ms -> track 2 at 3000 ms"));
+
+        // Everything is gone in a snapshot of an empty timeline
+        let empty = project.create_snapshot("Empty", &TimelineManager::new());
+        let diff = project.diff_snapshots(second, empty).expect("test assertion");
+        assert_eq!((diff.tracks.len(), diff.clips.len()), (2, 2));
+        assert!(diff.to_string().contains("- track 2 \"V2\""));
+        assert!(project.diff_snapshots(first, 99).is_err());
+        assert!(project.diff_snapshots(99, first).is_err());
+
+        // Restoring brings back the clip groups too, and backs up the
+        // state it replaces
+        let backup = project.restore_snapshot(first, &mut timeline).expect("test assertion");
+        assert_eq!(timeline.get_track(v1).map(|t| t.clips.len()), Some(2));
+        assert_eq!(project.clip_groups().len(), 1);
+        assert!(project.diff_snapshots(second, backup).expect("test assertion").is_empty());
+        project.restore_snapshot(empty, &mut timeline).expect("test assertion");
+        assert!(timeline.tracks().is_empty());
+        assert_eq!(timeline.duration_ms(), 0);
+
+        // Removed snapshots are gone for good, and their IDs aren't reused
+        assert!(project.remove_snapshot(first));
+        assert!(!project.remove_snapshot(first));
+        assert!(project.diff_snapshots(first, second).is_err());
+        assert!(project.restore_snapshot(first, &mut timeline).is_err());
+        let latest = project.create_snapshot("Latest", &timeline);
+        assert!(project.snapshots().iter().all(|s| s.id <= latest && s.id != first));
+        assert!(latest > first);
+    }
+
     #[test]
     fn test_templates() {
         let manager = ProjectManager::new();
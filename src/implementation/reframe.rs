@@ -0,0 +1,300 @@
+//! Smart reframe for exports to another aspect ratio.
+//!
+//! [`SmartReframe`] picks a point of interest every few frames of a clip
+//! from the object detections in the [`MetadataIndex`] (faces and people
+//! weigh more), falls back to image saliency where nothing was detected,
+//! smooths the path and keyframes it as the crop window of the clip's
+//! [`ClipTransform`]. The keyframes are ordinary animation keyframes, so
+//! the framing can be adjusted by hand afterwards.
+
+use super::{clip_transform::ClipTransform, export_pipeline::FrameRenderer};
+use crate::{
+    errors::VideoEditorResult,
+    metadata::{MetadataIndex, ObjectDetection},
+    types::{Resolution, TimePosition, timeline::TimelineClip},
+};
+
+/// Keyframes closer than this to the previous one are dropped.
+const KEYFRAME_TOLERANCE: f32 = 0.005;
+
+/// Automatic crop window placement for a target aspect ratio.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmartReframe {
+    /// Target aspect ratio as width and height, e.g. `(9, 16)`.
+    pub aspect:         (u32, u32),
+    /// Frames between analyzed points and keyframes.
+    pub stride:         u32,
+    /// How strongly the window path is smoothed (0.0 = follows every
+    /// point, towards 1.0 = barely moves).
+    pub smoothing:      f32,
+    /// Weight of faces and people relative to other objects.
+    pub face_weight:    f32,
+    /// Detections below this confidence are ignored.
+    pub min_confidence: f32,
+}
+
+impl SmartReframe {
+    /// Vertical 9:16 output.
+    pub const VERTICAL: (u32, u32) = (9, 16);
+    /// Square 1:1 output.
+    pub const SQUARE: (u32, u32) = (1, 1);
+
+    /// Create a reframe for an aspect ratio, analyzing every fifth frame.
+    pub fn new(aspect: (u32, u32)) -> Self {
+        Self { aspect, stride: 5, smoothing: 0.8, face_weight: 3.0, min_confidence: 0.5 }
+    }
+
+    /// Normalized size of the largest crop window of the target aspect
+    /// ratio inside a source frame.
+    pub fn window_size(&self, source: Resolution) -> [f32; 2] {
+        let source_aspect = source.width as f32 / source.height.max(1) as f32;
+        let target_aspect = self.aspect.0 as f32 / self.aspect.1.max(1) as f32;
+        if target_aspect < source_aspect {
+            [target_aspect / source_aspect, 1.0]
+        } else {
+            [1.0, source_aspect / target_aspect]
+        }
+    }
+
+    /// Compute the smoothed crop window centers for a clip, as times
+    /// relative to the clip start.
+    ///
+    /// Frames are rendered through `renderer` only where no detection is
+    /// near enough to a sampled frame.
+    pub fn analyze(
+        &self, clip: &TimelineClip, index: &MetadataIndex, renderer: &dyn FrameRenderer,
+    ) -> VideoEditorResult<Vec<(TimePosition, [f32; 2])>> {
+        let rate = renderer.frame_rate();
+        let resolution = renderer.resolution();
+        let stride = u64::from(self.stride.max(1));
+        let (first, last) = (clip.start.to_frame(&rate), clip.end().to_frame(&rate));
+
+        let mut focus = [0.5, 0.5];
+        let mut points = Vec::new();
+        for frame in (first..last.max(first + 1)).step_by(stride as usize) {
+            let time = TimePosition::from_frame(frame, &rate);
+            let detected = self.nearest_detections(index, frame, stride);
+            let point = match self.detection_focus(&detected) {
+                Some(point) => Some(point),
+                None => {
+                    let pixels = renderer.render_frame(time)?;
+                    saliency_center(&pixels, resolution.width, resolution.height)
+                },
+            };
+            focus = point.unwrap_or(focus);
+            points.push((TimePosition::from_ms(time.ms.saturating_sub(clip.start.ms)), focus));
+        }
+
+        // Forward and backward passes keep the smoothed path from lagging
+        let alpha = 1.0 - self.smoothing.clamp(0.0, 0.99);
+        for pass in 0..2 {
+            let mut state: Option<[f32; 2]> = None;
+            let mut smooth = |(_, point): &mut (TimePosition, [f32; 2])| {
+                let next = state.map_or(*point, |s| {
+                    [s[0] + alpha * (point[0] - s[0]), s[1] + alpha * (point[1] - s[1])]
+                });
+                *point = next;
+                state = Some(next);
+            };
+            if pass == 0 {
+                points.iter_mut().for_each(&mut smooth);
+            } else {
+                points.iter_mut().rev().for_each(&mut smooth);
+            }
+        }
+
+        let size = self.window_size(resolution);
+        for (_, point) in &mut points {
+            for axis in 0..2 {
+                let half = size[axis] / 2.0;
+                point[axis] = point[axis].clamp(half, 1.0 - half);
+            }
+        }
+        Ok(points)
+    }
+
+    /// Analyze a clip and replace its crop keyframes. Returns how many
+    /// center keyframes were written.
+    pub fn apply(
+        &self, clip: &TimelineClip, index: &MetadataIndex, renderer: &dyn FrameRenderer,
+        transform: &mut ClipTransform,
+    ) -> VideoEditorResult<usize> {
+        let points = self.analyze(clip, index, renderer)?;
+        Ok(self.write_keyframes(&points, renderer.resolution(), transform))
+    }
+
+    /// Replace a transform's crop keyframes with analyzed centers, dropping
+    /// keyframes that barely move. Returns how many center keyframes were
+    /// written.
+    pub fn write_keyframes(
+        &self, points: &[(TimePosition, [f32; 2])], source: Resolution,
+        transform: &mut ClipTransform,
+    ) -> usize {
+        transform.clear_crop();
+        transform.set_crop_size(TimePosition::default(), self.window_size(source));
+        let mut written: Option<[f32; 2]> = None;
+        let mut count = 0;
+        for (i, &(time, center)) in points.iter().enumerate() {
+            let moved = written.is_none_or(|w| {
+                (w[0] - center[0]).abs() > KEYFRAME_TOLERANCE
+                    || (w[1] - center[1]).abs() > KEYFRAME_TOLERANCE
+            });
+            if moved || i + 1 == points.len() {
+                transform.set_crop_center(time, center);
+                written = Some(center);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Detections of the analyzed frame nearest to `frame`, if within one
+    /// stride of it.
+    fn nearest_detections<'a>(
+        &self, index: &'a MetadataIndex, frame: u64, stride: u64,
+    ) -> Vec<&'a ObjectDetection> {
+        let detections = index.detections();
+        let pos = detections.partition_point(|(f, _)| *f < frame);
+        let before = pos.checked_sub(1).map(|i| detections[i].0);
+        let after = detections.get(pos).map(|(f, _)| *f);
+        let nearest = match (before, after) {
+            (Some(b), Some(a)) if frame - b < a - frame => Some(b),
+            (_, Some(a)) => Some(a),
+            (b, None) => b,
+        };
+        nearest
+            .filter(|&n| n.abs_diff(frame) <= stride)
+            .map(|n| index.detections_at(n).collect())
+            .unwrap_or_default()
+    }
+
+    /// Weighted center of the confident detections.
+    fn detection_focus(&self, detections: &[&ObjectDetection]) -> Option<[f32; 2]> {
+        let mut sum = [0.0; 2];
+        let mut total = 0.0;
+        for detection in detections.iter().filter(|d| d.confidence >= self.min_confidence) {
+            let class = detection.class.as_str();
+            let is_person = ["face", "person"].iter().any(|c| class.eq_ignore_ascii_case(c));
+            let weight = detection.confidence
+                * detection.bbox.area().sqrt()
+                * if is_person { self.face_weight } else { 1.0 };
+            let (cx, cy) = detection.bbox.center();
+            sum[0] += cx * weight;
+            sum[1] += cy * weight;
+            total += weight;
+        }
+        (total > f32::EPSILON).then(|| [sum[0] / total, sum[1] / total])
+    }
+}
+
+/// Center of visual interest of an RGBA8 frame: the centroid of its luma
+/// edges. Returns `None` for a flat frame.
+#[must_use]
+pub fn saliency_center(pixels: &[u8], width: u32, height: u32) -> Option<[f32; 2]> {
+    let (w, h) = (width as usize, height as usize);
+    if w < 2 || h < 2 || pixels.len() < w * h * 4 {
+        return None;
+    }
+    let luma = |x: usize, y: usize| {
+        let p = &pixels[(y * w + x) * 4..];
+        0.2126 * f32::from(p[0]) + 0.7152 * f32::from(p[1]) + 0.0722 * f32::from(p[2])
+    };
+    // Sample a grid of about 64x64 points
+    let step = (w.max(h) / 64).max(1);
+    let mut sum = [0.0; 2];
+    let mut total = 0.0;
+    for y in (0..h).step_by(step) {
+        for x in (0..w).step_by(step) {
+            let here = luma(x, y);
+            let (right, below) = ((x + step).min(w - 1), (y + step).min(h - 1));
+            let energy = (luma(right, y) - here).abs() + (luma(x, below) - here).abs();
+            sum[0] += (x as f32 + 0.5) / w as f32 * energy;
+            sum[1] += (y as f32 + 0.5) / h as f32 * energy;
+            total += energy;
+        }
+    }
+    (total > 1.0).then(|| [sum[0] / total, sum[1] / total])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        metadata::BoundingBox,
+        types::{FrameRate, Resolution},
+    };
+
+    /// 160x90 frames with a bright square on the right.
+    struct SquareRenderer;
+
+    impl FrameRenderer for SquareRenderer {
+        fn resolution(&self) -> Resolution {
+            Resolution { width: 160, height: 90 }
+        }
+
+        fn frame_rate(&self) -> FrameRate {
+            FrameRate::FPS_25
+        }
+
+        fn render_frame(&self, _time: TimePosition) -> VideoEditorResult<Vec<u8>> {
+            Ok((0..160 * 90)
+                .flat_map(|i| {
+                    let (x, y) = (i % 160, i / 160);
+                    let v = if (120..140).contains(&x) && (30..60).contains(&y) { 255 } else { 0 };
+                    [v, v, v, 255]
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_smart_reframe() {
+        let reframe = SmartReframe::new(SmartReframe::VERTICAL);
+        let size = reframe.window_size(Resolution { width: 1920, height: 1080 });
+        assert!((size[0] - 0.316).abs() < 1e-3 && size[1] == 1.0);
+        let size =
+            SmartReframe::new((16, 9)).window_size(Resolution { width: 1080, height: 1920 });
+        assert!(size[0] == 1.0 && (size[1] - 0.316).abs() < 1e-3);
+
+        // A face walks left to right over the first second, then leaves
+        let mut index = MetadataIndex::new();
+        for frame in (0..25).step_by(5) {
+            let x = frame as f32 / 25.0 * 0.8;
+            index.set_detections(
+                frame,
+                [
+                    ObjectDetection::new(1, "face", 0.9, BoundingBox::new(x, 0.2, 0.2, 0.3)),
+                    ObjectDetection::new(2, "chair", 0.9, BoundingBox::new(0.0, 0.6, 0.1, 0.1)),
+                ],
+            );
+        }
+        let clip = TimelineClip::new(1, 1, TimePosition::from_ms(0), TimePosition::from_secs(2));
+        let unsmoothed = SmartReframe { smoothing: 0.0, ..reframe.clone() };
+        let points = unsmoothed.analyze(&clip, &index, &SquareRenderer).expect("test assertion");
+        assert_eq!(points.len(), 10);
+        // The face outweighs the chair, then the window finds the salient square
+        assert!((points[2].1[0] - 0.375).abs() < 0.01, "{:?}", points[2]);
+        let last = points.last().expect("test assertion").1;
+        assert!((last[0] - 0.81).abs() < 0.03, "{last:?}");
+
+        let points = reframe.analyze(&clip, &index, &SquareRenderer).expect("test assertion");
+        assert!(points.windows(2).all(|w| w[1].1[0] >= w[0].1[0]));
+        let half = reframe.window_size(SquareRenderer.resolution())[0] / 2.0;
+        assert!(points.iter().all(|(_, p)| p[0] >= half && p[0] <= 1.0 - half));
+        let last = points.last().expect("test assertion").1;
+
+        let mut transform = ClipTransform::new(clip.id);
+        let written =
+            reframe.apply(&clip, &index, &SquareRenderer, &mut transform).expect("test assertion");
+        assert!(written >= 2 && written <= points.len());
+        let state = transform.state_at(TimePosition::from_secs(2));
+        assert!((state.crop_center[0] - last[0]).abs() < 1e-4);
+        assert_eq!(state.crop_rect().height, 1.0);
+
+        // Cropping produces a frame of the target size showing the square
+        let frame = SquareRenderer.render_frame(TimePosition::default()).expect("test assertion");
+        let cropped = state.apply(&frame, 160, 90, 9, 16);
+        assert_eq!(cropped.len(), 9 * 16 * 4);
+        assert!(cropped.chunks_exact(4).any(|p| p[0] == 255));
+    }
+}
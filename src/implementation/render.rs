@@ -0,0 +1,335 @@
+//! CPU frame rendering and deterministic render mode.
+//!
+//! [`render_frame`] composites the visible video tracks at a timeline time
+//! into an RGBA8 buffer, bottom track first. Generator assets render
+//! in-process; other media is pulled from the host through a
+//! [`ClipFrameSource`]. Clips whose media is offline render a slate with
+//! the file name and duration instead. Interlaced media on a progressive
+//! output is deinterlaced and rotated media turned upright before it is
+//! scaled. 360° clips are either reframed to their keyframed flat view or
+//! left equirectangular.
+//!
+//! By default blends use fast fixed-point arithmetic and the float path
+//! dithers with a time-seeded generator, so two renders of a frame may
+//! differ in the last bit. [`RenderDeterminism::strict`] pins all of that
+//! down for golden-frame tests.
+
+use std::{path::Path, sync::Arc};
+
+use super::{
+    assets::AssetLibrary,
+    export_pipeline::draw_centered_lines,
+    generators::GeneratorSource,
+    keyframe_animation::AnimationManager,
+    spherical::SphericalView,
+    timeline::TimelineManager,
+    video_filters::{DeinterlaceMode, Deinterlacer},
+};
+use crate::{
+    checksum::Xxh64,
+    decoder::DecodedFrame,
+    errors::{CodecStage, VideoEditorError, VideoEditorResult},
+    trace::{self, TracePhase},
+    types::{
+        BufferDescriptor, FieldOrder, FrameBuffer, FrameBufferPool, FrameRate, Resolution,
+        Rotation, TimePosition, TrackType, timeline::TimelineClip,
+    },
+};
+
+/// Background of the offline media slate.
+const OFFLINE_SLATE_COLOR: [u8; 4] = [96, 16, 16, 255];
+
+/// Supplies a decoded RGBA8 frame of an asset at a source time.
+pub type ClipFrameSource =
+    Arc<dyn Fn(u64, TimePosition) -> VideoEditorResult<DecodedFrame> + Send + Sync>;
+
+/// Controls for byte-stable rendering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct RenderDeterminism {
+    /// Seed for dither and other noise. `None` seeds from the clock.
+    pub fixed_seed:      Option<u64>,
+    /// Run export jobs one at a time in queue order.
+    pub ordered_threads: bool,
+    /// Use exact IEEE float blending on CPU paths instead of fixed-point
+    /// approximations, and keep effects off the GPU.
+    pub strict_float:    bool,
+}
+
+impl RenderDeterminism {
+    /// Everything pinned, seeded with `seed`.
+    #[must_use]
+    pub const fn strict(seed: u64) -> Self {
+        Self { fixed_seed: Some(seed), ordered_threads: true, strict_float: true }
+    }
+
+    /// Whether renders are byte-stable across runs.
+    #[must_use]
+    pub const fn is_deterministic(&self) -> bool {
+        self.fixed_seed.is_some() && self.ordered_threads && self.strict_float
+    }
+
+    /// Seed for one noise stream, e.g. a frame number.
+    #[must_use]
+    pub fn seed(&self, stream: u64) -> u64 {
+        let base = self.fixed_seed.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos() as u64)
+        });
+        let mut hasher = Xxh64::new(base);
+        hasher.update(&stream.to_le_bytes());
+        hasher.finish()
+    }
+}
+
+/// Composite the timeline at `time` into an RGBA8 frame from `pool`.
+///
+/// `frame` numbers the dither stream so each frame gets its own noise.
+/// `field_order` is the scan of the output. Equirectangular clips are
+/// reframed to the views keyframed in `reframe`, or kept as they are when
+/// it is `None`. Source frames already at the output size are blended
+/// straight from their decoded buffers.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn render_frame(
+    timeline: &TimelineManager, assets: &AssetLibrary, reframe: Option<&AnimationManager>,
+    source: Option<&ClipFrameSource>, determinism: &RenderDeterminism, time: TimePosition,
+    frame: u64, resolution: Resolution, field_order: FieldOrder, pool: &FrameBufferPool,
+) -> VideoEditorResult<FrameBuffer> {
+    let Resolution { width, height } = resolution;
+    let descriptor = BufferDescriptor::rgba8(width, height);
+    let mut tracks: Vec<_> = timeline
+        .tracks()
+        .iter()
+        .filter(|t| t.track_type == TrackType::Video && timeline.is_track_visible(t.id))
+        .collect();
+    tracks.sort_by_key(|t| t.index);
+
+    let mut canvas = Canvas::new(descriptor, determinism.strict_float, pool);
+    for track in tracks {
+        let Some(clip) = track.clips.iter().find(|c| c.enabled && c.contains(time)) else {
+            continue;
+        };
+        let layer: FrameBuffer = match assets.generator(clip.source_id) {
+            Some(GeneratorSource::Pattern(pattern)) => pattern.render(width, height).into(),
+            Some(GeneratorSource::Tone(_)) => continue,
+            None if assets.is_offline(clip.source_id) => {
+                offline_slate(assets, clip.source_id, width, height).into()
+            },
+            None => {
+                let source = source.ok_or_else(|| {
+                    VideoEditorError::Asset(format!("No frame source for asset {}", clip.source_id))
+                })?;
+                let decoded = {
+                    let _span = trace::span(TracePhase::Decode, "clip source");
+                    source(clip.source_id, source_time(clip, time))?
+                };
+                let media = assets.video_clip(clip.source_id);
+                let decoded = match media.map(|c| c.field_order) {
+                    Some(scan) if scan.is_interlaced() && !field_order.is_interlaced() => {
+                        deinterlace(decoded, scan, pool)?
+                    },
+                    _ => decoded,
+                };
+                let decoded = match media.map(|c| c.effective_rotation()) {
+                    Some(rotation) if rotation != Rotation::None => {
+                        rotate(decoded, rotation, pool)?
+                    },
+                    _ => decoded,
+                };
+                match reframe {
+                    Some(animation) if media.is_some_and(|c| c.projection.is_spherical()) => {
+                        let _span = trace::span(TracePhase::Effect, "reframe clip");
+                        let view = SphericalView::at(animation, clip.id, time - clip.start);
+                        let mut out = pool.acquire(descriptor);
+                        view.apply_into(
+                            &decoded.data,
+                            decoded.width,
+                            decoded.height,
+                            &mut out,
+                            width,
+                            height,
+                        );
+                        out
+                    },
+                    _ => scale_nearest(decoded, descriptor, pool)?,
+                }
+            },
+        };
+        canvas.draw(&layer);
+    }
+
+    Ok(canvas.finish(determinism.seed(frame), pool))
+}
+
+/// Placeholder for a clip whose media is missing: the file name and
+/// duration on a dark red frame.
+fn offline_slate(assets: &AssetLibrary, asset_id: u64, width: u32, height: u32) -> Vec<u8> {
+    let (path, rate) = match assets.video_clip(asset_id) {
+        Some(clip) => (clip.path.as_str(), clip.frame_rate),
+        None => (assets.audio_clip(asset_id).map_or("", |c| c.path.as_str()), FrameRate::default()),
+    };
+    let name = Path::new(path).file_name().map_or_else(|| path.into(), |n| n.to_string_lossy());
+    let duration = assets.asset_duration(asset_id).unwrap_or_default().to_timecode(&rate);
+
+    let mut pixels = OFFLINE_SLATE_COLOR.repeat(width as usize * height as usize);
+    draw_centered_lines(&mut pixels, width, height, &["MEDIA OFFLINE", &*name, duration.as_str()]);
+    pixels
+}
+
+/// Media time shown by `clip` at timeline time `time`.
+fn source_time(clip: &TimelineClip, time: TimePosition) -> TimePosition {
+    let offset = time.ms.saturating_sub(clip.start.ms) as f64 * f64::from(clip.speed);
+    TimePosition::from_ms(clip.in_point.ms + offset.round() as u64)
+}
+
+/// Progressive version of an interlaced `frame`, in a buffer from `pool`.
+///
+/// Frames are pulled one at a time with no history, so the adaptive mode
+/// interpolates the second field along edges.
+fn deinterlace(
+    frame: DecodedFrame, field_order: FieldOrder, pool: &FrameBufferPool,
+) -> VideoEditorResult<DecodedFrame> {
+    let _span = trace::span(TracePhase::Effect, "deinterlace clip");
+    let mut out = pool.acquire(BufferDescriptor::rgba8(frame.width, frame.height));
+    Deinterlacer::new(DeinterlaceMode::Adaptive, field_order).process_into(
+        &frame.data,
+        frame.width,
+        frame.height,
+        &mut out,
+    )?;
+    Ok(DecodedFrame { data: out, ..frame })
+}
+
+/// Upright version of a `frame` stored turned by `rotation`, in a buffer
+/// from `pool`.
+fn rotate(
+    frame: DecodedFrame, rotation: Rotation, pool: &FrameBufferPool,
+) -> VideoEditorResult<DecodedFrame> {
+    let (sw, sh) = (frame.width as usize, frame.height as usize);
+    if frame.data.len() < sw * sh * 4 {
+        return Err(VideoEditorError::codec(
+            "rgba8",
+            CodecStage::Decode,
+            format!("Frame at {} ms has no {sw}x{sh} RGBA data", frame.pts_ms),
+        ));
+    }
+    let Resolution { width, height } = rotation.apply(Resolution::new(frame.width, frame.height));
+    let mut out = pool.acquire(BufferDescriptor::rgba8(width, height));
+    let w = width as usize;
+    for (y, row) in out.chunks_exact_mut((w * 4).max(1)).enumerate() {
+        for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+            // Source pixel shown at (x, y) once turned clockwise
+            let (sx, sy) = match rotation {
+                Rotation::None => (x, y),
+                Rotation::Clockwise90 => (y, sh - 1 - x),
+                Rotation::Clockwise180 => (sw - 1 - x, sh - 1 - y),
+                Rotation::Clockwise270 => (sw - 1 - y, x),
+            };
+            let i = (sy * sw + sx) * 4;
+            pixel.copy_from_slice(&frame.data[i..i + 4]);
+        }
+    }
+    Ok(DecodedFrame { width, height, data: out, ..frame })
+}
+
+/// `frame` at the size of `descriptor`. A frame already that size is
+/// passed through without copying.
+fn scale_nearest(
+    frame: DecodedFrame, descriptor: BufferDescriptor, pool: &FrameBufferPool,
+) -> VideoEditorResult<FrameBuffer> {
+    let (sw, sh) = (frame.width as usize, frame.height as usize);
+    if sw == 0 || sh == 0 || frame.data.len() < sw * sh * 4 {
+        return Err(VideoEditorError::codec(
+            "rgba8",
+            CodecStage::Decode,
+            format!("Frame at {} ms has no {sw}x{sh} RGBA data", frame.pts_ms),
+        ));
+    }
+    let (w, h) = (descriptor.width as usize, descriptor.height as usize);
+    if (sw, sh) == (w, h) {
+        return Ok(frame.data);
+    }
+    let mut out = pool.acquire(descriptor);
+    for (y, row) in out.chunks_exact_mut((w * 4).max(1)).enumerate() {
+        let source_row = y * sh / h.max(1) * sw;
+        for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+            let i = (source_row + x * sw / w.max(1)) * 4;
+            pixel.copy_from_slice(&frame.data[i..i + 4]);
+        }
+    }
+    Ok(out)
+}
+
+/// Opaque black frame that layers are blended over.
+enum Canvas {
+    /// Exact float accumulation, quantized with dither at the end.
+    Float(Vec<[f32; 3]>, BufferDescriptor),
+    /// 8.8 fixed-point blending straight into the RGBA8 output.
+    Fixed(FrameBuffer),
+}
+
+impl Canvas {
+    fn new(descriptor: BufferDescriptor, strict_float: bool, pool: &FrameBufferPool) -> Self {
+        let pixels = descriptor.width as usize * descriptor.height as usize;
+        if strict_float {
+            Self::Float(vec![[0.0; 3]; pixels], descriptor)
+        } else {
+            let mut buffer = pool.acquire(descriptor);
+            for pixel in buffer.chunks_exact_mut(4) {
+                pixel.copy_from_slice(&[0, 0, 0, 255]);
+            }
+            Self::Fixed(buffer)
+        }
+    }
+
+    /// Blend a straight-alpha RGBA8 layer over the canvas.
+    fn draw(&mut self, layer: &[u8]) {
+        match self {
+            Self::Float(canvas, _) => {
+                for (dst, src) in canvas.iter_mut().zip(layer.chunks_exact(4)) {
+                    let alpha = f32::from(src[3]) / 255.0;
+                    for (d, &s) in dst.iter_mut().zip(src) {
+                        *d = f32::from(s) / 255.0 * alpha + *d * (1.0 - alpha);
+                    }
+                }
+            },
+            Self::Fixed(canvas) => {
+                for (dst, src) in canvas.chunks_exact_mut(4).zip(layer.chunks_exact(4)) {
+                    let alpha = u32::from(src[3]) + u32::from(src[3] >> 7);
+                    for (d, &s) in dst[..3].iter_mut().zip(src) {
+                        let blended = u32::from(s) * alpha + u32::from(*d) * (256 - alpha);
+                        *d = (blended >> 8) as u8;
+                    }
+                }
+            },
+        }
+    }
+
+    /// Convert to RGBA8. Float values between code values get TPDF dither
+    /// from a generator seeded with `seed`.
+    fn finish(self, seed: u64, pool: &FrameBufferPool) -> FrameBuffer {
+        match self {
+            Self::Float(canvas, descriptor) => {
+                let mut state = seed | 1;
+                let mut noise = move || {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    (state >> 40) as f32 / (1u64 << 24) as f32
+                };
+                let mut out = pool.acquire(descriptor);
+                for (pixel, dst) in canvas.into_iter().zip(out.chunks_exact_mut(4)) {
+                    for (v, d) in pixel.into_iter().zip(dst.iter_mut()) {
+                        let code = v.clamp(0.0, 1.0) * 255.0;
+                        let dither =
+                            if code.fract() == 0.0 { 0.0 } else { noise() + noise() - 1.0 };
+                        *d = (code + dither).round().clamp(0.0, 255.0) as u8;
+                    }
+                    dst[3] = 255;
+                }
+                out
+            },
+            Self::Fixed(canvas) => canvas,
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Render-to-texture hooks for host compositing.
+//!
+//! Hosts attach render targets (a native texture, a surface or a CPU buffer)
+//! and the plugin renders the program output into every enabled target each
+//! frame. This lets a host embed the editor viewport inside a larger scene,
+//! e.g. as picture-in-picture.
+
+use crate::errors::{VideoEditorError, VideoEditorResult};
+
+/// Unique identifier for an attached render target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenderTargetId(u64);
+
+impl RenderTargetId {
+    /// Returns the inner ID value.
+    #[must_use]
+    pub const fn inner(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Host-supplied destination for program output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderTargetHandle {
+    /// Native GPU texture handle owned by the host.
+    Texture(u64),
+    /// Native window/surface handle owned by the host.
+    Surface(u64),
+    /// Plugin-owned CPU pixel buffer the host reads back.
+    CpuBuffer,
+}
+
+impl RenderTargetHandle {
+    /// Returns whether rendering into this target needs the GPU.
+    #[must_use]
+    pub const fn requires_gpu(&self) -> bool {
+        !matches!(self, Self::CpuBuffer)
+    }
+}
+
+/// Pixel format of a render target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RenderTargetFormat {
+    /// 8-bit RGBA.
+    #[default]
+    Rgba8,
+    /// 8-bit BGRA (common swapchain format).
+    Bgra8,
+}
+
+impl RenderTargetFormat {
+    /// Returns bytes per pixel.
+    #[must_use]
+    pub const fn bytes_per_pixel(&self) -> usize {
+        4
+    }
+}
+
+/// How program output is fitted into a target of a different aspect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RenderScaleMode {
+    /// Letterbox/pillarbox to keep the aspect ratio.
+    #[default]
+    Fit,
+    /// Fill the target and crop the overflow.
+    Fill,
+    /// Stretch to the target size.
+    Stretch,
+}
+
+/// Render target description supplied by the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderTargetDesc {
+    /// Destination handle.
+    pub handle:     RenderTargetHandle,
+    /// Target width in pixels.
+    pub width:      u32,
+    /// Target height in pixels.
+    pub height:     u32,
+    /// Pixel format.
+    pub format:     RenderTargetFormat,
+    /// Scale mode.
+    pub scale_mode: RenderScaleMode,
+}
+
+impl RenderTargetDesc {
+    /// Creates a description with default format and scale mode.
+    #[must_use]
+    pub fn new(handle: RenderTargetHandle, width: u32, height: u32) -> Self {
+        Self {
+            handle,
+            width,
+            height,
+            format: RenderTargetFormat::default(),
+            scale_mode: RenderScaleMode::default(),
+        }
+    }
+
+    /// Returns the destination rectangle `(x, y, width, height)` for a
+    /// source of the given size. Fill may return a rectangle larger than
+    /// the target; the overflow is cropped.
+    #[must_use]
+    pub fn viewport(&self, src_width: u32, src_height: u32) -> (i32, i32, u32, u32) {
+        if src_width == 0 || src_height == 0 || self.scale_mode == RenderScaleMode::Stretch {
+            return (0, 0, self.width, self.height);
+        }
+
+        let sx = self.width as f64 / src_width as f64;
+        let sy = self.height as f64 / src_height as f64;
+        let scale = match self.scale_mode {
+            RenderScaleMode::Fit => sx.min(sy),
+            RenderScaleMode::Fill | RenderScaleMode::Stretch => sx.max(sy),
+        };
+        let w = (src_width as f64 * scale).round() as u32;
+        let h = (src_height as f64 * scale).round() as u32;
+        let x = (i64::from(self.width) - i64::from(w)) / 2;
+        let y = (i64::from(self.height) - i64::from(h)) / 2;
+        (x as i32, y as i32, w, h)
+    }
+}
+
+/// An attached render target.
+#[derive(Debug, Clone)]
+pub struct RenderTarget {
+    /// Target ID.
+    pub id:              RenderTargetId,
+    /// Description.
+    pub desc:            RenderTargetDesc,
+    /// Whether the target receives frames.
+    pub enabled:         bool,
+    /// Frames rendered into the target.
+    pub frames_rendered: u64,
+    /// Program frame index last rendered.
+    pub last_frame:      Option<u64>,
+    /// Pixel data for `CpuBuffer` targets.
+    pixels:              Vec<u8>,
+}
+
+impl RenderTarget {
+    /// Returns the pixel data of a `CpuBuffer` target.
+    #[must_use]
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Renders an RGBA8 source into the CPU buffer (nearest neighbour).
+    fn blit(&mut self, src: &[u8], src_width: u32, src_height: u32) {
+        let (width, height) = (self.desc.width as usize, self.desc.height as usize);
+        let bpp = self.desc.format.bytes_per_pixel();
+        self.pixels.clear();
+        self.pixels.resize(width * height * bpp, 0);
+
+        let (vx, vy, vw, vh) = self.desc.viewport(src_width, src_height);
+        if vw == 0 || vh == 0 {
+            return;
+        }
+        let swap_rb = self.desc.format == RenderTargetFormat::Bgra8;
+
+        for y in 0..height {
+            let dy = y as i64 - i64::from(vy);
+            if dy < 0 || dy >= i64::from(vh) {
+                continue;
+            }
+            let sy = (dy as u64 * u64::from(src_height) / u64::from(vh)) as usize;
+            for x in 0..width {
+                let dx = x as i64 - i64::from(vx);
+                if dx < 0 || dx >= i64::from(vw) {
+                    continue;
+                }
+                let sx = (dx as u64 * u64::from(src_width) / u64::from(vw)) as usize;
+                let s = (sy * src_width as usize + sx) * 4;
+                let d = (y * width + x) * bpp;
+                if let (Some(from), Some(to)) = (src.get(s..s + 4), self.pixels.get_mut(d..d + 4)) {
+                    to.copy_from_slice(from);
+                    if swap_rb {
+                        to.swap(0, 2);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Set of host render targets fed with program output.
+#[derive(Debug, Default)]
+pub struct RenderTargetRegistry {
+    /// Attached targets.
+    targets: Vec<RenderTarget>,
+    /// Next target ID.
+    next_id: u64,
+}
+
+impl RenderTargetRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { targets: Vec::new(), next_id: 1 }
+    }
+
+    /// Attaches a render target.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Gpu` if the target has a zero size.
+    pub fn attach(&mut self, desc: RenderTargetDesc) -> VideoEditorResult<RenderTargetId> {
+        if desc.width == 0 || desc.height == 0 {
+            return Err(VideoEditorError::Gpu("Render target has zero size".into()));
+        }
+
+        let id = RenderTargetId(self.next_id.max(1));
+        self.next_id = id.0 + 1;
+        self.targets.push(RenderTarget {
+            id,
+            desc,
+            enabled: true,
+            frames_rendered: 0,
+            last_frame: None,
+            pixels: Vec::new(),
+        });
+        Ok(id)
+    }
+
+    /// Detaches a render target.
+    pub fn detach(&mut self, id: RenderTargetId) -> bool {
+        let before = self.targets.len();
+        self.targets.retain(|t| t.id != id);
+        self.targets.len() != before
+    }
+
+    /// Resizes a target (e.g. after the host resized its viewport).
+    pub fn resize(&mut self, id: RenderTargetId, width: u32, height: u32) -> bool {
+        match self.get_mut(id) {
+            Some(target) if width > 0 && height > 0 => {
+                target.desc.width = width;
+                target.desc.height = height;
+                target.pixels.clear();
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Enables or disables a target.
+    pub fn set_enabled(&mut self, id: RenderTargetId, enabled: bool) -> bool {
+        self.get_mut(id).map(|t| t.enabled = enabled).is_some()
+    }
+
+    /// Gets a target by ID.
+    #[must_use]
+    pub fn get(&self, id: RenderTargetId) -> Option<&RenderTarget> {
+        self.targets.iter().find(|t| t.id == id)
+    }
+
+    fn get_mut(&mut self, id: RenderTargetId) -> Option<&mut RenderTarget> {
+        self.targets.iter_mut().find(|t| t.id == id)
+    }
+
+    /// Returns all attached targets.
+    #[must_use]
+    pub fn targets(&self) -> &[RenderTarget] {
+        &self.targets
+    }
+
+    /// Renders a program frame (RGBA8) into every enabled target.
+    ///
+    /// Texture and surface targets are skipped when the GPU is unavailable.
+    /// Returns the number of targets updated.
+    pub fn present(
+        &mut self, frame: u64, pixels: &[u8], width: u32, height: u32, gpu_available: bool,
+    ) -> usize {
+        let mut updated = 0;
+        for target in self.targets.iter_mut().filter(|t| t.enabled) {
+            if target.desc.handle.requires_gpu() && !gpu_available {
+                continue;
+            }
+            if target.desc.handle == RenderTargetHandle::CpuBuffer {
+                target.blit(pixels, width, height);
+            }
+            // Texture/surface targets are filled by the GPU compositor pass.
+            target.frames_rendered += 1;
+            target.last_frame = Some(frame);
+            updated += 1;
+        }
+        updated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_viewport_fit_and_fill() {
+        let mut desc = RenderTargetDesc::new(RenderTargetHandle::Texture(7), 400, 400);
+        assert_eq!(desc.viewport(1920, 1080), (0, 87, 400, 225));
+
+        desc.scale_mode = RenderScaleMode::Fill;
+        let (x, y, w, h) = desc.viewport(1920, 1080);
+        assert_eq!((y, h), (0, 400));
+        assert!(x < 0 && w > 400);
+    }
+
+    #[test]
+    fn test_cpu_buffer_present() {
+        let mut registry = RenderTargetRegistry::new();
+        let mut desc = RenderTargetDesc::new(RenderTargetHandle::CpuBuffer, 2, 2);
+        desc.format = RenderTargetFormat::Bgra8;
+        let id = registry.attach(desc).expect("test assertion");
+
+        let red = [255u8, 0, 0, 255].repeat(16);
+        assert_eq!(registry.present(3, &red, 4, 4, false), 1);
+
+        let target = registry.get(id).expect("test assertion");
+        assert_eq!(target.last_frame, Some(3));
+        assert_eq!(&target.pixels()[..4], &[0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_gpu_targets_need_gpu() {
+        let mut registry = RenderTargetRegistry::new();
+        let tex = registry
+            .attach(RenderTargetDesc::new(RenderTargetHandle::Texture(1), 64, 64))
+            .expect("test assertion");
+        assert!(
+            registry.attach(RenderTargetDesc::new(RenderTargetHandle::Surface(2), 0, 10)).is_err()
+        );
+
+        assert_eq!(registry.present(0, &[], 0, 0, false), 0);
+        assert_eq!(registry.present(1, &[], 0, 0, true), 1);
+
+        registry.set_enabled(tex, false);
+        assert_eq!(registry.present(2, &[], 0, 0, true), 0);
+        assert!(registry.detach(tex));
+        assert!(registry.targets().is_empty());
+    }
+}
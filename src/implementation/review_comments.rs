@@ -0,0 +1,295 @@
+//! Review comment exchange.
+//!
+//! Markers are exported as a JSON comment list for reviewers, in the
+//! spirit of hosted review tools: each comment carries its frame, SMPTE
+//! timecode, author, text and reply thread. Returned files are imported
+//! back as Comment and NeedsReview markers. Comments are matched to
+//! existing markers by frame, and replies become marker notes, so a file
+//! can go back and forth without duplicating markers.
+
+use super::marker_system::{Marker, MarkerId, MarkerManager, MarkerNote, MarkerType};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    json::JsonValue,
+    types::{FrameRate, TimePosition},
+};
+
+/// Value of the `format` member.
+const REVIEW_FORMAT: &str = "evep-review";
+
+/// Format version written by [`export_review_comments`].
+const REVIEW_VERSION: f64 = 1.0;
+
+/// Markers touched by a review comment import.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReviewImport {
+    /// Markers created for new comments.
+    pub created: Vec<MarkerId>,
+    /// Existing markers that gained replies or changed status.
+    pub updated: Vec<MarkerId>,
+    /// Comments without a usable time, or matching a locked marker.
+    pub skipped: usize,
+}
+
+/// Exchange name of a marker type.
+fn type_key(marker_type: MarkerType) -> &'static str {
+    match marker_type {
+        MarkerType::Standard => "marker",
+        MarkerType::Chapter => "chapter",
+        MarkerType::SyncPoint => "sync_point",
+        MarkerType::Comment => "comment",
+        MarkerType::Todo => "todo",
+        MarkerType::Approved => "approved",
+        MarkerType::NeedsReview => "needs_review",
+        MarkerType::InPoint => "in_point",
+        MarkerType::OutPoint => "out_point",
+        MarkerType::Beat => "beat",
+        MarkerType::Cue => "cue",
+    }
+}
+
+/// Returns whether an import may change a marker's type.
+const fn is_review_type(marker_type: MarkerType) -> bool {
+    matches!(marker_type, MarkerType::Comment | MarkerType::NeedsReview)
+}
+
+fn string(value: &str) -> JsonValue {
+    JsonValue::String(value.to_string())
+}
+
+fn comment_json(marker: &Marker, rate: &FrameRate) -> JsonValue {
+    let mut members = vec![
+        ("marker_id".into(), JsonValue::Number(marker.id().inner() as f64)),
+        ("frame".into(), JsonValue::Number(marker.position().to_frame(rate) as f64)),
+        ("timecode".into(), string(&marker.position().to_timecode(rate))),
+        ("duration_frames".into(), JsonValue::Number(marker.duration().to_frame(rate) as f64)),
+        ("type".into(), string(type_key(marker.marker_type()))),
+    ];
+    if let Some(author) = marker.author() {
+        members.push(("author".into(), string(author)));
+    }
+    let text = if marker.comment().is_empty() { marker.name() } else { marker.comment() };
+    members.push(("text".into(), string(text)));
+    members.push((
+        "completed".into(),
+        JsonValue::Bool(marker.marker_type() != MarkerType::NeedsReview),
+    ));
+    let replies = marker
+        .notes()
+        .iter()
+        .map(|note| {
+            let mut reply = Vec::new();
+            if let Some(author) = &note.author {
+                reply.push(("author".into(), string(author)));
+            }
+            reply.push(("text".into(), string(&note.text)));
+            JsonValue::Object(reply)
+        })
+        .collect();
+    members.push(("replies".into(), JsonValue::Array(replies)));
+    JsonValue::Object(members)
+}
+
+/// Writes markers as a review comment file, in timeline order.
+#[must_use]
+pub fn export_review_comments(markers: &MarkerManager, rate: &FrameRate, project: &str) -> String {
+    let document = JsonValue::Object(vec![
+        ("format".into(), string(REVIEW_FORMAT)),
+        ("version".into(), JsonValue::Number(REVIEW_VERSION)),
+        ("project".into(), string(project)),
+        (
+            "frame_rate".into(),
+            JsonValue::Object(vec![
+                ("numerator".into(), JsonValue::Number(f64::from(rate.numerator))),
+                ("denominator".into(), JsonValue::Number(f64::from(rate.denominator))),
+            ]),
+        ),
+        (
+            "comments".into(),
+            JsonValue::Array(markers.markers().map(|m| comment_json(m, rate)).collect()),
+        ),
+    ]);
+    document.to_pretty_string()
+}
+
+/// Imports a returned review comment file into the markers.
+///
+/// Comment times come from `frame` at the file's frame rate, or from
+/// `timecode` when there is no frame. A comment matches an existing marker
+/// on the same project frame with the same `marker_id`, or failing that
+/// the same text; its replies are added as notes and a Comment or
+/// NeedsReview marker follows its `completed` state. Unmatched comments
+/// become new markers: Comment when completed, NeedsReview otherwise.
+///
+/// # Errors
+///
+/// Returns `VideoEditorError::Conversion` for malformed JSON and
+/// `VideoEditorError::UnsupportedFormat` if the document has no comment
+/// list.
+pub fn import_review_comments(
+    markers: &mut MarkerManager, json: &str, rate: &FrameRate,
+) -> VideoEditorResult<ReviewImport> {
+    let document = JsonValue::parse(json)?;
+    let Some(JsonValue::Array(comments)) = document.get("comments") else {
+        return Err(VideoEditorError::unsupported_format("Review file has no comment list"));
+    };
+    let file_rate = document
+        .get("frame_rate")
+        .and_then(|r| {
+            let part = |key| r.get(key).and_then(JsonValue::as_usize).map(|n| n as u32);
+            Some(FrameRate::new(part("numerator")?, part("denominator")?))
+        })
+        .filter(|r| r.numerator > 0 && r.denominator > 0)
+        .unwrap_or(*rate);
+
+    let mut report = ReviewImport::default();
+    for comment in comments {
+        let position = comment
+            .get("frame")
+            .and_then(JsonValue::as_usize)
+            .map(|frame| TimePosition::from_frame(frame as u64, &file_rate))
+            .or_else(|| {
+                let timecode = comment.get("timecode")?.as_str()?;
+                TimePosition::from_timecode(timecode, &file_rate)
+            });
+        let Some(position) = position else {
+            report.skipped += 1;
+            continue;
+        };
+        let text = comment.get("text").and_then(JsonValue::as_str).unwrap_or_default();
+        let author = comment.get("author").and_then(JsonValue::as_str);
+        let completed = comment.get("completed").and_then(JsonValue::as_bool).unwrap_or(false);
+        let replies: Vec<MarkerNote> = comment
+            .get("replies")
+            .map(JsonValue::items)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|reply| {
+                let text = reply.get("text")?.as_str()?;
+                Some(MarkerNote::new(reply.get("author").and_then(JsonValue::as_str), text))
+            })
+            .collect();
+        let status = if completed { MarkerType::Comment } else { MarkerType::NeedsReview };
+
+        let frame = position.to_frame(rate);
+        let marker_id = comment.get("marker_id").and_then(JsonValue::as_usize);
+        let at_frame = |m: &&Marker| m.position().to_frame(rate) == frame;
+        let existing = markers
+            .markers()
+            .filter(at_frame)
+            .find(|m| marker_id.is_some_and(|id| m.id().inner() == id as u64))
+            .or_else(|| {
+                markers
+                    .markers()
+                    .filter(at_frame)
+                    .find(|m| !text.is_empty() && (m.comment() == text || m.name() == text))
+            })
+            .map(|m| (m.id(), m.is_locked()));
+
+        match existing {
+            Some((_, true)) => report.skipped += 1,
+            Some((id, false)) => {
+                let mut changed = false;
+                markers.update_marker(id, |marker| {
+                    for reply in replies {
+                        changed |= marker.add_note(reply);
+                    }
+                    if is_review_type(marker.marker_type()) && marker.marker_type() != status {
+                        marker.set_marker_type(status);
+                        changed = true;
+                    }
+                })?;
+                if changed {
+                    report.updated.push(id);
+                }
+            },
+            None => {
+                let id = markers.add_marker(position, status);
+                let duration = comment
+                    .get("duration_frames")
+                    .and_then(JsonValue::as_usize)
+                    .map_or(TimePosition::default(), |frames| {
+                        TimePosition::from_frame(frames as u64, &file_rate)
+                    });
+                markers.update_marker(id, |marker| {
+                    marker.set_comment(text);
+                    marker.set_duration(duration);
+                    if let Some(author) = author {
+                        marker.set_author(author);
+                    }
+                    for reply in replies {
+                        marker.add_note(reply);
+                    }
+                })?;
+                report.created.push(id);
+            },
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_review_comment_round_trip() {
+        let rate = FrameRate::FPS_24;
+        let mut markers = MarkerManager::new();
+        let at = TimePosition::from_frame(36, &rate);
+        let id = markers.add_marker(at, MarkerType::NeedsReview);
+        markers
+            .update_marker(id, |m| {
+                m.set_comment("Colour \"pops\" here");
+                m.set_author("Ana");
+            })
+            .expect("test assertion");
+        markers.add_chapter(TimePosition::from_secs(10), "Act 2");
+
+        let json = export_review_comments(&markers, &rate, "Promo");
+        let parsed = JsonValue::parse(&json).expect("test assertion");
+        let comments = parsed.get("comments").expect("test assertion").items();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].get("timecode").and_then(JsonValue::as_str), Some("00:00:01:12"));
+        assert_eq!(comments[0].get("frame").and_then(JsonValue::as_usize), Some(36));
+        assert_eq!(
+            comments[0].get("text").and_then(JsonValue::as_str),
+            Some("Colour \"pops\" here")
+        );
+        assert_eq!(comments[1].get("text").and_then(JsonValue::as_str), Some("Act 2"));
+
+        // A reviewer replies to the first comment and adds one by timecode
+        let returned = json
+            .replacen(
+                "\"replies\": []",
+                r#""completed": true, "replies": [{"author": "Ben", "text": "Fixed in v3"}]"#,
+                1,
+            )
+            .replacen("\"completed\": false,", "", 1)
+            .replace(
+                "\"comments\": [",
+                r#""comments": [{"timecode": "00:00:05:06", "author": "Cy", "text": "Cut earlier"},"#,
+            );
+        let report =
+            import_review_comments(&mut markers, &returned, &rate).expect("test assertion");
+        assert_eq!(report.updated, vec![id]);
+        assert_eq!(report.created.len(), 1);
+        assert_eq!(report.skipped, 0);
+
+        let marker = markers.get_marker(id).expect("test assertion");
+        assert_eq!(marker.marker_type(), MarkerType::Comment);
+        assert_eq!(marker.notes(), &[MarkerNote::new(Some("Ben"), "Fixed in v3")]);
+        let added = markers.get_marker(report.created[0]).expect("test assertion");
+        assert_eq!(added.position(), TimePosition::from_frame(126, &rate));
+        assert_eq!(added.marker_type(), MarkerType::NeedsReview);
+        assert_eq!(added.author(), Some("Cy"));
+
+        // Importing the same file again changes nothing
+        let again = import_review_comments(&mut markers, &returned, &rate).expect("test assertion");
+        assert!(again.created.is_empty() && again.updated.is_empty());
+        assert_eq!(markers.count(), 3);
+
+        assert!(import_review_comments(&mut markers, "{}", &rate).is_err());
+        assert!(import_review_comments(&mut markers, "{\"comments\": [", &rate).is_err());
+    }
+}
@@ -0,0 +1,476 @@
+//! Scriptable timeline automation.
+//!
+//! [`EditorScriptApi`] exposes timeline, transition and marker edits as
+//! [`ScriptOperation`]s with a stable binary encoding, so a host scripting
+//! layer can record, send and replay batches of edits.
+
+use super::{
+    RippleSync, TimelineManager,
+    marker_system::{MarkerId, MarkerManager, MarkerType},
+    transitions::{TransitionManager, TransitionType},
+};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    types::{FrameRate, TimePosition, TrackType},
+};
+
+/// Magic number of an encoded operation batch ("EVSB").
+pub const SCRIPT_BATCH_MAGIC: u32 = 0x4556_5342;
+
+/// A single scripted edit.
+///
+/// Opcodes are part of the encoding and never change meaning; new
+/// operations get new opcodes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptOperation {
+    /// Add a track.
+    AddTrack { name: String, track_type: TrackType },
+    /// Remove an unlocked track.
+    RemoveTrack { track_id: u64 },
+    /// Move a clip (and its linked clips) to an exact start.
+    MoveClip { clip_id: u64, start: TimePosition },
+    /// Trim the start of a clip.
+    TrimClipStart { clip_id: u64, start: TimePosition },
+    /// Trim the end of a clip.
+    TrimClipEnd { clip_id: u64, end: TimePosition },
+    /// Split a clip at a timeline position.
+    SplitClip { clip_id: u64, at: TimePosition },
+    /// Remove a clip and close the gap on its track.
+    RippleDelete { clip_id: u64 },
+    /// Add a named marker.
+    AddMarker { at: TimePosition, name: String },
+    /// Remove a marker.
+    RemoveMarker { marker_id: u64 },
+    /// Add a crossfade centered on the cut between two adjacent clips.
+    AddCrossfade { track_id: u64, clip_a_id: u64, clip_b_id: u64, frames: u32 },
+    /// Add a crossfade at every cut on a track, or on every video track
+    /// when `track_id` is `None`. Cuts that already have a transition are
+    /// skipped.
+    CrossfadeAllCuts { track_id: Option<u64>, frames: u32 },
+}
+
+impl ScriptOperation {
+    /// Stable operation name for logs and script errors.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AddTrack { .. } => "add_track",
+            Self::RemoveTrack { .. } => "remove_track",
+            Self::MoveClip { .. } => "move_clip",
+            Self::TrimClipStart { .. } => "trim_clip_start",
+            Self::TrimClipEnd { .. } => "trim_clip_end",
+            Self::SplitClip { .. } => "split_clip",
+            Self::RippleDelete { .. } => "ripple_delete",
+            Self::AddMarker { .. } => "add_marker",
+            Self::RemoveMarker { .. } => "remove_marker",
+            Self::AddCrossfade { .. } => "add_crossfade",
+            Self::CrossfadeAllCuts { .. } => "crossfade_all_cuts",
+        }
+    }
+
+    fn opcode(&self) -> u8 {
+        match self {
+            Self::AddTrack { .. } => 0,
+            Self::RemoveTrack { .. } => 1,
+            Self::MoveClip { .. } => 2,
+            Self::TrimClipStart { .. } => 3,
+            Self::TrimClipEnd { .. } => 4,
+            Self::SplitClip { .. } => 5,
+            Self::RippleDelete { .. } => 6,
+            Self::AddMarker { .. } => 7,
+            Self::RemoveMarker { .. } => 8,
+            Self::AddCrossfade { .. } => 9,
+            Self::CrossfadeAllCuts { .. } => 10,
+        }
+    }
+
+    /// Converts to bytes (opcode followed by little-endian fields).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.opcode()];
+        match self {
+            Self::AddTrack { name, track_type } => {
+                bytes.push(track_type_code(*track_type));
+                put_str(&mut bytes, name);
+            },
+            Self::RemoveTrack { track_id } => put_u64(&mut bytes, *track_id),
+            Self::MoveClip { clip_id, start: at }
+            | Self::TrimClipStart { clip_id, start: at }
+            | Self::TrimClipEnd { clip_id, end: at }
+            | Self::SplitClip { clip_id, at } => {
+                put_u64(&mut bytes, *clip_id);
+                put_u64(&mut bytes, at.ms);
+            },
+            Self::RippleDelete { clip_id } => put_u64(&mut bytes, *clip_id),
+            Self::AddMarker { at, name } => {
+                put_u64(&mut bytes, at.ms);
+                put_str(&mut bytes, name);
+            },
+            Self::RemoveMarker { marker_id } => put_u64(&mut bytes, *marker_id),
+            Self::AddCrossfade { track_id, clip_a_id, clip_b_id, frames } => {
+                put_u64(&mut bytes, *track_id);
+                put_u64(&mut bytes, *clip_a_id);
+                put_u64(&mut bytes, *clip_b_id);
+                bytes.extend_from_slice(&frames.to_le_bytes());
+            },
+            Self::CrossfadeAllCuts { track_id, frames } => {
+                // 0 = all video tracks; track IDs start at 1
+                put_u64(&mut bytes, track_id.unwrap_or(0));
+                bytes.extend_from_slice(&frames.to_le_bytes());
+            },
+        }
+        bytes
+    }
+
+    /// Parses one operation, returning it with the number of bytes read.
+    pub fn from_bytes(bytes: &[u8]) -> Option<(Self, usize)> {
+        let mut reader = Reader { bytes, offset: 0 };
+        let op = match reader.u8()? {
+            0 => {
+                let track_type = track_type_from_code(reader.u8()?)?;
+                Self::AddTrack { name: reader.string()?, track_type }
+            },
+            1 => Self::RemoveTrack { track_id: reader.u64()? },
+            opcode @ 2..=5 => {
+                let clip_id = reader.u64()?;
+                let at = TimePosition::from_ms(reader.u64()?);
+                match opcode {
+                    2 => Self::MoveClip { clip_id, start: at },
+                    3 => Self::TrimClipStart { clip_id, start: at },
+                    4 => Self::TrimClipEnd { clip_id, end: at },
+                    _ => Self::SplitClip { clip_id, at },
+                }
+            },
+            6 => Self::RippleDelete { clip_id: reader.u64()? },
+            7 => {
+                let at = TimePosition::from_ms(reader.u64()?);
+                Self::AddMarker { at, name: reader.string()? }
+            },
+            8 => Self::RemoveMarker { marker_id: reader.u64()? },
+            9 => Self::AddCrossfade {
+                track_id:  reader.u64()?,
+                clip_a_id: reader.u64()?,
+                clip_b_id: reader.u64()?,
+                frames:    reader.u32()?,
+            },
+            10 => {
+                let track_id = Some(reader.u64()?).filter(|&id| id != 0);
+                Self::CrossfadeAllCuts { track_id, frames: reader.u32()? }
+            },
+            _ => return None,
+        };
+        Some((op, reader.offset))
+    }
+
+    /// Encodes a batch of operations.
+    pub fn encode_batch(ops: &[Self]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&SCRIPT_BATCH_MAGIC.to_le_bytes());
+        bytes.extend_from_slice(&(ops.len() as u32).to_le_bytes());
+        for op in ops {
+            bytes.extend_from_slice(&op.to_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes a batch written by [`Self::encode_batch`].
+    pub fn decode_batch(bytes: &[u8]) -> Option<Vec<Self>> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.u32()? != SCRIPT_BATCH_MAGIC {
+            return None;
+        }
+        let count = reader.u32()? as usize;
+        let mut ops = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            let (op, len) = Self::from_bytes(&bytes[reader.offset..])?;
+            reader.offset += len;
+            ops.push(op);
+        }
+        Some(ops)
+    }
+}
+
+pub(super) fn put_u64(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+pub(super) fn put_str(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+fn track_type_code(track_type: TrackType) -> u8 {
+    match track_type {
+        TrackType::Video => 0,
+        TrackType::Audio => 1,
+        TrackType::Subtitle => 2,
+        TrackType::Data => 3,
+        TrackType::Effect => 4,
+    }
+}
+
+fn track_type_from_code(code: u8) -> Option<TrackType> {
+    Some(match code {
+        0 => TrackType::Video,
+        1 => TrackType::Audio,
+        2 => TrackType::Subtitle,
+        3 => TrackType::Data,
+        4 => TrackType::Effect,
+        _ => return None,
+    })
+}
+
+pub(super) struct Reader<'a> {
+    pub(super) bytes:  &'a [u8],
+    pub(super) offset: usize,
+}
+
+impl Reader<'_> {
+    pub(super) fn take(&mut self, len: usize) -> Option<&[u8]> {
+        let field = self.bytes.get(self.offset..self.offset.checked_add(len)?)?;
+        self.offset += len;
+        Some(field)
+    }
+
+    pub(super) fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    pub(super) fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    pub(super) fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    pub(super) fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+}
+
+/// What an operation produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OperationOutput {
+    /// Nothing to report.
+    Done,
+    /// ID of a new track.
+    Track(u64),
+    /// ID of a new clip.
+    Clip(u64),
+    /// ID of a new marker.
+    Marker(u64),
+    /// IDs of new transitions.
+    Transitions(Vec<u64>),
+}
+
+/// Result of a batch run.
+///
+/// Batches stop at the first failing operation; earlier operations stay
+/// applied.
+#[derive(Debug)]
+pub struct ScriptBatchResult {
+    /// Outputs of the operations that ran, in order.
+    pub outputs: Vec<OperationOutput>,
+    /// Index and error of the operation that failed.
+    pub error:   Option<(usize, VideoEditorError)>,
+}
+
+impl ScriptBatchResult {
+    /// Returns whether every operation ran.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// High-level editing facade for host scripts.
+pub struct EditorScriptApi<'a> {
+    timeline:    &'a mut TimelineManager,
+    transitions: &'a mut TransitionManager,
+    markers:     &'a mut MarkerManager,
+    frame_rate:  FrameRate,
+}
+
+impl<'a> EditorScriptApi<'a> {
+    /// Create a facade over the editor's managers.
+    pub(crate) fn new(
+        timeline: &'a mut TimelineManager, transitions: &'a mut TransitionManager,
+        markers: &'a mut MarkerManager, frame_rate: FrameRate,
+    ) -> Self {
+        Self { timeline, transitions, markers, frame_rate }
+    }
+
+    /// Run one operation.
+    ///
+    /// Snapping is off while scripts edit, so positions are exact.
+    pub fn run(&mut self, op: &ScriptOperation) -> VideoEditorResult<OperationOutput> {
+        let snapping = self.timeline.snap_engine().enabled;
+        self.timeline.snap_engine_mut().enabled = false;
+        let output = self.apply(op);
+        self.timeline.snap_engine_mut().enabled = snapping;
+        output
+    }
+
+    /// Run operations in order, stopping at the first failure.
+    pub fn run_batch(&mut self, ops: Vec<ScriptOperation>) -> ScriptBatchResult {
+        let mut outputs = Vec::with_capacity(ops.len());
+        for (index, op) in ops.iter().enumerate() {
+            match self.run(op) {
+                Ok(output) => outputs.push(output),
+                Err(error) => return ScriptBatchResult { outputs, error: Some((index, error)) },
+            }
+        }
+        ScriptBatchResult { outputs, error: None }
+    }
+
+    /// Cuts on a track as `(outgoing clip, incoming clip, cut position)`:
+    /// places where one clip ends exactly where the next starts.
+    pub fn cuts(&self, track_id: u64) -> Vec<(u64, u64, TimePosition)> {
+        self.timeline
+            .get_track(track_id)
+            .map(|track| {
+                track
+                    .clips
+                    .windows(2)
+                    .filter(|pair| pair[0].end().ms == pair[1].start.ms)
+                    .map(|pair| (pair[0].id, pair[1].id, pair[1].start))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn apply(&mut self, op: &ScriptOperation) -> VideoEditorResult<OperationOutput> {
+        match op {
+            ScriptOperation::AddTrack { name, track_type } => {
+                Ok(OperationOutput::Track(self.timeline.add_track(name.clone(), *track_type)))
+            },
+            ScriptOperation::RemoveTrack { track_id } => {
+                if self.timeline.remove_track(*track_id) {
+                    Ok(OperationOutput::Done)
+                } else {
+                    Err(VideoEditorError::timeline(
+                        "script",
+                        format!("Track {track_id} is missing or locked"),
+                    ))
+                }
+            },
+            ScriptOperation::MoveClip { clip_id, start } => {
+                self.timeline.move_clip(*clip_id, *start).map(|_| OperationOutput::Done)
+            },
+            ScriptOperation::TrimClipStart { clip_id, start } => {
+                self.timeline.trim_clip_start(*clip_id, *start).map(|_| OperationOutput::Done)
+            },
+            ScriptOperation::TrimClipEnd { clip_id, end } => {
+                self.timeline.trim_clip_end(*clip_id, *end).map(|_| OperationOutput::Done)
+            },
+            ScriptOperation::SplitClip { clip_id, at } => {
+                self.timeline.split_clip(*clip_id, *at).map(OperationOutput::Clip)
+            },
+            ScriptOperation::RippleDelete { clip_id } => self
+                .timeline
+                .ripple_delete(*clip_id, RippleSync::Track)
+                .map(|_| OperationOutput::Done),
+            ScriptOperation::AddMarker { at, name } => {
+                let id = self.markers.add_marker(*at, MarkerType::Standard);
+                if let Some(marker) = self.markers.get_marker_mut(id) {
+                    marker.set_name(name.clone());
+                }
+                Ok(OperationOutput::Marker(id.inner()))
+            },
+            ScriptOperation::RemoveMarker { marker_id } => {
+                if self.markers.remove_marker(MarkerId::new(*marker_id)) {
+                    Ok(OperationOutput::Done)
+                } else {
+                    Err(VideoEditorError::timeline(
+                        "script",
+                        format!("Marker {marker_id} is missing or locked"),
+                    ))
+                }
+            },
+            ScriptOperation::AddCrossfade { track_id, clip_a_id, clip_b_id, frames } => {
+                let cut = self
+                    .cuts(*track_id)
+                    .into_iter()
+                    .find(|&(a, b, _)| a == *clip_a_id && b == *clip_b_id)
+                    .ok_or_else(|| {
+                        VideoEditorError::timeline(
+                            "script",
+                            format!("Clips {clip_a_id} and {clip_b_id} don't meet at a cut"),
+                        )
+                    })?;
+                let id = self.add_crossfade(*track_id, cut, *frames)?;
+                Ok(OperationOutput::Transitions(vec![id]))
+            },
+            ScriptOperation::CrossfadeAllCuts { track_id, frames } => {
+                let tracks: Vec<u64> = match track_id {
+                    Some(id) => vec![*id],
+                    None => self
+                        .timeline
+                        .tracks()
+                        .iter()
+                        .filter(|t| t.track_type == TrackType::Video)
+                        .map(|t| t.id)
+                        .collect(),
+                };
+                let mut added = Vec::new();
+                for track in tracks {
+                    for cut in self.cuts(track) {
+                        let exists = self
+                            .transitions
+                            .transitions_for_track(track)
+                            .iter()
+                            .any(|t| t.clip_a_id == cut.0 && t.clip_b_id == cut.1);
+                        if !exists {
+                            added.push(self.add_crossfade(track, cut, *frames)?);
+                        }
+                    }
+                }
+                Ok(OperationOutput::Transitions(added))
+            },
+        }
+    }
+
+    fn add_crossfade(
+        &mut self, track_id: u64, (clip_a, clip_b, cut): (u64, u64, TimePosition), frames: u32,
+    ) -> VideoEditorResult<u64> {
+        let duration_ms = u64::from(frames) * self.frame_rate.frame_duration_us() / 1000;
+        let start = cut.ms.checked_sub(duration_ms / 2).ok_or_else(|| {
+            VideoEditorError::timeline(
+                "add_crossfade",
+                format!("Crossfade at {} ms would start before the timeline", cut.ms),
+            )
+        })?;
+        let id = self.transitions.add_transition(
+            track_id,
+            clip_a,
+            clip_b,
+            TimePosition::from_ms(start),
+            Some(TransitionType::CrossFade),
+            Some(TimePosition::from_ms(duration_ms)),
+        );
+        Ok(id.inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_encoding_round_trip() {
+        let ops = vec![
+            ScriptOperation::AddTrack { name: "B-roll".into(), track_type: TrackType::Video },
+            ScriptOperation::MoveClip { clip_id: 3, start: TimePosition::from_ms(1500) },
+            ScriptOperation::AddMarker { at: TimePosition::from_secs(2), name: "Beat".into() },
+            ScriptOperation::AddCrossfade {
+                track_id:  1,
+                clip_a_id: 2,
+                clip_b_id: 3,
+                frames:    10,
+            },
+            ScriptOperation::CrossfadeAllCuts { track_id: None, frames: 10 },
+        ];
+        let bytes = ScriptOperation::encode_batch(&ops);
+        assert_eq!(ScriptOperation::decode_batch(&bytes), Some(ops));
+        assert!(ScriptOperation::decode_batch(&bytes[..bytes.len() - 1]).is_none());
+    }
+}
@@ -0,0 +1,266 @@
+//! Timeline selection.
+//!
+//! The [`SelectionModel`] holds what the user picked on the timeline:
+//! individual clips and at most one time range across a set of tracks.
+//! The timeline resolves it into the clips an edit touches, adding linked
+//! clips, and its delete, nudge and effect edits apply to the whole
+//! selection or fail without changing anything.
+
+use crate::types::TimePosition;
+
+/// Time range selected across tracks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectionRange {
+    /// Range start.
+    pub start:     TimePosition,
+    /// Range end (exclusive).
+    pub end:       TimePosition,
+    /// Tracks the range spans; empty for every track.
+    pub track_ids: Vec<u64>,
+}
+
+impl SelectionRange {
+    /// Creates a range between two times on `track_ids` (every track when
+    /// empty), in either order.
+    #[must_use]
+    pub fn new(a: TimePosition, b: TimePosition, track_ids: &[u64]) -> Self {
+        let mut ids = Vec::with_capacity(track_ids.len());
+        for &track_id in track_ids {
+            if !ids.contains(&track_id) {
+                ids.push(track_id);
+            }
+        }
+        let (start, end) = if a.ms <= b.ms { (a, b) } else { (b, a) };
+        Self { start, end, track_ids: ids }
+    }
+
+    /// Checks if the range spans a track.
+    #[must_use]
+    pub fn covers_track(&self, track_id: u64) -> bool {
+        self.track_ids.is_empty() || self.track_ids.contains(&track_id)
+    }
+}
+
+/// Clips and range selected on the timeline.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectionModel {
+    /// Picked clip IDs in the order they were selected.
+    clips: Vec<u64>,
+    /// Selected range across tracks.
+    range: Option<SelectionRange>,
+}
+
+impl SelectionModel {
+    /// Creates an empty selection.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks if nothing is selected.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.clips.is_empty() && self.range.is_none()
+    }
+
+    /// Returns the picked clip IDs, without linked clips or clips in the
+    /// range.
+    #[must_use]
+    pub fn clips(&self) -> &[u64] {
+        &self.clips
+    }
+
+    /// Returns the selected range.
+    #[must_use]
+    pub fn range(&self) -> Option<&SelectionRange> {
+        self.range.as_ref()
+    }
+
+    /// Checks if a clip was picked.
+    #[must_use]
+    pub fn contains_clip(&self, clip_id: u64) -> bool {
+        self.clips.contains(&clip_id)
+    }
+
+    /// Selects a clip, replacing the selection unless `add_to_selection`.
+    pub fn select_clip(&mut self, clip_id: u64, add_to_selection: bool) {
+        if !add_to_selection {
+            self.clear();
+        }
+        if !self.clips.contains(&clip_id) {
+            self.clips.push(clip_id);
+        }
+    }
+
+    /// Adds a clip to the selection, or removes it if already picked.
+    pub fn toggle_clip(&mut self, clip_id: u64) {
+        if !self.deselect_clip(clip_id) {
+            self.clips.push(clip_id);
+        }
+    }
+
+    /// Deselects a clip.
+    pub fn deselect_clip(&mut self, clip_id: u64) -> bool {
+        let before = self.clips.len();
+        self.clips.retain(|&id| id != clip_id);
+        self.clips.len() != before
+    }
+
+    /// Replaces the selection with clips, dropping duplicates.
+    pub fn select_clips(&mut self, clip_ids: &[u64]) {
+        self.clear();
+        for &clip_id in clip_ids {
+            self.select_clip(clip_id, true);
+        }
+    }
+
+    /// Selects a range, replacing the selection unless `add_to_selection`,
+    /// in which case only an earlier range is replaced.
+    pub fn select_range(&mut self, range: SelectionRange, add_to_selection: bool) {
+        if !add_to_selection {
+            self.clips.clear();
+        }
+        self.range = Some(range);
+    }
+
+    /// Clears the range, keeping picked clips.
+    pub fn clear_range(&mut self) {
+        self.range = None;
+    }
+
+    /// Clears the selection.
+    pub fn clear(&mut self) {
+        self.clips.clear();
+        self.range = None;
+    }
+
+    /// Keeps only the picked clips matching `keep`.
+    pub(super) fn retain_clips(&mut self, keep: impl FnMut(&u64) -> bool) {
+        self.clips.retain(keep);
+    }
+
+    /// Moves the range by `delta_ms`, stopping at the timeline start.
+    pub(super) fn offset_range(&mut self, delta_ms: i64) {
+        if let Some(range) = &mut self.range {
+            range.start.ms = range.start.ms.saturating_add_signed(delta_ms);
+            range.end.ms = range.end.ms.saturating_add_signed(delta_ms);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        implementation::{RippleSync, TimelineManager},
+        types::{EditSuggestion, EditSuggestionKind, TrackType, timeline::TimelineClip},
+    };
+
+    /// Two video tracks with clips 1-3 and 4-5 a second apart, and a sound
+    /// clip 6 linked to clip 1.
+    fn timeline() -> (TimelineManager, [u64; 3]) {
+        let mut timeline = TimelineManager::new();
+        let v1 = timeline.add_track("V1", TrackType::Video);
+        let v2 = timeline.add_track("V2", TrackType::Video);
+        let a1 = timeline.add_track("A1", TrackType::Audio);
+        for (track, id, start) in [(v1, 1, 0), (v1, 2, 2), (v1, 3, 4), (v2, 4, 1), (v2, 5, 5)] {
+            let clip = TimelineClip::new(
+                id,
+                1,
+                TimePosition::from_secs(start),
+                TimePosition::from_secs(1),
+            );
+            timeline.add_clip(track, clip).expect("test assertion");
+        }
+        let sound = TimelineClip::new(6, 2, TimePosition::from_ms(0), TimePosition::from_secs(1));
+        timeline.add_clip(a1, sound).expect("test assertion");
+        timeline.link_clips(&[1, 6]).expect("test assertion");
+        (timeline, [v1, v2, a1])
+    }
+
+    #[test]
+    fn test_clip_and_range_selection() {
+        let (mut timeline, [v1, v2, _]) = timeline();
+        let selection = timeline.selection_mut();
+        selection.select_clip(3, false);
+        selection.select_clip(2, true);
+        selection.toggle_clip(3);
+        assert_eq!(selection.clips(), [2]);
+        selection.select_clip(1, false);
+        // Linked clips come along, in timeline order
+        assert_eq!(timeline.selected_clips(), vec![1, 6]);
+        timeline.set_linked_selection(false);
+        assert_eq!(timeline.selected_clips(), vec![1]);
+
+        // The range covers clips it overlaps on its tracks
+        let (from, to) = (TimePosition::from_secs(5), TimePosition::from_ms(1_500));
+        let range = SelectionRange::new(from, to, &[v2, v1, v2]);
+        assert_eq!((range.start.ms, range.track_ids.len()), (1_500, 2));
+        timeline.selection_mut().select_range(range.clone(), true);
+        assert_eq!(timeline.selected_clips(), vec![1, 2, 3, 4]);
+        timeline.selection_mut().select_range(range, false);
+        assert_eq!(timeline.selected_clips(), vec![2, 3, 4]);
+
+        timeline.select_forward(TimePosition::from_ms(4_500), &[], false);
+        assert_eq!(timeline.selected_clips(), vec![3, 5]);
+        timeline.select_forward(TimePosition::from_secs(2), &[v2], true);
+        assert_eq!(timeline.selected_clips(), vec![3, 5]);
+        timeline.select_forward(TimePosition::from_ms(1_500), &[v2], true);
+        assert_eq!(timeline.selected_clips(), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_selection_edits_are_atomic() {
+        let (mut timeline, [_, _, a1]) = timeline();
+        let starts = |timeline: &TimelineManager| -> Vec<u64> {
+            timeline.tracks().iter().flat_map(|t| &t.clips).map(|c| c.start.ms).collect()
+        };
+        timeline.selection_mut().select_clips(&[1, 4]);
+
+        // Clip 1 can't go before the start, and once its linked sound is
+        // locked nothing in the selection changes
+        assert!(timeline.nudge_selection(-500).is_err());
+        timeline.set_track_locked(a1, true);
+        assert!(timeline.nudge_selection(500).is_err());
+        assert!(timeline.apply_effects_to_selection(&[9]).is_err());
+        assert!(timeline.delete_selection().is_err());
+        assert_eq!(starts(&timeline), vec![0, 2_000, 4_000, 1_000, 5_000, 0]);
+        assert!(timeline.tracks().iter().flat_map(|t| &t.clips).all(|c| c.effect_ids.is_empty()));
+
+        timeline.set_track_locked(a1, false);
+        timeline.nudge_selection(500).expect("test assertion");
+        assert_eq!(starts(&timeline), vec![500, 2_000, 4_000, 1_500, 5_000, 500]);
+        // Clip 1 would run into clip 2
+        assert!(timeline.nudge_selection(1_000).is_err());
+        let changed = timeline.apply_effects_to_selection(&[9]).expect("test assertion");
+        assert_eq!(changed, vec![1, 4, 6]);
+        assert_eq!(timeline.tracks()[0].clips[0].effect_ids, vec![9]);
+
+        let removed = timeline.delete_selection().expect("test assertion");
+        assert_eq!(removed.len(), 3);
+        assert!(timeline.selection().is_empty());
+        // Clips around the removed ones stay put
+        assert_eq!(starts(&timeline), vec![2_000, 4_000, 5_000]);
+    }
+
+    #[test]
+    fn test_ripple_cuts_drop_removed_clips_from_selection() {
+        let (mut timeline, [v1, _, _]) = timeline();
+        timeline.selection_mut().select_clips(&[2, 3]);
+        let (start, end) = (TimePosition::from_ms(1_500), TimePosition::from_ms(3_500));
+        timeline.ripple_delete_range(v1, start, end, RippleSync::Track).expect("test assertion");
+        assert_eq!(timeline.selection().clips(), [3]);
+
+        // Suggested cuts go through the same path
+        let suggestion = EditSuggestion {
+            track_id: v1,
+            clip_id:  3,
+            start:    TimePosition::from_ms(2_000),
+            end:      TimePosition::from_ms(3_000),
+            kind:     EditSuggestionKind::RemoveSilence,
+        };
+        let cut = timeline.apply_suggestions(&[suggestion], RippleSync::Track);
+        assert_eq!(cut.expect("test assertion"), 1);
+        assert!(timeline.selection().is_empty());
+    }
+}
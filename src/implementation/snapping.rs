@@ -0,0 +1,335 @@
+//! Timeline snapping and magnetic edit points.
+//!
+//! The snap engine collects candidate positions (clip edges, markers, the
+//! playhead and the zoom-dependent grid) and pulls edit positions onto the
+//! nearest candidate within a tolerance. Timeline move/trim operations snap
+//! through it; hosts feed it markers, the playhead and the viewport.
+
+use super::playhead_follow::TimelineViewport;
+use crate::types::{TimePosition, TimelineTrack};
+
+/// What a snapped position was attracted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SnapSource {
+    /// Start of a clip.
+    ClipStart {
+        /// Track holding the clip.
+        track_id: u64,
+        /// Clip ID.
+        clip_id:  u64,
+    },
+    /// End of a clip.
+    ClipEnd {
+        /// Track holding the clip.
+        track_id: u64,
+        /// Clip ID.
+        clip_id:  u64,
+    },
+    /// Timeline marker.
+    Marker(u64),
+    /// Playhead.
+    Playhead,
+    /// Grid line at the current zoom.
+    Grid,
+}
+
+impl SnapSource {
+    /// Returns the priority when two candidates are equally close (higher wins).
+    #[must_use]
+    pub const fn priority(&self) -> u8 {
+        match self {
+            Self::Playhead => 4,
+            Self::Marker(_) => 3,
+            Self::ClipStart { .. } | Self::ClipEnd { .. } => 2,
+            Self::Grid => 1,
+        }
+    }
+}
+
+/// A position edits can snap to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapCandidate {
+    /// Candidate position.
+    pub position: TimePosition,
+    /// What the candidate belongs to.
+    pub source:   SnapSource,
+}
+
+/// Result of snapping a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnappedPosition {
+    /// Resulting position.
+    pub position: TimePosition,
+    /// Position before snapping.
+    pub original: TimePosition,
+    /// Snap source, or `None` if the position was left as is.
+    pub source:   Option<SnapSource>,
+}
+
+impl SnappedPosition {
+    /// Returns whether the position was snapped.
+    #[must_use]
+    pub const fn is_snapped(&self) -> bool {
+        self.source.is_some()
+    }
+
+    /// Returns the signed offset applied by snapping in milliseconds.
+    #[must_use]
+    pub fn offset_ms(&self) -> i64 {
+        self.position.ms as i64 - self.original.ms as i64
+    }
+}
+
+/// Snap candidate collection and lookup.
+#[derive(Debug, Clone)]
+pub struct SnapEngine {
+    /// Master snapping switch.
+    pub enabled:       bool,
+    /// Snap to clip edges.
+    pub snap_clips:    bool,
+    /// Snap to markers.
+    pub snap_markers:  bool,
+    /// Snap to the playhead.
+    pub snap_playhead: bool,
+    /// Snap to the grid.
+    pub snap_to_grid:  bool,
+    /// Snap tolerance in pixels at the current zoom.
+    pub tolerance_px:  f64,
+    candidates:        Vec<SnapCandidate>,
+    playhead:          Option<TimePosition>,
+    pixels_per_second: f64,
+}
+
+impl SnapEngine {
+    /// Default snap tolerance in pixels.
+    pub const DEFAULT_TOLERANCE_PX: f64 = 8.0;
+    /// Minimum spacing between grid lines in pixels.
+    pub const MIN_GRID_SPACING_PX: f64 = 40.0;
+    /// Grid intervals in milliseconds, finest first.
+    const GRID_STEPS_MS: [u64; 14] =
+        [10, 20, 50, 100, 250, 500, 1_000, 2_000, 5_000, 10_000, 15_000, 30_000, 60_000, 300_000];
+
+    /// Creates a snap engine with every snap source enabled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            enabled:           true,
+            snap_clips:        true,
+            snap_markers:      true,
+            snap_playhead:     true,
+            snap_to_grid:      true,
+            tolerance_px:      Self::DEFAULT_TOLERANCE_PX,
+            candidates:        Vec::new(),
+            playhead:          None,
+            pixels_per_second: 100.0,
+        }
+    }
+
+    /// Rebuilds clip edge candidates, skipping the clips being edited.
+    pub fn set_clip_edges(&mut self, tracks: &[TimelineTrack], exclude: &[u64]) {
+        self.candidates.retain(|c| {
+            !matches!(c.source, SnapSource::ClipStart { .. } | SnapSource::ClipEnd { .. })
+        });
+        for track in tracks {
+            for clip in track.clips.iter().filter(|c| !exclude.contains(&c.id)) {
+                let (track_id, clip_id) = (track.id, clip.id);
+                self.candidates.push(SnapCandidate {
+                    position: clip.start,
+                    source:   SnapSource::ClipStart { track_id, clip_id },
+                });
+                self.candidates.push(SnapCandidate {
+                    position: clip.end(),
+                    source:   SnapSource::ClipEnd { track_id, clip_id },
+                });
+            }
+        }
+    }
+
+    /// Replaces marker candidates with `(marker_id, position)` pairs.
+    pub fn set_markers(&mut self, markers: impl IntoIterator<Item = (u64, TimePosition)>) {
+        self.candidates.retain(|c| !matches!(c.source, SnapSource::Marker(_)));
+        self.candidates.extend(
+            markers
+                .into_iter()
+                .map(|(id, position)| SnapCandidate { position, source: SnapSource::Marker(id) }),
+        );
+    }
+
+    /// Sets the playhead position.
+    pub fn set_playhead(&mut self, playhead: TimePosition) {
+        self.playhead = Some(playhead);
+    }
+
+    /// Updates the zoom used for the grid and pixel tolerance.
+    pub fn set_viewport(&mut self, viewport: &TimelineViewport) {
+        self.pixels_per_second = viewport.pixels_per_second.max(f64::EPSILON);
+    }
+
+    /// Returns the snap tolerance at the current zoom.
+    #[must_use]
+    pub fn tolerance(&self) -> TimePosition {
+        TimePosition::from_ms((self.tolerance_px / self.pixels_per_second * 1000.0).round() as u64)
+    }
+
+    /// Returns the grid interval at the current zoom in milliseconds.
+    #[must_use]
+    pub fn grid_interval_ms(&self) -> u64 {
+        let min_ms = Self::MIN_GRID_SPACING_PX / self.pixels_per_second * 1000.0;
+        Self::GRID_STEPS_MS
+            .iter()
+            .copied()
+            .find(|&step| step as f64 >= min_ms)
+            .unwrap_or(Self::GRID_STEPS_MS[Self::GRID_STEPS_MS.len() - 1])
+    }
+
+    /// Returns all enabled candidates, including the playhead.
+    pub fn candidates(&self) -> impl Iterator<Item = SnapCandidate> + '_ {
+        let playhead = self
+            .playhead
+            .filter(|_| self.snap_playhead)
+            .map(|position| SnapCandidate { position, source: SnapSource::Playhead });
+        self.candidates
+            .iter()
+            .copied()
+            .filter(|c| match c.source {
+                SnapSource::ClipStart { .. } | SnapSource::ClipEnd { .. } => self.snap_clips,
+                SnapSource::Marker(_) => self.snap_markers,
+                SnapSource::Playhead | SnapSource::Grid => true,
+            })
+            .chain(playhead)
+    }
+
+    /// Snaps a position to the nearest candidate within `tolerance`.
+    #[must_use]
+    pub fn snap(&self, position: TimePosition, tolerance: TimePosition) -> SnappedPosition {
+        let mut best: Option<(u64, SnapCandidate)> = None;
+        if self.enabled {
+            let grid = self.snap_to_grid.then(|| {
+                let step = self.grid_interval_ms();
+                let line = (position.ms + step / 2) / step * step;
+                SnapCandidate { position: TimePosition::from_ms(line), source: SnapSource::Grid }
+            });
+
+            for candidate in self.candidates().chain(grid) {
+                let distance = candidate.position.ms.abs_diff(position.ms);
+                if distance > tolerance.ms {
+                    continue;
+                }
+                let better = best.is_none_or(|(d, b)| {
+                    distance < d
+                        || (distance == d && candidate.source.priority() > b.source.priority())
+                });
+                if better {
+                    best = Some((distance, candidate));
+                }
+            }
+        }
+
+        let mut snapped = SnappedPosition { position, original: position, source: None };
+        if let Some((_, candidate)) = best {
+            snapped.position = candidate.position;
+            snapped.source = Some(candidate.source);
+        }
+        snapped
+    }
+
+    /// Snaps a clip span so that whichever edge is closer to a candidate
+    /// lands on it. Returns the snapped start.
+    #[must_use]
+    pub fn snap_span(
+        &self, start: TimePosition, duration: TimePosition, tolerance: TimePosition,
+    ) -> SnappedPosition {
+        let head = self.snap(start, tolerance);
+        let tail = self.snap(start + duration, tolerance);
+
+        if tail.is_snapped()
+            && (!head.is_snapped() || tail.offset_ms().abs() < head.offset_ms().abs())
+        {
+            let shifted = (start.ms as i64 + tail.offset_ms()).max(0) as u64;
+            SnappedPosition {
+                position: TimePosition::from_ms(shifted),
+                original: start,
+                source:   tail.source,
+            }
+        } else {
+            head
+        }
+    }
+}
+
+impl Default for SnapEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::timeline::TimelineClip;
+
+    fn track_with_clip() -> TimelineTrack {
+        let mut track = TimelineTrack::new(1, "V1", crate::types::TrackType::Video, 0);
+        track.add_clip(TimelineClip::new(
+            7,
+            1,
+            TimePosition::from_ms(1_000),
+            TimePosition::from_ms(2_000),
+        ));
+        track
+    }
+
+    #[test]
+    fn test_snap_to_clip_edges_and_playhead() {
+        let mut engine = SnapEngine::new();
+        engine.snap_to_grid = false;
+        engine.set_clip_edges(&[track_with_clip()], &[]);
+        engine.set_playhead(TimePosition::from_ms(3_010));
+
+        let snapped = engine.snap(TimePosition::from_ms(1_030), TimePosition::from_ms(50));
+        assert_eq!(snapped.position.ms, 1_000);
+        assert_eq!(snapped.source, Some(SnapSource::ClipStart { track_id: 1, clip_id: 7 }));
+
+        // Clip end (3000) is farther than the playhead (3010) from 3008
+        let snapped = engine.snap(TimePosition::from_ms(3_008), TimePosition::from_ms(50));
+        assert_eq!(snapped.source, Some(SnapSource::Playhead));
+
+        let free = engine.snap(TimePosition::from_ms(5_000), TimePosition::from_ms(50));
+        assert!(!free.is_snapped());
+        assert_eq!(free.position.ms, 5_000);
+
+        engine.set_clip_edges(&[track_with_clip()], &[7]);
+        assert!(!engine.snap(TimePosition::from_ms(1_030), TimePosition::from_ms(50)).is_snapped());
+    }
+
+    #[test]
+    fn test_grid_follows_zoom() {
+        let mut engine = SnapEngine::new();
+        engine.set_viewport(&TimelineViewport::new(1_000.0, 100.0));
+        assert_eq!(engine.grid_interval_ms(), 500);
+        assert_eq!(engine.tolerance().ms, 80);
+
+        let snapped = engine.snap(TimePosition::from_ms(1_460), engine.tolerance());
+        assert_eq!(snapped.position.ms, 1_500);
+        assert_eq!(snapped.source, Some(SnapSource::Grid));
+
+        engine.set_viewport(&TimelineViewport::new(1_000.0, 1.0));
+        assert_eq!(engine.grid_interval_ms(), 60_000);
+    }
+
+    #[test]
+    fn test_snap_span_uses_closest_edge() {
+        let mut engine = SnapEngine::new();
+        engine.snap_to_grid = false;
+        engine.set_markers([(3, TimePosition::from_ms(5_000))]);
+
+        // Dragging a 1s clip to 3980: its end (4980) snaps to the marker
+        let snapped = engine.snap_span(
+            TimePosition::from_ms(3_980),
+            TimePosition::from_ms(1_000),
+            TimePosition::from_ms(50),
+        );
+        assert_eq!(snapped.position.ms, 4_000);
+        assert_eq!(snapped.source, Some(SnapSource::Marker(3)));
+    }
+}
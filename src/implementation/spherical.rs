@@ -0,0 +1,272 @@
+//! 360° reframing.
+//!
+//! Equirectangular clips hold the whole sphere around the camera. A
+//! [`SphericalView`] looks into it like a virtual camera, with yaw, pitch,
+//! roll and field of view keyframed on the clip's layer in the project's
+//! [`AnimationManager`] at times relative to the clip start. Preview and
+//! reframed exports draw that flat view; exports can instead keep the
+//! equirectangular frame and tag it as spherical.
+
+use super::keyframe_animation::{AnimatedValue, AnimationManager};
+use crate::{errors::VideoEditorResult, types::TimePosition};
+
+/// Virtual camera looking into a 360° frame, angles in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SphericalView {
+    /// Turn to the right, from the center of the frame.
+    pub yaw:   f32,
+    /// Tilt up.
+    pub pitch: f32,
+    /// Counter-clockwise roll about the view axis.
+    pub roll:  f32,
+    /// Horizontal field of view.
+    pub fov:   f32,
+}
+
+impl SphericalView {
+    /// Looking at the center of the frame with a 90° lens.
+    pub const FORWARD: Self = Self { yaw: 0.0, pitch: 0.0, roll: 0.0, fov: 90.0 };
+
+    /// Yaw track.
+    pub const YAW: &'static str = "reframe.yaw";
+    /// Pitch track.
+    pub const PITCH: &'static str = "reframe.pitch";
+    /// Roll track.
+    pub const ROLL: &'static str = "reframe.roll";
+    /// Field of view track.
+    pub const FOV: &'static str = "reframe.fov";
+
+    /// Evaluate the view of a clip at a time relative to its start. Clips
+    /// without reframe keyframes look forward.
+    #[must_use]
+    pub fn at(animation: &AnimationManager, clip_id: u64, time: TimePosition) -> Self {
+        let mut view = Self::FORWARD;
+        for (property, value) in animation.evaluate(clip_id, time) {
+            let Some(value) = value.as_float() else {
+                continue;
+            };
+            match property {
+                Self::YAW => view.yaw = value as f32,
+                Self::PITCH => view.pitch = value as f32,
+                Self::ROLL => view.roll = value as f32,
+                Self::FOV => view.fov = value as f32,
+                _ => {},
+            }
+        }
+        view
+    }
+
+    /// Keyframe this view on a clip at a time relative to its start,
+    /// creating the clip's layer and reframe tracks if needed.
+    pub fn keyframe(
+        &self, animation: &mut AnimationManager, clip_id: u64, time: TimePosition,
+    ) -> VideoEditorResult<()> {
+        if animation.get_layer(clip_id).is_none() {
+            animation.create_layer("Reframe", clip_id)?;
+        }
+        let Some(layer) = animation.get_layer_mut(clip_id) else {
+            return Ok(());
+        };
+        let rest = Self::FORWARD;
+        for (property, rest, value) in [
+            (Self::YAW, rest.yaw, self.yaw),
+            (Self::PITCH, rest.pitch, self.pitch),
+            (Self::ROLL, rest.roll, self.roll),
+            (Self::FOV, rest.fov, self.fov),
+        ] {
+            if layer.get_track_by_property(property).is_none() {
+                layer.create_track(property, AnimatedValue::Float(f64::from(rest)));
+            }
+            if let Some(track) = layer.get_track_by_property_mut(property) {
+                track.add_keyframe(time, AnimatedValue::Float(f64::from(value)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Rows of the rotation taking view rays to sphere directions. Rays
+    /// look down -Z with +Y up; the frame center is straight ahead.
+    #[must_use]
+    pub fn rotation(&self) -> [[f32; 3]; 3] {
+        let (sy, cy) = (-self.yaw).to_radians().sin_cos();
+        let (sp, cp) = self.pitch.to_radians().sin_cos();
+        let (sr, cr) = self.roll.to_radians().sin_cos();
+        // Yaw about Y, then pitch about X, then roll about Z, outermost first
+        let yaw = [[cy, 0.0, sy], [0.0, 1.0, 0.0], [-sy, 0.0, cy]];
+        let pitch = [[1.0, 0.0, 0.0], [0.0, cp, -sp], [0.0, sp, cp]];
+        let roll = [[cr, -sr, 0.0], [sr, cr, 0.0], [0.0, 0.0, 1.0]];
+        mul3(&mul3(&yaw, &pitch), &roll)
+    }
+
+    /// Half-width of the image plane one unit in front of the camera.
+    #[must_use]
+    pub fn half_width(&self) -> f32 {
+        (self.fov.clamp(1.0, 179.0).to_radians() / 2.0).tan()
+    }
+
+    /// Draw the view of an equirectangular RGBA8 frame as an `out_width`
+    /// x `out_height` flat frame.
+    pub fn apply(
+        &self, pixels: &[u8], width: u32, height: u32, out_width: u32, out_height: u32,
+    ) -> Vec<u8> {
+        let mut out = vec![0; out_width as usize * out_height as usize * 4];
+        self.apply_into(pixels, width, height, &mut out, out_width, out_height);
+        out
+    }
+
+    /// [`apply`](Self::apply) into caller memory holding at least
+    /// `out_width` x `out_height` RGBA8 pixels.
+    pub fn apply_into(
+        &self, pixels: &[u8], width: u32, height: u32, out: &mut [u8], out_width: u32,
+        out_height: u32,
+    ) {
+        let (w, h) = (width as usize, height as usize);
+        let (ow, oh) = (out_width as usize, out_height as usize);
+        if pixels.len() < w * h * 4 || w == 0 || h == 0 || ow == 0 {
+            return;
+        }
+        let rotation = self.rotation();
+        let half_width = self.half_width();
+        let half_height = half_width * oh as f32 / ow as f32;
+        let fetch = |x: isize, y: isize, channel: usize| {
+            // Longitude wraps, latitude stops at the poles
+            let x = x.rem_euclid(w as isize) as usize;
+            let y = y.clamp(0, h as isize - 1) as usize;
+            f32::from(pixels[(y * w + x) * 4 + channel])
+        };
+        for (y, row) in out.chunks_exact_mut(ow * 4).take(oh).enumerate() {
+            for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                let ray = [
+                    ((x as f32 + 0.5) / ow as f32 * 2.0 - 1.0) * half_width,
+                    (1.0 - (y as f32 + 0.5) / oh as f32 * 2.0) * half_height,
+                    -1.0,
+                ];
+                let [dx, dy, dz] = rotation.map(|r| r[0] * ray[0] + r[1] * ray[1] + r[2] * ray[2]);
+                let length = (dx * dx + dy * dy + dz * dz).sqrt();
+                let longitude = dx.atan2(-dz);
+                let latitude = (dy / length).clamp(-1.0, 1.0).asin();
+                let u = 0.5 + longitude / std::f32::consts::TAU;
+                let v = 0.5 - latitude / std::f32::consts::PI;
+
+                let sx = u * w as f32 - 0.5;
+                let sy = v * h as f32 - 0.5;
+                let (x0, y0) = (sx.floor(), sy.floor());
+                let (fx, fy) = (sx - x0, sy - y0);
+                let (x0, y0) = (x0 as isize, y0 as isize);
+                for (channel, value) in pixel.iter_mut().enumerate() {
+                    let top = fetch(x0, y0, channel) * (1.0 - fx) + fetch(x0 + 1, y0, channel) * fx;
+                    let bottom = fetch(x0, y0 + 1, channel) * (1.0 - fx)
+                        + fetch(x0 + 1, y0 + 1, channel) * fx;
+                    *value = (top * (1.0 - fy) + bottom * fy).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+}
+
+impl Default for SphericalView {
+    fn default() -> Self {
+        Self::FORWARD
+    }
+}
+
+fn mul3(a: &[[f32; 3]; 3], b: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let mut out = [[0.0; 3]; 3];
+    for (row, out_row) in out.iter_mut().enumerate() {
+        for (column, value) in out_row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[row][k] * b[k][column]).sum();
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        implementation::{AssetLibrary, GpuPipeline, TimelineManager},
+        types::{ProjectionMode, TrackType, timeline::TimelineClip},
+    };
+
+    /// 64x32 equirectangular frame: red counts columns, green rows.
+    fn grid() -> Vec<u8> {
+        (0..32u8).flat_map(|y| (0..64u8).flat_map(move |x| [x * 4, y * 8, 0, 255])).collect()
+    }
+
+    /// Red and green at the center of a 9x9 view.
+    fn center(view: SphericalView) -> (u8, u8) {
+        let out = view.apply(&grid(), 64, 32, 9, 9);
+        let i = (4 * 9 + 4) * 4;
+        (out[i], out[i + 1])
+    }
+
+    #[test]
+    fn test_view_samples_sphere() {
+        // Straight ahead is the middle of the frame
+        assert_eq!(center(SphericalView::FORWARD), (126, 124));
+        // A quarter turn right is three quarters across
+        let right = SphericalView { yaw: 90.0, ..SphericalView::FORWARD };
+        assert_eq!(center(right).0, 190);
+        // Looking up reaches the top row
+        let up = SphericalView { pitch: 89.0, ..SphericalView::FORWARD };
+        assert!(center(up).1 < 8);
+        // Roll turns the picture, not where it looks
+        let rolled = SphericalView { roll: 45.0, ..SphericalView::FORWARD };
+        assert_eq!(center(rolled), center(SphericalView::FORWARD));
+    }
+
+    #[test]
+    fn test_view_keyframes() {
+        let mut animation = AnimationManager::new();
+        let start = SphericalView::at(&animation, 7, TimePosition::from_ms(0));
+        assert_eq!(start, SphericalView::FORWARD);
+
+        SphericalView::FORWARD
+            .keyframe(&mut animation, 7, TimePosition::from_ms(0))
+            .expect("test assertion");
+        SphericalView { yaw: 90.0, fov: 60.0, ..SphericalView::FORWARD }
+            .keyframe(&mut animation, 7, TimePosition::from_ms(1000))
+            .expect("test assertion");
+        let view = SphericalView::at(&animation, 7, TimePosition::from_ms(500));
+        assert!((view.yaw - 45.0).abs() < 1e-3 && (view.fov - 75.0).abs() < 1e-3);
+        assert_eq!(animation.get_layer(7).map(|l| l.tracks().len()), Some(4));
+        assert_eq!(SphericalView::at(&animation, 8, TimePosition::from_ms(500)).yaw, 0.0);
+    }
+
+    #[test]
+    fn test_reframe_dispatch() {
+        let mut timeline = TimelineManager::new();
+        let mut assets = AssetLibrary::new();
+        let flat = assets.import_video("flat.mp4").expect("test assertion");
+        let sphere = assets.import_video("sphere.mp4").expect("test assertion");
+        assets.video_clip_mut(sphere).expect("test assertion").projection =
+            ProjectionMode::Equirectangular;
+        for (clip_id, media) in [(1, flat), (2, sphere)] {
+            let track = timeline.add_track("V", TrackType::Video);
+            let clip = TimelineClip::new(
+                clip_id,
+                media,
+                TimePosition::from_ms(1_000),
+                TimePosition::from_ms(2_000),
+            );
+            timeline.add_clip(track, clip).expect("test assertion");
+        }
+        let mut animation = AnimationManager::new();
+        SphericalView { yaw: 90.0, ..SphericalView::FORWARD }
+            .keyframe(&mut animation, 2, TimePosition::from_ms(0))
+            .expect("test assertion");
+        let mut gpu = GpuPipeline::new(true);
+        let time = TimePosition::from_ms(1_500);
+        assert!(gpu.dispatch_reframes(&timeline, &assets, &animation, time, 1920, 1080).is_err());
+        gpu.initialize();
+        let dispatches = gpu
+            .dispatch_reframes(&timeline, &assets, &animation, time, 1920, 1080)
+            .expect("test assertion");
+        // Only the 360° clip is reprojected
+        assert_eq!(dispatches.len(), 1);
+        assert_eq!((dispatches[0].clip_id, dispatches[0].view.yaw), (2, 90.0));
+        let lens = dispatches[0].uniforms.iter().find(|(name, _)| name == "lens");
+        let [half_width, half_height, ..] = lens.expect("test assertion").1;
+        assert!((half_width - 1.0).abs() < 1e-5 && (half_height - 0.5625).abs() < 1e-5);
+    }
+}
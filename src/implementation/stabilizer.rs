@@ -0,0 +1,529 @@
+//! Video stabilization.
+//!
+//! Stabilizing a clip is two passes. [`Stabilizer::analyze`] estimates the
+//! global motion between consecutive frames and records it in the clip's
+//! [`MetadataIndex`]; [`Stabilizer::solve`] smooths the accumulated camera
+//! path and turns the difference into a per-frame [`StabilizeTransform`]
+//! that the `Stabilize` clip effect applies at render time.
+//!
+//! Analysis of a long clip can be paused or cancelled between frames
+//! through a [`CancellationToken`].
+
+use super::effects::{EffectType, VideoEffect};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    metadata::{MetadataIndex, MotionVector},
+    tasks::CancellationToken,
+};
+
+/// Widest luma plane motion is estimated on.
+const ANALYSIS_WIDTH: usize = 320;
+
+/// Smallest pyramid level.
+const MIN_LEVEL_WIDTH: usize = 40;
+
+/// Frames of smoothing radius at full smoothness.
+const MAX_SMOOTHING_SIGMA: f32 = 30.0;
+
+/// Stabilizer progress callback.
+pub type StabilizerProgressCallback = Box<dyn Fn(StabilizerProgress) + Send + Sync>;
+
+/// Stabilizer pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StabilizerPhase {
+    /// Estimating frame-to-frame motion.
+    Analyzing,
+    /// Smoothing the camera path into per-frame transforms.
+    Smoothing,
+}
+
+/// Stabilizer progress information.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StabilizerProgress {
+    /// Current pass.
+    pub phase:            StabilizerPhase,
+    /// Frames processed in this pass.
+    pub frames_processed: u64,
+    /// Frames in this pass.
+    pub total_frames:     u64,
+}
+
+impl StabilizerProgress {
+    /// Progress within the pass (0.0 - 1.0).
+    pub fn fraction(&self) -> f32 {
+        if self.total_frames == 0 {
+            1.0
+        } else {
+            self.frames_processed as f32 / self.total_frames as f32
+        }
+    }
+}
+
+/// Correction applied to one frame, as a fraction of the frame size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StabilizeTransform {
+    /// Horizontal shift.
+    pub dx:    f32,
+    /// Vertical shift.
+    pub dy:    f32,
+    /// Zoom about the frame center hiding the shifted edges.
+    pub scale: f32,
+}
+
+impl StabilizeTransform {
+    /// No correction.
+    pub const IDENTITY: Self = Self { dx: 0.0, dy: 0.0, scale: 1.0 };
+
+    /// Resample an RGBA8 frame through the transform.
+    pub fn apply(&self, pixels: &[u8], width: u32, height: u32) -> Vec<u8> {
+        let (w, h) = (width as usize, height as usize);
+        let mut out = vec![0; w * h * 4];
+        if pixels.len() < w * h * 4 {
+            return out;
+        }
+        let fetch = |x: isize, y: isize, channel: usize| {
+            let x = x.clamp(0, w as isize - 1) as usize;
+            let y = y.clamp(0, h as isize - 1) as usize;
+            f32::from(pixels[(y * w + x) * 4 + channel])
+        };
+        for y in 0..h {
+            for x in 0..w {
+                // Output pixel center back to normalized source coordinates
+                let u = ((x as f32 + 0.5) / w as f32 - 0.5) / self.scale - self.dx + 0.5;
+                let v = ((y as f32 + 0.5) / h as f32 - 0.5) / self.scale - self.dy + 0.5;
+                let sx = u * w as f32 - 0.5;
+                let sy = v * h as f32 - 0.5;
+                let (x0, y0) = (sx.floor(), sy.floor());
+                let (fx, fy) = (sx - x0, sy - y0);
+                let (x0, y0) = (x0 as isize, y0 as isize);
+                for channel in 0..4 {
+                    let top = fetch(x0, y0, channel) * (1.0 - fx) + fetch(x0 + 1, y0, channel) * fx;
+                    let bottom = fetch(x0, y0 + 1, channel) * (1.0 - fx)
+                        + fetch(x0 + 1, y0 + 1, channel) * fx;
+                    out[(y * w + x) * 4 + channel] =
+                        (top * (1.0 - fy) + bottom * fy).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Default for StabilizeTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Two-pass video stabilizer.
+pub struct Stabilizer {
+    /// Path smoothing strength (0.0 - 1.0).
+    pub smoothness: f32,
+    /// Largest fraction of the frame that may be cropped away (0.0 - 0.5).
+    pub max_crop:   f32,
+    progress:       Option<StabilizerProgressCallback>,
+    cancellation:   Option<CancellationToken>,
+}
+
+impl std::fmt::Debug for Stabilizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Stabilizer")
+            .field("smoothness", &self.smoothness)
+            .field("max_crop", &self.max_crop)
+            .field("progress", &self.progress.as_ref().map(|_| "<callback>"))
+            .field("cancellation", &self.cancellation)
+            .finish()
+    }
+}
+
+impl Stabilizer {
+    /// Default smoothing strength.
+    pub const DEFAULT_SMOOTHNESS: f32 = 0.5;
+    /// Default crop limit.
+    pub const DEFAULT_MAX_CROP: f32 = 0.1;
+
+    /// Create a stabilizer.
+    pub fn new(smoothness: f32, max_crop: f32) -> Self {
+        Self {
+            smoothness:   smoothness.clamp(0.0, 1.0),
+            max_crop:     max_crop.clamp(0.0, 0.5),
+            progress:     None,
+            cancellation: None,
+        }
+    }
+
+    /// Create a stabilizer from a `Stabilize` clip effect's `smoothness`
+    /// and `max_crop` parameters.
+    pub fn from_effect(effect: &VideoEffect) -> VideoEditorResult<Self> {
+        if effect.effect_type != EffectType::Stabilize {
+            return Err(VideoEditorError::Effect(format!(
+                "Effect {} is not a stabilizer",
+                effect.id
+            )));
+        }
+        Ok(Self::new(
+            effect.parameter("smoothness").map_or(Self::DEFAULT_SMOOTHNESS, |v| v as f32),
+            effect.parameter("max_crop").map_or(Self::DEFAULT_MAX_CROP, |v| v as f32),
+        ))
+    }
+
+    /// Set the progress callback.
+    pub fn set_progress_callback(&mut self, callback: StabilizerProgressCallback) {
+        self.progress = Some(callback);
+    }
+
+    /// Set the token analysis checks between frames.
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = Some(token);
+    }
+
+    fn report(&self, phase: StabilizerPhase, frames_processed: u64, total_frames: u64) {
+        if let Some(ref callback) = self.progress {
+            callback(StabilizerProgress { phase, frames_processed, total_frames });
+        }
+    }
+
+    /// Analysis pass: estimate the motion of each RGBA8 frame relative to
+    /// the previous one and store it in `index`.
+    ///
+    /// Replaces any earlier analysis. Returns the number of frames analyzed,
+    /// or a cancellation error if the token set with
+    /// [`Self::set_cancellation_token`] is cancelled part way.
+    pub fn analyze<'a, I>(
+        &self, frames: I, width: u32, height: u32, index: &mut MetadataIndex,
+    ) -> VideoEditorResult<u64>
+    where
+        I: IntoIterator<Item = &'a [u8]>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let frames = frames.into_iter();
+        let total = frames.len() as u64;
+        let expected = width as usize * height as usize * 4;
+        if width == 0 || height == 0 {
+            return Err(VideoEditorError::Effect("Cannot stabilize an empty frame".into()));
+        }
+
+        index.clear_motion();
+        let mut previous: Option<Vec<Luma>> = None;
+        for (frame, pixels) in frames.enumerate() {
+            if let Some(token) = &self.cancellation {
+                token.checkpoint("stabilizer analysis")?;
+            }
+            if pixels.len() != expected {
+                return Err(VideoEditorError::Effect(format!(
+                    "Frame {frame} is {} bytes, expected {expected}",
+                    pixels.len()
+                )));
+            }
+            let pyramid = Luma::pyramid(pixels, width as usize, height as usize);
+            let motion = previous
+                .as_deref()
+                .map_or_else(MotionVector::default, |prev| estimate_motion(prev, &pyramid));
+            index.set_motion(frame as u64, motion);
+            previous = Some(pyramid);
+            self.report(StabilizerPhase::Analyzing, frame as u64 + 1, total);
+        }
+        Ok(total)
+    }
+
+    /// Smoothing pass: turn analyzed motion into per-frame corrections.
+    ///
+    /// The camera path is smoothed with a Gaussian whose width follows
+    /// `smoothness`; corrections are clamped so no frame needs more than
+    /// `max_crop`, and one zoom for the whole clip hides the shifted edges.
+    pub fn solve(&self, index: &MetadataIndex) -> VideoEditorResult<Vec<StabilizeTransform>> {
+        let motion = index.motion_vectors();
+        if motion.is_empty() {
+            return Err(VideoEditorError::Effect("Clip has not been analyzed".into()));
+        }
+        let total = motion.len() as u64;
+
+        let mut path = Vec::with_capacity(motion.len());
+        let mut position = (0.0f32, 0.0f32);
+        for (i, (_, vector)) in motion.iter().enumerate() {
+            if i > 0 {
+                position.0 += vector.dx;
+                position.1 += vector.dy;
+            }
+            path.push(position);
+        }
+
+        let sigma = self.smoothness * MAX_SMOOTHING_SIGMA;
+        let radius = (sigma * 3.0).ceil() as usize;
+        let limit = self.max_crop / 2.0;
+        let mut corrections = Vec::with_capacity(path.len());
+        for (i, &(x, y)) in path.iter().enumerate() {
+            let start = i.saturating_sub(radius);
+            let window = &path[start..(i + radius + 1).min(path.len())];
+            let (mut sum, mut weight) = ((0.0, 0.0), 0.0);
+            for (j, point) in window.iter().enumerate() {
+                let d = (start + j) as f32 - i as f32;
+                let w = if sigma > 0.0 { (-d * d / (2.0 * sigma * sigma)).exp() } else { 1.0 };
+                sum.0 += point.0 * w;
+                sum.1 += point.1 * w;
+                weight += w;
+            }
+            let smooth = (sum.0 / weight, sum.1 / weight);
+            corrections
+                .push(((smooth.0 - x).clamp(-limit, limit), (smooth.1 - y).clamp(-limit, limit)));
+            self.report(StabilizerPhase::Smoothing, i as u64 + 1, total);
+        }
+
+        let shift = corrections.iter().map(|(dx, dy)| dx.abs().max(dy.abs())).fold(0.0, f32::max);
+        let scale = 1.0 / (1.0 - 2.0 * shift);
+        Ok(corrections.into_iter().map(|(dx, dy)| StabilizeTransform { dx, dy, scale }).collect())
+    }
+}
+
+impl Default for Stabilizer {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_SMOOTHNESS, Self::DEFAULT_MAX_CROP)
+    }
+}
+
+/// Grayscale plane used for motion search.
+struct Luma {
+    width:  usize,
+    height: usize,
+    data:   Vec<f32>,
+}
+
+impl Luma {
+    /// Luma pyramid, full analysis resolution first.
+    fn pyramid(pixels: &[u8], width: usize, height: usize) -> Vec<Self> {
+        let factor = width.div_ceil(ANALYSIS_WIDTH).max(1);
+        let (lw, lh) = ((width / factor).max(1), (height / factor).max(1));
+        let mut data = vec![0.0; lw * lh];
+        for (y, row) in data.chunks_exact_mut(lw).enumerate() {
+            for (x, value) in row.iter_mut().enumerate() {
+                let mut sum = 0.0;
+                for sy in y * factor..(y + 1) * factor {
+                    for sx in x * factor..(x + 1) * factor {
+                        let p = &pixels[(sy * width + sx) * 4..];
+                        sum += 0.299 * f32::from(p[0])
+                            + 0.587 * f32::from(p[1])
+                            + 0.114 * f32::from(p[2]);
+                    }
+                }
+                *value = sum / (factor * factor) as f32;
+            }
+        }
+
+        let mut levels = vec![Self { width: lw, height: lh, data }];
+        while let Some(last) = levels.last()
+            && last.width / 2 >= MIN_LEVEL_WIDTH
+            && last.height / 2 >= 8
+        {
+            let half = last.half();
+            levels.push(half);
+        }
+        levels
+    }
+
+    fn half(&self) -> Self {
+        let (width, height) = (self.width / 2, self.height / 2);
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let at = |dx: usize, dy: usize| self.data[(y * 2 + dy) * self.width + x * 2 + dx];
+                data.push((at(0, 0) + at(1, 0) + at(0, 1) + at(1, 1)) / 4.0);
+            }
+        }
+        Self { width, height, data }
+    }
+
+    /// Mean absolute difference between `self` shifted by `(dx, dy)` and
+    /// `next` over their overlap; infinite if the overlap is under half.
+    fn cost(&self, next: &Self, dx: isize, dy: isize) -> f32 {
+        let (w, h) = (self.width as isize, self.height as isize);
+        let (x0, x1) = (dx.max(0), (w + dx).min(w));
+        let (y0, y1) = (dy.max(0), (h + dy).min(h));
+        let area = (x1 - x0).max(0) * (y1 - y0).max(0);
+        if area * 2 < w * h {
+            return f32::INFINITY;
+        }
+        let mut sum = 0.0;
+        for y in y0..y1 {
+            let row = (y * w) as usize;
+            let prev_row = ((y - dy) * w) as usize;
+            for x in x0..x1 {
+                sum +=
+                    (next.data[row + x as usize] - self.data[prev_row + (x - dx) as usize]).abs();
+            }
+        }
+        sum / area as f32
+    }
+}
+
+/// Coarse-to-fine translation search between two pyramids.
+fn estimate_motion(prev: &[Luma], next: &[Luma]) -> MotionVector {
+    let coarsest = prev.len() - 1;
+    let (mut dx, mut dy) = (0isize, 0isize);
+    for level in (0..=coarsest).rev() {
+        let range = if level == coarsest { 4 } else { 1 };
+        if level != coarsest {
+            dx *= 2;
+            dy *= 2;
+        }
+        let (cx, cy) = (dx, dy);
+        let mut best = f32::INFINITY;
+        for sy in cy - range..=cy + range {
+            for sx in cx - range..=cx + range {
+                let cost = prev[level].cost(&next[level], sx, sy);
+                if cost < best {
+                    best = cost;
+                    (dx, dy) = (sx, sy);
+                }
+            }
+        }
+    }
+
+    // Parabolic sub-pixel refinement on the finest level
+    let (base, next) = (&prev[0], &next[0]);
+    let refine = |minus: f32, center: f32, plus: f32| {
+        let denom = minus - 2.0 * center + plus;
+        if denom.is_finite() && denom > f32::EPSILON {
+            ((minus - plus) / (2.0 * denom)).clamp(-0.5, 0.5)
+        } else {
+            0.0
+        }
+    };
+    let center = base.cost(next, dx, dy);
+    let fx = refine(base.cost(next, dx - 1, dy), center, base.cost(next, dx + 1, dy));
+    let fy = refine(base.cost(next, dx, dy - 1), center, base.cost(next, dx, dy + 1));
+    MotionVector::new((dx as f32 + fx) / base.width as f32, (dy as f32 + fy) / base.height as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::tasks::ControlHandle;
+
+    /// Smooth texture with content shifted by `(ox, oy)` pixels.
+    fn frame(width: u32, height: u32, ox: f32, oy: f32) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let (u, v) = (x as f32 - ox, y as f32 - oy);
+                let value = 128.0
+                    + 60.0 * (u * 0.11).sin() * (v * 0.07).cos()
+                    + 40.0 * (u * 0.031 + v * 0.043).sin();
+                let value = value.clamp(0.0, 255.0) as u8;
+                pixels.extend_from_slice(&[value, value, value, 255]);
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn test_analyze_records_motion() {
+        let frames: Vec<Vec<u8>> = [(0.0, 0.0), (6.0, -3.0), (2.0, 4.0)]
+            .iter()
+            .map(|&(x, y)| frame(320, 180, x, y))
+            .collect();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let mut stabilizer = Stabilizer::default();
+        stabilizer.set_progress_callback(Box::new(move |p| {
+            sink.lock().expect("test assertion").push(p);
+        }));
+
+        let mut index = MetadataIndex::new();
+        let analyzed = stabilizer
+            .analyze(frames.iter().map(Vec::as_slice), 320, 180, &mut index)
+            .expect("test assertion");
+        assert_eq!(analyzed, 3);
+        assert_eq!(index.motion(0), Some(MotionVector::default()));
+        let motion = index.motion(1).expect("test assertion");
+        assert!((motion.dx * 320.0 - 6.0).abs() < 0.5, "dx {}", motion.dx * 320.0);
+        assert!((motion.dy * 180.0 + 3.0).abs() < 0.5, "dy {}", motion.dy * 180.0);
+        let motion = index.motion(2).expect("test assertion");
+        assert!((motion.dx * 320.0 + 4.0).abs() < 0.5);
+        assert!((motion.dy * 180.0 - 7.0).abs() < 0.5);
+
+        let events = events.lock().expect("test assertion");
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|p| p.phase == StabilizerPhase::Analyzing));
+        assert_eq!(events[2].fraction(), 1.0);
+
+        assert!(stabilizer.analyze([&[0u8; 4][..]], 320, 180, &mut index).is_err());
+    }
+
+    #[test]
+    fn test_analyze_cancelled() {
+        let frames: Vec<Vec<u8>> = (0..4).map(|i| frame(320, 180, i as f32, 0.0)).collect();
+        let control = ControlHandle::new();
+        let mut stabilizer = Stabilizer::default();
+        stabilizer.set_cancellation_token(control.token());
+        control.cancel();
+
+        let mut index = MetadataIndex::new();
+        let err = stabilizer
+            .analyze(frames.iter().map(Vec::as_slice), 320, 180, &mut index)
+            .expect_err("test assertion");
+        assert!(err.is_cancelled());
+        assert!(index.motion(0).is_none());
+    }
+
+    #[test]
+    fn test_solve_smooths_jitter_within_crop() {
+        let mut index = MetadataIndex::new();
+        assert!(Stabilizer::default().solve(&index).is_err());
+        // Steady pan with alternating shake
+        for frame in 0..60u64 {
+            let shake = if frame % 2 == 0 { 0.02 } else { -0.02 };
+            index.set_motion(
+                frame,
+                MotionVector::new(if frame == 0 { 0.0 } else { 0.01 + shake }, 0.0),
+            );
+        }
+
+        let stabilizer = Stabilizer::new(0.2, 0.1);
+        let transforms = stabilizer.solve(&index).expect("test assertion");
+        assert_eq!(transforms.len(), 60);
+        let mut path = 0.0;
+        let mut residual = Vec::new();
+        for (frame, (_, motion)) in index.motion_vectors().iter().enumerate() {
+            if frame > 0 {
+                path += motion.dx;
+            }
+            residual.push(path + transforms[frame].dx);
+        }
+        // The corrected path keeps the pan but loses the shake
+        for pair in residual[20..40].windows(2) {
+            assert!((pair[1] - pair[0] - 0.01).abs() < 0.005);
+        }
+        assert!(transforms.iter().all(|t| t.dx.abs() <= 0.05 + f32::EPSILON));
+        assert!(transforms[0].scale > 1.0 && transforms[0].scale <= 1.0 / 0.9 + 1e-4);
+
+        let effect = VideoEffect {
+            id:          3,
+            effect_type: EffectType::Stabilize,
+            parameters:  vec![("smoothness".into(), 0.0), ("max_crop".into(), 0.2)],
+            ab:          Default::default(),
+        };
+        let still = Stabilizer::from_effect(&effect).expect("test assertion");
+        assert_eq!(still.max_crop, 0.2);
+        assert!(still.solve(&index).expect("test assertion").iter().all(|t| t.dx == 0.0));
+        let blur = VideoEffect { effect_type: EffectType::Blur, ..effect };
+        assert!(Stabilizer::from_effect(&blur).is_err());
+    }
+
+    #[test]
+    fn test_transform_cancels_shift() {
+        let reference = frame(64, 64, 0.0, 0.0);
+        let shifted = frame(64, 64, 4.0, 0.0);
+        let transform = StabilizeTransform { dx: -4.0 / 64.0, dy: 0.0, scale: 1.0 };
+        let fixed = transform.apply(&shifted, 64, 64);
+        // Pixels line up again except the clamped right edge
+        for y in 0..64 {
+            for x in 0..56 {
+                let i = (y * 64 + x) * 4;
+                assert!(fixed[i].abs_diff(reference[i]) <= 1);
+            }
+        }
+        assert_eq!(StabilizeTransform::IDENTITY.apply(&reference, 64, 64), reference);
+    }
+}
@@ -1,24 +1,92 @@
 //! Timeline management.
 
-use crate::types::{TimelinePosition, TimelineTrack, TrackType};
+use std::{collections::HashMap, sync::OnceLock};
+
+use super::{
+    camera::CameraLayer,
+    captions::CaptionTrack,
+    clip_index::{ClipIndex, TimelineItem},
+    clip_transform::ClipTransform,
+    effects::EffectsPipeline,
+    events::{EditorEvent, EventBus},
+    playhead_follow::TimelineViewport,
+    selection::SelectionModel,
+    snapping::{SnapEngine, SnappedPosition},
+    track_templates::{TrackLayout, TrackTemplate},
+};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    types::{
+        AdjustmentClip, ChannelMap, ClipFade, ClipGroup, ClipPitch, EditSuggestion, TimePosition,
+        TimelinePosition, TimelineTrack, TrackGroup, TrackType,
+        pipeline::{RenderGraph, RenderPass, RenderPassType},
+        timeline::TimelineClip,
+    },
+};
+
+/// Which tracks a ripple edit shifts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RippleSync {
+    /// Only the edited track.
+    #[default]
+    Track,
+    /// Every unlocked track, keeping synchronized audio and video aligned.
+    AllTracks,
+}
 
 /// Timeline manager.
 pub struct TimelineManager {
-    tracks:        Vec<TimelineTrack>,
-    next_track_id: u64,
-    duration:      TimelinePosition,
+    tracks:              Vec<TimelineTrack>,
+    next_track_id:       u64,
+    duration:            TimelinePosition,
+    snap:                SnapEngine,
+    groups:              Vec<ClipGroup>,
+    next_group_id:       u64,
+    linked_selection:    bool,
+    track_groups:        Vec<TrackGroup>,
+    next_track_group_id: u64,
+    events:              EventBus,
+    camera:              Option<CameraLayer>,
+    transforms:          HashMap<u64, ClipTransform>,
+    captions:            Vec<CaptionTrack>,
+    clip_index:          OnceLock<ClipIndex>,
+    selection:           SelectionModel,
+    next_clip_id:        u64,
 }
 
 impl TimelineManager {
     /// Create a new timeline manager.
     pub fn new() -> Self {
         Self {
-            tracks:        Vec::new(),
-            next_track_id: 1,
-            duration:      TimelinePosition::default(),
+            tracks:              Vec::new(),
+            next_track_id:       1,
+            duration:            TimelinePosition::default(),
+            snap:                SnapEngine::new(),
+            groups:              Vec::new(),
+            next_group_id:       1,
+            linked_selection:    true,
+            track_groups:        Vec::new(),
+            next_track_group_id: 1,
+            events:              EventBus::new(),
+            camera:              None,
+            transforms:          HashMap::new(),
+            captions:            Vec::new(),
+            clip_index:          OnceLock::new(),
+            selection:           SelectionModel::new(),
+            next_clip_id:        1,
         }
     }
 
+    /// Emit change events on a shared bus.
+    pub fn set_event_bus(&mut self, events: EventBus) {
+        self.events = events;
+    }
+
+    /// Get the bus change events are emitted on.
+    pub fn event_bus(&self) -> &EventBus {
+        &self.events
+    }
+
     /// Add a new track.
     pub fn add_track(&mut self, name: impl Into<String>, track_type: TrackType) -> u64 {
         let id = self.next_track_id;
@@ -26,19 +94,88 @@ impl TimelineManager {
         let index = self.tracks.len();
 
         self.tracks.push(TimelineTrack::new(id, name, track_type, index));
+        self.clip_index.take();
+        self.events.emit(EditorEvent::TrackAdded { track_id: id });
+
+        id
+    }
+
+    /// Add a track set up from a template, creating its effect stack in
+    /// `effects`. Returns the new track ID.
+    pub fn apply_track_template(
+        &mut self, template: &TrackTemplate, effects: &mut EffectsPipeline,
+    ) -> u64 {
+        let id = self.add_track(template.name.clone(), template.track_type);
+        let effect_ids = template
+            .effects
+            .iter()
+            .map(|(effect_type, parameters)| {
+                effects.add_effect_with(*effect_type, parameters.clone())
+            })
+            .collect();
+        if let Some(track) = self.get_track_mut(id) {
+            track.color = template.color;
+            track.height = template.height;
+            track.muted = template.muted;
+            track.effect_ids = effect_ids;
+        }
+        id
+    }
+
+    /// Add every track of a layout below the existing tracks. Returns the
+    /// new track IDs in layout order.
+    pub fn apply_track_layout(
+        &mut self, layout: &TrackLayout, effects: &mut EffectsPipeline,
+    ) -> Vec<u64> {
+        layout.tracks.iter().map(|t| self.apply_track_template(t, effects)).collect()
+    }
 
+    /// Capture the current tracks, without clips, as a named layout.
+    pub fn save_track_layout(
+        &self, name: impl Into<String>, effects: &EffectsPipeline,
+    ) -> TrackLayout {
+        let mut tracks: Vec<&TimelineTrack> = self.tracks().iter().collect();
+        tracks.sort_by_key(|t| t.index);
+        TrackLayout {
+            name:   name.into(),
+            tracks: tracks.into_iter().map(|t| TrackTemplate::from_track(t, effects)).collect(),
+        }
+    }
+
+    /// Add a subtitle track with an empty caption track.
+    pub fn add_caption_track(&mut self, name: impl Into<String>) -> u64 {
+        let id = self.add_track(name, TrackType::Subtitle);
+        self.captions.push(CaptionTrack::new(id));
         id
     }
 
-    /// Remove a track.
+    /// Get the captions of a subtitle track.
+    pub fn caption_track(&self, track_id: u64) -> Option<&CaptionTrack> {
+        self.captions.iter().find(|c| c.track_id() == track_id)
+    }
+
+    /// Get the mutable captions of a subtitle track.
+    pub fn caption_track_mut(&mut self, track_id: u64) -> Option<&mut CaptionTrack> {
+        self.captions.iter_mut().find(|c| c.track_id() == track_id)
+    }
+
+    /// Remove a track. Locked tracks are kept.
     pub fn remove_track(&mut self, track_id: u64) -> bool {
-        if let Some(pos) = self.tracks.iter().position(|t| t.id == track_id) {
-            self.tracks.remove(pos);
-            // Reindex remaining tracks
-            for (i, track) in self.tracks.iter_mut().enumerate() {
-                track.index = i;
+        if let Some(pos) = self.tracks.iter().position(|t| t.id == track_id && !self.locked(t)) {
+            let removed = self.tracks.remove(pos);
+            for group in &mut self.groups {
+                group.clip_ids.retain(|id| !removed.clips.iter().any(|c| c.id == *id));
+            }
+            self.prune_groups();
+            self.selection.retain_clips(|id| !removed.clips.iter().any(|c| c.id == *id));
+            for group in &mut self.track_groups {
+                group.track_ids.retain(|&id| id != track_id);
             }
+            self.track_groups.retain(|g| !g.track_ids.is_empty());
+            self.captions.retain(|c| c.track_id() != track_id);
+            self.reindex_tracks();
             self.recalculate_duration();
+            self.events.emit(EditorEvent::TrackRemoved { track_id });
             true
         } else {
             false
@@ -52,6 +189,7 @@ impl TimelineManager {
 
     /// Get mutable access to tracks.
     pub fn tracks_mut(&mut self) -> &mut Vec<TimelineTrack> {
+        self.clip_index.take();
         &mut self.tracks
     }
 
@@ -67,6 +205,7 @@ impl TimelineManager {
 
     /// Update timeline duration based on clips.
     pub fn recalculate_duration(&mut self) {
+        self.clip_index.take();
         self.duration = self
             .tracks
             .iter()
@@ -82,8 +221,1471 @@ impl TimelineManager {
 
     /// Get a mutable track by ID.
     pub fn get_track_mut(&mut self, track_id: u64) -> Option<&mut TimelineTrack> {
+        self.clip_index.take();
         self.tracks.iter_mut().find(|t| t.id == track_id)
     }
+
+    /// Lock or unlock a track against edits.
+    pub fn set_track_locked(&mut self, track_id: u64, locked: bool) -> bool {
+        self.get_track_mut(track_id).map(|t| t.locked = locked).is_some()
+    }
+
+    /// Enable or disable a track. Disabled tracks are excluded from render
+    /// and preview.
+    pub fn set_track_enabled(&mut self, track_id: u64, enabled: bool) -> bool {
+        self.get_track_mut(track_id).map(|t| t.enabled = enabled).is_some()
+    }
+
+    /// Mute or unmute an audio track.
+    pub fn set_track_muted(&mut self, track_id: u64, muted: bool) -> bool {
+        self.get_track_mut(track_id).map(|t| t.muted = muted).is_some()
+    }
+
+    /// Solo or unsolo a track.
+    pub fn set_track_solo(&mut self, track_id: u64, solo: bool) -> bool {
+        self.get_track_mut(track_id).map(|t| t.solo = solo).is_some()
+    }
+
+    /// Returns whether any enabled track of a type is soloed.
+    ///
+    /// Video and audio solo are independent: soloing a video track hides
+    /// the other video tracks but leaves audio untouched, and vice versa.
+    pub fn has_solo(&self, track_type: TrackType) -> bool {
+        self.tracks.iter().any(|t| t.enabled && self.soloed(t) && t.track_type == track_type)
+    }
+
+    /// Checks if a track is locked, directly or through its track group.
+    pub fn is_track_locked(&self, track_id: u64) -> bool {
+        self.get_track(track_id).is_some_and(|t| self.locked(t))
+    }
+
+    /// Checks if a track is muted, directly or through its track group.
+    pub fn is_track_muted(&self, track_id: u64) -> bool {
+        self.get_track(track_id).is_some_and(|t| self.muted(t))
+    }
+
+    /// Checks if a track is soloed, directly or through its track group.
+    pub fn is_track_soloed(&self, track_id: u64) -> bool {
+        self.get_track(track_id).is_some_and(|t| self.soloed(t))
+    }
+
+    fn locked(&self, track: &TimelineTrack) -> bool {
+        track.locked || self.track_group_of(track.id).is_some_and(|g| g.locked)
+    }
+
+    fn muted(&self, track: &TimelineTrack) -> bool {
+        track.muted || self.track_group_of(track.id).is_some_and(|g| g.muted)
+    }
+
+    fn soloed(&self, track: &TimelineTrack) -> bool {
+        track.solo || self.track_group_of(track.id).is_some_and(|g| g.solo)
+    }
+
+    /// Set a track's 2.5D depth for perspective compositing.
+    pub fn set_track_z_position(&mut self, track_id: u64, z_position: f32) -> bool {
+        self.get_track_mut(track_id).map(|t| t.z_position = z_position).is_some()
+    }
+
+    /// Get the camera used for perspective compositing.
+    pub fn camera(&self) -> Option<&CameraLayer> {
+        self.camera.as_ref()
+    }
+
+    /// Get the mutable camera.
+    pub fn camera_mut(&mut self) -> Option<&mut CameraLayer> {
+        self.camera.as_mut()
+    }
+
+    /// Set or clear the camera; without one, perspective compositing uses
+    /// a rest camera framing the composite.
+    pub fn set_camera(&mut self, camera: Option<CameraLayer>) {
+        self.camera = camera;
+    }
+
+    /// Get a clip's transform, if it has been set up.
+    pub fn clip_transform(&self, clip_id: u64) -> Option<&ClipTransform> {
+        self.transforms.get(&clip_id)
+    }
+
+    /// Get a clip's mutable transform, starting from the identity if the
+    /// clip has none yet. Returns `None` if the clip doesn't exist.
+    pub fn clip_transform_mut(&mut self, clip_id: u64) -> Option<&mut ClipTransform> {
+        self.find_clip(clip_id)?;
+        Some(self.transforms.entry(clip_id).or_insert_with(|| ClipTransform::new(clip_id)))
+    }
+
+    /// Checks if a visual track is rendered (considering enable and solo
+    /// state).
+    pub fn is_track_visible(&self, track_id: u64) -> bool {
+        self.get_track(track_id).is_some_and(|t| self.track_visible(t))
+    }
+
+    /// Checks if an audio track should be audible (considering enable,
+    /// mute and solo state).
+    pub fn is_track_audible(&self, track_id: u64) -> bool {
+        self.get_track(track_id).is_some_and(|t| self.track_audible(t))
+    }
+
+    fn track_visible(&self, track: &TimelineTrack) -> bool {
+        if !track.enabled || !track.track_type.is_visual() {
+            return false;
+        }
+        if track.track_type == TrackType::Video && self.has_solo(TrackType::Video) {
+            self.soloed(track)
+        } else {
+            true
+        }
+    }
+
+    fn track_audible(&self, track: &TimelineTrack) -> bool {
+        if !track.enabled || self.muted(track) || track.track_type != TrackType::Audio {
+            return false;
+        }
+        if self.has_solo(TrackType::Audio) { self.soloed(track) } else { true }
+    }
+
+    /// Get the clips evaluated for preview at a time as `(track_id, clip)`:
+    /// clips on visible visual tracks and audible audio tracks.
+    pub fn active_clips_at(&self, time: TimePosition) -> Vec<(u64, &TimelineClip)> {
+        self.tracks
+            .iter()
+            .filter(|t| self.track_visible(t) || self.track_audible(t))
+            .flat_map(|t| {
+                t.clips.iter().filter(|c| c.enabled && c.contains(time)).map(move |c| (t.id, c))
+            })
+            .collect()
+    }
+
+    /// Get the clips overlapping `start..end` as `(track_id, clip)`, by
+    /// track then start.
+    ///
+    /// Served from a time index rebuilt on the first query after an edit,
+    /// so repeated queries cost `O(log n + k)` per track.
+    pub fn clips_in_range(
+        &self, start: TimePosition, end: TimePosition,
+    ) -> Vec<(u64, &TimelineClip)> {
+        self.clip_index()
+            .in_range(start.ms, end.ms)
+            .into_iter()
+            .map(|(track_pos, clip_pos)| {
+                let track = &self.tracks[track_pos];
+                (track.id, &track.clips[clip_pos])
+            })
+            .collect()
+    }
+
+    /// Get the items to draw in a viewport, by track then start.
+    ///
+    /// Clips narrower than a few pixels at the viewport's zoom merge into
+    /// summary items, so a zoomed-out view of a dense timeline stays cheap.
+    pub fn visible_items(&self, viewport: &TimelineViewport) -> Vec<TimelineItem> {
+        let start = viewport.scroll_ms.max(0.0) as u64;
+        let end = viewport.end_ms().max(0.0).ceil() as u64;
+        let ms_per_px = 1000.0 / viewport.pixels_per_second.max(f64::EPSILON);
+        self.clip_index().visible(&self.tracks, start, end, ms_per_px)
+    }
+
+    fn clip_index(&self) -> &ClipIndex {
+        self.clip_index.get_or_init(|| ClipIndex::new(&self.tracks))
+    }
+
+    /// Add a clip to an unlocked track.
+    pub fn add_clip(&mut self, track_id: u64, clip: TimelineClip) -> VideoEditorResult<()> {
+        let track = self.editable_track_mut(track_id)?;
+        if !track.is_range_available(clip.start, clip.end()) {
+            return Err(VideoEditorError::timeline_clip(
+                "add_clip",
+                clip.id,
+                format!("Clip {} overlaps an existing clip", clip.id),
+            ));
+        }
+        let clip_id = clip.id;
+        track.add_clip(clip);
+        self.next_clip_id = self.next_clip_id.max(clip_id + 1);
+        self.recalculate_duration();
+        self.events.emit(EditorEvent::ClipAdded { track_id, clip_id });
+        Ok(())
+    }
+
+    /// Remove a clip from an unlocked track.
+    ///
+    /// With linked selection on, the rest of the clip's group is removed too.
+    /// Returns the removed clips, the requested one first.
+    pub fn remove_clip(&mut self, clip_id: u64) -> VideoEditorResult<Vec<TimelineClip>> {
+        let members = self.edit_set(clip_id);
+        let removed = self.take_clips(clip_id, &members)?;
+        self.recalculate_duration();
+        Ok(removed)
+    }
+
+    /// Remove `members` from their tracks, `clip_id` first, once all of them
+    /// are known to be editable.
+    fn take_clips(
+        &mut self, clip_id: u64, members: &[u64],
+    ) -> VideoEditorResult<Vec<TimelineClip>> {
+        let mut locations = vec![(self.locate_editable_clip(clip_id)?.0, clip_id)];
+        for &id in members.iter().filter(|&&id| id != clip_id) {
+            locations.push((self.locate_editable_clip(id)?.0, id));
+        }
+
+        let removed: Vec<TimelineClip> = locations
+            .into_iter()
+            .filter_map(|(track_index, id)| self.tracks[track_index].remove_clip(id))
+            .collect();
+        for group in &mut self.groups {
+            group.clip_ids.retain(|id| !members.contains(id));
+        }
+        self.prune_groups();
+        self.selection.retain_clips(|id| !members.contains(id));
+        for clip in &removed {
+            self.events.emit(EditorEvent::ClipRemoved { clip_id: clip.id });
+        }
+        Ok(removed)
+    }
+
+    /// Get a mutable track by ID, failing if it is missing or locked.
+    fn editable_track_mut(&mut self, track_id: u64) -> VideoEditorResult<&mut TimelineTrack> {
+        let index = self.editable_track_index(track_id)?;
+        self.clip_index.take();
+        Ok(&mut self.tracks[index])
+    }
+
+    /// Remove a clip and shift everything after it left by its duration.
+    ///
+    /// With `RippleSync::AllTracks` the other unlocked tracks shift too;
+    /// this fails if one of them has material inside the removed range.
+    /// Linked clips are removed with it and their tracks shift as well.
+    pub fn ripple_delete(
+        &mut self, clip_id: u64, sync: RippleSync,
+    ) -> VideoEditorResult<Vec<TimelineClip>> {
+        let members = self.edit_set(clip_id);
+        let (track_index, clip_index) = self.locate_editable_clip(clip_id)?;
+        let clip = &self.tracks[track_index].clips[clip_index];
+        let (start, end) = (clip.start, clip.end());
+        let mut shifted = self.ripple_tracks(track_index, sync);
+        for &id in &members {
+            let (index, _) = self.locate_editable_clip(id)?;
+            if !shifted.contains(&index) {
+                shifted.push(index);
+            }
+        }
+        self.ensure_range_free(&shifted, track_index, start, end, &members)?;
+
+        let removed = self.take_clips(clip_id, &members)?;
+        for &index in &shifted {
+            Self::shift_track(&mut self.tracks[index], end, -((end.ms - start.ms) as i64));
+        }
+        self.recalculate_duration();
+        self.emit_rippled(&shifted);
+        Ok(removed)
+    }
+
+    /// Close every gap on a track, including one at the timeline start.
+    ///
+    /// With `RippleSync::AllTracks` a gap is only closed when it is empty on
+    /// every shifted track. Returns the number of gaps closed.
+    pub fn close_gaps(&mut self, track_id: u64, sync: RippleSync) -> VideoEditorResult<usize> {
+        let track_index = self.editable_track_index(track_id)?;
+        let shifted = self.ripple_tracks(track_index, sync);
+
+        let mut gaps = Vec::new();
+        let mut cursor = 0;
+        for clip in &self.tracks[track_index].clips {
+            if clip.start.ms > cursor {
+                gaps.push((TimePosition::from_ms(cursor), clip.start));
+            }
+            cursor = cursor.max(clip.end().ms);
+        }
+
+        // Latest gap first so earlier gap positions stay valid
+        let mut closed = 0;
+        for &(start, end) in gaps.iter().rev() {
+            if self.ensure_range_free(&shifted, track_index, start, end, &[]).is_err() {
+                continue;
+            }
+            for &index in &shifted {
+                Self::shift_track(&mut self.tracks[index], end, -((end.ms - start.ms) as i64));
+            }
+            closed += 1;
+        }
+        self.recalculate_duration();
+        if closed > 0 {
+            self.emit_rippled(&shifted);
+        }
+        Ok(closed)
+    }
+
+    /// Insert empty time on a track, shifting everything at or after `at`
+    /// right.
+    ///
+    /// Fails if a clip on a shifted track spans `at`; split it first.
+    pub fn insert_gap(
+        &mut self, track_id: u64, at: TimePosition, duration: TimePosition, sync: RippleSync,
+    ) -> VideoEditorResult<()> {
+        let track_index = self.editable_track_index(track_id)?;
+        let shifted = self.ripple_tracks(track_index, sync);
+        if let Some(track) = shifted
+            .iter()
+            .map(|&i| &self.tracks[i])
+            .find(|t| t.clips.iter().any(|c| c.start.ms < at.ms && c.end().ms > at.ms))
+        {
+            return Err(VideoEditorError::timeline(
+                "insert_gap",
+                format!("A clip on track {} spans the insert point", track.id),
+            ));
+        }
+
+        for &index in &shifted {
+            Self::shift_track(&mut self.tracks[index], at, duration.ms as i64);
+        }
+        self.recalculate_duration();
+        self.emit_rippled(&shifted);
+        Ok(())
+    }
+
+    /// Cut `start..end` out of a track and close the hole.
+    ///
+    /// Clips crossing the range edges are split; the pieces inside are
+    /// removed. With `RippleSync::AllTracks` the range is cut from every
+    /// unlocked track so they stay in sync. Fails if an adjustment clip on a
+    /// shifted track overlaps the range.
+    pub fn ripple_delete_range(
+        &mut self, track_id: u64, start: TimePosition, end: TimePosition, sync: RippleSync,
+    ) -> VideoEditorResult<()> {
+        if end.ms <= start.ms {
+            return Err(VideoEditorError::timeline("ripple_delete_range", "Cut range is empty"));
+        }
+        let track_index = self.editable_track_index(track_id)?;
+        let shifted = self.ripple_tracks(track_index, sync);
+        if let Some(track) = shifted
+            .iter()
+            .map(|&i| &self.tracks[i])
+            .find(|t| t.adjustments.iter().any(|a| a.overlaps(start, end)))
+        {
+            return Err(VideoEditorError::timeline(
+                "ripple_delete_range",
+                format!("An adjustment clip on track {} overlaps the cut", track.id),
+            ));
+        }
+
+        for &index in &shifted {
+            for at in [end, start] {
+                let crossing = self.tracks[index]
+                    .clips
+                    .iter()
+                    .find(|c| c.start.ms < at.ms && c.end().ms > at.ms)
+                    .map(|c| c.id);
+                if let Some(clip_id) = crossing {
+                    self.split_clip(clip_id, at)?;
+                }
+            }
+            let inside: Vec<u64> = self.tracks[index]
+                .clips
+                .iter()
+                .filter(|c| c.start.ms >= start.ms && c.end().ms <= end.ms)
+                .map(|c| c.id)
+                .collect();
+            for &clip_id in &inside {
+                self.tracks[index].remove_clip(clip_id);
+                self.events.emit(EditorEvent::ClipRemoved { clip_id });
+            }
+            for group in &mut self.groups {
+                group.clip_ids.retain(|id| !inside.contains(id));
+            }
+            self.selection.retain_clips(|id| !inside.contains(id));
+        }
+        self.prune_groups();
+
+        for &index in &shifted {
+            Self::shift_track(&mut self.tracks[index], end, -((end.ms - start.ms) as i64));
+        }
+        self.recalculate_duration();
+        self.emit_rippled(&shifted);
+        Ok(())
+    }
+
+    /// Apply suggested cuts, latest first so earlier ranges stay valid.
+    ///
+    /// Overlapping suggestions on the same track are merged. Returns the
+    /// number of ranges cut.
+    pub fn apply_suggestions(
+        &mut self, suggestions: &[EditSuggestion], sync: RippleSync,
+    ) -> VideoEditorResult<usize> {
+        for suggestion in suggestions {
+            self.editable_track_index(suggestion.track_id)?;
+        }
+        let mut ranges: Vec<(u64, TimePosition, TimePosition)> = suggestions
+            .iter()
+            .filter(|s| s.end.ms > s.start.ms)
+            .map(|s| (s.track_id, s.start, s.end))
+            .collect();
+        ranges.sort_by_key(|&(track_id, start, _)| (track_id, start.ms));
+        let mut merged: Vec<(u64, TimePosition, TimePosition)> = Vec::with_capacity(ranges.len());
+        for (track_id, start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if last.0 == track_id && start.ms <= last.2.ms => {
+                    last.2 = TimePosition::from_ms(last.2.ms.max(end.ms));
+                },
+                _ => merged.push((track_id, start, end)),
+            }
+        }
+
+        merged.sort_by_key(|&(_, start, _)| std::cmp::Reverse(start.ms));
+        for &(track_id, start, end) in &merged {
+            self.ripple_delete_range(track_id, start, end, sync)?;
+        }
+        Ok(merged.len())
+    }
+
+    fn emit_rippled(&self, shifted: &[usize]) {
+        let track_ids = shifted.iter().map(|&i| self.tracks[i].id).collect();
+        self.events.emit(EditorEvent::TracksRippled { track_ids });
+    }
+
+    /// Indices of the tracks a ripple edit on `track_index` shifts.
+    fn ripple_tracks(&self, track_index: usize, sync: RippleSync) -> Vec<usize> {
+        match sync {
+            RippleSync::Track => vec![track_index],
+            RippleSync::AllTracks => self
+                .tracks
+                .iter()
+                .enumerate()
+                .filter(|(i, t)| *i == track_index || !self.locked(t))
+                .map(|(i, _)| i)
+                .collect(),
+        }
+    }
+
+    /// Check that no shifted track other than `track_index` has material in
+    /// `start..end`, ignoring the clips in `exclude`.
+    fn ensure_range_free(
+        &self, shifted: &[usize], track_index: usize, start: TimePosition, end: TimePosition,
+        exclude: &[u64],
+    ) -> VideoEditorResult<()> {
+        for track in shifted.iter().filter(|&&i| i != track_index).map(|&i| &self.tracks[i]) {
+            if track
+                .clips
+                .iter()
+                .any(|c| !exclude.contains(&c.id) && start.ms < c.end().ms && end.ms > c.start.ms)
+                || track.adjustments.iter().any(|a| a.overlaps(start, end))
+            {
+                return Err(VideoEditorError::timeline(
+                    "ripple",
+                    format!("Ripple would break sync on track {}", track.id),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Shift clips and adjustment clips starting at or after `from`.
+    fn shift_track(track: &mut TimelineTrack, from: TimePosition, delta_ms: i64) {
+        let shift = |start: &mut TimePosition| {
+            if start.ms >= from.ms {
+                start.ms = start.ms.saturating_add_signed(delta_ms);
+            }
+        };
+        track.clips.iter_mut().for_each(|c| shift(&mut c.start));
+        track.adjustments.iter_mut().for_each(|a| shift(&mut a.start));
+    }
+
+    fn editable_track_index(&self, track_id: u64) -> VideoEditorResult<usize> {
+        let index = self.tracks.iter().position(|t| t.id == track_id).ok_or_else(|| {
+            VideoEditorError::timeline("edit_track", format!("Track not found: {track_id}"))
+        })?;
+        if self.locked(&self.tracks[index]) {
+            return Err(VideoEditorError::timeline(
+                "edit_track",
+                format!("Track {track_id} is locked"),
+            ));
+        }
+        Ok(index)
+    }
+
+    /// Get the snap engine.
+    pub fn snap_engine(&self) -> &SnapEngine {
+        &self.snap
+    }
+
+    /// Get the mutable snap engine.
+    pub fn snap_engine_mut(&mut self) -> &mut SnapEngine {
+        &mut self.snap
+    }
+
+    /// Move a clip to a new start, snapping either of its edges.
+    ///
+    /// With linked selection on, every clip in the clip's group moves by the
+    /// same offset. Returns the snapped start the clip was moved to.
+    pub fn move_clip(
+        &mut self, clip_id: u64, start: TimePosition,
+    ) -> VideoEditorResult<SnappedPosition> {
+        let members = self.edit_set(clip_id);
+        let (track_index, clip_index) = self.locate_editable_clip(clip_id)?;
+        let anchor = &self.tracks[track_index].clips[clip_index];
+        let (old_start, duration) = (anchor.start, anchor.duration);
+        self.snap.set_clip_edges(&self.tracks, &members);
+        let snapped = self.snap.snap_span(start, duration, self.snap.tolerance());
+        let delta = snapped.position.ms as i64 - old_start.ms as i64;
+
+        let mut moves = Vec::with_capacity(members.len());
+        for &id in &members {
+            let (track_index, clip_index) = self.locate_editable_clip(id)?;
+            let track = &self.tracks[track_index];
+            let clip = &track.clips[clip_index];
+            let new_start = Self::offset(clip.start, delta, id)?;
+            let new_end = new_start + clip.duration;
+            if track.clips.iter().any(|c| {
+                !members.contains(&c.id) && new_start.ms < c.end().ms && new_end.ms > c.start.ms
+            }) {
+                return Err(VideoEditorError::timeline_clip(
+                    "move_clip",
+                    id,
+                    format!("Clip {id} would overlap another clip"),
+                ));
+            }
+            moves.push((track_index, id, new_start));
+        }
+
+        for (track_index, id, new_start) in moves {
+            let track = &mut self.tracks[track_index];
+            if let Some(mut clip) = track.remove_clip(id) {
+                let from = clip.start;
+                clip.start = new_start;
+                track.add_clip(clip);
+                self.events.emit(EditorEvent::ClipMoved { clip_id: id, from, to: new_start });
+            }
+        }
+        self.recalculate_duration();
+        Ok(snapped)
+    }
+
+    /// Trim the start of a clip to a new (snapped) position.
+    ///
+    /// With linked selection on, linked clips are trimmed by the same amount.
+    pub fn trim_clip_start(
+        &mut self, clip_id: u64, start: TimePosition,
+    ) -> VideoEditorResult<SnappedPosition> {
+        let members = self.edit_set(clip_id);
+        let (track_index, clip_index) = self.locate_editable_clip(clip_id)?;
+        let old_start = self.tracks[track_index].clips[clip_index].start;
+        self.snap.set_clip_edges(&self.tracks, &members);
+        let snapped = self.snap.snap(start, self.snap.tolerance());
+        let delta = snapped.position.ms as i64 - old_start.ms as i64;
+
+        let mut trims = Vec::with_capacity(members.len());
+        for &id in &members {
+            let (track_index, clip_index) = self.locate_editable_clip(id)?;
+            let new_start =
+                Self::offset(self.tracks[track_index].clips[clip_index].start, delta, id)?;
+            trims.push((
+                track_index,
+                clip_index,
+                self.trimmed_start(track_index, clip_index, new_start)?,
+            ));
+        }
+        for (track_index, clip_index, clip) in trims {
+            let clip_id = clip.id;
+            self.tracks[track_index].clips[clip_index] = clip;
+            self.events.emit(EditorEvent::ClipTrimmed { clip_id });
+        }
+        self.recalculate_duration();
+        Ok(snapped)
+    }
+
+    /// Trim the end of a clip to a new (snapped) position.
+    ///
+    /// With linked selection on, linked clips are trimmed by the same amount.
+    pub fn trim_clip_end(
+        &mut self, clip_id: u64, end: TimePosition,
+    ) -> VideoEditorResult<SnappedPosition> {
+        let members = self.edit_set(clip_id);
+        let (track_index, clip_index) = self.locate_editable_clip(clip_id)?;
+        let old_end = self.tracks[track_index].clips[clip_index].end();
+        self.snap.set_clip_edges(&self.tracks, &members);
+        let snapped = self.snap.snap(end, self.snap.tolerance());
+        let delta = snapped.position.ms as i64 - old_end.ms as i64;
+
+        let mut trims = Vec::with_capacity(members.len());
+        for &id in &members {
+            let (track_index, clip_index) = self.locate_editable_clip(id)?;
+            let new_end =
+                Self::offset(self.tracks[track_index].clips[clip_index].end(), delta, id)?;
+            trims.push((
+                track_index,
+                clip_index,
+                self.trimmed_end(track_index, clip_index, new_end)?,
+            ));
+        }
+        for (track_index, clip_index, clip) in trims {
+            let clip_id = clip.id;
+            self.tracks[track_index].clips[clip_index] = clip;
+            self.events.emit(EditorEvent::ClipTrimmed { clip_id });
+        }
+        self.recalculate_duration();
+        Ok(snapped)
+    }
+
+    /// Roll every cut between adjacent clips onto the nearest beat within
+    /// `tolerance`, trimming the outgoing clip's end and the incoming
+    /// clip's start together.
+    ///
+    /// Cuts on locked tracks and rolls the clips' media or neighbours
+    /// cannot cover are left alone. Returns the IDs of the outgoing clips
+    /// whose cut moved.
+    pub fn snap_cuts_to_beats(
+        &mut self, beats: &[TimePosition], tolerance: TimePosition,
+    ) -> Vec<u64> {
+        let mut cuts = Vec::new();
+        for (track_index, track) in self.tracks.iter().enumerate() {
+            if self.locked(track) {
+                continue;
+            }
+            for (clip_index, pair) in track.clips.windows(2).enumerate() {
+                let cut = pair[0].end().ms;
+                if cut != pair[1].start.ms {
+                    continue;
+                }
+                let nearest = beats
+                    .iter()
+                    .map(|b| b.ms)
+                    .filter(|&b| b != cut && b.abs_diff(cut) <= tolerance.ms)
+                    .min_by_key(|b| b.abs_diff(cut));
+                if let Some(beat) = nearest {
+                    cuts.push((track_index, clip_index, TimePosition::from_ms(beat)));
+                }
+            }
+        }
+
+        let mut moved = Vec::new();
+        for (track_index, clip_index, beat) in cuts {
+            // Shrink whichever side gives up time before growing the other
+            let later = beat.ms > self.tracks[track_index].clips[clip_index].end().ms;
+            let order = if later { [1, 0] } else { [0, 1] };
+            let original = [
+                self.tracks[track_index].clips[clip_index].clone(),
+                self.tracks[track_index].clips[clip_index + 1].clone(),
+            ];
+            let rolled = order.iter().try_for_each(|&side| {
+                let index = clip_index + side;
+                let clip = if side == 0 {
+                    self.trimmed_end(track_index, index, beat)?
+                } else {
+                    self.trimmed_start(track_index, index, beat)?
+                };
+                self.tracks[track_index].clips[index] = clip;
+                Ok::<_, VideoEditorError>(())
+            });
+            if rolled.is_err() {
+                let [outgoing, incoming] = original;
+                self.tracks[track_index].clips[clip_index] = outgoing;
+                self.tracks[track_index].clips[clip_index + 1] = incoming;
+                continue;
+            }
+            for index in [clip_index, clip_index + 1] {
+                let clip_id = self.tracks[track_index].clips[index].id;
+                self.events.emit(EditorEvent::ClipTrimmed { clip_id });
+            }
+            moved.push(self.tracks[track_index].clips[clip_index].id);
+        }
+        self.recalculate_duration();
+        moved
+    }
+
+    /// Returns a copy of a clip with its start trimmed to `start`.
+    fn trimmed_start(
+        &self, track_index: usize, clip_index: usize, start: TimePosition,
+    ) -> VideoEditorResult<TimelineClip> {
+        let track = &self.tracks[track_index];
+        let clip = &track.clips[clip_index];
+        let end = clip.end();
+        if start.ms >= end.ms {
+            return Err(VideoEditorError::timeline_clip(
+                "trim_start",
+                clip.id,
+                "Trim would leave the clip empty",
+            ));
+        }
+        let earliest = track
+            .clips
+            .iter()
+            .filter(|c| c.id != clip.id && c.end().ms <= clip.start.ms)
+            .map(|c| c.end().ms)
+            .max()
+            .unwrap_or(0);
+        let source_offset = (start.ms as f64 - clip.start.ms as f64) * clip.speed as f64;
+        let in_point = clip.in_point.ms as f64 + source_offset;
+        if start.ms < earliest || in_point < 0.0 {
+            return Err(VideoEditorError::timeline_clip(
+                "trim_start",
+                clip.id,
+                format!("Clip {} cannot be extended past its neighbour or source start", clip.id),
+            ));
+        }
+
+        let mut trimmed = clip.clone();
+        trimmed.in_point = TimePosition::from_ms(in_point.round() as u64);
+        trimmed.duration = TimePosition::from_ms(end.ms - start.ms);
+        trimmed.start = start;
+        Ok(trimmed)
+    }
+
+    /// Returns a copy of a clip with its end trimmed to `end`.
+    fn trimmed_end(
+        &self, track_index: usize, clip_index: usize, end: TimePosition,
+    ) -> VideoEditorResult<TimelineClip> {
+        let track = &self.tracks[track_index];
+        let clip = &track.clips[clip_index];
+        if end.ms <= clip.start.ms {
+            return Err(VideoEditorError::timeline_clip(
+                "trim_end",
+                clip.id,
+                "Trim would leave the clip empty",
+            ));
+        }
+        let latest = track
+            .clips
+            .iter()
+            .filter(|c| c.id != clip.id && c.start.ms >= clip.end().ms)
+            .map(|c| c.start.ms)
+            .min()
+            .unwrap_or(u64::MAX);
+        if end.ms > latest {
+            return Err(VideoEditorError::timeline_clip(
+                "trim_end",
+                clip.id,
+                format!("Clip {} cannot be extended past its neighbour", clip.id),
+            ));
+        }
+
+        let mut trimmed = clip.clone();
+        let duration = end.ms - clip.start.ms;
+        trimmed.duration = TimePosition::from_ms(duration);
+        trimmed.out_point =
+            TimePosition::from_ms(clip.in_point.ms + (duration as f64 * clip.speed as f64) as u64);
+        Ok(trimmed)
+    }
+
+    /// Shift a position by a signed offset, failing before the timeline start.
+    fn offset(
+        position: TimePosition, delta_ms: i64, clip_id: u64,
+    ) -> VideoEditorResult<TimePosition> {
+        position.ms.checked_add_signed(delta_ms).map(TimePosition::from_ms).ok_or_else(|| {
+            VideoEditorError::timeline_clip(
+                "move_clip",
+                clip_id,
+                format!("Clip {clip_id} would move before the timeline start"),
+            )
+        })
+    }
+
+    /// Split a clip in two at a timeline position.
+    ///
+    /// The clip keeps the part before `at`; the part after becomes a new,
+    /// unlinked clip. Returns the new clip's ID.
+    pub fn split_clip(&mut self, clip_id: u64, at: TimePosition) -> VideoEditorResult<u64> {
+        let (track_index, clip_index) = self.locate_editable_clip(clip_id)?;
+        let new_id = self.next_clip_id;
+        let track = &mut self.tracks[track_index];
+        let (first, second) = track.clips[clip_index].split_at(at, new_id).ok_or_else(|| {
+            VideoEditorError::timeline_clip(
+                "split_clip",
+                clip_id,
+                format!("Split point is outside clip {clip_id}"),
+            )
+        })?;
+
+        track.clips[clip_index] = first;
+        track.add_clip(second);
+        self.next_clip_id += 1;
+        self.clip_index.take();
+        self.events.emit(EditorEvent::ClipSplit { clip_id, new_clip_id: new_id });
+        Ok(new_id)
+    }
+
+    /// Set the audio pitch shift of a clip on an unlocked track.
+    pub fn set_clip_pitch(&mut self, clip_id: u64, pitch: ClipPitch) -> VideoEditorResult<()> {
+        let (track_index, clip_index) = self.locate_editable_clip(clip_id)?;
+        self.tracks[track_index].clips[clip_index].pitch = pitch;
+        Ok(())
+    }
+
+    /// Set the source channel routing of a clip on an unlocked track.
+    pub fn set_clip_channel_map(&mut self, clip_id: u64, map: ChannelMap) -> VideoEditorResult<()> {
+        let (track_index, clip_index) = self.locate_editable_clip(clip_id)?;
+        self.tracks[track_index].clips[clip_index].channel_map = map;
+        Ok(())
+    }
+
+    /// Set the audio edge fades of a clip on an unlocked audio track.
+    ///
+    /// The fades are shortened to fit the clip, fade-in first.
+    pub fn set_clip_audio_fades(
+        &mut self, clip_id: u64, fade_in: ClipFade, fade_out: ClipFade,
+    ) -> VideoEditorResult<()> {
+        let (track_index, clip_index) = self.locate_editable_clip(clip_id)?;
+        let track = &mut self.tracks[track_index];
+        if !track.track_type.accepts_audio() {
+            return Err(VideoEditorError::timeline_clip(
+                "set_clip_audio_fades",
+                clip_id,
+                format!("Clip {clip_id} is not on an audio track"),
+            ));
+        }
+        let clip = &mut track.clips[clip_index];
+        let fade_in_ms = fade_in.duration.ms.min(clip.duration.ms);
+        let fade_out_ms = fade_out.duration.ms.min(clip.duration.ms - fade_in_ms);
+        clip.audio_fade_in = ClipFade { duration: TimePosition::from_ms(fade_in_ms), ..fade_in };
+        clip.audio_fade_out = ClipFade { duration: TimePosition::from_ms(fade_out_ms), ..fade_out };
+        Ok(())
+    }
+
+    /// Append effects to the end of a clip's effect stack.
+    pub fn add_clip_effects(&mut self, clip_id: u64, effect_ids: &[u64]) -> VideoEditorResult<()> {
+        let (track_index, clip_index) = self.locate_editable_clip(clip_id)?;
+        self.tracks[track_index].clips[clip_index].effect_ids.extend_from_slice(effect_ids);
+        Ok(())
+    }
+
+    /// The clip cut against the end of a clip on the same track, with
+    /// that track's ID.
+    #[must_use]
+    pub fn following_clip(&self, clip_id: u64) -> Option<(u64, &TimelineClip)> {
+        self.tracks.iter().find_map(|track| {
+            let clip = track.clips.iter().find(|c| c.id == clip_id)?;
+            let end = clip.end();
+            track.clips.iter().find(|c| c.start == end).map(|next| (track.id, next))
+        })
+    }
+
+    /// Break a multichannel audio clip out into linked mono clips, one per
+    /// source channel.
+    ///
+    /// The clip keeps channel 1. Each further channel goes to the nearest
+    /// unlocked audio track above the clip's (higher index) that is free
+    /// over the clip's range, or to a new audio track on top. Nothing
+    /// changes if the clip can't be broken out. Returns the clip IDs in
+    /// channel order.
+    pub fn break_out_channels(
+        &mut self, clip_id: u64, source_channels: u8,
+    ) -> VideoEditorResult<Vec<u64>> {
+        let (track_index, clip_index) = self.locate_editable_clip(clip_id)?;
+        let track = &self.tracks[track_index];
+        if !track.track_type.accepts_audio() {
+            return Err(VideoEditorError::timeline_clip(
+                "break_out_channels",
+                clip_id,
+                format!("Clip {clip_id} is not on an audio track"),
+            ));
+        }
+        if source_channels < 2 {
+            return Err(VideoEditorError::timeline_clip(
+                "break_out_channels",
+                clip_id,
+                format!("Clip {clip_id} has no channels to break out"),
+            ));
+        }
+        let (track_name, track_order) = (track.name.clone(), track.index);
+        let clip = track.clips[clip_index].clone();
+
+        // Free tracks, nearest first, each taking one channel
+        let mut free: Vec<(usize, u64)> = self
+            .tracks
+            .iter()
+            .filter(|t| {
+                t.index > track_order
+                    && t.track_type == TrackType::Audio
+                    && !self.locked(t)
+                    && t.is_range_available(clip.start, clip.end())
+            })
+            .map(|t| (t.index, t.id))
+            .collect();
+        free.sort_unstable();
+        let mut free = free.into_iter().map(|(_, id)| id);
+
+        self.tracks[track_index].clips[clip_index].channel_map = ChannelMap::mono(0);
+        let mut ids = vec![clip_id];
+        for channel in 1..source_channels {
+            let target = free.next().unwrap_or_else(|| {
+                self.add_track(format!("{track_name} Ch {}", channel + 1), TrackType::Audio)
+            });
+            let id = self.next_clip_id;
+            let mono = TimelineClip { id, channel_map: ChannelMap::mono(channel), ..clip.clone() };
+            self.add_clip(target, mono)?;
+            ids.push(id);
+        }
+        self.link_clips(&ids)?;
+        Ok(ids)
+    }
+
+    /// Move a clip so its source lines up with a reference clip, then link
+    /// the two.
+    ///
+    /// `offset_ms` is the reference's source time at the clip's source time
+    /// zero, as found by `sync_by_waveform`. Only the clip moves, without
+    /// snapping. Returns the new group's ID.
+    pub fn align_and_link(
+        &mut self, reference_id: u64, clip_id: u64, offset_ms: i64,
+    ) -> VideoEditorResult<u64> {
+        let (ref_track, ref_index) = self.locate_editable_clip(reference_id)?;
+        let reference = &self.tracks[ref_track].clips[ref_index];
+        let (track_index, clip_index) = self.locate_editable_clip(clip_id)?;
+        let clip = &self.tracks[track_index].clips[clip_index];
+
+        let source = clip.in_point.ms as f64 + offset_ms as f64 - reference.in_point.ms as f64;
+        let start =
+            reference.start.ms as f64 + source / f64::from(reference.speed.max(f32::EPSILON));
+        if start < 0.0 {
+            return Err(VideoEditorError::timeline_clip(
+                "align_and_link",
+                clip_id,
+                format!("Clip {clip_id} would move before the timeline start"),
+            ));
+        }
+        let start = TimePosition::from_ms(start.round() as u64);
+        let end = start + clip.duration;
+        let track = &self.tracks[track_index];
+        if track
+            .clips
+            .iter()
+            .any(|c| c.id != clip_id && start.ms < c.end().ms && end.ms > c.start.ms)
+        {
+            return Err(VideoEditorError::timeline_clip(
+                "align_and_link",
+                clip_id,
+                format!("Clip {clip_id} would overlap another clip"),
+            ));
+        }
+
+        let track = &mut self.tracks[track_index];
+        if let Some(mut clip) = track.remove_clip(clip_id) {
+            let from = clip.start;
+            clip.start = start;
+            track.add_clip(clip);
+            self.events.emit(EditorEvent::ClipMoved { clip_id, from, to: start });
+        }
+        self.recalculate_duration();
+        self.link_clips(&[reference_id, clip_id])
+    }
+
+    /// Link clips into a group so they are selected and edited together.
+    ///
+    /// Clips already in a group are moved into the new one.
+    pub fn link_clips(&mut self, clip_ids: &[u64]) -> VideoEditorResult<u64> {
+        let group = ClipGroup::new(self.next_group_id, clip_ids);
+        if group.clip_ids.len() < 2 {
+            return Err(VideoEditorError::timeline(
+                "link_clips",
+                "A clip group needs at least two clips",
+            ));
+        }
+        if let Some(&missing) = group.clip_ids.iter().find(|&&id| self.find_clip(id).is_none()) {
+            return Err(VideoEditorError::timeline_clip(
+                "link_clips",
+                missing,
+                format!("Clip not found: {missing}"),
+            ));
+        }
+
+        self.next_group_id += 1;
+        for group_clip in &group.clip_ids {
+            for existing in &mut self.groups {
+                existing.clip_ids.retain(|id| id != group_clip);
+            }
+        }
+        let id = group.id;
+        self.groups.push(group);
+        self.prune_groups();
+        Ok(id)
+    }
+
+    /// Dissolve a group. Returns whether it existed.
+    pub fn unlink_group(&mut self, group_id: u64) -> bool {
+        let before = self.groups.len();
+        self.groups.retain(|g| g.id != group_id);
+        self.groups.len() != before
+    }
+
+    /// Remove a single clip from its group. Returns whether it was linked.
+    pub fn unlink_clip(&mut self, clip_id: u64) -> bool {
+        let mut unlinked = false;
+        for group in &mut self.groups {
+            let before = group.clip_ids.len();
+            group.clip_ids.retain(|&id| id != clip_id);
+            unlinked |= group.clip_ids.len() != before;
+        }
+        self.prune_groups();
+        unlinked
+    }
+
+    /// Get the group a clip belongs to.
+    pub fn group_of(&self, clip_id: u64) -> Option<&ClipGroup> {
+        self.groups.iter().find(|g| g.contains(clip_id))
+    }
+
+    /// Get all clip groups.
+    pub fn groups(&self) -> &[ClipGroup] {
+        &self.groups
+    }
+
+    /// Replace all groups (e.g. when loading a project). Links to clips not
+    /// on the timeline are dropped.
+    pub fn set_groups(&mut self, groups: Vec<ClipGroup>) {
+        self.next_group_id =
+            groups.iter().map(|g| g.id + 1).max().unwrap_or(1).max(self.next_group_id);
+        self.groups = groups;
+        let tracks = &self.tracks;
+        for group in &mut self.groups {
+            group.clip_ids.retain(|&id| tracks.iter().any(|t| t.clips.iter().any(|c| c.id == id)));
+        }
+        self.prune_groups();
+    }
+
+    /// Returns the clips selected together with a clip: its whole group
+    /// with linked selection on, otherwise just the clip.
+    pub fn resolve_selection(&self, clip_id: u64) -> Vec<u64> {
+        self.edit_set(clip_id)
+    }
+
+    /// Returns whether linked selection is on.
+    pub fn linked_selection(&self) -> bool {
+        self.linked_selection
+    }
+
+    /// Turn linked selection on or off. When off, edits ignore groups.
+    pub fn set_linked_selection(&mut self, enabled: bool) {
+        self.linked_selection = enabled;
+    }
+
+    /// Get the selection.
+    pub fn selection(&self) -> &SelectionModel {
+        &self.selection
+    }
+
+    /// Get the mutable selection.
+    pub fn selection_mut(&mut self) -> &mut SelectionModel {
+        &mut self.selection
+    }
+
+    /// Select the clips under `from` and every clip after it on `track_ids`
+    /// (every track when empty), replacing the selection unless
+    /// `add_to_selection`.
+    pub fn select_forward(
+        &mut self, from: TimePosition, track_ids: &[u64], add_to_selection: bool,
+    ) {
+        let clip_ids: Vec<u64> = self
+            .tracks
+            .iter()
+            .filter(|t| track_ids.is_empty() || track_ids.contains(&t.id))
+            .flat_map(|t| &t.clips)
+            .filter(|c| c.end().ms > from.ms)
+            .map(|c| c.id)
+            .collect();
+        if !add_to_selection {
+            self.selection.clear();
+        }
+        for clip_id in clip_ids {
+            self.selection.select_clip(clip_id, true);
+        }
+    }
+
+    /// Get the clips the selection covers, by track then start: the picked
+    /// clips and the clips overlapping the range on its tracks, with their
+    /// linked clips when linked selection is on.
+    pub fn selected_clips(&self) -> Vec<u64> {
+        let mut covered: Vec<u64> = self.selection.clips().to_vec();
+        if let Some(range) = self.selection.range() {
+            covered.extend(
+                self.clips_in_range(range.start, range.end)
+                    .into_iter()
+                    .filter(|(track_id, _)| range.covers_track(*track_id))
+                    .map(|(_, clip)| clip.id),
+            );
+        }
+        let covered: Vec<u64> = covered.into_iter().flat_map(|id| self.edit_set(id)).collect();
+        self.tracks
+            .iter()
+            .flat_map(|t| &t.clips)
+            .map(|c| c.id)
+            .filter(|id| covered.contains(id))
+            .collect()
+    }
+
+    /// Remove the selected clips, leaving gaps, and clear the selection.
+    ///
+    /// Nothing is removed if a selected clip is on a locked track. Returns
+    /// the removed clips.
+    pub fn delete_selection(&mut self) -> VideoEditorResult<Vec<TimelineClip>> {
+        let clip_ids = self.selected_clips();
+        let Some(&first) = clip_ids.first() else {
+            return Ok(Vec::new());
+        };
+        let removed = self.take_clips(first, &clip_ids)?;
+        self.selection.clear();
+        self.recalculate_duration();
+        Ok(removed)
+    }
+
+    /// Move the selected clips together by `delta_ms`, ignoring snapping.
+    /// A selected range moves with them.
+    ///
+    /// Nothing moves if a selected clip is on a locked track, would start
+    /// before the timeline or would overlap a clip left out of the selection.
+    pub fn nudge_selection(&mut self, delta_ms: i64) -> VideoEditorResult<()> {
+        let clip_ids = self.selected_clips();
+        let mut moves = Vec::with_capacity(clip_ids.len());
+        for &id in &clip_ids {
+            let (track_index, clip_index) = self.locate_editable_clip(id)?;
+            let track = &self.tracks[track_index];
+            let clip = &track.clips[clip_index];
+            let new_start = Self::offset(clip.start, delta_ms, id)?;
+            let new_end = new_start + clip.duration;
+            if track.clips.iter().any(|c| {
+                !clip_ids.contains(&c.id) && new_start.ms < c.end().ms && new_end.ms > c.start.ms
+            }) {
+                return Err(VideoEditorError::timeline_clip(
+                    "nudge_selection",
+                    id,
+                    format!("Clip {id} would overlap another clip"),
+                ));
+            }
+            moves.push((track_index, id, new_start));
+        }
+
+        // Take every clip out before placing any, so clips moving past each
+        // other on a track never meet
+        let mut placed = Vec::with_capacity(moves.len());
+        for (track_index, id, new_start) in moves {
+            if let Some(clip) = self.tracks[track_index].remove_clip(id) {
+                placed.push((track_index, clip, new_start));
+            }
+        }
+        for (track_index, mut clip, new_start) in placed {
+            let (clip_id, from) = (clip.id, clip.start);
+            clip.start = new_start;
+            self.tracks[track_index].add_clip(clip);
+            self.events.emit(EditorEvent::ClipMoved { clip_id, from, to: new_start });
+        }
+        self.selection.offset_range(delta_ms);
+        self.recalculate_duration();
+        Ok(())
+    }
+
+    /// Append effects to the effect stack of every selected clip.
+    ///
+    /// Nothing changes if a selected clip is on a locked track. Returns the
+    /// clips changed.
+    pub fn apply_effects_to_selection(
+        &mut self, effect_ids: &[u64],
+    ) -> VideoEditorResult<Vec<u64>> {
+        let clip_ids = self.selected_clips();
+        let locations = clip_ids
+            .iter()
+            .map(|&id| self.locate_editable_clip(id))
+            .collect::<VideoEditorResult<Vec<_>>>()?;
+        for (track_index, clip_index) in locations {
+            self.tracks[track_index].clips[clip_index].effect_ids.extend_from_slice(effect_ids);
+        }
+        Ok(clip_ids)
+    }
+
+    fn edit_set(&self, clip_id: u64) -> Vec<u64> {
+        match self.group_of(clip_id) {
+            Some(group) if self.linked_selection => group.clip_ids.clone(),
+            _ => vec![clip_id],
+        }
+    }
+
+    fn find_clip(&self, clip_id: u64) -> Option<&TimelineClip> {
+        self.tracks.iter().find_map(|t| t.clips.iter().find(|c| c.id == clip_id))
+    }
+
+    /// Drop groups left with fewer than two clips.
+    fn prune_groups(&mut self) {
+        self.groups.retain(|g| g.clip_ids.len() >= 2);
+    }
+
+    /// Find a clip on an unlocked track as `(track index, clip index)`.
+    fn locate_editable_clip(&self, clip_id: u64) -> VideoEditorResult<(usize, usize)> {
+        for (track_index, track) in self.tracks.iter().enumerate() {
+            if let Some(clip_index) = track.clips.iter().position(|c| c.id == clip_id) {
+                if self.locked(track) {
+                    return Err(VideoEditorError::timeline(
+                        "edit_clip",
+                        format!("Track {} is locked", track.id),
+                    ));
+                }
+                return Ok((track_index, clip_index));
+            }
+        }
+        Err(VideoEditorError::timeline_clip(
+            "edit_clip",
+            clip_id,
+            format!("Clip not found: {clip_id}"),
+        ))
+    }
+
+    /// Place an adjustment clip on an effect track.
+    ///
+    /// Adjustment clips may only live on unlocked `TrackType::Effect` tracks
+    /// and must not overlap other adjustment clips on the same track.
+    pub fn add_adjustment_clip(
+        &mut self, track_id: u64, clip: AdjustmentClip,
+    ) -> VideoEditorResult<()> {
+        let track = self.editable_track_mut(track_id)?;
+
+        if track.track_type != TrackType::Effect {
+            return Err(VideoEditorError::timeline(
+                "add_adjustment_clip",
+                format!(
+                    "Adjustment clips require an effect track, got {}",
+                    track.track_type.name()
+                ),
+            ));
+        }
+        if clip.duration.ms == 0 {
+            return Err(VideoEditorError::timeline(
+                "add_adjustment_clip",
+                "Adjustment clip has zero duration",
+            ));
+        }
+        if track.adjustments.iter().any(|a| a.overlaps(clip.start, clip.end())) {
+            return Err(VideoEditorError::timeline(
+                "add_adjustment_clip",
+                "Adjustment clip overlaps an existing adjustment clip",
+            ));
+        }
+
+        track.adjustments.push(clip);
+        track.adjustments.sort_by_key(|a| a.start.ms);
+        self.recalculate_duration();
+        Ok(())
+    }
+
+    /// Remove an adjustment clip from an unlocked track.
+    pub fn remove_adjustment_clip(&mut self, clip_id: u64) -> Option<AdjustmentClip> {
+        let locked: Vec<u64> =
+            self.tracks.iter().filter(|t| self.locked(t)).map(|t| t.id).collect();
+        let removed =
+            self.tracks.iter_mut().filter(|t| !locked.contains(&t.id)).find_map(|track| {
+                let pos = track.adjustments.iter().position(|a| a.id == clip_id)?;
+                Some(track.adjustments.remove(pos))
+            });
+        if removed.is_some() {
+            self.recalculate_duration();
+        }
+        removed
+    }
+
+    /// Move a track to a new position in the stack (0 is the bottom). The
+    /// render order follows.
+    pub fn move_track(&mut self, track_id: u64, index: usize) -> VideoEditorResult<()> {
+        let pos = self.tracks.iter().position(|t| t.id == track_id).ok_or_else(|| {
+            VideoEditorError::timeline("move_track", format!("Track not found: {track_id}"))
+        })?;
+        let track = self.tracks.remove(pos);
+        let index = index.min(self.tracks.len());
+        self.tracks.insert(index, track);
+        self.reindex_tracks();
+        self.events.emit(EditorEvent::TrackMoved { track_id, index });
+        Ok(())
+    }
+
+    /// Move all tracks of a group to `index`, keeping their relative order
+    /// and making them adjacent.
+    pub fn move_track_group(&mut self, group_id: u64, index: usize) -> VideoEditorResult<()> {
+        let members = self.track_group(group_id).map(|g| g.track_ids.clone()).ok_or_else(|| {
+            VideoEditorError::timeline(
+                "move_track_group",
+                format!("Track group not found: {group_id}"),
+            )
+        })?;
+        let (moved, mut rest): (Vec<_>, Vec<_>) =
+            self.tracks.drain(..).partition(|t| members.contains(&t.id));
+        let index = index.min(rest.len());
+        rest.splice(index..index, moved);
+        self.tracks = rest;
+        self.reindex_tracks();
+        Ok(())
+    }
+
+    /// Returns track IDs bottom to top, for project storage.
+    pub fn track_order(&self) -> Vec<u64> {
+        self.tracks.iter().map(|t| t.id).collect()
+    }
+
+    /// Restore a saved track order. Tracks missing from `order` keep their
+    /// relative order above the listed ones.
+    pub fn set_track_order(&mut self, order: &[u64]) {
+        self.tracks.sort_by_key(|t| order.iter().position(|&id| id == t.id).unwrap_or(usize::MAX));
+        self.reindex_tracks();
+    }
+
+    fn reindex_tracks(&mut self) {
+        self.clip_index.take();
+        for (i, track) in self.tracks.iter_mut().enumerate() {
+            track.index = i;
+        }
+    }
+
+    /// Group tracks under a name. Tracks already in a group are moved into
+    /// the new one.
+    pub fn group_tracks(
+        &mut self, name: impl Into<String>, track_ids: &[u64],
+    ) -> VideoEditorResult<u64> {
+        let group = TrackGroup::new(self.next_track_group_id, name, track_ids);
+        if group.track_ids.is_empty() {
+            return Err(VideoEditorError::timeline(
+                "group_tracks",
+                "A track group needs at least one track",
+            ));
+        }
+        if let Some(&missing) = group.track_ids.iter().find(|&&id| self.get_track(id).is_none()) {
+            return Err(VideoEditorError::timeline(
+                "group_tracks",
+                format!("Track not found: {missing}"),
+            ));
+        }
+
+        self.next_track_group_id += 1;
+        for existing in &mut self.track_groups {
+            existing.track_ids.retain(|id| !group.track_ids.contains(id));
+        }
+        self.track_groups.retain(|g| !g.track_ids.is_empty());
+        let id = group.id;
+        self.track_groups.push(group);
+        Ok(id)
+    }
+
+    /// Dissolve a track group, keeping its tracks. Returns whether it
+    /// existed.
+    pub fn ungroup_tracks(&mut self, group_id: u64) -> bool {
+        let before = self.track_groups.len();
+        self.track_groups.retain(|g| g.id != group_id);
+        self.track_groups.len() != before
+    }
+
+    /// Get a track group by ID.
+    pub fn track_group(&self, group_id: u64) -> Option<&TrackGroup> {
+        self.track_groups.iter().find(|g| g.id == group_id)
+    }
+
+    /// Get the group a track belongs to.
+    pub fn track_group_of(&self, track_id: u64) -> Option<&TrackGroup> {
+        self.track_groups.iter().find(|g| g.contains(track_id))
+    }
+
+    /// Get all track groups.
+    pub fn track_groups(&self) -> &[TrackGroup] {
+        &self.track_groups
+    }
+
+    /// Replace all track groups (e.g. when loading a project). Tracks not on
+    /// the timeline are dropped.
+    pub fn set_track_groups(&mut self, groups: Vec<TrackGroup>) {
+        self.next_track_group_id =
+            groups.iter().map(|g| g.id + 1).max().unwrap_or(1).max(self.next_track_group_id);
+        self.track_groups = groups;
+        let tracks = &self.tracks;
+        for group in &mut self.track_groups {
+            group.track_ids.retain(|&id| tracks.iter().any(|t| t.id == id));
+        }
+        self.track_groups.retain(|g| !g.track_ids.is_empty());
+    }
+
+    /// Collapse or expand a track group in the track list.
+    pub fn set_track_group_collapsed(&mut self, group_id: u64, collapsed: bool) -> bool {
+        self.track_group_mut(group_id).map(|g| g.collapsed = collapsed).is_some()
+    }
+
+    /// Mute or unmute every track in a group.
+    pub fn set_track_group_muted(&mut self, group_id: u64, muted: bool) -> bool {
+        self.track_group_mut(group_id).map(|g| g.muted = muted).is_some()
+    }
+
+    /// Solo or unsolo every track in a group.
+    pub fn set_track_group_solo(&mut self, group_id: u64, solo: bool) -> bool {
+        self.track_group_mut(group_id).map(|g| g.solo = solo).is_some()
+    }
+
+    /// Lock or unlock every track in a group against edits.
+    pub fn set_track_group_locked(&mut self, group_id: u64, locked: bool) -> bool {
+        self.track_group_mut(group_id).map(|g| g.locked = locked).is_some()
+    }
+
+    /// Checks if a track is hidden inside a collapsed group.
+    pub fn is_track_collapsed(&self, track_id: u64) -> bool {
+        self.track_group_of(track_id).is_some_and(|g| g.collapsed)
+    }
+
+    fn track_group_mut(&mut self, group_id: u64) -> Option<&mut TrackGroup> {
+        self.track_groups.iter_mut().find(|g| g.id == group_id)
+    }
+
+    /// Get the adjustment clips applied to a track at a time, bottom to top.
+    pub fn adjustments_for(&self, track_id: u64, time: TimePosition) -> Vec<&AdjustmentClip> {
+        let Some(index) = self.get_track(track_id).map(|t| t.index) else {
+            return Vec::new();
+        };
+        let mut above: Vec<&TimelineTrack> = self
+            .tracks
+            .iter()
+            .filter(|t| {
+                t.track_type == TrackType::Effect && t.index > index && self.track_visible(t)
+            })
+            .collect();
+        above.sort_by_key(|t| t.index);
+        above.into_iter().filter_map(|t| t.adjustment_at(time)).collect()
+    }
+
+    /// Build the compositing render graph for a time.
+    ///
+    /// Tracks composite bottom (index 0) to top into the `composite`
+    /// attachment; each active adjustment clip adds a post-process pass over
+    /// everything composited so far. Disabled tracks and video tracks hidden
+    /// by another track's solo are skipped.
+    pub fn build_render_graph(&self, time: TimePosition) -> RenderGraph {
+        let mut graph = RenderGraph::new();
+        let mut tracks: Vec<&TimelineTrack> =
+            self.tracks.iter().filter(|t| self.track_visible(t)).collect();
+        tracks.sort_by_key(|t| t.index);
+
+        for track in tracks {
+            match track.track_type {
+                TrackType::Video => {
+                    if track.clips.iter().any(|c| c.enabled && c.contains(time)) {
+                        graph.add_pass(
+                            RenderPass::new(
+                                RenderPassType::Composite,
+                                format!("track_{}", track.id),
+                            )
+                            .with_input(format!("track_{}_source", track.id))
+                            .with_output("composite"),
+                        );
+                    }
+                },
+                TrackType::Effect => {
+                    if let Some(adjustment) = track.adjustment_at(time) {
+                        graph.add_pass(
+                            RenderPass::new(
+                                RenderPassType::PostProcess,
+                                format!("adjustment_{}", adjustment.id),
+                            )
+                            .with_input("composite")
+                            .with_output("composite"),
+                        );
+                    }
+                },
+                TrackType::Audio | TrackType::Subtitle | TrackType::Data => {},
+            }
+        }
+
+        graph.add_pass(
+            RenderPass::new(RenderPassType::Output, "output")
+                .with_input("composite")
+                .with_output("program"),
+        );
+        graph
+    }
 }
 
 impl Default for TimelineManager {
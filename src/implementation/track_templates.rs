@@ -0,0 +1,234 @@
+//! Track templates and saved track layouts.
+//!
+//! A [`TrackTemplate`] captures how a track is set up (type, label color,
+//! height, mute state, effect stack and mixer strip) without its clips. A
+//! [`TrackLayout`] is a named list of templates for a whole timeline. Both
+//! are kept in the user config and serialize to bytes for storage.
+
+use super::{
+    audio_mixer::AudioTrackStrip,
+    effects::{EffectType, EffectsPipeline},
+};
+use crate::types::{TimelineTrack, TrackType, timeline::ByteReader};
+
+/// Mixer strip settings applied to an audio track's strip.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackStripSettings {
+    /// Volume level (0.0 to 2.0, where 1.0 = unity gain).
+    pub volume: f32,
+    /// Pan position (-1.0 = left, 0.0 = center, 1.0 = right).
+    pub pan:    f32,
+}
+
+impl Default for TrackStripSettings {
+    fn default() -> Self {
+        Self { volume: 1.0, pan: 0.0 }
+    }
+}
+
+/// Reusable track setup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackTemplate {
+    /// Name given to created tracks.
+    pub name:       String,
+    /// Track type.
+    pub track_type: TrackType,
+    /// Default effect stack with parameters, in application order.
+    pub effects:    Vec<(EffectType, Vec<(String, f64)>)>,
+    /// Mixer strip settings.
+    pub strip:      TrackStripSettings,
+    /// Label color.
+    pub color:      Option<[f32; 4]>,
+    /// Track height in pixels.
+    pub height:     u32,
+    /// Whether created tracks start muted.
+    pub muted:      bool,
+}
+
+impl TrackTemplate {
+    /// Serialized format version.
+    const VERSION: u8 = 1;
+
+    /// Creates a template with default track settings.
+    #[must_use]
+    pub fn new(name: impl Into<String>, track_type: TrackType) -> Self {
+        Self {
+            name: name.into(),
+            track_type,
+            effects: Vec::new(),
+            strip: TrackStripSettings::default(),
+            color: None,
+            height: 64,
+            muted: false,
+        }
+    }
+
+    /// Captures the setup of an existing track. Effects missing from
+    /// `effects` are skipped.
+    #[must_use]
+    pub fn from_track(track: &TimelineTrack, effects: &EffectsPipeline) -> Self {
+        Self {
+            name:       track.name.clone(),
+            track_type: track.track_type,
+            effects:    track
+                .effect_ids
+                .iter()
+                .filter_map(|&id| effects.effect(id))
+                .map(|e| (e.effect_type, e.parameters.clone()))
+                .collect(),
+            strip:      TrackStripSettings::default(),
+            color:      track.color,
+            height:     track.height,
+            muted:      track.muted,
+        }
+    }
+
+    /// Takes the strip settings from a mixer strip.
+    #[must_use]
+    pub fn with_strip(mut self, strip: &AudioTrackStrip) -> Self {
+        self.strip = TrackStripSettings { volume: strip.volume(), pan: strip.pan() };
+        self
+    }
+
+    /// Applies the strip settings to a mixer strip.
+    pub fn apply_to_strip(&self, strip: &mut AudioTrackStrip) {
+        strip.set_volume(self.strip.volume);
+        strip.set_pan(self.strip.pan);
+    }
+
+    /// Converts to bytes for config storage.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![Self::VERSION, track_type_code(self.track_type)];
+        write_str(&mut bytes, &self.name);
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+        bytes.push(u8::from(self.muted));
+        bytes.extend_from_slice(&self.strip.volume.to_le_bytes());
+        bytes.extend_from_slice(&self.strip.pan.to_le_bytes());
+        match self.color {
+            Some(color) => {
+                bytes.push(1);
+                color.iter().for_each(|c| bytes.extend_from_slice(&c.to_le_bytes()));
+            },
+            None => bytes.push(0),
+        }
+        bytes.extend_from_slice(&(self.effects.len() as u32).to_le_bytes());
+        for (effect_type, parameters) in &self.effects {
+            let code = EffectType::ALL.iter().position(|t| t == effect_type).unwrap_or(0);
+            bytes.push(code as u8);
+            bytes.extend_from_slice(&(parameters.len() as u32).to_le_bytes());
+            for (name, value) in parameters {
+                write_str(&mut bytes, name);
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Parses a template from bytes.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let reader = &mut ByteReader { bytes, offset: 0 };
+        if reader.u8()? != Self::VERSION {
+            return None;
+        }
+        let track_type = track_type_from_code(reader.u8()?)?;
+        let name = read_str(reader)?;
+        let height = reader.u32()?;
+        let muted = reader.u8()? != 0;
+        let strip = TrackStripSettings {
+            volume: f32::from_bits(reader.u32()?),
+            pan:    f32::from_bits(reader.u32()?),
+        };
+        let color = match reader.u8()? {
+            0 => None,
+            _ => Some(
+                [reader.u32()?, reader.u32()?, reader.u32()?, reader.u32()?].map(f32::from_bits),
+            ),
+        };
+        let effect_count = reader.u32()? as usize;
+        let effects = (0..effect_count)
+            .map(|_| {
+                let effect_type = *EffectType::ALL.get(usize::from(reader.u8()?))?;
+                let count = reader.u32()? as usize;
+                let parameters = (0..count)
+                    .map(|_| Some((read_str(reader)?, f64::from_bits(reader.u64()?))))
+                    .collect::<Option<Vec<_>>>()?;
+                Some((effect_type, parameters))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self { name, track_type, effects, strip, color, height, muted })
+    }
+}
+
+/// Named track layout of a whole timeline, top-level index order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackLayout {
+    /// Layout name.
+    pub name:   String,
+    /// Track templates in index order.
+    pub tracks: Vec<TrackTemplate>,
+}
+
+impl TrackLayout {
+    /// Converts to bytes for config storage.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_str(&mut bytes, &self.name);
+        bytes.extend_from_slice(&(self.tracks.len() as u32).to_le_bytes());
+        for track in &self.tracks {
+            let template = track.to_bytes();
+            bytes.extend_from_slice(&(template.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&template);
+        }
+        bytes
+    }
+
+    /// Parses a layout from bytes.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = ByteReader { bytes, offset: 0 };
+        let name = read_str(&mut reader)?;
+        let count = reader.u32()? as usize;
+        let tracks = (0..count)
+            .map(|_| {
+                let len = reader.u32()? as usize;
+                TrackTemplate::from_bytes(reader.take(len)?)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { name, tracks })
+    }
+}
+
+fn track_type_code(track_type: TrackType) -> u8 {
+    match track_type {
+        TrackType::Video => 0,
+        TrackType::Audio => 1,
+        TrackType::Subtitle => 2,
+        TrackType::Data => 3,
+        TrackType::Effect => 4,
+    }
+}
+
+fn track_type_from_code(code: u8) -> Option<TrackType> {
+    Some(match code {
+        0 => TrackType::Video,
+        1 => TrackType::Audio,
+        2 => TrackType::Subtitle,
+        3 => TrackType::Data,
+        4 => TrackType::Effect,
+        _ => return None,
+    })
+}
+
+pub(super) fn write_str(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+pub(super) fn read_str(reader: &mut ByteReader<'_>) -> Option<String> {
+    let len = reader.u32()? as usize;
+    String::from_utf8(reader.take(len)?.to_vec()).ok()
+}
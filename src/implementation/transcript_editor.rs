@@ -0,0 +1,226 @@
+//! Text-based editing: cut the timeline by editing its transcript.
+//!
+//! Word ranges index into the transcript stored in the [`MetadataIndex`]
+//! by the transcription orchestrator. Each range maps back to a timeline
+//! range that takes half of the pause on either side, so removing adjacent
+//! sentences leaves natural spacing; deleting words ripple-deletes that
+//! range and retimes the remaining transcript to match.
+
+use std::ops::Range;
+
+use super::timeline::{RippleSync, TimelineManager};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    metadata::{MetadataIndex, TranscriptWord},
+    types::TimePosition,
+};
+
+/// Edits a transcribed track through its words.
+#[derive(Debug, Clone)]
+pub struct TranscriptEditor {
+    /// Track whose audio was transcribed.
+    pub track_id:   u64,
+    /// Tracks shifted by a cut; `AllTracks` keeps linked video in sync
+    /// with the edited audio.
+    pub sync:       RippleSync,
+    /// Most of a neighbouring pause a cut takes, in milliseconds.
+    pub max_pad_ms: u64,
+}
+
+impl TranscriptEditor {
+    /// Create an editor for a transcribed track.
+    pub fn new(track_id: u64) -> Self {
+        Self { track_id, sync: RippleSync::AllTracks, max_pad_ms: 250 }
+    }
+
+    /// Word ranges of each sentence; a sentence ends with `.`, `?` or `!`.
+    pub fn sentences(words: &[TranscriptWord]) -> Vec<Range<usize>> {
+        let mut sentences = Vec::new();
+        let mut start = 0;
+        for (i, word) in words.iter().enumerate() {
+            if word.text.ends_with(['.', '?', '!']) {
+                sentences.push(start..i + 1);
+                start = i + 1;
+            }
+        }
+        if start < words.len() {
+            sentences.push(start..words.len());
+        }
+        sentences
+    }
+
+    /// Word range of the first occurrence of `phrase`, ignoring case and
+    /// punctuation.
+    pub fn find_phrase(words: &[TranscriptWord], phrase: &str) -> Option<Range<usize>> {
+        let normalize = |text: &str| {
+            text.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+        };
+        let needle: Vec<String> = phrase.split_whitespace().map(normalize).collect();
+        if needle.is_empty() {
+            return None;
+        }
+        let hay: Vec<String> = words.iter().map(|w| normalize(&w.text)).collect();
+        hay.windows(needle.len()).position(|window| window == needle).map(|i| i..i + needle.len())
+    }
+
+    /// Timeline range covered by a word range, padded into the pauses
+    /// around it.
+    pub fn time_range(
+        &self, words: &[TranscriptWord], range: Range<usize>,
+    ) -> Option<(TimePosition, TimePosition)> {
+        if range.is_empty() || range.end > words.len() {
+            return None;
+        }
+        let (first, last) = (&words[range.start], &words[range.end - 1]);
+        let start = match range.start.checked_sub(1).map(|i| &words[i]) {
+            Some(previous) => {
+                let pause = first.start_ms.saturating_sub(previous.end_ms);
+                first.start_ms - (pause / 2).min(self.max_pad_ms)
+            },
+            None => first.start_ms.saturating_sub(self.max_pad_ms),
+        };
+        let end = match words.get(range.end) {
+            Some(next) => {
+                last.end_ms + (next.start_ms.saturating_sub(last.end_ms) / 2).min(self.max_pad_ms)
+            },
+            None => last.end_ms + self.max_pad_ms,
+        };
+        Some((TimePosition::from_ms(start), TimePosition::from_ms(end.max(start + 1))))
+    }
+
+    /// Ripple-delete word ranges of the stored transcript, returning the
+    /// number of timeline ranges cut.
+    ///
+    /// Overlapping and touching ranges merge into one cut. The deleted words
+    /// leave the transcript and later words move earlier with the timeline.
+    pub fn delete_words(
+        &self, timeline: &mut TimelineManager, metadata: &mut MetadataIndex,
+        ranges: &[Range<usize>],
+    ) -> VideoEditorResult<usize> {
+        let words = metadata.transcript();
+        let mut cuts = Vec::with_capacity(ranges.len());
+        for range in ranges.iter().filter(|r| !r.is_empty()) {
+            let cut = self.time_range(words, range.clone()).ok_or_else(|| {
+                VideoEditorError::timeline(
+                    "delete_words",
+                    format!("Word range {}..{} is outside the transcript", range.start, range.end),
+                )
+            })?;
+            cuts.push(cut);
+        }
+        cuts.sort_by_key(|(start, _)| start.ms);
+        let mut merged: Vec<(TimePosition, TimePosition)> = Vec::with_capacity(cuts.len());
+        for (start, end) in cuts {
+            match merged.last_mut() {
+                Some(last) if start.ms <= last.1.ms => {
+                    last.1 = TimePosition::from_ms(last.1.ms.max(end.ms))
+                },
+                _ => merged.push((start, end)),
+            }
+        }
+
+        for &(start, end) in merged.iter().rev() {
+            timeline.ripple_delete_range(self.track_id, start, end, self.sync)?;
+        }
+
+        let retimed: Vec<TranscriptWord> = words
+            .iter()
+            .filter(|w| !merged.iter().any(|(s, e)| w.start_ms >= s.ms && w.start_ms < e.ms))
+            .map(|w| {
+                let removed: u64 = merged
+                    .iter()
+                    .filter(|(_, e)| e.ms <= w.start_ms)
+                    .map(|(s, e)| e.ms - s.ms)
+                    .sum();
+                TranscriptWord {
+                    start_ms: w.start_ms - removed,
+                    end_ms: w.end_ms - removed,
+                    ..w.clone()
+                }
+            })
+            .collect();
+        metadata.set_transcript_range(0, u64::MAX, retimed);
+        Ok(merged.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{TrackType, timeline::TimelineClip};
+
+    fn transcript() -> MetadataIndex {
+        let mut metadata = MetadataIndex::new();
+        let words = [
+            ("Hello", 1_000, 1_400),
+            ("there.", 1_500, 2_000),
+            ("Um,", 3_000, 3_400),
+            ("scratch", 3_500, 3_900),
+            ("that.", 4_000, 4_400),
+            ("Welcome", 5_000, 5_400),
+            ("in!", 5_500, 5_900),
+        ];
+        metadata.set_transcript_range(
+            0,
+            u64::MAX,
+            words.map(|(text, start, end)| TranscriptWord::new(text, start, end, 0.9)),
+        );
+        metadata
+    }
+
+    #[test]
+    fn test_sentences_and_ranges() {
+        let metadata = transcript();
+        let words = metadata.transcript();
+        assert_eq!(TranscriptEditor::sentences(words), vec![0..2, 2..5, 5..7]);
+        assert_eq!(TranscriptEditor::find_phrase(words, "SCRATCH that"), Some(3..5));
+        assert_eq!(TranscriptEditor::find_phrase(words, "that hello"), None);
+
+        let editor = TranscriptEditor::new(1);
+        // Half the pause on each side, capped at the padding
+        let range = editor.time_range(words, 2..5).expect("test assertion");
+        assert_eq!(range, (TimePosition::from_ms(2_750), TimePosition::from_ms(4_650)));
+        let range = editor.time_range(words, 0..1).expect("test assertion");
+        assert_eq!(range, (TimePosition::from_ms(750), TimePosition::from_ms(1_450)));
+        assert!(editor.time_range(words, 6..8).is_none());
+    }
+
+    #[test]
+    fn test_delete_sentence_ripples_linked_clips() {
+        let mut timeline = TimelineManager::new();
+        let video = timeline.add_track("V1", TrackType::Video);
+        let audio = timeline.add_track("A1", TrackType::Audio);
+        for (clip_id, track_id) in [(1, video), (2, audio)] {
+            let clip = TimelineClip::new(
+                clip_id,
+                1,
+                TimePosition::from_ms(0),
+                TimePosition::from_ms(8_000),
+            );
+            timeline.add_clip(track_id, clip).expect("test assertion");
+        }
+        timeline.link_clips(&[1, 2]).expect("test assertion");
+
+        let mut metadata = transcript();
+        let editor = TranscriptEditor::new(audio);
+        let sentences = TranscriptEditor::sentences(metadata.transcript());
+        let cuts = editor.delete_words(&mut timeline, &mut metadata, &sentences[1..2]);
+        assert_eq!(cuts.expect("test assertion"), 1);
+
+        // 1.9 s cut from both linked tracks
+        for track_id in [video, audio] {
+            let track = timeline.get_track(track_id).expect("test assertion");
+            let length: u64 = track.clips.iter().map(|c| c.duration.ms).sum();
+            assert_eq!(length, 6_100);
+            assert_eq!(track.clips[1].start, TimePosition::from_ms(2_750));
+            assert!(timeline.group_of(track.clips[0].id).is_some());
+        }
+
+        let text: Vec<&str> = metadata.transcript().iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(text, vec!["Hello", "there.", "Welcome", "in!"]);
+        assert_eq!(metadata.transcript()[2].start_ms, 3_100);
+
+        assert!(editor.delete_words(&mut timeline, &mut metadata, &[0..1, 3..9]).is_err());
+        assert_eq!(metadata.transcript().len(), 4);
+    }
+}
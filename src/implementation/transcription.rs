@@ -0,0 +1,378 @@
+//! Speech-to-text hook for automatic captions.
+//!
+//! Recognition engines plug in through [`TranscriptionProvider`]. The
+//! [`TranscriptionOrchestrator`] feeds a provider overlapping chunks of
+//! timeline audio, stitches the timed words back together, lays them out
+//! as caption cues and keeps the word timings in the [`MetadataIndex`] for
+//! text-based editing.
+
+use std::{future::Future, pin::Pin};
+
+use super::{audio_analysis::ClipAudio, captions::CaptionTrack, timeline::TimelineManager};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    metadata::{MetadataIndex, TranscriptWord},
+    types::TimePosition,
+};
+
+/// Boxed future returned by [`TranscriptionProvider::transcribe`].
+pub type TranscriptionFuture<'a> =
+    Pin<Box<dyn Future<Output = VideoEditorResult<Vec<TranscriptWord>>> + Send + 'a>>;
+
+/// Slice of timeline audio handed to a provider.
+#[derive(Debug, Clone, Copy)]
+pub struct AudioChunk<'a> {
+    /// Chunk number, from zero.
+    pub index:       usize,
+    /// Timeline time of the first sample.
+    pub start:       TimePosition,
+    /// Interleaved samples.
+    pub samples:     &'a [f32],
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// Channel count.
+    pub channels:    usize,
+    /// Expected BCP 47 language, if known.
+    pub language:    Option<&'a str>,
+}
+
+impl AudioChunk<'_> {
+    /// Chunk length in milliseconds.
+    pub fn duration_ms(&self) -> u64 {
+        let frames = self.samples.len() / self.channels.max(1);
+        frames as u64 * 1000 / u64::from(self.sample_rate.max(1))
+    }
+}
+
+/// Speech recognition engine.
+pub trait TranscriptionProvider: Send + Sync {
+    /// Provider name for diagnostics.
+    fn name(&self) -> &str;
+
+    /// Recognize the words in a chunk, timed in milliseconds from the
+    /// chunk start.
+    fn transcribe<'a>(&'a self, chunk: AudioChunk<'a>) -> TranscriptionFuture<'a>;
+}
+
+/// Runs a [`TranscriptionProvider`] over timeline audio.
+#[derive(Debug, Clone)]
+pub struct TranscriptionOrchestrator {
+    /// Audio sent to the provider per request.
+    pub chunk_ms:       u64,
+    /// Audio shared by neighbouring chunks so words on a boundary are
+    /// heard whole by one of them.
+    pub overlap_ms:     u64,
+    /// Expected BCP 47 language passed to the provider.
+    pub language:       Option<String>,
+    /// Longest caption line, in characters.
+    pub max_line_chars: usize,
+    /// Longest cue, in milliseconds.
+    pub max_cue_ms:     u64,
+    /// Pause between words that starts a new cue.
+    pub cue_gap_ms:     u64,
+}
+
+impl Default for TranscriptionOrchestrator {
+    fn default() -> Self {
+        Self {
+            chunk_ms:       30_000,
+            overlap_ms:     2_000,
+            language:       None,
+            max_line_chars: 42,
+            max_cue_ms:     6_000,
+            cue_gap_ms:     700,
+        }
+    }
+}
+
+impl TranscriptionOrchestrator {
+    /// Create an orchestrator with broadcast caption defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Split audio starting at timeline time `start` into provider chunks.
+    pub fn chunks<'a>(&'a self, audio: &ClipAudio<'a>, start: TimePosition) -> Vec<AudioChunk<'a>> {
+        let channels = audio.channels.max(1);
+        let frames_per_ms = f64::from(audio.sample_rate) / 1000.0;
+        let frame_at = |ms: u64| (ms as f64 * frames_per_ms).round() as usize;
+        let total = audio.samples.len() / channels;
+        let step = self.chunk_ms.saturating_sub(self.overlap_ms).max(1);
+
+        let mut chunks = Vec::new();
+        let mut offset_ms = 0;
+        while frame_at(offset_ms) < total || chunks.is_empty() {
+            let first = frame_at(offset_ms).min(total);
+            let last = frame_at(offset_ms + self.chunk_ms).min(total);
+            chunks.push(AudioChunk {
+                index: chunks.len(),
+                start: TimePosition::from_ms(start.ms + offset_ms),
+                samples: &audio.samples[first * channels..last * channels],
+                sample_rate: audio.sample_rate,
+                channels,
+                language: self.language.as_deref(),
+            });
+            if last == total {
+                break;
+            }
+            offset_ms += step;
+        }
+        chunks
+    }
+
+    /// Transcribe audio starting at timeline time `start`.
+    ///
+    /// Chunks are sent one at a time. Each overlap is split down the middle
+    /// and a word is kept from the chunk owning its start, so words are
+    /// neither dropped nor doubled at chunk boundaries. Returned words are
+    /// in timeline time.
+    pub async fn transcribe(
+        &self, provider: &dyn TranscriptionProvider, audio: &ClipAudio<'_>, start: TimePosition,
+    ) -> VideoEditorResult<Vec<TranscriptWord>> {
+        let chunks = self.chunks(audio, start);
+        let mut words = Vec::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let owned_from = if i == 0 { 0 } else { chunk.start.ms + self.overlap_ms / 2 };
+            let owned_to =
+                chunks.get(i + 1).map_or(u64::MAX, |next| next.start.ms + self.overlap_ms / 2);
+
+            let recognized = provider.transcribe(*chunk).await.map_err(|e| {
+                VideoEditorError::Asset(format!(
+                    "Transcription by {} failed at {} ms: {e}",
+                    provider.name(),
+                    chunk.start.ms
+                ))
+            })?;
+            words.extend(
+                recognized
+                    .into_iter()
+                    .map(|mut word| {
+                        word.end_ms = word.end_ms.max(word.start_ms) + chunk.start.ms;
+                        word.start_ms += chunk.start.ms;
+                        word
+                    })
+                    .filter(|w| (owned_from..owned_to).contains(&w.start_ms)),
+            );
+        }
+        words.sort_by_key(|w| w.start_ms);
+        Ok(words)
+    }
+
+    /// Lay words out as cues on a caption track, returning the cue count.
+    ///
+    /// A cue ends at a pause of [`cue_gap_ms`](Self::cue_gap_ms), after a
+    /// sentence, when it would run longer than
+    /// [`max_cue_ms`](Self::max_cue_ms), or when its text no longer wraps
+    /// into two lines.
+    pub fn build_cues(
+        &self, words: &[TranscriptWord], captions: &mut CaptionTrack,
+    ) -> VideoEditorResult<usize> {
+        let mut count = 0;
+        let mut cue: Vec<&TranscriptWord> = Vec::new();
+        for word in words {
+            if let (Some(first), Some(last)) = (cue.first(), cue.last()) {
+                let sentence_end = last.text.ends_with(['.', '?', '!']);
+                let paused = word.start_ms.saturating_sub(last.end_ms) >= self.cue_gap_ms;
+                let too_long = word.end_ms.saturating_sub(first.start_ms) > self.max_cue_ms;
+                let mut text: Vec<&str> = cue.iter().map(|w| w.text.as_str()).collect();
+                text.push(&word.text);
+                if sentence_end || paused || too_long || self.wrap(&text).len() > 2 {
+                    self.emit(&cue, captions)?;
+                    count += 1;
+                    cue.clear();
+                }
+            }
+            cue.push(word);
+        }
+        if !cue.is_empty() {
+            self.emit(&cue, captions)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn emit(&self, cue: &[&TranscriptWord], captions: &mut CaptionTrack) -> VideoEditorResult<()> {
+        let text: Vec<&str> = cue.iter().map(|w| w.text.as_str()).collect();
+        let start = cue[0].start_ms;
+        let end = cue[cue.len() - 1].end_ms.max(start + 1);
+        captions.add_cue(
+            TimePosition::from_ms(start),
+            TimePosition::from_ms(end),
+            self.wrap(&text).join("\n"),
+        )?;
+        Ok(())
+    }
+
+    /// Greedy word wrap at `max_line_chars`.
+    fn wrap(&self, words: &[&str]) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        for word in words {
+            match lines.last_mut() {
+                Some(line)
+                    if line.chars().count() + 1 + word.chars().count() <= self.max_line_chars =>
+                {
+                    line.push(' ');
+                    line.push_str(word);
+                },
+                _ => lines.push((*word).to_string()),
+            }
+        }
+        lines
+    }
+
+    /// Transcribe timeline audio into a subtitle track's captions and the
+    /// metadata transcript, returning the number of cues added.
+    ///
+    /// `audio` is the timeline mix starting at `start`. Transcript words in
+    /// the transcribed span are replaced; existing cues are kept.
+    pub async fn run(
+        &self, provider: &dyn TranscriptionProvider, audio: &ClipAudio<'_>, start: TimePosition,
+        timeline: &mut TimelineManager, caption_track_id: u64, metadata: &mut MetadataIndex,
+    ) -> VideoEditorResult<usize> {
+        if timeline.caption_track(caption_track_id).is_none() {
+            return Err(VideoEditorError::timeline(
+                "transcribe",
+                format!("Track {caption_track_id} has no captions"),
+            ));
+        }
+        let words = self.transcribe(provider, audio, start).await?;
+
+        let captions = timeline
+            .caption_track_mut(caption_track_id)
+            .ok_or_else(|| VideoEditorError::timeline("transcribe", "Caption track removed"))?;
+        if captions.language().is_none() {
+            captions.set_language(self.language.clone());
+        }
+        let count = self.build_cues(&words, captions)?;
+
+        let channels = audio.channels.max(1);
+        let duration_ms =
+            (audio.samples.len() / channels) as u64 * 1000 / u64::from(audio.sample_rate.max(1));
+        metadata.set_transcript_range(start.ms, start.ms + duration_ms, words);
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    use super::*;
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Hears a fixed script: every word audible within the chunk.
+    struct ScriptedProvider {
+        script:   Vec<TranscriptWord>,
+        requests: Mutex<Vec<(u64, u64)>>,
+    }
+
+    impl TranscriptionProvider for ScriptedProvider {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+
+        fn transcribe<'a>(&'a self, chunk: AudioChunk<'a>) -> TranscriptionFuture<'a> {
+            Box::pin(async move {
+                let (from, to) = (chunk.start.ms, chunk.start.ms + chunk.duration_ms());
+                self.requests.lock().expect("test assertion").push((from, to));
+                if chunk.language != Some("en") {
+                    return Err(VideoEditorError::Asset("unsupported language".into()));
+                }
+                Ok(self
+                    .script
+                    .iter()
+                    .filter(|w| w.start_ms >= from && w.end_ms <= to)
+                    .map(|w| TranscriptWord {
+                        start_ms: w.start_ms - from,
+                        end_ms: w.end_ms - from,
+                        ..w.clone()
+                    })
+                    .collect())
+            })
+        }
+    }
+
+    fn script() -> Vec<TranscriptWord> {
+        let text = "Welcome back everyone. Today we look at how chunked transcription keeps \
+                    every single word exactly once across boundaries";
+        text.split(' ')
+            .enumerate()
+            .map(|(i, word)| {
+                let start = 10_000 + i as u64 * 450;
+                TranscriptWord::new(word, start, start + 400, 0.9)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chunks_cover_audio_with_overlap() {
+        let samples = vec![0.0; 1_000 * 2 * 25];
+        let audio = ClipAudio { samples: &samples, sample_rate: 1_000, channels: 2 };
+        let orchestrator = TranscriptionOrchestrator { chunk_ms: 10_000, ..Default::default() };
+        let chunks = orchestrator.chunks(&audio, TimePosition::from_ms(5_000));
+        let spans: Vec<(u64, u64)> =
+            chunks.iter().map(|c| (c.start.ms, c.start.ms + c.duration_ms())).collect();
+        assert_eq!(spans, vec![(5_000, 15_000), (13_000, 23_000), (21_000, 30_000)]);
+    }
+
+    #[test]
+    fn test_transcribe_to_captions_and_metadata() {
+        let provider = ScriptedProvider { script: script(), requests: Mutex::new(Vec::new()) };
+        let samples = vec![0.0; 16_000 * 12];
+        let audio = ClipAudio { samples: &samples, sample_rate: 16_000, channels: 1 };
+        let start = TimePosition::from_ms(8_000);
+        let orchestrator = TranscriptionOrchestrator {
+            chunk_ms: 4_000,
+            overlap_ms: 1_000,
+            language: Some("en".into()),
+            ..Default::default()
+        };
+
+        let mut timeline = TimelineManager::new();
+        let subtitles = timeline.add_caption_track("Auto captions");
+        let mut metadata = MetadataIndex::new();
+        let run =
+            orchestrator.run(&provider, &audio, start, &mut timeline, subtitles, &mut metadata);
+        let cues = block_on(run).expect("test assertion");
+        assert_eq!(provider.requests.lock().expect("test assertion").len(), 4);
+
+        // Every word once, in order, in timeline time
+        let transcript: Vec<&str> = metadata.transcript().iter().map(|w| w.text.as_str()).collect();
+        let expected = script();
+        assert_eq!(transcript, expected.iter().map(|w| w.text.as_str()).collect::<Vec<_>>());
+        assert_eq!(metadata.transcript()[0].start_ms, 10_000);
+        assert_eq!(metadata.transcript_between(10_500, 11_000).len(), 2);
+
+        let captions = timeline.caption_track(subtitles).expect("test assertion");
+        assert_eq!(captions.language(), Some("en"));
+        assert_eq!(captions.cues().len(), cues);
+        assert_eq!(captions.cues()[0].text, "Welcome back everyone.");
+        assert!(captions.cues().iter().all(|c| c.text.lines().count() <= 2
+            && c.text.lines().all(|l| l.chars().count() <= 42)
+            && c.end.ms - c.start.ms <= 6_000));
+
+        // Provider failures surface with context
+        let french = TranscriptionOrchestrator { language: Some("fr".into()), ..orchestrator };
+        let error = block_on(french.transcribe(&provider, &audio, start));
+        assert!(error.expect_err("test assertion").to_string().contains("scripted"));
+    }
+}
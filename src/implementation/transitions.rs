@@ -4,6 +4,7 @@
 //! Features: CrossFade, Wipe, Dissolve, Push, Slide, Zoom transitions
 //! with configurable duration, easing, and parameters.
 
+use super::id_index::IdVec;
 use crate::{
     errors::{VideoEditorError, VideoEditorResult},
     types::TimePosition,
@@ -322,10 +323,280 @@ pub struct TransitionParameters {
     pub rotation:        f64,
     /// Custom shader ID (if applicable).
     pub custom_shader:   Option<u64>,
+    /// Custom shader uniform values (overrides shader defaults).
+    pub shader_uniforms: Vec<(String, [f32; 4])>,
     /// Audio crossfade enabled.
     pub audio_crossfade: bool,
 }
 
+impl TransitionParameters {
+    /// Sets a custom shader uniform value.
+    pub fn set_shader_uniform(&mut self, name: impl Into<String>, value: [f32; 4]) {
+        let name = name.into();
+        if let Some(entry) = self.shader_uniforms.iter_mut().find(|(n, _)| *n == name) {
+            entry.1 = value;
+        } else {
+            self.shader_uniforms.push((name, value));
+        }
+    }
+
+    /// Gets a custom shader uniform value.
+    #[must_use]
+    pub fn shader_uniform(&self, name: &str) -> Option<[f32; 4]> {
+        self.shader_uniforms.iter().find(|(n, _)| n == name).map(|(_, v)| *v)
+    }
+}
+
+/// Source language of a custom transition shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShaderLanguage {
+    /// WebGPU shading language.
+    Wgsl,
+    /// OpenGL shading language.
+    Glsl,
+}
+
+impl ShaderLanguage {
+    /// Returns the marker every fragment shader source must contain.
+    #[must_use]
+    pub const fn entry_point_marker(&self) -> &'static str {
+        match self {
+            Self::Wgsl => "@fragment",
+            Self::Glsl => "void main",
+        }
+    }
+}
+
+/// Type of a declared shader uniform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShaderUniformType {
+    /// Single float.
+    Float,
+    /// 2D vector.
+    Vec2,
+    /// 3D vector.
+    Vec3,
+    /// 4D vector.
+    Vec4,
+    /// RGBA color.
+    Color,
+    /// Boolean (0.0 or 1.0).
+    Bool,
+}
+
+impl ShaderUniformType {
+    /// Returns the number of used components.
+    #[must_use]
+    pub const fn components(&self) -> usize {
+        match self {
+            Self::Float | Self::Bool => 1,
+            Self::Vec2 => 2,
+            Self::Vec3 => 3,
+            Self::Vec4 | Self::Color => 4,
+        }
+    }
+}
+
+/// Declared uniform of a custom transition shader (its parameter schema).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderUniform {
+    /// Uniform name as used in the shader source.
+    pub name:         String,
+    /// Uniform type.
+    pub uniform_type: ShaderUniformType,
+    /// Default value.
+    pub default:      [f32; 4],
+    /// Minimum component value.
+    pub min:          f32,
+    /// Maximum component value.
+    pub max:          f32,
+}
+
+impl ShaderUniform {
+    /// Creates a float uniform with a range.
+    #[must_use]
+    pub fn float(name: impl Into<String>, default: f32, min: f32, max: f32) -> Self {
+        Self {
+            name: name.into(),
+            uniform_type: ShaderUniformType::Float,
+            default: [default, 0.0, 0.0, 0.0],
+            min,
+            max,
+        }
+    }
+
+    /// Creates a color uniform.
+    #[must_use]
+    pub fn color(name: impl Into<String>, default: [f32; 4]) -> Self {
+        Self {
+            name: name.into(),
+            uniform_type: ShaderUniformType::Color,
+            default,
+            min: 0.0,
+            max: 1.0,
+        }
+    }
+
+    /// Clamps a value to the declared range, zeroing unused components.
+    #[must_use]
+    pub fn clamp(&self, value: [f32; 4]) -> [f32; 4] {
+        let mut out = [0.0; 4];
+        for (i, component) in out.iter_mut().enumerate().take(self.uniform_type.components()) {
+            *component = value[i].clamp(self.min, self.max);
+        }
+        out
+    }
+}
+
+/// A registered custom transition shader.
+#[derive(Debug, Clone)]
+pub struct TransitionShader {
+    /// Shader ID (used by `TransitionType::CustomShader`).
+    pub id:       u64,
+    /// Display name.
+    pub name:     String,
+    /// Source language.
+    pub language: ShaderLanguage,
+    /// Shader source.
+    pub source:   String,
+    /// Declared uniforms.
+    pub uniforms: Vec<ShaderUniform>,
+}
+
+/// Registry of user-supplied transition shaders.
+///
+/// Shaders receive the outgoing and incoming frames plus the eased progress
+/// from the renderer; the names below are reserved for those bindings.
+#[derive(Debug, Default)]
+pub struct TransitionShaderRegistry {
+    /// Registered shaders.
+    shaders: Vec<TransitionShader>,
+    /// Next shader ID.
+    next_id: u64,
+}
+
+impl TransitionShaderRegistry {
+    /// Uniform names provided by the renderer.
+    pub const RESERVED_UNIFORMS: [&'static str; 4] =
+        ["progress", "from_frame", "to_frame", "resolution"];
+
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { shaders: Vec::new(), next_id: 1 }
+    }
+
+    /// Validates and registers a shader, returning its ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Effect` if the source is empty, lacks a
+    /// fragment entry point, or a uniform is duplicated, reserved, missing
+    /// from the source or has an invalid range.
+    pub fn register(
+        &mut self, name: impl Into<String>, language: ShaderLanguage, source: impl Into<String>,
+        uniforms: Vec<ShaderUniform>,
+    ) -> VideoEditorResult<u64> {
+        let name = name.into();
+        let source = source.into();
+        Self::validate(&name, language, &source, &uniforms)?;
+
+        let id = self.next_id.max(1);
+        self.next_id = id + 1;
+        self.shaders.push(TransitionShader { id, name, language, source, uniforms });
+        Ok(id)
+    }
+
+    fn validate(
+        name: &str, language: ShaderLanguage, source: &str, uniforms: &[ShaderUniform],
+    ) -> VideoEditorResult<()> {
+        let fail = |reason: String| {
+            Err(VideoEditorError::Effect(format!("Invalid transition shader '{name}': {reason}")))
+        };
+
+        if source.trim().is_empty() {
+            return fail("empty source".into());
+        }
+        if !source.contains(language.entry_point_marker()) {
+            return fail(format!("missing entry point `{}`", language.entry_point_marker()));
+        }
+        for (i, uniform) in uniforms.iter().enumerate() {
+            if Self::RESERVED_UNIFORMS.contains(&uniform.name.as_str()) {
+                return fail(format!("uniform `{}` is reserved", uniform.name));
+            }
+            if uniforms[..i].iter().any(|u| u.name == uniform.name) {
+                return fail(format!("duplicate uniform `{}`", uniform.name));
+            }
+            if !source.contains(uniform.name.as_str()) {
+                return fail(format!("uniform `{}` not used in source", uniform.name));
+            }
+            if uniform.min > uniform.max {
+                return fail(format!("uniform `{}` has min > max", uniform.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes a shader.
+    pub fn unregister(&mut self, id: u64) -> bool {
+        let before = self.shaders.len();
+        self.shaders.retain(|s| s.id != id);
+        self.shaders.len() != before
+    }
+
+    /// Gets a shader by ID.
+    #[must_use]
+    pub fn get(&self, id: u64) -> Option<&TransitionShader> {
+        self.shaders.iter().find(|s| s.id == id)
+    }
+
+    /// Returns all registered shaders.
+    #[must_use]
+    pub fn shaders(&self) -> &[TransitionShader] {
+        &self.shaders
+    }
+
+    /// Returns transition parameters populated with the shader's defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Effect` if the shader is not registered.
+    pub fn default_parameters(&self, id: u64) -> VideoEditorResult<TransitionParameters> {
+        let shader = self.require(id)?;
+        let mut parameters =
+            TransitionParameters { custom_shader: Some(id), ..TransitionParameters::default() };
+        for uniform in &shader.uniforms {
+            parameters.set_shader_uniform(uniform.name.clone(), uniform.default);
+        }
+        Ok(parameters)
+    }
+
+    /// Resolves final uniform values for a render: declared defaults
+    /// overridden by the transition parameters, clamped to range.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Effect` if the shader is not registered.
+    pub fn resolve_uniforms(
+        &self, id: u64, parameters: &TransitionParameters,
+    ) -> VideoEditorResult<Vec<(String, [f32; 4])>> {
+        let shader = self.require(id)?;
+        Ok(shader
+            .uniforms
+            .iter()
+            .map(|u| {
+                let value = parameters.shader_uniform(&u.name).unwrap_or(u.default);
+                (u.name.clone(), u.clamp(value))
+            })
+            .collect())
+    }
+
+    fn require(&self, id: u64) -> VideoEditorResult<&TransitionShader> {
+        self.get(id)
+            .ok_or_else(|| VideoEditorError::Effect(format!("Transition shader not found: {id}")))
+    }
+}
+
 impl Transition {
     /// Creates a new transition.
     #[must_use]
@@ -451,8 +722,8 @@ impl Transition {
 
 /// Manager for video transitions.
 pub struct TransitionManager {
-    /// All transitions in the project.
-    transitions:      Vec<TransitionPlacement>,
+    /// All transitions in the project, in creation order.
+    transitions:      IdVec<TransitionId, TransitionPlacement>,
     /// Next transition ID.
     next_id:          u64,
     /// Default transition type.
@@ -461,6 +732,8 @@ pub struct TransitionManager {
     default_duration: TimePosition,
     /// Preset transitions.
     presets:          Vec<TransitionPreset>,
+    /// Registered custom transition shaders.
+    shaders:          TransitionShaderRegistry,
 }
 
 /// A transition placed between two clips.
@@ -498,11 +771,12 @@ impl TransitionManager {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            transitions:      Vec::new(),
+            transitions:      IdVec::new(),
             next_id:          1,
             default_type:     TransitionType::CrossFade,
             default_duration: TimePosition::from_ms(500),
             presets:          Self::create_default_presets(),
+            shaders:          TransitionShaderRegistry::new(),
         }
     }
 
@@ -567,7 +841,7 @@ impl TransitionManager {
         let placement =
             TransitionPlacement { transition, track_id, clip_a_id, clip_b_id, start_time };
 
-        self.transitions.push(placement);
+        self.transitions.push(id, placement);
         id
     }
 
@@ -583,37 +857,41 @@ impl TransitionManager {
             .ok_or_else(|| VideoEditorError::Effect(format!("Preset not found: {preset_name}")))?
             .clone();
 
+        Ok(self.add_with_preset(track_id, clip_a_id, clip_b_id, start_time, &preset))
+    }
+
+    /// Adds a transition configured by a preset that need not be
+    /// registered with the manager.
+    pub fn add_with_preset(
+        &mut self, track_id: u64, clip_a_id: u64, clip_b_id: u64, start_time: TimePosition,
+        preset: &TransitionPreset,
+    ) -> TransitionId {
         let id = self.next_id();
         let mut transition = Transition::new(id, preset.transition_type, preset.duration);
         transition.set_easing(preset.easing);
-        *transition.parameters_mut() = preset.parameters;
+        *transition.parameters_mut() = preset.parameters.clone();
 
         let placement =
             TransitionPlacement { transition, track_id, clip_a_id, clip_b_id, start_time };
 
-        self.transitions.push(placement);
-        Ok(id)
+        self.transitions.push(id, placement);
+        id
     }
 
     /// Removes a transition.
     pub fn remove_transition(&mut self, id: TransitionId) -> bool {
-        if let Some(pos) = self.transitions.iter().position(|t| t.transition.id() == id) {
-            self.transitions.remove(pos);
-            true
-        } else {
-            false
-        }
+        self.transitions.remove(id).is_some()
     }
 
     /// Gets a transition by ID.
     #[must_use]
     pub fn get_transition(&self, id: TransitionId) -> Option<&TransitionPlacement> {
-        self.transitions.iter().find(|t| t.transition.id() == id)
+        self.transitions.get(id)
     }
 
     /// Gets a mutable transition by ID.
     pub fn get_transition_mut(&mut self, id: TransitionId) -> Option<&mut TransitionPlacement> {
-        self.transitions.iter_mut().find(|t| t.transition.id() == id)
+        self.transitions.get_mut(id)
     }
 
     /// Gets all transitions for a track.
@@ -636,15 +914,14 @@ impl TransitionManager {
 
     /// Updates all transitions for the current time.
     pub fn update_all(&mut self, current_time: TimePosition) {
-        for placement in &mut self.transitions {
+        for placement in self.transitions.iter_mut() {
             placement.transition.update(current_time, placement.start_time);
         }
     }
 
-    /// Returns all transition placements.
-    #[must_use]
-    pub fn all_transitions(&self) -> &[TransitionPlacement] {
-        &self.transitions
+    /// Returns all transition placements, in creation order.
+    pub fn all_transitions(&self) -> impl DoubleEndedIterator<Item = &TransitionPlacement> + Clone {
+        self.transitions.iter()
     }
 
     /// Returns available presets.
@@ -658,6 +935,41 @@ impl TransitionManager {
         self.presets.push(preset);
     }
 
+    /// Returns the custom shader registry.
+    #[must_use]
+    pub fn shaders(&self) -> &TransitionShaderRegistry {
+        &self.shaders
+    }
+
+    /// Returns the mutable custom shader registry.
+    pub fn shaders_mut(&mut self) -> &mut TransitionShaderRegistry {
+        &mut self.shaders
+    }
+
+    /// Adds a custom shader transition with the shader's default parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Effect` if the shader is not registered.
+    pub fn add_shader_transition(
+        &mut self, track_id: u64, clip_a_id: u64, clip_b_id: u64, start_time: TimePosition,
+        shader_id: u64, duration: Option<TimePosition>,
+    ) -> VideoEditorResult<TransitionId> {
+        let parameters = self.shaders.default_parameters(shader_id)?;
+        let id = self.add_transition(
+            track_id,
+            clip_a_id,
+            clip_b_id,
+            start_time,
+            Some(TransitionType::CustomShader(shader_id)),
+            duration,
+        );
+        if let Some(placement) = self.get_transition_mut(id) {
+            *placement.transition.parameters_mut() = parameters;
+        }
+        Ok(id)
+    }
+
     /// Sets the default transition type.
     pub fn set_default_type(&mut self, transition_type: TransitionType) {
         self.default_type = transition_type;
@@ -678,6 +990,7 @@ impl Default for TransitionManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::implementation::gpu_pipeline::GpuPipeline;
 
     #[test]
     fn test_transition_creation() {
@@ -712,6 +1025,80 @@ mod tests {
         assert!(manager.get_transition(id).is_none());
     }
 
+    const WGSL_SOURCE: &str = "@fragment fn main() { let edge = softness * progress; }";
+
+    #[test]
+    fn test_shader_registration_validation() {
+        let mut registry = TransitionShaderRegistry::new();
+        let softness = || vec![ShaderUniform::float("softness", 0.2, 0.0, 1.0)];
+
+        assert!(registry.register("Soft", ShaderLanguage::Wgsl, WGSL_SOURCE, softness()).is_ok());
+        assert!(registry.register("Empty", ShaderLanguage::Wgsl, " ", Vec::new()).is_err());
+        assert!(registry.register("Glsl", ShaderLanguage::Glsl, WGSL_SOURCE, softness()).is_err());
+        assert!(
+            registry
+                .register(
+                    "Reserved",
+                    ShaderLanguage::Wgsl,
+                    WGSL_SOURCE,
+                    vec![ShaderUniform::float("progress", 0.0, 0.0, 1.0)]
+                )
+                .is_err()
+        );
+        assert!(
+            registry
+                .register(
+                    "Unused",
+                    ShaderLanguage::Wgsl,
+                    WGSL_SOURCE,
+                    vec![ShaderUniform::float("angle", 0.0, 0.0, 1.0)]
+                )
+                .is_err()
+        );
+        assert_eq!(registry.shaders().len(), 1);
+    }
+
+    #[test]
+    fn test_shader_transition_parameters() {
+        let mut manager = TransitionManager::new();
+        let shader = manager
+            .shaders_mut()
+            .register(
+                "Soft",
+                ShaderLanguage::Wgsl,
+                WGSL_SOURCE,
+                vec![ShaderUniform::float("softness", 0.2, 0.0, 1.0)],
+            )
+            .expect("test assertion");
+
+        let id = manager
+            .add_shader_transition(1, 1, 2, TimePosition::from_ms(0), shader, None)
+            .expect("test assertion");
+        let placement = manager.get_transition_mut(id).expect("test assertion");
+        assert_eq!(placement.transition.parameters().custom_shader, Some(shader));
+
+        placement.transition.parameters_mut().set_shader_uniform("softness", [3.0, 0.0, 0.0, 0.0]);
+        let parameters = placement.transition.parameters().clone();
+        let uniforms =
+            manager.shaders().resolve_uniforms(shader, &parameters).expect("test assertion");
+        assert_eq!(uniforms, vec![("softness".to_string(), [1.0, 0.0, 0.0, 0.0])]);
+
+        assert!(
+            manager.add_shader_transition(1, 1, 2, TimePosition::from_ms(0), 99, None).is_err()
+        );
+
+        let mut gpu = GpuPipeline::new(true);
+        let transition = &manager.get_transition(id).expect("test assertion").transition;
+        assert!(gpu.dispatch_transition(manager.shaders(), transition).is_err());
+        gpu.initialize();
+        let dispatch = gpu
+            .dispatch_transition(manager.shaders(), transition)
+            .expect("test assertion")
+            .expect("test assertion");
+        assert_eq!(dispatch.shader_id, shader);
+        assert_eq!(dispatch.uniforms[0].1[0], 1.0);
+    }
+
     #[test]
     fn test_transition_progress() {
         let mut transition =
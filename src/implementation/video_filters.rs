@@ -0,0 +1,711 @@
+//! Denoise, sharpen and deinterlace filters.
+//!
+//! CPU reference implementations of the `Denoise`, `Sharpen` and
+//! `Deinterlace` clip effects on RGBA8 frames. The GPU kernels dispatched
+//! through `GpuPipeline::dispatch_filter` follow the same math, and each
+//! [`EffectQuality`] tier selects the same kernel sizes on both backends.
+//! Alpha passes through unchanged.
+
+use super::effects::{EffectQuality, EffectType, VideoEffect};
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    trace::{self, TracePhase},
+    types::FieldOrder,
+};
+
+/// Luma difference below which a pixel counts as static for temporal
+/// denoising.
+const MOTION_THRESHOLD: f32 = 24.0;
+
+/// Largest share of the previous frame blended into a static pixel.
+const MAX_TEMPORAL_BLEND: f32 = 0.8;
+
+/// Temporal and spatial noise reduction.
+///
+/// The spatial pass is an edge-preserving bilateral filter. The temporal
+/// pass blends in the previous output frame where the image is static,
+/// backing off with motion so moving edges do not smear. Draft quality
+/// skips the temporal pass.
+#[derive(Debug, Clone)]
+pub struct VideoDenoiser {
+    /// Spatial strength (0.0 - 1.0).
+    pub spatial:  f32,
+    /// Temporal strength (0.0 - 1.0).
+    pub temporal: f32,
+    /// Kernel quality.
+    pub quality:  EffectQuality,
+    previous:     Option<(u32, u32, Vec<u8>)>,
+}
+
+impl VideoDenoiser {
+    /// Default spatial strength.
+    pub const DEFAULT_SPATIAL: f32 = 0.5;
+    /// Default temporal strength.
+    pub const DEFAULT_TEMPORAL: f32 = 0.5;
+
+    /// Create a denoiser.
+    pub fn new(spatial: f32, temporal: f32, quality: EffectQuality) -> Self {
+        Self {
+            spatial: spatial.clamp(0.0, 1.0),
+            temporal: temporal.clamp(0.0, 1.0),
+            quality,
+            previous: None,
+        }
+    }
+
+    /// Create a denoiser from a `Denoise` effect's `spatial`, `temporal`
+    /// and `quality` parameters.
+    pub fn from_effect(effect: &VideoEffect) -> VideoEditorResult<Self> {
+        if effect.effect_type != EffectType::Denoise {
+            return Err(VideoEditorError::Effect(format!(
+                "Effect {} is not a denoiser",
+                effect.id
+            )));
+        }
+        Ok(Self::new(
+            effect.parameter("spatial").map_or(Self::DEFAULT_SPATIAL, |v| v as f32),
+            effect.parameter("temporal").map_or(Self::DEFAULT_TEMPORAL, |v| v as f32),
+            effect.quality(),
+        ))
+    }
+
+    /// Update the strengths and quality from a (possibly animated) effect,
+    /// keeping the temporal history.
+    pub fn update(&mut self, effect: &VideoEffect) -> VideoEditorResult<()> {
+        let previous = self.previous.take();
+        *self = Self { previous, ..Self::from_effect(effect)? };
+        Ok(())
+    }
+
+    /// Spatial filter radius in pixels.
+    pub fn radius(&self) -> usize {
+        match self.quality {
+            EffectQuality::Draft => 1,
+            EffectQuality::Good => 2,
+            EffectQuality::Best => 3,
+        }
+    }
+
+    /// Forget the previous frame, e.g. after a seek or cut.
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+
+    /// Denoise the next frame of a sequence.
+    pub fn process(
+        &mut self, pixels: &[u8], width: u32, height: u32,
+    ) -> VideoEditorResult<Vec<u8>> {
+        let mut out = vec![0; pixels.len()];
+        self.process_into(pixels, width, height, &mut out)?;
+        Ok(out)
+    }
+
+    /// Denoise the next frame of a sequence into `out`, a frame of the
+    /// same size such as a pooled buffer.
+    pub fn process_into(
+        &mut self, pixels: &[u8], width: u32, height: u32, out: &mut [u8],
+    ) -> VideoEditorResult<()> {
+        let _span = trace::span(TracePhase::Effect, "denoise");
+        check_frame(pixels, width, height)?;
+        check_frame(out, width, height)?;
+        let (w, h) = (width as usize, height as usize);
+        if self.spatial > 0.0 {
+            self.bilateral(pixels, w, h, out);
+        } else {
+            out.copy_from_slice(pixels);
+        }
+
+        if self.temporal > 0.0
+            && self.quality != EffectQuality::Draft
+            && let Some((pw, ph, previous)) = &self.previous
+            && (*pw, *ph) == (width, height)
+        {
+            // Motion is measured on the input against the previous output
+            let window = if self.quality == EffectQuality::Best { 1 } else { 0 };
+            for y in 0..h {
+                for x in 0..w {
+                    let mut diff = 0.0;
+                    let mut count = 0.0;
+                    for ny in y.saturating_sub(window)..=(y + window).min(h - 1) {
+                        for nx in x.saturating_sub(window)..=(x + window).min(w - 1) {
+                            let i = (ny * w + nx) * 4;
+                            diff += (luma(&pixels[i..]) - luma(&previous[i..])).abs();
+                            count += 1.0;
+                        }
+                    }
+                    let still = (1.0 - diff / count / MOTION_THRESHOLD).max(0.0);
+                    let blend = self.temporal * MAX_TEMPORAL_BLEND * still;
+                    let i = (y * w + x) * 4;
+                    for c in 0..3 {
+                        let value = f32::from(out[i + c]) * (1.0 - blend)
+                            + f32::from(previous[i + c]) * blend;
+                        out[i + c] = value.round() as u8;
+                    }
+                }
+            }
+        }
+
+        // Keep the history in the same allocation from frame to frame
+        let (pw, ph, previous) = self.previous.get_or_insert_with(Default::default);
+        (*pw, *ph) = (width, height);
+        previous.clear();
+        previous.extend_from_slice(out);
+        Ok(())
+    }
+
+    /// Edge-preserving blur weighted by distance and luma difference.
+    fn bilateral(&self, pixels: &[u8], w: usize, h: usize, out: &mut [u8]) {
+        let radius = self.radius();
+        let sigma_space = radius as f32 * 0.75 + 0.25;
+        let sigma_range = 4.0 + 36.0 * self.spatial;
+        let space: Vec<f32> = (0..=radius)
+            .map(|d| (-((d * d) as f32) / (2.0 * sigma_space * sigma_space)).exp())
+            .collect();
+        for y in 0..h {
+            for x in 0..w {
+                let i = (y * w + x) * 4;
+                let center = luma(&pixels[i..]);
+                let mut sum = [0.0f32; 3];
+                let mut total = 0.0;
+                for ny in y.saturating_sub(radius)..=(y + radius).min(h - 1) {
+                    for nx in x.saturating_sub(radius)..=(x + radius).min(w - 1) {
+                        let j = (ny * w + nx) * 4;
+                        let range = (luma(&pixels[j..]) - center) / sigma_range;
+                        let weight = space[ny.abs_diff(y)]
+                            * space[nx.abs_diff(x)]
+                            * (-0.5 * range * range).exp();
+                        for (c, s) in sum.iter_mut().enumerate() {
+                            *s += f32::from(pixels[j + c]) * weight;
+                        }
+                        total += weight;
+                    }
+                }
+                for (c, s) in sum.iter().enumerate() {
+                    out[i + c] = (s / total).round().clamp(0.0, 255.0) as u8;
+                }
+                out[i + 3] = pixels[i + 3];
+            }
+        }
+    }
+}
+
+/// Unsharp mask sharpening.
+///
+/// Adds back `amount` times the difference between the frame and a blurred
+/// copy, ignoring differences below `threshold` so flat areas and grain
+/// are left alone. Draft quality blurs with a box filter, Good with a
+/// Gaussian, and Best with a Gaussian applied to luma only, which avoids
+/// color fringes on saturated edges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnsharpMask {
+    /// Sharpening strength (0.0 - 5.0).
+    pub amount:    f32,
+    /// Blur radius in pixels (0.5 - 8.0).
+    pub radius:    f32,
+    /// Smallest difference sharpened, in 0-255 levels.
+    pub threshold: f32,
+    /// Kernel quality.
+    pub quality:   EffectQuality,
+}
+
+impl UnsharpMask {
+    /// Default strength.
+    pub const DEFAULT_AMOUNT: f32 = 0.5;
+    /// Default blur radius.
+    pub const DEFAULT_RADIUS: f32 = 1.0;
+    /// Default threshold.
+    pub const DEFAULT_THRESHOLD: f32 = 2.0;
+
+    /// Create an unsharp mask.
+    pub fn new(amount: f32, radius: f32, threshold: f32, quality: EffectQuality) -> Self {
+        Self {
+            amount: amount.clamp(0.0, 5.0),
+            radius: radius.clamp(0.5, 8.0),
+            threshold: threshold.max(0.0),
+            quality,
+        }
+    }
+
+    /// Create an unsharp mask from a `Sharpen` effect's `amount`, `radius`,
+    /// `threshold` and `quality` parameters.
+    pub fn from_effect(effect: &VideoEffect) -> VideoEditorResult<Self> {
+        if effect.effect_type != EffectType::Sharpen {
+            return Err(VideoEditorError::Effect(format!(
+                "Effect {} is not a sharpen effect",
+                effect.id
+            )));
+        }
+        Ok(Self::new(
+            effect.parameter("amount").map_or(Self::DEFAULT_AMOUNT, |v| v as f32),
+            effect.parameter("radius").map_or(Self::DEFAULT_RADIUS, |v| v as f32),
+            effect.parameter("threshold").map_or(Self::DEFAULT_THRESHOLD, |v| v as f32),
+            effect.quality(),
+        ))
+    }
+
+    /// Sharpen a frame.
+    pub fn apply(&self, pixels: &[u8], width: u32, height: u32) -> VideoEditorResult<Vec<u8>> {
+        let mut out = vec![0; pixels.len()];
+        self.apply_into(pixels, width, height, &mut out)?;
+        Ok(out)
+    }
+
+    /// Sharpen a frame into `out`, a frame of the same size such as a
+    /// pooled buffer.
+    pub fn apply_into(
+        &self, pixels: &[u8], width: u32, height: u32, out: &mut [u8],
+    ) -> VideoEditorResult<()> {
+        let _span = trace::span(TracePhase::Effect, "unsharp");
+        check_frame(pixels, width, height)?;
+        check_frame(out, width, height)?;
+        let (w, h) = (width as usize, height as usize);
+        let kernel = match self.quality {
+            EffectQuality::Draft => {
+                let r = self.radius.round().max(1.0) as usize;
+                vec![1.0 / (2 * r + 1) as f32; 2 * r + 1]
+            },
+            EffectQuality::Good | EffectQuality::Best => gaussian(self.radius),
+        };
+        let blurred = blur(pixels, w, h, &kernel);
+
+        let sharpen = |value: f32, detail: f32| {
+            if detail.abs() < self.threshold { value } else { value + self.amount * detail }
+        };
+        for (dst, (src, soft)) in
+            out.chunks_exact_mut(4).zip(pixels.chunks_exact(4).zip(blurred.chunks_exact(4)))
+        {
+            if self.quality == EffectQuality::Best {
+                let detail = luma(src) - luma(soft);
+                for c in 0..3 {
+                    dst[c] = sharpen(f32::from(src[c]), detail).round().clamp(0.0, 255.0) as u8;
+                }
+            } else {
+                for c in 0..3 {
+                    let detail = f32::from(src[c]) - f32::from(soft[c]);
+                    dst[c] = sharpen(f32::from(src[c]), detail).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+            dst[3] = src[3];
+        }
+        Ok(())
+    }
+}
+
+/// How [`Deinterlacer`] rebuilds the lines of the second field.
+///
+/// Stored in a `Deinterlace` effect's `mode` parameter as 0, 1 or 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DeinterlaceMode {
+    /// Keep both fields as they are, for static or progressive-in-interlaced
+    /// material.
+    Weave,
+    /// Interpolate the second field from the lines of the first.
+    Bob,
+    /// Keep the second field where the picture is static and interpolate
+    /// along edges where it moves.
+    #[default]
+    Adaptive,
+}
+
+impl DeinterlaceMode {
+    /// Value of the `mode` parameter.
+    pub fn to_parameter(self) -> f64 {
+        match self {
+            Self::Weave => 0.0,
+            Self::Bob => 1.0,
+            Self::Adaptive => 2.0,
+        }
+    }
+
+    /// Mode from a `mode` parameter value, rounding to the nearest one.
+    pub fn from_parameter(value: f64) -> Self {
+        match value.round() {
+            v if v <= 0.0 => Self::Weave,
+            v if v >= 2.0 => Self::Adaptive,
+            _ => Self::Bob,
+        }
+    }
+}
+
+/// Deinterlacing to one progressive frame per interlaced frame.
+///
+/// The first field in time is kept and the lines of the second are
+/// rebuilt. The adaptive mode works like yadif: the interpolated value
+/// is an edge-directed average of the lines above and below, pulled
+/// towards the second field's own sample as far as the motion measured
+/// against the previous frame allows. Static areas keep their full
+/// vertical resolution and moving ones lose the combing. Without a
+/// previous frame it interpolates along edges only.
+#[derive(Debug, Clone)]
+pub struct Deinterlacer {
+    /// Interpolation mode.
+    pub mode:        DeinterlaceMode,
+    /// Field order of the source. Progressive frames pass through.
+    pub field_order: FieldOrder,
+    previous:        Option<(u32, u32, Vec<u8>)>,
+}
+
+impl Deinterlacer {
+    /// Create a deinterlacer.
+    pub fn new(mode: DeinterlaceMode, field_order: FieldOrder) -> Self {
+        Self { mode, field_order, previous: None }
+    }
+
+    /// Create a deinterlacer from a `Deinterlace` effect's `mode` and
+    /// `bottom_first` parameters. Sources are top field first unless
+    /// `bottom_first` is non-zero.
+    pub fn from_effect(effect: &VideoEffect) -> VideoEditorResult<Self> {
+        if effect.effect_type != EffectType::Deinterlace {
+            return Err(VideoEditorError::Effect(format!(
+                "Effect {} is not a deinterlacer",
+                effect.id
+            )));
+        }
+        let field_order = if effect.parameter("bottom_first").is_some_and(|v| v != 0.0) {
+            FieldOrder::BottomFieldFirst
+        } else {
+            FieldOrder::TopFieldFirst
+        };
+        let mode =
+            effect.parameter("mode").map_or_else(Default::default, DeinterlaceMode::from_parameter);
+        Ok(Self::new(mode, field_order))
+    }
+
+    /// Update the mode and field order from a (possibly animated) effect,
+    /// keeping the previous frame.
+    pub fn update(&mut self, effect: &VideoEffect) -> VideoEditorResult<()> {
+        let previous = self.previous.take();
+        *self = Self { previous, ..Self::from_effect(effect)? };
+        Ok(())
+    }
+
+    /// Forget the previous frame, e.g. after a seek or cut.
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+
+    /// Deinterlace the next frame of a sequence.
+    pub fn process(
+        &mut self, pixels: &[u8], width: u32, height: u32,
+    ) -> VideoEditorResult<Vec<u8>> {
+        let mut out = vec![0; pixels.len()];
+        self.process_into(pixels, width, height, &mut out)?;
+        Ok(out)
+    }
+
+    /// Deinterlace the next frame of a sequence into `out`, a frame of the
+    /// same size such as a pooled buffer.
+    pub fn process_into(
+        &mut self, pixels: &[u8], width: u32, height: u32, out: &mut [u8],
+    ) -> VideoEditorResult<()> {
+        let _span = trace::span(TracePhase::Effect, "deinterlace");
+        check_frame(pixels, width, height)?;
+        check_frame(out, width, height)?;
+        out.copy_from_slice(pixels);
+        let Some(parity) = self.field_order.first_field_parity() else {
+            return Ok(());
+        };
+        if self.mode != DeinterlaceMode::Weave {
+            self.rebuild_second_field(pixels, width as usize, height as usize, parity, out);
+        }
+
+        // Motion is measured between inputs, so keep this one
+        let (pw, ph, previous) = self.previous.get_or_insert_with(Default::default);
+        (*pw, *ph) = (width, height);
+        previous.clear();
+        previous.extend_from_slice(pixels);
+        Ok(())
+    }
+
+    /// Replace the lines whose parity differs from `parity`.
+    fn rebuild_second_field(
+        &self, pixels: &[u8], w: usize, h: usize, parity: usize, out: &mut [u8],
+    ) {
+        let adaptive = self.mode == DeinterlaceMode::Adaptive;
+        let previous = self
+            .previous
+            .as_ref()
+            .filter(|(pw, ph, _)| (*pw as usize, *ph as usize) == (w, h) && adaptive)
+            .map(|(_, _, previous)| previous.as_slice());
+        let at = |x: usize, y: usize| (y * w + x) * 4;
+
+        for y in (0..h).filter(|y| y % 2 != parity) {
+            let (above, below) = match (y.checked_sub(1), (y + 1 < h).then_some(y + 1)) {
+                (Some(a), Some(b)) => (a, b),
+                (Some(n), None) | (None, Some(n)) => (n, n),
+                (None, None) => continue,
+            };
+            for x in 0..w {
+                // Interpolate along the direction where the lines agree most
+                let mut shift = 0isize;
+                if adaptive {
+                    let cost = |d: isize| {
+                        let xa = x.saturating_add_signed(d).min(w - 1);
+                        let xb = x.saturating_add_signed(-d).min(w - 1);
+                        (luma(&pixels[at(xa, above)..]) - luma(&pixels[at(xb, below)..])).abs()
+                    };
+                    let mut best = cost(0);
+                    for d in [-1, 1] {
+                        let c = cost(d);
+                        if c < best {
+                            (best, shift) = (c, d);
+                        }
+                    }
+                }
+                let a = at(x.saturating_add_signed(shift).min(w - 1), above);
+                let b = at(x.saturating_add_signed(-shift).min(w - 1), below);
+                let i = at(x, y);
+                for c in 0..4 {
+                    let spatial = (f32::from(pixels[a + c]) + f32::from(pixels[b + c])) / 2.0;
+                    let value = match previous {
+                        Some(previous) => {
+                            let woven = f32::from(pixels[i + c]);
+                            let (va, vb) = (at(x, above) + c, at(x, below) + c);
+                            let diff =
+                                |j: usize| (f32::from(pixels[j]) - f32::from(previous[j])).abs();
+                            let motion = diff(i + c).max((diff(va) + diff(vb)) / 2.0);
+                            spatial.clamp(woven - motion, woven + motion)
+                        },
+                        None => spatial,
+                    };
+                    out[i + c] = value.round() as u8;
+                }
+            }
+        }
+    }
+}
+
+/// Normalized Gaussian kernel covering three sigmas.
+fn gaussian(sigma: f32) -> Vec<f32> {
+    let radius = (sigma * 3.0).ceil() as isize;
+    let kernel: Vec<f32> =
+        (-radius..=radius).map(|d| (-((d * d) as f32) / (2.0 * sigma * sigma)).exp()).collect();
+    let total: f32 = kernel.iter().sum();
+    kernel.into_iter().map(|k| k / total).collect()
+}
+
+/// Separable blur of the color channels with clamped edges; alpha is
+/// copied.
+fn blur(pixels: &[u8], w: usize, h: usize, kernel: &[f32]) -> Vec<u8> {
+    let radius = (kernel.len() / 2) as isize;
+    let pass = |src: &[f32], horizontal: bool| {
+        let mut dst = vec![0.0; src.len()];
+        for y in 0..h {
+            for x in 0..w {
+                for c in 0..3 {
+                    let mut sum = 0.0;
+                    for (k, weight) in kernel.iter().enumerate() {
+                        let offset = k as isize - radius;
+                        let (sx, sy) = if horizontal {
+                            ((x as isize + offset).clamp(0, w as isize - 1) as usize, y)
+                        } else {
+                            (x, (y as isize + offset).clamp(0, h as isize - 1) as usize)
+                        };
+                        sum += src[(sy * w + sx) * 4 + c] * weight;
+                    }
+                    dst[(y * w + x) * 4 + c] = sum;
+                }
+            }
+        }
+        dst
+    };
+    let source: Vec<f32> = pixels.iter().map(|&p| f32::from(p)).collect();
+    let blurred = pass(&pass(&source, true), false);
+    blurred
+        .iter()
+        .zip(pixels)
+        .enumerate()
+        .map(|(i, (v, &p))| if i % 4 == 3 { p } else { v.round().clamp(0.0, 255.0) as u8 })
+        .collect()
+}
+
+/// Rec. 709 luma of an RGBA8 pixel.
+fn luma(pixel: &[u8]) -> f32 {
+    0.2126 * f32::from(pixel[0]) + 0.7152 * f32::from(pixel[1]) + 0.0722 * f32::from(pixel[2])
+}
+
+fn check_frame(pixels: &[u8], width: u32, height: u32) -> VideoEditorResult<()> {
+    let expected = width as usize * height as usize * 4;
+    if width == 0 || height == 0 || pixels.len() != expected {
+        return Err(VideoEditorError::Effect(format!(
+            "Frame is {} bytes, expected {expected} for {width}x{height}",
+            pixels.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{BufferDescriptor, FrameBufferPool};
+
+    const SIZE: u32 = 24;
+
+    /// Gray frame split into a dark left and bright right half, with noise.
+    fn frame(seed: u32, noise: f32) -> Vec<u8> {
+        let mut seed = seed;
+        let mut pixels = Vec::new();
+        for _ in 0..SIZE {
+            for x in 0..SIZE {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let n = ((seed >> 8) as f32 / (1 << 24) as f32 - 0.5) * 2.0 * noise;
+                let base = if x < SIZE / 2 { 60.0 } else { 180.0 };
+                let v = (base + n).round().clamp(0.0, 255.0) as u8;
+                pixels.extend([v, v, v, 255]);
+            }
+        }
+        pixels
+    }
+
+    /// Mean squared error against the clean frame.
+    fn error(pixels: &[u8]) -> f32 {
+        let clean = frame(0, 0.0);
+        let sum: f32 =
+            pixels.iter().zip(&clean).map(|(&a, &b)| (f32::from(a) - f32::from(b)).powi(2)).sum();
+        sum / pixels.len() as f32
+    }
+
+    #[test]
+    fn test_denoise() {
+        let noisy = frame(1, 12.0);
+        let mut results = Vec::new();
+        for quality in [EffectQuality::Draft, EffectQuality::Good, EffectQuality::Best] {
+            let mut denoiser = VideoDenoiser::new(1.0, 1.0, quality);
+            let mut out = Vec::new();
+            for seed in 1..=6 {
+                out = denoiser.process(&frame(seed, 12.0), SIZE, SIZE).expect("test assertion");
+            }
+            // The edge between the halves survives
+            let row = (SIZE as usize / 2) * SIZE as usize * 4;
+            let left = out[row + (SIZE as usize / 2 - 2) * 4];
+            let right = out[row + (SIZE as usize / 2 + 1) * 4];
+            assert!(right - left > 100, "{quality:?}: {left} {right}");
+            results.push(error(&out));
+        }
+        assert!(results[0] < error(&noisy) * 0.5, "{results:?}");
+        // Temporal passes accumulate over the sequence
+        assert!(results[1] < results[0] && results[2] < results[0], "{results:?}");
+
+        let mut effect = VideoEffect {
+            id:          1,
+            effect_type: EffectType::Blur,
+            parameters:  Vec::new(),
+            ab:          Default::default(),
+        };
+        assert!(VideoDenoiser::from_effect(&effect).is_err());
+        effect.effect_type = EffectType::Denoise;
+        effect.set_parameter("quality", EffectQuality::Best.to_parameter());
+        let mut denoiser = VideoDenoiser::from_effect(&effect).expect("test assertion");
+        assert_eq!((denoiser.radius(), denoiser.spatial), (3, 0.5));
+        assert!(denoiser.process(&noisy[4..], SIZE, SIZE).is_err());
+    }
+
+    #[test]
+    fn test_unsharp_mask() {
+        // Soft ramp across the edge
+        let soft = blur(&frame(0, 0.0), SIZE as usize, SIZE as usize, &gaussian(1.5));
+        let row = (SIZE as usize / 2) * SIZE as usize * 4;
+        let contrast = |pixels: &[u8]| {
+            let at = |x: usize| i32::from(pixels[row + x * 4]);
+            at(SIZE as usize / 2) - at(SIZE as usize / 2 - 1)
+        };
+        for quality in [EffectQuality::Draft, EffectQuality::Good, EffectQuality::Best] {
+            let sharp = UnsharpMask::new(1.0, 1.0, 2.0, quality)
+                .apply(&soft, SIZE, SIZE)
+                .expect("test assertion");
+            assert!(contrast(&sharp) > contrast(&soft), "{quality:?}");
+            // Flat areas are untouched
+            assert_eq!(sharp[..16], soft[..16]);
+            assert_eq!(sharp[3], 255);
+        }
+    }
+
+    #[test]
+    fn test_filter_into_pooled_buffer() {
+        let pool = FrameBufferPool::new(2);
+        let descriptor = BufferDescriptor::rgba8(SIZE, SIZE);
+        let noisy = frame(1, 12.0);
+
+        let mut denoiser = VideoDenoiser::new(1.0, 1.0, EffectQuality::Good);
+        let mut reference = VideoDenoiser::new(1.0, 1.0, EffectQuality::Good);
+        for seed in 1..=3 {
+            let input = frame(seed, 12.0);
+            let mut out = pool.acquire(descriptor);
+            denoiser.process_into(&input, SIZE, SIZE, &mut out).expect("test assertion");
+            assert_eq!(*out, *reference.process(&input, SIZE, SIZE).expect("test assertion"));
+        }
+        // Each frame went back to the pool, so one buffer served all three
+        assert_eq!(pool.stats().allocated, 1);
+
+        let sharpen = UnsharpMask::new(1.0, 1.0, 2.0, EffectQuality::Best);
+        let mut out = pool.acquire(descriptor);
+        sharpen.apply_into(&noisy, SIZE, SIZE, &mut out).expect("test assertion");
+        assert_eq!(*out, *sharpen.apply(&noisy, SIZE, SIZE).expect("test assertion"));
+        assert!(sharpen.apply_into(&noisy, SIZE, SIZE, &mut out[4..]).is_err());
+    }
+
+    #[test]
+    fn test_deinterlace() {
+        // Bright bar that moved right between the top and bottom fields
+        let combed: Vec<u8> = (0..SIZE)
+            .flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+            .flat_map(|(x, y)| {
+                let left = if y % 2 == 0 { 4 } else { 12 };
+                let v = if (left..left + 6).contains(&x) { 220 } else { 30 };
+                [v, v, v, 255]
+            })
+            .collect();
+        // Difference between each line and the average of its neighbours
+        let combing = |pixels: &[u8]| {
+            let row = SIZE as usize * 4;
+            (row..pixels.len() - row)
+                .map(|i| {
+                    let mid = (i32::from(pixels[i - row]) + i32::from(pixels[i + row])) / 2;
+                    (i32::from(pixels[i]) - mid).abs()
+                })
+                .sum::<i32>()
+        };
+
+        let weave = Deinterlacer::new(DeinterlaceMode::Weave, FieldOrder::TopFieldFirst)
+            .process(&combed, SIZE, SIZE)
+            .expect("test assertion");
+        assert_eq!(weave, combed);
+        let progressive = Deinterlacer::new(DeinterlaceMode::Bob, FieldOrder::Progressive)
+            .process(&combed, SIZE, SIZE)
+            .expect("test assertion");
+        assert_eq!(progressive, combed);
+
+        for mode in [DeinterlaceMode::Bob, DeinterlaceMode::Adaptive] {
+            let mut deinterlacer = Deinterlacer::new(mode, FieldOrder::TopFieldFirst);
+            let out = deinterlacer.process(&combed, SIZE, SIZE).expect("test assertion");
+            assert!(combing(&out) * 4 < combing(&combed), "{mode:?}");
+            // The top field is kept
+            assert_eq!(out[..SIZE as usize * 4], combed[..SIZE as usize * 4]);
+            assert_eq!(out[3], 255);
+        }
+
+        // A static picture keeps both fields once there is history
+        let still = frame(1, 12.0);
+        let mut adaptive =
+            Deinterlacer::new(DeinterlaceMode::Adaptive, FieldOrder::BottomFieldFirst);
+        adaptive.process(&still, SIZE, SIZE).expect("test assertion");
+        assert_eq!(adaptive.process(&still, SIZE, SIZE).expect("test assertion"), still);
+        adaptive.reset();
+        assert_ne!(adaptive.process(&still, SIZE, SIZE).expect("test assertion"), still);
+
+        let mut effect = VideoEffect {
+            id:          1,
+            effect_type: EffectType::Sharpen,
+            parameters:  Vec::new(),
+            ab:          Default::default(),
+        };
+        assert!(Deinterlacer::from_effect(&effect).is_err());
+        effect.effect_type = EffectType::Deinterlace;
+        effect.set_parameter("mode", DeinterlaceMode::Bob.to_parameter());
+        effect.set_parameter("bottom_first", 1.0);
+        let deinterlacer = Deinterlacer::from_effect(&effect).expect("test assertion");
+        assert_eq!(deinterlacer.mode, DeinterlaceMode::Bob);
+        assert_eq!(deinterlacer.field_order, FieldOrder::BottomFieldFirst);
+    }
+}
@@ -0,0 +1,352 @@
+//! Watch-folder auto-import.
+//!
+//! The plugin does not watch the filesystem itself. The host forwards file
+//! events for a folder through a [`FolderEventSource`] (or directly with
+//! [`WatchFolder::handle_event`]), and [`WatchFolder`] imports media once a
+//! file has gone quiet for the settle time, so copies still in progress are
+//! not picked up half written. Imported assets are handed to a
+//! [`MediaPreparer`] for proxies and thumbnails and can be dropped into a
+//! bin or appended to the end of a timeline track.
+
+use std::path::{Path, PathBuf};
+
+use super::{assets::AssetLibrary, timeline::TimelineManager};
+use crate::{
+    converter::{FormatConverter, ImportReport, ImportWarning},
+    errors::VideoEditorResult,
+    types::{FrameRate, TimePosition, timeline::TimelineClip},
+};
+
+/// Filesystem change reported by the host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FolderEvent {
+    /// A file appeared.
+    Created(PathBuf),
+    /// A file's contents changed.
+    Modified(PathBuf),
+    /// A file was deleted.
+    Removed(PathBuf),
+    /// A file was renamed or moved within the folder.
+    Renamed {
+        /// Old path.
+        from: PathBuf,
+        /// New path.
+        to:   PathBuf,
+    },
+}
+
+/// Host-side folder watcher.
+pub trait FolderEventSource: Send {
+    /// Take the events observed since the last call.
+    fn drain_events(&mut self) -> Vec<FolderEvent>;
+}
+
+/// Background proxy and thumbnail generation for newly imported assets.
+pub trait MediaPreparer {
+    /// Queue a proxy for an asset.
+    fn generate_proxy(&mut self, asset_id: u64, path: &Path) -> VideoEditorResult<()>;
+
+    /// Queue a thumbnail for an asset.
+    fn generate_thumbnail(&mut self, asset_id: u64, path: &Path) -> VideoEditorResult<()>;
+}
+
+/// Where imported assets go besides the library.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum WatchTarget {
+    /// Library only.
+    #[default]
+    Library,
+    /// Add to a named bin.
+    Bin(String),
+    /// Append to the end of a timeline track.
+    Track(u64),
+}
+
+/// Auto-imports media appearing in a watched folder.
+#[derive(Debug, Clone)]
+pub struct WatchFolder {
+    root:              PathBuf,
+    /// Also import from subfolders.
+    pub recursive:     bool,
+    /// Quiet time after the last event before a file is imported.
+    pub settle_ms:     u64,
+    /// Frame rate for image sequence patterns.
+    pub sequence_rate: FrameRate,
+    /// Bin or track receiving imported assets.
+    pub target:        WatchTarget,
+    /// Generate proxies for imported video.
+    pub proxies:       bool,
+    /// Generate thumbnails for imported assets.
+    pub thumbnails:    bool,
+    pending:           Vec<(PathBuf, u64)>,
+    imported:          Vec<(PathBuf, u64)>,
+}
+
+impl WatchFolder {
+    /// Watch `root` with a two second settle time.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root:          root.into(),
+            recursive:     false,
+            settle_ms:     2_000,
+            sequence_rate: FrameRate::FPS_24,
+            target:        WatchTarget::Library,
+            proxies:       true,
+            thumbnails:    true,
+            pending:       Vec::new(),
+            imported:      Vec::new(),
+        }
+    }
+
+    /// Set the bin or track receiving imported assets.
+    #[must_use]
+    pub fn with_target(mut self, target: WatchTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Watched folder.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Files waiting for their settle time.
+    pub fn pending(&self) -> impl Iterator<Item = &Path> {
+        self.pending.iter().map(|(path, _)| path.as_path())
+    }
+
+    /// Asset imported from `path`, if any.
+    pub fn asset_for(&self, path: &Path) -> Option<u64> {
+        self.imported.iter().find(|(p, _)| p == path).map(|&(_, id)| id)
+    }
+
+    /// Feed every event from `source`.
+    pub fn poll(&mut self, source: &mut dyn FolderEventSource, now_ms: u64) {
+        for event in source.drain_events() {
+            self.handle_event(event, now_ms);
+        }
+    }
+
+    /// Track a single event.
+    ///
+    /// Files outside the folder, with unrecognized extensions or already
+    /// imported are ignored; a rename onto a media name (the usual
+    /// "write to `.part`, then rename" pattern) counts as a new file.
+    pub fn handle_event(&mut self, event: FolderEvent, now_ms: u64) {
+        match event {
+            FolderEvent::Created(path) | FolderEvent::Modified(path) => self.touch(path, now_ms),
+            FolderEvent::Removed(path) => self.pending.retain(|(p, _)| *p != path),
+            FolderEvent::Renamed { from, to } => {
+                self.pending.retain(|(p, _)| *p != from);
+                if let Some(entry) = self.imported.iter_mut().find(|(p, _)| *p == from) {
+                    entry.0 = to;
+                } else {
+                    self.touch(to, now_ms);
+                }
+            },
+        }
+    }
+
+    fn touch(&mut self, path: PathBuf, now_ms: u64) {
+        if !self.watches(&path) || self.asset_for(&path).is_some() {
+            return;
+        }
+        match self.pending.iter_mut().find(|(p, _)| *p == path) {
+            Some(entry) => entry.1 = now_ms,
+            None => self.pending.push((path, now_ms)),
+        }
+    }
+
+    fn watches(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let nested = relative.components().count() > 1;
+        let hidden = relative
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_none_or(|name| name.starts_with('.'));
+        (self.recursive || !nested)
+            && !hidden
+            && path.to_str().and_then(FormatConverter::detect_format).is_some()
+    }
+
+    /// Import every file that has settled by `now_ms`.
+    ///
+    /// Each imported asset is sent to `preparer` and placed according to
+    /// the target; a track target needs `timeline`. Preparation and
+    /// placement problems become warnings on the file's report entry.
+    pub fn import_settled(
+        &mut self, library: &mut AssetLibrary, mut timeline: Option<&mut TimelineManager>,
+        preparer: &mut dyn MediaPreparer, now_ms: u64,
+    ) -> ImportReport {
+        let settle_ms = self.settle_ms;
+        let (ready, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|&(_, last)| now_ms.saturating_sub(last) >= settle_ms);
+        self.pending = waiting;
+
+        let paths: Vec<PathBuf> = ready.into_iter().map(|(path, _)| path).collect();
+        let names: Vec<&str> = paths.iter().filter_map(|p| p.to_str()).collect();
+        let mut report = library.import_batch(&names, self.sequence_rate);
+
+        for entry in &mut report.files {
+            let Some(id) = entry.asset_id else {
+                continue;
+            };
+            let path = PathBuf::from(&entry.path);
+            if self.proxies
+                && library.video_clip(id).is_some()
+                && let Err(err) = preparer.generate_proxy(id, &path)
+            {
+                entry.warnings.push(ImportWarning::PreparationFailed(err.to_string()));
+            }
+            if self.thumbnails
+                && let Err(err) = preparer.generate_thumbnail(id, &path)
+            {
+                entry.warnings.push(ImportWarning::PreparationFailed(err.to_string()));
+            }
+            if let Err(reason) = self.place(id, library, timeline.as_deref_mut()) {
+                entry.warnings.push(ImportWarning::PlacementFailed(reason));
+            }
+            self.imported.push((path, id));
+        }
+
+        report
+    }
+
+    fn place(
+        &self, id: u64, library: &mut AssetLibrary, timeline: Option<&mut TimelineManager>,
+    ) -> Result<(), String> {
+        match &self.target {
+            WatchTarget::Library => Ok(()),
+            WatchTarget::Bin(bin) => {
+                library.add_to_bin(bin, id);
+                Ok(())
+            },
+            WatchTarget::Track(track_id) => {
+                let timeline = timeline.ok_or("no timeline to append to")?;
+                let track = timeline
+                    .get_track(*track_id)
+                    .ok_or_else(|| format!("track {track_id} not found"))?;
+                let start = track.clips.iter().map(|c| c.end().ms).max().unwrap_or(0);
+                let duration = library
+                    .asset_duration(id)
+                    .ok_or_else(|| format!("asset {id} has no duration"))?;
+                let clip_id = timeline
+                    .tracks()
+                    .iter()
+                    .flat_map(|t| &t.clips)
+                    .map(|c| c.id)
+                    .max()
+                    .unwrap_or(0)
+                    + 1;
+                let clip = TimelineClip::new(clip_id, id, TimePosition::from_ms(start), duration);
+                timeline.add_clip(*track_id, clip).map_err(|err| err.to_string())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TrackType;
+
+    #[derive(Default)]
+    struct Recorder {
+        proxies:    Vec<u64>,
+        thumbnails: Vec<u64>,
+    }
+
+    impl MediaPreparer for Recorder {
+        fn generate_proxy(&mut self, asset_id: u64, _path: &Path) -> VideoEditorResult<()> {
+            self.proxies.push(asset_id);
+            Ok(())
+        }
+
+        fn generate_thumbnail(&mut self, asset_id: u64, _path: &Path) -> VideoEditorResult<()> {
+            self.thumbnails.push(asset_id);
+            Ok(())
+        }
+    }
+
+    struct Events(Vec<FolderEvent>);
+
+    impl FolderEventSource for Events {
+        fn drain_events(&mut self) -> Vec<FolderEvent> {
+            std::mem::take(&mut self.0)
+        }
+    }
+
+    fn placement_failures(report: &ImportReport) -> usize {
+        let warnings = report.files.iter().flat_map(|f| &f.warnings);
+        warnings.filter(|w| matches!(w, ImportWarning::PlacementFailed(_))).count()
+    }
+
+    #[test]
+    fn test_watch_folder_imports_settled_files() {
+        let root = PathBuf::from("/ingest");
+        let mut watch = WatchFolder::new(&root).with_target(WatchTarget::Bin("Dailies".into()));
+        let mut library = AssetLibrary::new();
+        let mut prep = Recorder::default();
+
+        let mut source = Events(vec![
+            FolderEvent::Created(root.join("a001.mov")),
+            FolderEvent::Created(root.join("notes.txt")),
+            FolderEvent::Created(root.join(".a001.mov")),
+            FolderEvent::Created(root.join("sub/b001.mov")),
+            FolderEvent::Created(PathBuf::from("/elsewhere/c001.mov")),
+            FolderEvent::Created(root.join("vo.mp3.part")),
+        ]);
+        watch.poll(&mut source, 0);
+        assert_eq!(watch.pending().count(), 1);
+
+        // Still being written
+        watch.handle_event(FolderEvent::Modified(root.join("a001.mov")), 1_500);
+        watch.handle_event(
+            FolderEvent::Renamed { from: root.join("vo.mp3.part"), to: root.join("vo.mp3") },
+            1_500,
+        );
+        let report = watch.import_settled(&mut library, None, &mut prep, 2_000);
+        assert!(report.files.is_empty());
+
+        let report = watch.import_settled(&mut library, None, &mut prep, 3_500);
+        assert_eq!(report.imported_count(), 2);
+        let video = watch.asset_for(&root.join("a001.mov")).expect("test assertion");
+        let audio = watch.asset_for(&root.join("vo.mp3")).expect("test assertion");
+        assert_eq!(prep.proxies, vec![video]);
+        assert_eq!(prep.thumbnails, vec![video, audio]);
+        assert_eq!(library.bin("Dailies"), Some(&[video, audio][..]));
+
+        // Later writes to an imported file are ignored
+        watch.handle_event(FolderEvent::Modified(root.join("a001.mov")), 4_000);
+        assert_eq!(watch.pending().count(), 0);
+    }
+
+    #[test]
+    fn test_watch_folder_appends_to_track() {
+        let root = PathBuf::from("/ingest");
+        let mut timeline = TimelineManager::new();
+        let track = timeline.add_track("V1", TrackType::Video);
+        let mut watch = WatchFolder::new(&root).with_target(WatchTarget::Track(track));
+        watch.settle_ms = 0;
+        let mut library = AssetLibrary::new();
+        let mut prep = Recorder::default();
+
+        watch.handle_event(FolderEvent::Created(root.join("a.mp4")), 0);
+        watch.handle_event(FolderEvent::Created(root.join("b.mp4")), 0);
+        let report = watch.import_settled(&mut library, Some(&mut timeline), &mut prep, 0);
+        assert_eq!(report.imported_count(), 2);
+        assert_eq!(placement_failures(&report), 0);
+
+        let clips = &timeline.get_track(track).expect("test assertion").clips;
+        assert_eq!(clips.len(), 2);
+        assert_eq!(clips[1].start, clips[0].end());
+
+        watch.handle_event(FolderEvent::Created(root.join("c.mp4")), 0);
+        let report = watch.import_settled(&mut library, None, &mut prep, 0);
+        assert_eq!(report.imported_count(), 1);
+        assert_eq!(placement_failures(&report), 1);
+    }
+}
@@ -0,0 +1,243 @@
+//! Automatic white balance and skin-tone checks.
+//!
+//! [`WhiteBalance`] finds the temperature/tint correction that makes a
+//! sampled region (or the frame average) neutral, in the terms of
+//! [`ColorGradingNode`]'s white balance. [`SkinToneCheck`] reads faces and
+//! skin regions tagged in frame metadata and reports how far each sits
+//! from the vectorscope skin-tone line.
+
+use super::color_grading::ColorGradingNode;
+use crate::metadata::{AnnotationType, BoundingBox, FrameMetadata};
+
+/// Vectorscope angle of the skin-tone line, in degrees counterclockwise
+/// from the +Cb axis.
+pub const SKIN_TONE_ANGLE: f32 = 123.0;
+
+/// BT.709 chroma of a color as vectorscope coordinates `(Cb, Cr)`.
+#[must_use]
+pub fn vectorscope_point(rgb: [f32; 3]) -> (f32, f32) {
+    let [r, g, b] = rgb;
+    let luma = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    ((b - luma) / 1.8556, (r - luma) / 1.5748)
+}
+
+/// White balance correction.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WhiteBalance {
+    /// Temperature offset for [`ColorGradingNode::temperature`].
+    pub temperature: f32,
+    /// Tint for [`ColorGradingNode::tint`].
+    pub tint:        f32,
+}
+
+impl WhiteBalance {
+    /// The correction that maps `rgb` to a neutral gray.
+    #[must_use]
+    pub fn neutralize(rgb: [f32; 3]) -> Self {
+        let [r, g, b] = rgb;
+        if r + b <= f32::EPSILON || g <= f32::EPSILON {
+            return Self::default();
+        }
+        // Balance red against blue first; green then meets their
+        // corrected level, which is their harmonic mean.
+        let warm = (b - r) / (b + r);
+        let balanced = 2.0 * r * b / (r + b);
+        Self { temperature: warm * 10_000.0, tint: (1.0 - balanced / g).clamp(-1.0, 1.0) }
+    }
+
+    /// Samples a region that should be neutral, e.g. a gray card, in an
+    /// RGBA8 frame. Returns `None` for an empty region.
+    #[must_use]
+    pub fn from_region(
+        pixels: &[u8], width: u32, height: u32, region: &BoundingBox,
+    ) -> Option<Self> {
+        region_mean(pixels, width, height, region).map(Self::neutralize)
+    }
+
+    /// Estimates the correction from the whole RGBA8 frame, assuming its
+    /// average is gray. Clipped and near-black pixels are ignored. Returns
+    /// `None` if no pixel qualifies.
+    #[must_use]
+    pub fn auto(pixels: &[u8]) -> Option<Self> {
+        let usable = pixels.chunks_exact(4).filter(|p| {
+            let (min, max) = (p[..3].iter().min(), p[..3].iter().max());
+            min.is_some_and(|&v| v > 10) && max.is_some_and(|&v| v < 250)
+        });
+        mean(usable).map(Self::neutralize)
+    }
+
+    /// Writes the correction into a grade.
+    pub fn apply_to(&self, grade: &mut ColorGradingNode) {
+        grade.temperature = self.temperature;
+        grade.tint = self.tint;
+    }
+}
+
+/// Skin tone of one tagged region.
+#[derive(Debug, Clone, Copy)]
+pub struct SkinToneReading {
+    /// Detected object, or `None` for a tagged annotation region.
+    pub object_id:  Option<u64>,
+    /// Sampled region.
+    pub region:     BoundingBox,
+    /// Vectorscope angle of the region's average color, in degrees.
+    pub angle:      f32,
+    /// Signed angle from the skin-tone line, in degrees (-180 to 180).
+    pub deviation:  f32,
+    /// Chroma magnitude of the average color.
+    pub saturation: f32,
+    /// Whether the deviation is outside the check's tolerance.
+    pub off_line:   bool,
+}
+
+/// Checks tagged faces and skin regions against the skin-tone line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkinToneCheck {
+    /// Allowed deviation from the line, in degrees.
+    pub tolerance: f32,
+}
+
+impl Default for SkinToneCheck {
+    fn default() -> Self {
+        Self { tolerance: 10.0 }
+    }
+}
+
+impl SkinToneCheck {
+    /// Reads every `face` or `skin` object detection and `face` or `skin`
+    /// label annotation with a region in the frame's metadata. Regions
+    /// with no usable pixels are skipped.
+    #[must_use]
+    pub fn check(
+        &self, pixels: &[u8], width: u32, height: u32, metadata: &FrameMetadata,
+    ) -> Vec<SkinToneReading> {
+        let is_skin =
+            |tag: &str| tag.eq_ignore_ascii_case("face") || tag.eq_ignore_ascii_case("skin");
+        let objects = metadata
+            .objects
+            .iter()
+            .filter(|o| is_skin(&o.class))
+            .map(|o| (Some(o.object_id), o.bbox));
+        let labels = metadata
+            .annotations
+            .iter()
+            .filter(|a| a.annotation_type == AnnotationType::Label && is_skin(&a.value))
+            .filter_map(|a| a.region.map(|region| (None, region)));
+
+        objects
+            .chain(labels)
+            .filter_map(|(object_id, region)| {
+                let (cb, cr) = vectorscope_point(region_mean(pixels, width, height, &region)?);
+                let angle = cr.atan2(cb).to_degrees().rem_euclid(360.0);
+                let deviation = (angle - SKIN_TONE_ANGLE + 180.0).rem_euclid(360.0) - 180.0;
+                Some(SkinToneReading {
+                    object_id,
+                    region,
+                    angle,
+                    deviation,
+                    saturation: cb.hypot(cr),
+                    off_line: deviation.abs() > self.tolerance,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Average RGB (0.0 to 1.0) of a normalized region of an RGBA8 frame.
+fn region_mean(pixels: &[u8], width: u32, height: u32, region: &BoundingBox) -> Option<[f32; 3]> {
+    let (x, y, w, h) = region.to_pixels(width, height);
+    let (x_end, y_end) = ((x + w).min(width) as usize, (y + h).min(height) as usize);
+    let rows = (y as usize..y_end).filter_map(|row| {
+        let start = (row * width as usize + x as usize) * 4;
+        pixels.get(start..(row * width as usize + x_end) * 4)
+    });
+    mean(rows.flat_map(|row| row.chunks_exact(4)))
+}
+
+fn mean<'a>(pixels: impl Iterator<Item = &'a [u8]>) -> Option<[f32; 3]> {
+    let mut sum = [0u64; 3];
+    let mut count = 0u64;
+    for pixel in pixels {
+        for (total, &value) in sum.iter_mut().zip(pixel) {
+            *total += u64::from(value);
+        }
+        count += 1;
+    }
+    (count > 0).then(|| sum.map(|total| total as f32 / count as f32 / 255.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{Annotation, ObjectDetection};
+
+    fn fill(width: u32, height: u32, color: [u8; 3]) -> Vec<u8> {
+        (0..width * height).flat_map(|_| [color[0], color[1], color[2], 255]).collect()
+    }
+
+    fn paint(pixels: &mut [u8], width: u32, region: (u32, u32, u32, u32), color: [u8; 3]) {
+        let (x, y, w, h) = region;
+        for row in y..y + h {
+            for col in x..x + w {
+                let i = ((row * width + col) * 4) as usize;
+                pixels[i..i + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    #[test]
+    fn test_white_balance() {
+        let mut frame = fill(32, 32, [40, 90, 30]);
+        paint(&mut frame, 32, (8, 8, 8, 8), [110, 128, 160]);
+        let card = BoundingBox::from_pixels(8, 8, 8, 8, 32, 32);
+
+        let balance = WhiteBalance::from_region(&frame, 32, 32, &card).expect("test assertion");
+        assert!(balance.temperature > 0.0, "bluish card should warm up: {balance:?}");
+        let mut grade = ColorGradingNode::default();
+        balance.apply_to(&mut grade);
+        grade.apply_rgba8(&mut frame);
+        let [r, g, b] = region_mean(&frame, 32, 32, &card).expect("test assertion");
+        assert!((r - g).abs() < 0.01 && (g - b).abs() < 0.01, "{:?}", [r, g, b]);
+
+        let cast = fill(16, 16, [150, 120, 100]);
+        let balance = WhiteBalance::auto(&cast).expect("test assertion");
+        assert!(balance.temperature < 0.0);
+        assert!(WhiteBalance::auto(&fill(4, 4, [255, 255, 255])).is_none());
+        assert!(WhiteBalance::from_region(&cast, 16, 16, &BoundingBox::default()).is_none());
+    }
+
+    #[test]
+    fn test_skin_tone_check() {
+        let mut frame = fill(40, 20, [30, 30, 30]);
+        paint(&mut frame, 40, (0, 0, 20, 20), [217, 158, 128]);
+        paint(&mut frame, 40, (20, 0, 20, 20), [150, 190, 120]);
+        let mut metadata = FrameMetadata::new(0);
+        metadata.add_object(ObjectDetection::new(
+            7,
+            "Face",
+            0.9,
+            BoundingBox::new(0.0, 0.0, 0.5, 1.0),
+        ));
+        metadata.add_object(ObjectDetection::new(
+            8,
+            "car",
+            0.9,
+            BoundingBox::new(0.0, 0.0, 1.0, 1.0),
+        ));
+        metadata.add_annotation(Annotation {
+            annotation_type: AnnotationType::Label,
+            value:           "skin".into(),
+            region:          Some(BoundingBox::new(0.5, 0.0, 0.5, 1.0)),
+            author:          "colorist".into(),
+            timestamp_ms:    0,
+        });
+
+        let readings = SkinToneCheck::default().check(&frame, 40, 20, &metadata);
+        assert_eq!(readings.len(), 2);
+        assert_eq!(readings[0].object_id, Some(7));
+        assert!(!readings[0].off_line, "{:?}", readings[0]);
+        assert!(readings[0].deviation.abs() < 5.0);
+        assert_eq!(readings[1].object_id, None);
+        assert!(readings[1].off_line && readings[1].deviation > 60.0, "{:?}", readings[1]);
+    }
+}
@@ -3,36 +3,106 @@
 #![allow(dead_code, missing_docs)]
 #![allow(clippy::pedantic)]
 
-pub mod errors;
-mod implementation;
-mod types;
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod bundle;
+pub mod checksum;
 pub mod converter;
+pub mod decoder;
+pub mod errors;
+pub mod evlf_reader;
 pub mod evlf_types;
+pub mod evlf_writer;
 pub mod flexforge;
+pub mod gltf;
+mod implementation;
+mod json;
 pub mod metadata;
+pub mod psd;
+pub mod scene3d;
+pub mod stills;
+pub mod svg;
+pub mod tasks;
+pub mod trace;
+mod types;
+pub mod vector;
 
+pub use bundle::{
+    BUNDLE_MAGIC, BUNDLE_PROJECT_MEMBER, BUNDLE_TRACK_VIEW_MEMBER, BundleAssets, BundleMember,
+    BundleMemberKind, BundleSaveStats, ProjectBundle,
+};
+pub use checksum::{FileDigest, Sha256, Xxh64};
 pub use converter::{
-    ConversionOptions, ConversionPhase, ConversionProgress, ConversionResult, ConversionStats,
-    FormatConverter, InputFormat, InputFormatCategory, OutputFormat, ProgressCallback,
+    BatchCancel, BatchConverter, BatchProgress, BatchProgressCallback, BatchResult, BatchSummary,
+    ChecksumVerification, ConversionOptions, ConversionPhase, ConversionProgress, ConversionResult,
+    ConversionStats, FormatCapabilities, FormatConverter, FormatFeatures, ImageSequenceInfo,
+    ImplementationStatus, ImportFileResult, ImportOutcome, ImportReport, ImportWarning,
+    InputFormat, InputFormatCategory, OutputFormat, ProgressCallback, SequencePattern,
 };
-pub use errors::{VideoEditorError, VideoEditorResult};
+pub use decoder::{DecodedFrame, Decoder, DecoderFactory, DecoderRegistry, StreamInfo};
+pub use errors::{CodecStage, ErrorContext, VideoEditorError, VideoEditorResult};
+pub use evlf_reader::EvlfReader;
 pub use evlf_types::{
-    BlendMode, BranchFork, BranchPoint, BranchType, EVLF_MAGIC, EVLF_VERSION, EvlfFlags,
-    EvlfHeader, EvlfTrackHeader, EvlfTrackType, FrameIndexEntry, FrameType, TrackFlags,
+    BlendMode, BranchFork, BranchPoint, BranchType, EVLF_CHECKSUM_MAGIC, EVLF_MAGIC, EVLF_VERSION,
+    EvlfChecksums, EvlfFlags, EvlfHeader, EvlfTrackHeader, EvlfTrackType, FrameIndexEntry,
+    FrameType, TrackFlags,
 };
+pub use evlf_writer::EvlfWriter;
 pub use flexforge::VideoEditorFlexForge;
+pub use gltf::GLB_MAGIC;
 pub use implementation::{
-    AssetLibrary, EffectType, EffectsPipeline, GpuPipeline, TimelineManager, VideoEditorConfig,
-    VideoEditorPlugin, VideoEffect,
+    AbCompare, AbSlot, ActivityRange, AssetLibrary, AudioBusId, AudioChannelConfig, AudioChunk,
+    Beat, BeatDetector, BeatGrid, CameraLayer, CameraState, CaptionCue, CaptionExportOptions,
+    CaptionFormat, CaptionPosition, CaptionSidecar, CaptionStyle, CaptionTrack, ClipAudio,
+    ClipFrameSource, ClipTransform, ClipTransformState, CommandHandler, CommandRegistry,
+    CompositingMode, DeinterlaceMode, Deinterlacer, DetectionFrame, DetectionPipeline,
+    DetectionProvider, DetectionSummary, Diagnostic, DiagnosticIssue, DiagnosticSeverity,
+    DoctorFix, DoctorReport, DownmixMatrix, EditJournal, EditorCommand, EditorEvent,
+    EditorScriptApi, EffectBackend, EffectCapabilities, EffectPreset, EffectQuality, EffectType,
+    EffectsPipeline, EventBus, EventCallback, ExecutionMode, FolderEvent, FolderEventSource,
+    FollowMode, GeneratorSource, GpuAllocationId, GpuMemoryPool, GpuMemoryStats, GpuPipeline,
+    GpuPriority, GpuResourceDesc, GpuScheduler, GpuSchedulerStats, GpuTimeSlice, GpuWorkId,
+    GpuWorkItem, HardwareOutput, JOURNAL_MAGIC, JournalEntry, JournalMerge, MIN_ITEM_PX,
+    MediaPreparer, MemoryPressure, MemoryPressureCallback, MergeConflict, MergeSide, MetadataMatch,
+    MetadataQuery, NoiseProfile, OpTarget, OperationOutput, OutputAssignment, OutputRouting,
+    PRESET_PACK_MAGIC, PipelineCheck, PipelineValidation, PlayheadFollow, Preset, PresetLibrary,
+    ProjectDoctor, RenderDeterminism, RenderScaleMode, RenderTarget, RenderTargetDesc,
+    RenderTargetFormat, RenderTargetHandle, RenderTargetId, RenderTargetRegistry, RippleSync,
+    SCRIPT_BATCH_MAGIC, SDI_GROUP_CHANNELS, SMPTE_BARS, ScriptBatchResult, ScriptOperation,
+    SelectionModel, SelectionRange, SmartReframe, SnapCandidate, SnapEngine, SnapSource,
+    SnappedPosition, SpectralDenoiser, SphericalView, StabilizeTransform, Stabilizer,
+    StabilizerPhase, StabilizerProgress, StabilizerProgressCallback, SubscriptionId, TestPattern,
+    TimelineItem, TimelineManager, TimelineViewport, ToneGenerator, TrackLayout,
+    TrackStripSettings, TrackTemplate, TranscriptEditor, TranscriptionFuture,
+    TranscriptionOrchestrator, TranscriptionProvider, UnsharpMask, VideoDenoiser,
+    VideoEditorConfig, VideoEditorPlugin, VideoEffect, VoiceActivityDetector, WatchFolder,
+    WatchTarget, WaveformSync, saliency_center, sync_by_waveform,
 };
 pub use metadata::{
-    Annotation, AnnotationType, BoundingBox, FrameMetadata, MetadataIndex, ObjectDetection,
-    SceneClassification, SemanticRegion, TrackingState,
+    Annotation, AnnotationType, BoundingBox, FrameMetadata, MetadataIndex, MotionVector,
+    ObjectDetection, SceneClassification, SemanticRegion, TrackingState, TranscriptWord,
 };
+pub use psd::{PSD_SIGNATURE, PsdDocument, PsdLayer, PsdLayerKind, PsdMask};
+pub use scene3d::{
+    AlphaMode, Animation3D, AnimationChannel, AnimationInterpolation, AnimationPath,
+    CameraProjection, DrawItem, Mat4, Mesh3D, MeshPrimitive, NodeTransform, PbrMaterial, Scene3D,
+    SceneNode, Skin, Texture3D,
+};
+pub use stills::{EXR_MAGIC, PNG_SIGNATURE, StillFormat};
+pub use tasks::{CancellationToken, ControlHandle, TaskHandle};
+pub use trace::{SpanRecord, TracePhase, TraceSink};
 pub use types::{
-    AudioClip, AudioFormat, FrameRate, Resolution, TimePosition, TimelinePosition, TimelineTrack,
-    TrackType, VideoClip, VideoFormat,
+    AdjustmentClip, AudioClip, AudioFormat, BufferDescriptor, BufferFormat, BwfMetadata,
+    ChannelMap, ClipFade, ClipGroup, ClipPitch, EditSuggestion, EditSuggestionKind, FadeShape,
+    FieldOrder, FrameBuffer, FrameBufferPool, FramePoolStats, FrameRate, ImageSequenceClip,
+    IxmlMetadata, IxmlTrack, PixelAspectRatio, ProjectionMode, Resolution, Rotation,
+    STRIDE_ALIGNMENT, TimePosition, TimelinePosition, TimelineTrack, TrackGroup, TrackType,
+    TrackView, TrackViewState, VideoClip, VideoFormat,
+};
+pub use vector::{
+    FillRule, PathCommand, Polyline, Stroke, Transform2D, VectorDocument, VectorMesh, VectorPath,
+    VectorShape, VectorVertex,
 };
 
 #[cfg(all(test, feature = "full-tests"))]
-mod tests;
+mod tests;
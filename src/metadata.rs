@@ -296,6 +296,45 @@ impl SemanticRegion {
     }
 }
 
+/// Global frame-to-frame motion, as a fraction of the frame size.
+///
+/// Positive values mean the picture content moved right/down since the
+/// previous frame.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MotionVector {
+    /// Horizontal displacement.
+    pub dx: f32,
+    /// Vertical displacement.
+    pub dy: f32,
+}
+
+impl MotionVector {
+    /// Creates a motion vector.
+    pub fn new(dx: f32, dy: f32) -> Self {
+        Self { dx, dy }
+    }
+}
+
+/// Timed word from speech transcription.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptWord {
+    /// Word text, including trailing punctuation.
+    pub text:       String,
+    /// Start in milliseconds.
+    pub start_ms:   u64,
+    /// End in milliseconds.
+    pub end_ms:     u64,
+    /// Recognition confidence (0.0-1.0).
+    pub confidence: f32,
+}
+
+impl TranscriptWord {
+    /// Creates a transcript word.
+    pub fn new(text: impl Into<String>, start_ms: u64, end_ms: u64, confidence: f32) -> Self {
+        Self { text: text.into(), start_ms, end_ms, confidence }
+    }
+}
+
 /// Metadata index for efficient lookup.
 #[derive(Debug, Clone, Default)]
 pub struct MetadataIndex {
@@ -305,6 +344,14 @@ pub struct MetadataIndex {
     object_index:      Vec<(u64, Vec<u64>)>,
     /// Scene transition frames.
     scene_transitions: Vec<(u64, String)>,
+    /// Per-frame motion from stabilization analysis, sorted by frame.
+    motion_vectors:    Vec<(u64, MotionVector)>,
+    /// Word-level transcript in timeline time, sorted by start.
+    transcript:        Vec<TranscriptWord>,
+    /// Tracked object detections, sorted by frame.
+    detections:        Vec<(u64, ObjectDetection)>,
+    /// Frame annotations, sorted by frame.
+    annotations:       Vec<(u64, Annotation)>,
 }
 
 impl MetadataIndex {
@@ -329,9 +376,17 @@ impl MetadataIndex {
         self.object_index.push((object_id, vec![frame]));
     }
 
-    /// Adds scene transition.
+    /// Adds scene transition, keeping transitions sorted by frame.
     pub fn add_scene_transition(&mut self, frame: u64, scene: impl Into<String>) {
-        self.scene_transitions.push((frame, scene.into()));
+        let pos = self.scene_transitions.partition_point(|(f, _)| *f <= frame);
+        self.scene_transitions.insert(pos, (frame, scene.into()));
+    }
+
+    /// Gets the scene a frame belongs to: the last transition at or
+    /// before it.
+    pub fn scene_at(&self, frame: u64) -> Option<&str> {
+        let pos = self.scene_transitions.partition_point(|(f, _)| *f <= frame);
+        pos.checked_sub(1).map(|pos| self.scene_transitions[pos].1.as_str())
     }
 
     /// Finds frames containing object.
@@ -346,6 +401,115 @@ impl MetadataIndex {
     pub fn scene_transitions(&self) -> &[(u64, String)] {
         &self.scene_transitions
     }
+
+    /// Sets the motion of a frame relative to its predecessor.
+    pub fn set_motion(&mut self, frame: u64, motion: MotionVector) {
+        match self.motion_vectors.binary_search_by_key(&frame, |(f, _)| *f) {
+            Ok(pos) => self.motion_vectors[pos].1 = motion,
+            Err(pos) => self.motion_vectors.insert(pos, (frame, motion)),
+        }
+    }
+
+    /// Gets the motion of a frame, if analyzed.
+    pub fn motion(&self, frame: u64) -> Option<MotionVector> {
+        self.motion_vectors
+            .binary_search_by_key(&frame, |(f, _)| *f)
+            .ok()
+            .map(|pos| self.motion_vectors[pos].1)
+    }
+
+    /// Gets all analyzed motion, sorted by frame.
+    pub fn motion_vectors(&self) -> &[(u64, MotionVector)] {
+        &self.motion_vectors
+    }
+
+    /// Removes all motion analysis.
+    pub fn clear_motion(&mut self) {
+        self.motion_vectors.clear();
+    }
+
+    /// Replaces the transcript words starting in `start_ms..end_ms`.
+    pub fn set_transcript_range(
+        &mut self, start_ms: u64, end_ms: u64, words: impl IntoIterator<Item = TranscriptWord>,
+    ) {
+        self.transcript.retain(|w| w.start_ms < start_ms || w.start_ms >= end_ms);
+        self.transcript.extend(words);
+        self.transcript.sort_by_key(|w| w.start_ms);
+    }
+
+    /// Gets the transcript, sorted by start.
+    pub fn transcript(&self) -> &[TranscriptWord] {
+        &self.transcript
+    }
+
+    /// Gets the transcript words overlapping `start_ms..end_ms`.
+    pub fn transcript_between(&self, start_ms: u64, end_ms: u64) -> &[TranscriptWord] {
+        let first = self.transcript.partition_point(|w| w.end_ms <= start_ms);
+        let last = self.transcript.partition_point(|w| w.start_ms < end_ms);
+        &self.transcript[first..last.max(first)]
+    }
+
+    /// Removes the transcript.
+    pub fn clear_transcript(&mut self) {
+        self.transcript.clear();
+    }
+
+    /// Replaces the detections of a frame and indexes their objects.
+    pub fn set_detections(
+        &mut self, frame: u64, detections: impl IntoIterator<Item = ObjectDetection>,
+    ) {
+        let first = self.detections.partition_point(|(f, _)| *f < frame);
+        let last = self.detections.partition_point(|(f, _)| *f <= frame);
+        let detections: Vec<_> = detections.into_iter().map(|d| (frame, d)).collect();
+        for (_, detection) in &detections {
+            if !self.frames_with_object(detection.object_id).is_some_and(|f| f.contains(&frame)) {
+                self.track_object(detection.object_id, frame);
+            }
+        }
+        self.detections.splice(first..last, detections);
+    }
+
+    /// Gets the detections of a frame.
+    pub fn detections_at(&self, frame: u64) -> impl Iterator<Item = &ObjectDetection> {
+        let first = self.detections.partition_point(|(f, _)| *f < frame);
+        self.detections[first..].iter().take_while(move |(f, _)| *f == frame).map(|(_, d)| d)
+    }
+
+    /// Gets all detections with their frames, sorted by frame.
+    pub fn detections(&self) -> &[(u64, ObjectDetection)] {
+        &self.detections
+    }
+
+    /// Adds an annotation to a frame.
+    pub fn add_annotation(&mut self, frame: u64, annotation: Annotation) {
+        let pos = self.annotations.partition_point(|(f, _)| *f <= frame);
+        self.annotations.insert(pos, (frame, annotation));
+    }
+
+    /// Gets all annotations with their frames, sorted by frame.
+    pub fn annotations(&self) -> &[(u64, Annotation)] {
+        &self.annotations
+    }
+
+    /// Last frame with any indexed metadata.
+    pub fn last_frame(&self) -> Option<u64> {
+        [
+            self.frame_offsets.iter().map(|(f, _)| *f).max(),
+            self.object_index.iter().flat_map(|(_, frames)| frames.iter().copied()).max(),
+            self.scene_transitions.last().map(|(f, _)| *f),
+            self.motion_vectors.last().map(|(f, _)| *f),
+            self.detections.last().map(|(f, _)| *f),
+            self.annotations.last().map(|(f, _)| *f),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+    }
+
+    /// Highest object ID in the index, if any.
+    pub fn max_object_id(&self) -> Option<u64> {
+        self.object_index.iter().map(|(id, _)| *id).max()
+    }
 }
 
 #[cfg(all(test, feature = "full-tests"))]
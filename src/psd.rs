@@ -0,0 +1,785 @@
+//! Native Photoshop document reader.
+//!
+//! Parses 8-bit RGB/RGBA PSD files into their layer tree: per-layer RGBA
+//! pixels, blend modes, opacity, visibility, clipping and user masks, plus
+//! the flattened composite. Layers are kept in file order (bottom to top)
+//! and groups are linked through parent indices.
+
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    evlf_types::BlendMode,
+};
+
+/// PSD file signature.
+pub const PSD_SIGNATURE: &[u8; 4] = b"8BPS";
+
+/// Color mode value for RGB documents.
+const COLOR_MODE_RGB: u16 = 3;
+
+/// Layer flag bit marking a hidden layer.
+const LAYER_FLAG_HIDDEN: u8 = 1 << 1;
+
+/// Mask flag bit marking a disabled mask.
+const MASK_FLAG_DISABLED: u8 = 1 << 1;
+
+/// Largest width or height of a PSD document (PSB raises it to 300,000).
+const MAX_DIMENSION: u32 = 30_000;
+
+/// Largest expansion of PackBits data: a two-byte run yields 128 bytes.
+const MAX_PACKBITS_RATIO: usize = 64;
+
+/// Kind of layer record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PsdLayerKind {
+    /// Layer with pixel data.
+    Pixel,
+    /// Layer group; `open` is the folder state in the layers panel.
+    Group { open: bool },
+}
+
+/// User mask attached to a layer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PsdMask {
+    /// Left edge in document coordinates.
+    pub left:          i32,
+    /// Top edge in document coordinates.
+    pub top:           i32,
+    /// Mask width.
+    pub width:         u32,
+    /// Mask height.
+    pub height:        u32,
+    /// Value outside the mask rectangle.
+    pub default_color: u8,
+    /// Mask is switched off.
+    pub disabled:      bool,
+    /// 8-bit mask values, row-major.
+    pub data:          Vec<u8>,
+}
+
+/// One layer of a PSD document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PsdLayer {
+    /// Layer name (Unicode name when present).
+    pub name:       String,
+    /// Pixel layer or group.
+    pub kind:       PsdLayerKind,
+    /// Index of the enclosing group.
+    pub parent:     Option<usize>,
+    /// Left edge in document coordinates.
+    pub left:       i32,
+    /// Top edge in document coordinates.
+    pub top:        i32,
+    /// Layer width.
+    pub width:      u32,
+    /// Layer height.
+    pub height:     u32,
+    /// Blend mode.
+    pub blend_mode: BlendMode,
+    /// Opacity (0-255).
+    pub opacity:    u8,
+    /// Visibility flag of the layer itself.
+    pub visible:    bool,
+    /// Clipped to the layer below.
+    pub clipped:    bool,
+    /// RGBA8 pixels, row-major (empty for groups).
+    pub pixels:     Vec<u8>,
+    /// User mask.
+    pub mask:       Option<PsdMask>,
+}
+
+/// Parsed PSD document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PsdDocument {
+    /// Document width.
+    pub width:     u32,
+    /// Document height.
+    pub height:    u32,
+    /// Layers and groups, bottom to top.
+    pub layers:    Vec<PsdLayer>,
+    /// Flattened RGBA8 image stored in the file.
+    pub composite: Vec<u8>,
+}
+
+impl PsdDocument {
+    /// Parses a PSD file from memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the data is not a PSD file, uses an unsupported
+    /// depth, color mode or compression, is truncated, or has document,
+    /// layer or mask bounds outside the PSD limits.
+    pub fn parse(bytes: &[u8]) -> VideoEditorResult<Self> {
+        let mut r = Reader::new(bytes);
+        if r.take(4)? != PSD_SIGNATURE {
+            return Err(VideoEditorError::conversion("Not a PSD file"));
+        }
+        if r.u16()? != 1 {
+            return Err(VideoEditorError::unsupported_format(
+                "Large document (PSB) files are not supported",
+            ));
+        }
+        r.take(6)?;
+        let channels = usize::from(r.u16()?);
+        let height = r.u32()?;
+        let width = r.u32()?;
+        let depth = r.u16()?;
+        if depth != 8 {
+            return Err(VideoEditorError::unsupported_format(format!(
+                "{depth}-bit PSD files are not supported"
+            )));
+        }
+        if r.u16()? != COLOR_MODE_RGB || channels < 3 {
+            return Err(VideoEditorError::unsupported_format("Only RGB PSD files are supported"));
+        }
+        if !(1..=MAX_DIMENSION).contains(&width) || !(1..=MAX_DIMENSION).contains(&height) {
+            return Err(VideoEditorError::conversion(format!(
+                "PSD size {width}x{height} is outside 1..={MAX_DIMENSION}"
+            )));
+        }
+
+        // Color mode data and image resources
+        let len = r.u32()? as usize;
+        r.take(len)?;
+        let len = r.u32()? as usize;
+        r.take(len)?;
+
+        let len = r.u32()? as usize;
+        let layers = read_layer_info(r.take(len)?, width, height)?;
+
+        let planes = decode_planes(&mut r, width as usize, height as usize, channels)?;
+        let alpha = planes.get(3).map(Vec::as_slice);
+        let composite = interleave([&planes[0], &planes[1], &planes[2]], alpha);
+
+        Ok(Self { width, height, layers, composite })
+    }
+
+    /// Whether a layer and all its enclosing groups are visible.
+    #[must_use]
+    pub fn is_visible(&self, index: usize) -> bool {
+        let mut current = Some(index);
+        while let Some(i) = current {
+            let Some(layer) = self.layers.get(i) else {
+                return false;
+            };
+            if !layer.visible {
+                return false;
+            }
+            current = layer.parent;
+        }
+        true
+    }
+
+    /// Pixel layers with their indices, bottom to top.
+    pub fn pixel_layers(&self) -> impl Iterator<Item = (usize, &PsdLayer)> {
+        self.layers.iter().enumerate().filter(|(_, l)| l.kind == PsdLayerKind::Pixel)
+    }
+
+    /// Direct children of a group, bottom to top.
+    pub fn children(&self, group: usize) -> impl Iterator<Item = (usize, &PsdLayer)> {
+        self.layers.iter().enumerate().filter(move |(_, l)| l.parent == Some(group))
+    }
+}
+
+/// Section divider types from the `lsct` block.
+const SECTION_OPEN: u32 = 1;
+const SECTION_CLOSED: u32 = 2;
+const SECTION_END: u32 = 3;
+
+/// Layer record before channel data is attached.
+struct LayerRecord {
+    top:        i32,
+    left:       i32,
+    bottom:     i32,
+    right:      i32,
+    channels:   Vec<(i16, usize)>,
+    blend_mode: BlendMode,
+    opacity:    u8,
+    clipped:    bool,
+    visible:    bool,
+    name:       String,
+    section:    u32,
+    mask:       Option<PsdMask>,
+}
+
+fn read_layer_info(
+    section: &[u8], doc_width: u32, doc_height: u32,
+) -> VideoEditorResult<Vec<PsdLayer>> {
+    if section.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut r = Reader::new(section);
+    let len = r.u32()? as usize;
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    let mut r = Reader::new(r.take(len)?);
+
+    // Negative count means the first alpha channel holds merged transparency
+    let count = r.i16()?.unsigned_abs() as usize;
+    let records = (0..count)
+        .map(|_| read_layer_record(&mut r, doc_width, doc_height))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut layers: Vec<PsdLayer> = Vec::with_capacity(records.len());
+    // Children collected for each group whose end marker has been seen
+    let mut open_groups: Vec<Vec<usize>> = Vec::new();
+    for record in records {
+        let width = dimension(record.left, record.right);
+        let height = dimension(record.top, record.bottom);
+
+        let mut planes: [Option<Vec<u8>>; 4] = Default::default();
+        let mut mask = record.mask;
+        for &(id, len) in &record.channels {
+            let mut channel = Reader::new(r.take(len)?);
+            let slot = match id {
+                0..=2 => id as usize,
+                -1 => 3,
+                -2 => {
+                    if let Some(mask) = &mut mask {
+                        let (w, h) = (mask.width as usize, mask.height as usize);
+                        mask.data = decode_planes(&mut channel, w, h, 1)?.pop().unwrap_or_default();
+                    }
+                    continue;
+                },
+                _ => continue,
+            };
+            planes[slot] = decode_planes(&mut channel, width, height, 1)?.pop();
+        }
+
+        let kind = match record.section {
+            SECTION_OPEN => PsdLayerKind::Group { open: true },
+            SECTION_CLOSED => PsdLayerKind::Group { open: false },
+            SECTION_END => {
+                open_groups.push(Vec::new());
+                continue;
+            },
+            _ => PsdLayerKind::Pixel,
+        };
+
+        let pixels = if kind == PsdLayerKind::Pixel && width * height > 0 {
+            // Missing color channels are filled with black, sized after a
+            // channel that was decoded (and so bounded by the file data)
+            let Some(len) = planes.iter().flatten().map(Vec::len).next() else {
+                return Err(VideoEditorError::conversion(format!(
+                    "PSD layer '{}' has no channel data",
+                    record.name
+                )));
+            };
+            let empty = vec![0; len];
+            let color = |i: usize| planes[i].as_deref().unwrap_or(&empty);
+            interleave([color(0), color(1), color(2)], planes[3].as_deref())
+        } else {
+            Vec::new()
+        };
+
+        let index = layers.len();
+        if matches!(kind, PsdLayerKind::Group { .. }) {
+            for child in open_groups.pop().unwrap_or_default() {
+                layers[child].parent = Some(index);
+            }
+        }
+        if let Some(siblings) = open_groups.last_mut() {
+            siblings.push(index);
+        }
+
+        layers.push(PsdLayer {
+            name: record.name,
+            kind,
+            parent: None,
+            left: record.left,
+            top: record.top,
+            width: width as u32,
+            height: height as u32,
+            blend_mode: record.blend_mode,
+            opacity: record.opacity,
+            visible: record.visible,
+            clipped: record.clipped,
+            pixels,
+            mask,
+        });
+    }
+    Ok(layers)
+}
+
+fn read_layer_record(
+    r: &mut Reader<'_>, doc_width: u32, doc_height: u32,
+) -> VideoEditorResult<LayerRecord> {
+    let (top, left, bottom, right) = (r.i32()?, r.i32()?, r.i32()?, r.i32()?);
+    check_rect((top, left, bottom, right), doc_width, doc_height)?;
+    let channel_count = r.u16()?;
+    let channels = (0..channel_count)
+        .map(|_| Ok((r.i16()?, r.u32()? as usize)))
+        .collect::<VideoEditorResult<Vec<_>>>()?;
+
+    if r.take(4)? != b"8BIM" {
+        return Err(VideoEditorError::conversion("Invalid PSD blend mode signature"));
+    }
+    let blend_mode = blend_mode_from_key(r.take(4)?);
+    let opacity = r.u8()?;
+    let clipped = r.u8()? == 1;
+    let flags = r.u8()?;
+    r.u8()?;
+
+    let len = r.u32()? as usize;
+    let mut extra = Reader::new(r.take(len)?);
+
+    let len = extra.u32()? as usize;
+    let mut mask_data = Reader::new(extra.take(len)?);
+    let mask = if len >= 18 {
+        let (top, left) = (mask_data.i32()?, mask_data.i32()?);
+        let (bottom, right) = (mask_data.i32()?, mask_data.i32()?);
+        check_rect((top, left, bottom, right), doc_width, doc_height)?;
+        Some(PsdMask {
+            left,
+            top,
+            width: dimension(left, right) as u32,
+            height: dimension(top, bottom) as u32,
+            default_color: mask_data.u8()?,
+            disabled: mask_data.u8()? & MASK_FLAG_DISABLED != 0,
+            data: Vec::new(),
+        })
+    } else {
+        None
+    };
+
+    // Blending ranges
+    let len = extra.u32()? as usize;
+    extra.take(len)?;
+
+    // Pascal name padded to a multiple of four bytes
+    let len = usize::from(extra.u8()?);
+    let mut name = String::from_utf8_lossy(extra.take(len)?).into_owned();
+    extra.take((4 - (len + 1) % 4) % 4)?;
+
+    let mut section = 0;
+    while extra.remaining() >= 12 {
+        let signature = extra.take(4)?;
+        if signature != b"8BIM" && signature != b"8B64" {
+            break;
+        }
+        let key = extra.take(4)?;
+        let len = extra.u32()? as usize;
+        let mut block = Reader::new(extra.take(len)?);
+        match key {
+            b"luni" => {
+                let count = block.u32()? as usize;
+                let units =
+                    (0..count).map(|_| block.u16()).collect::<VideoEditorResult<Vec<_>>>()?;
+                name = String::from_utf16_lossy(&units).trim_end_matches('\0').to_string();
+            },
+            b"lsct" | b"lsdk" => section = block.u32()?,
+            _ => {},
+        }
+    }
+
+    Ok(LayerRecord {
+        top,
+        left,
+        bottom,
+        right,
+        channels,
+        blend_mode,
+        opacity,
+        clipped,
+        visible: flags & LAYER_FLAG_HIDDEN == 0,
+        name,
+        section,
+        mask,
+    })
+}
+
+/// Maps a PSD blend mode key. Pass-through and modes without an EVLF
+/// equivalent fall back to normal.
+fn blend_mode_from_key(key: &[u8]) -> BlendMode {
+    match key {
+        b"mul " => BlendMode::Multiply,
+        b"scrn" => BlendMode::Screen,
+        b"over" => BlendMode::Overlay,
+        b"dark" => BlendMode::Darken,
+        b"lite" => BlendMode::Lighten,
+        b"div " => BlendMode::ColorDodge,
+        b"idiv" => BlendMode::ColorBurn,
+        b"hLit" => BlendMode::HardLight,
+        b"sLit" => BlendMode::SoftLight,
+        b"diff" => BlendMode::Difference,
+        b"smud" => BlendMode::Exclusion,
+        b"hue " => BlendMode::Hue,
+        b"sat " => BlendMode::Saturation,
+        b"colr" => BlendMode::Color,
+        b"lum " => BlendMode::Luminosity,
+        b"lddg" => BlendMode::Add,
+        b"fsub" => BlendMode::Subtract,
+        _ => BlendMode::Normal,
+    }
+}
+
+fn dimension(from: i32, to: i32) -> usize {
+    usize::try_from(i64::from(to) - i64::from(from)).unwrap_or(0)
+}
+
+/// Checks a `(top, left, bottom, right)` layer or mask rectangle: it may
+/// not be inverted, larger than the format allows, or lie further than
+/// that outside the document.
+fn check_rect(
+    (top, left, bottom, right): (i32, i32, i32, i32), doc_width: u32, doc_height: u32,
+) -> VideoEditorResult<()> {
+    let max = i64::from(MAX_DIMENSION);
+    let within = |from: i32, to: i32, size: u32| {
+        let (from, to) = (i64::from(from), i64::from(to));
+        from <= to && to - from <= max && from >= -max && to <= i64::from(size) + max
+    };
+    if within(left, right, doc_width) && within(top, bottom, doc_height) {
+        Ok(())
+    } else {
+        Err(VideoEditorError::conversion(format!(
+            "PSD rectangle ({top}, {left}, {bottom}, {right}) is outside the document bounds"
+        )))
+    }
+}
+
+/// Interleaves color planes and an optional alpha plane into RGBA8.
+fn interleave(color: [&[u8]; 3], alpha: Option<&[u8]>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(color[0].len() * 4);
+    for i in 0..color[0].len() {
+        out.extend_from_slice(&[
+            color[0][i],
+            color[1][i],
+            color[2][i],
+            alpha.map_or(255, |a| a[i]),
+        ]);
+    }
+    out
+}
+
+/// Decodes `planes` planes of `width` x `height` bytes preceded by a
+/// compression tag.
+fn decode_planes(
+    r: &mut Reader<'_>, width: usize, height: usize, planes: usize,
+) -> VideoEditorResult<Vec<Vec<u8>>> {
+    let compression = r.u16()?;
+    let size = width.checked_mul(height).ok_or_else(truncated)?;
+    if size == 0 {
+        return Ok(vec![Vec::new(); planes]);
+    }
+    // Check sizes against the bytes left before allocating anything
+    let available = r.remaining();
+    match compression {
+        0 => {
+            if size.checked_mul(planes).is_none_or(|total| total > available) {
+                return Err(truncated());
+            }
+            (0..planes).map(|_| r.take(size).map(<[u8]>::to_vec)).collect()
+        },
+        1 => {
+            let rows = planes.checked_mul(height).ok_or_else(truncated)?;
+            let packed = available.saturating_sub(rows * 2);
+            if rows * 2 > available
+                || size.checked_mul(planes).is_none_or(|total| total / MAX_PACKBITS_RATIO > packed)
+            {
+                return Err(truncated());
+            }
+            let counts = (0..planes * height)
+                .map(|_| r.u16().map(usize::from))
+                .collect::<VideoEditorResult<Vec<_>>>()?;
+            counts
+                .chunks(height)
+                .map(|rows| {
+                    let mut plane = Vec::with_capacity(size);
+                    for &count in rows {
+                        unpack_bits(r.take(count)?, width, &mut plane)?;
+                    }
+                    Ok(plane)
+                })
+                .collect()
+        },
+        2 | 3 => Err(VideoEditorError::unsupported_format(
+            "ZIP-compressed PSD channels are not supported",
+        )),
+        other => Err(VideoEditorError::conversion(format!("Unknown PSD compression {other}"))),
+    }
+}
+
+/// Decodes one PackBits row of `width` bytes.
+fn unpack_bits(mut src: &[u8], width: usize, out: &mut Vec<u8>) -> VideoEditorResult<()> {
+    let end = out.len() + width;
+    while let [header, rest @ ..] = src {
+        let n = *header as i8;
+        if n >= 0 {
+            let len = n as usize + 1;
+            let literal = rest.get(..len).ok_or_else(truncated)?;
+            out.extend_from_slice(literal);
+            src = &rest[len..];
+        } else if n != -128 {
+            let value = *rest.first().ok_or_else(truncated)?;
+            out.extend(std::iter::repeat_n(value, (1 - isize::from(n)) as usize));
+            src = &rest[1..];
+        } else {
+            src = rest;
+        }
+    }
+    if out.len() != end {
+        return Err(VideoEditorError::conversion("PSD row length mismatch"));
+    }
+    Ok(())
+}
+
+fn truncated() -> VideoEditorError {
+    VideoEditorError::conversion("Truncated PSD data")
+}
+
+/// Big-endian cursor over PSD data.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos:  usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn take(&mut self, len: usize) -> VideoEditorResult<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos + len).ok_or_else(truncated)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> VideoEditorResult<[u8; N]> {
+        let mut out = [0; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> VideoEditorResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> VideoEditorResult<u16> {
+        self.array().map(u16::from_be_bytes)
+    }
+
+    fn i16(&mut self) -> VideoEditorResult<i16> {
+        self.array().map(i16::from_be_bytes)
+    }
+
+    fn u32(&mut self) -> VideoEditorResult<u32> {
+        self.array().map(u32::from_be_bytes)
+    }
+
+    fn i32(&mut self) -> VideoEditorResult<i32> {
+        self.array().map(i32::from_be_bytes)
+    }
+}
+
+#[cfg(all(test, feature = "full-tests"))]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Layer description for [`build_psd`].
+    pub(crate) struct TestLayer {
+        pub name:    &'static str,
+        pub rect:    (i32, i32, i32, i32),
+        pub blend:   &'static [u8; 4],
+        pub opacity: u8,
+        pub hidden:  bool,
+        pub section: Option<u32>,
+        pub fill:    [u8; 4],
+        pub rle:     bool,
+    }
+
+    impl TestLayer {
+        pub(crate) fn pixel(name: &'static str, rect: (i32, i32, i32, i32), fill: [u8; 4]) -> Self {
+            Self {
+                name,
+                rect,
+                blend: b"norm",
+                opacity: 255,
+                hidden: false,
+                section: None,
+                fill,
+                rle: false,
+            }
+        }
+
+        pub(crate) fn divider(name: &'static str, section: u32) -> Self {
+            Self { section: Some(section), ..Self::pixel(name, (0, 0, 0, 0), [0; 4]) }
+        }
+    }
+
+    fn channel_data(value: u8, width: usize, height: usize, rle: bool) -> Vec<u8> {
+        let mut out = Vec::new();
+        if rle {
+            out.extend_from_slice(&1u16.to_be_bytes());
+            // One repeat run per row
+            for _ in 0..height {
+                out.extend_from_slice(&2u16.to_be_bytes());
+            }
+            for _ in 0..height {
+                out.extend_from_slice(&[(1 - width as i32) as i8 as u8, value]);
+            }
+        } else {
+            out.extend_from_slice(&0u16.to_be_bytes());
+            out.extend(std::iter::repeat_n(value, width * height));
+        }
+        out
+    }
+
+    /// Builds an 8-bit RGB PSD with solid-color layers (bottom first).
+    pub(crate) fn build_psd(width: u32, height: u32, layers: &[TestLayer]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(PSD_SIGNATURE);
+        out.extend_from_slice(&1u16.to_be_bytes());
+        out.extend_from_slice(&[0; 6]);
+        out.extend_from_slice(&4u16.to_be_bytes());
+        out.extend_from_slice(&height.to_be_bytes());
+        out.extend_from_slice(&width.to_be_bytes());
+        out.extend_from_slice(&8u16.to_be_bytes());
+        out.extend_from_slice(&COLOR_MODE_RGB.to_be_bytes());
+        out.extend_from_slice(&0u32.to_be_bytes());
+        out.extend_from_slice(&0u32.to_be_bytes());
+
+        let mut records = Vec::new();
+        let mut channels = Vec::new();
+        for layer in layers {
+            let (top, left, bottom, right) = layer.rect;
+            let (w, h) = (dimension(left, right), dimension(top, bottom));
+            for v in [top, left, bottom, right] {
+                records.extend_from_slice(&v.to_be_bytes());
+            }
+            records.extend_from_slice(&4u16.to_be_bytes());
+            for (id, value) in
+                [(-1i16, layer.fill[3]), (0, layer.fill[0]), (1, layer.fill[1]), (2, layer.fill[2])]
+            {
+                let data = channel_data(value, w, h, layer.rle);
+                records.extend_from_slice(&id.to_be_bytes());
+                records.extend_from_slice(&(data.len() as u32).to_be_bytes());
+                channels.extend(data);
+            }
+            records.extend_from_slice(b"8BIM");
+            records.extend_from_slice(layer.blend);
+            records.extend_from_slice(&[layer.opacity, 0, if layer.hidden { 2 } else { 0 }, 0]);
+
+            let mut extra = Vec::new();
+            extra.extend_from_slice(&0u32.to_be_bytes());
+            extra.extend_from_slice(&0u32.to_be_bytes());
+            extra.push(layer.name.len() as u8);
+            extra.extend_from_slice(layer.name.as_bytes());
+            while !extra.len().is_multiple_of(4) {
+                extra.push(0);
+            }
+            if let Some(section) = layer.section {
+                extra.extend_from_slice(b"8BIMlsct");
+                extra.extend_from_slice(&4u32.to_be_bytes());
+                extra.extend_from_slice(&section.to_be_bytes());
+            }
+            records.extend_from_slice(&(extra.len() as u32).to_be_bytes());
+            records.extend(extra);
+        }
+
+        let mut info = (layers.len() as i16).to_be_bytes().to_vec();
+        info.extend(records);
+        info.extend(channels);
+        if !info.len().is_multiple_of(2) {
+            info.push(0);
+        }
+        out.extend_from_slice(&(info.len() as u32 + 4).to_be_bytes());
+        out.extend_from_slice(&(info.len() as u32).to_be_bytes());
+        out.extend(info);
+
+        // Composite: mid-gray, opaque
+        let size = (width * height) as usize;
+        out.extend_from_slice(&0u16.to_be_bytes());
+        for value in [128, 128, 128, 255] {
+            out.extend(std::iter::repeat_n(value, size));
+        }
+        out
+    }
+
+    #[test]
+    fn test_parse_layer_tree() {
+        let bytes = build_psd(
+            4,
+            2,
+            &[
+                TestLayer::pixel("Background", (0, 0, 2, 4), [10, 20, 30, 255]),
+                TestLayer::divider("</Layer group>", SECTION_END),
+                TestLayer {
+                    blend: b"mul ",
+                    opacity: 128,
+                    rle: true,
+                    ..TestLayer::pixel("Shade", (1, 1, 2, 3), [200, 0, 0, 100])
+                },
+                TestLayer { hidden: true, ..TestLayer::divider("Group", SECTION_OPEN) },
+            ],
+        );
+        let doc = PsdDocument::parse(&bytes).expect("test assertion");
+
+        assert_eq!((doc.width, doc.height), (4, 2));
+        assert_eq!(doc.composite.len(), 4 * 2 * 4);
+        assert_eq!(&doc.composite[..4], &[128, 128, 128, 255]);
+        assert_eq!(doc.layers.len(), 3);
+
+        let background = &doc.layers[0];
+        assert_eq!(background.kind, PsdLayerKind::Pixel);
+        assert_eq!(background.parent, None);
+        assert_eq!(&background.pixels[..4], &[10, 20, 30, 255]);
+
+        let shade = &doc.layers[1];
+        assert_eq!(shade.name, "Shade");
+        assert_eq!((shade.left, shade.top, shade.width, shade.height), (1, 1, 2, 1));
+        assert_eq!(shade.blend_mode, BlendMode::Multiply);
+        assert_eq!(shade.opacity, 128);
+        assert_eq!(shade.pixels, vec![200, 0, 0, 100, 200, 0, 0, 100]);
+        assert_eq!(shade.parent, Some(2));
+
+        assert_eq!(doc.layers[2].kind, PsdLayerKind::Group { open: true });
+        assert!(doc.is_visible(0));
+        assert!(!doc.is_visible(1));
+        assert_eq!(doc.pixel_layers().count(), 2);
+        assert_eq!(doc.children(2).map(|(i, _)| i).collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn test_rejects_malformed_rects() {
+        // Offset of the first layer record's rectangle
+        const RECT: usize = 44;
+        for rle in [false, true] {
+            let layers =
+                [TestLayer { rle, ..TestLayer::pixel("Layer", (0, 0, 2, 4), [1, 2, 3, 255]) }];
+            let parse_with_rect = |rect: [i32; 4]| {
+                let mut bytes = build_psd(4, 2, &layers);
+                for (i, v) in rect.iter().enumerate() {
+                    bytes[RECT + i * 4..RECT + i * 4 + 4].copy_from_slice(&v.to_be_bytes());
+                }
+                PsdDocument::parse(&bytes)
+            };
+            assert!(parse_with_rect([0, 0, 2, 4]).is_ok());
+            // Layers may hang over the canvas edge
+            assert!(parse_with_rect([-1, -1, 1, 3]).is_ok());
+            // Inverted, over the format limit, far outside the document
+            assert!(parse_with_rect([2, 0, 0, 4]).is_err());
+            assert!(parse_with_rect([0, 0, 40_000, 40_000]).is_err());
+            assert!(parse_with_rect([-100_000, 0, -99_998, 4]).is_err());
+            // Within the limits but larger than the channel data
+            assert!(parse_with_rect([0, 0, 20_000, 20_000]).is_err());
+        }
+
+        let mut bytes = build_psd(4, 2, &[]);
+        bytes[18..22].copy_from_slice(&40_000u32.to_be_bytes());
+        assert!(PsdDocument::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_rejects_unsupported_documents() {
+        assert!(PsdDocument::parse(b"GIF89a").is_err());
+        let mut bytes = build_psd(1, 1, &[]);
+        bytes[22..24].copy_from_slice(&16u16.to_be_bytes());
+        assert!(PsdDocument::parse(&bytes).is_err());
+        let bytes = build_psd(2, 2, &[]);
+        assert!(PsdDocument::parse(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
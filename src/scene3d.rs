@@ -0,0 +1,716 @@
+//! 3D layer content.
+//!
+//! A [`Scene3D`] is a node hierarchy of triangle meshes with PBR materials,
+//! embedded textures, skins and keyframe animations, as imported from
+//! glTF. [`Scene3D::draw_list`] flattens it into world-space draws and
+//! [`Scene3D::view_projection`] supplies the camera the GPU pipeline
+//! renders the layer through.
+
+/// Column-major 4x4 matrix (glTF convention).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4(pub [f32; 16]);
+
+impl Mat4 {
+    /// Identity matrix.
+    pub const IDENTITY: Self =
+        Self([1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+
+    /// Element at `row`, `column`.
+    #[must_use]
+    pub fn at(&self, row: usize, column: usize) -> f32 {
+        self.0[column * 4 + row]
+    }
+
+    /// Matrix product `self * rhs` (`rhs` applied first).
+    #[must_use]
+    pub fn mul(&self, rhs: &Self) -> Self {
+        let mut out = [0.0; 16];
+        for column in 0..4 {
+            for row in 0..4 {
+                out[column * 4 + row] = (0..4).map(|k| self.at(row, k) * rhs.at(k, column)).sum();
+            }
+        }
+        Self(out)
+    }
+
+    /// Translation, rotation (unit quaternion `xyzw`) and scale.
+    #[must_use]
+    pub fn from_trs(t: [f32; 3], [x, y, z, w]: [f32; 4], s: [f32; 3]) -> Self {
+        let (xx, yy, zz) = (x * x, y * y, z * z);
+        let (xy, xz, yz) = (x * y, x * z, y * z);
+        let (wx, wy, wz) = (w * x, w * y, w * z);
+        Self([
+            (1.0 - 2.0 * (yy + zz)) * s[0],
+            2.0 * (xy + wz) * s[0],
+            2.0 * (xz - wy) * s[0],
+            0.0,
+            2.0 * (xy - wz) * s[1],
+            (1.0 - 2.0 * (xx + zz)) * s[1],
+            2.0 * (yz + wx) * s[1],
+            0.0,
+            2.0 * (xz + wy) * s[2],
+            2.0 * (yz - wx) * s[2],
+            (1.0 - 2.0 * (xx + yy)) * s[2],
+            0.0,
+            t[0],
+            t[1],
+            t[2],
+            1.0,
+        ])
+    }
+
+    /// Right-handed perspective projection with a `[-1, 1]` depth range.
+    #[must_use]
+    pub fn perspective(yfov: f32, aspect: f32, znear: f32, zfar: f32) -> Self {
+        let f = 1.0 / (yfov / 2.0).tan();
+        let mut m = [0.0; 16];
+        m[0] = f / aspect.max(f32::EPSILON);
+        m[5] = f;
+        m[10] = (zfar + znear) / (znear - zfar);
+        m[11] = -1.0;
+        m[14] = 2.0 * zfar * znear / (znear - zfar);
+        Self(m)
+    }
+
+    /// Orthographic projection with half extents `xmag`, `ymag`.
+    #[must_use]
+    pub fn orthographic(xmag: f32, ymag: f32, znear: f32, zfar: f32) -> Self {
+        let mut m = Self::IDENTITY.0;
+        m[0] = 1.0 / xmag;
+        m[5] = 1.0 / ymag;
+        m[10] = 2.0 / (znear - zfar);
+        m[14] = (zfar + znear) / (znear - zfar);
+        Self(m)
+    }
+
+    /// Transforms a point (with perspective divide).
+    #[must_use]
+    pub fn transform_point(&self, [x, y, z]: [f32; 3]) -> [f32; 3] {
+        let row =
+            |r: usize| self.at(r, 0) * x + self.at(r, 1) * y + self.at(r, 2) * z + self.at(r, 3);
+        let w = row(3);
+        let w = if w.abs() > f32::EPSILON { w } else { 1.0 };
+        [row(0) / w, row(1) / w, row(2) / w]
+    }
+
+    /// Inverse, or `None` if singular.
+    #[must_use]
+    pub fn inverse(&self) -> Option<Self> {
+        let m = &self.0;
+        let mut inv = [0.0f32; 16];
+        inv[0] = m[5] * m[10] * m[15] - m[5] * m[11] * m[14] - m[9] * m[6] * m[15]
+            + m[9] * m[7] * m[14]
+            + m[13] * m[6] * m[11]
+            - m[13] * m[7] * m[10];
+        inv[4] = -m[4] * m[10] * m[15] + m[4] * m[11] * m[14] + m[8] * m[6] * m[15]
+            - m[8] * m[7] * m[14]
+            - m[12] * m[6] * m[11]
+            + m[12] * m[7] * m[10];
+        inv[8] = m[4] * m[9] * m[15] - m[4] * m[11] * m[13] - m[8] * m[5] * m[15]
+            + m[8] * m[7] * m[13]
+            + m[12] * m[5] * m[11]
+            - m[12] * m[7] * m[9];
+        inv[12] = -m[4] * m[9] * m[14] + m[4] * m[10] * m[13] + m[8] * m[5] * m[14]
+            - m[8] * m[6] * m[13]
+            - m[12] * m[5] * m[10]
+            + m[12] * m[6] * m[9];
+        inv[1] = -m[1] * m[10] * m[15] + m[1] * m[11] * m[14] + m[9] * m[2] * m[15]
+            - m[9] * m[3] * m[14]
+            - m[13] * m[2] * m[11]
+            + m[13] * m[3] * m[10];
+        inv[5] = m[0] * m[10] * m[15] - m[0] * m[11] * m[14] - m[8] * m[2] * m[15]
+            + m[8] * m[3] * m[14]
+            + m[12] * m[2] * m[11]
+            - m[12] * m[3] * m[10];
+        inv[9] = -m[0] * m[9] * m[15] + m[0] * m[11] * m[13] + m[8] * m[1] * m[15]
+            - m[8] * m[3] * m[13]
+            - m[12] * m[1] * m[11]
+            + m[12] * m[3] * m[9];
+        inv[13] = m[0] * m[9] * m[14] - m[0] * m[10] * m[13] - m[8] * m[1] * m[14]
+            + m[8] * m[2] * m[13]
+            + m[12] * m[1] * m[10]
+            - m[12] * m[2] * m[9];
+        inv[2] = m[1] * m[6] * m[15] - m[1] * m[7] * m[14] - m[5] * m[2] * m[15]
+            + m[5] * m[3] * m[14]
+            + m[13] * m[2] * m[7]
+            - m[13] * m[3] * m[6];
+        inv[6] = -m[0] * m[6] * m[15] + m[0] * m[7] * m[14] + m[4] * m[2] * m[15]
+            - m[4] * m[3] * m[14]
+            - m[12] * m[2] * m[7]
+            + m[12] * m[3] * m[6];
+        inv[10] = m[0] * m[5] * m[15] - m[0] * m[7] * m[13] - m[4] * m[1] * m[15]
+            + m[4] * m[3] * m[13]
+            + m[12] * m[1] * m[7]
+            - m[12] * m[3] * m[5];
+        inv[14] = -m[0] * m[5] * m[14] + m[0] * m[6] * m[13] + m[4] * m[1] * m[14]
+            - m[4] * m[2] * m[13]
+            - m[12] * m[1] * m[6]
+            + m[12] * m[2] * m[5];
+        inv[3] = -m[1] * m[6] * m[11] + m[1] * m[7] * m[10] + m[5] * m[2] * m[11]
+            - m[5] * m[3] * m[10]
+            - m[9] * m[2] * m[7]
+            + m[9] * m[3] * m[6];
+        inv[7] = m[0] * m[6] * m[11] - m[0] * m[7] * m[10] - m[4] * m[2] * m[11]
+            + m[4] * m[3] * m[10]
+            + m[8] * m[2] * m[7]
+            - m[8] * m[3] * m[6];
+        inv[11] = -m[0] * m[5] * m[11] + m[0] * m[7] * m[9] + m[4] * m[1] * m[11]
+            - m[4] * m[3] * m[9]
+            - m[8] * m[1] * m[7]
+            + m[8] * m[3] * m[5];
+        inv[15] = m[0] * m[5] * m[10] - m[0] * m[6] * m[9] - m[4] * m[1] * m[10]
+            + m[4] * m[2] * m[9]
+            + m[8] * m[1] * m[6]
+            - m[8] * m[2] * m[5];
+
+        let det = m[0] * inv[0] + m[1] * inv[4] + m[2] * inv[8] + m[3] * inv[12];
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        Some(Self(inv.map(|v| v / det)))
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Local node transform as translation, rotation and scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeTransform {
+    /// Translation.
+    pub translation: [f32; 3],
+    /// Rotation quaternion `xyzw`.
+    pub rotation:    [f32; 4],
+    /// Scale.
+    pub scale:       [f32; 3],
+}
+
+impl NodeTransform {
+    /// Identity transform.
+    pub const IDENTITY: Self =
+        Self { translation: [0.0; 3], rotation: [0.0, 0.0, 0.0, 1.0], scale: [1.0; 3] };
+
+    /// Matrix form.
+    #[must_use]
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_trs(self.translation, self.rotation, self.scale)
+    }
+}
+
+impl Default for NodeTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Node of the scene hierarchy.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SceneNode {
+    /// Node name.
+    pub name:      String,
+    /// Local transform.
+    pub transform: NodeTransform,
+    /// Local matrix replacing `transform` (nodes that are not animated).
+    pub matrix:    Option<Mat4>,
+    /// Child node indices.
+    pub children:  Vec<usize>,
+    /// Mesh index.
+    pub mesh:      Option<usize>,
+    /// Skin index.
+    pub skin:      Option<usize>,
+    /// Camera index.
+    pub camera:    Option<usize>,
+}
+
+/// Triangle list with vertex attributes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshPrimitive {
+    /// Vertex positions.
+    pub positions: Vec<[f32; 3]>,
+    /// Vertex normals (empty if absent).
+    pub normals:   Vec<[f32; 3]>,
+    /// First texture coordinate set (empty if absent).
+    pub uvs:       Vec<[f32; 2]>,
+    /// Skin joint indices (empty if not skinned).
+    pub joints:    Vec<[u16; 4]>,
+    /// Skin joint weights (empty if not skinned).
+    pub weights:   Vec<[f32; 4]>,
+    /// Triangle indices.
+    pub indices:   Vec<u32>,
+    /// Material index.
+    pub material:  Option<usize>,
+}
+
+/// Named set of primitives.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh3D {
+    /// Mesh name.
+    pub name:       String,
+    /// Primitives.
+    pub primitives: Vec<MeshPrimitive>,
+}
+
+/// Material alpha handling.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum AlphaMode {
+    /// Alpha ignored.
+    #[default]
+    Opaque,
+    /// Alpha tested against a cutoff.
+    Mask(f32),
+    /// Alpha blended.
+    Blend,
+}
+
+/// Metallic-roughness PBR material.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PbrMaterial {
+    /// Material name.
+    pub name:                       String,
+    /// Linear base color factor (RGBA).
+    pub base_color:                 [f32; 4],
+    /// Base color texture index.
+    pub base_color_texture:         Option<usize>,
+    /// Metallic factor.
+    pub metallic:                   f32,
+    /// Roughness factor.
+    pub roughness:                  f32,
+    /// Metallic (B) / roughness (G) texture index.
+    pub metallic_roughness_texture: Option<usize>,
+    /// Tangent-space normal map texture index.
+    pub normal_texture:             Option<usize>,
+    /// Emissive color factor.
+    pub emissive:                   [f32; 3],
+    /// Emissive texture index.
+    pub emissive_texture:           Option<usize>,
+    /// Alpha mode.
+    pub alpha_mode:                 AlphaMode,
+    /// Render back faces.
+    pub double_sided:               bool,
+}
+
+impl Default for PbrMaterial {
+    fn default() -> Self {
+        Self {
+            name:                       String::new(),
+            base_color:                 [1.0; 4],
+            base_color_texture:         None,
+            metallic:                   1.0,
+            roughness:                  1.0,
+            metallic_roughness_texture: None,
+            normal_texture:             None,
+            emissive:                   [0.0; 3],
+            emissive_texture:           None,
+            alpha_mode:                 AlphaMode::Opaque,
+            double_sided:               false,
+        }
+    }
+}
+
+/// Encoded texture image.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Texture3D {
+    /// Image name.
+    pub name:      String,
+    /// MIME type (`image/png`, `image/jpeg`).
+    pub mime_type: String,
+    /// Encoded image bytes.
+    pub data:      Vec<u8>,
+}
+
+/// Joint hierarchy for skinned meshes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Skin {
+    /// Skin name.
+    pub name:          String,
+    /// Joint node indices.
+    pub joints:        Vec<usize>,
+    /// Inverse bind matrix per joint.
+    pub inverse_binds: Vec<Mat4>,
+}
+
+/// Camera projection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraProjection {
+    /// Perspective with vertical field of view in radians.
+    Perspective { yfov: f32, aspect: Option<f32>, znear: f32, zfar: Option<f32> },
+    /// Orthographic with half extents.
+    Orthographic { xmag: f32, ymag: f32, znear: f32, zfar: f32 },
+}
+
+impl CameraProjection {
+    /// Projection matrix for an output aspect ratio.
+    #[must_use]
+    pub fn matrix(&self, output_aspect: f32) -> Mat4 {
+        match *self {
+            Self::Perspective { yfov, aspect, znear, zfar } => Mat4::perspective(
+                yfov,
+                aspect.unwrap_or(output_aspect),
+                znear,
+                zfar.unwrap_or(znear * 10_000.0),
+            ),
+            Self::Orthographic { xmag, ymag, znear, zfar } => {
+                Mat4::orthographic(xmag, ymag, znear, zfar)
+            },
+        }
+    }
+}
+
+/// Animated node property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationPath {
+    /// Translation.
+    Translation,
+    /// Rotation.
+    Rotation,
+    /// Scale.
+    Scale,
+    /// Morph target weights (imported, not applied).
+    Weights,
+}
+
+impl AnimationPath {
+    fn components(self) -> usize {
+        match self {
+            Self::Rotation => 4,
+            Self::Translation | Self::Scale => 3,
+            Self::Weights => 1,
+        }
+    }
+}
+
+/// Keyframe interpolation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnimationInterpolation {
+    /// Linear (spherical for rotations).
+    #[default]
+    Linear,
+    /// Hold until the next key.
+    Step,
+    /// Cubic Hermite with in/out tangents stored around each value.
+    CubicSpline,
+}
+
+/// Keyframes driving one node property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationChannel {
+    /// Target node.
+    pub node:          usize,
+    /// Target property.
+    pub path:          AnimationPath,
+    /// Interpolation.
+    pub interpolation: AnimationInterpolation,
+    /// Key times in seconds.
+    pub times:         Vec<f32>,
+    /// Flattened key values.
+    pub values:        Vec<f32>,
+}
+
+impl AnimationChannel {
+    /// Value at `time`, clamped to the key range.
+    fn sample(&self, time: f32) -> Vec<f32> {
+        let n = self.path.components();
+        let cubic = self.interpolation == AnimationInterpolation::CubicSpline;
+        // Cubic splines store [in tangent, value, out tangent] per key
+        let stride = if cubic { n * 3 } else { n };
+        let value = |key: usize, part: usize| -> &[f32] {
+            let start = key * stride + if cubic { part * n } else { 0 };
+            self.values.get(start..start + n).unwrap_or(&[])
+        };
+
+        let Some(last) = self.times.len().checked_sub(1) else {
+            return Vec::new();
+        };
+        if time <= self.times[0] {
+            return value(0, 1).to_vec();
+        }
+        if time >= self.times[last] {
+            return value(last, 1).to_vec();
+        }
+        let next = self.times.partition_point(|&t| t <= time);
+        let key = next - 1;
+        let (t0, t1) = (self.times[key], self.times[next]);
+        let dt = t1 - t0;
+        let t = if dt > 0.0 { (time - t0) / dt } else { 0.0 };
+
+        let mut out: Vec<f32> = match self.interpolation {
+            AnimationInterpolation::Step => value(key, 1).to_vec(),
+            AnimationInterpolation::Linear if self.path == AnimationPath::Rotation => {
+                return slerp(value(key, 1), value(next, 1), t);
+            },
+            AnimationInterpolation::Linear => {
+                value(key, 1).iter().zip(value(next, 1)).map(|(a, b)| a + (b - a) * t).collect()
+            },
+            AnimationInterpolation::CubicSpline => {
+                let (t2, t3) = (t * t, t * t * t);
+                let (p0, m0) = (value(key, 1), value(key, 2));
+                let (p1, m1) = (value(next, 1), value(next, 0));
+                (0..p0.len().min(p1.len()).min(m0.len()).min(m1.len()))
+                    .map(|i| {
+                        (2.0 * t3 - 3.0 * t2 + 1.0) * p0[i]
+                            + (t3 - 2.0 * t2 + t) * dt * m0[i]
+                            + (-2.0 * t3 + 3.0 * t2) * p1[i]
+                            + (t3 - t2) * dt * m1[i]
+                    })
+                    .collect()
+            },
+        };
+        if self.path == AnimationPath::Rotation {
+            normalize(&mut out);
+        }
+        out
+    }
+}
+
+fn normalize(q: &mut [f32]) {
+    let len = q.iter().map(|c| c * c).sum::<f32>().sqrt();
+    if len > f32::EPSILON {
+        q.iter_mut().for_each(|c| *c /= len);
+    }
+}
+
+fn slerp(a: &[f32], b: &[f32], t: f32) -> Vec<f32> {
+    let mut dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let sign = if dot < 0.0 { -1.0 } else { 1.0 };
+    dot *= sign;
+    let (wa, wb) = if dot > 0.9995 {
+        (1.0 - t, t)
+    } else {
+        let theta = dot.clamp(-1.0, 1.0).acos();
+        let sin = theta.sin();
+        (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+    };
+    let mut out: Vec<f32> = a.iter().zip(b).map(|(x, y)| wa * x + wb * sign * y).collect();
+    normalize(&mut out);
+    out
+}
+
+/// Keyframe animation clip.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Animation3D {
+    /// Animation name.
+    pub name:     String,
+    /// Channels.
+    pub channels: Vec<AnimationChannel>,
+}
+
+impl Animation3D {
+    /// Length in seconds.
+    #[must_use]
+    pub fn duration(&self) -> f32 {
+        self.channels.iter().filter_map(|c| c.times.last()).fold(0.0, |a, &b| a.max(b))
+    }
+}
+
+/// Mesh primitive placed in world space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DrawItem {
+    /// Node drawing the mesh.
+    pub node:      usize,
+    /// Mesh index.
+    pub mesh:      usize,
+    /// Primitive index within the mesh.
+    pub primitive: usize,
+    /// Node world transform.
+    pub transform: Mat4,
+    /// Material index (`None` = default material).
+    pub material:  Option<usize>,
+}
+
+/// Imported 3D scene.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scene3D {
+    /// All nodes.
+    pub nodes:      Vec<SceneNode>,
+    /// Root nodes of the displayed scene.
+    pub roots:      Vec<usize>,
+    /// Meshes.
+    pub meshes:     Vec<Mesh3D>,
+    /// Materials.
+    pub materials:  Vec<PbrMaterial>,
+    /// Textures.
+    pub textures:   Vec<Texture3D>,
+    /// Skins.
+    pub skins:      Vec<Skin>,
+    /// Cameras.
+    pub cameras:    Vec<CameraProjection>,
+    /// Animations.
+    pub animations: Vec<Animation3D>,
+}
+
+impl Scene3D {
+    /// Local node transforms of an animation at `time` seconds.
+    #[must_use]
+    pub fn pose(&self, animation: usize, time: f32) -> Vec<NodeTransform> {
+        let mut pose: Vec<NodeTransform> = self.nodes.iter().map(|n| n.transform).collect();
+        let Some(animation) = self.animations.get(animation) else {
+            return pose;
+        };
+        for channel in &animation.channels {
+            let Some(transform) = pose.get_mut(channel.node) else {
+                continue;
+            };
+            let value = channel.sample(time);
+            match (channel.path, value.as_slice()) {
+                (AnimationPath::Translation, &[x, y, z]) => transform.translation = [x, y, z],
+                (AnimationPath::Rotation, &[x, y, z, w]) => transform.rotation = [x, y, z, w],
+                (AnimationPath::Scale, &[x, y, z]) => transform.scale = [x, y, z],
+                _ => {},
+            }
+        }
+        pose
+    }
+
+    /// World matrix of every node, optionally using a sampled pose.
+    #[must_use]
+    pub fn world_transforms(&self, pose: Option<&[NodeTransform]>) -> Vec<Mat4> {
+        let mut world = vec![Mat4::IDENTITY; self.nodes.len()];
+        let mut visited = vec![false; self.nodes.len()];
+        let mut stack: Vec<(usize, Mat4)> =
+            self.roots.iter().map(|&root| (root, Mat4::IDENTITY)).collect();
+        while let Some((index, parent)) = stack.pop() {
+            let Some(node) = self.nodes.get(index) else {
+                continue;
+            };
+            // Malformed files can contain cycles
+            if std::mem::replace(&mut visited[index], true) {
+                continue;
+            }
+            let local = match (pose.and_then(|p| p.get(index)), node.matrix) {
+                (Some(transform), _) => transform.matrix(),
+                (None, Some(matrix)) => matrix,
+                (None, None) => node.transform.matrix(),
+            };
+            world[index] = parent.mul(&local);
+            stack.extend(node.children.iter().map(|&child| (child, world[index])));
+        }
+        world
+    }
+
+    /// World-space draws of every mesh primitive reachable from the roots.
+    #[must_use]
+    pub fn draw_list(&self, pose: Option<&[NodeTransform]>) -> Vec<DrawItem> {
+        let world = self.world_transforms(pose);
+        let mut draws = Vec::new();
+        for (node_index, node) in self.nodes.iter().enumerate() {
+            let Some(mesh_index) = node.mesh else {
+                continue;
+            };
+            if !self.is_reachable(node_index) {
+                continue;
+            }
+            let Some(mesh) = self.meshes.get(mesh_index) else {
+                continue;
+            };
+            for (primitive_index, primitive) in mesh.primitives.iter().enumerate() {
+                draws.push(DrawItem {
+                    node:      node_index,
+                    mesh:      mesh_index,
+                    primitive: primitive_index,
+                    transform: world[node_index],
+                    material:  primitive.material,
+                });
+            }
+        }
+        draws
+    }
+
+    fn is_reachable(&self, node: usize) -> bool {
+        let mut stack = self.roots.clone();
+        let mut visited = vec![false; self.nodes.len()];
+        while let Some(index) = stack.pop() {
+            if index == node {
+                return true;
+            }
+            if let Some(n) = self.nodes.get(index)
+                && !std::mem::replace(&mut visited[index], true)
+            {
+                stack.extend_from_slice(&n.children);
+            }
+        }
+        false
+    }
+
+    /// Joint matrices (`world * inverse_bind`) of a skin for vertex skinning.
+    #[must_use]
+    pub fn joint_matrices(&self, skin: usize, world: &[Mat4]) -> Vec<Mat4> {
+        let Some(skin) = self.skins.get(skin) else {
+            return Vec::new();
+        };
+        skin.joints
+            .iter()
+            .enumerate()
+            .map(|(i, &joint)| {
+                let inverse_bind = skin.inverse_binds.get(i).copied().unwrap_or_default();
+                world.get(joint).copied().unwrap_or_default().mul(&inverse_bind)
+            })
+            .collect()
+    }
+
+    /// World-space bounds of all draws as `[min, max]`.
+    #[must_use]
+    pub fn bounds(&self, pose: Option<&[NodeTransform]>) -> Option<[[f32; 3]; 2]> {
+        let mut bounds: Option<[[f32; 3]; 2]> = None;
+        for draw in self.draw_list(pose) {
+            let primitive = &self.meshes[draw.mesh].primitives[draw.primitive];
+            for &position in &primitive.positions {
+                let p = draw.transform.transform_point(position);
+                let [min, max] = bounds.get_or_insert([p, p]);
+                for axis in 0..3 {
+                    min[axis] = min[axis].min(p[axis]);
+                    max[axis] = max[axis].max(p[axis]);
+                }
+            }
+        }
+        bounds
+    }
+
+    /// Total vertices across all meshes.
+    #[must_use]
+    pub fn vertex_count(&self) -> usize {
+        self.meshes.iter().flat_map(|m| &m.primitives).map(|p| p.positions.len()).sum()
+    }
+
+    /// Total triangles across all meshes.
+    #[must_use]
+    pub fn triangle_count(&self) -> usize {
+        self.meshes.iter().flat_map(|m| &m.primitives).map(|p| p.indices.len() / 3).sum()
+    }
+
+    /// View-projection for rendering the layer at `aspect`.
+    ///
+    /// Uses the first node carrying a camera; scenes without one are framed
+    /// by a 45° camera looking down -Z at their bounds.
+    #[must_use]
+    pub fn view_projection(&self, aspect: f32, pose: Option<&[NodeTransform]>) -> Mat4 {
+        let world = self.world_transforms(pose);
+        let camera = self.nodes.iter().enumerate().find_map(|(index, node)| {
+            let projection = self.cameras.get(node.camera?)?;
+            self.is_reachable(index).then_some((index, projection))
+        });
+        if let Some((node, projection)) = camera
+            && let Some(view) = world[node].inverse()
+        {
+            return projection.matrix(aspect).mul(&view);
+        }
+
+        let [min, max] = self.bounds(pose).unwrap_or([[-1.0; 3], [1.0; 3]]);
+        let center = [0, 1, 2].map(|axis| (min[axis] + max[axis]) / 2.0);
+        let radius = (0..3)
+            .map(|axis| (max[axis] - min[axis]) / 2.0)
+            .map(|half| half * half)
+            .sum::<f32>()
+            .sqrt()
+            .max(1e-3);
+        let yfov = std::f32::consts::FRAC_PI_4;
+        let distance = radius / (yfov / 2.0).sin();
+        let eye = [center[0], center[1], center[2] + distance];
+        let view =
+            Mat4::from_trs(eye, [0.0, 0.0, 0.0, 1.0], [1.0; 3]).inverse().unwrap_or_default();
+        Mat4::perspective(yfov, aspect, (distance - radius).max(distance * 1e-3), distance + radius)
+            .mul(&view)
+    }
+}
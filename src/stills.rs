@@ -0,0 +1,572 @@
+//! Still image writers for frame export.
+//!
+//! Encodes rendered frames as PNG (RGBA8 in stored deflate blocks),
+//! baseline JPEG (4:4:4 YCbCr with the Annex K tables) or scanline
+//! OpenEXR (uncompressed half-float RGBA). PNG and JPEG take display-encoded
+//! RGBA8; EXR takes scene-linear RGBA floats.
+
+use crate::errors::{VideoEditorError, VideoEditorResult};
+
+/// PNG file signature.
+pub const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+/// OpenEXR magic number.
+pub const EXR_MAGIC: &[u8; 4] = b"\x76\x2f\x31\x01";
+
+/// Largest deflate stored block.
+const STORED_BLOCK: usize = 65_535;
+
+/// Still image format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StillFormat {
+    /// PNG, 8-bit RGBA.
+    Png,
+    /// Baseline JPEG at a quality from 1 to 100.
+    Jpeg {
+        /// Encoder quality.
+        quality: u8,
+    },
+    /// OpenEXR, half-float scene-linear RGBA.
+    Exr,
+}
+
+impl StillFormat {
+    /// Returns the file extension.
+    #[must_use]
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg { .. } => "jpg",
+            Self::Exr => "exr",
+        }
+    }
+
+    /// Returns the format for a file extension, JPEG at quality 90.
+    #[must_use]
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "png" => Some(Self::Png),
+            "jpg" | "jpeg" => Some(Self::Jpeg { quality: 90 }),
+            "exr" => Some(Self::Exr),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the format stores scene-linear float data.
+    #[must_use]
+    pub const fn is_linear(&self) -> bool {
+        matches!(self, Self::Exr)
+    }
+}
+
+fn check_size(width: u32, height: u32, len: usize, what: &str) -> VideoEditorResult<()> {
+    let expected = width as usize * height as usize * 4;
+    if width == 0 || height == 0 || len != expected {
+        return Err(VideoEditorError::Export(format!(
+            "{what} needs {width}x{height} RGBA ({expected} values), got {len}"
+        )));
+    }
+    Ok(())
+}
+
+/// Encodes RGBA8 pixels as a PNG file.
+///
+/// # Errors
+///
+/// Returns an error if the buffer does not match the dimensions.
+pub fn encode_png(width: u32, height: u32, rgba: &[u8]) -> VideoEditorResult<Vec<u8>> {
+    check_size(width, height, rgba.len(), "PNG")?;
+
+    // Filter type 0 in front of every row
+    let stride = width as usize * 4;
+    let mut raw = Vec::with_capacity((stride + 1) * height as usize);
+    for row in rgba.chunks_exact(stride) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(STORED_BLOCK).peekable();
+    while let Some(block) = blocks.next() {
+        zlib.push(u8::from(blocks.peek().is_none()));
+        let len = block.len() as u16;
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut out = PNG_SIGNATURE.to_vec();
+    png_chunk(&mut out, b"IHDR", &header);
+    png_chunk(&mut out, b"IDAT", &zlib);
+    png_chunk(&mut out, b"IEND", &[]);
+    Ok(out)
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= 65_521;
+        b %= 65_521;
+    }
+    (b << 16) | a
+}
+
+/// Zigzag scan order: natural index of the k-th coefficient.
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+/// Annex K luminance quantization table, natural order.
+const LUMA_QUANT: [u16; 64] = [
+    16, 11, 10, 16, 24, 40, 51, 61, 12, 12, 14, 19, 26, 58, 60, 55, 14, 13, 16, 24, 40, 57, 69, 56,
+    14, 17, 22, 29, 51, 87, 80, 62, 18, 22, 37, 56, 68, 109, 103, 77, 24, 35, 55, 64, 81, 104, 113,
+    92, 49, 64, 78, 87, 103, 121, 120, 101, 72, 92, 95, 98, 112, 100, 103, 99,
+];
+
+/// Annex K chrominance quantization table, natural order.
+const CHROMA_QUANT: [u16; 64] = [
+    17, 18, 24, 47, 99, 99, 99, 99, 18, 21, 26, 66, 99, 99, 99, 99, 24, 26, 56, 99, 99, 99, 99, 99,
+    47, 66, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+];
+
+const DC_LUMA_BITS: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+const DC_CHROMA_BITS: [u8; 16] = [0, 3, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0];
+const DC_VALUES: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+const AC_LUMA_BITS: [u8; 16] = [0, 2, 1, 3, 3, 2, 4, 3, 5, 5, 4, 4, 0, 0, 1, 0x7d];
+const AC_LUMA_VALUES: [u8; 162] = [
+    0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12, 0x21, 0x31, 0x41, 0x06, 0x13, 0x51, 0x61, 0x07,
+    0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xa1, 0x08, 0x23, 0x42, 0xb1, 0xc1, 0x15, 0x52, 0xd1, 0xf0,
+    0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0a, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x25, 0x26, 0x27, 0x28,
+    0x29, 0x2a, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49,
+    0x4a, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69,
+    0x6a, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89,
+    0x8a, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9a, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7,
+    0xa8, 0xa9, 0xaa, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6, 0xb7, 0xb8, 0xb9, 0xba, 0xc2, 0xc3, 0xc4, 0xc5,
+    0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xd2, 0xd3, 0xd4, 0xd5, 0xd6, 0xd7, 0xd8, 0xd9, 0xda, 0xe1, 0xe2,
+    0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9, 0xea, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8,
+    0xf9, 0xfa,
+];
+
+const AC_CHROMA_BITS: [u8; 16] = [0, 2, 1, 2, 4, 4, 3, 4, 7, 5, 4, 4, 0, 1, 2, 0x77];
+const AC_CHROMA_VALUES: [u8; 162] = [
+    0x00, 0x01, 0x02, 0x03, 0x11, 0x04, 0x05, 0x21, 0x31, 0x06, 0x12, 0x41, 0x51, 0x07, 0x61, 0x71,
+    0x13, 0x22, 0x32, 0x81, 0x08, 0x14, 0x42, 0x91, 0xa1, 0xb1, 0xc1, 0x09, 0x23, 0x33, 0x52, 0xf0,
+    0x15, 0x62, 0x72, 0xd1, 0x0a, 0x16, 0x24, 0x34, 0xe1, 0x25, 0xf1, 0x17, 0x18, 0x19, 0x1a, 0x26,
+    0x27, 0x28, 0x29, 0x2a, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48,
+    0x49, 0x4a, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68,
+    0x69, 0x6a, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87,
+    0x88, 0x89, 0x8a, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9a, 0xa2, 0xa3, 0xa4, 0xa5,
+    0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6, 0xb7, 0xb8, 0xb9, 0xba, 0xc2, 0xc3,
+    0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xd2, 0xd3, 0xd4, 0xd5, 0xd6, 0xd7, 0xd8, 0xd9, 0xda,
+    0xe2, 0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9, 0xea, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8,
+    0xf9, 0xfa,
+];
+
+/// Canonical Huffman codes indexed by symbol: (code, length).
+struct HuffmanTable {
+    codes: [(u16, u8); 256],
+}
+
+impl HuffmanTable {
+    fn new(bits: &[u8; 16], values: &[u8]) -> Self {
+        let mut codes = [(0, 0); 256];
+        let mut code = 0u16;
+        let mut symbols = values.iter();
+        for (len, &count) in bits.iter().enumerate() {
+            for _ in 0..count {
+                if let Some(&symbol) = symbols.next() {
+                    codes[usize::from(symbol)] = (code, len as u8 + 1);
+                }
+                code += 1;
+            }
+            code <<= 1;
+        }
+        Self { codes }
+    }
+}
+
+/// Entropy-coded segment writer with 0xFF byte stuffing.
+struct BitWriter {
+    out:   Vec<u8>,
+    acc:   u32,
+    count: u8,
+}
+
+impl BitWriter {
+    fn put(&mut self, bits: u16, len: u8) {
+        self.acc = (self.acc << len) | u32::from(bits) & ((1 << len) - 1);
+        self.count += len;
+        while self.count >= 8 {
+            self.count -= 8;
+            let byte = (self.acc >> self.count) as u8;
+            self.out.push(byte);
+            if byte == 0xFF {
+                self.out.push(0);
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.count > 0 {
+            let pad = 8 - self.count;
+            self.put((1 << pad) - 1, pad);
+        }
+    }
+}
+
+/// Bit count of a coefficient magnitude and its JPEG value bits.
+fn magnitude(value: i32) -> (u8, u16) {
+    let size = (32 - value.unsigned_abs().leading_zeros()) as u8;
+    let bits = if value < 0 { value - 1 } else { value };
+    (size, bits as u16)
+}
+
+/// Encodes RGBA8 pixels as a baseline JPEG; alpha is dropped.
+///
+/// # Errors
+///
+/// Returns an error if the buffer does not match the dimensions or the
+/// image exceeds 65535 pixels on a side.
+pub fn encode_jpeg(
+    width: u32, height: u32, rgba: &[u8], quality: u8,
+) -> VideoEditorResult<Vec<u8>> {
+    check_size(width, height, rgba.len(), "JPEG")?;
+    if width > 0xFFFF || height > 0xFFFF {
+        return Err(VideoEditorError::Export(format!(
+            "JPEG cannot store a {width}x{height} image"
+        )));
+    }
+
+    // IJG quality scaling
+    let quality = u32::from(quality.clamp(1, 100));
+    let scale = if quality < 50 { 5000 / quality } else { 200 - quality * 2 };
+    let scaled =
+        |table: &[u16; 64]| table.map(|q| ((u32::from(q) * scale + 50) / 100).clamp(1, 255) as u16);
+    let quant = [scaled(&LUMA_QUANT), scaled(&CHROMA_QUANT)];
+
+    let mut out = vec![0xFF, 0xD8];
+    out.extend_from_slice(&[0xFF, 0xE0, 0, 16]);
+    out.extend_from_slice(b"JFIF\0");
+    out.extend_from_slice(&[1, 1, 0, 0, 1, 0, 1, 0, 0]);
+
+    out.extend_from_slice(&[0xFF, 0xDB, 0, 132]);
+    for (id, table) in quant.iter().enumerate() {
+        out.push(id as u8);
+        out.extend(ZIGZAG.iter().map(|&i| table[i] as u8));
+    }
+
+    out.extend_from_slice(&[0xFF, 0xC0, 0, 17, 8]);
+    out.extend_from_slice(&(height as u16).to_be_bytes());
+    out.extend_from_slice(&(width as u16).to_be_bytes());
+    out.extend_from_slice(&[3, 1, 0x11, 0, 2, 0x11, 1, 3, 0x11, 1]);
+
+    let tables: [(u8, &[u8; 16], &[u8]); 4] = [
+        (0x00, &DC_LUMA_BITS, &DC_VALUES),
+        (0x10, &AC_LUMA_BITS, &AC_LUMA_VALUES),
+        (0x01, &DC_CHROMA_BITS, &DC_VALUES),
+        (0x11, &AC_CHROMA_BITS, &AC_CHROMA_VALUES),
+    ];
+    for (class, bits, values) in tables {
+        out.extend_from_slice(&[0xFF, 0xC4]);
+        out.extend_from_slice(&(19 + values.len() as u16).to_be_bytes());
+        out.push(class);
+        out.extend_from_slice(bits);
+        out.extend_from_slice(values);
+    }
+
+    out.extend_from_slice(&[0xFF, 0xDA, 0, 12, 3, 1, 0x00, 2, 0x11, 3, 0x11, 0, 63, 0]);
+
+    let huffman = [
+        (
+            HuffmanTable::new(&DC_LUMA_BITS, &DC_VALUES),
+            HuffmanTable::new(&AC_LUMA_BITS, &AC_LUMA_VALUES),
+        ),
+        (
+            HuffmanTable::new(&DC_CHROMA_BITS, &DC_VALUES),
+            HuffmanTable::new(&AC_CHROMA_BITS, &AC_CHROMA_VALUES),
+        ),
+    ];
+    let cosines: [[f32; 8]; 8] = std::array::from_fn(|x| {
+        std::array::from_fn(|u| (((2 * x + 1) * u) as f32 * core::f32::consts::PI / 16.0).cos())
+    });
+
+    let mut writer = BitWriter { out, acc: 0, count: 0 };
+    let mut predictors = [0i32; 3];
+    let (w, h) = (width as usize, height as usize);
+    for by in (0..h).step_by(8) {
+        for bx in (0..w).step_by(8) {
+            let sample = |i: usize| {
+                let x = (bx + i % 8).min(w - 1);
+                let y = (by + i / 8).min(h - 1);
+                let p = &rgba[(y * w + x) * 4..];
+                (f32::from(p[0]), f32::from(p[1]), f32::from(p[2]))
+            };
+            let blocks: [[f32; 64]; 3] = [
+                std::array::from_fn(|i| {
+                    let (r, g, b) = sample(i);
+                    0.299 * r + 0.587 * g + 0.114 * b - 128.0
+                }),
+                std::array::from_fn(|i| {
+                    let (r, g, b) = sample(i);
+                    -0.168_736 * r - 0.331_264 * g + 0.5 * b
+                }),
+                std::array::from_fn(|i| {
+                    let (r, g, b) = sample(i);
+                    0.5 * r - 0.418_688 * g - 0.081_312 * b
+                }),
+            ];
+            for (component, block) in blocks.iter().enumerate() {
+                let table = usize::from(component > 0);
+                let coefficients = fdct_quantize(block, &quant[table], &cosines);
+                let (dc, ac) = &huffman[table];
+                encode_block(&mut writer, &coefficients, &mut predictors[component], dc, ac);
+            }
+        }
+    }
+    writer.flush();
+
+    let mut out = writer.out;
+    out.extend_from_slice(&[0xFF, 0xD9]);
+    Ok(out)
+}
+
+/// Forward DCT of a level-shifted block, quantized, in zigzag order.
+fn fdct_quantize(block: &[f32; 64], quant: &[u16; 64], cosines: &[[f32; 8]; 8]) -> [i32; 64] {
+    let mut out = [0; 64];
+    for (k, &natural) in ZIGZAG.iter().enumerate() {
+        let (u, v) = (natural % 8, natural / 8);
+        let mut sum = 0.0;
+        for y in 0..8 {
+            for x in 0..8 {
+                sum += block[y * 8 + x] * cosines[x][u] * cosines[y][v];
+            }
+        }
+        let cu = if u == 0 { core::f32::consts::FRAC_1_SQRT_2 } else { 1.0 };
+        let cv = if v == 0 { core::f32::consts::FRAC_1_SQRT_2 } else { 1.0 };
+        out[k] = (0.25 * cu * cv * sum / f32::from(quant[natural])).round() as i32;
+    }
+    out
+}
+
+fn encode_block(
+    writer: &mut BitWriter, coefficients: &[i32; 64], predictor: &mut i32, dc: &HuffmanTable,
+    ac: &HuffmanTable,
+) {
+    let (size, bits) = magnitude(coefficients[0] - *predictor);
+    *predictor = coefficients[0];
+    let (code, len) = dc.codes[usize::from(size)];
+    writer.put(code, len);
+    writer.put(bits, size);
+
+    let mut run = 0;
+    for &value in &coefficients[1..] {
+        if value == 0 {
+            run += 1;
+            continue;
+        }
+        while run > 15 {
+            let (code, len) = ac.codes[0xF0];
+            writer.put(code, len);
+            run -= 16;
+        }
+        let (size, bits) = magnitude(value);
+        let (code, len) = ac.codes[(run << 4) | usize::from(size)];
+        writer.put(code, len);
+        writer.put(bits, size);
+        run = 0;
+    }
+    if run > 0 {
+        let (code, len) = ac.codes[0x00];
+        writer.put(code, len);
+    }
+}
+
+/// Encodes scene-linear RGBA floats as an uncompressed half-float EXR.
+///
+/// # Errors
+///
+/// Returns an error if the buffer does not match the dimensions.
+pub fn encode_exr(width: u32, height: u32, rgba: &[f32]) -> VideoEditorResult<Vec<u8>> {
+    check_size(width, height, rgba.len(), "EXR")?;
+
+    let mut out = EXR_MAGIC.to_vec();
+    out.extend_from_slice(&2u32.to_le_bytes());
+
+    // Channels are stored in alphabetical order
+    let channels = [("A", 3), ("B", 2), ("G", 1), ("R", 0)];
+    let mut chlist = Vec::new();
+    for (name, _) in channels {
+        chlist.extend_from_slice(name.as_bytes());
+        chlist.push(0);
+        chlist.extend_from_slice(&1i32.to_le_bytes());
+        chlist.extend_from_slice(&[0; 4]);
+        chlist.extend_from_slice(&1i32.to_le_bytes());
+        chlist.extend_from_slice(&1i32.to_le_bytes());
+    }
+    chlist.push(0);
+
+    let mut window = Vec::with_capacity(16);
+    for v in [0, 0, width as i32 - 1, height as i32 - 1] {
+        window.extend_from_slice(&v.to_le_bytes());
+    }
+    let attributes: [(&str, &str, &[u8]); 8] = [
+        ("channels", "chlist", &chlist),
+        ("compression", "compression", &[0]),
+        ("dataWindow", "box2i", &window),
+        ("displayWindow", "box2i", &window),
+        ("lineOrder", "lineOrder", &[0]),
+        ("pixelAspectRatio", "float", &1f32.to_le_bytes()),
+        ("screenWindowCenter", "v2f", &[0; 8]),
+        ("screenWindowWidth", "float", &1f32.to_le_bytes()),
+    ];
+    for (name, kind, value) in attributes {
+        out.extend_from_slice(name.as_bytes());
+        out.push(0);
+        out.extend_from_slice(kind.as_bytes());
+        out.push(0);
+        out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        out.extend_from_slice(value);
+    }
+    out.push(0);
+
+    let w = width as usize;
+    let line_bytes = w * channels.len() * 2;
+    let table_start = out.len();
+    let first_line = table_start + height as usize * 8;
+    for y in 0..height as usize {
+        let offset = (first_line + y * (8 + line_bytes)) as u64;
+        out.extend_from_slice(&offset.to_le_bytes());
+    }
+    for (y, row) in rgba.chunks_exact(w * 4).enumerate() {
+        out.extend_from_slice(&(y as i32).to_le_bytes());
+        out.extend_from_slice(&(line_bytes as u32).to_le_bytes());
+        for (_, channel) in channels {
+            for pixel in row.chunks_exact(4) {
+                out.extend_from_slice(&f32_to_half(pixel[channel]).to_le_bytes());
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Converts to IEEE 754 half precision, rounding to nearest even.
+fn f32_to_half(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xFF) as i32;
+    let mantissa = bits & 0x007F_FFFF;
+
+    if exponent == 0xFF {
+        let nan = if mantissa == 0 { 0 } else { 0x200 };
+        return sign | 0x7C00 | nan;
+    }
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1F {
+        return sign | 0x7C00;
+    }
+    if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - exponent) as u32;
+        let half = mantissa >> shift;
+        let rest = mantissa & ((1 << shift) - 1);
+        let midpoint = 1 << (shift - 1);
+        let round = u32::from(rest > midpoint || (rest == midpoint && half & 1 == 1));
+        return sign | (half + round) as u16;
+    }
+    let half = ((exponent as u32) << 10) | (mantissa >> 13);
+    let rest = mantissa & 0x1FFF;
+    let round = u32::from(rest > 0x1000 || (rest == 0x1000 && half & 1 == 1));
+    sign | (half + round) as u16
+}
+
+#[cfg(all(test, feature = "full-tests"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_png_structure() {
+        let pixels: Vec<u8> = (0..4 * 3 * 4).map(|i| i as u8).collect();
+        let png = encode_png(4, 3, &pixels).expect("test assertion");
+        assert_eq!(&png[..8], PNG_SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 4, 0, 0, 0, 3]);
+        // Known CRC of the IEND chunk
+        assert_eq!(&png[png.len() - 8..], &[b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]);
+
+        // The stored zlib stream holds the filtered rows verbatim
+        let idat = &png[33 + 8..];
+        assert_eq!(&idat[..2], &[0x78, 0x01]);
+        assert_eq!(&idat[7..7 + 17], &[[0].as_slice(), &pixels[..16]].concat()[..]);
+        assert!(encode_png(4, 4, &pixels).is_err());
+    }
+
+    #[test]
+    fn test_jpeg_structure() {
+        let gray = [128u8, 128, 128, 255].repeat(16 * 9);
+        let jpeg = encode_jpeg(16, 9, &gray, 85).expect("test assertion");
+        assert_eq!(&jpeg[..4], &[0xFF, 0xD8, 0xFF, 0xE0]);
+        assert_eq!(&jpeg[jpeg.len() - 2..], &[0xFF, 0xD9]);
+        let sof = jpeg.windows(2).position(|w| w == [0xFF, 0xC0]).expect("test assertion");
+        assert_eq!(&jpeg[sof + 5..sof + 9], &[0, 9, 0, 16]);
+
+        // Mid gray quantizes to all zeros: in each of the four MCUs luma
+        // codes a 2-bit DC and a 4-bit end-of-block, each chroma block 2 + 2
+        let sos = jpeg.windows(2).position(|w| w == [0xFF, 0xDA]).expect("test assertion");
+        let scan = &jpeg[sos + 14..jpeg.len() - 2];
+        assert_eq!(scan.len(), (4 * (6 + 4 + 4usize)).div_ceil(8));
+    }
+
+    #[test]
+    fn test_exr_structure() {
+        let pixels = [0.5f32, 1.0, 2.0, 1.0].repeat(6);
+        let exr = encode_exr(3, 2, &pixels).expect("test assertion");
+        assert_eq!(&exr[..4], EXR_MAGIC);
+        assert!(exr.windows(8).any(|w| w == b"chlist\0I"));
+
+        // Last scanline: y, size, then A, B, G, R halves
+        let line = &exr[exr.len() - (8 + 3 * 4 * 2)..];
+        assert_eq!(&line[..4], &1i32.to_le_bytes());
+        let half = |i: usize| u16::from_le_bytes([line[8 + i * 2], line[9 + i * 2]]);
+        assert_eq!([half(0), half(3), half(6), half(9)], [0x3C00, 0x4000, 0x3C00, 0x3800]);
+        assert_eq!(f32_to_half(65_504.0), 0x7BFF);
+        assert_eq!(f32_to_half(1e6), 0x7C00);
+        assert_eq!(f32_to_half(-0.0), 0x8000);
+    }
+}
@@ -0,0 +1,812 @@
+//! SVG reader for vector layers.
+//!
+//! Parses the static subset of SVG 1.1 used for titles and graphics: paths,
+//! basic shapes, groups, transforms, solid fills and strokes (presentation
+//! attributes or inline `style`). Gradients, patterns, text and filters
+//! are not rendered. The `viewBox` is mapped onto the document size so the
+//! resulting [`VectorDocument`] is in output units.
+
+use crate::{
+    errors::{VideoEditorError, VideoEditorResult},
+    vector::{FillRule, Stroke, Transform2D, VectorDocument, VectorPath, VectorShape},
+};
+
+/// Cubic control-point distance approximating a quarter circle.
+const KAPPA: f32 = 0.552_284_8;
+
+/// Elements whose content is never rendered directly.
+const NON_RENDERED: &[&str] = &[
+    "defs",
+    "clipPath",
+    "mask",
+    "symbol",
+    "marker",
+    "pattern",
+    "linearGradient",
+    "radialGradient",
+    "filter",
+    "style",
+    "script",
+    "title",
+    "desc",
+    "metadata",
+    "text",
+];
+
+impl VectorDocument {
+    /// Parses an SVG document.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the markup is malformed or has no `<svg>` root.
+    pub fn from_svg(source: &str) -> VideoEditorResult<Self> {
+        let mut document = Self::default();
+        let mut states: Vec<Option<PaintState>> = Vec::new();
+        let mut skip_depth = 0usize;
+        let mut has_root = false;
+
+        for tag in Tags::new(source) {
+            let tag = tag?;
+            let Tag::Start { name, attributes, empty } = tag else {
+                if skip_depth > 0 {
+                    skip_depth -= 1;
+                } else {
+                    states.pop();
+                }
+                continue;
+            };
+            if skip_depth > 0 || NON_RENDERED.contains(&name) {
+                skip_depth += usize::from(!empty);
+                continue;
+            }
+
+            let parent = states.last().cloned().flatten();
+            let state = match (parent, name) {
+                (None, "svg") => {
+                    has_root = true;
+                    Some(document.root_state(&attributes))
+                },
+                (None, _) => None,
+                (Some(parent), _) => Some(parent.inherit(&attributes)?),
+            };
+
+            if let Some(state) = &state
+                && state.displayed
+                && let Some(path) = shape_path(name, &attributes)?
+                && !path.is_empty()
+            {
+                document.shapes.push(state.shape(path));
+            }
+            if !empty {
+                states.push(state);
+            }
+        }
+
+        if !has_root {
+            return Err(VideoEditorError::conversion("SVG has no <svg> root element"));
+        }
+        Ok(document)
+    }
+
+    /// Sets the document size and returns the root drawing state.
+    fn root_state(&mut self, attributes: &[(&str, String)]) -> PaintState {
+        let view_box = attribute(attributes, "viewBox")
+            .map(numbers)
+            .filter(|v| v.len() == 4 && v[2] > 0.0 && v[3] > 0.0);
+        let length = |name| attribute(attributes, name).filter(|v| !v.ends_with('%')).map(number);
+        let (box_w, box_h) = view_box.as_ref().map_or((300.0, 150.0), |v| (v[2], v[3]));
+        self.width = length("width").unwrap_or(box_w);
+        self.height = length("height").unwrap_or(box_h);
+
+        // preserveAspectRatio="xMidYMid meet"
+        let transform = view_box.map_or(Transform2D::IDENTITY, |v| {
+            let scale = (self.width / v[2]).min(self.height / v[3]);
+            let x = (self.width - v[2] * scale) / 2.0 - v[0] * scale;
+            let y = (self.height - v[3] * scale) / 2.0 - v[1] * scale;
+            Transform2D::translate(x, y).then(&Transform2D::scale(scale, scale))
+        });
+        PaintState { transform, ..PaintState::default() }.with(attributes)
+    }
+}
+
+/// Inherited drawing properties.
+#[derive(Debug, Clone)]
+struct PaintState {
+    transform:      Transform2D,
+    fill:           Option<[u8; 3]>,
+    fill_opacity:   f32,
+    fill_rule:      FillRule,
+    stroke:         Option<[u8; 3]>,
+    stroke_opacity: f32,
+    stroke_width:   f32,
+    opacity:        f32,
+    displayed:      bool,
+}
+
+impl Default for PaintState {
+    fn default() -> Self {
+        Self {
+            transform:      Transform2D::IDENTITY,
+            fill:           Some([0, 0, 0]),
+            fill_opacity:   1.0,
+            fill_rule:      FillRule::NonZero,
+            stroke:         None,
+            stroke_opacity: 1.0,
+            stroke_width:   1.0,
+            opacity:        1.0,
+            displayed:      true,
+        }
+    }
+}
+
+impl PaintState {
+    fn inherit(&self, attributes: &[(&str, String)]) -> VideoEditorResult<Self> {
+        let mut state = self.clone();
+        if let Some(transform) = attribute(attributes, "transform") {
+            state.transform = self.transform.then(&parse_transform(transform)?);
+        }
+        Ok(state.with(attributes))
+    }
+
+    /// Applies presentation attributes, then `style` declarations.
+    fn with(mut self, attributes: &[(&str, String)]) -> Self {
+        let style = attribute(attributes, "style").unwrap_or_default();
+        let declarations = style.split(';').filter_map(|d| {
+            let (name, value) = d.split_once(':')?;
+            Some((name.trim(), value.trim()))
+        });
+        let properties = attributes.iter().map(|(name, value)| (*name, value.trim()));
+        for (name, value) in properties.chain(declarations) {
+            match name {
+                "fill" => self.fill = parse_paint(value).unwrap_or(self.fill),
+                "stroke" => self.stroke = parse_paint(value).unwrap_or(self.stroke),
+                "fill-opacity" => self.fill_opacity = opacity(value),
+                "stroke-opacity" => self.stroke_opacity = opacity(value),
+                "opacity" => self.opacity *= opacity(value),
+                "stroke-width" => self.stroke_width = number(value).max(0.0),
+                "fill-rule" => {
+                    self.fill_rule =
+                        if value == "evenodd" { FillRule::EvenOdd } else { FillRule::NonZero };
+                },
+                "display" if value == "none" => self.displayed = false,
+                "visibility" if value == "hidden" || value == "collapse" => self.displayed = false,
+                _ => {},
+            }
+        }
+        self
+    }
+
+    fn shape(&self, path: VectorPath) -> VectorShape {
+        let rgba = |[r, g, b]: [u8; 3], alpha: f32| {
+            [r, g, b, (alpha * self.opacity * 255.0).round().clamp(0.0, 255.0) as u8]
+        };
+        VectorShape {
+            path:      path.transformed(&self.transform),
+            fill:      self.fill.map(|c| rgba(c, self.fill_opacity)),
+            fill_rule: self.fill_rule,
+            stroke:    self.stroke.filter(|_| self.stroke_width > 0.0).map(|c| Stroke {
+                color: rgba(c, self.stroke_opacity),
+                width: self.stroke_width * self.transform.scale_factor(),
+            }),
+        }
+    }
+}
+
+fn attribute<'a>(attributes: &'a [(&str, String)], name: &str) -> Option<&'a str> {
+    attributes.iter().find(|(n, _)| *n == name).map(|(_, v)| v.as_str())
+}
+
+/// Leading number of a length or plain value (units are ignored).
+fn number(value: &str) -> f32 {
+    numbers(value).first().copied().unwrap_or(0.0)
+}
+
+fn opacity(value: &str) -> f32 {
+    let value = value.trim();
+    match value.strip_suffix('%') {
+        Some(percent) => number(percent) / 100.0,
+        None => number(value),
+    }
+    .clamp(0.0, 1.0)
+}
+
+/// Splits a list of numbers separated by whitespace and/or commas.
+fn numbers(value: &str) -> Vec<f32> {
+    let mut scanner = NumberScanner { bytes: value.as_bytes(), pos: 0 };
+    std::iter::from_fn(|| scanner.number()).collect()
+}
+
+/// Parses a paint; `None` for values to ignore, `Some(None)` for `none`.
+fn parse_paint(value: &str) -> Option<Option<[u8; 3]>> {
+    let value = value.trim();
+    if value == "none" || value == "transparent" {
+        return Some(None);
+    }
+    // Paint servers fall back to their listed color, else nothing
+    if let Some(rest) = value.strip_prefix("url(") {
+        let fallback = rest.split_once(')').map_or("", |(_, f)| f.trim());
+        return Some(parse_color(fallback));
+    }
+    parse_color(value).map(Some)
+}
+
+fn parse_color(value: &str) -> Option<[u8; 3]> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let digit = |i: usize| u8::from_str_radix(hex.get(i..=i)?, 16).ok();
+        return match hex.len() {
+            3 => Some([digit(0)? * 17, digit(1)? * 17, digit(2)? * 17]),
+            6 => Some([
+                digit(0)? * 16 + digit(1)?,
+                digit(2)? * 16 + digit(3)?,
+                digit(4)? * 16 + digit(5)?,
+            ]),
+            _ => None,
+        };
+    }
+    if let Some(args) = value.strip_prefix("rgb(").and_then(|v| v.strip_suffix(')')) {
+        let channels: Vec<u8> = args
+            .split(',')
+            .map(|c| {
+                let c = c.trim();
+                let value = match c.strip_suffix('%') {
+                    Some(percent) => number(percent) * 2.55,
+                    None => number(c),
+                };
+                value.round().clamp(0.0, 255.0) as u8
+            })
+            .collect();
+        return (channels.len() == 3).then(|| [channels[0], channels[1], channels[2]]);
+    }
+    let named = match value.to_ascii_lowercase().as_str() {
+        "black" => [0, 0, 0],
+        "white" => [255, 255, 255],
+        "red" => [255, 0, 0],
+        "green" => [0, 128, 0],
+        "lime" => [0, 255, 0],
+        "blue" => [0, 0, 255],
+        "yellow" => [255, 255, 0],
+        "cyan" | "aqua" => [0, 255, 255],
+        "magenta" | "fuchsia" => [255, 0, 255],
+        "gray" | "grey" => [128, 128, 128],
+        "silver" => [192, 192, 192],
+        "maroon" => [128, 0, 0],
+        "navy" => [0, 0, 128],
+        "olive" => [128, 128, 0],
+        "purple" => [128, 0, 128],
+        "teal" => [0, 128, 128],
+        "orange" => [255, 165, 0],
+        _ => return None,
+    };
+    Some(named)
+}
+
+fn parse_transform(value: &str) -> VideoEditorResult<Transform2D> {
+    let mut transform = Transform2D::IDENTITY;
+    let mut rest = value.trim();
+    while !rest.is_empty() {
+        let (name, tail) = rest.split_once('(').ok_or_else(|| {
+            VideoEditorError::conversion(format!("Invalid SVG transform: {value}"))
+        })?;
+        let (args, tail) = tail.split_once(')').ok_or_else(|| {
+            VideoEditorError::conversion(format!("Invalid SVG transform: {value}"))
+        })?;
+        let args = numbers(args);
+        let arg = |i: usize| args.get(i).copied().unwrap_or(0.0);
+        let step = match name.trim().trim_start_matches(',').trim() {
+            "matrix" if args.len() == 6 => {
+                Transform2D { a: arg(0), b: arg(1), c: arg(2), d: arg(3), e: arg(4), f: arg(5) }
+            },
+            "translate" => Transform2D::translate(arg(0), arg(1)),
+            "scale" => Transform2D::scale(arg(0), args.get(1).copied().unwrap_or(arg(0))),
+            "rotate" => {
+                let (cx, cy) = (arg(1), arg(2));
+                Transform2D::translate(cx, cy)
+                    .then(&Transform2D::rotate(arg(0)))
+                    .then(&Transform2D::translate(-cx, -cy))
+            },
+            "skewX" => Transform2D::skew_x(arg(0)),
+            "skewY" => Transform2D::skew_y(arg(0)),
+            other => {
+                return Err(VideoEditorError::conversion(format!(
+                    "Unsupported SVG transform: {other}"
+                )));
+            },
+        };
+        transform = transform.then(&step);
+        rest = tail.trim_start_matches([' ', ',', '\t', '\n', '\r']);
+    }
+    Ok(transform)
+}
+
+/// Geometry of a shape element, `None` for non-shape elements.
+fn shape_path(name: &str, attributes: &[(&str, String)]) -> VideoEditorResult<Option<VectorPath>> {
+    let get = |n| attribute(attributes, n).map_or(0.0, number);
+    let mut path = VectorPath::new();
+    match name {
+        "path" => return attribute(attributes, "d").map(parse_path_data).transpose(),
+        "rect" => {
+            let (x, y, w, h) = (get("x"), get("y"), get("width"), get("height"));
+            if w <= 0.0 || h <= 0.0 {
+                return Ok(None);
+            }
+            let rx = attribute(attributes, "rx").map(number);
+            let ry = attribute(attributes, "ry").map(number);
+            let rx = rx.or(ry).unwrap_or(0.0).clamp(0.0, w / 2.0);
+            let ry = ry.or(Some(rx)).unwrap_or(0.0).clamp(0.0, h / 2.0);
+            if rx > 0.0 && ry > 0.0 {
+                let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+                path.move_to([x + rx, y]);
+                path.line_to([x + w - rx, y]);
+                path.cubic_to([x + w - rx + kx, y], [x + w, y + ry - ky], [x + w, y + ry]);
+                path.line_to([x + w, y + h - ry]);
+                path.cubic_to(
+                    [x + w, y + h - ry + ky],
+                    [x + w - rx + kx, y + h],
+                    [x + w - rx, y + h],
+                );
+                path.line_to([x + rx, y + h]);
+                path.cubic_to([x + rx - kx, y + h], [x, y + h - ry + ky], [x, y + h - ry]);
+                path.line_to([x, y + ry]);
+                path.cubic_to([x, y + ry - ky], [x + rx - kx, y], [x + rx, y]);
+            } else {
+                path.move_to([x, y]);
+                path.line_to([x + w, y]);
+                path.line_to([x + w, y + h]);
+                path.line_to([x, y + h]);
+            }
+            path.close();
+        },
+        "circle" | "ellipse" => {
+            let (cx, cy) = (get("cx"), get("cy"));
+            let (rx, ry) =
+                if name == "circle" { (get("r"), get("r")) } else { (get("rx"), get("ry")) };
+            if rx <= 0.0 || ry <= 0.0 {
+                return Ok(None);
+            }
+            let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+            path.move_to([cx + rx, cy]);
+            path.cubic_to([cx + rx, cy + ky], [cx + kx, cy + ry], [cx, cy + ry]);
+            path.cubic_to([cx - kx, cy + ry], [cx - rx, cy + ky], [cx - rx, cy]);
+            path.cubic_to([cx - rx, cy - ky], [cx - kx, cy - ry], [cx, cy - ry]);
+            path.cubic_to([cx + kx, cy - ry], [cx + rx, cy - ky], [cx + rx, cy]);
+            path.close();
+        },
+        "line" => {
+            path.move_to([get("x1"), get("y1")]);
+            path.line_to([get("x2"), get("y2")]);
+        },
+        "polyline" | "polygon" => {
+            let points = numbers(attribute(attributes, "points").unwrap_or_default());
+            let mut pairs = points.chunks_exact(2).map(|p| [p[0], p[1]]);
+            let Some(first) = pairs.next() else {
+                return Ok(None);
+            };
+            path.move_to(first);
+            pairs.for_each(|p| path.line_to(p));
+            if name == "polygon" {
+                path.close();
+            }
+        },
+        _ => return Ok(None),
+    }
+    Ok(Some(path))
+}
+
+/// Parses SVG path data into absolute lines and cubics.
+fn parse_path_data(data: &str) -> VideoEditorResult<VectorPath> {
+    let mut path = VectorPath::new();
+    let mut scanner = NumberScanner { bytes: data.as_bytes(), pos: 0 };
+    let mut current = [0.0f32; 2];
+    let mut start = current;
+    // Control points a following S/T reflects
+    let mut last_cubic: Option<[f32; 2]> = None;
+    let mut last_quad: Option<[f32; 2]> = None;
+    let mut command = None;
+
+    loop {
+        scanner.skip_separators();
+        let Some(&byte) = scanner.bytes.get(scanner.pos) else {
+            break;
+        };
+        if byte.is_ascii_alphabetic() {
+            scanner.pos += 1;
+            command = Some(byte);
+            if byte == b'Z' || byte == b'z' {
+                path.close();
+                current = start;
+                (last_cubic, last_quad) = (None, None);
+                continue;
+            }
+        }
+        let Some(cmd) = command else {
+            return Err(VideoEditorError::conversion("SVG path data must start with a command"));
+        };
+        let relative = cmd.is_ascii_lowercase();
+        let origin = if relative { current } else { [0.0, 0.0] };
+        let mut point = || -> VideoEditorResult<[f32; 2]> {
+            Ok([origin[0] + scanner.argument(cmd)?, origin[1] + scanner.argument(cmd)?])
+        };
+
+        let (mut cubic, mut quad) = (None, None);
+        match cmd.to_ascii_uppercase() {
+            b'M' => {
+                current = point()?;
+                start = current;
+                path.move_to(current);
+                // Further pairs are implicit line-tos
+                command = Some(if relative { b'l' } else { b'L' });
+            },
+            b'L' => {
+                current = point()?;
+                path.line_to(current);
+            },
+            b'H' => {
+                current[0] = origin[0] + scanner.argument(cmd)?;
+                path.line_to(current);
+            },
+            b'V' => {
+                current[1] = origin[1] + scanner.argument(cmd)?;
+                path.line_to(current);
+            },
+            b'C' | b'S' => {
+                let c1 = if cmd.eq_ignore_ascii_case(&b'C') {
+                    point()?
+                } else {
+                    last_cubic.map_or(current, |c| reflect(c, current))
+                };
+                let (c2, end) = (point()?, point()?);
+                path.cubic_to(c1, c2, end);
+                (current, cubic) = (end, Some(c2));
+            },
+            b'Q' | b'T' => {
+                let control = if cmd.eq_ignore_ascii_case(&b'Q') {
+                    point()?
+                } else {
+                    last_quad.map_or(current, |c| reflect(c, current))
+                };
+                let end = point()?;
+                let lerp = |a: [f32; 2], b: [f32; 2]| {
+                    [a[0] + (b[0] - a[0]) * 2.0 / 3.0, a[1] + (b[1] - a[1]) * 2.0 / 3.0]
+                };
+                path.cubic_to(lerp(current, control), lerp(end, control), end);
+                (current, quad) = (end, Some(control));
+            },
+            b'A' => {
+                let radii = [scanner.argument(cmd)?, scanner.argument(cmd)?];
+                let angle = scanner.argument(cmd)?;
+                let (large_arc, sweep) = (scanner.flag(cmd)?, scanner.flag(cmd)?);
+                let end = [origin[0] + scanner.argument(cmd)?, origin[1] + scanner.argument(cmd)?];
+                arc_to(&mut path, current, radii, angle, large_arc, sweep, end);
+                current = end;
+            },
+            other => {
+                return Err(VideoEditorError::conversion(format!(
+                    "Unknown SVG path command '{}'",
+                    other as char
+                )));
+            },
+        }
+        (last_cubic, last_quad) = (cubic, quad);
+    }
+    Ok(path)
+}
+
+fn missing_argument(command: u8) -> VideoEditorError {
+    VideoEditorError::conversion(format!("Missing SVG path argument for '{}'", command as char))
+}
+
+fn reflect(control: [f32; 2], about: [f32; 2]) -> [f32; 2] {
+    [2.0 * about[0] - control[0], 2.0 * about[1] - control[1]]
+}
+
+/// Appends an elliptical arc as cubics (SVG implementation notes F.6).
+fn arc_to(
+    path: &mut VectorPath, from: [f32; 2], radii: [f32; 2], angle: f32, large_arc: bool,
+    sweep: bool, to: [f32; 2],
+) {
+    let (mut rx, mut ry) = (radii[0].abs(), radii[1].abs());
+    if from == to {
+        return;
+    }
+    if rx == 0.0 || ry == 0.0 {
+        path.line_to(to);
+        return;
+    }
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (dx, dy) = ((from[0] - to[0]) / 2.0, (from[1] - to[1]) / 2.0);
+    let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+
+    // Scale radii up if they cannot span the end points
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let numerator = (rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1).max(0.0);
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut factor = (numerator / denominator).sqrt();
+    if large_arc == sweep {
+        factor = -factor;
+    }
+    let (cx1, cy1) = (factor * rx * y1 / ry, -factor * ry * x1 / rx);
+    let center = [
+        cos * cx1 - sin * cy1 + (from[0] + to[0]) / 2.0,
+        sin * cx1 + cos * cy1 + (from[1] + to[1]) / 2.0,
+    ];
+
+    let vector_angle =
+        |ux: f32, uy: f32, vx: f32, vy: f32| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let theta = vector_angle(1.0, 0.0, (x1 - cx1) / rx, (y1 - cy1) / ry);
+    let mut delta =
+        vector_angle((x1 - cx1) / rx, (y1 - cy1) / ry, (-x1 - cx1) / rx, (-y1 - cy1) / ry);
+    if !sweep && delta > 0.0 {
+        delta -= std::f32::consts::TAU;
+    } else if sweep && delta < 0.0 {
+        delta += std::f32::consts::TAU;
+    }
+
+    // At most a quarter turn per cubic
+    let segments = (delta.abs() / std::f32::consts::FRAC_PI_2).ceil().max(1.0) as usize;
+    let step = delta / segments as f32;
+    let k = 4.0 / 3.0 * (step / 4.0).tan();
+    let point = |t: f32| {
+        let (s, c) = t.sin_cos();
+        let (x, y) = (rx * c, ry * s);
+        [cos * x - sin * y + center[0], sin * x + cos * y + center[1]]
+    };
+    let tangent = |t: f32| {
+        let (s, c) = t.sin_cos();
+        let (x, y) = (-rx * s, ry * c);
+        [cos * x - sin * y, sin * x + cos * y]
+    };
+    for i in 0..segments {
+        let (t0, t1) = (theta + step * i as f32, theta + step * (i + 1) as f32);
+        let (p0, p1) = (point(t0), if i + 1 == segments { to } else { point(t1) });
+        let (d0, d1) = (tangent(t0), tangent(t1));
+        path.cubic_to(
+            [p0[0] + k * d0[0], p0[1] + k * d0[1]],
+            [p1[0] - k * d1[0], p1[1] - k * d1[1]],
+            p1,
+        );
+    }
+}
+
+/// Scanner for SVG number lists (`1.5.5`, `-1-2`, `1e-3` are all valid).
+struct NumberScanner<'a> {
+    bytes: &'a [u8],
+    pos:   usize,
+}
+
+impl NumberScanner<'_> {
+    fn skip_separators(&mut self) {
+        while self.bytes.get(self.pos).is_some_and(|b| b.is_ascii_whitespace() || *b == b',') {
+            self.pos += 1;
+        }
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let start = self.pos;
+        let mut end = start;
+        if matches!(self.bytes.get(end), Some(b'+' | b'-')) {
+            end += 1;
+        }
+        let mut seen_dot = false;
+        while let Some(&b) = self.bytes.get(end) {
+            match b {
+                b'0'..=b'9' => end += 1,
+                b'.' if !seen_dot => {
+                    seen_dot = true;
+                    end += 1;
+                },
+                b'e' | b'E' if end > start => {
+                    let mut exp = end + 1;
+                    if matches!(self.bytes.get(exp), Some(b'+' | b'-')) {
+                        exp += 1;
+                    }
+                    if !self.bytes.get(exp).is_some_and(u8::is_ascii_digit) {
+                        break;
+                    }
+                    end = exp;
+                    while self.bytes.get(end).is_some_and(u8::is_ascii_digit) {
+                        end += 1;
+                    }
+                    break;
+                },
+                _ => break,
+            }
+        }
+        let value = std::str::from_utf8(&self.bytes[start..end]).ok()?.parse().ok()?;
+        self.pos = end;
+        Some(value)
+    }
+
+    /// Path command argument.
+    fn argument(&mut self, command: u8) -> VideoEditorResult<f32> {
+        self.number().ok_or_else(|| missing_argument(command))
+    }
+
+    /// Single-digit arc flag, which needs no separator.
+    fn flag(&mut self, command: u8) -> VideoEditorResult<bool> {
+        self.skip_separators();
+        let flag = match self.bytes.get(self.pos) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(missing_argument(command)),
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+}
+
+/// Markup tag.
+enum Tag<'a> {
+    Start { name: &'a str, attributes: Vec<(&'a str, String)>, empty: bool },
+    End,
+}
+
+/// Iterator over element tags, skipping text, comments and declarations.
+struct Tags<'a> {
+    source: &'a str,
+    pos:    usize,
+}
+
+impl<'a> Tags<'a> {
+    fn new(source: &'a str) -> Self {
+        Self { source, pos: 0 }
+    }
+
+    fn skip_past(&mut self, end: &str) -> VideoEditorResult<()> {
+        let found = self.source[self.pos..].find(end).ok_or_else(|| {
+            VideoEditorError::conversion(format!("Unterminated SVG markup, expected '{end}'"))
+        })?;
+        self.pos += found + end.len();
+        Ok(())
+    }
+
+    fn next_tag(&mut self) -> VideoEditorResult<Option<Tag<'a>>> {
+        loop {
+            let Some(offset) = self.source[self.pos..].find('<') else {
+                return Ok(None);
+            };
+            self.pos += offset;
+            let rest = &self.source[self.pos..];
+            if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.skip_past("]]>")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with("<!") || rest.starts_with("</") {
+                let is_end = rest.starts_with("</");
+                self.skip_past(">")?;
+                if is_end {
+                    return Ok(Some(Tag::End));
+                }
+            } else {
+                return self.start_tag().map(Some);
+            }
+        }
+    }
+
+    fn start_tag(&mut self) -> VideoEditorResult<Tag<'a>> {
+        let source = self.source;
+        let malformed = || VideoEditorError::conversion("Malformed SVG tag");
+        let mut pos = self.pos + 1;
+        let name_end = source[pos..]
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .map_or(source.len(), |i| pos + i);
+        let name = &source[pos..name_end];
+        // Namespace prefixes such as `svg:path`
+        let name = name.rsplit(':').next().unwrap_or(name);
+        pos = name_end;
+
+        let mut attributes = Vec::new();
+        loop {
+            pos += source[pos..].len() - source[pos..].trim_start().len();
+            let rest = &source[pos..];
+            if rest.starts_with("/>") {
+                self.pos = pos + 2;
+                return Ok(Tag::Start { name, attributes, empty: true });
+            }
+            if rest.starts_with('>') {
+                self.pos = pos + 1;
+                return Ok(Tag::Start { name, attributes, empty: false });
+            }
+            let eq = rest.find('=').ok_or_else(malformed)?;
+            let key = rest[..eq].trim();
+            let after = rest[eq + 1..].trim_start();
+            let quote =
+                after.chars().next().filter(|c| *c == '"' || *c == '\'').ok_or_else(malformed)?;
+            let value_start = source.len() - after.len() + 1;
+            let value_len = source[value_start..].find(quote).ok_or_else(malformed)?;
+            let value = &source[value_start..value_start + value_len];
+            attributes.push((key.rsplit(':').next().unwrap_or(key), unescape(value)));
+            pos = value_start + value_len + 1;
+        }
+    }
+}
+
+impl<'a> Iterator for Tags<'a> {
+    type Item = VideoEditorResult<Tag<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_tag() {
+            Ok(tag) => tag.map(Ok),
+            Err(e) => {
+                self.pos = self.source.len();
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+fn unescape(value: &str) -> String {
+    if !value.contains('&') {
+        return value.to_string();
+    }
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(all(test, feature = "full-tests"))]
+mod tests {
+    use super::*;
+
+    const BADGE: &str = r##"<?xml version="1.0"?>
+<!-- exported -->
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100" viewBox="0 0 100 50">
+  <defs><linearGradient id="g"><stop offset="0" stop-color="red"/></linearGradient></defs>
+  <rect width="100" height="50" fill="#336699"/>
+  <g transform="translate(50 25)" style="stroke:white; stroke-width:2" fill="none">
+    <circle r="10"/>
+    <path d="M-5-5h10v10h-10z M0 0 a5 5 0 1 1 0.01 0" fill="rgb(255, 0, 0)" fill-opacity="0.5"/>
+  </g>
+  <g display="none"><rect width="10" height="10"/></g>
+  <polygon points="0,0 10,0 5,8" fill="url(#g) orange"/>
+</svg>"##;
+
+    #[test]
+    fn test_parse_shapes_and_styles() {
+        let doc = VectorDocument::from_svg(BADGE).expect("test assertion");
+        assert_eq!((doc.width, doc.height), (200.0, 100.0));
+        assert_eq!(doc.shapes.len(), 4);
+
+        // viewBox doubles every coordinate
+        let background = &doc.shapes[0];
+        assert_eq!(background.fill, Some([0x33, 0x66, 0x99, 255]));
+        assert_eq!(background.path.bounds(), Some([[0.0, 0.0], [200.0, 100.0]]));
+
+        let ring = &doc.shapes[1];
+        assert_eq!(ring.fill, None);
+        let stroke = ring.stroke.expect("test assertion");
+        assert_eq!(stroke.color, [255, 255, 255, 255]);
+        assert!((stroke.width - 4.0).abs() < 1e-4);
+        let [[x0, y0], [x1, y1]] = ring.path.bounds().expect("test assertion");
+        assert!((x0 - 80.0).abs() < 1e-3 && (x1 - 120.0).abs() < 1e-3);
+        assert!((y0 - 30.0).abs() < 1e-3 && (y1 - 70.0).abs() < 1e-3);
+
+        let mark = &doc.shapes[2];
+        assert_eq!(mark.fill, Some([255, 0, 0, 128]));
+        assert_eq!(mark.path.flatten(0.1).len(), 2);
+
+        assert_eq!(doc.shapes[3].fill, Some([255, 165, 0, 255]));
+        assert!(doc.tessellate(0.5).triangle_count() > 0);
+    }
+
+    #[test]
+    fn test_path_data_forms() {
+        let path = parse_path_data("M10,10L20-10.5.5 20 Q 30 30 40 20 T60 20 S 80 0 90 20")
+            .expect("test assertion");
+        assert_eq!(path.commands.len(), 6);
+        assert_eq!(path.commands[2], crate::vector::PathCommand::LineTo([0.5, 20.0]));
+        assert!(parse_path_data("10 10").is_err());
+        assert!(parse_path_data("M0 0 L").is_err());
+        assert!(VectorDocument::from_svg("<g/>").is_err());
+        assert!(VectorDocument::from_svg("<svg><rect width=\"1\"").is_err());
+    }
+}
@@ -0,0 +1,485 @@
+//! Background tasks behind the asynchronous APIs.
+//!
+//! Long operations such as [`FormatConverter::convert_async`] run on a
+//! shared pool of worker threads and hand back a [`TaskHandle`]: a future
+//! resolving to the operation's result, with a [`ControlHandle`] to
+//! cancel, pause or resume it. The futures don't need a particular
+//! runtime; a finished task wakes whatever polled it last, and
+//! [`TaskHandle::wait`] blocks without an executor at all.
+//!
+//! Operations take a [`CancellationToken`] and check it between units of
+//! work, so control requests are honoured within one frame or file.
+//!
+//! A task that is waited on before a worker picks it up runs on the
+//! waiting thread instead, so blocking on a handle never deadlocks on a
+//! busy pool.
+//!
+//! [`FormatConverter::convert_async`]: crate::converter::FormatConverter::convert_async
+
+use std::{
+    any::Any,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        Arc, Condvar, Mutex, MutexGuard, OnceLock, PoisonError,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    task::{Context, Poll, Waker},
+};
+
+use crate::errors::{VideoEditorError, VideoEditorResult};
+
+/// Cancel and pause requests shared by a token and its control handles.
+#[derive(Debug, Default)]
+struct ControlState {
+    cancelled: AtomicBool,
+    paused:    Mutex<bool>,
+    changed:   Condvar,
+}
+
+impl ControlState {
+    fn paused(&self) -> MutexGuard<'_, bool> {
+        self.paused.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Cancel and pause requests as seen by a running operation.
+///
+/// Clones share the requests. Operations call [`Self::checkpoint`] between
+/// units of work (frames, files), which holds them while paused and
+/// returns [`VideoEditorError::Cancelled`] once cancelled, so a request
+/// takes effect within one unit of work.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<ControlState>);
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation, releasing a paused operation.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Relaxed);
+        let _paused = self.0.paused();
+        self.0.changed.notify_all();
+    }
+
+    /// Checks if cancellation was requested.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Checks if the operation was asked to pause.
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        *self.0.paused()
+    }
+
+    /// Returns a cancellation error naming `operation` if cancellation was
+    /// requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VideoEditorError::Cancelled`] once the token is cancelled.
+    pub fn check(&self, operation: &str) -> VideoEditorResult<()> {
+        if self.is_cancelled() {
+            return Err(VideoEditorError::cancelled(operation));
+        }
+        Ok(())
+    }
+
+    /// Blocks while the operation is paused, then checks for cancellation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VideoEditorError::Cancelled`] once the token is cancelled,
+    /// including while paused.
+    pub fn checkpoint(&self, operation: &str) -> VideoEditorResult<()> {
+        let mut paused = self.0.paused();
+        while *paused && !self.is_cancelled() {
+            paused = self.0.changed.wait(paused).unwrap_or_else(PoisonError::into_inner);
+        }
+        drop(paused);
+        self.check(operation)
+    }
+}
+
+/// Controls a started job from another thread.
+///
+/// Pausing holds the job at its next checkpoint until it is resumed or
+/// cancelled; the handle and the job share one [`CancellationToken`].
+#[derive(Debug, Clone, Default)]
+pub struct ControlHandle {
+    token: CancellationToken,
+}
+
+impl ControlHandle {
+    /// Creates a handle for a job not started yet; pass [`Self::token`] to
+    /// the job.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the token the job checks.
+    #[must_use]
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Stops the job at its next checkpoint, even while paused.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Holds the job at its next checkpoint.
+    pub fn pause(&self) {
+        *self.token.0.paused() = true;
+    }
+
+    /// Lets a paused job continue.
+    pub fn resume(&self) {
+        *self.token.0.paused() = false;
+        self.token.0.changed.notify_all();
+    }
+
+    /// Checks if the job was asked to pause.
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.token.is_paused()
+    }
+
+    /// Checks if the job was cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+type TaskJob<T> = Box<dyn FnOnce(&CancellationToken) -> VideoEditorResult<T> + Send>;
+
+/// Outcome of a task; `Err` holds the payload of a panic in the job.
+type TaskOutcome<T> = Result<VideoEditorResult<T>, Box<dyn Any + Send>>;
+
+struct TaskState<T> {
+    /// Work not yet picked up by a worker or a waiter.
+    job:    Option<TaskJob<T>>,
+    /// Result once the job ran, until the handle takes it.
+    result: Option<TaskOutcome<T>>,
+    /// Waker of the last poll that found the task unfinished.
+    waker:  Option<Waker>,
+}
+
+struct Task<T> {
+    state: Mutex<TaskState<T>>,
+    done:  Condvar,
+    token: CancellationToken,
+}
+
+impl<T> Task<T> {
+    fn lock(&self) -> MutexGuard<'_, TaskState<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Runs the job if nobody has yet. A task cancelled before it starts
+    /// resolves without running.
+    fn run(&self) {
+        let Some(job) = self.lock().job.take() else {
+            return;
+        };
+        let outcome = if self.token.is_cancelled() {
+            Ok(Err(VideoEditorError::cancelled("task")))
+        } else {
+            panic::catch_unwind(AssertUnwindSafe(|| job(&self.token)))
+        };
+        self.finish(outcome);
+    }
+
+    fn finish(&self, outcome: TaskOutcome<T>) {
+        let waker = {
+            let mut state = self.lock();
+            state.result = Some(outcome);
+            state.waker.take()
+        };
+        self.done.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Type-erased task as seen by the pool workers.
+trait Runnable: Send + Sync {
+    fn run(&self);
+}
+
+impl<T: Send> Runnable for Task<T> {
+    fn run(&self) {
+        Task::run(self);
+    }
+}
+
+/// Handle to a task on the pool.
+///
+/// Polling the handle yields the task's result; a job that panicked
+/// resumes the panic in the caller, as joining a thread would. Dropping
+/// the handle doesn't stop the task, cancel it first.
+#[must_use = "the task's result is only available through its handle"]
+pub struct TaskHandle<T> {
+    task: Arc<Task<T>>,
+}
+
+impl<T> TaskHandle<T> {
+    /// Gets the token the task checks for cancellation.
+    #[must_use]
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.task.token.clone()
+    }
+
+    /// Gets a handle that cancels, pauses and resumes the task.
+    #[must_use]
+    pub fn control(&self) -> ControlHandle {
+        ControlHandle { token: self.task.token.clone() }
+    }
+
+    /// Requests cancellation. A task still waiting for a worker resolves
+    /// to [`VideoEditorError::Cancelled`] immediately; a running one stops
+    /// at its next check.
+    pub fn cancel(&self) {
+        self.task.token.cancel();
+        let job = self.task.lock().job.take();
+        if job.is_some() {
+            self.task.finish(Ok(Err(VideoEditorError::cancelled("task"))));
+        }
+    }
+
+    /// Checks if the task has finished.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.task.lock().result.is_some()
+    }
+
+    /// Blocks until the task finishes and returns its result, running it on
+    /// the calling thread if no worker has started it yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns the task's error, or [`VideoEditorError::Cancelled`] if it
+    /// was cancelled before it started.
+    pub fn wait(self) -> VideoEditorResult<T> {
+        self.task.run();
+        let mut state = self.task.lock();
+        loop {
+            if let Some(outcome) = state.result.take() {
+                drop(state);
+                return outcome.unwrap_or_else(|payload| panic::resume_unwind(payload));
+            }
+            state = self.task.done.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
+
+impl<T> Future for TaskHandle<T> {
+    type Output = VideoEditorResult<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.task.lock();
+        match state.result.take() {
+            Some(outcome) => {
+                drop(state);
+                Poll::Ready(outcome.unwrap_or_else(|payload| panic::resume_unwind(payload)))
+            },
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
+
+impl<T> core::fmt::Debug for TaskHandle<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TaskHandle")
+            .field("finished", &self.is_finished())
+            .field("cancelled", &self.task.token.is_cancelled())
+            .finish()
+    }
+}
+
+/// Fixed set of worker threads running tasks in submission order.
+pub(crate) struct TaskPool {
+    sender:  Mutex<Sender<Arc<dyn Runnable>>>,
+    workers: usize,
+}
+
+impl TaskPool {
+    /// Starts a pool with `workers` threads (at least one).
+    pub(crate) fn new(workers: usize) -> Self {
+        let workers = workers.max(1);
+        let (sender, receiver) = mpsc::channel::<Arc<dyn Runnable>>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for index in 0..workers {
+            let receiver = Arc::clone(&receiver);
+            // A pool that cannot start workers still runs tasks in `wait`
+            let _ = std::thread::Builder::new()
+                .name(format!("evep-task-{index}"))
+                .spawn(move || Self::work(&receiver));
+        }
+        Self { sender: Mutex::new(sender), workers }
+    }
+
+    /// Pool shared by the library's asynchronous APIs, one worker per
+    /// available CPU.
+    pub(crate) fn global() -> &'static Self {
+        static POOL: OnceLock<TaskPool> = OnceLock::new();
+        POOL.get_or_init(|| Self::new(std::thread::available_parallelism().map_or(1, usize::from)))
+    }
+
+    /// Number of worker threads.
+    pub(crate) const fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Queues `job` and returns a handle to its result.
+    pub(crate) fn spawn<T, F>(&self, job: F) -> TaskHandle<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&CancellationToken) -> VideoEditorResult<T> + Send + 'static,
+    {
+        let task = Arc::new(Task {
+            state: Mutex::new(TaskState {
+                job:    Some(Box::new(job)),
+                result: None,
+                waker:  None,
+            }),
+            done:  Condvar::new(),
+            token: CancellationToken::new(),
+        });
+        if let Ok(sender) = self.sender.lock() {
+            let _ = sender.send(Arc::clone(&task) as Arc<dyn Runnable>);
+        }
+        TaskHandle { task }
+    }
+
+    fn work(receiver: &Mutex<Receiver<Arc<dyn Runnable>>>) {
+        loop {
+            let next = match receiver.lock() {
+                Ok(receiver) => receiver.recv(),
+                Err(_) => return,
+            };
+            match next {
+                Ok(task) => task.run(),
+                Err(_) => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::mpsc,
+        task::Wake,
+        thread::{self, Thread},
+        time::Duration,
+    };
+
+    use super::*;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park_timeout(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_task_handles() {
+        let pool = TaskPool::new(2);
+        assert_eq!(pool.workers(), 2);
+        assert_eq!(block_on(pool.spawn(|_| Ok(6 * 7))).expect("test assertion"), 42);
+
+        // A cancelled task stops at its next check
+        let (started, running) = mpsc::channel();
+        let handle = pool.spawn(move |token| {
+            let _ = started.send(());
+            while !token.is_cancelled() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            token.check("spin")?;
+            Ok(())
+        });
+        running.recv().expect("test assertion");
+        handle.cancel();
+        assert!(block_on(handle).expect_err("test assertion").is_cancelled());
+
+        // Tasks queued behind busy workers run on the waiting thread
+        let busy = TaskPool::new(1);
+        let (release, gate) = mpsc::channel::<()>();
+        let blocker = busy.spawn(move |_| {
+            let _ = gate.recv();
+            Ok(())
+        });
+        let caller = thread::current().id();
+        let inline = busy.spawn(move |_| Ok(thread::current().id() == caller));
+        assert!(inline.wait().expect("test assertion"));
+        let skipped = busy.spawn(|_| Ok(()));
+        skipped.cancel();
+        assert!(skipped.is_finished());
+        release.send(()).expect("test assertion");
+        blocker.wait().expect("test assertion");
+    }
+
+    #[test]
+    fn test_control_pause_resume() {
+        let pool = TaskPool::new(1);
+        let steps = Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let counter = Arc::clone(&steps);
+        let handle = pool.spawn::<(), _>(move |token| {
+            loop {
+                token.checkpoint("count")?;
+                counter.fetch_add(1, Ordering::Relaxed);
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+        let control = handle.control();
+        let counted = || steps.load(Ordering::Relaxed);
+        while counted() < 3 {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        control.pause();
+        assert!(control.is_paused());
+        thread::sleep(Duration::from_millis(10));
+        let held = counted();
+        thread::sleep(Duration::from_millis(30));
+        assert_eq!(counted(), held);
+
+        control.resume();
+        while counted() < held + 3 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        control.pause();
+        control.cancel();
+        let err = handle.wait().expect_err("test assertion");
+        assert!(err.is_cancelled());
+    }
+}
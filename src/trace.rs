@@ -0,0 +1,247 @@
+//! Timing spans around the render and export hot paths.
+//!
+//! With the `trace` feature, frame decode, effect evaluation, GPU submit,
+//! encode and mux are wrapped in [`Span`]s that report their phase, label
+//! and duration to the installed [`TraceSink`] when they end, so hosts can
+//! see where frame time goes in production builds. Spans nest per thread;
+//! each record carries its depth so a sink can rebuild the call tree.
+//!
+//! Without the feature, or with no sink installed, [`span`] returns an
+//! inert guard and [`event`] never builds its message, so the hooks cost
+//! nothing on the hot paths.
+
+#[cfg(feature = "trace")]
+use std::{
+    cell::Cell,
+    sync::{Arc, PoisonError, RwLock},
+};
+use std::{
+    thread::ThreadId,
+    time::{Duration, Instant},
+};
+
+/// Hot path a span measures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TracePhase {
+    /// Decoding a source frame.
+    Decode,
+    /// Evaluating effect parameters or running an effect on the CPU.
+    Effect,
+    /// Preparing and submitting GPU work.
+    GpuSubmit,
+    /// Encoding a rendered frame.
+    Encode,
+    /// Writing encoded data to its container or file.
+    Mux,
+}
+
+impl TracePhase {
+    /// Lowercase name of the phase, e.g. `"gpu_submit"`.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Decode => "decode",
+            Self::Effect => "effect",
+            Self::GpuSubmit => "gpu_submit",
+            Self::Encode => "encode",
+            Self::Mux => "mux",
+        }
+    }
+}
+
+/// A finished span.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanRecord {
+    /// Hot path measured.
+    pub phase:    TracePhase,
+    /// What was processed, e.g. the decoder or kernel name.
+    pub label:    String,
+    /// Number of spans open on the thread when this one started.
+    pub depth:    u32,
+    /// Thread the span ran on.
+    pub thread:   ThreadId,
+    /// When the span started.
+    pub started:  Instant,
+    /// How long the span was open.
+    pub duration: Duration,
+}
+
+/// Receiver of spans and events, installed with [`set_sink`].
+///
+/// Called on the thread that ran the span, so implementations should
+/// hand records off rather than do slow work inline.
+pub trait TraceSink: Send + Sync {
+    /// Called when a span ends.
+    fn span(&self, record: &SpanRecord);
+
+    /// Called for a point-in-time event. Ignored by default.
+    fn event(&self, phase: TracePhase, message: &str) {
+        let _ = (phase, message);
+    }
+}
+
+#[cfg(feature = "trace")]
+static SINK: RwLock<Option<Arc<dyn TraceSink>>> = RwLock::new(None);
+
+#[cfg(feature = "trace")]
+thread_local! {
+    static DEPTH: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Install the sink that receives spans and events, replacing any other.
+#[cfg(feature = "trace")]
+pub fn set_sink(sink: Arc<dyn TraceSink>) {
+    *SINK.write().unwrap_or_else(PoisonError::into_inner) = Some(sink);
+}
+
+/// Remove the installed sink; spans stop being timed.
+#[cfg(feature = "trace")]
+pub fn clear_sink() {
+    *SINK.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+#[cfg(feature = "trace")]
+fn sink() -> Option<Arc<dyn TraceSink>> {
+    SINK.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Open span; reports to the sink when dropped.
+#[must_use = "a span ends when it is dropped"]
+pub struct Span {
+    #[cfg(feature = "trace")]
+    active: Option<ActiveSpan>,
+}
+
+#[cfg(feature = "trace")]
+struct ActiveSpan {
+    sink:    Arc<dyn TraceSink>,
+    phase:   TracePhase,
+    label:   String,
+    depth:   u32,
+    started: Instant,
+}
+
+impl std::fmt::Debug for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        #[cfg(feature = "trace")]
+        {
+            let active = self.active.as_ref().map(|a| (a.phase, &a.label));
+            f.debug_struct("Span").field("active", &active).finish()
+        }
+        #[cfg(not(feature = "trace"))]
+        {
+            f.debug_struct("Span").finish()
+        }
+    }
+}
+
+#[cfg(feature = "trace")]
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(active) = self.active.take() else {
+            return;
+        };
+        DEPTH.with(|d| d.set(active.depth));
+        active.sink.span(&SpanRecord {
+            phase:    active.phase,
+            label:    active.label,
+            depth:    active.depth,
+            thread:   std::thread::current().id(),
+            started:  active.started,
+            duration: active.started.elapsed(),
+        });
+    }
+}
+
+/// Start timing `phase` for `label`; the span ends when the guard drops.
+#[inline]
+pub fn span(phase: TracePhase, label: &str) -> Span {
+    #[cfg(feature = "trace")]
+    {
+        let active = sink().map(|sink| {
+            let depth = DEPTH.with(|d| d.replace(d.get() + 1));
+            ActiveSpan { sink, phase, label: label.to_string(), depth, started: Instant::now() }
+        });
+        Span { active }
+    }
+    #[cfg(not(feature = "trace"))]
+    {
+        let _ = (phase, label);
+        Span {}
+    }
+}
+
+/// Report a point-in-time event. `message` is only built if a sink is
+/// installed.
+#[inline]
+pub fn event(phase: TracePhase, message: impl FnOnce() -> String) {
+    #[cfg(feature = "trace")]
+    if let Some(sink) = sink() {
+        sink.event(phase, &message());
+    }
+    #[cfg(not(feature = "trace"))]
+    let _ = (phase, message);
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Keeps the spans and events of one thread; other tests may be
+    /// tracing concurrently.
+    struct ThreadSink {
+        thread: ThreadId,
+        spans:  Mutex<Vec<SpanRecord>>,
+        events: Mutex<Vec<String>>,
+    }
+
+    impl TraceSink for ThreadSink {
+        fn span(&self, record: &SpanRecord) {
+            if record.thread == self.thread {
+                self.spans.lock().expect("test assertion").push(record.clone());
+            }
+        }
+
+        fn event(&self, phase: TracePhase, message: &str) {
+            if std::thread::current().id() == self.thread {
+                let message = format!("{}: {message}", phase.name());
+                self.events.lock().expect("test assertion").push(message);
+            }
+        }
+    }
+
+    #[test]
+    fn test_nested_spans() {
+        let sink = Arc::new(ThreadSink {
+            thread: std::thread::current().id(),
+            spans:  Mutex::new(Vec::new()),
+            events: Mutex::new(Vec::new()),
+        });
+        set_sink(sink.clone());
+        {
+            let _encode = span(TracePhase::Encode, "png");
+            let _effect = span(TracePhase::Effect, "unsharp");
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        drop(span(TracePhase::Mux, "out_00001.png"));
+        event(TracePhase::Encode, || "job 3 failed".into());
+        clear_sink();
+        drop(span(TracePhase::Decode, "ignored"));
+
+        let spans = sink.spans.lock().expect("test assertion");
+        let order: Vec<_> = spans.iter().map(|s| (s.phase, s.label.as_str(), s.depth)).collect();
+        assert_eq!(
+            order,
+            [
+                (TracePhase::Effect, "unsharp", 1),
+                (TracePhase::Encode, "png", 0),
+                (TracePhase::Mux, "out_00001.png", 0),
+            ]
+        );
+        assert!(spans[1].duration >= spans[0].duration);
+        assert!(spans[0].duration >= Duration::from_millis(2));
+        assert_eq!(*sink.events.lock().expect("test assertion"), ["encode: job 3 failed"]);
+    }
+}
@@ -0,0 +1,380 @@
+//! Broadcast Wave (BWF) and iXML metadata for production sound.
+//!
+//! Field recorders write a `bext` chunk carrying the recording start as a
+//! sample count since midnight (the BWF timecode) and an `iXML` chunk with
+//! scene, take and per-channel track names. Both are read on import,
+//! preserved on the clip and written back into exported WAV files.
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+};
+
+use super::core::{FrameRate, TimePosition};
+use crate::errors::{VideoEditorError, VideoEditorResult};
+
+/// Named channel from an iXML track list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IxmlTrack {
+    /// One-based channel index in the file.
+    pub channel_index: u16,
+    /// Track name (e.g. "Boom", "Lav 1").
+    pub name:          String,
+}
+
+/// Production metadata from an iXML chunk.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IxmlMetadata {
+    /// Project name.
+    pub project:       String,
+    /// Scene.
+    pub scene:         String,
+    /// Take.
+    pub take:          String,
+    /// Sound roll / tape name.
+    pub tape:          String,
+    /// Recorder note.
+    pub note:          String,
+    /// Timecode rate the recorder ran at.
+    pub timecode_rate: Option<FrameRate>,
+    /// Per-channel track names.
+    pub tracks:        Vec<IxmlTrack>,
+}
+
+impl IxmlMetadata {
+    /// Parses an iXML document.
+    #[must_use]
+    pub fn parse(xml: &str) -> Option<Self> {
+        tag(xml, "BWFXML")?;
+        let text = |name| tag(xml, name).map(unescape).unwrap_or_default();
+
+        let timecode_rate = tag(xml, "TIMECODE_RATE").and_then(|rate| {
+            let (num, den) = rate.trim().split_once('/')?;
+            Some(FrameRate::new(num.trim().parse().ok()?, den.trim().parse().ok()?))
+        });
+
+        let mut tracks = Vec::new();
+        let mut rest = tag(xml, "TRACK_LIST").unwrap_or_default();
+        while let Some(track) = tag(rest, "TRACK") {
+            if let Some(index) = tag(track, "CHANNEL_INDEX").and_then(|i| i.trim().parse().ok()) {
+                tracks.push(IxmlTrack {
+                    channel_index: index,
+                    name:          tag(track, "NAME").map(unescape).unwrap_or_default(),
+                });
+            }
+            let end = rest.find("</TRACK>").map_or(rest.len(), |i| i + "</TRACK>".len());
+            rest = &rest[end..];
+        }
+
+        Some(Self {
+            project: text("PROJECT"),
+            scene: text("SCENE"),
+            take: text("TAKE"),
+            tape: text("TAPE"),
+            note: text("NOTE"),
+            timecode_rate,
+            tracks,
+        })
+    }
+
+    /// Returns the name of a one-based channel.
+    #[must_use]
+    pub fn track_name(&self, channel_index: u16) -> Option<&str> {
+        self.tracks.iter().find(|t| t.channel_index == channel_index).map(|t| t.name.as_str())
+    }
+
+    /// Serializes to an iXML document.
+    #[must_use]
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<BWFXML>\n");
+        xml.push_str("<IXML_VERSION>1.61</IXML_VERSION>\n");
+        for (name, value) in [
+            ("PROJECT", &self.project),
+            ("SCENE", &self.scene),
+            ("TAKE", &self.take),
+            ("TAPE", &self.tape),
+            ("NOTE", &self.note),
+        ] {
+            if !value.is_empty() {
+                xml.push_str(&format!("<{name}>{}</{name}>\n", escape(value)));
+            }
+        }
+        if let Some(rate) = self.timecode_rate {
+            xml.push_str(&format!(
+                "<SPEED><TIMECODE_RATE>{}/{}</TIMECODE_RATE></SPEED>\n",
+                rate.numerator, rate.denominator
+            ));
+        }
+        if !self.tracks.is_empty() {
+            xml.push_str(&format!(
+                "<TRACK_LIST>\n<TRACK_COUNT>{}</TRACK_COUNT>\n",
+                self.tracks.len()
+            ));
+            for track in &self.tracks {
+                xml.push_str(&format!(
+                    "<TRACK><CHANNEL_INDEX>{0}</CHANNEL_INDEX><INTERLEAVE_INDEX>{0}</INTERLEAVE_INDEX><NAME>{1}</NAME></TRACK>\n",
+                    track.channel_index,
+                    escape(&track.name)
+                ));
+            }
+            xml.push_str("</TRACK_LIST>\n");
+        }
+        xml.push_str("</BWFXML>\n");
+        xml
+    }
+}
+
+/// Broadcast Wave metadata (`bext` chunk, EBU Tech 3285) with optional iXML.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BwfMetadata {
+    /// Free-text description.
+    pub description:          String,
+    /// Originating device or organization.
+    pub originator:           String,
+    /// Originator reference (unique ID).
+    pub originator_reference: String,
+    /// Origination date (`yyyy-mm-dd`).
+    pub origination_date:     String,
+    /// Origination time (`hh:mm:ss`).
+    pub origination_time:     String,
+    /// Recording start in samples since midnight.
+    pub time_reference:       u64,
+    /// Coding history.
+    pub coding_history:       String,
+    /// Sample rate from the `fmt ` chunk.
+    pub sample_rate:          u32,
+    /// Channel count from the `fmt ` chunk.
+    pub channels:             u16,
+    /// iXML production metadata.
+    pub ixml:                 Option<IxmlMetadata>,
+}
+
+impl BwfMetadata {
+    /// `bext` version written on export.
+    const BEXT_VERSION: u16 = 2;
+    /// Size of the fixed part of the `bext` chunk.
+    const BEXT_FIXED_LEN: usize = 602;
+
+    /// Creates metadata for an export starting at a time of day.
+    #[must_use]
+    pub fn for_export(
+        description: impl Into<String>, start: TimePosition, sample_rate: u32, channels: u16,
+    ) -> Self {
+        Self {
+            description: description.into(),
+            originator: "Essentia Video Editor".into(),
+            time_reference: start.ms * u64::from(sample_rate) / 1000,
+            sample_rate,
+            channels,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the recording start (BWF timecode) as a time of day.
+    #[must_use]
+    pub fn start_time(&self) -> TimePosition {
+        if self.sample_rate == 0 {
+            return TimePosition::from_ms(0);
+        }
+        TimePosition::from_ms(self.time_reference * 1000 / u64::from(self.sample_rate))
+    }
+
+    /// Returns the recording start formatted as `HH:MM:SS:FF`, using the
+    /// iXML timecode rate when present.
+    #[must_use]
+    pub fn start_timecode(&self, fallback_rate: FrameRate) -> String {
+        let rate = self.ixml.as_ref().and_then(|x| x.timecode_rate).unwrap_or(fallback_rate);
+        self.start_time().to_timecode(&rate)
+    }
+
+    /// Parses BWF and iXML metadata from an in-memory WAV file. Returns
+    /// `None` if the file isn't RIFF/WAVE or carries neither chunk.
+    #[must_use]
+    pub fn parse_wav(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return None;
+        }
+        let mut chunks = Vec::new();
+        let mut offset = 12;
+        while offset + 8 <= bytes.len() {
+            let id: [u8; 4] = bytes[offset..offset + 4].try_into().ok()?;
+            let size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().ok()?) as usize;
+            let body = bytes.get(offset + 8..offset + 8 + size)?;
+            chunks.push((id, body.to_vec()));
+            offset += 8 + size + size % 2;
+        }
+        Self::from_chunks(&chunks)
+    }
+
+    /// Reads BWF and iXML metadata from a WAV file without loading the
+    /// audio data.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Io` if the file cannot be read.
+    pub fn read_wav(path: &str) -> VideoEditorResult<Option<Self>> {
+        let io = |e: std::io::Error| VideoEditorError::io(path, e);
+        let mut file = File::open(path).map_err(io)?;
+        let mut header = [0u8; 12];
+        if file.read_exact(&mut header).is_err()
+            || &header[..4] != b"RIFF"
+            || &header[8..] != b"WAVE"
+        {
+            return Ok(None);
+        }
+
+        let mut chunks = Vec::new();
+        let mut chunk = [0u8; 8];
+        while file.read_exact(&mut chunk).is_ok() {
+            let id = [chunk[0], chunk[1], chunk[2], chunk[3]];
+            let size = u64::from(u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]));
+            if matches!(&id, b"fmt " | b"bext" | b"iXML") {
+                let mut body = vec![0u8; size as usize];
+                file.read_exact(&mut body).map_err(io)?;
+                chunks.push((id, body));
+                if size % 2 == 1 {
+                    file.seek(SeekFrom::Current(1)).map_err(io)?;
+                }
+            } else {
+                file.seek(SeekFrom::Current((size + size % 2) as i64)).map_err(io)?;
+            }
+        }
+        Ok(Self::from_chunks(&chunks))
+    }
+
+    fn from_chunks(chunks: &[([u8; 4], Vec<u8>)]) -> Option<Self> {
+        let find = |id: &[u8; 4]| chunks.iter().find(|(c, _)| c == id).map(|(_, b)| b.as_slice());
+        let bext = find(b"bext");
+        let ixml = find(b"iXML")
+            .and_then(|b| IxmlMetadata::parse(String::from_utf8_lossy(b).trim_end_matches('\0')));
+        if bext.is_none() && ixml.is_none() {
+            return None;
+        }
+
+        let mut metadata = Self { ixml, ..Default::default() };
+        if let Some(fmt) = find(b"fmt ").filter(|f| f.len() >= 8) {
+            metadata.channels = u16::from_le_bytes([fmt[2], fmt[3]]);
+            metadata.sample_rate = u32::from_le_bytes([fmt[4], fmt[5], fmt[6], fmt[7]]);
+        }
+        if let Some(bext) = bext.filter(|b| b.len() >= 346) {
+            let text = |range: core::ops::Range<usize>| {
+                let field = &bext[range];
+                let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+                String::from_utf8_lossy(&field[..end]).trim().to_string()
+            };
+            metadata.description = text(0..256);
+            metadata.originator = text(256..288);
+            metadata.originator_reference = text(288..320);
+            metadata.origination_date = text(320..330);
+            metadata.origination_time = text(330..338);
+            let low = u32::from_le_bytes([bext[338], bext[339], bext[340], bext[341]]);
+            let high = u32::from_le_bytes([bext[342], bext[343], bext[344], bext[345]]);
+            metadata.time_reference = u64::from(high) << 32 | u64::from(low);
+            if bext.len() > Self::BEXT_FIXED_LEN {
+                metadata.coding_history = text(Self::BEXT_FIXED_LEN..bext.len());
+            }
+        }
+        Some(metadata)
+    }
+
+    /// Builds the `bext` chunk body.
+    #[must_use]
+    pub fn to_bext(&self) -> Vec<u8> {
+        let mut bext = Vec::with_capacity(Self::BEXT_FIXED_LEN + self.coding_history.len());
+        for (value, len) in [
+            (&self.description, 256),
+            (&self.originator, 32),
+            (&self.originator_reference, 32),
+            (&self.origination_date, 10),
+            (&self.origination_time, 8),
+        ] {
+            let bytes = value.as_bytes();
+            let take = bytes.len().min(len);
+            bext.extend_from_slice(&bytes[..take]);
+            bext.resize(bext.len() + len - take, 0);
+        }
+        bext.extend_from_slice(&(self.time_reference as u32).to_le_bytes());
+        bext.extend_from_slice(&((self.time_reference >> 32) as u32).to_le_bytes());
+        bext.extend_from_slice(&Self::BEXT_VERSION.to_le_bytes());
+        // UMID, loudness fields and reserved space
+        bext.resize(Self::BEXT_FIXED_LEN, 0);
+        bext.extend_from_slice(self.coding_history.as_bytes());
+        bext
+    }
+
+    /// Embeds the metadata into a WAV file, replacing existing `bext` and
+    /// `iXML` chunks. The chunks are placed before the audio data.
+    ///
+    /// # Errors
+    ///
+    /// Returns `VideoEditorError::Export` if `wav` is not a RIFF/WAVE file.
+    pub fn write_wav(&self, wav: &[u8]) -> VideoEditorResult<Vec<u8>> {
+        if wav.len() < 12 || &wav[..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+            return Err(VideoEditorError::Export("Not a RIFF/WAVE file".into()));
+        }
+
+        let mut out = Vec::with_capacity(wav.len() + Self::BEXT_FIXED_LEN + 1024);
+        out.extend_from_slice(b"RIFFsizeWAVE");
+        let push_chunk = |out: &mut Vec<u8>, id: &[u8], body: &[u8]| {
+            out.extend_from_slice(id);
+            out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            out.extend_from_slice(body);
+            if body.len() % 2 == 1 {
+                out.push(0);
+            }
+        };
+
+        let mut written = false;
+        let mut offset = 12;
+        while offset + 8 <= wav.len() {
+            let id = &wav[offset..offset + 4];
+            let size = u32::from_le_bytes([
+                wav[offset + 4],
+                wav[offset + 5],
+                wav[offset + 6],
+                wav[offset + 7],
+            ]) as usize;
+            let end = (offset + 8 + size).min(wav.len());
+            if !written && id == b"data" {
+                push_chunk(&mut out, b"bext", &self.to_bext());
+                if let Some(ixml) = &self.ixml {
+                    push_chunk(&mut out, b"iXML", ixml.to_xml().as_bytes());
+                }
+                written = true;
+            }
+            if id != b"bext" && id != b"iXML" {
+                push_chunk(&mut out, id, &wav[offset + 8..end]);
+            }
+            offset += 8 + size + size % 2;
+        }
+        if !written {
+            return Err(VideoEditorError::Export("WAV file has no data chunk".into()));
+        }
+
+        let riff_size = (out.len() - 8) as u32;
+        out[4..8].copy_from_slice(&riff_size.to_le_bytes());
+        Ok(out)
+    }
+}
+
+/// Returns the text between the first `<name>` and its closing tag.
+fn tag<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{name}>");
+    let close = format!("</{name}>");
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find(&close)? + start;
+    Some(&xml[start..end])
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn unescape(text: &str) -> String {
+    text.trim()
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
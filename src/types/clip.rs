@@ -1,8 +1,17 @@
 //! Clip types for media assets.
 //!
-//! Video and audio clip representations with metadata.
+//! Video, audio and image sequence clip representations with metadata.
 
-use super::core::{AudioFormat, FrameRate, Resolution, TimePosition, VideoFormat};
+use std::sync::Arc;
+
+use super::{
+    bwf::BwfMetadata,
+    core::{
+        AudioFormat, FieldOrder, FrameRate, PixelAspectRatio, ProjectionMode, Resolution, Rotation,
+        TimePosition, VideoFormat,
+    },
+};
+use crate::converter::{ImageSequenceInfo, SequencePattern};
 
 /// Video clip state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
@@ -18,6 +27,8 @@ pub enum ClipState {
     Error,
     /// Clip is being processed.
     Processing,
+    /// Clip media is missing; clips using it render a placeholder slate.
+    Offline,
 }
 
 /// Common clip metadata.
@@ -66,25 +77,37 @@ impl ClipMetadata {
 #[derive(Debug, Clone)]
 pub struct VideoClip {
     /// Unique clip ID.
-    pub id:          u64,
+    pub id:                u64,
     /// File path or URI.
-    pub path:        String,
+    pub path:              String,
     /// Video resolution.
-    pub resolution:  Resolution,
+    pub resolution:        Resolution,
     /// Frame rate.
-    pub frame_rate:  FrameRate,
+    pub frame_rate:        FrameRate,
     /// Total duration.
-    pub duration:    TimePosition,
+    pub duration:          TimePosition,
     /// Video codec format.
-    pub format:      VideoFormat,
+    pub format:            VideoFormat,
+    /// Progressive or interlaced scan.
+    pub field_order:       FieldOrder,
+    /// Shape of the stored pixels.
+    pub pixel_aspect:      PixelAspectRatio,
+    /// Rotation tagged in the source.
+    pub rotation:          Rotation,
+    /// User rotation replacing a missing or wrong tag.
+    pub rotation_override: Option<Rotation>,
+    /// Flat or 360° media.
+    pub projection:        ProjectionMode,
     /// Clip state.
-    pub state:       ClipState,
+    pub state:             ClipState,
     /// Clip metadata.
-    pub metadata:    ClipMetadata,
+    pub metadata:          ClipMetadata,
     /// Has audio track.
-    pub has_audio:   bool,
+    pub has_audio:         bool,
     /// Number of frames.
-    pub frame_count: u64,
+    pub frame_count:       u64,
+    /// Source timecode of the first frame, as a time of day.
+    pub start_timecode:    Option<TimePosition>,
 }
 
 impl VideoClip {
@@ -98,10 +121,16 @@ impl VideoClip {
             frame_rate: FrameRate::default(),
             duration: TimePosition::default(),
             format: VideoFormat::default(),
+            field_order: FieldOrder::Progressive,
+            pixel_aspect: PixelAspectRatio::SQUARE,
+            rotation: Rotation::None,
+            rotation_override: None,
+            projection: ProjectionMode::Flat,
             state: ClipState::Unloaded,
             metadata: ClipMetadata::default(),
             has_audio: false,
             frame_count: 0,
+            start_timecode: None,
         }
     }
 
@@ -134,6 +163,55 @@ impl VideoClip {
         self
     }
 
+    /// Sets the scan.
+    #[must_use]
+    pub fn with_field_order(mut self, field_order: FieldOrder) -> Self {
+        self.field_order = field_order;
+        self
+    }
+
+    /// Sets the pixel aspect.
+    #[must_use]
+    pub fn with_pixel_aspect(mut self, pixel_aspect: PixelAspectRatio) -> Self {
+        self.pixel_aspect = pixel_aspect;
+        self
+    }
+
+    /// Sets the tagged rotation.
+    #[must_use]
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Sets the projection.
+    #[must_use]
+    pub fn with_projection(mut self, projection: ProjectionMode) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Returns the rotation the clip is shown with: the override if set,
+    /// else the tag.
+    #[must_use]
+    pub fn effective_rotation(&self) -> Rotation {
+        self.rotation_override.unwrap_or(self.rotation)
+    }
+
+    /// Returns the size the clip is shown at, in square pixels and upright.
+    #[must_use]
+    pub fn display_resolution(&self) -> Resolution {
+        let square = self.resolution.with_pixel_aspect(self.pixel_aspect, PixelAspectRatio::SQUARE);
+        self.effective_rotation().apply(square)
+    }
+
+    /// Sets the source timecode of the first frame.
+    #[must_use]
+    pub fn with_start_timecode(mut self, timecode: TimePosition) -> Self {
+        self.start_timecode = Some(timecode);
+        self
+    }
+
     /// Returns whether the clip is ready.
     #[must_use]
     pub const fn is_ready(&self) -> bool {
@@ -170,6 +248,8 @@ pub struct AudioClip {
     pub bit_depth:    u8,
     /// Total sample count.
     pub sample_count: u64,
+    /// Broadcast Wave and iXML metadata from production sound.
+    pub bwf:          Option<BwfMetadata>,
 }
 
 impl AudioClip {
@@ -187,6 +267,7 @@ impl AudioClip {
             metadata: ClipMetadata::default(),
             bit_depth: 16,
             sample_count: 0,
+            bwf: None,
         }
     }
 
@@ -219,6 +300,29 @@ impl AudioClip {
         self
     }
 
+    /// Attaches BWF metadata, taking the sample rate and channel count from
+    /// it when present.
+    #[must_use]
+    pub fn with_bwf(mut self, bwf: BwfMetadata) -> Self {
+        if bwf.sample_rate > 0 {
+            self.sample_rate = bwf.sample_rate;
+        }
+        if bwf.channels > 0 {
+            self.channels = bwf.channels.min(u16::from(u8::MAX)) as u8;
+        }
+        if let Some(ixml) = bwf.ixml.as_ref().filter(|x| !x.scene.is_empty()) {
+            self.metadata.title = format!("{} / {}", ixml.scene, ixml.take);
+        }
+        self.bwf = Some(bwf);
+        self
+    }
+
+    /// Returns the BWF recording start as a time of day.
+    #[must_use]
+    pub fn start_timecode(&self) -> Option<TimePosition> {
+        self.bwf.as_ref().map(BwfMetadata::start_time)
+    }
+
     /// Returns whether the clip is ready.
     #[must_use]
     pub const fn is_ready(&self) -> bool {
@@ -231,3 +335,199 @@ impl AudioClip {
         (position.ms * self.sample_rate as u64) / 1000
     }
 }
+
+/// Image sequence clip (EXR/DPX/PNG frame sequences).
+///
+/// Behaves like a video clip on the timeline: each timeline frame maps to one
+/// file of the sequence. Decoded frames are kept in a small per-clip cache.
+#[derive(Debug, Clone)]
+pub struct ImageSequenceClip {
+    /// Unique clip ID.
+    pub id:             u64,
+    /// Sequence file pattern.
+    pub pattern:        SequencePattern,
+    /// First frame number on disk.
+    pub first_frame:    u64,
+    /// Last frame number on disk.
+    pub last_frame:     u64,
+    /// Frame numbers missing from disk.
+    pub missing_frames: Vec<u64>,
+    /// Frame resolution.
+    pub resolution:     Resolution,
+    /// Assigned playback frame rate.
+    pub frame_rate:     FrameRate,
+    /// Clip state.
+    pub state:          ClipState,
+    /// Clip metadata.
+    pub metadata:       ClipMetadata,
+    /// Decoded frame cache.
+    cache:              SequenceFrameCache,
+}
+
+impl ImageSequenceClip {
+    /// Default number of decoded frames kept in the cache.
+    pub const DEFAULT_CACHE_FRAMES: usize = 32;
+
+    /// Creates a new image sequence clip from scanned sequence info.
+    #[must_use]
+    pub fn new(id: u64, info: ImageSequenceInfo) -> Self {
+        Self {
+            id,
+            pattern: info.pattern,
+            first_frame: info.first_frame,
+            last_frame: info.last_frame,
+            missing_frames: info.missing_frames,
+            resolution: Resolution::default(),
+            frame_rate: FrameRate::FPS_24,
+            state: ClipState::Unloaded,
+            metadata: ClipMetadata::default(),
+            cache: SequenceFrameCache::new(Self::DEFAULT_CACHE_FRAMES),
+        }
+    }
+
+    /// Sets the playback frame rate.
+    #[must_use]
+    pub fn with_frame_rate(mut self, frame_rate: FrameRate) -> Self {
+        self.frame_rate = frame_rate;
+        self
+    }
+
+    /// Sets the resolution.
+    #[must_use]
+    pub fn with_resolution(mut self, resolution: Resolution) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// Sets the decoded frame cache capacity.
+    #[must_use]
+    pub fn with_cache_capacity(mut self, frames: usize) -> Self {
+        self.cache = SequenceFrameCache::new(frames);
+        self
+    }
+
+    /// Returns the number of frames spanned by the sequence.
+    #[must_use]
+    pub const fn frame_count(&self) -> u64 {
+        self.last_frame - self.first_frame + 1
+    }
+
+    /// Returns the clip duration at the assigned frame rate.
+    #[must_use]
+    pub fn duration(&self) -> TimePosition {
+        TimePosition::from_frame(self.frame_count(), &self.frame_rate)
+    }
+
+    /// Returns whether any frames are missing.
+    #[must_use]
+    pub fn has_missing_frames(&self) -> bool {
+        !self.missing_frames.is_empty()
+    }
+
+    /// Returns whether a sequence frame is missing from disk.
+    #[must_use]
+    pub fn is_missing(&self, frame: u64) -> bool {
+        self.missing_frames.binary_search(&frame).is_ok()
+    }
+
+    /// Returns the sequence frame number shown at a clip-relative time.
+    #[must_use]
+    pub fn frame_at(&self, position: TimePosition) -> Option<u64> {
+        let frame = self.first_frame + position.to_frame(&self.frame_rate);
+        (frame <= self.last_frame).then_some(frame)
+    }
+
+    /// Returns the frame to display for `frame`, holding the previous
+    /// available frame when it is missing.
+    #[must_use]
+    pub fn resolve_frame(&self, frame: u64) -> Option<u64> {
+        if frame < self.first_frame || frame > self.last_frame {
+            return None;
+        }
+        (self.first_frame..=frame).rev().find(|f| !self.is_missing(*f))
+    }
+
+    /// Returns the file path of a sequence frame.
+    #[must_use]
+    pub fn frame_path(&self, frame: u64) -> String {
+        self.pattern.frame_path(frame)
+    }
+
+    /// Returns a cached decoded frame.
+    pub fn cached_frame(&mut self, frame: u64) -> Option<Arc<[u8]>> {
+        self.cache.get(frame)
+    }
+
+    /// Stores a decoded frame in the cache.
+    pub fn cache_frame(&mut self, frame: u64, data: Arc<[u8]>) {
+        self.cache.put(frame, data);
+    }
+
+    /// Returns the number of cached frames.
+    #[must_use]
+    pub fn cached_count(&self) -> usize {
+        self.cache.entries.len()
+    }
+
+    /// Drops all cached frames.
+    pub fn clear_cache(&mut self) {
+        self.cache.entries.clear();
+    }
+
+    /// Returns a video clip view so the sequence can be placed on the timeline.
+    #[must_use]
+    pub fn to_video_clip(&self) -> VideoClip {
+        let mut clip = VideoClip::new(self.id, self.pattern.printf())
+            .with_resolution(self.resolution)
+            .with_frame_rate(self.frame_rate)
+            .with_duration(self.duration())
+            .with_format(VideoFormat::Raw);
+        clip.frame_count = self.frame_count();
+        clip.state = self.state;
+        clip.metadata = self.metadata.clone();
+        clip
+    }
+}
+
+/// Least recently used cache of decoded sequence frames.
+#[derive(Clone)]
+struct SequenceFrameCache {
+    /// Maximum number of cached frames.
+    capacity: usize,
+    /// Cached frames, least recently used first.
+    entries:  Vec<(u64, Arc<[u8]>)>,
+}
+
+impl SequenceFrameCache {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, entries: Vec::with_capacity(capacity) }
+    }
+
+    fn get(&mut self, frame: u64) -> Option<Arc<[u8]>> {
+        let pos = self.entries.iter().position(|(f, _)| *f == frame)?;
+        let entry = self.entries.remove(pos);
+        let data = Arc::clone(&entry.1);
+        self.entries.push(entry);
+        Some(data)
+    }
+
+    fn put(&mut self, frame: u64, data: Arc<[u8]>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.entries.retain(|(f, _)| *f != frame);
+        if self.entries.len() >= self.capacity {
+            self.entries.remove(0);
+        }
+        self.entries.push((frame, data));
+    }
+}
+
+impl std::fmt::Debug for SequenceFrameCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SequenceFrameCache")
+            .field("capacity", &self.capacity)
+            .field("cached", &self.entries.len())
+            .finish()
+    }
+}
@@ -74,6 +74,26 @@ impl Resolution {
         let scale = target_height as f64 / self.height as f64;
         Self { width: (self.width as f64 * scale).round() as u32, height: target_height }
     }
+
+    /// Calculates the shape of the picture as shown, for pixels of the
+    /// given aspect.
+    #[must_use]
+    pub fn display_aspect_ratio(&self, pixel_aspect: PixelAspectRatio) -> f64 {
+        self.aspect_ratio() * pixel_aspect.as_f64()
+    }
+
+    /// Calculates the resolution with pixels of one aspect resampled to
+    /// another, keeping the line count and the display aspect.
+    ///
+    /// Desqueezing 1440x1080 HDV to square pixels gives 1920x1080.
+    #[must_use]
+    pub fn with_pixel_aspect(&self, from: PixelAspectRatio, to: PixelAspectRatio) -> Self {
+        if from == to {
+            return *self;
+        }
+        let width = self.width as f64 * from.as_f64() / to.as_f64();
+        Self { width: (width.round() as u32).max(1), height: self.height }
+    }
 }
 
 impl Default for Resolution {
@@ -82,6 +102,112 @@ impl Default for Resolution {
     }
 }
 
+/// Shape of a pixel as width over height; 1:1 for square pixels.
+///
+/// SD video, HDV and anamorphic lenses store pictures on a raster whose
+/// pixels are wider or narrower than they are tall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PixelAspectRatio {
+    /// Pixel width.
+    pub numerator:   u32,
+    /// Pixel height.
+    pub denominator: u32,
+}
+
+impl PixelAspectRatio {
+    /// Square pixels.
+    pub const SQUARE: PixelAspectRatio = PixelAspectRatio { numerator: 1, denominator: 1 };
+    /// NTSC DV and D1, 4:3 (720x480).
+    pub const NTSC: PixelAspectRatio = PixelAspectRatio { numerator: 10, denominator: 11 };
+    /// NTSC DV and D1, 16:9 (720x480).
+    pub const NTSC_WIDE: PixelAspectRatio = PixelAspectRatio { numerator: 40, denominator: 33 };
+    /// PAL DV and D1, 4:3 (720x576).
+    pub const PAL: PixelAspectRatio = PixelAspectRatio { numerator: 59, denominator: 54 };
+    /// PAL DV and D1, 16:9 (720x576).
+    pub const PAL_WIDE: PixelAspectRatio = PixelAspectRatio { numerator: 118, denominator: 81 };
+    /// HDV and XDCAM HD (1440x1080), DVCPRO HD 720p (960x720).
+    pub const HDV: PixelAspectRatio = PixelAspectRatio { numerator: 4, denominator: 3 };
+    /// DVCPRO HD 1080 (1280x1080).
+    pub const DVCPRO_HD: PixelAspectRatio = PixelAspectRatio { numerator: 3, denominator: 2 };
+    /// 2x anamorphic lens.
+    pub const ANAMORPHIC_2X: PixelAspectRatio = PixelAspectRatio { numerator: 2, denominator: 1 };
+
+    /// Named ratios, tried by [`Self::from_f64`].
+    const KNOWN: [Self; 8] = [
+        Self::SQUARE,
+        Self::NTSC,
+        Self::NTSC_WIDE,
+        Self::PAL,
+        Self::PAL_WIDE,
+        Self::HDV,
+        Self::DVCPRO_HD,
+        Self::ANAMORPHIC_2X,
+    ];
+
+    /// Creates a new pixel aspect ratio.
+    #[must_use]
+    pub const fn new(numerator: u32, denominator: u32) -> Self {
+        Self { numerator, denominator: if denominator == 0 { 1 } else { denominator } }
+    }
+
+    /// Creates the ratio closest to a decimal value, e.g. a project
+    /// setting. Values within 0.001 of a named ratio give that ratio.
+    #[must_use]
+    pub fn from_f64(value: f64) -> Self {
+        if !value.is_finite() || value <= 0.0 {
+            return Self::SQUARE;
+        }
+        if let Some(known) = Self::KNOWN.into_iter().find(|k| (k.as_f64() - value).abs() < 0.001) {
+            return known;
+        }
+        let numerator = (value * 1000.0).round() as u32;
+        let divisor = gcd(numerator.max(1), 1000);
+        Self::new(numerator.max(1) / divisor, 1000 / divisor)
+    }
+
+    /// Pixel aspect implied by a raster that is only used for
+    /// non-square video, e.g. 1440x1080 HDV. `None` for other sizes.
+    ///
+    /// SD rasters are assumed 4:3; the wide variants can only come from
+    /// the source's own tag.
+    #[must_use]
+    pub const fn for_raster(resolution: Resolution) -> Option<Self> {
+        match (resolution.width, resolution.height) {
+            (720, 480 | 486) => Some(Self::NTSC),
+            (720, 576) => Some(Self::PAL),
+            (1440, 1080) | (960, 720) => Some(Self::HDV),
+            (1280, 1080) => Some(Self::DVCPRO_HD),
+            _ => None,
+        }
+    }
+
+    /// Returns the ratio as a float.
+    #[must_use]
+    pub fn as_f64(&self) -> f64 {
+        if self.denominator == 0 {
+            return 1.0;
+        }
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    /// Returns whether pixels are square.
+    #[must_use]
+    pub const fn is_square(&self) -> bool {
+        self.numerator == self.denominator
+    }
+}
+
+impl Default for PixelAspectRatio {
+    fn default() -> Self {
+        Self::SQUARE
+    }
+}
+
+/// Greatest common divisor.
+const fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
 /// Frame rate representation using numerator/denominator.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct FrameRate {
@@ -213,6 +339,115 @@ impl VideoFormat {
     }
 }
 
+/// Scan of a video stream: progressive, or interlaced with the field
+/// shown first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FieldOrder {
+    /// Whole frames.
+    #[default]
+    Progressive,
+    /// Interlaced, top field (even lines) first.
+    TopFieldFirst,
+    /// Interlaced, bottom field (odd lines) first.
+    BottomFieldFirst,
+}
+
+impl FieldOrder {
+    /// Returns whether frames are made of two fields.
+    #[must_use]
+    pub const fn is_interlaced(&self) -> bool {
+        !matches!(self, Self::Progressive)
+    }
+
+    /// Parity of the lines in the field shown first (0 = even lines), or
+    /// `None` when progressive.
+    #[must_use]
+    pub const fn first_field_parity(&self) -> Option<usize> {
+        match self {
+            Self::Progressive => None,
+            Self::TopFieldFirst => Some(0),
+            Self::BottomFieldFirst => Some(1),
+        }
+    }
+}
+
+/// Clockwise turn a player applies to show a stream upright, e.g. from
+/// the orientation tag of phone footage shot in portrait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Rotation {
+    /// Shown as stored.
+    #[default]
+    None,
+    /// Turned 90° clockwise.
+    Clockwise90,
+    /// Turned upside down.
+    Clockwise180,
+    /// Turned 270° clockwise (90° counter-clockwise).
+    Clockwise270,
+}
+
+impl Rotation {
+    /// Creates a rotation from a clockwise angle in degrees, e.g. a
+    /// `rotate` tag. Negative angles turn counter-clockwise. `None` unless
+    /// the angle is a multiple of 90°.
+    #[must_use]
+    pub const fn from_degrees(degrees: i32) -> Option<Self> {
+        match degrees.rem_euclid(360) {
+            0 => Some(Self::None),
+            90 => Some(Self::Clockwise90),
+            180 => Some(Self::Clockwise180),
+            270 => Some(Self::Clockwise270),
+            _ => None,
+        }
+    }
+
+    /// Returns the clockwise angle in degrees.
+    #[must_use]
+    pub const fn degrees(&self) -> u32 {
+        match self {
+            Self::None => 0,
+            Self::Clockwise90 => 90,
+            Self::Clockwise180 => 180,
+            Self::Clockwise270 => 270,
+        }
+    }
+
+    /// Returns whether width and height trade places when shown.
+    #[must_use]
+    pub const fn swaps_dimensions(&self) -> bool {
+        matches!(self, Self::Clockwise90 | Self::Clockwise270)
+    }
+
+    /// Returns the size a stored `resolution` is shown at.
+    #[must_use]
+    pub const fn apply(&self, resolution: Resolution) -> Resolution {
+        if self.swaps_dimensions() {
+            Resolution { width: resolution.height, height: resolution.width }
+        } else {
+            resolution
+        }
+    }
+}
+
+/// How a stream maps onto the view: a flat picture, or a 360° sphere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ProjectionMode {
+    /// Flat picture.
+    #[default]
+    Flat,
+    /// Full sphere, longitude across and latitude down, 2:1 aspect.
+    Equirectangular,
+}
+
+impl ProjectionMode {
+    /// Returns whether frames cover a sphere and need reframing to show
+    /// a flat view.
+    #[must_use]
+    pub const fn is_spherical(&self) -> bool {
+        matches!(self, Self::Equirectangular)
+    }
+}
+
 /// Audio codec formats.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum AudioFormat {
@@ -2,7 +2,13 @@
 //!
 //! Inspired by rust-av's Frame and media-rs's FrameData abstractions.
 
-use std::sync::Arc;
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
 use super::{
     color::Formaton,
@@ -357,92 +363,306 @@ impl VideoFrame {
     }
 }
 
-/// Frame buffer for GPU-compatible memory management.
+/// Row alignment of pooled frame buffers, in bytes.
+pub const STRIDE_ALIGNMENT: usize = 32;
+
+/// Pixel layout of a pooled frame buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BufferFormat {
+    /// 8-bit RGBA, straight alpha.
+    #[default]
+    Rgba8,
+    /// 8-bit BGRA, straight alpha.
+    Bgra8,
+    /// 16-bit half float RGBA.
+    Rgba16F,
+    /// 32-bit float RGBA.
+    Rgba32F,
+}
+
+impl BufferFormat {
+    /// Bytes per pixel.
+    #[must_use]
+    pub const fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::Rgba8 | Self::Bgra8 => 4,
+            Self::Rgba16F => 8,
+            Self::Rgba32F => 16,
+        }
+    }
+}
+
+/// Size, pixel format and row stride of a frame buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BufferDescriptor {
+    /// Width in pixels.
+    pub width:  u32,
+    /// Height in pixels.
+    pub height: u32,
+    /// Pixel format.
+    pub format: BufferFormat,
+    /// Bytes per row, at least `width` pixels.
+    pub stride: usize,
+}
+
+impl BufferDescriptor {
+    /// Descriptor with rows padded to [`STRIDE_ALIGNMENT`].
+    #[must_use]
+    pub const fn new(width: u32, height: u32, format: BufferFormat) -> Self {
+        let row = width as usize * format.bytes_per_pixel();
+        let stride = row.next_multiple_of(STRIDE_ALIGNMENT);
+        Self { width, height, format, stride }
+    }
+
+    /// Descriptor with unpadded rows.
+    #[must_use]
+    pub const fn packed(width: u32, height: u32, format: BufferFormat) -> Self {
+        Self { width, height, format, stride: width as usize * format.bytes_per_pixel() }
+    }
+
+    /// Packed RGBA8, the layout decoders, effects and the renderer
+    /// exchange.
+    #[must_use]
+    pub const fn rgba8(width: u32, height: u32) -> Self {
+        Self::packed(width, height, BufferFormat::Rgba8)
+    }
+
+    /// Whether rows have no padding.
+    #[must_use]
+    pub const fn is_packed(&self) -> bool {
+        self.stride == self.width as usize * self.format.bytes_per_pixel()
+    }
+
+    /// Total buffer size in bytes.
+    #[must_use]
+    pub const fn byte_size(&self) -> usize {
+        self.stride * self.height as usize
+    }
+}
+
+/// Frame memory, returned to its [`FrameBufferPool`] when dropped.
+///
+/// Dereferences to its bytes. Buffers made from a `Vec` belong to no pool
+/// and are freed as usual.
 pub struct FrameBuffer {
-    /// Underlying data.
-    data:     Vec<u8>,
-    /// Buffer size.
-    size:     usize,
-    /// Reference count.
-    refcount: std::sync::atomic::AtomicU32,
+    data:       Vec<u8>,
+    descriptor: Option<BufferDescriptor>,
+    pool:       Weak<PoolShared>,
 }
 
 impl FrameBuffer {
-    /// Creates a new frame buffer with the specified size.
+    /// Layout the buffer was acquired with, if it came from a pool.
     #[must_use]
-    pub fn new(size: usize) -> Self {
-        Self { data: vec![0u8; size], size, refcount: std::sync::atomic::AtomicU32::new(1) }
+    pub const fn descriptor(&self) -> Option<&BufferDescriptor> {
+        self.descriptor.as_ref()
     }
 
-    /// Returns the buffer size.
+    /// Whether the buffer goes back to a pool when dropped.
     #[must_use]
-    pub const fn size(&self) -> usize {
-        self.size
+    pub fn is_pooled(&self) -> bool {
+        self.pool.strong_count() > 0
     }
 
-    /// Returns a slice of the buffer data.
+    /// Takes the bytes out of the buffer; they are not returned to the
+    /// pool.
     #[must_use]
-    pub fn as_slice(&self) -> &[u8] {
+    pub fn into_vec(mut self) -> Vec<u8> {
+        self.pool = Weak::new();
+        std::mem::take(&mut self.data)
+    }
+}
+
+impl Deref for FrameBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
         &self.data
     }
+}
 
-    /// Returns a mutable slice of the buffer data.
-    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+impl DerefMut for FrameBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
         &mut self.data
     }
+}
 
-    /// Increments the reference count.
-    pub fn retain(&self) {
-        self.refcount.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+impl From<Vec<u8>> for FrameBuffer {
+    fn from(data: Vec<u8>) -> Self {
+        Self { data, descriptor: None, pool: Weak::new() }
+    }
+}
+
+impl Clone for FrameBuffer {
+    fn clone(&self) -> Self {
+        Self {
+            data:       self.data.clone(),
+            descriptor: self.descriptor,
+            pool:       self.pool.clone(),
+        }
     }
+}
 
-    /// Decrements the reference count and returns true if it reached zero.
-    pub fn release(&self) -> bool {
-        self.refcount.fetch_sub(1, std::sync::atomic::Ordering::Release) == 1
+impl PartialEq for FrameBuffer {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
     }
 }
 
-/// Frame buffer pool for efficient frame reuse.
+impl Drop for FrameBuffer {
+    fn drop(&mut self) {
+        if let Some(descriptor) = self.descriptor
+            && let Some(pool) = self.pool.upgrade()
+        {
+            pool.recycle(descriptor, std::mem::take(&mut self.data));
+        }
+    }
+}
+
+impl std::fmt::Debug for FrameBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrameBuffer")
+            .field("size", &self.data.len())
+            .field("descriptor", &self.descriptor)
+            .field("pooled", &self.is_pooled())
+            .finish()
+    }
+}
+
+/// Allocation counters of a [`FrameBufferPool`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FramePoolStats {
+    /// Buffers allocated because none of the format was free.
+    pub allocated: u64,
+    /// Acquisitions served by a recycled buffer.
+    pub reused:    u64,
+    /// Buffers free in the pool.
+    pub free:      usize,
+}
+
+#[derive(Debug, Default)]
+struct PoolShared {
+    /// Free buffers by layout.
+    free:      Mutex<Vec<(BufferDescriptor, Vec<Vec<u8>>)>>,
+    /// Most free buffers kept per layout.
+    max_free:  usize,
+    allocated: AtomicU64,
+    reused:    AtomicU64,
+}
+
+impl PoolShared {
+    fn lock(&self) -> MutexGuard<'_, Vec<(BufferDescriptor, Vec<Vec<u8>>)>> {
+        self.free.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn recycle(&self, descriptor: BufferDescriptor, data: Vec<u8>) {
+        let mut free = self.lock();
+        let index = match free.iter().position(|(d, _)| *d == descriptor) {
+            Some(index) => index,
+            None => {
+                free.push((descriptor, Vec::new()));
+                free.len() - 1
+            },
+        };
+        let buffers = &mut free[index].1;
+        if buffers.len() < self.max_free && data.len() == descriptor.byte_size() {
+            buffers.push(data);
+        }
+    }
+}
+
+/// Pool of frame buffers shared by decode, effects, the preview cache and
+/// export.
+///
+/// Handles are reference-counted: clones share the same free lists.
+/// [`acquire`](Self::acquire) hands out a recycled buffer of the requested
+/// layout when one is free, so frames moving from decoder to cache to
+/// encoder change owner without being copied and steady-state playback
+/// allocates nothing. Buffers outliving every handle are simply freed.
+#[derive(Debug, Clone)]
 pub struct FrameBufferPool {
-    /// Available buffers.
-    buffers:     Vec<Arc<FrameBuffer>>,
-    /// Buffer size for this pool.
-    buffer_size: usize,
-    /// Maximum pool size.
-    max_size:    usize,
+    shared: Arc<PoolShared>,
 }
 
 impl FrameBufferPool {
-    /// Creates a new frame buffer pool.
+    /// Default number of free buffers kept per layout.
+    pub const DEFAULT_MAX_FREE: usize = 16;
+
+    /// Creates a pool keeping up to `max_free` free buffers per layout.
     #[must_use]
-    pub fn new(buffer_size: usize, max_size: usize) -> Self {
-        Self { buffers: Vec::with_capacity(max_size), buffer_size, max_size }
+    pub fn new(max_free: usize) -> Self {
+        Self { shared: Arc::new(PoolShared { max_free, ..PoolShared::default() }) }
     }
 
-    /// Acquires a buffer from the pool or creates a new one.
-    pub fn acquire(&mut self) -> Arc<FrameBuffer> {
-        if let Some(buffer) = self.buffers.pop() {
-            buffer
-        } else {
-            Arc::new(FrameBuffer::new(self.buffer_size))
+    /// Allocates `count` free buffers of a layout ahead of use, up to the
+    /// per-layout limit.
+    pub fn preallocate(&self, descriptor: BufferDescriptor, count: usize) {
+        let free = self.free_count(&descriptor);
+        for _ in free..count.min(self.shared.max_free) {
+            self.shared.allocated.fetch_add(1, Ordering::Relaxed);
+            self.shared.recycle(descriptor, vec![0; descriptor.byte_size()]);
         }
     }
 
-    /// Returns a buffer to the pool.
-    pub fn release(&mut self, buffer: Arc<FrameBuffer>) {
-        if self.buffers.len() < self.max_size {
-            self.buffers.push(buffer);
-        }
+    /// Takes a buffer of `descriptor`'s layout, recycled if one is free.
+    ///
+    /// Recycled buffers hold whatever the last user wrote; callers
+    /// overwrite the whole frame.
+    #[must_use]
+    pub fn acquire(&self, descriptor: BufferDescriptor) -> FrameBuffer {
+        let recycled = self
+            .shared
+            .lock()
+            .iter_mut()
+            .find(|(d, _)| *d == descriptor)
+            .and_then(|(_, buffers)| buffers.pop());
+        let data = match recycled {
+            Some(data) => {
+                self.shared.reused.fetch_add(1, Ordering::Relaxed);
+                data
+            },
+            None => {
+                self.shared.allocated.fetch_add(1, Ordering::Relaxed);
+                vec![0; descriptor.byte_size()]
+            },
+        };
+        FrameBuffer { data, descriptor: Some(descriptor), pool: Arc::downgrade(&self.shared) }
+    }
+
+    /// Copies `pixels` into a buffer of `descriptor`'s layout. For sources
+    /// that only hand out borrowed frames.
+    #[must_use]
+    pub fn acquire_copy(&self, descriptor: BufferDescriptor, pixels: &[u8]) -> FrameBuffer {
+        let mut buffer = self.acquire(descriptor);
+        let len = buffer.len().min(pixels.len());
+        buffer[..len].copy_from_slice(&pixels[..len]);
+        buffer
     }
 
-    /// Clears all pooled buffers.
-    pub fn clear(&mut self) {
-        self.buffers.clear();
+    /// Number of free buffers of a layout.
+    #[must_use]
+    pub fn free_count(&self, descriptor: &BufferDescriptor) -> usize {
+        self.shared.lock().iter().find(|(d, _)| d == descriptor).map_or(0, |(_, b)| b.len())
     }
 
-    /// Returns the current number of pooled buffers.
+    /// Allocation counters.
     #[must_use]
-    pub fn pooled_count(&self) -> usize {
-        self.buffers.len()
+    pub fn stats(&self) -> FramePoolStats {
+        FramePoolStats {
+            allocated: self.shared.allocated.load(Ordering::Relaxed),
+            reused:    self.shared.reused.load(Ordering::Relaxed),
+            free:      self.shared.lock().iter().map(|(_, b)| b.len()).sum(),
+        }
+    }
+
+    /// Frees every pooled buffer, e.g. after the project resolution
+    /// changes.
+    pub fn clear(&self) {
+        self.shared.lock().clear();
+    }
+}
+
+impl Default for FrameBufferPool {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_FREE)
     }
 }
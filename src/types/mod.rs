@@ -31,6 +31,7 @@
 // Allow dead code during development - types are being prepared for full NLE
 #![allow(dead_code)]
 
+pub mod bwf;
 pub mod clip;
 pub mod codec;
 pub mod color;
@@ -40,9 +41,21 @@ pub mod pipeline;
 pub mod timeline;
 
 // Re-exports - Core types (primary API)
-pub use core::{AudioFormat, FrameRate, Resolution, TimePosition, Timestamp, VideoFormat};
+pub use core::{
+    AudioFormat, FieldOrder, FrameRate, PixelAspectRatio, ProjectionMode, Resolution, Rotation,
+    TimePosition, Timestamp, VideoFormat,
+};
 
+// Re-exports - Broadcast Wave metadata
+pub use bwf::{BwfMetadata, IxmlMetadata, IxmlTrack};
 // Re-exports - Clip types (media clips)
-pub use clip::{AudioClip, VideoClip};
+pub use clip::{AudioClip, ImageSequenceClip, VideoClip};
+// Re-exports - Pooled frame memory
+pub use frame::{
+    BufferDescriptor, BufferFormat, FrameBuffer, FrameBufferPool, FramePoolStats, STRIDE_ALIGNMENT,
+};
 // Re-exports - Timeline types (NLE operations)
-pub use timeline::{TimelinePosition, TimelineTrack, TrackType};
+pub use timeline::{
+    AdjustmentClip, ChannelMap, ClipFade, ClipGroup, ClipPitch, EditSuggestion, EditSuggestionKind,
+    FadeShape, TimelinePosition, TimelineTrack, TrackGroup, TrackType, TrackView, TrackViewState,
+};
@@ -371,6 +371,12 @@ impl RenderGraph {
         self.passes.len()
     }
 
+    /// Returns the passes in the order they were added.
+    #[must_use]
+    pub fn passes(&self) -> &[RenderPass] {
+        &self.passes
+    }
+
     /// Returns the pass execution order.
     #[must_use]
     pub fn execution_order(&self) -> &[usize] {
@@ -44,31 +44,46 @@ impl TrackType {
     pub const fn accepts_audio(&self) -> bool {
         matches!(self, Self::Audio)
     }
+
+    /// Returns whether this track contributes to the picture.
+    #[must_use]
+    pub const fn is_visual(&self) -> bool {
+        matches!(self, Self::Video | Self::Effect | Self::Subtitle)
+    }
 }
 
 /// Timeline track representing a single horizontal lane.
 #[derive(Debug, Clone)]
 pub struct TimelineTrack {
     /// Unique track identifier.
-    pub id:         u64,
+    pub id:          u64,
     /// Track name.
-    pub name:       String,
+    pub name:        String,
     /// Track type.
-    pub track_type: TrackType,
+    pub track_type:  TrackType,
     /// Track index (vertical position).
-    pub index:      usize,
+    pub index:       usize,
     /// Whether track is enabled.
-    pub enabled:    bool,
+    pub enabled:     bool,
     /// Whether track is locked (prevents editing).
-    pub locked:     bool,
+    pub locked:      bool,
     /// Whether track is muted (for audio).
-    pub muted:      bool,
+    pub muted:       bool,
     /// Whether track is soloed (only this plays).
-    pub solo:       bool,
+    pub solo:        bool,
     /// Track height in pixels (for UI).
-    pub height:     u32,
+    pub height:      u32,
+    /// Label color (for UI).
+    pub color:       Option<[f32; 4]>,
+    /// Track effect stack (effect IDs in application order).
+    pub effect_ids:  Vec<u64>,
     /// Track clips.
-    pub clips:      Vec<TimelineClip>,
+    pub clips:       Vec<TimelineClip>,
+    /// Adjustment clips (effect tracks only).
+    pub adjustments: Vec<AdjustmentClip>,
+    /// 2.5D depth in pixels for perspective compositing; positive values
+    /// push the track away from the camera.
+    pub z_position:  f32,
 }
 
 impl TimelineTrack {
@@ -85,7 +100,11 @@ impl TimelineTrack {
             muted: false,
             solo: false,
             height: 64,
+            color: None,
+            effect_ids: Vec::new(),
             clips: Vec::new(),
+            adjustments: Vec::new(),
+            z_position: 0.0,
         }
     }
 
@@ -107,7 +126,15 @@ impl TimelineTrack {
     /// Returns the total duration of all clips.
     #[must_use]
     pub fn duration(&self) -> TimePosition {
-        self.clips.last().map(|c| c.end()).unwrap_or_default()
+        let clips = self.clips.last().map(|c| c.end()).unwrap_or_default();
+        let adjustments = self.adjustments.last().map(|a| a.end()).unwrap_or_default();
+        if adjustments.ms > clips.ms { adjustments } else { clips }
+    }
+
+    /// Returns the adjustment clip covering a position, if any.
+    #[must_use]
+    pub fn adjustment_at(&self, position: TimePosition) -> Option<&AdjustmentClip> {
+        self.adjustments.iter().find(|a| a.enabled && a.contains(position))
     }
 
     /// Checks if a time range is available (no overlapping clips).
@@ -124,23 +151,174 @@ impl TimelineTrack {
 #[derive(Debug, Clone)]
 pub struct TimelineClip {
     /// Unique clip identifier.
-    pub id:        u64,
+    pub id:             u64,
     /// Start position on timeline.
-    pub start:     TimePosition,
+    pub start:          TimePosition,
     /// Clip duration.
-    pub duration:  TimePosition,
+    pub duration:       TimePosition,
     /// Source media ID.
-    pub source_id: u64,
+    pub source_id:      u64,
     /// In point (trim start).
-    pub in_point:  TimePosition,
+    pub in_point:       TimePosition,
     /// Out point (trim end).
-    pub out_point: TimePosition,
+    pub out_point:      TimePosition,
     /// Playback speed multiplier.
-    pub speed:     f32,
+    pub speed:          f32,
+    /// Audio pitch shift, independent of speed.
+    pub pitch:          ClipPitch,
+    /// Source audio channel routing.
+    pub channel_map:    ChannelMap,
+    /// Audio fade from silence at the clip start.
+    pub audio_fade_in:  ClipFade,
+    /// Audio fade to silence at the clip end.
+    pub audio_fade_out: ClipFade,
+    /// Effect stack (effect IDs in application order).
+    pub effect_ids:     Vec<u64>,
     /// Whether clip is enabled.
-    pub enabled:   bool,
+    pub enabled:        bool,
     /// Clip name.
-    pub name:      String,
+    pub name:           String,
+}
+
+/// Audio pitch shift for a clip.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ClipPitch {
+    /// Shift in semitones.
+    pub semitones:         f32,
+    /// Fine shift in cents.
+    pub cents:             f32,
+    /// Keep the spectral envelope so voices don't sound "chipmunked".
+    pub preserve_formants: bool,
+}
+
+impl ClipPitch {
+    /// Largest supported shift in either direction, in semitones.
+    pub const MAX_SEMITONES: f32 = 24.0;
+
+    /// Creates a pitch shift without formant preservation.
+    #[must_use]
+    pub fn new(semitones: f32, cents: f32) -> Self {
+        Self { semitones, cents, preserve_formants: false }
+    }
+
+    /// Enables or disables formant preservation.
+    #[must_use]
+    pub fn with_formants_preserved(mut self, preserve: bool) -> Self {
+        self.preserve_formants = preserve;
+        self
+    }
+
+    /// Returns the total shift in semitones, clamped to the supported range.
+    #[must_use]
+    pub fn total_semitones(&self) -> f32 {
+        (self.semitones + self.cents / 100.0).clamp(-Self::MAX_SEMITONES, Self::MAX_SEMITONES)
+    }
+
+    /// Returns the frequency ratio of the shift.
+    #[must_use]
+    pub fn ratio(&self) -> f32 {
+        2f32.powf(self.total_semitones() / 12.0)
+    }
+
+    /// Returns whether the shift leaves audio unchanged.
+    #[must_use]
+    pub fn is_identity(&self) -> bool {
+        self.total_semitones().abs() < 1e-4
+    }
+}
+
+/// Gain curve of a clip edge fade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FadeShape {
+    /// Gain rises linearly.
+    #[default]
+    Linear,
+    /// Quarter-sine gain, keeping the summed power of two overlapping
+    /// fades constant.
+    EqualPower,
+    /// Slow start and finish.
+    SCurve,
+}
+
+/// Audio fade on one edge of a clip.
+///
+/// Independent of video transitions: the fade only shapes the clip's own
+/// audio and needs no neighbouring clip.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ClipFade {
+    /// Fade length; zero for no fade.
+    pub duration: TimePosition,
+    /// Gain curve.
+    pub shape:    FadeShape,
+}
+
+impl ClipFade {
+    /// Creates a fade.
+    #[must_use]
+    pub fn new(duration: TimePosition, shape: FadeShape) -> Self {
+        Self { duration, shape }
+    }
+
+    /// Returns whether the fade has no length.
+    #[must_use]
+    pub fn is_none(&self) -> bool {
+        self.duration.ms == 0
+    }
+}
+
+/// Source channel routing for a clip's audio.
+///
+/// Output channel `i` plays source channel `sources[i]`, or silence for
+/// `None`. An empty map passes the source channels through unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ChannelMap {
+    /// Source channel (0-based) feeding each output channel.
+    pub sources: Vec<Option<u8>>,
+}
+
+impl ChannelMap {
+    /// Creates a map from the source channel of each output channel.
+    #[must_use]
+    pub fn new(sources: Vec<Option<u8>>) -> Self {
+        Self { sources }
+    }
+
+    /// One source channel on both sides of a stereo output, e.g. a single
+    /// mic broken out of a two-mic camera file.
+    #[must_use]
+    pub fn mono(channel: u8) -> Self {
+        Self { sources: vec![Some(channel); 2] }
+    }
+
+    /// Returns whether the source channels pass through unchanged.
+    #[must_use]
+    pub fn is_passthrough(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Number of output channels for a source with `source_channels`.
+    #[must_use]
+    pub fn output_channels(&self, source_channels: usize) -> usize {
+        if self.is_passthrough() { source_channels } else { self.sources.len() }
+    }
+
+    /// Routes interleaved source samples. Output channels mapped to a
+    /// channel the source doesn't have are silent.
+    #[must_use]
+    pub fn apply(&self, samples: &[f32], source_channels: usize) -> Vec<f32> {
+        let source_channels = source_channels.max(1);
+        if self.is_passthrough() {
+            return samples.to_vec();
+        }
+        samples
+            .chunks_exact(source_channels)
+            .flat_map(|frame| {
+                self.sources.iter().map(|source| {
+                    source.and_then(|c| frame.get(usize::from(c)).copied()).unwrap_or(0.0)
+                })
+            })
+            .collect()
+    }
 }
 
 impl TimelineClip {
@@ -155,6 +333,11 @@ impl TimelineClip {
             in_point: TimePosition::default(),
             out_point: duration,
             speed: 1.0,
+            pitch: ClipPitch::default(),
+            channel_map: ChannelMap::default(),
+            audio_fade_in: ClipFade::default(),
+            audio_fade_out: ClipFade::default(),
+            effect_ids: Vec::new(),
             enabled: true,
             name: String::new(),
         }
@@ -175,6 +358,8 @@ impl TimelineClip {
     }
 
     /// Splits the clip at the given position.
+    ///
+    /// The first part keeps the audio fade-in and the second the fade-out.
     #[must_use]
     pub fn split_at(&self, position: TimePosition, new_id: u64) -> Option<(Self, Self)> {
         if position.ms <= self.start.ms || position.ms >= self.end().ms {
@@ -185,27 +370,45 @@ impl TimelineClip {
         let source_split = self.in_point.ms + (split_offset as f64 * self.speed as f64) as u64;
 
         let first = Self {
-            id:        self.id,
-            start:     self.start,
-            duration:  TimePosition::from_ms(split_offset),
-            source_id: self.source_id,
-            in_point:  self.in_point,
-            out_point: TimePosition::from_ms(source_split),
-            speed:     self.speed,
-            enabled:   self.enabled,
-            name:      self.name.clone(),
+            id:             self.id,
+            start:          self.start,
+            duration:       TimePosition::from_ms(split_offset),
+            source_id:      self.source_id,
+            in_point:       self.in_point,
+            out_point:      TimePosition::from_ms(source_split),
+            speed:          self.speed,
+            pitch:          self.pitch,
+            channel_map:    self.channel_map.clone(),
+            audio_fade_in:  ClipFade {
+                duration: TimePosition::from_ms(self.audio_fade_in.duration.ms.min(split_offset)),
+                ..self.audio_fade_in
+            },
+            audio_fade_out: ClipFade::default(),
+            effect_ids:     self.effect_ids.clone(),
+            enabled:        self.enabled,
+            name:           self.name.clone(),
         };
 
         let second = Self {
-            id:        new_id,
-            start:     position,
-            duration:  TimePosition::from_ms(self.duration.ms - split_offset),
-            source_id: self.source_id,
-            in_point:  TimePosition::from_ms(source_split),
-            out_point: self.out_point,
-            speed:     self.speed,
-            enabled:   self.enabled,
-            name:      self.name.clone(),
+            id:             new_id,
+            start:          position,
+            duration:       TimePosition::from_ms(self.duration.ms - split_offset),
+            source_id:      self.source_id,
+            in_point:       TimePosition::from_ms(source_split),
+            out_point:      self.out_point,
+            speed:          self.speed,
+            pitch:          self.pitch,
+            channel_map:    self.channel_map.clone(),
+            audio_fade_in:  ClipFade::default(),
+            audio_fade_out: ClipFade {
+                duration: TimePosition::from_ms(
+                    self.audio_fade_out.duration.ms.min(self.duration.ms - split_offset),
+                ),
+                ..self.audio_fade_out
+            },
+            effect_ids:     self.effect_ids.clone(),
+            enabled:        self.enabled,
+            name:           self.name.clone(),
         };
 
         Some((first, second))
@@ -218,5 +421,481 @@ impl TimelineClip {
     }
 }
 
+/// Adjustment layer clip.
+///
+/// Holds an effect/grade stack but no media. During compositing the stack is
+/// applied to everything beneath it (tracks with a lower index) within its
+/// time range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdjustmentClip {
+    /// Unique clip identifier.
+    pub id:         u64,
+    /// Start position on timeline.
+    pub start:      TimePosition,
+    /// Clip duration.
+    pub duration:   TimePosition,
+    /// Effect stack (effect IDs in application order).
+    pub effect_ids: Vec<u64>,
+    /// Mix of the adjusted result over the unadjusted one (0.0 to 1.0).
+    pub opacity:    f32,
+    /// Whether clip is enabled.
+    pub enabled:    bool,
+    /// Clip name.
+    pub name:       String,
+}
+
+impl AdjustmentClip {
+    /// Serialized format version.
+    const VERSION: u8 = 1;
+
+    /// Creates a new adjustment clip with an empty stack.
+    #[must_use]
+    pub fn new(id: u64, start: TimePosition, duration: TimePosition) -> Self {
+        Self {
+            id,
+            start,
+            duration,
+            effect_ids: Vec::new(),
+            opacity: 1.0,
+            enabled: true,
+            name: String::new(),
+        }
+    }
+
+    /// Returns the end position of the clip.
+    #[must_use]
+    pub fn end(&self) -> TimePosition {
+        TimePosition::from_ms(self.start.ms + self.duration.ms)
+    }
+
+    /// Checks if the clip contains the given position.
+    #[must_use]
+    pub fn contains(&self, position: TimePosition) -> bool {
+        position.ms >= self.start.ms && position.ms < self.end().ms
+    }
+
+    /// Checks if the clip overlaps a time range.
+    #[must_use]
+    pub fn overlaps(&self, start: TimePosition, end: TimePosition) -> bool {
+        start.ms < self.end().ms && end.ms > self.start.ms
+    }
+
+    /// Converts to bytes for project storage.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let name = self.name.as_bytes();
+        let mut bytes = Vec::with_capacity(34 + self.effect_ids.len() * 8 + name.len());
+        bytes.push(Self::VERSION);
+        bytes.extend_from_slice(&self.id.to_le_bytes());
+        bytes.extend_from_slice(&self.start.ms.to_le_bytes());
+        bytes.extend_from_slice(&self.duration.ms.to_le_bytes());
+        bytes.extend_from_slice(&self.opacity.to_le_bytes());
+        bytes.push(u8::from(self.enabled));
+        bytes.extend_from_slice(&(self.effect_ids.len() as u32).to_le_bytes());
+        for id in &self.effect_ids {
+            bytes.extend_from_slice(&id.to_le_bytes());
+        }
+        bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(name);
+        bytes
+    }
+
+    /// Parses an adjustment clip from bytes.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = ByteReader { bytes, offset: 0 };
+        if reader.u8()? != Self::VERSION {
+            return None;
+        }
+
+        let id = reader.u64()?;
+        let start = TimePosition::from_ms(reader.u64()?);
+        let duration = TimePosition::from_ms(reader.u64()?);
+        let opacity = f32::from_bits(reader.u32()?);
+        let enabled = reader.u8()? != 0;
+        let effect_count = reader.u32()? as usize;
+        let effect_ids = (0..effect_count).map(|_| reader.u64()).collect::<Option<Vec<_>>>()?;
+        let name_len = reader.u32()? as usize;
+        let name = String::from_utf8(reader.take(name_len)?.to_vec()).ok()?;
+
+        Some(Self { id, start, duration, effect_ids, opacity, enabled, name })
+    }
+}
+
+/// Set of linked clips that are selected and edited together, e.g. a video
+/// clip and its audio.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipGroup {
+    /// Unique group identifier.
+    pub id:       u64,
+    /// Linked clip IDs.
+    pub clip_ids: Vec<u64>,
+    /// Group name.
+    pub name:     String,
+}
+
+impl ClipGroup {
+    /// Serialized format version.
+    const VERSION: u8 = 1;
+
+    /// Creates a group from clip IDs, dropping duplicates.
+    #[must_use]
+    pub fn new(id: u64, clip_ids: &[u64]) -> Self {
+        let mut ids = Vec::with_capacity(clip_ids.len());
+        for &clip_id in clip_ids {
+            if !ids.contains(&clip_id) {
+                ids.push(clip_id);
+            }
+        }
+        Self { id, clip_ids: ids, name: String::new() }
+    }
+
+    /// Checks if the group links a clip.
+    #[must_use]
+    pub fn contains(&self, clip_id: u64) -> bool {
+        self.clip_ids.contains(&clip_id)
+    }
+
+    /// Converts to bytes for project storage.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let name = self.name.as_bytes();
+        let mut bytes = Vec::with_capacity(17 + self.clip_ids.len() * 8 + name.len());
+        bytes.push(Self::VERSION);
+        bytes.extend_from_slice(&self.id.to_le_bytes());
+        bytes.extend_from_slice(&(self.clip_ids.len() as u32).to_le_bytes());
+        for id in &self.clip_ids {
+            bytes.extend_from_slice(&id.to_le_bytes());
+        }
+        bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(name);
+        bytes
+    }
+
+    /// Parses a clip group from bytes.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = ByteReader { bytes, offset: 0 };
+        if reader.u8()? != Self::VERSION {
+            return None;
+        }
+
+        let id = reader.u64()?;
+        let clip_count = reader.u32()? as usize;
+        let clip_ids = (0..clip_count).map(|_| reader.u64()).collect::<Option<Vec<_>>>()?;
+        let name_len = reader.u32()? as usize;
+        let name = String::from_utf8(reader.take(name_len)?.to_vec()).ok()?;
+
+        Some(Self { id, clip_ids, name })
+    }
+}
+
+/// Named set of tracks (e.g. all dialogue tracks) that collapse together
+/// and share mute, solo and lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackGroup {
+    /// Unique group identifier.
+    pub id:        u64,
+    /// Group name.
+    pub name:      String,
+    /// Member track IDs.
+    pub track_ids: Vec<u64>,
+    /// Whether the group is collapsed in the track list.
+    pub collapsed: bool,
+    /// Mutes every member track.
+    pub muted:     bool,
+    /// Solos every member track.
+    pub solo:      bool,
+    /// Locks every member track against edits.
+    pub locked:    bool,
+}
+
+impl TrackGroup {
+    /// Serialized format version.
+    const VERSION: u8 = 1;
+
+    /// Creates a group from track IDs, dropping duplicates.
+    #[must_use]
+    pub fn new(id: u64, name: impl Into<String>, track_ids: &[u64]) -> Self {
+        let mut ids = Vec::with_capacity(track_ids.len());
+        for &track_id in track_ids {
+            if !ids.contains(&track_id) {
+                ids.push(track_id);
+            }
+        }
+        Self {
+            id,
+            name: name.into(),
+            track_ids: ids,
+            collapsed: false,
+            muted: false,
+            solo: false,
+            locked: false,
+        }
+    }
+
+    /// Checks if the group contains a track.
+    #[must_use]
+    pub fn contains(&self, track_id: u64) -> bool {
+        self.track_ids.contains(&track_id)
+    }
+
+    /// Converts to bytes for project storage.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let name = self.name.as_bytes();
+        let mut bytes = Vec::with_capacity(18 + self.track_ids.len() * 8 + name.len());
+        bytes.push(Self::VERSION);
+        bytes.extend_from_slice(&self.id.to_le_bytes());
+        bytes.push(
+            u8::from(self.collapsed)
+                | u8::from(self.muted) << 1
+                | u8::from(self.solo) << 2
+                | u8::from(self.locked) << 3,
+        );
+        bytes.extend_from_slice(&(self.track_ids.len() as u32).to_le_bytes());
+        for id in &self.track_ids {
+            bytes.extend_from_slice(&id.to_le_bytes());
+        }
+        bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(name);
+        bytes
+    }
+
+    /// Parses a track group from bytes.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = ByteReader { bytes, offset: 0 };
+        if reader.u8()? != Self::VERSION {
+            return None;
+        }
+
+        let id = reader.u64()?;
+        let flags = reader.u8()?;
+        let track_count = reader.u32()? as usize;
+        let track_ids = (0..track_count).map(|_| reader.u64()).collect::<Option<Vec<_>>>()?;
+        let name_len = reader.u32()? as usize;
+        let name = String::from_utf8(reader.take(name_len)?.to_vec()).ok()?;
+
+        Some(Self {
+            id,
+            name,
+            track_ids,
+            collapsed: flags & 1 != 0,
+            muted: flags & 1 << 1 != 0,
+            solo: flags & 1 << 2 != 0,
+            locked: flags & 1 << 3 != 0,
+        })
+    }
+}
+
+/// How the host UI draws one track in the track list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackView {
+    /// Track height in pixels.
+    pub height:    u32,
+    /// Label color.
+    pub color:     Option<[f32; 4]>,
+    /// Whether the track is collapsed to its header.
+    pub collapsed: bool,
+}
+
+impl Default for TrackView {
+    fn default() -> Self {
+        Self { height: 64, color: None, collapsed: false }
+    }
+}
+
+/// Track list display state: per-track looks and the order the user
+/// arranged the list in.
+///
+/// Saved with the project but kept apart from the edit, so changing it
+/// never reaches undo history or snapshots. The custom order only affects
+/// how tracks are listed, not how they composite.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackViewState {
+    /// Stored track views by track ID.
+    views: Vec<(u64, TrackView)>,
+    /// Track IDs bottom to top as the user arranged the list.
+    order: Vec<u64>,
+}
+
+impl TrackViewState {
+    /// Serialized format version.
+    const VERSION: u8 = 1;
+
+    /// Creates an empty state.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks if nothing was customized.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.views.is_empty() && self.order.is_empty()
+    }
+
+    /// Gets the stored view of a track.
+    #[must_use]
+    pub fn view(&self, track_id: u64) -> Option<&TrackView> {
+        self.views.iter().find(|(id, _)| *id == track_id).map(|(_, view)| view)
+    }
+
+    /// Stores the view of a track.
+    pub fn set_view(&mut self, track_id: u64, view: TrackView) {
+        match self.views.iter_mut().find(|(id, _)| *id == track_id) {
+            Some((_, existing)) => *existing = view,
+            None => self.views.push((track_id, view)),
+        }
+    }
+
+    /// Forgets the stored view of a track.
+    pub fn clear_view(&mut self, track_id: u64) -> bool {
+        let before = self.views.len();
+        self.views.retain(|(id, _)| *id != track_id);
+        self.views.len() != before
+    }
+
+    /// Returns the custom order, bottom to top.
+    #[must_use]
+    pub fn order(&self) -> &[u64] {
+        &self.order
+    }
+
+    /// Replaces the custom order, dropping duplicates; an empty order
+    /// lists tracks in timeline order.
+    pub fn set_order(&mut self, order: &[u64]) {
+        self.order.clear();
+        for &track_id in order {
+            if !self.order.contains(&track_id) {
+                self.order.push(track_id);
+            }
+        }
+    }
+
+    /// Lists `track_ids`, bottom to top, in the custom order. Tracks
+    /// missing from it keep their relative order above the arranged ones,
+    /// and unknown IDs in it are skipped.
+    #[must_use]
+    pub fn ordered(&self, track_ids: &[u64]) -> Vec<u64> {
+        let mut ordered: Vec<u64> =
+            self.order.iter().copied().filter(|id| track_ids.contains(id)).collect();
+        ordered.extend(track_ids.iter().filter(|id| !self.order.contains(id)));
+        ordered
+    }
+
+    /// Drops state of tracks that no longer exist.
+    pub fn retain_tracks(&mut self, track_ids: &[u64]) {
+        self.views.retain(|(id, _)| track_ids.contains(id));
+        self.order.retain(|id| track_ids.contains(id));
+    }
+
+    /// Converts to bytes for project storage.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(9 + self.views.len() * 29 + self.order.len() * 8);
+        bytes.push(Self::VERSION);
+        bytes.extend_from_slice(&(self.views.len() as u32).to_le_bytes());
+        for (id, view) in &self.views {
+            bytes.extend_from_slice(&id.to_le_bytes());
+            bytes.extend_from_slice(&view.height.to_le_bytes());
+            bytes.push(u8::from(view.collapsed) | u8::from(view.color.is_some()) << 1);
+            for channel in view.color.unwrap_or_default() {
+                bytes.extend_from_slice(&channel.to_bits().to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(&(self.order.len() as u32).to_le_bytes());
+        for id in &self.order {
+            bytes.extend_from_slice(&id.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Parses a track view state from bytes.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut reader = ByteReader { bytes, offset: 0 };
+        if reader.u8()? != Self::VERSION {
+            return None;
+        }
+
+        let view_count = reader.u32()? as usize;
+        let mut views = Vec::with_capacity(view_count.min(bytes.len() / 29));
+        for _ in 0..view_count {
+            let id = reader.u64()?;
+            let height = reader.u32()?;
+            let flags = reader.u8()?;
+            let mut color = [0.0; 4];
+            for channel in &mut color {
+                *channel = f32::from_bits(reader.u32()?);
+            }
+            let color = (flags & 1 << 1 != 0).then_some(color);
+            views.push((id, TrackView { height, color, collapsed: flags & 1 != 0 }));
+        }
+        let order_count = reader.u32()? as usize;
+        let order = (0..order_count).map(|_| reader.u64()).collect::<Option<Vec<_>>>()?;
+
+        Some(Self { views, order })
+    }
+}
+
+/// Why an edit was suggested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EditSuggestionKind {
+    /// Silence between speech.
+    RemoveSilence,
+}
+
+/// Suggested cut of a timeline range, produced by analysis and applied by
+/// the user (see `TimelineManager::apply_suggestions`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditSuggestion {
+    /// Track the range was found on.
+    pub track_id: u64,
+    /// Clip the range was found in.
+    pub clip_id:  u64,
+    /// Range start on the timeline.
+    pub start:    TimePosition,
+    /// Range end on the timeline (exclusive).
+    pub end:      TimePosition,
+    /// Reason for the suggestion.
+    pub kind:     EditSuggestionKind,
+}
+
+impl EditSuggestion {
+    /// Returns the length of the suggested cut.
+    #[must_use]
+    pub fn duration(&self) -> TimePosition {
+        TimePosition::from_ms(self.end.ms.saturating_sub(self.start.ms))
+    }
+}
+
+/// Bounds-checked little-endian reader.
+pub(crate) struct ByteReader<'a> {
+    pub(crate) bytes:  &'a [u8],
+    pub(crate) offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let slice = self.bytes.get(self.offset..self.offset.checked_add(len)?)?;
+        self.offset += len;
+        Some(slice)
+    }
+
+    pub(crate) fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Option<u32> {
+        self.take(4)?.try_into().ok().map(u32::from_le_bytes)
+    }
+
+    pub(crate) fn u64(&mut self) -> Option<u64> {
+        self.take(8)?.try_into().ok().map(u64::from_le_bytes)
+    }
+}
+
 /// Timeline position alias for compatibility.
 pub type TimelinePosition = TimePosition;
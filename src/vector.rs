@@ -0,0 +1,659 @@
+//! Resolution-independent vector layers.
+//!
+//! A [`VectorDocument`] holds filled and stroked paths in document units.
+//! Paths tessellate into triangle meshes for the GPU pipeline, and can be
+//! rasterized on the CPU at any size as a preview/export fallback.
+
+/// 2D affine transform `[a c e; b d f]`, in SVG matrix order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2D {
+    /// X scale/rotation.
+    pub a: f32,
+    /// Y shear/rotation.
+    pub b: f32,
+    /// X shear/rotation.
+    pub c: f32,
+    /// Y scale/rotation.
+    pub d: f32,
+    /// X translation.
+    pub e: f32,
+    /// Y translation.
+    pub f: f32,
+}
+
+impl Transform2D {
+    /// Identity transform.
+    pub const IDENTITY: Self = Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 0.0, f: 0.0 };
+
+    /// Translation.
+    #[must_use]
+    pub const fn translate(x: f32, y: f32) -> Self {
+        Self { e: x, f: y, ..Self::IDENTITY }
+    }
+
+    /// Scale about the origin.
+    #[must_use]
+    pub const fn scale(x: f32, y: f32) -> Self {
+        Self { a: x, d: y, ..Self::IDENTITY }
+    }
+
+    /// Rotation about the origin, in degrees.
+    #[must_use]
+    pub fn rotate(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self { a: cos, b: sin, c: -sin, d: cos, ..Self::IDENTITY }
+    }
+
+    /// Horizontal skew, in degrees.
+    #[must_use]
+    pub fn skew_x(degrees: f32) -> Self {
+        Self { c: degrees.to_radians().tan(), ..Self::IDENTITY }
+    }
+
+    /// Vertical skew, in degrees.
+    #[must_use]
+    pub fn skew_y(degrees: f32) -> Self {
+        Self { b: degrees.to_radians().tan(), ..Self::IDENTITY }
+    }
+
+    /// Transform applying `inner` first, then `self`.
+    #[must_use]
+    pub fn then(&self, inner: &Self) -> Self {
+        Self {
+            a: self.a * inner.a + self.c * inner.b,
+            b: self.b * inner.a + self.d * inner.b,
+            c: self.a * inner.c + self.c * inner.d,
+            d: self.b * inner.c + self.d * inner.d,
+            e: self.a * inner.e + self.c * inner.f + self.e,
+            f: self.b * inner.e + self.d * inner.f + self.f,
+        }
+    }
+
+    /// Transform a point.
+    #[must_use]
+    pub fn apply(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        [self.a * x + self.c * y + self.e, self.b * x + self.d * y + self.f]
+    }
+
+    /// Average scale factor, used for stroke widths.
+    #[must_use]
+    pub fn scale_factor(&self) -> f32 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Path drawing command in absolute coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    /// Start a new subpath.
+    MoveTo([f32; 2]),
+    /// Straight line.
+    LineTo([f32; 2]),
+    /// Cubic Bézier with two control points.
+    CubicTo([f32; 2], [f32; 2], [f32; 2]),
+    /// Close the current subpath.
+    Close,
+}
+
+/// Flattened subpath.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline {
+    /// Points along the subpath.
+    pub points: Vec<[f32; 2]>,
+    /// Subpath was explicitly closed.
+    pub closed: bool,
+}
+
+/// Sequence of subpaths.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VectorPath {
+    /// Drawing commands.
+    pub commands: Vec<PathCommand>,
+}
+
+impl VectorPath {
+    /// Creates an empty path.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a new subpath.
+    pub fn move_to(&mut self, p: [f32; 2]) {
+        self.commands.push(PathCommand::MoveTo(p));
+    }
+
+    /// Adds a line.
+    pub fn line_to(&mut self, p: [f32; 2]) {
+        self.commands.push(PathCommand::LineTo(p));
+    }
+
+    /// Adds a cubic Bézier.
+    pub fn cubic_to(&mut self, c1: [f32; 2], c2: [f32; 2], p: [f32; 2]) {
+        self.commands.push(PathCommand::CubicTo(c1, c2, p));
+    }
+
+    /// Closes the current subpath.
+    pub fn close(&mut self) {
+        self.commands.push(PathCommand::Close);
+    }
+
+    /// Whether the path draws nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        !self.commands.iter().any(|c| !matches!(c, PathCommand::MoveTo(_)))
+    }
+
+    /// Copy of the path with every point transformed.
+    #[must_use]
+    pub fn transformed(&self, transform: &Transform2D) -> Self {
+        let t = |p| transform.apply(p);
+        let commands = self
+            .commands
+            .iter()
+            .map(|command| match *command {
+                PathCommand::MoveTo(p) => PathCommand::MoveTo(t(p)),
+                PathCommand::LineTo(p) => PathCommand::LineTo(t(p)),
+                PathCommand::CubicTo(c1, c2, p) => PathCommand::CubicTo(t(c1), t(c2), t(p)),
+                PathCommand::Close => PathCommand::Close,
+            })
+            .collect();
+        Self { commands }
+    }
+
+    /// Bounding box of the on-curve and control points as `[min, max]`.
+    #[must_use]
+    pub fn bounds(&self) -> Option<[[f32; 2]; 2]> {
+        let points = self.commands.iter().flat_map(|command| match *command {
+            PathCommand::MoveTo(p) | PathCommand::LineTo(p) => vec![p],
+            PathCommand::CubicTo(c1, c2, p) => vec![c1, c2, p],
+            PathCommand::Close => Vec::new(),
+        });
+        points.fold(None, |bounds, [x, y]| {
+            let [[x0, y0], [x1, y1]] = bounds.unwrap_or([[x, y], [x, y]]);
+            Some([[x0.min(x), y0.min(y)], [x1.max(x), y1.max(y)]])
+        })
+    }
+
+    /// Flattens curves into polylines within `tolerance` units.
+    #[must_use]
+    pub fn flatten(&self, tolerance: f32) -> Vec<Polyline> {
+        let tolerance = tolerance.max(1e-3);
+        let mut polylines = Vec::new();
+        let mut current = Polyline { points: Vec::new(), closed: false };
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo(p) => {
+                    if current.points.len() > 1 {
+                        polylines.push(current);
+                    }
+                    current = Polyline { points: vec![p], closed: false };
+                },
+                PathCommand::LineTo(p) => current.points.push(p),
+                PathCommand::CubicTo(c1, c2, p) => {
+                    let p0 = current.points.last().copied().unwrap_or(c1);
+                    flatten_cubic(p0, c1, c2, p, tolerance, &mut current.points);
+                },
+                PathCommand::Close => {
+                    let start = current.points.first().copied();
+                    current.closed = true;
+                    if current.points.len() > 1 {
+                        polylines.push(current);
+                    }
+                    current = Polyline { points: start.into_iter().collect(), closed: false };
+                },
+            }
+        }
+        if current.points.len() > 1 {
+            polylines.push(current);
+        }
+        polylines
+    }
+}
+
+fn flatten_cubic(
+    p0: [f32; 2], c1: [f32; 2], c2: [f32; 2], p3: [f32; 2], tolerance: f32, out: &mut Vec<[f32; 2]>,
+) {
+    // Segment count from the second differences of the control polygon
+    let dd = |a: [f32; 2], b: [f32; 2], c: [f32; 2]| {
+        (a[0] - 2.0 * b[0] + c[0]).hypot(a[1] - 2.0 * b[1] + c[1])
+    };
+    let l = dd(p0, c1, c2).max(dd(c1, c2, p3));
+    let n = ((0.75 * l / tolerance).sqrt().ceil() as usize).clamp(1, 256);
+    for i in 1..=n {
+        let t = i as f32 / n as f32;
+        let mt = 1.0 - t;
+        let (w0, w1, w2, w3) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+        out.push([
+            w0 * p0[0] + w1 * c1[0] + w2 * c2[0] + w3 * p3[0],
+            w0 * p0[1] + w1 * c1[1] + w2 * c2[1] + w3 * p3[1],
+        ]);
+    }
+}
+
+/// Rule deciding which regions of a self-overlapping path are filled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// Filled where the winding number is not zero.
+    #[default]
+    NonZero,
+    /// Filled where the winding number is odd.
+    EvenOdd,
+}
+
+impl FillRule {
+    fn is_inside(self, winding: i32) -> bool {
+        match self {
+            Self::NonZero => winding != 0,
+            Self::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// Stroke paint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stroke {
+    /// RGBA8 color (alpha includes opacity).
+    pub color: [u8; 4],
+    /// Line width in document units.
+    pub width: f32,
+}
+
+/// Filled and/or stroked path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorShape {
+    /// Geometry in document units.
+    pub path:      VectorPath,
+    /// RGBA8 fill color (alpha includes opacity).
+    pub fill:      Option<[u8; 4]>,
+    /// Fill rule.
+    pub fill_rule: FillRule,
+    /// Stroke.
+    pub stroke:    Option<Stroke>,
+}
+
+/// Tessellated vertex.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VectorVertex {
+    /// Position in document units.
+    pub position: [f32; 2],
+    /// RGBA8 color.
+    pub color:    [u8; 4],
+}
+
+/// Indexed triangle list.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VectorMesh {
+    /// Vertices.
+    pub vertices: Vec<VectorVertex>,
+    /// Triangle indices, three per triangle.
+    pub indices:  Vec<u32>,
+}
+
+impl VectorMesh {
+    /// Number of triangles.
+    #[must_use]
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn push_triangles(&mut self, triangles: &[[[f32; 2]; 3]], color: [u8; 4]) {
+        for triangle in triangles {
+            for position in triangle {
+                self.indices.push(self.vertices.len() as u32);
+                self.vertices.push(VectorVertex { position: *position, color });
+            }
+        }
+    }
+}
+
+/// Vector layer content.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VectorDocument {
+    /// Document width in units.
+    pub width:  f32,
+    /// Document height in units.
+    pub height: f32,
+    /// Shapes in paint order.
+    pub shapes: Vec<VectorShape>,
+}
+
+impl VectorDocument {
+    /// Tessellates every shape into one triangle list, in paint order.
+    ///
+    /// `tolerance` is the maximum curve flattening error in document units.
+    #[must_use]
+    pub fn tessellate(&self, tolerance: f32) -> VectorMesh {
+        let mut mesh = VectorMesh::default();
+        for shape in &self.shapes {
+            let polylines = shape.path.flatten(tolerance);
+            if let Some(color) = shape.fill {
+                mesh.push_triangles(&fill_triangles(&polylines, shape.fill_rule), color);
+            }
+            if let Some(stroke) = shape.stroke {
+                mesh.push_triangles(&stroke_triangles(&polylines, stroke.width), stroke.color);
+            }
+        }
+        mesh
+    }
+
+    /// Rasterizes the document scaled to `width` x `height` into RGBA8.
+    #[must_use]
+    pub fn rasterize(&self, width: u32, height: u32) -> Vec<u8> {
+        let (w, h) = (width as usize, height as usize);
+        let mut canvas = vec![[0.0f32; 4]; w * h];
+        let transform = Transform2D::scale(
+            width as f32 / self.width.max(f32::EPSILON),
+            height as f32 / self.height.max(f32::EPSILON),
+        );
+        for shape in &self.shapes {
+            let polylines = shape.path.transformed(&transform).flatten(0.25);
+            if let Some(color) = shape.fill {
+                let contours: Vec<&[[f32; 2]]> =
+                    polylines.iter().map(|p| p.points.as_slice()).collect();
+                let coverage = coverage(&contours, shape.fill_rule, w, h);
+                composite(&mut canvas, &coverage, color);
+            }
+            if let Some(stroke) = shape.stroke {
+                // Triangles oriented alike so overlaps union under non-zero
+                let triangles: Vec<[[f32; 2]; 3]> =
+                    stroke_triangles(&polylines, stroke.width * transform.scale_factor())
+                        .into_iter()
+                        .map(
+                            |[a, b, c]| {
+                                if signed_area(&[a, b, c]) < 0.0 { [a, c, b] } else { [a, b, c] }
+                            },
+                        )
+                        .collect();
+                let contours: Vec<&[[f32; 2]]> = triangles.iter().map(|t| t.as_slice()).collect();
+                let coverage = coverage(&contours, FillRule::NonZero, w, h);
+                composite(&mut canvas, &coverage, stroke.color);
+            }
+        }
+
+        let mut out = Vec::with_capacity(w * h * 4);
+        for [r, g, b, a] in canvas {
+            let unpremultiply = |c: f32| {
+                if a > 0.0 { (c / a * 255.0).round() as u8 } else { 0 }
+            };
+            out.extend_from_slice(&[
+                unpremultiply(r),
+                unpremultiply(g),
+                unpremultiply(b),
+                (a * 255.0).round() as u8,
+            ]);
+        }
+        out
+    }
+}
+
+fn signed_area(points: &[[f32; 2]]) -> f32 {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let ([x0, y0], [x1, y1]) = (points[i], points[(i + 1) % n]);
+            x0 * y1 - x1 * y0
+        })
+        .sum::<f32>()
+        / 2.0
+}
+
+/// Non-horizontal polygon edge with `y0 < y1`.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    x0:        f32,
+    y0:        f32,
+    x1:        f32,
+    y1:        f32,
+    direction: i32,
+}
+
+impl Edge {
+    fn x_at(&self, y: f32) -> f32 {
+        self.x0 + (self.x1 - self.x0) * (y - self.y0) / (self.y1 - self.y0)
+    }
+}
+
+/// Edges of every contour, each implicitly closed.
+fn edges<P: AsRef<[[f32; 2]]>>(contours: &[P]) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    for contour in contours {
+        let points = contour.as_ref();
+        for (i, &[ax, ay]) in points.iter().enumerate() {
+            let [bx, by] = points[(i + 1) % points.len()];
+            if ay < by {
+                edges.push(Edge {
+                    x0:        ax,
+                    y0:        ay,
+                    x1:        bx,
+                    y1:        by,
+                    direction: 1,
+                });
+            } else if by < ay {
+                edges.push(Edge {
+                    x0:        bx,
+                    y0:        by,
+                    x1:        ax,
+                    y1:        ay,
+                    direction: -1,
+                });
+            }
+        }
+    }
+    edges
+}
+
+/// Spans inside the fill at height `y`, from edges crossing it.
+fn spans(edges: &[Edge], y: f32, rule: FillRule, mut span: impl FnMut(&Edge, &Edge)) {
+    let mut crossing: Vec<&Edge> = edges.iter().filter(|e| e.y0 <= y && y < e.y1).collect();
+    crossing.sort_by(|a, b| a.x_at(y).total_cmp(&b.x_at(y)));
+    let mut winding = 0;
+    let mut start = None;
+    for edge in crossing {
+        let was_inside = rule.is_inside(winding);
+        winding += edge.direction;
+        match (was_inside, rule.is_inside(winding)) {
+            (false, true) => start = Some(edge),
+            (true, false) => {
+                if let Some(left) = start.take() {
+                    span(left, edge);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Triangulates a fill into trapezoids between consecutive vertex heights.
+fn fill_triangles(polylines: &[Polyline], rule: FillRule) -> Vec<[[f32; 2]; 3]> {
+    let contours: Vec<&[[f32; 2]]> = polylines.iter().map(|p| p.points.as_slice()).collect();
+    let edges = edges(&contours);
+    let mut ys: Vec<f32> = edges.iter().flat_map(|e| [e.y0, e.y1]).collect();
+    ys.sort_by(f32::total_cmp);
+    ys.dedup();
+
+    let mut triangles = Vec::new();
+    for band in ys.windows(2) {
+        let (top, bottom) = (band[0], band[1]);
+        spans(&edges, (top + bottom) / 2.0, rule, |left, right| {
+            let tl = [left.x_at(top), top];
+            let tr = [right.x_at(top), top];
+            let br = [right.x_at(bottom), bottom];
+            let bl = [left.x_at(bottom), bottom];
+            triangles.push([tl, tr, br]);
+            triangles.push([tl, br, bl]);
+        });
+    }
+    triangles
+}
+
+/// Butt-capped stroke quads with bevel joins.
+fn stroke_triangles(polylines: &[Polyline], width: f32) -> Vec<[[f32; 2]; 3]> {
+    let half = width / 2.0;
+    let mut triangles = Vec::new();
+    for polyline in polylines {
+        let mut points = polyline.points.clone();
+        points.dedup();
+        if polyline.closed && points.len() > 2 {
+            points.push(points[0]);
+        }
+        let offsets: Vec<[f32; 2]> = points
+            .windows(2)
+            .map(|s| {
+                let (dx, dy) = (s[1][0] - s[0][0], s[1][1] - s[0][1]);
+                let len = dx.hypot(dy).max(f32::EPSILON);
+                [-dy / len * half, dx / len * half]
+            })
+            .collect();
+        let add = |p: [f32; 2], n: [f32; 2], sign: f32| [p[0] + n[0] * sign, p[1] + n[1] * sign];
+
+        for (i, s) in points.windows(2).enumerate() {
+            let n = offsets[i];
+            let quad =
+                [add(s[0], n, 1.0), add(s[1], n, 1.0), add(s[1], n, -1.0), add(s[0], n, -1.0)];
+            triangles.push([quad[0], quad[1], quad[2]]);
+            triangles.push([quad[0], quad[2], quad[3]]);
+        }
+        let joins =
+            offsets.len().saturating_sub(1) + usize::from(polyline.closed && offsets.len() > 2);
+        for i in 0..joins {
+            let (prev, next) = (offsets[i], offsets[(i + 1) % offsets.len()]);
+            let p = points[i + 1];
+            for sign in [1.0, -1.0] {
+                triangles.push([p, add(p, prev, sign), add(p, next, sign)]);
+            }
+        }
+    }
+    triangles
+}
+
+/// Per-pixel coverage (0-1) of contours, 4 sub-rows per pixel row.
+fn coverage(contours: &[&[[f32; 2]]], rule: FillRule, width: usize, height: usize) -> Vec<f32> {
+    const SUB_ROWS: usize = 4;
+    let edges = edges(contours);
+    let mut coverage = vec![0.0f32; width * height];
+    let weight = 1.0 / SUB_ROWS as f32;
+    for row in 0..height {
+        let line = &mut coverage[row * width..(row + 1) * width];
+        for sub in 0..SUB_ROWS {
+            let y = row as f32 + (sub as f32 + 0.5) * weight;
+            spans(&edges, y, rule, |left, right| {
+                add_span(line, left.x_at(y), right.x_at(y), weight);
+            });
+        }
+    }
+    coverage
+}
+
+/// Adds horizontal coverage of `[x0, x1)` with partial end pixels.
+fn add_span(line: &mut [f32], x0: f32, x1: f32, weight: f32) {
+    let (x0, x1) = (x0.clamp(0.0, line.len() as f32), x1.clamp(0.0, line.len() as f32));
+    if x1 <= x0 {
+        return;
+    }
+    let (first, last) = (x0.floor() as usize, x1.floor() as usize);
+    if first == last {
+        line[first] += (x1 - x0) * weight;
+        return;
+    }
+    line[first] += (first as f32 + 1.0 - x0) * weight;
+    for value in &mut line[first + 1..last] {
+        *value += weight;
+    }
+    if let Some(value) = line.get_mut(last) {
+        *value += (x1 - last as f32) * weight;
+    }
+}
+
+/// Source-over composite of a solid color onto a premultiplied canvas.
+fn composite(canvas: &mut [[f32; 4]], coverage: &[f32], [r, g, b, a]: [u8; 4]) {
+    let color = [r, g, b].map(|c| f32::from(c) / 255.0);
+    for (pixel, &cover) in canvas.iter_mut().zip(coverage) {
+        let alpha = f32::from(a) / 255.0 * cover.min(1.0);
+        if alpha <= 0.0 {
+            continue;
+        }
+        for (channel, value) in pixel.iter_mut().zip(color) {
+            *channel = value * alpha + *channel * (1.0 - alpha);
+        }
+        pixel[3] = alpha + pixel[3] * (1.0 - alpha);
+    }
+}
+
+#[cfg(all(test, feature = "full-tests"))]
+mod tests {
+    use super::*;
+
+    fn square(size: f32) -> VectorPath {
+        let mut path = VectorPath::new();
+        path.move_to([0.0, 0.0]);
+        path.line_to([size, 0.0]);
+        path.line_to([size, size]);
+        path.line_to([0.0, size]);
+        path.close();
+        path
+    }
+
+    fn mesh_area(mesh: &VectorMesh) -> f32 {
+        mesh.indices
+            .chunks(3)
+            .map(|t| {
+                let points: Vec<[f32; 2]> =
+                    t.iter().map(|&i| mesh.vertices[i as usize].position).collect();
+                signed_area(&points).abs()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_tessellate_fill_rules() {
+        // Outer square with a same-direction inner square
+        let mut path = square(10.0);
+        path.commands.extend(square(4.0).transformed(&Transform2D::translate(3.0, 3.0)).commands);
+        let mut document = VectorDocument {
+            width:  10.0,
+            height: 10.0,
+            shapes: vec![VectorShape {
+                path,
+                fill: Some([255, 0, 0, 255]),
+                fill_rule: FillRule::NonZero,
+                stroke: None,
+            }],
+        };
+        assert!((mesh_area(&document.tessellate(0.1)) - 100.0).abs() < 1e-3);
+        document.shapes[0].fill_rule = FillRule::EvenOdd;
+        assert!((mesh_area(&document.tessellate(0.1)) - 84.0).abs() < 1e-3);
+
+        document.shapes[0].fill = None;
+        document.shapes[0].stroke = Some(Stroke { color: [0, 0, 0, 255], width: 1.0 });
+        let mesh = document.tessellate(0.1);
+        assert!(mesh.triangle_count() > 0);
+        assert!(mesh.vertices.iter().all(|v| v.color == [0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_rasterize_scales_with_output() {
+        let document = VectorDocument {
+            width:  10.0,
+            height: 10.0,
+            shapes: vec![VectorShape {
+                path:      square(5.0),
+                fill:      Some([0, 0, 255, 255]),
+                fill_rule: FillRule::NonZero,
+                stroke:    None,
+            }],
+        };
+        for size in [10u32, 40] {
+            let pixels = document.rasterize(size, size);
+            let at = |x: u32, y: u32| &pixels[((y * size + x) * 4) as usize..][..4];
+            assert_eq!(at(0, 0), &[0, 0, 255, 255]);
+            assert_eq!(at(size / 2 - 1, size / 2 - 1), &[0, 0, 255, 255]);
+            assert_eq!(at(size / 2, size / 2)[3], 0);
+        }
+    }
+}